@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rui::*;
+
+fn bench_frame<V: View>(c: &mut Criterion, name: &str, view: V) {
+    let mut harness = Harness::new(view);
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            harness.invalidate();
+            harness.update();
+        })
+    });
+}
+
+fn deep_tree_benchmark(c: &mut Criterion) {
+    bench_frame(c, "deep tree", deep_tree(100));
+}
+
+fn large_list_benchmark(c: &mut Criterion) {
+    bench_frame(c, "large list", large_list(1000));
+}
+
+fn text_editor_benchmark(c: &mut Criterion) {
+    let mut harness = Harness::new(large_document(1000));
+    harness.key(Key::Tab);
+    c.bench_function("type in large document", |b| {
+        b.iter(|| harness.key(Key::Character('x')))
+    });
+}
+
+criterion_group!(
+    benches,
+    deep_tree_benchmark,
+    large_list_benchmark,
+    text_editor_benchmark
+);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use rui::*;
+
+// Resize the window to switch between a row and a column.
+fn main() {
+    rui(geometry_reader(|size, _, _| {
+        let items = (
+            "one".padding(Auto),
+            "two".padding(Auto),
+            "three".padding(Auto),
+        );
+        if size.width > 400.0 {
+            any_view(hstack(items))
+        } else {
+            any_view(vstack(items))
+        }
+        .on_size_change(|_, size| println!("size: {:?}", size))
+    }));
+}
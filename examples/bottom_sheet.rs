@@ -0,0 +1,22 @@
+use rui::*;
+
+// Show the sheet, then drag it up to the second detent or swipe it away.
+fn main() {
+    rui(state(
+        || false,
+        |presented, _| {
+            zstack((
+                button("show sheet", move |cx| cx[presented] = true).padding(Auto),
+                bottom_sheet(
+                    presented,
+                    vec![Detent::Height(200.0), Detent::LARGE],
+                    vstack((
+                        "drag me".padding(Auto),
+                        button("close", move |cx| cx[presented] = false),
+                        spacer(),
+                    )),
+                ),
+            ))
+        },
+    ));
+}
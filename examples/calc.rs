@@ -57,7 +57,7 @@ fn main() {
                 )),
                 hstack((
                     digit_button("0", s),
-                    calc_button(".", move |cx| cx[s].push_str(".")),
+                    calc_button(".", move |cx| cx[s].push('.')),
                     calc_button("=", |_| ()),
                 )),
             ))
@@ -0,0 +1,44 @@
+use rui::*;
+
+fn bubble(i: i32) -> impl View {
+    // Our messages on the right, theirs on the left.
+    let ours = i % 3 == 0;
+    let bubble = format!("message {}", i)
+        .padding(Auto)
+        .background(rounded_rect(10.0).color(if ours {
+            AZURE_HIGHLIGHT_DARK
+        } else {
+            Color::gray(0.2)
+        }));
+    if ours {
+        any_view(hstack((spacer(), bubble)).padding(5.0))
+    } else {
+        any_view(hstack((bubble, spacer())).padding(5.0))
+    }
+}
+
+// Send messages, scroll up, and send some more to see the list hold its
+// place until you jump back down.
+fn main() {
+    rui(state(
+        || (0..20).collect::<Vec<i32>>(),
+        |messages, cx| {
+            vstack((
+                message_list(cx[messages].clone(), |i| bubble(*i)).animate_appear(0.25),
+                hstack((
+                    button("load older", move |cx| {
+                        let first = cx[messages][0];
+                        for i in 1..=10 {
+                            cx[messages].insert(0, first - i);
+                        }
+                    }),
+                    button("send", move |cx| {
+                        let next = cx[messages].last().unwrap() + 1;
+                        cx[messages].push(next);
+                    }),
+                ))
+                .padding(Auto),
+            ))
+        },
+    ));
+}
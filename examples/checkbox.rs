@@ -0,0 +1,36 @@
+use rui::*;
+
+fn main() {
+    rui(state(
+        || vec![true, false, true],
+        |items, cx| {
+            let check = CheckState::of(cx[items].clone());
+            let ids = (0..cx[items].len()).collect();
+            vstack((
+                hstack((
+                    tri_state_checkbox(check, move |cx, check| {
+                        for item in cx[items].iter_mut() {
+                            *item = check == CheckState::Checked;
+                        }
+                    }),
+                    "select all".padding(Auto),
+                )),
+                list(ids, move |i| {
+                    let i = *i;
+                    state(
+                        || (),
+                        move |_, cx| {
+                            hstack((
+                                tri_state_checkbox(cx[items][i].into(), move |cx, check| {
+                                    cx[items][i] = check == CheckState::Checked
+                                }),
+                                format!("item {}", i + 1).padding(Auto),
+                            ))
+                        },
+                    )
+                }),
+            ))
+            .padding(Auto)
+        },
+    ));
+}
@@ -0,0 +1,42 @@
+use rui::*;
+use std::ops::Range;
+
+const KEYWORDS: &[&str] = &["fn", "let", "mut", "if", "else", "for", "in", "return"];
+
+/// Colors keywords and numbers.
+fn highlight(text: &str) -> Vec<(Range<usize>, Color)> {
+    let mut spans = vec![];
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            let word = &text[s..i];
+            if KEYWORDS.contains(&word) {
+                spans.push((s..i, AZURE_HIGHLIGHT));
+            } else if word.chars().all(|c| c.is_ascii_digit()) {
+                spans.push((s..i, GREEN_HIGHLIGHT));
+            }
+        }
+    }
+    spans
+}
+
+fn main() {
+    rui(state(
+        || "fn main() {\n    let x = 42;\n    if x > 7 { return; }\n}".to_string(),
+        |source, _| {
+            text_editor_highlighted(source, highlight)
+                .padding(Auto)
+                .background(
+                    rectangle()
+                        .color(BUTTON_BACKGROUND_COLOR)
+                        .corner_radius(5.0),
+                )
+                .padding(Auto)
+        },
+    ));
+}
@@ -0,0 +1,18 @@
+use rui::*;
+
+fn main() {
+    rui(state(
+        || AZURE_HIGHLIGHT,
+        |color, cx| {
+            vstack((
+                color_picker(color),
+                text(&cx[color].to_hex()).padding(Auto),
+                rectangle()
+                    .color(cx[color])
+                    .corner_radius(8.0)
+                    .size([200.0, 100.0]),
+            ))
+            .padding(Auto)
+        },
+    ));
+}
@@ -0,0 +1,13 @@
+use rui::*;
+
+fn main() {
+    rui(state(
+        || 0,
+        |bursts, cx| {
+            zstack((
+                list(vec![cx[bursts]], |_| particles(ParticleConfig::default())),
+                button("celebrate", move |cx| cx[bursts] += 1).padding(Auto),
+            ))
+        },
+    ));
+}
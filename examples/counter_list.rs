@@ -35,7 +35,7 @@ impl Counters {
     fn ids(&self) -> Vec<usize> {
         let mut ids = vec![];
         for i in 0..self.counters.len() {
-            if let Some(_) = self.counters[i] {
+            if self.counters[i].is_some() {
                 ids.push(i);
             }
         }
@@ -49,10 +49,8 @@ impl Counters {
     }
     fn sum_counters(&self) -> i32 {
         let mut sum = 0;
-        for c in &self.counters {
-            if let Some(x) = c {
-                sum += x
-            }
+        for x in self.counters.iter().flatten() {
+            sum += x
         }
         sum
     }
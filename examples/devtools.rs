@@ -0,0 +1,16 @@
+use rui::*;
+
+fn main() {
+    // Try: echo '{"method": "tree"}' | nc 127.0.0.1 9123
+    App::new(state(
+        || 0,
+        |count, cx| {
+            vstack((
+                cx[count].padding(Auto),
+                button("increment", move |cx| cx[count] += 1).padding(Auto),
+            ))
+        },
+    ))
+    .plugin(DevTools::default())
+    .run();
+}
@@ -0,0 +1,27 @@
+use rui::*;
+
+fn number(n: i32) -> impl View {
+    format!("{}", n)
+        .padding(Auto)
+        .background(rectangle().color(AZURE_HIGHLIGHT))
+        .draggable(n)
+        .padding(Auto)
+}
+
+fn main() {
+    rui(state(
+        || (0, String::from("drop files here")),
+        |s, cx| {
+            let (total, files) = cx[s].clone();
+            vstack((
+                hstack((number(1), number(2), number(3))),
+                format!("drag numbers here: {}", total)
+                    .padding(Auto)
+                    .on_drop_target(move |cx, n: i32| cx[s].0 += n),
+                files
+                    .padding(Auto)
+                    .on_drop(move |cx, paths| cx[s].1 = format!("{:?}", paths)),
+            ))
+        },
+    ));
+}
@@ -0,0 +1,19 @@
+use rui::*;
+
+// Drag the card up and down. It snaps to the top, middle or bottom, and
+// stretches a little past either end.
+fn main() {
+    let card = zstack((
+        rounded_rect(10.0).color(AZURE_HIGHLIGHT),
+        "drag me".padding(Auto),
+    ))
+    .size([200.0, 100.0]);
+
+    rui(draggable(card)
+        .bounds(LocalRect::new([0.0, -150.0].into(), [0.0, 300.0].into()))
+        .snap_points(vec![
+            [0.0, -150.0].into(),
+            [0.0, 0.0].into(),
+            [0.0, 150.0].into(),
+        ]));
+}
@@ -0,0 +1,20 @@
+use rui::*;
+
+const RIPPLE: &str = "
+fn effect(uv: vec2<f32>) -> vec4<f32> {
+    let d = distance(uv, vec2<f32>(0.5, 0.5));
+    let offset = uniforms.params[0].x * sin(40.0 * d - 4.0 * uniforms.time);
+    return layer(uv + vec2<f32>(offset, offset));
+}
+";
+
+fn main() {
+    rui(vstack((
+        "ripple".padding(Auto),
+        rectangle()
+            .corner_radius(5.0)
+            .color(AZURE_HIGHLIGHT)
+            .padding(Auto)
+            .effect(ShaderEffect::new(RIPPLE, &[0.01])),
+    )));
+}
@@ -0,0 +1,113 @@
+//! rui as the UI layer of an app which owns its window and GPU, like a game.
+
+use futures::executor::block_on;
+use rui::*;
+use std::sync::Arc;
+use winit::{
+    event::{Event as WEvent, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title("embed")
+        .build(&event_loop)
+        .unwrap();
+
+    let instance = wgpu::Instance::default();
+    let surface = unsafe { instance.create_surface(&window) }.unwrap();
+    let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        compatible_surface: Some(&surface),
+        ..Default::default()
+    }))
+    .expect("no GPU adapter");
+    let (device, queue) =
+        block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)).unwrap();
+    let (device, queue) = (Arc::new(device), Arc::new(queue));
+
+    let size = window.inner_size();
+    let mut config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: surface.get_capabilities(&adapter).formats[0],
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![],
+    };
+    surface.configure(&device, &config);
+
+    let ui = state(
+        || 0,
+        |count, cx| {
+            vstack((
+                format!("{} hits", cx[count]).padding(Auto),
+                button("hit", move |cx| cx[count] += 1).padding(Auto),
+            ))
+        },
+    );
+    let mut rui = RuiSurface::new(device.clone(), queue.clone(), config.format, ui);
+    rui.resize(size.width, size.height, window.scale_factor() as f32);
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            // Events the UI doesn't use are the game's.
+            WEvent::WindowEvent { event, .. } if rui.handle_event(&event) => (),
+            WEvent::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                config.width = size.width.max(1);
+                config.height = size.height.max(1);
+                surface.configure(&device, &config);
+            }
+            WEvent::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            WEvent::MainEventsCleared if rui.update() || rui.needs_update() => {
+                window.request_redraw()
+            }
+            WEvent::RedrawRequested(_) => {
+                let frame = match surface.get_current_texture() {
+                    Ok(frame) => frame,
+                    Err(_) => {
+                        surface.configure(&device, &config);
+                        return;
+                    }
+                };
+                let target = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+
+                // The game's frame: a plain color.
+                let mut encoder = device.create_command_encoder(&Default::default());
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("game"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: 0.1,
+                                g: 0.3,
+                                b: 0.2,
+                                a: 1.0,
+                            }),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+                queue.submit(Some(encoder.finish()));
+
+                rui.render(&target);
+                frame.present();
+            }
+            _ => (),
+        }
+    });
+}
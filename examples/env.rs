@@ -1,17 +1,12 @@
 use rui::*;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 enum MyControlType {
+    #[default]
     Chill,
     Agro,
 }
 
-impl Default for MyControlType {
-    fn default() -> Self {
-        Self::Chill
-    }
-}
-
 fn my_control() -> impl View {
     env(|t, _| {
         circle().color(match t {
@@ -0,0 +1,31 @@
+use rui::*;
+
+fn main() {
+    rui(state(
+        || String::from("no file"),
+        |file, cx| {
+            vstack((
+                cx[file].clone().padding(Auto),
+                button("open", move |cx| {
+                    cx.open_file_dialog(
+                        &[FileFilter::new("Images", &["png", "jpg"])],
+                        move |path, cx| {
+                            if let Some(path) = path {
+                                cx[file] = format!("opened {}", path.display());
+                            }
+                        },
+                    )
+                })
+                .padding(Auto),
+                button("save as", move |cx| {
+                    cx.save_file_dialog(&[], "untitled.txt", move |path, cx| {
+                        if let Some(path) = path {
+                            cx[file] = format!("saving to {}", path.display());
+                        }
+                    })
+                })
+                .padding(Auto),
+            ))
+        },
+    ));
+}
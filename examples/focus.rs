@@ -0,0 +1,28 @@
+use rui::*;
+
+fn field(label: &'static str) -> impl View {
+    focus(move |has_focus| {
+        text(label)
+            .padding(Auto)
+            .background(
+                rectangle()
+                    .color(if has_focus {
+                        AZURE_HIGHLIGHT_BACKGROUND
+                    } else {
+                        BUTTON_BACKGROUND_COLOR
+                    })
+                    .corner_radius(4.0),
+            )
+            .padding(Auto)
+    })
+}
+
+fn main() {
+    // Press Tab and Shift+Tab to move between the fields.
+    rui(
+        vstack((field("first"), field("second"), field("third"))).env(FocusRing {
+            color: Some(RED_HIGHLIGHT),
+            ..Default::default()
+        }),
+    );
+}
@@ -0,0 +1,30 @@
+use rui::*;
+
+fn main() {
+    let font = include_bytes!("fonts/AtkinsonHyperlegible-Regular.ttf");
+    register_font(
+        "Atkinson",
+        FontWeight::Regular,
+        FontStyle::Normal,
+        &font[..],
+    )
+    .unwrap();
+
+    rui(state(String::new, |name, _| {
+        vstack((
+            "Atkinson Hyperlegible".font_size(32),
+            "Bold, emboldened from the regular face".font(
+                "Atkinson",
+                18,
+                FontWeight::Bold,
+                FontStyle::Normal,
+            ),
+            "Italic, slanted".font("Atkinson", 18, FontWeight::Regular, FontStyle::Italic),
+            text_field(name),
+            text_editor(name),
+            "The built-in face".env(Font::default()),
+        ))
+        .font("Atkinson", 18, FontWeight::Regular, FontStyle::Normal)
+        .padding(Auto)
+    }));
+}
@@ -0,0 +1,44 @@
+use rui::*;
+
+fn main() {
+    // A checkerboard, to show an image paint.
+    let mut pixels = vec![];
+    for y in 0..8 {
+        for x in 0..8 {
+            let c = if (x + y) % 2 == 0 { 255 } else { 64 };
+            pixels.extend_from_slice(&[c, c, c, 255]);
+        }
+    }
+    let checkers = ImageData::from_rgba(8, 8, pixels);
+
+    rui(vstack((
+        hstack((
+            circle()
+                .fill(Paint::radial(
+                    [0.35, 0.65],
+                    1.3,
+                    Color::WHITE,
+                    RED_HIGHLIGHT,
+                ))
+                .padding(Auto),
+            rounded_rect(10.0)
+                .fill(Paint::linear(
+                    [0.0, 0.0],
+                    [1.0, 1.0],
+                    AZURE_HIGHLIGHT,
+                    GREEN_HIGHLIGHT,
+                ))
+                .padding(Auto),
+            rounded_rect(10.0)
+                .fill(Paint::image(checkers, ContentMode::Fill))
+                .padding(Auto),
+        )),
+        "gradient background"
+            .padding(Auto)
+            .background_gradient(Paint::vertical(AZURE_HIGHLIGHT_BACKGROUND, BLACK))
+            .padding(Auto),
+        button("shaded button", |_| println!("clicked"))
+            .env(ButtonStyle::gradient(Color::gray(0.35), Color::gray(0.15)))
+            .padding(Auto),
+    )));
+}
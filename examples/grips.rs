@@ -0,0 +1,39 @@
+use rui::*;
+
+#[derive(Clone, Copy)]
+struct Card {
+    offset: LocalOffset,
+    size: Size,
+}
+
+fn main() {
+    rui(state(
+        || Card {
+            offset: LocalOffset::zero(),
+            size: Size::new(200.0, 150.0),
+        },
+        |card, cx| {
+            let Card { offset, size } = cx[card];
+            vstack((
+                hstack((
+                    drag_handle(move |cx, delta, _| cx[card].offset += delta),
+                    "drag to move".padding(Auto),
+                )),
+                spacer(),
+                hstack((
+                    spacer(),
+                    resize_grip(move |cx, delta, _| {
+                        let size = cx[card].size;
+                        cx[card].size = Size::new(
+                            (size.width + delta.width).max(120.0),
+                            (size.height + delta.height).max(60.0),
+                        );
+                    }),
+                )),
+            ))
+            .background(rectangle().corner_radius(5.0).color(AZURE_HIGHLIGHT))
+            .size(size)
+            .offset(offset)
+        },
+    ));
+}
@@ -0,0 +1,35 @@
+use rui::*;
+
+/// A wide gradient, so the content modes look different.
+fn gradient() -> ImageData {
+    let (width, height) = (256, 128);
+    let mut pixels = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            pixels.extend_from_slice(&[x as u8, (2 * y) as u8, 255 - x as u8, 255]);
+        }
+    }
+    ImageData::from_rgba(width, height, pixels)
+}
+
+fn main() {
+    let data = gradient();
+    let path = std::env::args().nth(1);
+    rui(vstack((
+        hstack((
+            image(data.clone()).content_mode(ContentMode::Fit),
+            image(data.clone()).content_mode(ContentMode::Fill),
+            image(data).content_mode(ContentMode::Stretch),
+        ))
+        .size([600.0, 200.0]),
+        // Pass a PNG or JPEG path to load one in the background.
+        match path {
+            Some(path) => AnyView::new(
+                async_image(path, text("loading..."))
+                    .corner_radius(20.0)
+                    .size([600.0, 300.0]),
+            ),
+            None => AnyView::new(text("pass an image path to load it")),
+        },
+    )));
+}
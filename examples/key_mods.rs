@@ -23,7 +23,12 @@ fn main() {
             "Drag (inside rectangle)".padding(Auto),
         )),
         "Handle key pressed"
-            .key(|cx, key| println!("key: {:?}, key modifiers state: {:?}", key, cx.key_mods))
+            .key(|_, k| {
+                println!(
+                    "key: {:?} {:?}, repeat: {}, key modifiers state: {:?}",
+                    k.key, k.phase, k.repeat, k.mods
+                )
+            })
             .padding(Auto),
     )));
 }
@@ -0,0 +1,28 @@
+use rui::*;
+
+fn card(title: &str) -> impl View {
+    zstack((
+        rounded_rect(10.0).color(Color::gray(0.2)),
+        title.to_string().padding(Auto),
+    ))
+    .size([160.0, 100.0])
+}
+
+fn main() {
+    rui(state(
+        || 0.5,
+        |opacity, cx| {
+            vstack((
+                hstack((
+                    card("shadow")
+                        .shadow(8.0, [0.0, -4.0], Color::new(0.0, 0.0, 0.0, 0.8))
+                        .padding(Auto),
+                    card("opacity").opacity(cx[opacity]).padding(Auto),
+                    card("blur").blur(3.0).padding(Auto),
+                    card("rounded").corner_radius(30.0).padding(Auto),
+                )),
+                hslider(opacity).padding(Auto),
+            ))
+        },
+    ));
+}
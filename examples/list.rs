@@ -1,7 +1,7 @@
 use rui::*;
 
 fn main() {
-    let data = vec!["John", "Paul", "George", "Ringo"];
+    let data = ["John", "Paul", "George", "Ringo"];
 
     let ids = (0usize..data.len()).collect();
 
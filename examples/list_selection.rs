@@ -0,0 +1,14 @@
+use rui::*;
+use std::collections::HashSet;
+
+fn main() {
+    let data = ["John", "Paul", "George", "Ringo"];
+
+    rui(state(HashSet::<usize>::new, move |selection, cx| {
+        let ids = (0usize..data.len()).collect();
+        vstack((
+            format!("{} selected", cx[selection].len()).padding(Auto),
+            list(ids, move |id| data[*id].to_string().padding(Auto)).selection(selection),
+        ))
+    }));
+}
@@ -0,0 +1,24 @@
+use rui::*;
+
+// Menus are drawn in the window, since winit has no native menus.
+fn main() {
+    App::new(text("choose something from the menus").padding(Auto))
+        .menu_bar(menu_bar(vec![
+            menu(
+                "File",
+                vec![
+                    menu_item("New", |_| println!("new")).hotkey(HotKey::KeyN),
+                    menu_item("Open", |_| println!("open")).hotkey(HotKey::KeyO),
+                    menu_item("Quit", |_| std::process::exit(0)).hotkey(HotKey::KeyQ),
+                ],
+            ),
+            menu(
+                "Edit",
+                vec![
+                    menu_item("Copy", |_| println!("copy")).hotkey(HotKey::KeyC),
+                    menu_item("Paste", |_| println!("paste")).hotkey(HotKey::KeyV),
+                ],
+            ),
+        ]))
+        .run();
+}
@@ -0,0 +1,34 @@
+use rui::*;
+
+fn contacts() -> impl View {
+    state(Vec::<String>::new, |routes, _| {
+        navigation_stack(
+            routes,
+            vstack(
+                ["Ada", "Grace", "Edsger"]
+                    .iter()
+                    .map(|name| {
+                        button(*name, move |cx| cx[routes].push(name.to_string())).padding(Auto)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            |name: &String| text(&format!("All about {}", name)).padding(Auto),
+        )
+        .slide_transition()
+    })
+}
+
+fn main() {
+    rui(state(
+        || 0,
+        |selected, _| {
+            tab_view(
+                selected,
+                vec![
+                    tab("Contacts", contacts()),
+                    tab("Settings", text("Nothing to set").padding(Auto)),
+                ],
+            )
+        },
+    ));
+}
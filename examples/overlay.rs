@@ -0,0 +1,34 @@
+use rui::*;
+
+// A round, always on top window. Drag it by its face. Clicks outside the
+// circle reach the windows behind. Press space to let all clicks through,
+// until the window is focused and space is pressed again.
+fn main() {
+    App::new(
+        zstack((
+            circle()
+                .color(Color::new(0.1, 0.1, 0.1, 0.85))
+                .window_drag_area(),
+            vstack((
+                "overlay".padding(Auto),
+                button("quit", |_| std::process::exit(0)).padding(Auto),
+            )),
+        ))
+        .on_appear(|cx| cx.set_click_through_empty(true))
+        .key(|cx, k| {
+            if k.pressed() == Some(Key::Space) && !k.is_repeat() {
+                let click_through = cx.click_through();
+                cx.set_click_through(!click_through);
+            }
+        }),
+    )
+    .window(
+        WindowOptions::default()
+            .title("overlay")
+            .size(Size::new(240.0, 240.0))
+            .transparent(true)
+            .decorations(false)
+            .always_on_top(true),
+    )
+    .run();
+}
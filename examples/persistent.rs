@@ -0,0 +1,20 @@
+use rui::*;
+
+fn main() {
+    // The slider and text keep their values when the app is restarted.
+    rui(vstack((
+        persistent_state(
+            "volume",
+            || 0.5,
+            |volume, cx| {
+                vstack((
+                    format!("{:.2}", cx[volume]).padding(Auto),
+                    hslider(volume).padding(Auto),
+                ))
+            },
+        ),
+        persistent_state("notes", String::new, |notes, _| {
+            text_editor(notes).padding(Auto)
+        }),
+    )));
+}
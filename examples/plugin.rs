@@ -0,0 +1,37 @@
+use rui::*;
+
+/// Adds a greeting to the environment and logs key presses.
+struct Greeter {
+    greeting: &'static str,
+}
+
+#[derive(Clone, Default)]
+struct Greeting(&'static str);
+
+impl Plugin for Greeter {
+    fn build(&self, app: &mut AppConfig) {
+        app.resource(Greeting(self.greeting))
+            .command("Greet", None, |_| println!("greetings!"))
+            .on_event(|event, _| {
+                if let Event::Key(KeyEvent {
+                    key,
+                    phase: KeyPhase::Down,
+                    ..
+                }) = event
+                {
+                    println!("key: {:?}", key);
+                }
+            });
+    }
+}
+
+fn main() {
+    App::new(env(|greeting: Greeting, _| text(greeting.0).padding(Auto)))
+        .plugin(Greeter { greeting: "hello" })
+        .window(
+            WindowOptions::default()
+                .title("plugin")
+                .size(Size::new(400.0, 300.0)),
+        )
+        .run();
+}
@@ -0,0 +1,36 @@
+use rui::*;
+use std::{
+    thread::{sleep, spawn},
+    time::Duration,
+};
+
+const STEPS: usize = 20;
+
+fn main() {
+    rui(state(
+        || None,
+        |s, cx| {
+            let status = match cx[s] {
+                None => any_view(text("task not started")),
+                Some(done) if done < STEPS => any_view(hstack((
+                    spinner(),
+                    progress_bar(done as f32 / STEPS as f32),
+                ))),
+                Some(_) => any_view(text("task complete")),
+            };
+            vstack((
+                button("press to begin", move |cx| {
+                    cx[s] = Some(0);
+                    spawn(move || {
+                        for step in 1..=STEPS {
+                            sleep(Duration::from_millis(100));
+                            on_main(move |cx| cx[s] = Some(step));
+                        }
+                    });
+                }),
+                status,
+            ))
+            .padding(Auto)
+        },
+    ));
+}
@@ -0,0 +1,31 @@
+use rui::*;
+
+/// A small checkerboard to show inline.
+fn swatch() -> ImageData {
+    let pixels = (0..16)
+        .flat_map(|i| {
+            let c = if (i % 4 + i / 4) % 2 == 0 { 255 } else { 64 };
+            [c, c / 2, 255 - c, 255]
+        })
+        .collect();
+    ImageData::from_rgba(4, 4, pixels)
+}
+
+fn main() {
+    let message = AttributedText::new()
+        .run("Rich text ", TextStyle::default().size(24).bold())
+        .run("can mix ", TextStyle::default())
+        .run("colors", TextStyle::default().color(AZURE_HIGHLIGHT))
+        .run(", ", TextStyle::default())
+        .run("sizes", TextStyle::default().size(30))
+        .run(", ", TextStyle::default())
+        .run("underlines", TextStyle::default().underline())
+        .run(" and images ", TextStyle::default())
+        .attachment(swatch(), [20.0, 20.0])
+        .run(
+            ". Long paragraphs wrap to the width of the view.",
+            TextStyle::default(),
+        );
+
+    rui(rich_text(message).padding(Auto))
+}
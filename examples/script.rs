@@ -0,0 +1,20 @@
+use rui::*;
+
+const SCRIPT: &str = r#"
+    fn init() { #{ count: 0 } }
+
+    fn view() {
+        #{ type: "vstack", padding: 20, children: [
+            `Clicked ${this.count} times`,
+            #{ type: "button", label: "Click me", on_tap: "increment" },
+        ] }
+    }
+
+    fn increment() { this.count += 1; }
+"#;
+
+fn main() {
+    rui(script_view(WidgetRegistry::new(), || {
+        RhaiEngine::new(SCRIPT).unwrap()
+    }))
+}
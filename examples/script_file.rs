@@ -0,0 +1,10 @@
+use rui::*;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/scripts/counter.rhai");
+    rui(script_file_view(
+        WidgetRegistry::new(),
+        path,
+        RhaiEngine::new,
+    ))
+}
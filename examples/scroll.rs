@@ -0,0 +1,30 @@
+use rui::*;
+
+fn card(i: usize) -> impl View {
+    zstack((
+        rounded_rect(10.0).color(Color::gray(0.2)),
+        format!("card {}", i).padding(Auto),
+    ))
+    .size([160.0, 100.0])
+    .padding(Auto)
+}
+
+fn row(i: usize) -> impl View {
+    hstack((format!("row {}", i).padding(Auto), spacer())).size([400.0, 44.0])
+}
+
+// A carousel at the top of a long list. Drag sideways over the cards to
+// flip through them, and up and down anywhere to scroll the list.
+fn main() {
+    rui(vscroll(vstack((
+        hscroll(hstack((
+            card(0),
+            card(1),
+            card(2),
+            card(3),
+            card(4),
+            card(5),
+        ))),
+        list((0..50).collect(), |i| row(*i)),
+    ))));
+}
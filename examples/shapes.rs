@@ -7,5 +7,18 @@ fn main() {
             .corner_radius(5.0)
             .color(AZURE_HIGHLIGHT)
             .padding(Auto),
+        rounded_rect(10.0)
+            .stroke(2.0, GREEN_HIGHLIGHT)
+            .padding(Auto),
+        path(|p, rect| {
+            p.move_to([rect.min_x(), rect.min_y()])
+                .quad_to(
+                    [rect.center().x, rect.max_y() * 1.5],
+                    [rect.max_x(), rect.min_y()],
+                )
+                .close();
+        })
+        .fill(AZURE_HIGHLIGHT)
+        .padding(Auto),
     )));
 }
@@ -0,0 +1,58 @@
+use rui::*;
+
+struct Whiteboard {
+    drawing: Drawing,
+    color: Color,
+    size: Size,
+    status: String,
+}
+
+make_lens!(DrawingLens, Whiteboard, Drawing, drawing);
+
+fn main() {
+    rui(state(
+        || Whiteboard {
+            drawing: Drawing::new(),
+            color: AZURE_HIGHLIGHT,
+            size: Size::default(),
+            status: String::new(),
+        },
+        |board, cx| {
+            let swatch = move |color: Color| {
+                rectangle()
+                    .color(color)
+                    .corner_radius(4.0)
+                    .size([24.0, 24.0])
+                    .tap(move |cx| cx[board].color = color)
+            };
+            let save = move |cx: &mut Context| {
+                let board = &mut cx[board];
+                board.status = match board.drawing.render_to_image(board.size, 2.0) {
+                    Ok(image) => match image.save("sketch.png") {
+                        Ok(()) => "saved sketch.png".into(),
+                        Err(err) => format!("couldn't save: {}", err),
+                    },
+                    Err(err) => format!("couldn't render: {}", err),
+                };
+            };
+            vstack((
+                hstack((
+                    swatch(AZURE_HIGHLIGHT),
+                    swatch(RED_HIGHLIGHT),
+                    swatch(GREEN_HIGHLIGHT),
+                    button("undo", move |cx| {
+                        cx[board].drawing.pop();
+                    }),
+                    button("clear", move |cx| cx[board].drawing.clear()),
+                    button("save", save),
+                    text(&cx[board].status),
+                ))
+                .padding(Auto),
+                sketch(bind(board, DrawingLens {}))
+                    .stroke_color(cx[board].color)
+                    .stroke_width(4.0)
+                    .on_size_change(move |cx, size| cx[board].size = size),
+            ))
+        },
+    ));
+}
@@ -0,0 +1,14 @@
+use rui::*;
+
+fn main() {
+    rui(hsplit(
+        text("sidebar").padding(Auto),
+        vsplit(text("editor").padding(Auto), text("terminal").padding(Auto))
+            .ratio(0.7)
+            .min_sizes(100.0, 50.0)
+            .persist("editor_split"),
+    )
+    .ratio(0.25)
+    .min_sizes(120.0, 200.0)
+    .persist("sidebar_split"));
+}
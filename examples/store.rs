@@ -0,0 +1,40 @@
+use rui::*;
+
+#[derive(Clone)]
+enum Action {
+    Increment,
+    Rename(String),
+}
+
+#[derive(Default)]
+struct AppState {
+    count: i32,
+    name: String,
+}
+
+fn reduce(state: &mut AppState, action: &Action) {
+    match action {
+        Action::Increment => state.count += 1,
+        Action::Rename(name) => state.name = name.clone(),
+    }
+}
+
+fn main() {
+    rui(store(
+        reduce,
+        AppState::default,
+        vstack((
+            // Only rebuilt when the count changes.
+            connect(
+                |s: &AppState| s.count,
+                |count| format!("count: {}", count).padding(Auto),
+            ),
+            connect(
+                |s: &AppState| s.name.clone(),
+                |name| format!("name: {}", name).padding(Auto),
+            ),
+            button_a("increment", Action::Increment).padding(Auto),
+            button("rename", |cx| cx.dispatch(Action::Rename("rui".into()))).padding(Auto),
+        )),
+    ));
+}
@@ -0,0 +1,27 @@
+use rui::*;
+use std::{thread::sleep, time::Duration};
+
+/// Pretend to fetch something slowly.
+fn fetch(name: &'static str, secs: u64) -> impl View {
+    load(
+        move || async move {
+            sleep(Duration::from_secs(secs));
+            Ok::<_, String>(format!("{} loaded after {}s", name, secs))
+        },
+        |message, _| text(message),
+    )
+}
+
+fn main() {
+    rui(suspense(
+        || {
+            vstack((
+                fetch("profile", 1),
+                fetch("messages", 2),
+                fetch("settings", 3),
+            ))
+        },
+        spinner(),
+    )
+    .padding(Auto))
+}
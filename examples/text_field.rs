@@ -0,0 +1,30 @@
+use rui::*;
+
+#[derive(Default)]
+struct Login {
+    user: String,
+    password: String,
+    status: String,
+}
+
+make_lens!(UserLens, Login, String, user);
+make_lens!(PasswordLens, Login, String, password);
+
+fn main() {
+    rui(state(Login::default, |login, cx| {
+        let submit = move |cx: &mut Context| {
+            cx[login].status = format!("signing in as {}", cx[login].user);
+        };
+        vstack((
+            text_field(bind(login, UserLens {}))
+                .placeholder("user name")
+                .on_submit(submit),
+            text_field(bind(login, PasswordLens {}))
+                .placeholder("password")
+                .secure()
+                .on_submit(submit),
+            text(&cx[login].status),
+        ))
+        .padding(Auto)
+    }));
+}
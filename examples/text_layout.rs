@@ -5,7 +5,7 @@ fn main() {
 
         let lorem = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.";
 
-        let paint = vger.color_paint(vger::Color::MAGENTA.alpha(0.2));
+        let paint = vger.color_paint(Color::MAGENTA.alpha(0.2));
 
         vger.translate([0.0, rect.height()]);
 
@@ -24,7 +24,7 @@ fn main() {
 
         let rects = vger.glyph_positions(lorem, font_size, break_width);
 
-        let glyph_rect_paint = vger.color_paint(vger::Color::MAGENTA.alpha(0.1));
+        let glyph_rect_paint = vger.color_paint(Color::MAGENTA.alpha(0.1));
 
         for rect in rects {
             vger.fill_rect(rect, 0.0, glyph_rect_paint);
@@ -0,0 +1,13 @@
+use rui::*;
+
+fn main() {
+    rui(vstack((
+        theme(|theme, _| format!("{:?} mode", theme.scheme).padding(Auto)),
+        hstack((
+            button("light", |cx| cx.set_theme(Theme::LIGHT)).padding(Auto),
+            button("dark", |cx| cx.set_theme(Theme::DARK)).padding(Auto),
+            button("system", |cx| cx.follow_system_theme()).padding(Auto),
+        )),
+        state(|| 0.5, |s, _| hslider(s).padding(Auto)),
+    )));
+}
@@ -0,0 +1,26 @@
+use rui::*;
+
+fn main() {
+    rui(state(
+        || 0.0,
+        |angle, cx| {
+            let a = cx[angle];
+            vstack((
+                hstack((
+                    button("rotated", |_| println!("rotated"))
+                        .rotate(a * std::f32::consts::TAU)
+                        .padding(Auto),
+                    button("scaled", |_| println!("scaled"))
+                        .scale(0.5 + a)
+                        .padding(Auto),
+                    button("corner", |_| println!("corner"))
+                        .rotate(a)
+                        .anchor([0.0, 0.0])
+                        .offset([10.0, 0.0])
+                        .padding(Auto),
+                )),
+                hslider(angle).padding(Auto),
+            ))
+        },
+    ));
+}
@@ -0,0 +1,37 @@
+use rui::*;
+
+// Each window has its own state, so the counters are separate.
+fn counter() -> impl View {
+    state(
+        || 1,
+        |count, cx| {
+            vstack((
+                cx[count].padding(Auto),
+                button("increment", move |cx| cx[count] += 1).padding(Auto),
+                button("close", |cx| cx.close_window()).padding(Auto),
+            ))
+        },
+    )
+}
+
+fn main() {
+    App::new(vstack((
+        counter(),
+        button("new window", |cx| {
+            cx.open_window(
+                WindowOptions::default()
+                    .title("another window")
+                    .size(Size::new(300.0, 200.0)),
+                counter(),
+            )
+        })
+        .padding(Auto),
+    )))
+    .window(
+        WindowOptions::default()
+            .title("windows")
+            .min_size(Size::new(200.0, 200.0))
+            .max_size(Size::new(800.0, 600.0)),
+    )
+    .run();
+}
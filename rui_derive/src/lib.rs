@@ -0,0 +1,78 @@
+//! Derive macros for rui. Use them through the `rui` crate.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+/// Stores each field of a struct in its own state, so changing one only
+/// invalidates the views which read it. See `rui::model`.
+///
+/// For `struct Doc { title: String }` this generates `DocFields`, which
+/// has a `StateHandle` for each field.
+#[proc_macro_derive(Model)]
+pub fn derive_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Model can't be derived for generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Model can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Model can only be derived for structs",
+            ))
+        }
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let fields_name = format_ident!("{}Fields", name);
+    let names: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let docs = names.iter().map(|name| {
+        let doc = format!("The `{}` field's state.", name.as_ref().unwrap());
+        quote! { #[doc = #doc] }
+    });
+    let struct_doc = format!("Handles to the state of each field of a `{}`.", name);
+
+    Ok(quote! {
+        #[doc = #struct_doc]
+        #[derive(Clone, Copy)]
+        #vis struct #fields_name {
+            #( #docs pub #names: ::rui::StateHandle<#types>, )*
+        }
+
+        impl ::rui::Model for #name {
+            type Fields = #fields_name;
+
+            fn fields(fields: &mut ::rui::ModelFields) -> Self::Fields {
+                #fields_name {
+                    #( #names: fields.field(), )*
+                }
+            }
+
+            fn store(self, fields: &mut ::rui::ModelFields) {
+                #( fields.store(self.#names); )*
+            }
+        }
+    })
+}
@@ -0,0 +1,330 @@
+//! Accessibility tree for screen readers, built with AccessKit.
+
+use crate::*;
+use accesskit::{Action, Node, NodeBuilder, NodeId, Role, Tree, TreeUpdate};
+use std::collections::HashMap;
+use std::num::NonZeroU128;
+
+/// Touch slot used to press views for assistive technology.
+pub(crate) const ACCESS_TOUCH: usize = 15;
+
+/// The window's node, above the views. View ids are 64 bits, so this
+/// can't be one of theirs.
+pub(crate) fn root_access_id() -> NodeId {
+    NodeId(NonZeroU128::new(u128::MAX).unwrap())
+}
+
+/// Id for another node belonging to a view, like the text inside a text
+/// field. `part` goes in the bits above the view's id.
+pub(crate) fn part_access_id(id: NodeId, part: u64) -> NodeId {
+    NodeId(id.0 | ((part as u128) << 64))
+}
+
+/// Roles which are pressed to use them, so they're tapped when assistive
+/// technology asks, and named by the labels inside them.
+pub(crate) fn is_pressable(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button | Role::CheckBox | Role::Switch | Role::Link | Role::MenuItem | Role::Tab
+    )
+}
+
+/// The name of a view from the labels inside it. `nodes` are the nodes
+/// added for the view's children.
+pub(crate) fn contents_name(nodes: &[(NodeId, Node)]) -> String {
+    nodes
+        .iter()
+        .filter(|(_, node)| node.role() == Role::LabelText)
+        .filter_map(|(_, node)| node.name())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The accessibility tree as last built, and what has changed since it
+/// was last taken.
+#[derive(Default)]
+pub(crate) struct AccessTree {
+    nodes: HashMap<NodeId, Node>,
+    changed: Vec<NodeId>,
+    focus: Option<NodeId>,
+    focus_changed: bool,
+
+    /// Nodes of focusable views, and the `focus` view making them so.
+    /// Filled in while building.
+    pub(crate) focusable: HashMap<NodeId, ViewId>,
+
+    /// Requests from assistive technology, for the backend to send to
+    /// views as `Event::Access`.
+    #[cfg(feature = "winit")]
+    pub(crate) requests: Vec<accesskit::ActionRequest>,
+}
+
+impl AccessTree {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl Context {
+    /// Builds the accessibility tree for `view`, noting which nodes
+    /// changed.
+    pub(crate) fn update_access_tree(&mut self, view: &impl View) {
+        self.access.focusable.clear();
+        let mut nodes = vec![];
+        let mut path = vec![0];
+        let top = view.access(&mut path, self, &mut nodes);
+
+        let mut root = NodeBuilder::new(Role::Window);
+        root.set_name(self.window_title.clone());
+        root.set_children(top.into_iter().collect::<Vec<_>>());
+        nodes.push((root_access_id(), root.build(&mut self.access_node_classes)));
+
+        let access = &mut self.access;
+        if nodes.len() != access.nodes.len()
+            || nodes
+                .iter()
+                .any(|(id, node)| access.nodes.get(id) != Some(node))
+        {
+            println!("access nodes:");
+            for (id, node) in &nodes {
+                println!(
+                    "  id: {:?} role: {:?}, children: {:?}",
+                    id,
+                    node.role(),
+                    node.children()
+                );
+            }
+        }
+
+        let mut tree = HashMap::with_capacity(nodes.len());
+        for (id, node) in nodes {
+            if access.nodes.get(&id) != Some(&node) {
+                access.changed.push(id);
+            }
+            tree.insert(id, node);
+        }
+        access.nodes = tree;
+
+        let focus = self.focused_id.and_then(|focused| {
+            access
+                .focusable
+                .iter()
+                .find(|(_, vid)| **vid == focused)
+                .map(|(id, _)| *id)
+        });
+        let focus = Some(focus.unwrap_or_else(root_access_id));
+        if focus != access.focus {
+            access.focus = focus;
+            access.focus_changed = true;
+        }
+    }
+
+    /// The whole accessibility tree, as an AccessKit platform adapter
+    /// wants when it starts. Includes the last changes, so
+    /// `take_access_update` won't return them again.
+    pub fn access_tree(&mut self) -> TreeUpdate {
+        let access = &mut self.access;
+        access.changed.clear();
+        access.focus_changed = false;
+        TreeUpdate {
+            nodes: access
+                .nodes
+                .iter()
+                .map(|(id, node)| (*id, node.clone()))
+                .collect(),
+            tree: Some(Tree::new(root_access_id())),
+            focus: access.focus,
+        }
+    }
+
+    /// The nodes which changed since the tree was last taken, to pass to
+    /// an AccessKit platform adapter. `None` if nothing changed. Requests
+    /// from the adapter go back to views as `Event::Access`.
+    pub fn take_access_update(&mut self) -> Option<TreeUpdate> {
+        let access = &mut self.access;
+        if access.changed.is_empty() && !access.focus_changed {
+            return None;
+        }
+        access.focus_changed = false;
+        let mut changed = std::mem::take(&mut access.changed);
+        changed.sort_unstable_by_key(|id| id.0);
+        changed.dedup();
+        Some(TreeUpdate {
+            nodes: changed
+                .into_iter()
+                .filter_map(|id| Some((id, access.nodes.get(&id)?.clone())))
+                .collect(),
+            tree: None,
+            focus: access.focus,
+        })
+    }
+
+    /// Focuses the view with a node, as asked by assistive technology.
+    pub(crate) fn access_focus(&mut self, target: NodeId) {
+        if let Some(vid) = self.access.focusable.get(&target) {
+            self.focused_id = Some(*vid);
+            self.focus_visible = true;
+            self.set_dirty();
+        }
+    }
+}
+
+/// Adds `Action::Focus` to a view's node if it's the node of a focusable
+/// view, taking that over from the node inside it.
+pub(crate) fn take_focusable(
+    child: Option<NodeId>,
+    id: NodeId,
+    builder: &mut NodeBuilder,
+    cx: &mut Context,
+) {
+    if let Some(vid) = child.and_then(|child| cx.access.focusable.remove(&child)) {
+        cx.access.focusable.insert(id, vid);
+        builder.add_action(Action::Focus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use accesskit::{ActionData, ActionRequest, TextPosition, TextSelection};
+
+    fn find(update: &TreeUpdate, role: Role) -> (NodeId, Node) {
+        update
+            .nodes
+            .iter()
+            .find(|(_, node)| node.role() == role)
+            .cloned()
+            .unwrap_or_else(|| panic!("no {:?} node", role))
+    }
+
+    fn request(action: Action, target: NodeId, data: Option<ActionData>) -> Event {
+        Event::Access(ActionRequest {
+            action,
+            target,
+            data,
+        })
+    }
+
+    #[test]
+    fn test_access_tree() {
+        let mut h = Harness::new(state(
+            || 0,
+            |count, cx| {
+                vstack((
+                    format!("count {}", cx[count]),
+                    button("increment", move |cx| cx[count] += 1),
+                ))
+            },
+        ));
+        let tree = h.context().access_tree();
+        assert_eq!(tree.tree.as_ref().unwrap().root, root_access_id());
+        assert_eq!(tree.focus, Some(root_access_id()));
+        let (button, node) = find(&tree, Role::Button);
+        assert_eq!(node.name(), Some("increment"));
+        assert!(node.supports_action(Action::Default));
+        assert!(h.context().take_access_update().is_none());
+
+        h.event(request(Action::Default, button, None));
+        h.update();
+        assert_eq!(h.states::<i32>(), vec![1]);
+
+        // Only the label changed.
+        let update = h.context().take_access_update().unwrap();
+        assert_eq!(update.nodes.len(), 1);
+        assert_eq!(update.nodes[0].1.name(), Some("count 1"));
+    }
+
+    #[test]
+    fn test_access_text_field() {
+        let mut h = Harness::new(state(|| String::from("hello"), |s, _| text_field(s)));
+        let tree = h.context().access_tree();
+        let (field, node) = find(&tree, Role::TextField);
+        assert_eq!(node.value(), Some("hello"));
+        assert!(node.supports_action(Action::Focus));
+        let (inline, node) = find(&tree, Role::InlineTextBox);
+        assert_eq!(node.character_lengths(), &[1, 1, 1, 1, 1]);
+
+        h.event(request(Action::Focus, field, None));
+        h.update();
+        assert_eq!(h.context().take_access_update().unwrap().focus, Some(field));
+
+        let position = |character_index| TextPosition {
+            node: inline,
+            character_index,
+        };
+        h.event(request(
+            Action::SetTextSelection,
+            field,
+            Some(ActionData::SetTextSelection(TextSelection {
+                anchor: position(1),
+                focus: position(1),
+            })),
+        ));
+        h.event(request(
+            Action::ReplaceSelectedText,
+            field,
+            Some(ActionData::Value("i, ".into())),
+        ));
+        h.update();
+        assert_eq!(h.states::<String>(), vec!["hi, ello".to_string()]);
+        let tree = h.context().access_tree();
+        let (_, node) = find(&tree, Role::TextField);
+        assert_eq!(node.text_selection().unwrap().focus, position(4));
+
+        h.event(request(
+            Action::SetValue,
+            field,
+            Some(ActionData::Value("bye".into())),
+        ));
+        h.update();
+        assert_eq!(h.states::<String>(), vec!["bye".to_string()]);
+    }
+
+    #[test]
+    fn test_access_text_editor() {
+        let mut h = Harness::new(state(|| String::from("one\ntwo"), |s, _| text_editor(s)));
+        let tree = h.context().access_tree();
+        let (editor, node) = find(&tree, Role::TextField);
+        assert!(node.is_multiline());
+        assert_eq!(node.value(), Some("one\ntwo"));
+        // A text box for each line.
+        let (second, _) = tree
+            .nodes
+            .iter()
+            .find(|(_, node)| node.role() == Role::InlineTextBox && node.value() == Some("two"))
+            .unwrap();
+
+        let position = |character_index| TextPosition {
+            node: *second,
+            character_index,
+        };
+        h.event(request(
+            Action::SetTextSelection,
+            editor,
+            Some(ActionData::SetTextSelection(TextSelection {
+                anchor: position(0),
+                focus: position(3),
+            })),
+        ));
+        h.event(request(
+            Action::ReplaceSelectedText,
+            editor,
+            Some(ActionData::Value("2".into())),
+        ));
+        h.update();
+        assert_eq!(h.states::<String>(), vec!["one\n2".to_string()]);
+    }
+
+    #[test]
+    fn test_access_slider() {
+        let mut h = Harness::new(state(|| 0.5f32, |v, _| hslider(v)));
+        let tree = h.context().access_tree();
+        let (slider, node) = find(&tree, Role::Slider);
+        assert_eq!(node.numeric_value(), Some(0.5));
+
+        h.event(request(Action::Increment, slider, None));
+        h.update();
+        assert!((h.states::<f32>()[0] - 0.6).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,518 @@
+use crate::*;
+
+/// Values which can be blended, for `Context::animate_state`.
+pub trait Lerp: Clone {
+    /// Blends from `self` at `t = 0` to `other` at `t = 1`.
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t as f64
+    }
+}
+
+impl<U> Lerp for euclid::Point2D<f32, U> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        euclid::Point2D::lerp(*self, *other, t)
+    }
+}
+
+impl<U> Lerp for euclid::Vector2D<f32, U> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        euclid::Vector2D::lerp(*self, *other, t)
+    }
+}
+
+impl<U> Lerp for euclid::Size2D<f32, U> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        euclid::Size2D::lerp(*self, *other, t)
+    }
+}
+
+impl<U> Lerp for euclid::Rect<f32, U> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        euclid::Rect::new(
+            Lerp::lerp(&self.origin, &other.origin, t),
+            Lerp::lerp(&self.size, &other.size, t),
+        )
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Point::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+    }
+}
+
+impl Lerp for Size {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Size::new(
+            self.width.lerp(&other.width, t),
+            self.height.lerp(&other.height, t),
+        )
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Color::new(
+            self.r.lerp(&other.r, t),
+            self.g.lerp(&other.g, t),
+            self.b.lerp(&other.b, t),
+            self.a.lerp(&other.a, t),
+        )
+    }
+}
+
+impl<A: Lerp, B: Lerp> Lerp for (A, B) {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+/// How an animation moves between its start and end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Curve {
+    /// Maps the fraction of time elapsed to how far along the value is.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Curve::Linear => t,
+            Curve::EaseIn => t * t,
+            Curve::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Curve::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+        }
+    }
+}
+
+/// What a timeline does after its last keyframe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stop at the last keyframe.
+    Once,
+    /// Start again from the first keyframe.
+    Loop,
+    /// Play backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// Keyframes for a value over time. Build one with `timeline`, and play it
+/// on a state with `Context::play_timeline`. To drive several properties,
+/// animate a tuple or struct which implements `Lerp`.
+#[derive(Clone, Debug)]
+pub struct Timeline<S> {
+    /// Times in seconds, the values there, and the curve leading to them.
+    keys: Vec<(f32, S, Curve)>,
+    /// Index of the keyframe added last, which `ease` applies to.
+    last: Option<usize>,
+    repeat: Repeat,
+}
+
+/// Starts building a `Timeline`.
+pub fn timeline<S: Lerp>() -> Timeline<S> {
+    Timeline {
+        keys: vec![],
+        last: None,
+        repeat: Repeat::Once,
+    }
+}
+
+impl<S: Lerp> Timeline<S> {
+    /// Adds a keyframe with `value` at `time` seconds, reached from the
+    /// previous keyframe linearly. See `ease`.
+    pub fn at(mut self, time: f32, value: S) -> Self {
+        let i = self.keys.partition_point(|(t, _, _)| *t <= time);
+        self.keys.insert(i, (time, value, Curve::Linear));
+        self.last = Some(i);
+        self
+    }
+
+    /// Sets the curve leading to the last keyframe added.
+    pub fn ease(mut self, curve: Curve) -> Self {
+        if let Some(i) = self.last {
+            self.keys[i].2 = curve;
+        }
+        self
+    }
+
+    pub fn repeat(self, repeat: Repeat) -> Self {
+        Self { repeat, ..self }
+    }
+
+    /// Time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |(t, _, _)| *t)
+    }
+
+    /// Has the timeline finished by `time`? Repeating timelines never do.
+    pub fn is_finished(&self, time: f32) -> bool {
+        self.repeat == Repeat::Once && time >= self.duration()
+    }
+
+    /// The value at `time` seconds from the start, repeating as set. Before
+    /// the first keyframe the value is held at it. Panics if there are no
+    /// keyframes.
+    pub fn sample(&self, time: f32) -> S {
+        let duration = self.duration();
+        let time = if duration <= 0.0 {
+            duration
+        } else {
+            match self.repeat {
+                Repeat::Once => time.min(duration),
+                Repeat::Loop => time.rem_euclid(duration),
+                Repeat::PingPong => {
+                    let t = time.rem_euclid(2.0 * duration);
+                    if t > duration {
+                        2.0 * duration - t
+                    } else {
+                        t
+                    }
+                }
+            }
+        };
+
+        let next = self.keys.partition_point(|(t, _, _)| *t < time);
+        if next == 0 {
+            return self.keys[0].1.clone();
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1.clone();
+        }
+        let (t0, v0, _) = &self.keys[next - 1];
+        let (t1, v1, curve) = &self.keys[next];
+        let t = if t1 > t0 {
+            (time - t0) / (t1 - t0)
+        } else {
+            1.0
+        };
+        v0.lerp(v1, curve.apply(t))
+    }
+}
+
+/// Animation step for updates with no frame interval to go by.
+pub(crate) const NOMINAL_FRAME: f32 = 1.0 / 60.0;
+
+/// Longest animation step, so animations slow down rather than jump
+/// after a stall.
+const MAX_ANIM_STEP: f32 = 0.1;
+
+/// Sets the state for a time since the animation started, returning false
+/// once finished.
+type AnimationStep = Box<dyn FnMut(&mut Context, f32) -> bool>;
+
+struct Animation {
+    id: ViewId,
+    elapsed: f32,
+    paused: bool,
+    step: AnimationStep,
+}
+
+/// State values being animated, at most one per state.
+#[derive(Default)]
+pub(crate) struct Animations {
+    running: Vec<Animation>,
+}
+
+impl Animations {
+    /// Is any animation not paused? Paused ones still count as animations,
+    /// but don't need frames.
+    fn is_playing(&self) -> bool {
+        self.running.iter().any(|animation| !animation.paused)
+    }
+
+    fn get_mut(&mut self, id: ViewId) -> Option<&mut Animation> {
+        self.running.iter_mut().find(|animation| animation.id == id)
+    }
+}
+
+impl Context {
+    /// Animates the state `handle` from its current value to `target` over
+    /// `duration` seconds, on the same clock as animated views. Animating
+    /// a state which is already animating starts again from where it is.
+    pub fn animate_state<S: Lerp + 'static>(
+        &mut self,
+        handle: StateHandle<S>,
+        target: S,
+        curve: Curve,
+        duration: f32,
+    ) {
+        let start = self[handle].clone();
+        self.play_timeline(
+            handle,
+            timeline().at(0.0, start).at(duration, target).ease(curve),
+        );
+    }
+
+    /// Plays `timeline` on the state `handle`, replacing any animation
+    /// it has. Control it with `pause_animating`, `resume_animating` and
+    /// `seek_animation`.
+    pub fn play_timeline<S: Lerp + 'static>(
+        &mut self,
+        handle: StateHandle<S>,
+        timeline: Timeline<S>,
+    ) {
+        if timeline.keys.is_empty() {
+            return;
+        }
+        let step = move |cx: &mut Context, time: f32| {
+            // The state went away with its view.
+            if !cx.state_map.contains_key(&handle.id) {
+                return false;
+            }
+            cx[handle] = timeline.sample(time);
+            !timeline.is_finished(time)
+        };
+
+        let running = &mut self.animations.running;
+        running.retain(|animation| animation.id != handle.id);
+        running.push(Animation {
+            id: handle.id,
+            elapsed: 0.0,
+            paused: false,
+            step: Box::new(step),
+        });
+    }
+
+    /// Stops animating `handle`, leaving it where it is.
+    pub fn stop_animating<S: 'static>(&mut self, handle: StateHandle<S>) {
+        self.animations
+            .running
+            .retain(|animation| animation.id != handle.id);
+    }
+
+    /// Holds the animation on `handle` where it is until it's resumed.
+    pub fn pause_animating<S: 'static>(&mut self, handle: StateHandle<S>) {
+        if let Some(animation) = self.animations.get_mut(handle.id) {
+            animation.paused = true;
+        }
+    }
+
+    pub fn resume_animating<S: 'static>(&mut self, handle: StateHandle<S>) {
+        if let Some(animation) = self.animations.get_mut(handle.id) {
+            animation.paused = false;
+        }
+    }
+
+    /// Jumps the animation on `handle` to `time` seconds from its start,
+    /// updating the state right away, even if paused.
+    pub fn seek_animation<S: 'static>(&mut self, handle: StateHandle<S>, time: f32) {
+        let i = match self
+            .animations
+            .running
+            .iter()
+            .position(|animation| animation.id == handle.id)
+        {
+            Some(i) => i,
+            None => return,
+        };
+        let mut animation = self.animations.running.remove(i);
+        animation.elapsed = time;
+        if (animation.step)(self, time) {
+            self.animations.running.insert(i, animation);
+        }
+    }
+
+    /// Is any state animating, and not paused?
+    pub fn is_animating(&self) -> bool {
+        self.animations.is_playing()
+    }
+
+    /// Seconds the animations of this update step over: how long the last
+    /// frame took while frames are coming one after another, for example
+    /// while animating. `Event::Anim` handlers should step by this.
+    pub fn anim_interval(&self) -> f32 {
+        self.anim_interval
+    }
+
+    /// Measures the step for this update's animations. Frames which follow
+    /// idle time, and updates which aren't rendered, as in tests, take a
+    /// nominal step instead.
+    pub(crate) fn next_anim_interval(&self) -> f32 {
+        let interval = self.frame_timings.interval.as_secs_f32();
+        if self.wants_frame && interval > 0.0 {
+            interval.min(MAX_ANIM_STEP)
+        } else {
+            NOMINAL_FRAME
+        }
+    }
+
+    /// Steps each state animation by `dt` seconds.
+    pub(crate) fn run_animations(&mut self, dt: f32) {
+        let mut running = std::mem::take(&mut self.animations.running);
+        self.strict_set_animating(true);
+        running.retain_mut(|animation| {
+            if animation.paused {
+                return true;
+            }
+            animation.elapsed += dt;
+            (animation.step)(self, animation.elapsed)
+        });
+        self.strict_set_animating(false);
+
+        // Keep animations started while stepping, which replace ours.
+        let started = std::mem::take(&mut self.animations.running);
+        running.retain(|animation| !started.iter().any(|s| s.id == animation.id));
+        running.extend(started);
+        self.animations.running = running;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_curves() {
+        for curve in [
+            Curve::Linear,
+            Curve::EaseIn,
+            Curve::EaseOut,
+            Curve::EaseInOut,
+        ] {
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(1.0), 1.0);
+            assert_eq!(curve.apply(2.0), 1.0);
+        }
+        assert_eq!(Curve::EaseInOut.apply(0.5), 0.5);
+        assert!(Curve::EaseIn.apply(0.25) < 0.25);
+        assert!(Curve::EaseOut.apply(0.25) > 0.25);
+    }
+
+    #[test]
+    fn test_animate_state() {
+        let view = state(
+            || 0.0f32,
+            |x, cx| {
+                let label = format!("{}", cx[x].round());
+                vstack((
+                    button("go", move |cx| {
+                        cx.animate_state(x, 60.0, Curve::Linear, 1.0)
+                    }),
+                    text(&label),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("go");
+        assert!(h.context().is_animating());
+
+        // Each update is a sixtieth of a second.
+        for _ in 0..29 {
+            h.update();
+        }
+        assert!(h.has_text("30"));
+        for _ in 0..40 {
+            h.update();
+        }
+        assert!(h.has_text("60"));
+        assert!(!h.context().is_animating());
+    }
+
+    #[test]
+    fn test_timeline_sample() {
+        let t = timeline()
+            .at(0.0, 0.0f32)
+            .at(1.0, 10.0)
+            .at(2.0, 0.0)
+            .ease(Curve::EaseIn);
+        assert_eq!(t.duration(), 2.0);
+        assert_eq!(t.sample(-1.0), 0.0);
+        assert_eq!(t.sample(0.5), 5.0);
+        assert_eq!(t.sample(1.5), 7.5);
+        assert_eq!(t.sample(3.0), 0.0);
+        assert!(t.is_finished(2.0));
+
+        let t = t.repeat(Repeat::Loop);
+        assert_eq!(t.sample(2.5), 5.0);
+        assert!(!t.is_finished(10.0));
+
+        let t = timeline()
+            .at(0.0, 0.0f32)
+            .at(1.0, 10.0)
+            .repeat(Repeat::PingPong);
+        assert_eq!(t.sample(1.25), 7.5);
+        assert_eq!(t.sample(2.25), 2.5);
+
+        // Easing applies to the keyframe just added, wherever it lands.
+        let t = timeline()
+            .at(0.0, 0.0f32)
+            .at(2.0, 0.0)
+            .at(1.0, 10.0)
+            .ease(Curve::EaseIn);
+        assert_eq!(t.sample(0.5), 2.5);
+        assert_eq!(t.sample(1.5), 5.0);
+    }
+
+    #[test]
+    fn test_anim_interval() {
+        let mut cx = Context::new();
+        assert_eq!(cx.next_anim_interval(), NOMINAL_FRAME);
+
+        // While frames come one after another, animations step by them.
+        cx.wants_frame = true;
+        cx.frame_timings.interval = std::time::Duration::from_millis(8);
+        assert_eq!(cx.next_anim_interval(), 0.008);
+        cx.frame_timings.interval = std::time::Duration::from_secs(3);
+        assert_eq!(cx.next_anim_interval(), MAX_ANIM_STEP);
+
+        // The first frame after idling doesn't count the idle time.
+        cx.wants_frame = false;
+        assert_eq!(cx.next_anim_interval(), NOMINAL_FRAME);
+    }
+
+    #[test]
+    fn test_timeline_controls() {
+        let view = state(
+            || 0.0f32,
+            |x, cx| {
+                let label = format!("{}", cx[x].round());
+                vstack((
+                    button("play", move |cx| {
+                        let t = timeline().at(0.0, 0.0).at(1.0, 60.0);
+                        cx.play_timeline(x, t.repeat(Repeat::Loop))
+                    }),
+                    button("pause", move |cx| cx.pause_animating(x)),
+                    button("seek", move |cx| cx.seek_animation(x, 0.5)),
+                    text(&label),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("play");
+        for _ in 0..9 {
+            h.update();
+        }
+        assert!(h.has_text("10"));
+
+        // Paused animations hold still, and don't need frames.
+        h.click("pause");
+        assert!(!h.context().is_animating());
+        let paused = h.texts();
+        h.update();
+        assert_eq!(h.texts(), paused);
+
+        h.click("seek");
+        assert!(h.has_text("30"));
+    }
+}
@@ -0,0 +1,530 @@
+use crate::*;
+use std::any::Any;
+
+/// Options for the app's window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowOptions {
+    /// Initial title. Views can change it later with `window_title`.
+    pub title: String,
+
+    /// Initial size of the window's content in points. `None` lets the
+    /// platform decide.
+    pub size: Option<Size>,
+
+    /// Smallest size the user can resize the window's content to, in
+    /// points.
+    pub min_size: Option<Size>,
+
+    /// Largest size the user can resize the window's content to, in
+    /// points.
+    pub max_size: Option<Size>,
+
+    /// Can the user resize the window?
+    pub resizable: bool,
+
+    /// Show what's behind the window where nothing is drawn, instead of
+    /// the theme's background. Together with `decorations(false)` this
+    /// makes non-rectangular windows. Depends on platform support.
+    pub transparent: bool,
+
+    /// Show the platform's title bar and border?
+    pub decorations: bool,
+
+    /// Keep the window above other windows, for overlays.
+    pub always_on_top: bool,
+
+    /// Wait for the display to show each frame before drawing the next,
+    /// which avoids tearing.
+    pub vsync: bool,
+
+    /// Most frames per second to draw while animating. `None` draws as
+    /// fast as `vsync` allows.
+    pub max_fps: Option<f32>,
+
+    /// Key to save the window's size and position under, with
+    /// `persistent_state` values, so it opens where it was left.
+    pub persist: Option<String>,
+
+    /// Order of the display's color subpixels, to antialias text against
+    /// them. See `Context::set_lcd_text`. Transparent windows use gray.
+    pub lcd_text: Option<SubpixelOrder>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            title: "rui".into(),
+            size: None,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            transparent: false,
+            decorations: true,
+            always_on_top: false,
+            vsync: true,
+            max_fps: None,
+            persist: None,
+            lcd_text: None,
+        }
+    }
+}
+
+impl WindowOptions {
+    pub fn title(self, title: &str) -> Self {
+        Self {
+            title: title.into(),
+            ..self
+        }
+    }
+
+    pub fn size(self, size: impl Into<Size>) -> Self {
+        Self {
+            size: Some(size.into()),
+            ..self
+        }
+    }
+
+    pub fn min_size(self, size: impl Into<Size>) -> Self {
+        Self {
+            min_size: Some(size.into()),
+            ..self
+        }
+    }
+
+    pub fn max_size(self, size: impl Into<Size>) -> Self {
+        Self {
+            max_size: Some(size.into()),
+            ..self
+        }
+    }
+
+    pub fn resizable(self, resizable: bool) -> Self {
+        Self { resizable, ..self }
+    }
+
+    pub fn transparent(self, transparent: bool) -> Self {
+        Self {
+            transparent,
+            ..self
+        }
+    }
+
+    pub fn decorations(self, decorations: bool) -> Self {
+        Self {
+            decorations,
+            ..self
+        }
+    }
+
+    pub fn always_on_top(self, always_on_top: bool) -> Self {
+        Self {
+            always_on_top,
+            ..self
+        }
+    }
+
+    pub fn vsync(self, vsync: bool) -> Self {
+        Self { vsync, ..self }
+    }
+
+    /// Limits animation to `fps` frames per second, to save power.
+    pub fn max_fps(self, fps: f32) -> Self {
+        Self {
+            max_fps: Some(fps),
+            ..self
+        }
+    }
+
+    /// Remembers the window's size and position under `key`, so it reopens
+    /// where the user left it. Frames are saved with the main window's
+    /// `persistent_state` values.
+    pub fn persist(self, key: &str) -> Self {
+        Self {
+            persist: Some(key.into()),
+            ..self
+        }
+    }
+
+    /// Antialiases text for a display with color subpixels in `order`.
+    pub fn lcd_text(self, order: SubpixelOrder) -> Self {
+        Self {
+            lcd_text: Some(order),
+            ..self
+        }
+    }
+}
+
+/// A window opened with `Context::open_window`, waiting for the event loop
+/// to create it.
+#[cfg(feature = "winit")]
+pub(crate) struct WindowRequest {
+    pub(crate) options: WindowOptions,
+    pub(crate) view: AnyView,
+}
+
+impl Context {
+    /// Opens another window showing `view`. Each window has its own
+    /// `Context`, so state isn't shared between windows. Use `on_main` to
+    /// send changes to the main window.
+    ///
+    /// The window opens once the current event has been handled. Extra
+    /// windows aren't supported on the web.
+    pub fn open_window(&mut self, options: WindowOptions, view: impl View) {
+        #[cfg(feature = "winit")]
+        self.window_requests.push(WindowRequest {
+            options,
+            view: AnyView::new(view),
+        });
+
+        // Only the winit backend opens windows.
+        #[cfg(not(feature = "winit"))]
+        let _ = (options, view);
+    }
+
+    /// Closes the window showing this `Context`. Closing the main window
+    /// quits the app.
+    pub fn close_window(&mut self) {
+        self.close_requested = true;
+    }
+}
+
+type StartHook = Box<dyn Fn(&mut Context)>;
+type EventHook = Box<dyn Fn(&Event, &mut Context)>;
+
+struct AppCommand {
+    info: CommandInfo,
+    func: Box<dyn Fn(&mut Context)>,
+}
+
+/// Everything a `Plugin` can add to an app.
+#[derive(Default)]
+pub struct AppConfig {
+    pub(crate) window: WindowOptions,
+    start_hooks: Vec<StartHook>,
+    event_hooks: Vec<EventHook>,
+    commands: Vec<AppCommand>,
+    pub(crate) menus: Vec<Menu>,
+    pub(crate) wgpu_instance: Option<wgpu::Instance>,
+    #[cfg(feature = "winit")]
+    pub(crate) access_adapter: Option<std::rc::Rc<MakeAccessAdapter>>,
+}
+
+impl AppConfig {
+    /// Adds a value to the environment, where views can read it with `env`.
+    pub fn resource<T: Clone + 'static>(&mut self, value: T) -> &mut Self {
+        self.on_start(move |cx| {
+            cx.set_env(&value);
+        })
+    }
+
+    /// Adds a menu command which isn't tied to a view. See `command`.
+    pub fn command(
+        &mut self,
+        name: &str,
+        key: Option<HotKey>,
+        f: impl Fn(&mut Context) + 'static,
+    ) -> &mut Self {
+        self.commands.push(AppCommand {
+            info: CommandInfo {
+                path: name.into(),
+                key,
+            },
+            func: Box::new(f),
+        });
+        self
+    }
+
+    /// Calls a function for every event, before the view tree sees it.
+    pub fn on_event(&mut self, f: impl Fn(&Event, &mut Context) + 'static) -> &mut Self {
+        self.event_hooks.push(Box::new(f));
+        self
+    }
+
+    /// Calls a function once the `Context` has been created, before the
+    /// first update.
+    pub fn on_start(&mut self, f: impl Fn(&mut Context) + 'static) -> &mut Self {
+        self.start_hooks.push(Box::new(f));
+        self
+    }
+
+    /// Sets the options for the app's window.
+    pub fn window(&mut self, options: WindowOptions) -> &mut Self {
+        self.window = options;
+        self
+    }
+
+    /// Has the winit backend create its windows' surfaces with `instance`,
+    /// instead of making its own, for hosts which already have one.
+    pub fn wgpu_instance(&mut self, instance: wgpu::Instance) -> &mut Self {
+        self.wgpu_instance = Some(instance);
+        self
+    }
+
+    /// Tells assistive technology, like screen readers, about each window's
+    /// views, with an AccessKit platform adapter `f` makes. `f` is called
+    /// after the window's first update, with the whole tree, and gives the
+    /// `AccessActions` to the adapter as its action handler. For example,
+    /// with `accesskit_winit`, wrap `Adapter::with_action_handler` in a
+    /// type which implements `AccessAdapter`.
+    #[cfg(feature = "winit")]
+    pub fn access_adapter(
+        &mut self,
+        f: impl Fn(
+                &winit::window::Window,
+                accesskit::TreeUpdate,
+                AccessActions,
+            ) -> Box<dyn AccessAdapter>
+            + 'static,
+    ) -> &mut Self {
+        self.access_adapter = Some(std::rc::Rc::new(f));
+        self
+    }
+
+    /// Runs the start hooks. Backends call this once they've created the
+    /// `Context`.
+    pub fn start(&self, cx: &mut Context) {
+        cx.window_title = self.window.title.clone();
+        self.run_start_hooks(cx);
+    }
+
+    /// Runs the start hooks for the `Context` of another window, so it
+    /// gets the same resources.
+    pub(crate) fn run_start_hooks(&self, cx: &mut Context) {
+        for hook in &self.start_hooks {
+            (hook)(cx);
+        }
+    }
+}
+
+/// Extends an app with resources, commands and event hooks.
+///
+/// ```no_run
+/// # use rui::*;
+/// struct Hello;
+///
+/// impl Plugin for Hello {
+///     fn build(&self, app: &mut AppConfig) {
+///         app.command("Hello", None, |_| println!("hello"));
+///     }
+/// }
+///
+/// App::new("hello world").plugin(Hello).run();
+/// ```
+pub trait Plugin {
+    fn build(&self, app: &mut AppConfig);
+}
+
+/// Builder for an app. `rui(view)` is shorthand for `App::new(view).run()`.
+pub struct App<V> {
+    view: V,
+    config: AppConfig,
+}
+
+impl<V: View> App<V> {
+    pub fn new(view: V) -> Self {
+        Self {
+            view,
+            config: AppConfig::default(),
+        }
+    }
+
+    /// Adds a plugin.
+    pub fn plugin(mut self, plugin: impl Plugin) -> Self {
+        plugin.build(&mut self.config);
+        self
+    }
+
+    /// Sets the options for the app's window.
+    pub fn window(mut self, options: WindowOptions) -> Self {
+        self.config.window(options);
+        self
+    }
+
+    /// Uses a wgpu instance the host already created. See
+    /// `AppConfig::wgpu_instance`.
+    pub fn wgpu_instance(mut self, instance: wgpu::Instance) -> Self {
+        self.config.wgpu_instance(instance);
+        self
+    }
+
+    /// Adds menus. See `MenuBar`.
+    pub fn menu_bar(mut self, bar: MenuBar) -> Self {
+        self.config.menu_bar(bar);
+        self
+    }
+
+    /// Splits the app into a root view and its config, for backends other
+    /// than the built-in winit event loop.
+    pub fn build(self) -> (impl View, AppConfig) {
+        let App { view, mut config } = self;
+        let view = AppView {
+            child: MenuBarView {
+                child: view,
+                // Kept in the config for the winit backend to show natively.
+                menus: config.menus.clone(),
+            },
+            event_hooks: std::mem::take(&mut config.event_hooks),
+            commands: std::mem::take(&mut config.commands),
+        };
+        (view, config)
+    }
+
+    /// Opens the window and runs the event loop.
+    #[cfg(feature = "winit")]
+    pub fn run(self) {
+        let (view, config) = self.build();
+        run_app(view, config)
+    }
+}
+
+/// Root view which runs the app-level event hooks and commands.
+struct AppView<V> {
+    child: V,
+    event_hooks: Vec<EventHook>,
+    commands: Vec<AppCommand>,
+}
+
+impl<V> View for AppView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        for hook in &self.event_hooks {
+            (hook)(event, cx);
+        }
+        if let Event::Command(name) = event {
+            for cmd in &self.commands {
+                if cmd.info.path == *name {
+                    (cmd.func)(cx);
+                }
+            }
+        }
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+        cmds.extend(self.commands.iter().map(|cmd| cmd.info.clone()));
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for AppView<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Greeting(&'static str);
+
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {
+        fn build(&self, app: &mut AppConfig) {
+            app.resource(Greeting("hello"))
+                .command("Count", None, |cx| cx.window_title.push('!'))
+                .window(WindowOptions::default().title("test"));
+        }
+    }
+
+    #[test]
+    fn test_plugin() {
+        let (view, config) = App::new(EmptyView {}).plugin(TestPlugin).build();
+        assert_eq!(config.window.title, "test");
+
+        let mut cx = Context::new();
+        config.start(&mut cx);
+        assert_eq!(cx.init_env(&|| Greeting("")), Greeting("hello"));
+
+        let mut cmds = vec![];
+        cx.commands(&view, &mut cmds);
+        assert_eq!(cmds.len(), 1);
+
+        cx.process(&view, &Event::Command("Count".into()));
+        assert_eq!(cx.window_title, "test!");
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_open_window() {
+        let options = WindowOptions::default()
+            .title("inspector")
+            .size(Size::new(300.0, 200.0))
+            .min_size(Size::new(100.0, 100.0))
+            .max_size(Size::new(600.0, 400.0));
+        let opts = options.clone();
+        let mut h = Harness::new(vstack((
+            button("open", move |cx| cx.open_window(opts.clone(), text("hi"))),
+            button("close", |cx| cx.close_window()),
+        )));
+
+        h.click("open");
+        let requests = &mut h.context().window_requests;
+        assert_eq!(requests.len(), 1);
+        let request = requests.pop().unwrap();
+        assert_eq!(request.options, options);
+
+        // The new window gets a fresh context.
+        let mut window = Harness::new(request.view);
+        window.expect_text("hi");
+
+        assert!(!h.context().close_requested);
+        h.click("close");
+        assert!(h.context().close_requested);
+    }
+}
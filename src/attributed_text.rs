@@ -0,0 +1,272 @@
+use crate::*;
+use std::ops::Range;
+
+/// Font weight for a run of `AttributedText`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum FontWeight {
+    #[default]
+    Regular,
+    Bold,
+}
+
+/// Styling for a run of `AttributedText`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub size: u32,
+    pub weight: FontWeight,
+
+    /// Uses the theme's text color if not set.
+    pub color: Option<Color>,
+
+    pub underline: bool,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: Text::DEFAULT_SIZE,
+            weight: FontWeight::Regular,
+            color: None,
+            underline: false,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn size(self, size: u32) -> Self {
+        Self { size, ..self }
+    }
+
+    pub fn weight(self, weight: FontWeight) -> Self {
+        Self { weight, ..self }
+    }
+
+    pub fn bold(self) -> Self {
+        self.weight(FontWeight::Bold)
+    }
+
+    pub fn color(self, color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    pub fn underline(self) -> Self {
+        Self {
+            underline: true,
+            ..self
+        }
+    }
+}
+
+/// A run of text with a single style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// Stands in for an attachment in the text of an `AttributedText`.
+pub const ATTACHMENT_CHAR: char = '\u{FFFC}';
+
+/// An image laid out inline with text, as if it were one large glyph.
+/// Its bottom sits on the baseline.
+#[derive(Clone, Debug)]
+pub struct Attachment {
+    pub image: ImageSource,
+    pub size: LocalSize,
+}
+
+impl PartialEq for Attachment {
+    fn eq(&self, other: &Self) -> bool {
+        self.image.key() == other.image.key() && self.size == other.size
+    }
+}
+
+/// A string made of runs with different styles, for use with `rich_text`.
+///
+/// Build one run at a time:
+///
+/// ```
+/// # use rui::*;
+/// let s = AttributedText::new()
+///     .run("Hello ", TextStyle::default())
+///     .run("world", TextStyle::default().bold().color(RED_HIGHLIGHT));
+/// assert_eq!(s.plain_text(), "Hello world");
+/// ```
+///
+/// or style ranges of an existing string:
+///
+/// ```
+/// # use rui::*;
+/// let s = AttributedText::from("Hello world").style_range(6..11, |s| s.underline());
+/// assert_eq!(s.runs().len(), 2);
+/// ```
+///
+/// Images can be placed inline with `attachment`. Each is a single
+/// `ATTACHMENT_CHAR` in the text, so it's selected and stepped over as a
+/// whole.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttributedText {
+    runs: Vec<TextRun>,
+
+    /// Byte offsets of attachments in the text, in order.
+    attachments: Vec<(usize, Attachment)>,
+}
+
+impl AttributedText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a run of text.
+    pub fn run(mut self, text: impl Into<String>, style: TextStyle) -> Self {
+        self.push(text, style);
+        self
+    }
+
+    /// Appends a run of text in place.
+    pub fn push(&mut self, text: impl Into<String>, style: TextStyle) {
+        let text = text.into();
+        if text.is_empty() {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(&text),
+            _ => self.runs.push(TextRun { text, style }),
+        }
+    }
+
+    /// Appends an image, drawn at `size` and styled like the text before it.
+    pub fn attachment(mut self, image: impl Into<ImageSource>, size: impl Into<LocalSize>) -> Self {
+        self.push_attachment(image, size);
+        self
+    }
+
+    /// Appends an image in place.
+    pub fn push_attachment(&mut self, image: impl Into<ImageSource>, size: impl Into<LocalSize>) {
+        let offset = self.len();
+        let style = self.runs.last().map(|r| r.style).unwrap_or_default();
+        self.push(ATTACHMENT_CHAR.to_string(), style);
+        self.attachments.push((
+            offset,
+            Attachment {
+                image: image.into(),
+                size: size.into(),
+            },
+        ));
+    }
+
+    /// The attachment at byte offset `offset` of the text, if any.
+    pub fn attachment_at(&self, offset: usize) -> Option<&Attachment> {
+        self.attachments
+            .binary_search_by_key(&offset, |(o, _)| *o)
+            .ok()
+            .map(|i| &self.attachments[i].1)
+    }
+
+    /// Attachments and their byte offsets in the text.
+    pub fn attachments(&self) -> &[(usize, Attachment)] {
+        &self.attachments
+    }
+
+    pub fn runs(&self) -> &[TextRun] {
+        &self.runs
+    }
+
+    /// The text without any styling.
+    pub fn plain_text(&self) -> String {
+        self.runs.iter().map(|r| r.text.as_str()).collect()
+    }
+
+    /// Length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|r| r.text.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Restyles the byte range `range` of the text, splitting runs as
+    /// needed.
+    ///
+    /// Panics if the range doesn't fall on character boundaries.
+    pub fn style_range(self, range: Range<usize>, f: impl Fn(TextStyle) -> TextStyle) -> Self {
+        let mut result = AttributedText {
+            runs: vec![],
+            attachments: self.attachments,
+        };
+        let mut start = 0;
+        for run in self.runs {
+            let end = start + run.text.len();
+            let a = range.start.clamp(start, end) - start;
+            let b = range.end.clamp(start, end) - start;
+            if a < b {
+                result.push(&run.text[..a], run.style);
+                result.push(&run.text[a..b], f(run.style));
+                result.push(&run.text[b..], run.style);
+            } else {
+                result.push(run.text, run.style);
+            }
+            start = end;
+        }
+        result
+    }
+}
+
+impl From<&str> for AttributedText {
+    fn from(text: &str) -> Self {
+        AttributedText::new().run(text, TextStyle::default())
+    }
+}
+
+impl From<String> for AttributedText {
+    fn from(text: String) -> Self {
+        AttributedText::new().run(text, TextStyle::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_style_range() {
+        let s = AttributedText::from("one two three").style_range(4..7, |s| s.bold());
+        let texts: Vec<_> = s.runs().iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["one ", "two", " three"]);
+        assert_eq!(s.runs()[1].style.weight, FontWeight::Bold);
+        assert_eq!(s.plain_text(), "one two three");
+
+        // Restyling back merges runs.
+        let s = s.style_range(0..13, |s| s.weight(FontWeight::Regular));
+        assert_eq!(s.runs().len(), 1);
+    }
+
+    #[test]
+    fn test_attachments() {
+        let s = AttributedText::from("a")
+            .attachment(ImageData::from_rgba(1, 1, vec![0; 4]), [10.0, 10.0])
+            .run("b", TextStyle::default())
+            .style_range(0..1, |s| s.bold());
+        assert_eq!(s.plain_text(), format!("a{}b", ATTACHMENT_CHAR));
+        assert_eq!(s.runs().len(), 2);
+        assert_eq!(s.attachment_at(1).unwrap().size, LocalSize::new(10.0, 10.0));
+        assert!(s.attachment_at(0).is_none());
+    }
+
+    #[test]
+    fn test_style_range_across_runs() {
+        let s = AttributedText::new()
+            .run("ab", TextStyle::default())
+            .run("cd", TextStyle::default().size(30))
+            .style_range(1..3, |s| s.underline());
+        let texts: Vec<_> = s.runs().iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["a", "b", "c", "d"]);
+        assert!(s.runs()[1].style.underline && s.runs()[2].style.underline);
+        assert_eq!(s.runs()[2].style.size, 30);
+    }
+}
@@ -0,0 +1,153 @@
+//! Benchmarks for performance-sensitive changes. Run the criterion suite
+//! with `cargo bench --features bench`, or call `run_benchmarks` to check
+//! frame times headlessly, for example in CI.
+
+use crate::*;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Frame times measured by `bench_view`.
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    pub name: String,
+    pub frames: usize,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl BenchResult {
+    /// Did every frame finish within `budget`?
+    pub fn within_budget(&self, budget: Duration) -> bool {
+        self.max <= budget
+    }
+
+    /// Did every frame fit in a 60Hz frame?
+    pub fn within_frame_budget(&self) -> bool {
+        self.within_budget(FRAME_BUDGET)
+    }
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: mean {:?}, max {:?} over {} frames",
+            self.name, self.mean, self.max, self.frames
+        )
+    }
+}
+
+/// Times `frames` full updates of `view` in a `Harness`. Each frame
+/// relays out everything, as after a window resize.
+pub fn bench_view<V: View>(name: &str, view: V, frames: usize) -> BenchResult {
+    bench_with(name, view, frames, |_, _| ())
+}
+
+/// Like `bench_view`, but calls `step` before each frame, for sending
+/// events or changing state. The time taken by `step` is included.
+pub fn bench_with<V: View>(
+    name: &str,
+    view: V,
+    frames: usize,
+    mut step: impl FnMut(&mut Harness<V>, usize),
+) -> BenchResult {
+    let mut harness = Harness::new(view);
+    let mut total = Duration::ZERO;
+    let mut max = Duration::ZERO;
+    for frame in 0..frames {
+        harness.invalidate();
+        let start = Instant::now();
+        step(&mut harness, frame);
+        harness.update();
+        let elapsed = start.elapsed();
+        total += elapsed;
+        max = max.max(elapsed);
+    }
+    BenchResult {
+        name: name.into(),
+        frames,
+        mean: total / frames.max(1) as u32,
+        max,
+    }
+}
+
+/// Stacks nested `depth` deep.
+pub fn deep_tree(depth: usize) -> impl View {
+    let mut view = AnyView::new(text("leaf"));
+    for i in 0..depth {
+        view = AnyView::new(vstack((text(&i.to_string()), view)).padding(1.0));
+    }
+    view
+}
+
+/// A list of `rows` rows, each with some text and a button.
+pub fn large_list(rows: usize) -> impl View {
+    list((0..rows).collect(), |i| {
+        hstack((
+            text(&format!("row {}", i)),
+            spacer(),
+            button(text("select"), |_| ()),
+        ))
+    })
+}
+
+/// A focused `text_editor` holding a document of `lines` lines.
+pub fn large_document(lines: usize) -> impl View {
+    let doc: String = (0..lines)
+        .map(|i| format!("line {} of a large document\n", i))
+        .collect();
+    state(move || doc.clone(), |doc, _| text_editor(doc))
+}
+
+/// Runs the standard suite: a deep tree, a large list and typing into a
+/// large document.
+pub fn run_benchmarks(frames: usize) -> Vec<BenchResult> {
+    vec![
+        bench_view("deep tree", deep_tree(100), frames),
+        bench_view("large list", large_list(1000), frames),
+        bench_with("large document", large_document(1000), frames, |h, i| {
+            if i == 0 {
+                h.key(Key::Tab);
+            }
+            h.key(Key::Character('x'));
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Optimized, each benchmark fits in about a frame, and unoptimized
+    /// builds are around ten times slower. Leave some headroom for slow
+    /// machines, so only a real regression fails.
+    fn budget() -> Duration {
+        if cfg!(debug_assertions) {
+            FRAME_BUDGET * 20
+        } else {
+            FRAME_BUDGET * 2
+        }
+    }
+
+    #[test]
+    fn test_run_benchmarks() {
+        let results = run_benchmarks(2);
+        assert_eq!(results.len(), 3);
+        for result in results {
+            assert_eq!(result.frames, 2);
+            assert!(result.max >= result.mean);
+        }
+    }
+
+    /// Frame times depend on the machine and what else it's doing, so
+    /// this runs only when asked, with
+    /// `cargo test --features bench -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_frame_budget() {
+        for result in run_benchmarks(10) {
+            assert!(result.within_budget(budget()), "over budget: {}", result);
+        }
+    }
+}
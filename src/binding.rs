@@ -12,12 +12,69 @@ pub trait Binding<S>: Clone + Copy + 'static {
     fn with_mut<T>(&self, cx: &mut Context, f: impl FnOnce(&mut S) -> T) -> T {
         f(self.get_mut(cx))
     }
+
+    /// Replaces the bound value.
+    fn set(&self, cx: &mut Context, value: S) {
+        *self.get_mut(cx) = value
+    }
+
+    /// Projects the binding through a lens, for example into a struct field.
+    fn map<U: 'static, L: Lens<S, U>>(self, lens: L) -> Map<Self, L, U, S>
+    where
+        S: 'static,
+    {
+        Map::new(self, lens)
+    }
 }
 
 pub fn setter<S>(binding: impl Binding<S>) -> impl Fn(S, &mut Context) {
     move |s, cx| binding.with_mut(cx, |v| *v = s)
 }
 
+/// Binding built from a pair of closures. See `binding`.
+pub struct FnBinding<G, GM, S> {
+    get: G,
+    get_mut: GM,
+    phantom: std::marker::PhantomData<S>,
+}
+
+impl<G: Copy, GM: Copy, S> Clone for FnBinding<G, GM, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G: Copy, GM: Copy, S> Copy for FnBinding<G, GM, S> {}
+
+impl<S, G, GM> Binding<S> for FnBinding<G, GM, S>
+where
+    S: 'static,
+    G: Fn(&Context) -> &S + Copy + 'static,
+    GM: Fn(&mut Context) -> &mut S + Copy + 'static,
+{
+    fn get<'a>(&self, cx: &'a Context) -> &'a S {
+        (self.get)(cx)
+    }
+    fn get_mut<'a>(&self, cx: &'a mut Context) -> &'a mut S {
+        (self.get_mut)(cx)
+    }
+}
+
+/// Creates a binding from closures which locate a value in the `Context`.
+/// Useful when a value doesn't live directly in a `state`.
+pub fn binding<S, G, GM>(get: G, get_mut: GM) -> FnBinding<G, GM, S>
+where
+    S: 'static,
+    G: Fn(&Context) -> &S + Copy + 'static,
+    GM: Fn(&mut Context) -> &mut S + Copy + 'static,
+{
+    FnBinding {
+        get,
+        get_mut,
+        phantom: Default::default(),
+    }
+}
+
 pub struct Map<B, L, S, T> {
     binding: B,
     lens: L,
@@ -118,4 +175,32 @@ mod tests {
 
         assert_eq!(*b.get(&cx), 42);
     }
+
+    #[test]
+    fn test_map_closure_lens() {
+        let mut cx = Context::new();
+        let id = ViewId::default();
+        cx.init_state(id, &MyState::default);
+        let s = StateHandle::<MyState>::new(id);
+
+        let b = s.map(lens(|s: &MyState| &s.x, |s: &mut MyState| &mut s.x));
+        b.set(&mut cx, 42);
+
+        assert_eq!(cx[s].x, 42);
+    }
+
+    #[test]
+    fn test_fn_binding() {
+        let mut cx = Context::new();
+        let id = ViewId::default();
+        cx.init_state(id, &MyState::default);
+
+        let b = binding(
+            move |cx| &cx[StateHandle::<MyState>::new(id)].x,
+            move |cx| &mut cx[StateHandle::<MyState>::new(id)].x,
+        );
+        b.set(&mut cx, 7);
+
+        assert_eq!(*b.get(&cx), 7);
+    }
 }
@@ -0,0 +1,349 @@
+use crate::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Pixels read back from the app's own window around a point. Nothing
+/// outside the window, like other apps or the desktop, is sampled. See
+/// `Context::request_capture`.
+#[derive(Clone, Debug)]
+pub struct WindowCapture {
+    /// Window point the capture is centered on.
+    pub center: WorldPoint,
+
+    pub(crate) radius: u32,
+
+    /// Colors of the `2 * radius + 1` square of pixels around `center`, in
+    /// rows from the top. Pixels outside the window are `None`.
+    pub(crate) colors: Vec<Option<Color>>,
+
+    /// Counts captures, so views can tell when a new one arrives.
+    pub(crate) generation: u64,
+}
+
+impl WindowCapture {
+    /// Pixels captured on each side of the center.
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Color of the pixel under `center`.
+    pub fn color(&self) -> Option<Color> {
+        self.color_at(0, 0)
+    }
+
+    /// Color of the pixel `dx` right and `dy` down from the center.
+    pub fn color_at(&self, dx: i32, dy: i32) -> Option<Color> {
+        let r = self.radius as i32;
+        if dx.abs() > r || dy.abs() > r {
+            return None;
+        }
+        let size = 2 * r + 1;
+        self.colors[((dy + r) * size + dx + r) as usize]
+    }
+}
+
+/// Converts a pixel read back from a surface of `format` to a `Color`, as
+/// it would have been passed to vger.
+fn decode_pixel(bytes: &[u8], format: wgpu::TextureFormat) -> Option<Color> {
+    use wgpu::TextureFormat::*;
+    let (r, g, b) = match format {
+        Rgba8Unorm | Rgba8UnormSrgb => (bytes[0], bytes[1], bytes[2]),
+        Bgra8Unorm | Bgra8UnormSrgb => (bytes[2], bytes[1], bytes[0]),
+        _ => return None,
+    };
+    let mut color = Color::new(
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        bytes[3] as f32 / 255.0,
+    );
+    if format.is_srgb() {
+        color.r = srgb_to_linear(color.r);
+        color.g = srgb_to_linear(color.g);
+        color.b = srgb_to_linear(color.b);
+    }
+    Some(color)
+}
+
+/// Starts copying the `size` pixels at `origin` out of a four byte per
+/// pixel texture into a buffer to be mapped. Returns the buffer and its
+/// bytes per row, which are padded.
+fn copy_pixels(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    origin: [u32; 2],
+    size: [u32; 2],
+) -> (wgpu::Buffer, u32) {
+    let [w, h] = size;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let bytes_per_row = (w * 4).div_ceil(align) * align;
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rui capture buffer"),
+        size: (bytes_per_row * h) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("rui capture encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: origin[0],
+                y: origin[1],
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: w,
+            height: h,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+    (buffer, bytes_per_row)
+}
+
+/// The pixels of a mapped buffer from `copy_pixels`, `width` pixels to a
+/// row, without the padding.
+fn mapped_pixels(buffer: &wgpu::Buffer, bytes_per_row: u32, width: u32) -> Vec<u8> {
+    let mut pixels = vec![];
+    {
+        let data = buffer.slice(..).get_mapped_range();
+        for row in data.chunks(bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..(width * 4) as usize]);
+        }
+    }
+    buffer.unmap();
+    pixels
+}
+
+/// Copies the `size` pixels at `origin` out of a four byte per pixel
+/// texture, in rows from the top with no padding. Blocks until the GPU is
+/// done.
+#[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+pub(crate) fn read_pixels(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    origin: [u32; 2],
+    size: [u32; 2],
+) -> Vec<u8> {
+    let (buffer, bytes_per_row) = copy_pixels(device, queue, texture, origin, size);
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+    device.poll(wgpu::Maintain::Wait);
+    mapped_pixels(&buffer, bytes_per_row, size[0])
+}
+
+/// Can frames be read back?
+pub(crate) fn can_capture(format: wgpu::TextureFormat) -> bool {
+    decode_pixel(&[0; 4], format).is_some()
+}
+
+/// A capture being copied out of a frame. The GPU maps the buffer in the
+/// background, and it's read in a later frame.
+pub(crate) struct PendingCapture {
+    buffer: wgpu::Buffer,
+    bytes_per_row: u32,
+
+    /// Set once the buffer is mapped, or failed to map.
+    mapped: Arc<AtomicBool>,
+    ok: Arc<AtomicBool>,
+
+    center: WorldPoint,
+    radius: u32,
+    format: wgpu::TextureFormat,
+
+    /// Corner of the copied pixels in the capture's square, and their
+    /// size.
+    origin: [u32; 2],
+    size: [u32; 2],
+}
+
+impl PendingCapture {
+    /// The finished capture, if the buffer has been mapped.
+    fn finish(&self, generation: u64) -> Option<WindowCapture> {
+        if !self.mapped.load(Ordering::Acquire) {
+            return None;
+        }
+        let r = self.radius as usize;
+        let size = 2 * r + 1;
+        let mut colors = vec![None; size * size];
+        if self.ok.load(Ordering::Acquire) {
+            let [w, h] = self.size;
+            let data = mapped_pixels(&self.buffer, self.bytes_per_row, w);
+            for y in 0..h as usize {
+                for x in 0..w as usize {
+                    let offset = (y * w as usize + x) * 4;
+                    let i = (y + self.origin[1] as usize) * size + x + self.origin[0] as usize;
+                    colors[i] = decode_pixel(&data[offset..offset + 4], self.format);
+                }
+            }
+        }
+        Some(WindowCapture {
+            center: self.center,
+            radius: self.radius,
+            colors,
+            generation,
+        })
+    }
+}
+
+impl Context {
+    /// Can `request_capture` read pixels back from the window? This
+    /// depends on the platform and the window's pixel format, and is only
+    /// known after the first frame is drawn.
+    pub fn can_capture_window(&self) -> bool {
+        self.capture_supported
+    }
+
+    /// Reads back the pixels within `radius` pixels of `center` once the
+    /// next frame is drawn. The GPU copies them out in the background, so
+    /// the result is available from `window_capture` a frame or so later.
+    ///
+    /// Only the app's own window can be read, not the rest of the screen
+    /// or shader effects, and only where `can_capture_window` is true.
+    /// Pixels outside the window are `None`.
+    pub fn request_capture(&mut self, center: WorldPoint, radius: u32) {
+        self.capture_request = Some((center, radius));
+    }
+
+    /// The most recent capture.
+    pub fn window_capture(&self) -> Option<&WindowCapture> {
+        self.capture.as_ref()
+    }
+
+    /// Picks up a capture the GPU has finished copying, and starts copying
+    /// a pending `request_capture` out of the layer the frame was drawn
+    /// into. Only one capture is copied at a time.
+    pub(crate) fn read_capture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        window_size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        if let Some(pending) = &self.pending_capture {
+            device.poll(wgpu::Maintain::Poll);
+            let generation = self.capture.as_ref().map_or(0, |c| c.generation) + 1;
+            match pending.finish(generation) {
+                Some(capture) => {
+                    self.capture = Some(capture);
+                    self.pending_capture = None;
+                }
+                None => {
+                    // Check again next frame.
+                    self.redraw_requested = true;
+                    return;
+                }
+            }
+            self.redraw_requested = true;
+        }
+
+        let (center, radius) = match self.capture_request.take() {
+            Some(request) => request,
+            None => return,
+        };
+        let texture = match &self.effect_renderer {
+            Some(renderer) => renderer.layer_texture(),
+            None => return,
+        };
+
+        // World space is y-up in points, the frame is y-down in pixels.
+        let r = radius as i64;
+        let size = 2 * r + 1;
+        let cx = (center.x * scale) as i64;
+        let cy = ((window_size.height - center.y) * scale) as i64;
+        let (width, height) = (texture.width() as i64, texture.height() as i64);
+        let x0 = (cx - r).clamp(0, width);
+        let x1 = (cx + r + 1).clamp(0, width);
+        let y0 = (cy - r).clamp(0, height);
+        let y1 = (cy + r + 1).clamp(0, height);
+
+        if x1 <= x0 || y1 <= y0 {
+            let generation = self.capture.as_ref().map_or(0, |c| c.generation) + 1;
+            self.capture = Some(WindowCapture {
+                center,
+                radius,
+                colors: vec![None; (size * size) as usize],
+                generation,
+            });
+            return;
+        }
+
+        let size = [(x1 - x0) as u32, (y1 - y0) as u32];
+        let (buffer, bytes_per_row) =
+            copy_pixels(device, queue, texture, [x0 as u32, y0 as u32], size);
+        let mapped = Arc::new(AtomicBool::new(false));
+        let ok = Arc::new(AtomicBool::new(false));
+        let (done, succeeded) = (mapped.clone(), ok.clone());
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                succeeded.store(result.is_ok(), Ordering::Release);
+                done.store(true, Ordering::Release);
+            });
+        self.pending_capture = Some(PendingCapture {
+            buffer,
+            bytes_per_row,
+            mapped,
+            ok,
+            center,
+            radius,
+            format,
+            origin: [(x0 - (cx - r)) as u32, (y0 - (cy - r)) as u32],
+            size,
+        });
+        self.redraw_requested = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_decode_pixel() {
+        let red = [255, 0, 0, 255];
+        let c = decode_pixel(&red, wgpu::TextureFormat::Rgba8Unorm).unwrap();
+        assert_eq!((c.r, c.b), (1.0, 0.0));
+        let c = decode_pixel(&red, wgpu::TextureFormat::Bgra8Unorm).unwrap();
+        assert_eq!((c.r, c.b), (0.0, 1.0));
+
+        // sRGB surfaces encode what vger draws, so decode it again.
+        let gray = [188, 188, 188, 255];
+        let c = decode_pixel(&gray, wgpu::TextureFormat::Bgra8UnormSrgb).unwrap();
+        assert!((c.r - 0.5).abs() < 0.01);
+
+        assert!(decode_pixel(&red, wgpu::TextureFormat::Rgba16Float).is_none());
+    }
+
+    #[test]
+    fn test_color_at() {
+        let colors = (0..9).map(|i| Some(Color::gray(i as f32))).collect();
+        let capture = WindowCapture {
+            center: WorldPoint::zero(),
+            radius: 1,
+            colors,
+            generation: 1,
+        };
+        assert_eq!(capture.color(), Some(Color::gray(4.0)));
+        assert_eq!(capture.color_at(1, -1), Some(Color::gray(2.0)));
+        assert_eq!(capture.color_at(2, 0), None);
+    }
+}
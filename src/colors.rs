@@ -1,4 +1,8 @@
-use crate::*;
+/// RGBA color with components in 0..1, in sRGB space.
+///
+/// This is vger's color type, re-exported so apps don't need to depend on
+/// vger directly. Conversions and adjustments are in `ColorExt`.
+pub use vger::color::Color;
 
 pub const TEXT_COLOR: Color = Color::hex_const("#D6D6D6");
 pub const RED_HIGHLIGHT: Color = Color::hex_const("#FF0062");
@@ -52,3 +56,287 @@ pub const MEDIUM_GRAY: Color = Color {
 
 pub const GROOVES: Color = Color::hex_const("#252A2B");
 pub const GROOVES_DARK: Color = Color::hex_const("#0D0D0D");
+
+// Palette for the light theme.
+pub const LIGHT_TEXT_COLOR: Color = Color::hex_const("#1C1C1E");
+pub const LIGHT_BACKGROUND: Color = Color::hex_const("#F2F2F7");
+pub const LIGHT_AZURE_HIGHLIGHT_BACKGROUND: Color = Color::hex_const("#BDEFFF");
+pub const LIGHT_BUTTON_BACKGROUND_COLOR: Color = Color::hex_const("#E0E0E5");
+pub const LIGHT_BUTTON_HOVER_COLOR: Color = Color::hex_const("#CACACF");
+pub const LIGHT_CONTROL_BACKGROUND: Color = Color::hex_const("#D1D1D6");
+
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Conversions and adjustments for `Color`.
+pub trait ColorExt: Sized {
+    /// Creates a color from hue (degrees), saturation and lightness (0..1).
+    fn hsl(h: f32, s: f32, l: f32) -> Self;
+
+    /// Creates a color from OKLCH lightness (0..1), chroma and hue (degrees).
+    /// Out of gamut colors are clamped.
+    fn oklch(l: f32, c: f32, h: f32) -> Self;
+
+    /// Creates a color from hue (degrees), saturation and value (0..1).
+    fn hsv(h: f32, s: f32, v: f32) -> Self;
+
+    /// Returns (hue, saturation, lightness).
+    fn to_hsl(&self) -> (f32, f32, f32);
+
+    /// Returns (hue, saturation, value).
+    fn to_hsv(&self) -> (f32, f32, f32);
+
+    /// Returns (lightness, chroma, hue).
+    fn to_oklch(&self) -> (f32, f32, f32);
+
+    /// Formats the color as `#RRGGBB`, or `#RRGGBBAA` if not opaque.
+    fn to_hex(&self) -> String;
+
+    /// Increases HSL lightness by `amount`.
+    fn lighten(&self, amount: f32) -> Self;
+
+    /// Decreases HSL lightness by `amount`.
+    fn darken(&self, amount: f32) -> Self;
+
+    /// Relative luminance as defined by WCAG.
+    fn luminance(&self) -> f32;
+
+    /// WCAG contrast ratio between two colors, from 1 to 21.
+    fn contrast_ratio(&self, other: Self) -> f32;
+
+    /// Black or white, whichever is easier to read on this color.
+    fn contrasting(&self) -> Self;
+
+    /// This color drawn over `background`, ignoring its alpha.
+    fn over(&self, background: Self) -> Self;
+}
+
+impl ColorExt for Color {
+    fn hsl(h: f32, s: f32, l: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        Color::new(r + m, g + m, b + m, 1.0)
+    }
+
+    fn hsv(h: f32, s: f32, v: f32) -> Self {
+        let l = v * (1.0 - s / 2.0);
+        let s = if l <= 0.0 || l >= 1.0 {
+            0.0
+        } else {
+            (v - l) / l.min(1.0 - l)
+        };
+        Color::hsl(h, s, l)
+    }
+
+    fn oklch(l: f32, c: f32, h: f32) -> Self {
+        let (a, b) = (c * h.to_radians().cos(), c * h.to_radians().sin());
+
+        let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+        let r = 4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_;
+        let g = -1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_;
+        let b = -0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_;
+
+        Color::new(
+            linear_to_srgb(r.clamp(0.0, 1.0)),
+            linear_to_srgb(g.clamp(0.0, 1.0)),
+            linear_to_srgb(b.clamp(0.0, 1.0)),
+            1.0,
+        )
+    }
+
+    fn to_hsl(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        if d == 0.0 {
+            return (0.0, 0.0, l);
+        }
+        let s = d / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == self.r {
+            ((self.g - self.b) / d).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / d + 2.0
+        } else {
+            (self.r - self.g) / d + 4.0
+        };
+        (h * 60.0, s, l)
+    }
+
+    fn to_hsv(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let s = if max <= 0.0 { 0.0 } else { (max - min) / max };
+        (self.to_hsl().0, s, max)
+    }
+
+    fn to_oklch(&self) -> (f32, f32, f32) {
+        let r = srgb_to_linear(self.r);
+        let g = srgb_to_linear(self.g);
+        let b = srgb_to_linear(self.b);
+
+        let l_ = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m_ = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s_ = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        let l = 0.210_454_26 * l_ + 0.793_617_8 * m_ - 0.004_072_047 * s_;
+        let a = 1.977_998_5 * l_ - 2.428_592_2 * m_ + 0.450_593_7 * s_;
+        let b = 0.025_904_037 * l_ + 0.782_771_77 * m_ - 0.808_675_77 * s_;
+
+        (l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0))
+    }
+
+    fn to_hex(&self) -> String {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        if self.a >= 1.0 {
+            format!(
+                "#{:02X}{:02X}{:02X}",
+                byte(self.r),
+                byte(self.g),
+                byte(self.b)
+            )
+        } else {
+            format!(
+                "#{:02X}{:02X}{:02X}{:02X}",
+                byte(self.r),
+                byte(self.g),
+                byte(self.b),
+                byte(self.a)
+            )
+        }
+    }
+
+    fn lighten(&self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Color::hsl(h, s, (l + amount).clamp(0.0, 1.0)).alpha(self.a)
+    }
+
+    fn darken(&self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    fn luminance(&self) -> f32 {
+        0.2126 * srgb_to_linear(self.r)
+            + 0.7152 * srgb_to_linear(self.g)
+            + 0.0722 * srgb_to_linear(self.b)
+    }
+
+    fn contrast_ratio(&self, other: Self) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    fn contrasting(&self) -> Self {
+        if self.contrast_ratio(BLACK) >= self.contrast_ratio(Color::WHITE) {
+            BLACK
+        } else {
+            Color::WHITE
+        }
+    }
+
+    fn over(&self, background: Self) -> Self {
+        let mix = |a: f32, b: f32| a * self.a + b * (1.0 - self.a);
+        Color::new(
+            mix(self.r, background.r),
+            mix(self.g, background.g),
+            mix(self.b, background.b),
+            1.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn assert_close(a: Color, b: Color) {
+        assert!(
+            (a.r - b.r).abs() < 1e-3 && (a.g - b.g).abs() < 1e-3 && (a.b - b.b).abs() < 1e-3,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        assert_close(Color::hsl(0.0, 1.0, 0.5), Color::new(1.0, 0.0, 0.0, 1.0));
+        let (h, s, l) = AZURE_HIGHLIGHT.to_hsl();
+        assert_close(Color::hsl(h, s, l), AZURE_HIGHLIGHT);
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        assert_close(Color::hsv(120.0, 1.0, 0.5), Color::new(0.0, 0.5, 0.0, 1.0));
+        assert_close(Color::hsv(0.0, 0.0, 1.0), Color::WHITE);
+        let (h, s, v) = AZURE_HIGHLIGHT.to_hsv();
+        assert_close(Color::hsv(h, s, v), AZURE_HIGHLIGHT);
+        assert_eq!(BLACK.to_hsv(), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_oklch_round_trip() {
+        let (l, c, h) = GREEN_HIGHLIGHT.to_oklch();
+        assert_close(Color::oklch(l, c, h), GREEN_HIGHLIGHT);
+
+        let (l, c, _) = Color::WHITE.to_oklch();
+        assert!((l - 1.0).abs() < 1e-3 && c < 1e-3);
+    }
+
+    #[test]
+    fn test_lighten_darken() {
+        assert_close(BLACK.lighten(1.0), Color::WHITE);
+        assert_close(Color::WHITE.darken(1.0), BLACK);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((BLACK.contrast_ratio(Color::WHITE) - 21.0).abs() < 1e-3);
+        assert!((TEXT_COLOR.contrast_ratio(TEXT_COLOR) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_contrasting() {
+        assert_eq!(Color::WHITE.contrasting(), BLACK);
+        assert_eq!(BLACK.contrasting(), Color::WHITE);
+        assert_eq!(AZURE_HIGHLIGHT.contrasting(), BLACK);
+        assert_eq!(Color::hex("#0040A0").unwrap().contrasting(), Color::WHITE);
+
+        let half = Color::WHITE.alpha(0.5).over(BLACK);
+        assert_close(half, Color::new(0.5, 0.5, 0.5, 1.0));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(AZURE_HIGHLIGHT.to_hex(), "#00D4FF");
+        assert_close(Color::hex(&RED_HIGHLIGHT.to_hex()).unwrap(), RED_HIGHLIGHT);
+        assert_eq!(BLACK.alpha(0.0).to_hex(), "#00000000");
+    }
+}
@@ -1,21 +1,12 @@
 use crate::*;
-use euclid::*;
+use euclid::Size2D;
 use std::any::Any;
 use std::any::TypeId;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 use std::ops;
-
-pub type LocalSpace = vger::defs::LocalSpace;
-pub type WorldSpace = vger::defs::WorldSpace;
-pub type LocalRect = Rect<f32, LocalSpace>;
-pub type LocalOffset = Vector2D<f32, LocalSpace>;
-pub type LocalSize = Size2D<f32, LocalSpace>;
-pub type LocalPoint = Point2D<f32, LocalSpace>;
-pub type WorldRect = Rect<f32, WorldSpace>;
-pub type WorldPoint = Point2D<f32, WorldSpace>;
-pub type LocalToWorld = Transform2D<f32, LocalSpace, WorldSpace>;
-pub type WorldToLocal = Transform2D<f32, WorldSpace, LocalSpace>;
+use std::time::Duration;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct CommandInfo {
@@ -34,12 +25,50 @@ pub(crate) struct LayoutBox {
 pub(crate) struct StateHolder {
     pub state: Box<dyn Any>,
     pub dirty: bool,
+
+    /// Name of the state's type, for debugging tools.
+    pub type_name: &'static str,
+}
+
+/// Formats a state's value, for debugging tools. See `inspect_state`.
+type StateFormatter = fn(&dyn Any) -> String;
+
+fn format_state<S: std::fmt::Debug + 'static>(state: &dyn Any) -> String {
+    format!("{:?}", state.downcast_ref::<S>().unwrap())
+}
+
+fn default_state_formatters() -> HashMap<TypeId, StateFormatter> {
+    let mut formatters: HashMap<TypeId, StateFormatter> = HashMap::new();
+    macro_rules! formatters {
+        ($($t:ty),*) => {
+            $(formatters.insert(TypeId::of::<$t>(), format_state::<$t>);)*
+        };
+    }
+    formatters!(bool, char, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, String);
+    formatters
+}
+
+/// How long the last frame took to update and render, and each phase of
+/// that. See `FramePhase`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTimings {
+    pub update: std::time::Duration,
+    pub render: std::time::Duration,
+    pub build: std::time::Duration,
+    pub layout: std::time::Duration,
+    pub draw: std::time::Duration,
+    pub gpu: std::time::Duration,
+
+    /// Time between the last two frames drawn.
+    pub interval: std::time::Duration,
 }
 
 pub(crate) type StateMap = HashMap<ViewId, StateHolder>;
 
 pub(crate) type EnvMap = HashMap<TypeId, Box<dyn Any>>;
 
+pub(crate) type LayoutCallback = Box<dyn FnOnce(&mut Context)>;
+
 pub struct RenderInfo<'a> {
     pub device: &'a wgpu::Device,
     pub surface: &'a wgpu::Surface,
@@ -47,27 +76,54 @@ pub struct RenderInfo<'a> {
     pub queue: &'a wgpu::Queue,
 }
 
+/// Where `Context::draw_frame` draws: a window's surface, or a texture.
+pub(crate) struct FrameTarget<'a> {
+    pub(crate) device: &'a wgpu::Device,
+    pub(crate) queue: &'a wgpu::Queue,
+    pub(crate) view: &'a wgpu::TextureView,
+
+    /// The texture behind `view`, if it can be copied from, to keep frames
+    /// drawn straight into it for redrawing part of them next time.
+    pub(crate) texture: Option<&'a wgpu::Texture>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: wgpu::TextureFormat,
+}
+
 /// The Context stores all UI state. A user of the library
 /// shouldn't have to interact with it directly.
 pub struct Context {
     /// Layout information for all views.
-    layout: HashMap<IdPath, LayoutBox>,
+    pub(crate) layout: HashMap<IdPath, LayoutBox>,
 
     /// Allocated ViewIds.
-    view_ids: HashMap<IdPath, ViewId>,
+    pub(crate) view_ids: HashMap<IdPath, ViewId>,
 
     /// Next allocated id.
     next_id: ViewId,
 
+    /// Ids of the views gc found in the tree last time.
+    kept_ids: HashSet<ViewId>,
+
     /// Which views each touch (or mouse pointer) is interacting with.
     pub(crate) touches: [ViewId; 16],
 
+    /// Scroll view scrolling each touch, so views around it leave the
+    /// touch alone.
+    pub(crate) scroll_claims: [ViewId; 16],
+
+    /// Has a scroll view taken the wheel event being processed?
+    pub(crate) scroll_consumed: bool,
+
     /// Points at which touches (or click-drags) started.
     pub(crate) starts: [LocalPoint; 16],
 
     /// Previous touch/mouse positions.
     pub(crate) previous_position: [LocalPoint; 16],
 
+    /// How hard each touch is pressing. See `pressure`.
+    pub(crate) pressures: [f32; 16],
+
     /// Pressed mouse button.
     pub(crate) mouse_button: Option<MouseButton>,
 
@@ -77,12 +133,60 @@ pub struct Context {
     /// The view that has the keyboard focus.
     pub(crate) focused_id: Option<ViewId>,
 
+    /// Should the focused view draw a focus ring? True when focus was
+    /// moved with the keyboard or `request_focus`, rather than a click.
+    pub(crate) focus_visible: bool,
+
+    /// Focusable views in drawing order, for Tab traversal.
+    pub(crate) focus_chain: Vec<ViewId>,
+
+    /// Does a view want input method events? Reset before each draw.
+    pub(crate) ime_allowed: bool,
+
+    /// Where the input method should show its candidate window.
+    pub(crate) ime_cursor_area: Option<WorldRect>,
+
     /// The current title of the window
     pub window_title: String,
 
     /// Are we fullscreen?
     pub fullscreen: bool,
 
+    /// Is the window cleared to transparent rather than the theme's
+    /// background? Set from `WindowOptions::transparent`.
+    pub(crate) transparent_window: bool,
+
+    /// Is each frame drawn over what's already in the target, rather than
+    /// clearing it? Set by `RuiSurface`.
+    pub(crate) draw_over_target: bool,
+
+    /// Set by `request_redraw`, until the next update.
+    pub(crate) redraw_requested: bool,
+
+    /// Did the last update change anything, or was a redraw requested? See
+    /// `needs_frame`.
+    pub(crate) wants_frame: bool,
+
+    /// See `set_click_through`.
+    pub(crate) click_through: bool,
+
+    /// See `set_click_through_empty`.
+    pub(crate) click_through_empty: bool,
+
+    /// Set by `window_drag_area` when a press should start moving the
+    /// window.
+    pub(crate) window_drag_requested: bool,
+
+    /// Windows to open. See `open_window`.
+    #[cfg(feature = "winit")]
+    pub(crate) window_requests: Vec<WindowRequest>,
+
+    /// Set by `close_window`.
+    pub(crate) close_requested: bool,
+
+    /// See `set_native_menus`.
+    pub(crate) native_menus: bool,
+
     /// User state created by `state`.
     pub(crate) state_map: StateMap,
 
@@ -98,28 +202,204 @@ pub struct Context {
     /// Regions of window that needs repainting.
     pub(crate) dirty_region: Region<WorldSpace>,
 
+    /// Views which added to `dirty_region`. Changes inside them are
+    /// covered by their rects.
+    damage_paths: Vec<IdPath>,
+
+    /// Does the whole window need repainting, because something changed
+    /// that `dirty_region` doesn't cover?
+    pub(crate) full_redraw: bool,
+
+    /// See `set_partial_redraw`.
+    partial_redraw: bool,
+
+    /// The part of the window being redrawn, or `None` for all of it.
+    damage: Option<WorldRect>,
+
     /// State dependencies.
     pub(crate) deps: HashMap<ViewId, Vec<ViewId>>,
 
+    /// State read through `get` since `track_reads` started.
+    reads: RefCell<Option<Vec<ViewId>>>,
+
+    /// Other state each `state` or `model` view has read while laying out
+    /// or drawing, so it's invalidated when that state changes.
+    pub(crate) body_reads: HashMap<ViewId, Vec<ViewId>>,
+
+    /// Changed state whose readers added dirty rects.
+    damage_reads: Vec<ViewId>,
+
     /// A stack of ids for states to get parent dependencies.
     pub(crate) id_stack: Vec<ViewId>,
 
     /// Previous window size.
-    window_size: Size2D<f32, WorldSpace>,
+    pub(crate) window_size: Size2D<f32, WorldSpace>,
 
     /// Offset for events at the root level.
-    root_offset: LocalOffset,
+    pub(crate) root_offset: LocalOffset,
+
+    /// See `set_safe_area`.
+    safe_area: Insets,
 
     /// Render the dirty rectangle for debugging?
     render_dirty: bool,
 
+    /// Device pixels per point of the window's display.
+    pub(crate) scale: f32,
+
+    /// Device pixels per point vger measures text at, which it only
+    /// learns when a frame begins.
+    pub(crate) text_scale: f32,
+
+    /// vger's built-in face, for text without a registered one. Taken
+    /// from the `Vger` when first updating.
+    pub(crate) builtin_face: Option<std::sync::Arc<FontFace>>,
+
+    /// See `set_text_pixel_snapping`.
+    pub(crate) text_pixel_snapping: bool,
+
+    /// See `set_lcd_text`.
+    pub(crate) lcd_text: Option<SubpixelOrder>,
+
     pub(crate) access_node_classes: accesskit::NodeClassSet,
 
+    /// The accessibility tree, for screen readers.
+    pub(crate) access: AccessTree,
+
     /// Lock the cursor in position. Useful for dragging knobs.
     pub(crate) grab_cursor: bool,
 
     /// Value of grab_cursor before processing event.
+    #[cfg(feature = "winit")]
     pub(crate) prev_grab_cursor: bool,
+
+    /// Pointer shape asked for by the views under the mouse. See
+    /// `set_cursor`.
+    pub(crate) cursor: Option<CursorIcon>,
+
+    /// Value of cursor last shown by the window.
+    #[cfg(feature = "winit")]
+    pub(crate) prev_cursor: Option<CursorIcon>,
+
+    /// Shader effects collected while drawing the current frame.
+    pub(crate) effect_requests: Vec<EffectRequest>,
+
+    /// Renders shader effects. Created when first needed.
+    pub(crate) effect_renderer: Option<EffectRenderer>,
+
+    /// Draws views with shadows, opacity or blur offscreen. Without one,
+    /// as in tests, those views draw as usual.
+    pub(crate) layer_renderer: Option<LayerRenderer>,
+
+    /// Renders images and caches their textures. Created when first needed.
+    pub(crate) image_renderer: Option<ImageRenderer>,
+
+    /// Renders text from the glyph atlas. Created when text is first drawn.
+    pub(crate) glyph_renderer: Option<GlyphRenderer>,
+
+    /// Renders `particles` views. Created when particles are first drawn.
+    pub(crate) particle_renderer: Option<ParticleRenderer>,
+
+    /// Pixels to read back after the next frame. See `request_capture`.
+    pub(crate) capture_request: Option<(WorldPoint, u32)>,
+    pub(crate) capture: Option<WindowCapture>,
+
+    /// The capture the GPU is copying out of the last frame.
+    pub(crate) pending_capture: Option<PendingCapture>,
+    pub(crate) capture_supported: bool,
+
+    /// GPU debug groups for the current frame.
+    pub(crate) gpu_markers: GpuMarkers,
+
+    /// The OS dark/light preference, if known.
+    system_color_scheme: Option<ColorScheme>,
+
+    /// Should the theme follow the OS dark/light preference?
+    follow_system_theme: bool,
+
+    /// See `load_theme_file`.
+    pub(crate) theme_watch: Option<ThemeWatch>,
+
+    /// Set during each update by `script_file_view`s watching their files.
+    pub(crate) watching_scripts: bool,
+
+    /// Values saved by `persistent_state`.
+    pub(crate) persistent: PersistentStore,
+
+    /// Timings of the last frame, filled in by the backend.
+    pub(crate) frame_timings: FrameTimings,
+
+    /// Work waiting to run with `schedule_idle`.
+    pub(crate) idle: IdleQueue,
+
+    /// Results of futures started with `spawn`.
+    pub(crate) tasks: TaskQueue,
+
+    /// See `open_file_dialog`.
+    pub(crate) file_dialogs: FileDialogs,
+
+    /// The window this is the `Context` of, for work sent back to it from
+    /// other threads.
+    #[cfg(feature = "winit")]
+    pub(crate) window_id: Option<winit::window::WindowId>,
+
+    /// See `draggable` and `on_drop`.
+    pub(crate) drag: DragDrop,
+
+    /// State values animating with `animate_state`.
+    pub(crate) animations: Animations,
+
+    /// See `anim_interval`.
+    pub(crate) anim_interval: f32,
+
+    /// See `set_profiling_overlay`.
+    pub(crate) profiler: Profiler,
+
+    /// See `set_inspector`.
+    pub(crate) inspector: Inspector,
+
+    /// Paths and types of the views gc visited in the last update, in
+    /// order, for `view_tree`.
+    pub(crate) view_list: Vec<(IdPath, &'static str)>,
+
+    /// Filled in by `View::gc_view` during gc, when the view list is kept.
+    visited_views: Option<Vec<(IdPath, &'static str)>>,
+
+    /// Set by tools which read `view_tree`, which also keeps the view list
+    /// while the inspector is on.
+    pub(crate) keep_view_list: bool,
+
+    /// Set by `set_strict_mode`.
+    pub(crate) strict: Option<StrictMode>,
+
+    /// State types whose values debugging tools can show. See
+    /// `inspect_state`.
+    state_formatters: HashMap<TypeId, StateFormatter>,
+
+    /// Set by `on_state_change` and `add_state_middleware`.
+    pub(crate) observers: StateObservers,
+
+    /// Actions from `dispatch` which no `store` has taken yet.
+    pub(crate) dispatched: Vec<Box<dyn Any>>,
+
+    /// Where views with an `id` are, so their state follows them.
+    pub(crate) keyed_ids: KeyedIds,
+
+    /// Set by `on_appear` and `on_disappear`.
+    pub(crate) lifecycles: Lifecycles,
+
+    /// Text laid out for drawing. See `text_layout`.
+    pub(crate) text_layouts: RefCell<TextLayoutCache>,
+
+    /// Calls from `on_size_change`, deferred until layout is done.
+    pub(crate) layout_callbacks: Vec<LayoutCallback>,
+
+    /// Where each `geometry_reader` was in the window in the last update.
+    pub(crate) geometry_positions: HashMap<IdPath, WorldPoint>,
+
+    /// When set, text views add their string and world rect during hittest,
+    /// and focusable views add themselves to the focus chain. See `find_texts`.
+    pub(crate) text_probe: Option<Vec<(IdPath, String, WorldRect)>>,
 }
 
 impl Default for Context {
@@ -133,28 +413,107 @@ impl Context {
         Self {
             layout: HashMap::new(),
             view_ids: HashMap::new(),
-            next_id: ViewId { id: 0 },
+            // Zero is the default id, which means no view.
+            next_id: ViewId { id: 1 },
+            kept_ids: HashSet::new(),
             touches: [ViewId::default(); 16],
+            scroll_claims: [ViewId::default(); 16],
+            scroll_consumed: false,
             starts: [LocalPoint::zero(); 16],
             previous_position: [LocalPoint::zero(); 16],
+            pressures: [1.0; 16],
             mouse_button: None,
             key_mods: Default::default(),
             focused_id: None,
+            focus_visible: false,
+            focus_chain: vec![],
+            ime_allowed: false,
+            ime_cursor_area: None,
             window_title: "rui".into(),
             fullscreen: false,
+            transparent_window: false,
+            draw_over_target: false,
+            redraw_requested: false,
+            wants_frame: false,
+            click_through: false,
+            click_through_empty: false,
+            window_drag_requested: false,
+            #[cfg(feature = "winit")]
+            window_requests: vec![],
+            close_requested: false,
+            native_menus: false,
             state_map: HashMap::new(),
-            dirty: false,
+            // So the first update collects ids and layout for the whole tree.
+            dirty: true,
             enable_dirty: true,
             env: HashMap::new(),
             dirty_region: Region::EMPTY,
+            damage_paths: vec![],
+            full_redraw: true,
+            partial_redraw: true,
+            damage: None,
             deps: HashMap::new(),
+            reads: RefCell::new(None),
+            body_reads: HashMap::new(),
+            damage_reads: vec![],
             id_stack: vec![],
             window_size: Size2D::default(),
             root_offset: LocalOffset::zero(),
+            safe_area: Insets::default(),
             render_dirty: false,
+            scale: 1.0,
+            text_scale: 1.0,
+            builtin_face: None,
+            text_pixel_snapping: true,
+            lcd_text: None,
             access_node_classes: accesskit::NodeClassSet::default(),
+            access: AccessTree::default(),
             grab_cursor: false,
+            #[cfg(feature = "winit")]
             prev_grab_cursor: false,
+            cursor: None,
+            #[cfg(feature = "winit")]
+            prev_cursor: None,
+            effect_requests: vec![],
+            effect_renderer: None,
+            layer_renderer: None,
+            image_renderer: None,
+            glyph_renderer: None,
+            particle_renderer: None,
+            capture_request: None,
+            capture: None,
+            pending_capture: None,
+            capture_supported: false,
+            gpu_markers: GpuMarkers::new(),
+            system_color_scheme: None,
+            follow_system_theme: true,
+            theme_watch: None,
+            watching_scripts: false,
+            persistent: PersistentStore::default(),
+            frame_timings: FrameTimings::default(),
+            idle: IdleQueue::default(),
+            tasks: TaskQueue::default(),
+            file_dialogs: FileDialogs::default(),
+            #[cfg(feature = "winit")]
+            window_id: None,
+            drag: DragDrop::default(),
+            animations: Animations::default(),
+            anim_interval: NOMINAL_FRAME,
+            profiler: Profiler::default(),
+            inspector: Inspector::default(),
+            view_list: vec![],
+            visited_views: None,
+            keep_view_list: false,
+            strict: None,
+            state_formatters: default_state_formatters(),
+            observers: StateObservers::default(),
+            dispatched: vec![],
+            keyed_ids: KeyedIds::default(),
+            lifecycles: Lifecycles::default(),
+            text_layouts: Default::default(),
+            layout_callbacks: vec![],
+            geometry_positions: HashMap::new(),
+            text_probe: None,
         }
     }
 
@@ -163,9 +522,54 @@ impl Context {
         &mut self,
         view: &impl View,
         vger: &mut Vger,
-        access_nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
         window_size: Size2D<f32, WorldSpace>,
     ) -> bool {
+        // Measure text at the density it'll be drawn at, as glyphs are
+        // laid out on the pixel grid.
+        if self.text_scale != self.scale {
+            vger.begin(window_size.width, window_size.height, self.scale);
+            self.text_scale = self.scale;
+        }
+        self.init_builtin_face(vger);
+        self.update_with_text_bounds(
+            view,
+            &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
+            window_size,
+        )
+    }
+
+    /// Like `update`, but measures text with `text_bounds` instead of a
+    /// `Vger`, so it can run without a GPU.
+    pub(crate) fn update_with_text_bounds(
+        &mut self,
+        view: &impl View,
+        text_bounds: &mut dyn FnMut(&str, u32, Option<f32>) -> LocalRect,
+        window_size: Size2D<f32, WorldSpace>,
+    ) -> bool {
+        let changed = self.update_tree(view, text_bounds, window_size);
+        self.wants_frame = changed || std::mem::take(&mut self.redraw_requested);
+        self.wants_frame
+    }
+
+    /// Runs animations, then rebuilds and lays out what changed. Returns
+    /// true if anything did.
+    fn update_tree(
+        &mut self,
+        view: &impl View,
+        text_bounds: &mut dyn FnMut(&str, u32, Option<f32>) -> LocalRect,
+        window_size: Size2D<f32, WorldSpace>,
+    ) -> bool {
+        let update = Stopwatch::start();
+        self.strict_begin_frame();
+        // Layout adds up across updating and rendering, from here.
+        self.frame_timings.layout = Default::default();
+
+        self.poll_theme_file();
+        self.run_finished_tasks();
+
+        // Report sizes from rendering's layout.
+        self.run_layout_callbacks();
+
         // If the window size has changed, force a relayout.
         if window_size != self.window_size {
             self.deps.clear();
@@ -175,66 +579,204 @@ impl Context {
         let mut path = vec![0];
 
         // Run any animations.
+        self.anim_interval = self.next_anim_interval();
+        self.watching_scripts = false;
         let mut actions = vec![];
         view.process(&Event::Anim, &mut path, self, &mut actions);
         assert!(path.len() == 1);
 
+        // Actions with no store to take them are dropped.
+        self.dispatched.clear();
+        self.run_animations(self.anim_interval);
+        self.notify_state_changes();
+
         if self.dirty {
+            let previous = self.layout.clone();
+
             // Clean up state and layout.
             let mut keep = vec![];
-            view.gc(&mut path, self, &mut keep);
+            let keep_view_list = self.keep_view_list || self.inspector.enabled;
+            self.visited_views = keep_view_list.then(Vec::new);
+            self.begin_keyed_ids();
+            view.gc_view(&mut path, self, &mut keep);
+            if self.migrate_keyed_ids() {
+                keep.clear();
+                self.visited_views = keep_view_list.then(Vec::new);
+                view.gc_view(&mut path, self, &mut keep);
+            }
+            self.view_list = self.visited_views.take().unwrap_or_default();
             assert!(path.len() == 1);
             let keep_set = HashSet::<ViewId>::from_iter(keep);
+            self.run_lifecycles(&keep_set);
             self.state_map.retain(|k, _| keep_set.contains(k));
+            self.body_reads.retain(|k, _| keep_set.contains(k));
+            self.deps.retain(|k, _| keep_set.contains(k));
+            self.observers.retain(|k| keep_set.contains(k));
 
-            let mut new_layout = self.layout.clone();
-            new_layout.retain(|k, _| keep_set.contains(&self.view_id(k)));
-            self.layout = new_layout;
+            let view_ids = &self.view_ids;
+            self.layout
+                .retain(|k, _| view_ids.get(k).is_some_and(|id| keep_set.contains(id)));
+            self.geometry_positions
+                .retain(|k, _| view_ids.get(k).is_some_and(|id| keep_set.contains(id)));
+            self.forget_view_ids(keep_set);
 
-            // Get a new accesskit tree.
-            let mut nodes = vec![];
-
-            view.access(&mut path, self, &mut nodes);
-            assert_eq!(path.len(), 1);
-
-            if nodes != *access_nodes {
-                println!("access nodes:");
-                for (id, node) in &nodes {
-                    println!(
-                        "  id: {:?} role: {:?}, children: {:?}",
-                        id,
-                        node.role(),
-                        node.children()
-                    );
-                }
-                *access_nodes = nodes;
-            } else {
-                // println!("access nodes unchanged");
-            }
+            self.update_access_tree(view);
 
             // XXX: we're doing layout both here and in rendering.
+            let layout = Stopwatch::start();
             view.layout(
                 &mut path,
                 &mut LayoutArgs {
                     sz: [window_size.width, window_size.height].into(),
                     cx: self,
-                    text_bounds: &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
+                    text_bounds,
                 },
             );
             assert_eq!(path.len(), 1);
+            let layout = layout.elapsed();
+            self.record_span(FramePhase::Layout, layout);
 
             // Get dirty rectangles.
             view.dirty(&mut path, LocalToWorld::identity(), self);
+            self.note_unscoped_damage(&previous);
 
             self.clear_dirty();
 
+            // Changes from here are laid out when rendering.
+            self.run_layout_callbacks();
+
+            self.record_span(FramePhase::Build, update.elapsed().saturating_sub(layout));
+            self.record_startup_update(update.elapsed());
             true
         } else {
+            // The first update has nothing to redo, but the tree is still
+            // needed.
+            if self.access.is_empty() {
+                self.update_access_tree(view);
+            }
+            self.record_startup_update(update.elapsed());
             false
         }
     }
 
-    /// Redraw the UI using wgpu.
+    /// Views which changed state or layout outside of any view that added
+    /// a dirty rect might draw anywhere, so redraw everything.
+    fn note_unscoped_damage(&mut self, previous: &HashMap<IdPath, LayoutBox>) {
+        let paths = std::mem::take(&mut self.damage_paths);
+        let covered = |path: &IdPath| paths.iter().any(|p| path.starts_with(p));
+        let inside = |path: &IdPath| {
+            paths
+                .iter()
+                .any(|p| path.len() > p.len() && path.starts_with(p))
+        };
+
+        // Views removed by a state change are inside that state's view.
+        let moved = self
+            .layout
+            .iter()
+            .filter(|(path, layout_box)| previous.get(*path) != Some(*layout_box))
+            .any(|(path, _)| !inside(path));
+
+        let reads = std::mem::take(&mut self.damage_reads);
+        let mut changed = self
+            .view_ids
+            .iter()
+            .filter(|(_, id)| self.state_map.get(id).is_some_and(|holder| holder.dirty))
+            .peekable();
+        let unchanged = changed.peek().is_none();
+        let uncovered = changed.any(|(path, id)| !covered(path) && !reads.contains(id));
+
+        if moved || unchanged || uncovered {
+            self.full_redraw = true;
+        }
+    }
+
+    /// Adds `rect`, the bounds of the view at `path`, to the region to
+    /// repaint. Views call this while finding dirty rects in place of
+    /// recursing into their children.
+    pub(crate) fn add_dirty_rect(&mut self, path: &IdPath, rect: LocalRect, xform: LocalToWorld) {
+        self.dirty_region
+            .add_rect(xform.outer_transformed_rect(&rect));
+        self.damage_paths.push(path.clone());
+    }
+
+    /// The part of the window to repaint, in world coordinates, or `None`
+    /// to repaint everything.
+    fn damage(&self) -> Option<WorldRect> {
+        // The overlay changes every frame.
+        if self.full_redraw
+            || !self.partial_redraw
+            || self.profiler.overlay
+            || self.inspector.enabled
+        {
+            return None;
+        }
+        let window = WorldRect::new(WorldPoint::zero(), self.window_size);
+        let offset = self.root_offset.cast_unit();
+        let damage = self
+            .dirty_region
+            .rects()
+            .iter()
+            .fold(WorldRect::zero(), |damage, rect| {
+                damage.union(&rect.translate(offset))
+            })
+            .intersection(&window)
+            .unwrap_or_default();
+        Some(damage)
+    }
+
+    /// Takes the part of the window to repaint since the last call. See
+    /// `damage`.
+    pub(crate) fn take_damage(&mut self) -> Option<WorldRect> {
+        let damage = self.damage();
+        self.dirty_region.clear();
+        self.full_redraw = false;
+        damage
+    }
+
+    /// Can the next frame be skipped, leaving the last one showing? Only
+    /// if nothing was damaged, the last frame was kept, and no pixels are
+    /// being read back.
+    pub(crate) fn can_skip_frame(&self, width: u32, height: u32) -> bool {
+        let cached = self
+            .effect_renderer
+            .as_ref()
+            .is_some_and(|renderer| renderer.has_cache(width, height));
+        cached
+            && self.damage().is_some_and(|damage| damage.is_empty())
+            && self.capture_request.is_none()
+            && self.pending_capture.is_none()
+    }
+
+    /// Does `rect`, with transform `xform`, overlap what's being redrawn?
+    /// Views can skip drawing if not. Inside layers, whose transforms are
+    /// relative to the layer, everything is drawn.
+    pub(crate) fn is_damaged(&self, rect: &LocalRect, xform: &LocalToWorld) -> bool {
+        // Allow for antialiasing.
+        const MARGIN: f32 = 2.0;
+        match self.damage {
+            Some(damage) if !self.in_layer() => xform
+                .outer_transformed_rect(rect)
+                .inflate(MARGIN, MARGIN)
+                .intersects(&damage),
+            _ => true,
+        }
+    }
+
+    /// Only redraw the parts of the window which changed, keeping the rest
+    /// of the last frame. On by default.
+    pub fn set_partial_redraw(&mut self, partial_redraw: bool) {
+        self.partial_redraw = partial_redraw;
+    }
+
+    fn run_layout_callbacks(&mut self) {
+        for callback in std::mem::take(&mut self.layout_callbacks) {
+            callback(self);
+        }
+    }
+
+    /// Redraw the UI using wgpu. Fails if the surface couldn't be drawn
+    /// to, even after reconfiguring it.
     pub fn render(
         &mut self,
         render_info: RenderInfo,
@@ -242,49 +784,141 @@ impl Context {
         vger: &mut Vger,
         window_size: Size2D<f32, WorldSpace>,
         scale: f32,
-    ) {
+    ) -> Result<(), wgpu::SurfaceError> {
         let surface = render_info.surface;
         let device = render_info.device;
         let config = render_info.config;
+        let start = Stopwatch::start();
+        if self.can_skip_frame(config.width, config.height) {
+            self.dirty_region.clear();
+            return Ok(());
+        }
         let frame = match surface.get_current_texture() {
             Ok(frame) => frame,
-            Err(_) => {
+            // Skip this frame.
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
                 surface.configure(device, config);
-                surface
-                    .get_current_texture()
-                    .expect("Failed to acquire next surface texture!")
+                surface.get_current_texture()?
             }
+            Err(err) => return Err(err),
+        };
+        let texture_view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let target = FrameTarget {
+            device,
+            queue: render_info.queue,
+            view: &texture_view,
+            texture: config
+                .usage
+                .contains(wgpu::TextureUsages::COPY_SRC)
+                .then_some(&frame.texture),
+            width: config.width,
+            height: config.height,
+            format: config.format,
         };
+        let gpu = self.draw_frame(target, view, vger, window_size, scale);
+
+        frame.present();
+        self.record_span(FramePhase::Gpu, gpu.elapsed());
+        self.finish_startup(start.elapsed());
+        Ok(())
+    }
+
+    /// Lays out and draws a frame into `target`. Returns when the GPU work
+    /// started, so presenting can be timed with it.
+    pub(crate) fn draw_frame(
+        &mut self,
+        target: FrameTarget,
+        view: &impl View,
+        vger: &mut Vger,
+        window_size: Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) -> Stopwatch {
+        let FrameTarget {
+            device,
+            queue,
+            view: texture_view,
+            texture,
+            width,
+            height,
+            format,
+        } = target;
 
         vger.begin(window_size.width, window_size.height, scale);
+        self.init_builtin_face(vger);
+        self.set_scale_factor(scale);
+        self.text_scale = scale;
+        self.note_frame();
 
         let mut path = vec![0];
         // Disable dirtying the state during layout and rendering
         // to avoid constantly re-rendering if some state is saved.
         self.enable_dirty = false;
-        let local_window_size = window_size.cast_unit::<LocalSpace>();
-        let sz = view.layout(
+        let layout = Stopwatch::start();
+        self.layout_root(
+            view,
+            window_size.cast_unit(),
+            &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
+        );
+        self.record_span(FramePhase::Layout, layout.elapsed());
+
+        if let Some(layers) = &mut self.layer_renderer {
+            layers.begin_frame(window_size, scale);
+        }
+
+        let dirty_rects = self.dirty_region.rects().to_vec();
+
+        // Redraw only what changed if there's a last frame to draw over.
+        let cached = self
+            .effect_renderer
+            .as_ref()
+            .is_some_and(|renderer| renderer.has_cache(width, height));
+        let damage = self.take_damage().filter(|_| cached);
+
+        let mut painter = Painter::new(vger, window_size, scale);
+        painter.translate(self.root_offset);
+        self.focus_chain.clear();
+        self.ime_allowed = false;
+        self.damage = damage;
+        let draw = Stopwatch::start();
+        view.draw(
             &mut path,
-            &mut LayoutArgs {
-                sz: local_window_size,
+            &mut DrawArgs {
                 cx: self,
-                text_bounds: &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
+                vger: &mut painter,
             },
         );
-        assert!(path.len() == 1);
+        // Images and layers were rendered while drawing.
+        let pass_time = self
+            .layer_renderer
+            .as_ref()
+            .map_or(Duration::ZERO, |layers| layers.pass_time());
+        self.record_span(FramePhase::Draw, draw.elapsed().saturating_sub(pass_time));
+        self.damage = None;
+        self.enable_dirty = true;
 
-        // Center the root view in the window.
-        self.root_offset = ((local_window_size - sz) / 2.0).into();
+        if self.profiler.overlay {
+            painter.save();
+            painter.translate(-self.root_offset);
+            self.draw_overlay(&mut painter, window_size);
+            painter.restore();
+        }
 
-        vger.translate(self.root_offset);
-        view.draw(&mut path, &mut DrawArgs { cx: self, vger });
-        self.enable_dirty = true;
+        if self.inspector.enabled {
+            painter.save();
+            painter.translate(-self.root_offset);
+            self.draw_inspector(&mut painter);
+            painter.restore();
+        }
 
         if self.render_dirty {
-            let paint = vger.color_paint(RED_HIGHLIGHT);
+            let paint = painter.color_paint(RED_HIGHLIGHT);
             let xf = WorldToLocal::identity();
-            for rect in self.dirty_region.rects() {
-                vger.stroke_rect(
+            for rect in &dirty_rects {
+                painter.stroke_rect(
                     xf.transform_point(rect.min()),
                     xf.transform_point(rect.max()),
                     0.0,
@@ -294,32 +928,246 @@ impl Context {
             }
         }
 
-        self.dirty_region.clear();
+        let gpu = Stopwatch::started_ago(pass_time);
 
-        let texture_view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        // If images or layers were drawn, the frame is drawn into a layer
+        // of its own, in passes between them. Then vger only clears the
+        // target, and the frame's layer is composited over it.
+        self.end_pass(&mut painter);
+
+        let effect_requests = std::mem::take(&mut self.effect_requests);
+        let background = if self.transparent_window {
+            Color::new(0.0, 0.0, 0.0, 0.0)
+        } else {
+            self.theme().background
+        };
+
+        // With effects, when reading back pixels, or when redrawing part of
+        // the last frame, the frame renders to an intermediate layer which
+        // is composited into the target. Drawing over the target can't use
+        // a layer, which would be composited over the target's contents
+        // without blending.
+        let layered = !self.draw_over_target
+            && (damage.is_some() || !effect_requests.is_empty() || self.capture_request.is_some());
+        let target_view = if layered {
+            self.effect_renderer
+                .get_or_insert_with(|| EffectRenderer::new(device, format))
+                .layer_view(device, width, height)
+        } else {
+            texture_view
+        };
+
+        self.gpu_markers.end_frame();
 
         let desc = wgpu::RenderPassDescriptor {
-            label: None,
+            label: Some("rui vector content"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &texture_view,
+                view: target_view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    load: if self.draw_over_target {
+                        wgpu::LoadOp::Load
+                    } else {
+                        wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background.r as f64,
+                            g: background.g as f64,
+                            b: background.b as f64,
+                            a: background.a as f64,
+                        })
+                    },
                     store: true,
                 },
             })],
             depth_stencil_attachment: None,
         };
 
-        vger.encode(&desc);
+        painter.encode(&desc);
+        if let Some(renderer) = &mut self.image_renderer {
+            renderer.end_frame();
+        }
+        if let Some(renderer) = &mut self.glyph_renderer {
+            // Glyphs were left out, so draw them next frame.
+            if renderer.end_frame() {
+                self.redraw_requested = true;
+                self.full_redraw = true;
+            }
+        }
+        // Updates without a frame, as when the mouse moves, keep layouts
+        // for the next one.
+        self.text_layouts.get_mut().evict_unused();
+        if let Some(layers) = &mut self.layer_renderer {
+            layers.end_frame(target_view);
+        }
 
-        frame.present();
+        if layered {
+            let renderer = self.effect_renderer.as_mut().unwrap();
+            if self.partial_redraw {
+                // Everything outside the damage was culled, so keep the
+                // last frame there.
+                match damage {
+                    Some(damage) => {
+                        if let Some(px) = scissor_px(&damage, window_size, scale) {
+                            renderer.update_cache(device, queue, Some(px));
+                        }
+                    }
+                    None => renderer.update_cache(device, queue, None),
+                }
+            } else {
+                renderer.drop_cache();
+            }
+            renderer.apply(
+                device,
+                queue,
+                texture_view,
+                &effect_requests,
+                window_size,
+                scale,
+            );
+        } else if self.partial_redraw && !self.draw_over_target {
+            // Keep the frame, drawn straight into the target, to redraw
+            // part of it next time.
+            let renderer = self
+                .effect_renderer
+                .get_or_insert_with(|| EffectRenderer::new(device, format));
+            match texture {
+                Some(texture) => renderer.keep_frame(device, queue, texture, width, height),
+                None => renderer.drop_cache(),
+            }
+        }
+
+        self.capture_supported = can_capture(format);
+        self.read_capture(device, queue, format, window_size, scale);
+        gpu
+    }
+
+    /// Device pixels per point: 1 on most displays and 2 on most HiDPI
+    /// ones. Layout and events are in points, so this is only needed to
+    /// line drawing up with pixels.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets the display's pixels per point, as when the window moves to
+    /// another monitor. Lays out and redraws everything at the new density.
+    pub(crate) fn set_scale_factor(&mut self, scale: f32) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.deps.clear();
+            self.dirty = true;
+            self.full_redraw = true;
+        }
+    }
+
+    /// Drops anything holding GPU resources and redraws everything, after
+    /// the device is recreated or the surface format changes.
+    #[cfg(feature = "winit")]
+    pub(crate) fn reset_gpu_resources(&mut self) {
+        self.effect_renderer = None;
+        self.image_renderer = None;
+        self.glyph_renderer = None;
+        self.particle_renderer = None;
+        self.layer_renderer = None;
+        self.deps.clear();
+        self.dirty = true;
+        self.full_redraw = true;
+    }
+
+    /// Lays out the root view and centers it in the window.
+    pub(crate) fn layout_root(
+        &mut self,
+        view: &impl View,
+        window_size: LocalSize,
+        text_bounds: &mut dyn FnMut(&str, u32, Option<f32>) -> LocalRect,
+    ) {
+        let mut path = vec![0];
+        let sz = view.layout(
+            &mut path,
+            &mut LayoutArgs {
+                sz: window_size,
+                cx: self,
+                text_bounds,
+            },
+        );
+        assert!(path.len() == 1);
+
+        self.root_offset = ((window_size - sz) / 2.0).into();
+    }
+
+    /// Finds where each text view is on screen.
+    ///
+    /// This hittests the world origin, which each container transforms
+    /// into its children's coordinates, so a text view sees the negation
+    /// of its own position. Views which only forward hittests inside their
+    /// bounds (like `clip`) hide their contents.
+    pub(crate) fn find_texts(&mut self, view: &impl View) -> Vec<(IdPath, String, WorldRect)> {
+        self.text_probe = Some(vec![]);
+        let mut path = vec![0];
+        view.hittest(&mut path, (-self.root_offset).to_point(), self);
+        self.text_probe.take().unwrap_or_default()
+    }
+
+    pub(crate) fn probe_text(&mut self, path: &IdPath, text: &str, pt: LocalPoint) {
+        if self.text_probe.is_some() {
+            let size = self.get_layout(path).rect.size;
+            let rect = WorldRect::new((-pt.to_vector()).to_point().cast_unit(), size.cast_unit());
+            if let Some(probe) = &mut self.text_probe {
+                probe.push((path.clone(), text.to_string(), rect));
+            }
+        }
     }
 
     /// Process a UI event.
     pub fn process(&mut self, view: &impl View, event: &Event) {
+        if !matches!(event, Event::Anim) {
+            self.strict_note_input();
+        }
+
+        if let Event::Key(k) = event {
+            match k.pressed() {
+                Some(Key::F12) if k.mods.shift => {
+                    if !k.is_repeat() {
+                        self.set_profiling_overlay(!self.profiler.overlay);
+                    }
+                    return;
+                }
+                Some(Key::F11) if k.mods.shift => {
+                    if !k.is_repeat() {
+                        self.set_inspector(!self.inspector.enabled);
+                    }
+                    return;
+                }
+                _ => (),
+            }
+        }
+        self.inspect_event(event);
+
+        // Tab moves focus between views, if there are any to move between.
+        if let Event::Key(k) = event {
+            if k.pressed() == Some(Key::Tab) && !self.focus_chain.is_empty() {
+                if k.mods.shift {
+                    self.focus_previous();
+                } else {
+                    self.focus_next();
+                }
+                return;
+            }
+        }
+
+        if let Event::Access(request) = event {
+            if request.action == accesskit::Action::Focus {
+                self.access_focus(request.target);
+                return;
+            }
+        }
+
+        self.scroll_consumed = false;
+        self.begin_drop();
+
+        // Views under the mouse ask for their cursor again as it moves.
+        if matches!(event, Event::TouchMove { .. }) && self.mouse_button.is_none() {
+            self.cursor = None;
+        }
+
         let mut actions = vec![];
         let mut path = vec![0];
         view.process(
@@ -328,10 +1176,11 @@ impl Context {
             self,
             &mut actions,
         );
+        self.end_drop(event);
 
         for action in actions {
             if !action.is::<()>() {
-                println!("unhandled action: {:?}", action.type_id());
+                println!("unhandled action: {:?}", (*action).type_id());
             }
         }
     }
@@ -354,6 +1203,31 @@ impl Context {
         }
     }
 
+    /// Drops the ids of views which have left the tree, and of everything
+    /// under them, so views which come back start over like new ones.
+    fn forget_view_ids(&mut self, keep: HashSet<ViewId>) {
+        let before = std::mem::replace(&mut self.kept_ids, keep);
+        let gone: Vec<IdPath> = self
+            .view_ids
+            .iter()
+            .filter(|(_, id)| before.contains(id) && !self.kept_ids.contains(id))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !gone.is_empty() {
+            let kept = &self.kept_ids;
+            self.view_ids.retain(|path, id| {
+                kept.contains(id) || !gone.iter().any(|gone| path.starts_with(gone))
+            });
+        }
+    }
+
+    /// Adds a view to the view list, if it's being kept. See `View::gc_view`.
+    pub(crate) fn note_view(&mut self, path: &IdPath, name: &'static str) {
+        if let Some(views) = &mut self.visited_views {
+            views.push((path.clone(), name));
+        }
+    }
+
     pub(crate) fn get_layout(&self, path: &IdPath) -> LayoutBox {
         match self.layout.get(path) {
             Some(b) => *b,
@@ -378,7 +1252,7 @@ impl Context {
                     path.clone(),
                     LayoutBox {
                         rect: LocalRect::default(),
-                        offset: offset,
+                        offset,
                     },
                 );
             }
@@ -387,7 +1261,8 @@ impl Context {
 
     pub(crate) fn set_dirty(&mut self) {
         if self.enable_dirty {
-            self.dirty = true
+            self.dirty = true;
+            self.full_redraw = true;
         }
     }
 
@@ -404,10 +1279,59 @@ impl Context {
             StateHolder {
                 state: Box::new(value),
                 dirty: false,
+                type_name: std::any::type_name::<S>(),
             },
         );
     }
 
+    /// Starts noting the state read through `get`, for the view with
+    /// state `id`. Pass the result to `end_reads`.
+    pub(crate) fn begin_reads(&self) -> Option<Vec<ViewId>> {
+        self.reads.replace(Some(vec![]))
+    }
+
+    /// Adds the state read since `begin_reads` to what the view with state
+    /// `id` depends on. Views with state nested inside note their own
+    /// reads.
+    pub(crate) fn end_reads(&mut self, id: ViewId, outer: Option<Vec<ViewId>>) {
+        let reads = self.reads.replace(outer).unwrap_or_default();
+        let body_reads = self.body_reads.entry(id).or_default();
+        for read in reads {
+            if read != id && !body_reads.contains(&read) {
+                body_reads.push(read);
+            }
+        }
+    }
+
+    /// Adds a dirty rect for the view at `path` if state it read has
+    /// changed. Returns whether it did.
+    pub(crate) fn add_read_damage(
+        &mut self,
+        id: ViewId,
+        path: &IdPath,
+        xform: LocalToWorld,
+    ) -> bool {
+        let state_map = &self.state_map;
+        let dirty: Vec<ViewId> = self
+            .body_reads
+            .get(&id)
+            .map(|reads| {
+                reads
+                    .iter()
+                    .copied()
+                    .filter(|read| state_map.get(read).is_some_and(|holder| holder.dirty))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if dirty.is_empty() {
+            return false;
+        }
+        let rect = self.get_layout(path).rect;
+        self.add_dirty_rect(path, rect, xform);
+        self.damage_reads.extend(dirty);
+        true
+    }
+
     pub(crate) fn is_dirty(&self, id: ViewId) -> bool {
         self.state_map[&id].dirty
     }
@@ -416,9 +1340,24 @@ impl Context {
         self.state_map.entry(id).or_insert_with(|| StateHolder {
             state: Box::new((func)()),
             dirty: false,
+            type_name: std::any::type_name::<S>(),
         });
     }
 
+    /// Lets debugging tools, like strict mode and devtools, see the values
+    /// of states of type `S`. Numbers, bools and strings are already shown.
+    pub fn inspect_state<S: std::fmt::Debug + 'static>(&mut self) {
+        self.state_formatters
+            .insert(TypeId::of::<S>(), format_state::<S>);
+    }
+
+    /// The value of state `id`, if its type can be inspected.
+    pub(crate) fn debug_state(&self, id: ViewId) -> Option<String> {
+        let holder = self.state_map.get(&id)?;
+        let format = self.state_formatters.get(&(*holder.state).type_id())?;
+        Some(format(&*holder.state))
+    }
+
     pub(crate) fn init_env<S: Clone + 'static, D: Fn() -> S + 'static>(&mut self, func: &D) -> S {
         self.env
             .entry(TypeId::of::<S>())
@@ -438,10 +1377,203 @@ impl Context {
         old_value
     }
 
+    /// The view which has the keyboard focus.
+    pub fn focused_view(&self) -> Option<ViewId> {
+        self.focused_id
+    }
+
+    /// Gives the keyboard focus to a view. Get the id of a focusable view
+    /// with `focus_id`.
+    pub fn request_focus(&mut self, id: ViewId) {
+        self.focused_id = Some(id);
+        self.focus_visible = true;
+        self.set_dirty();
+    }
+
+    /// Removes the keyboard focus from whichever view has it.
+    pub fn clear_focus(&mut self) {
+        self.focused_id = None;
+        self.set_dirty();
+    }
+
+    /// Moves focus to the next focusable view, wrapping around.
+    pub fn focus_next(&mut self) {
+        self.move_focus(1)
+    }
+
+    /// Moves focus to the previous focusable view, wrapping around.
+    pub fn focus_previous(&mut self) {
+        self.move_focus(-1)
+    }
+
+    /// Call while drawing to receive `Event::Ime` during the next frame.
+    /// `cursor` is the text cursor in the current view's coordinates, so
+    /// the input method can show candidates next to it.
+    pub fn allow_ime(&mut self, cursor: LocalRect, transform: LocalToWorld) {
+        self.ime_allowed = true;
+        self.ime_cursor_area = Some(transform.outer_transformed_rect(&cursor));
+    }
+
+    /// Lets mouse events pass through the window to whatever is behind
+    /// it, as for a screen annotation overlay. The window gets no mouse
+    /// events until this is turned off again, say from a key press or
+    /// `on_main`. Not supported on all platforms.
+    ///
+    /// On macOS, transparent windows already let clicks through where
+    /// nothing is drawn.
+    pub fn set_click_through(&mut self, click_through: bool) {
+        self.click_through = click_through;
+    }
+
+    pub fn click_through(&self) -> bool {
+        self.click_through
+    }
+
+    /// Lets mouse events pass through the window wherever there's no view
+    /// under the cursor, so only the window's shapes and controls take
+    /// them. Views are hit-tested as the cursor moves. Supported on
+    /// Windows and macOS.
+    pub fn set_click_through_empty(&mut self, click_through_empty: bool) {
+        self.click_through_empty = click_through_empty;
+    }
+
+    pub fn click_through_empty(&self) -> bool {
+        self.click_through_empty
+    }
+
+    /// Would a click at `point`, in window coordinates, pass through the
+    /// window?
+    #[cfg(feature = "winit")]
+    pub(crate) fn clicks_through_at(&mut self, view: &impl View, point: LocalPoint) -> bool {
+        self.click_through
+            || (self.click_through_empty && view.hittest(&mut vec![0], point, self).is_none())
+    }
+
+    /// How hard the touch with `id` in the event being processed is
+    /// pressing, from 0 to 1, as for a pen on a tablet. The mouse, and
+    /// screens which can't tell, press with 1.
+    pub fn pressure(&self, id: usize) -> f32 {
+        self.pressures.get(id).copied().unwrap_or(1.0)
+    }
+
+    fn move_focus(&mut self, step: isize) {
+        let n = self.focus_chain.len() as isize;
+        if n == 0 {
+            return;
+        }
+        let current = self
+            .focused_id
+            .and_then(|id| self.focus_chain.iter().position(|x| *x == id));
+        let index = match current {
+            Some(i) => (i as isize + step).rem_euclid(n),
+            None if step > 0 => 0,
+            None => n - 1,
+        };
+        self.request_focus(self.focus_chain[index as usize]);
+    }
+
+    /// Sets how much of each edge of the window is covered by notches,
+    /// rounded corners or system bars. winit doesn't report this yet, so
+    /// apps on phones set it themselves. Views like `bottom_sheet` keep
+    /// their content clear of it.
+    pub fn set_safe_area(&mut self, insets: Insets) {
+        if insets != self.safe_area {
+            self.safe_area = insets;
+            self.set_dirty();
+        }
+    }
+
+    pub fn safe_area(&self) -> Insets {
+        self.safe_area
+    }
+
+    /// Returns the theme in effect for the view being processed.
+    pub fn theme(&self) -> Theme {
+        self.env
+            .get(&TypeId::of::<Theme>())
+            .and_then(|b| b.downcast_ref::<Theme>())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets the theme for the whole UI. The theme will no longer
+    /// follow the OS dark/light preference.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.follow_system_theme = false;
+        self.theme_watch = None;
+        self.apply_theme(theme);
+    }
+
+    /// Switches to the built-in theme matching the OS dark/light preference,
+    /// and keeps following it as it changes. This is the default.
+    pub fn follow_system_theme(&mut self) {
+        self.follow_system_theme = true;
+        self.theme_watch = None;
+        if let Some(scheme) = self.system_color_scheme {
+            self.apply_theme(Theme::for_scheme(scheme));
+        }
+    }
+
+    /// The OS dark/light preference, if known.
+    pub fn system_color_scheme(&self) -> Option<ColorScheme> {
+        self.system_color_scheme
+    }
+
+    /// Backends call this when the OS dark/light preference is known or changes.
+    pub fn set_system_color_scheme(&mut self, scheme: Option<ColorScheme>) {
+        self.system_color_scheme = scheme;
+        if self.follow_system_theme {
+            if let Some(scheme) = scheme {
+                self.apply_theme(Theme::for_scheme(scheme));
+            }
+        }
+    }
+
+    /// Loads values for `persistent_state` from a file, and saves them
+    /// there from then on. By default the winit backend uses
+    /// `default_persistence_path`.
+    ///
+    /// States which have already been created keep their current values.
+    pub fn set_persistence_path(&mut self, path: impl AsRef<std::path::Path>) {
+        self.persistent = PersistentStore::load(path.as_ref());
+    }
+
+    /// Writes any changed `persistent_state` values to disk, on another
+    /// thread, once they stop changing. The winit backend calls this after
+    /// each update.
+    pub fn save_persistent_state(&mut self) -> std::io::Result<()> {
+        self.persistent.save()
+    }
+
+    /// Like `save_persistent_state`, but waits until the values are
+    /// written. The winit backend calls this when a window closes.
+    pub fn flush_persistent_state(&mut self) -> std::io::Result<()> {
+        self.persistent.flush()
+    }
+
+    /// How long the last frame took. Not measured on wasm.
+    pub fn frame_timings(&self) -> FrameTimings {
+        self.frame_timings
+    }
+
+    pub(crate) fn apply_theme(&mut self, theme: Theme) {
+        if self.theme() != theme {
+            self.set_env(&theme);
+
+            // Force a relayout and redraw.
+            self.deps.clear();
+            self.dirty = true;
+            self.full_redraw = true;
+        }
+    }
+
     pub fn get<S>(&self, id: StateHandle<S>) -> &S
     where
         S: 'static,
     {
+        if let Some(reads) = self.reads.borrow_mut().as_mut() {
+            reads.push(id.id);
+        }
         self.state_map[&id.id].state.downcast_ref::<S>().unwrap()
     }
 
@@ -449,8 +1581,13 @@ impl Context {
     where
         S: 'static,
     {
-        self.set_dirty();
+        // The views which read the state add dirty rects for it.
+        if self.enable_dirty {
+            self.dirty = true;
+        }
 
+        self.observers.note_change(id.id);
+        self.strict_note_write(id.id);
         let holder = self.state_map.get_mut(&id.id).unwrap();
         holder.dirty = true;
         holder.state.downcast_mut::<S>().unwrap()
@@ -0,0 +1,179 @@
+//! Remote inspection of a running app.
+//!
+//! Add the `DevTools` plugin and connect to the socket it listens on. The
+//! protocol is newline-delimited JSON: each request is an object with a
+//! `method`, and each response is an object with either a `result` or an
+//! `error`. The methods are:
+//!
+//! - `tree`: every view, nested as in the view tree, with its type, id,
+//!   rect and offset in its parent's coordinates, if it's laid out, and
+//!   its `state`. See `ViewNode`.
+//! - `state`: the type, dirty flag and value of each `state`, plus the
+//!   values of `persistent_state`. Values are formatted with `Debug`, for
+//!   types registered with `Context::inspect_state`, and null otherwise.
+//! - `timings`: how long the last frame took, in milliseconds.
+//!
+//! For example, `echo '{"method": "timings"}' | nc 127.0.0.1 9123`.
+
+use crate::*;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+
+/// Address `DevTools::default()` listens on.
+pub const DEFAULT_DEVTOOLS_ADDR: &str = "127.0.0.1:9123";
+
+/// Plugin which serves the devtools protocol.
+pub struct DevTools {
+    addr: String,
+}
+
+impl DevTools {
+    /// Listens on `addr`, for example `"127.0.0.1:9123"`. Only bind to
+    /// localhost unless you trust your network.
+    pub fn new(addr: &str) -> Self {
+        Self { addr: addr.into() }
+    }
+}
+
+impl Default for DevTools {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEVTOOLS_ADDR)
+    }
+}
+
+impl Plugin for DevTools {
+    fn build(&self, app: &mut AppConfig) {
+        let addr = self.addr.clone();
+        app.on_start(move |cx| match TcpListener::bind(&addr) {
+            Ok(listener) => {
+                println!("devtools listening on {}", addr);
+                cx.keep_view_list = true;
+                cx.set_dirty();
+                std::thread::spawn(move || serve(listener));
+            }
+            Err(err) => println!("devtools couldn't listen on {}: {}", addr, err),
+        });
+    }
+}
+
+fn serve(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        std::thread::spawn(move || {
+            if let Err(err) = serve_client(stream) {
+                println!("devtools client error: {}", err);
+            }
+        });
+    }
+}
+
+fn serve_client(stream: TcpStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // The Context lives on the main thread, so answer the request there.
+        let (tx, rx) = mpsc::channel();
+        on_main(move |cx| {
+            let _ = tx.send(handle_request(cx, &line));
+        });
+
+        match rx.recv() {
+            Ok(response) => writeln!(writer, "{}", response)?,
+            Err(_) => break, // The app has exited.
+        }
+    }
+    Ok(())
+}
+
+/// Answers a single request.
+pub(crate) fn handle_request(cx: &Context, request: &str) -> Value {
+    let request: Value = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(err) => return json!({ "error": format!("invalid request: {}", err) }),
+    };
+
+    match request["method"].as_str() {
+        Some("tree") => json!({ "result": tree(cx) }),
+        Some("state") => json!({ "result": state(cx) }),
+        Some("timings") => json!({ "result": timings(cx) }),
+        Some(method) => json!({ "error": format!("unknown method: {}", method) }),
+        None => json!({ "error": "missing method" }),
+    }
+}
+
+fn tree(cx: &Context) -> Value {
+    json!({
+        "nodes": cx.view_tree(),
+        "focused": cx.focused_id.map(|id| id.id),
+    })
+}
+
+fn state(cx: &Context) -> Value {
+    let mut states: Vec<(&ViewId, &StateHolder)> = cx.state_map.iter().collect();
+    states.sort_by_key(|(id, _)| id.id);
+
+    let states: Vec<Value> = states
+        .into_iter()
+        .map(|(id, holder)| {
+            json!({
+                "id": id.id,
+                "type": holder.type_name,
+                "dirty": holder.dirty,
+                "value": cx.debug_state(*id),
+            })
+        })
+        .collect();
+
+    json!({
+        "states": states,
+        "persistent": cx.persistent.values(),
+    })
+}
+
+fn timings(cx: &Context) -> Value {
+    let t = cx.frame_timings();
+    json!({
+        "update_ms": t.update.as_secs_f64() * 1000.0,
+        "render_ms": t.render.as_secs_f64() * 1000.0,
+        "build_ms": t.build.as_secs_f64() * 1000.0,
+        "layout_ms": t.layout.as_secs_f64() * 1000.0,
+        "draw_ms": t.draw.as_secs_f64() * 1000.0,
+        "gpu_ms": t.gpu.as_secs_f64() * 1000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_requests() {
+        let mut cx = Context::new();
+        cx.init_state(ViewId { id: 1 }, &|| 0.5f32);
+        cx.init_state(ViewId { id: 2 }, &|| vec![1, 2]);
+
+        let response = handle_request(&cx, r#"{"method": "state"}"#);
+        assert_eq!(response["result"]["states"][0]["type"], "f32");
+        assert_eq!(response["result"]["states"][0]["value"], "0.5");
+        assert!(response["result"]["states"][1]["value"].is_null());
+
+        cx.inspect_state::<Vec<i32>>();
+        let response = handle_request(&cx, r#"{"method": "state"}"#);
+        assert_eq!(response["result"]["states"][1]["value"], "[1, 2]");
+
+        let response = handle_request(&cx, r#"{"method": "timings"}"#);
+        assert_eq!(response["result"]["update_ms"], 0.0);
+
+        let response = handle_request(&cx, r#"{"method": "tree"}"#);
+        assert!(response["result"]["nodes"].as_array().unwrap().is_empty());
+
+        let response = handle_request(&cx, r#"{"method": "nope"}"#);
+        assert!(response["error"].is_string());
+    }
+}
@@ -0,0 +1,247 @@
+//! Native file open and save dialogs.
+
+use crate::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Limits a file dialog to files with some extensions, like
+/// `FileFilter::new("Images", &["png", "jpg"])`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileFilter {
+    pub name: String,
+    /// Extensions without the leading dot.
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn new(name: impl Into<String>, extensions: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+/// A file dialog to be shown, passed to the handler set with
+/// `Context::set_file_dialog_handler`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileDialog {
+    Open {
+        filters: Vec<FileFilter>,
+    },
+    Save {
+        filters: Vec<FileFilter>,
+        /// File name to start with.
+        default_name: Option<String>,
+    },
+}
+
+type DialogCallback = Box<dyn FnOnce(Option<PathBuf>, &mut Context)>;
+type DialogHandler = dyn Fn(&FileDialog) -> Option<PathBuf> + Send + Sync;
+
+/// Ids for dialogs, unique across windows.
+static NEXT_DIALOG_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Dialogs which are showing, waiting for a path.
+#[derive(Default)]
+pub(crate) struct FileDialogs {
+    pending: HashMap<u64, DialogCallback>,
+    handler: Option<Arc<DialogHandler>>,
+}
+
+impl Context {
+    /// Asks the user for a file to open without blocking the UI. `f` is
+    /// called on the UI thread with the chosen path, or `None` if the
+    /// dialog was cancelled. With no filters, any file can be chosen.
+    ///
+    /// On Linux this uses the desktop portal, or zenity without one.
+    pub fn open_file_dialog(
+        &mut self,
+        filters: &[FileFilter],
+        f: impl FnOnce(Option<PathBuf>, &mut Context) + 'static,
+    ) {
+        self.show_file_dialog(
+            FileDialog::Open {
+                filters: filters.to_vec(),
+            },
+            Box::new(f),
+        )
+    }
+
+    /// Like `open_file_dialog`, but asks where to save, starting with
+    /// `default_name` unless it's empty. The user is asked before
+    /// replacing an existing file.
+    pub fn save_file_dialog(
+        &mut self,
+        filters: &[FileFilter],
+        default_name: &str,
+        f: impl FnOnce(Option<PathBuf>, &mut Context) + 'static,
+    ) {
+        self.show_file_dialog(
+            FileDialog::Save {
+                filters: filters.to_vec(),
+                default_name: Some(default_name.to_string()).filter(|n| !n.is_empty()),
+            },
+            Box::new(f),
+        )
+    }
+
+    /// Shows file dialogs with `handler` instead of the platform's, as
+    /// for tests or for apps with their own file browser. Like native
+    /// dialogs, `handler` runs on another thread.
+    pub fn set_file_dialog_handler(
+        &mut self,
+        handler: impl Fn(&FileDialog) -> Option<PathBuf> + Send + Sync + 'static,
+    ) {
+        self.file_dialogs.handler = Some(Arc::new(handler));
+    }
+
+    fn show_file_dialog(&mut self, dialog: FileDialog, f: DialogCallback) {
+        let id = NEXT_DIALOG_ID.fetch_add(1, Ordering::Relaxed);
+        self.file_dialogs.pending.insert(id, f);
+
+        #[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+        {
+            let handler = self.file_dialogs.handler.clone();
+            let window = self.window_id;
+            std::thread::spawn(move || {
+                let path = match handler {
+                    Some(handler) => handler(&dialog),
+                    None => native::show(&dialog),
+                };
+                let finish = move |cx: &mut Context| cx.finish_file_dialog(id, path);
+                match window {
+                    Some(window) => on_window(window, finish),
+                    None => on_main(finish),
+                }
+            });
+        }
+
+        // There's no thread to wait on, so carry on as if cancelled.
+        #[cfg(not(all(feature = "winit", not(target_arch = "wasm32"))))]
+        {
+            let _ = dialog;
+            self.finish_file_dialog(id, None);
+        }
+    }
+
+    fn finish_file_dialog(&mut self, id: u64, path: Option<PathBuf>) {
+        if let Some(f) = self.file_dialogs.pending.remove(&id) {
+            f(path, self);
+        }
+    }
+}
+
+/// Showing dialogs with the platform's own file choosers, with rfd.
+#[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+mod native {
+
+    use super::*;
+
+    /// Waits for the user to choose, on a thread other than the UI's.
+    pub(super) fn show(dialog: &FileDialog) -> Option<PathBuf> {
+        let (filters, save) = match dialog {
+            FileDialog::Open { filters } => (filters, None),
+            FileDialog::Save {
+                filters,
+                default_name,
+            } => (filters, Some(default_name)),
+        };
+        let mut chooser = rfd::AsyncFileDialog::new();
+        for filter in filters {
+            chooser = chooser.add_filter(&filter.name, &filter.extensions);
+        }
+        let chosen = match save {
+            None => futures::executor::block_on(chooser.pick_file()),
+            Some(name) => {
+                if let Some(name) = name {
+                    chooser = chooser.set_file_name(name);
+                }
+                futures::executor::block_on(chooser.save_file())
+            }
+        };
+        chosen.map(|file| file.path().to_path_buf())
+    }
+}
+
+#[cfg(all(test, feature = "winit", not(target_arch = "wasm32")))]
+mod tests {
+
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_file_dialogs() {
+        let _lock = MAIN_QUEUE_LOCK.lock().unwrap();
+        let mut h = Harness::new(state(
+            || String::from("none"),
+            |s, _| {
+                vstack((
+                    button("open", move |cx| {
+                        cx.open_file_dialog(
+                            &[FileFilter::new("Text", &["txt"])],
+                            move |path, cx| cx[s] = format!("opened {:?}", path),
+                        )
+                    }),
+                    button("save", move |cx| {
+                        cx.save_file_dialog(&[], "untitled.txt", move |path, cx| {
+                            cx[s] = format!("saved {:?}", path)
+                        })
+                    }),
+                    with_ref(s, |s| text(s)),
+                ))
+            },
+        ));
+        h.context().set_file_dialog_handler(|dialog| match dialog {
+            FileDialog::Open { filters } => {
+                assert_eq!(filters[0].extensions, vec!["txt".to_string()]);
+                Some(PathBuf::from("notes.txt"))
+            }
+            FileDialog::Save { default_name, .. } => {
+                assert_eq!(default_name.as_deref(), Some("untitled.txt"));
+                None
+            }
+        });
+
+        let wait_for = |h: &mut Harness<_>, s: &str| {
+            let start = Instant::now();
+            while !h.has_text(s) {
+                assert!(start.elapsed() < Duration::from_secs(1), "timed out");
+                std::thread::sleep(Duration::from_millis(5));
+                run_main_queue(h.context());
+                h.update();
+            }
+        };
+        h.click("open");
+        wait_for(&mut h, "opened Some(\"notes.txt\")");
+        h.click("save");
+        wait_for(&mut h, "saved None");
+    }
+
+    #[test]
+    fn test_file_dialog_window() {
+        let _lock = MAIN_QUEUE_LOCK.lock().unwrap();
+        let window = unsafe { winit::window::WindowId::dummy() };
+        let mut cx = Context::new();
+        cx.window_id = Some(window);
+        cx.set_file_dialog_handler(|_| Some(PathBuf::from("notes.txt")));
+        let chosen = Arc::new(std::sync::Mutex::new(None));
+        let c = chosen.clone();
+        cx.open_file_dialog(&[], move |path, _| *c.lock().unwrap() = path);
+
+        // The path goes back to the window which asked.
+        let start = Instant::now();
+        let (to, f) = loop {
+            if let Some(work) = pop_work() {
+                break work;
+            }
+            assert!(start.elapsed() < Duration::from_secs(1), "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(to, Some(window));
+        f(&mut cx);
+        assert_eq!(*chosen.lock().unwrap(), Some(PathBuf::from("notes.txt")));
+    }
+}
@@ -0,0 +1,605 @@
+use crate::*;
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+/// Maximum number of user uniforms that can be passed to a `ShaderEffect`.
+pub const MAX_EFFECT_UNIFORMS: usize = 16;
+
+/// A custom WGSL fragment shader applied to a view's rendered pixels.
+///
+/// The source must define `fn effect(uv: vec2<f32>) -> vec4<f32>`, which
+/// is called for every pixel covered by the view. `uv` runs from `(0, 0)`
+/// at the top-left of the view to `(1, 1)` at the bottom-right.
+///
+/// The shader can use:
+/// - `layer(uv)` to sample the pixels rendered beneath the effect.
+/// - `uniforms.time`, seconds since the view appeared.
+/// - `uniforms.size`, the size of the view in points.
+/// - `uniforms.params[i]`, user uniforms packed into `vec4`s.
+///
+/// For example:
+///
+/// ```no_run
+/// # use rui::*;
+/// let ripple = ShaderEffect::new(
+///     "fn effect(uv: vec2<f32>) -> vec4<f32> {
+///         let d = distance(uv, vec2<f32>(0.5, 0.5));
+///         let offset = 0.01 * sin(40.0 * d - 4.0 * uniforms.time);
+///         return layer(uv + vec2<f32>(offset, offset));
+///     }",
+///     &[],
+/// );
+/// rui(circle().effect(ripple));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShaderEffect {
+    source: String,
+    uniforms: [f32; MAX_EFFECT_UNIFORMS],
+}
+
+impl ShaderEffect {
+    pub fn new(source: &str, uniforms: &[f32]) -> Self {
+        assert!(
+            uniforms.len() <= MAX_EFFECT_UNIFORMS,
+            "at most {} effect uniforms are supported",
+            MAX_EFFECT_UNIFORMS
+        );
+        let mut u = [0.0; MAX_EFFECT_UNIFORMS];
+        u[..uniforms.len()].copy_from_slice(uniforms);
+        Self {
+            source: source.into(),
+            uniforms: u,
+        }
+    }
+
+    /// Returns a copy of the effect with new user uniforms.
+    pub fn uniforms(self, uniforms: &[f32]) -> Self {
+        Self::new(&self.source, uniforms)
+    }
+}
+
+/// An effect to be applied during rendering, collected while drawing.
+pub(crate) struct EffectRequest {
+    pub effect: ShaderEffect,
+    pub rect: WorldRect,
+    pub time: f32,
+
+    /// Debug group the effect was drawn in.
+    pub marker: Option<String>,
+}
+
+const EFFECT_PRELUDE: &str = r#"
+struct EffectUniforms {
+    rect: vec4<f32>,
+    screen: vec2<f32>,
+    size: vec2<f32>,
+    time: f32,
+    pad0: f32,
+    pad1: f32,
+    pad2: f32,
+    params: array<vec4<f32>, 4>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: EffectUniforms;
+@group(0) @binding(1) var layer_texture: texture_2d<f32>;
+@group(0) @binding(2) var layer_sampler: sampler;
+
+struct EffectVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn effect_vs(@builtin(vertex_index) index: u32) -> EffectVertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let px = uniforms.rect.xy + corner * uniforms.rect.zw;
+    var out: EffectVertexOutput;
+    out.position = vec4<f32>(
+        px.x / uniforms.screen.x * 2.0 - 1.0,
+        1.0 - px.y / uniforms.screen.y * 2.0,
+        0.0,
+        1.0
+    );
+    out.uv = corner;
+    return out;
+}
+
+fn layer(uv: vec2<f32>) -> vec4<f32> {
+    let px = uniforms.rect.xy + uv * uniforms.rect.zw;
+    return textureSampleLevel(layer_texture, layer_sampler, px / uniforms.screen, 0.0);
+}
+"#;
+
+const EFFECT_MAIN: &str = r#"
+@fragment
+fn effect_fs(in: EffectVertexOutput) -> @location(0) vec4<f32> {
+    return effect(in.uv);
+}
+"#;
+
+/// Copies the layer to the frame untouched.
+const BLIT_SOURCE: &str = r#"
+fn effect(uv: vec2<f32>) -> vec4<f32> {
+    return layer(uv);
+}
+"#;
+
+/// Layer texture which vger renders into when effects are present, or
+/// pixels are being read back.
+struct EffectLayer {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+/// Renders `ShaderEffect`s. Created lazily the first time an effect is drawn
+/// or pixels are read back.
+pub(crate) struct EffectRenderer {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    /// Compiled effects by source, or why they didn't compile.
+    pipelines: HashMap<String, Result<wgpu::RenderPipeline, String>>,
+    layer: Option<EffectLayer>,
+
+    /// The last frame, kept when redrawing only what changed. See
+    /// `Context::set_partial_redraw`.
+    cache: Option<EffectLayer>,
+}
+
+impl EffectRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rui effect bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rui effect pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rui effect sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            format,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: HashMap::new(),
+            layer: None,
+            cache: None,
+        }
+    }
+
+    fn texture(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        width: u32,
+        height: u32,
+        usage: wgpu::TextureUsages,
+    ) -> EffectLayer {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage,
+            view_formats: &[],
+        });
+        EffectLayer {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            texture,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the texture vger should render into, resizing it as needed.
+    pub fn layer_view(
+        &mut self,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> &wgpu::TextureView {
+        let stale = match &self.layer {
+            Some(layer) => layer.width != width || layer.height != height,
+            None => true,
+        };
+
+        if stale {
+            self.layer = Some(self.texture(
+                device,
+                "rui effect layer",
+                width,
+                height,
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+            ));
+        }
+
+        &self.layer.as_ref().unwrap().view
+    }
+
+    /// The frame as last drawn, for reading back pixels. That's the cache
+    /// when only part of the frame was redrawn into the layer.
+    pub fn layer_texture(&self) -> &wgpu::Texture {
+        &self.source().texture
+    }
+
+    fn source(&self) -> &EffectLayer {
+        self.cache.as_ref().or(self.layer.as_ref()).unwrap()
+    }
+
+    /// Is there a cached frame of this size to redraw part of?
+    pub fn has_cache(&self, width: u32, height: u32) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| cache.width == width && cache.height == height)
+    }
+
+    /// Stops keeping the last frame.
+    pub fn drop_cache(&mut self) {
+        self.cache = None;
+    }
+
+    /// Copies `damage`, a pixel rect, from the layer into the cache, or
+    /// the whole layer if `damage` is `None` or the cache is new.
+    pub fn update_cache(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        damage: Option<(u32, u32, u32, u32)>,
+    ) {
+        let (width, height) = {
+            let layer = self.layer.as_ref().unwrap();
+            (layer.width, layer.height)
+        };
+        let damage = damage.filter(|_| !self.make_cache(device, width, height));
+        let layer = &self.layer.as_ref().unwrap().texture;
+        self.copy_to_cache(device, queue, layer, damage);
+    }
+
+    /// Keeps `texture`, a frame drawn without the layer, as the cache.
+    pub fn keep_frame(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        width: u32,
+        height: u32,
+    ) {
+        self.make_cache(device, width, height);
+        self.copy_to_cache(device, queue, texture, None);
+    }
+
+    /// Makes a cache of the given size, unless there is one. Returns
+    /// whether it did.
+    fn make_cache(&mut self, device: &wgpu::Device, width: u32, height: u32) -> bool {
+        if self.has_cache(width, height) {
+            return false;
+        }
+        self.cache = Some(self.texture(
+            device,
+            "rui frame cache",
+            width,
+            height,
+            wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        ));
+        true
+    }
+
+    /// Copies `damage` from `source`, or all of it if `None`, into the
+    /// cache.
+    fn copy_to_cache(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        source: &wgpu::Texture,
+        damage: Option<(u32, u32, u32, u32)>,
+    ) {
+        let cache = self.cache.as_ref().unwrap();
+        let (width, height) = (cache.width, cache.height);
+        let (x, y, w, h) = match damage {
+            Some((x, y, w, h)) => {
+                let (x, y) = (x.min(width), y.min(height));
+                (x, y, w.min(width - x), h.min(height - y))
+            }
+            None => (0, 0, width, height),
+        };
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        let origin = wgpu::Origin3d { x, y, z: 0 };
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui frame cache encoder"),
+        });
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: source,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &cache.texture,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// Compiles the effect in `source` the first time it's seen. A shader
+    /// which doesn't compile is reported once, and its views are drawn
+    /// without the effect.
+    fn pipeline(&mut self, device: &wgpu::Device, source: &str) -> Result<(), &str> {
+        if !self.pipelines.contains_key(source) {
+            let pipeline = self.compile(device, source);
+            if let Err(err) = &pipeline {
+                println!("rui: couldn't compile shader effect: {}", err);
+            }
+            self.pipelines.insert(source.into(), pipeline);
+        }
+        match &self.pipelines[source] {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn compile(&self, device: &wgpu::Device, source: &str) -> Result<wgpu::RenderPipeline, String> {
+        // Catch errors in the user's WGSL, which wgpu would otherwise treat
+        // as fatal.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rui effect shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!("{}{}{}", EFFECT_PRELUDE, source, EFFECT_MAIN).into(),
+            ),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rui effect pipeline"),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "effect_vs",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "effect_fs",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        match futures::executor::block_on(device.pop_error_scope()) {
+            Some(err) => Err(err.to_string()),
+            None => Ok(pipeline),
+        }
+    }
+
+    fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        rect: [f32; 4],
+        size: LocalSize,
+        time: f32,
+        params: &[f32; MAX_EFFECT_UNIFORMS],
+    ) -> wgpu::BindGroup {
+        let layer = self.source();
+
+        let mut data = [0.0f32; 12 + MAX_EFFECT_UNIFORMS];
+        data[0..4].copy_from_slice(&rect);
+        data[4] = layer.width as f32;
+        data[5] = layer.height as f32;
+        data[6] = size.width;
+        data[7] = size.height;
+        data[8] = time;
+        data[12..].copy_from_slice(params);
+
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("rui effect uniforms"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rui effect bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&layer.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+
+    /// Composites the layer, or the cache if there is one, into `target`,
+    /// running each effect over its rect.
+    pub fn apply(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        requests: &[EffectRequest],
+        window_size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        let (width, height) = {
+            let layer = self.source();
+            (layer.width as f32, layer.height as f32)
+        };
+
+        self.pipeline(device, BLIT_SOURCE)
+            .expect("the blit shader compiles");
+        let requests: Vec<&EffectRequest> = requests
+            .iter()
+            .filter(|request| self.pipeline(device, &request.effect.source).is_ok())
+            .collect();
+
+        let mut draws = vec![(
+            BLIT_SOURCE,
+            None,
+            self.bind_group(
+                device,
+                [0.0, 0.0, width, height],
+                window_size.cast_unit(),
+                0.0,
+                &[0.0; MAX_EFFECT_UNIFORMS],
+            ),
+        )];
+
+        for request in requests {
+            // World space is y-up in points, the layer is y-down in pixels.
+            let r = request.rect;
+            let px = [
+                r.min_x() * scale,
+                (window_size.height - r.max_y()) * scale,
+                r.width() * scale,
+                r.height() * scale,
+            ];
+            draws.push((
+                request.effect.source.as_str(),
+                request.marker.as_deref(),
+                self.bind_group(
+                    device,
+                    px,
+                    r.size.cast_unit(),
+                    request.time,
+                    &request.effect.uniforms,
+                ),
+            ));
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui effect encoder"),
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rui effect pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            for (source, marker, bind_group) in &draws {
+                if let Some(marker) = marker {
+                    rpass.push_debug_group(marker);
+                }
+                rpass.set_pipeline(self.pipelines[*source].as_ref().unwrap());
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.draw(0..4, 0..1);
+                if marker.is_some() {
+                    rpass.pop_debug_group();
+                }
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+#[cfg(all(test, feature = "images", not(target_arch = "wasm32")))]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_compile_error() {
+        let (device, _queue) = test_device();
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let mut renderer = EffectRenderer::new(&device, format);
+
+        assert!(renderer.pipeline(&device, BLIT_SOURCE).is_ok());
+
+        let broken = "fn effect(uv: vec2<f32>) -> vec4<f32> { return nope; }";
+        assert!(renderer.pipeline(&device, broken).is_err());
+        // And it isn't compiled again.
+        assert!(renderer.pipelines[broken].is_err());
+        assert!(renderer.pipeline(&device, broken).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Running rui inside an app which owns the window, event loop and GPU,
+//! like a game using rui for its menus.
+
+use crate::*;
+use euclid::Size2D;
+use std::sync::Arc;
+#[cfg(feature = "winit")]
+use winit::event::WindowEvent;
+
+/// A UI drawn into textures from the app around it, instead of into a
+/// window rui opens. The app keeps its event loop and wgpu device.
+///
+/// For each frame, pass the window's events to `handle_event`, or
+/// `process` without the `winit` feature, call
+/// `update`, then draw the rest of the frame and `render` the UI over it.
+/// `needs_update` says whether to keep going without waiting for events.
+///
+/// The UI draws over what's in the texture, so shader effects and the
+/// eyedropper, which need rui's own layer, aren't available.
+pub struct RuiSurface<V> {
+    cx: Context,
+    view: V,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    format: wgpu::TextureFormat,
+    vger: Vger,
+
+    /// The mouse and fingers.
+    #[cfg(feature = "winit")]
+    input: WinitInput,
+
+    /// Size of the textures passed to `render`, in pixels.
+    width: u32,
+    height: u32,
+}
+
+impl<V: View> RuiSurface<V> {
+    /// Shows `view` in textures of `format`. Call `resize` before the
+    /// first frame.
+    pub fn new(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+        view: V,
+    ) -> Self {
+        let mut cx = Context::new();
+        cx.draw_over_target = true;
+        cx.set_partial_redraw(false);
+        Self {
+            cx,
+            view,
+            vger: Vger::new(device.clone(), queue.clone(), format),
+            device,
+            queue,
+            format,
+            #[cfg(feature = "winit")]
+            input: WinitInput::default(),
+            width: 0,
+            height: 0,
+        }
+    }
+
+    /// The UI's `Context`, for setting the theme, reading the cursor to
+    /// show, and so on.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.cx
+    }
+
+    /// Sets the size of the textures passed to `render`, in pixels, and
+    /// the window's scale factor. `handle_event` calls this when the
+    /// window's size or scale factor changes.
+    pub fn resize(&mut self, width: u32, height: u32, scale: f32) {
+        self.width = width;
+        self.height = height;
+        self.cx.set_scale_factor(scale);
+    }
+
+    /// The UI's size in points.
+    fn size(&self) -> Size2D<f32, WorldSpace> {
+        let scale = self.cx.scale_factor();
+        [self.width as f32 / scale, self.height as f32 / scale].into()
+    }
+
+    /// Passes an event from the window to the UI. Returns true if the UI
+    /// used it, so the rest of the app should ignore it, as for clicks on
+    /// buttons or typing into a focused text field.
+    #[cfg(feature = "winit")]
+    pub fn handle_event(&mut self, event: &WindowEvent<'_>) -> bool {
+        let scale = self.cx.scale_factor();
+        match event {
+            WindowEvent::Resized(size) => self.resize(size.width, size.height, scale),
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => self.resize(
+                new_inner_size.width,
+                new_inner_size.height,
+                *scale_factor as f32,
+            ),
+            WindowEvent::ThemeChanged(theme) => {
+                self.cx.set_system_color_scheme(Some(color_scheme(*theme)))
+            }
+            _ => (),
+        }
+
+        // rui's windows get mouse movement from the device, which the app
+        // may be using, so follow the cursor instead.
+        let previous = self.input.mouse_position;
+        let event = self
+            .input
+            .translate(event, &mut self.cx, scale, self.height as f32);
+        let event = match event {
+            Some(event) => event,
+            None if self.input.mouse_position != previous => Event::TouchMove {
+                id: 0,
+                position: self.input.mouse_position,
+                delta: self.input.mouse_position - previous,
+            },
+            None => return false,
+        };
+        self.process(&event)
+    }
+
+    /// Like `handle_event`, for apps which make rui's events themselves.
+    pub fn process(&mut self, event: &Event) -> bool {
+        let claimed = |cx: &Context, id: usize| !cx.touches[id].is_default();
+        let before = match event {
+            Event::TouchBegin { id, .. }
+            | Event::TouchMove { id, .. }
+            | Event::TouchEnd { id, .. } => claimed(&self.cx, *id),
+            Event::Key(_) | Event::Ime(_) => self.cx.focused_view().is_some(),
+            _ => false,
+        };
+
+        self.cx.process(&self.view, event);
+
+        before
+            || match event {
+                Event::TouchBegin { id, .. } | Event::TouchMove { id, .. } => {
+                    claimed(&self.cx, *id)
+                }
+                Event::Scroll { .. } => self.cx.scroll_consumed,
+                _ => false,
+            }
+    }
+
+    /// Runs animations and lays out the UI, after passing it the frame's
+    /// events. Returns true if it needs rendering again.
+    pub fn update(&mut self) -> bool {
+        #[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+        run_main_queue(&mut self.cx);
+
+        let size = self.size();
+        let redraw = self.cx.update(&self.view, &mut self.vger, size);
+        if let Err(err) = self.cx.save_persistent_state() {
+            println!("failed to save persistent state: {}", err);
+        }
+        redraw
+    }
+
+    /// Draws the UI over the contents of `target`, which should be of the
+    /// size given to `resize` and the format given to `new`.
+    pub fn render(&mut self, target: &wgpu::TextureView) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let (device, queue, format) = (self.device.clone(), self.queue.clone(), self.format);
+        self.cx
+            .layer_renderer
+            .get_or_insert_with(|| LayerRenderer::new(device.clone(), queue.clone(), format));
+
+        let size = self.size();
+        let scale = self.cx.scale_factor();
+        let gpu = self.cx.draw_frame(
+            FrameTarget {
+                device: &device,
+                queue: &queue,
+                view: target,
+                texture: None,
+                width: self.width,
+                height: self.height,
+                format,
+            },
+            &self.view,
+            &mut self.vger,
+            size,
+            scale,
+        );
+        self.cx.record_span(FramePhase::Gpu, gpu.elapsed());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.cx.run_idle_after_frame();
+    }
+
+    /// Is the UI changing or doing idle work, so `update` and `render`
+    /// should be called again without waiting for events? See
+    /// `Context::needs_frame`.
+    pub fn needs_update(&self) -> bool {
+        self.cx.needs_frame() || self.cx.has_idle_work()
+    }
+}
+
+#[cfg(all(test, feature = "images", not(target_arch = "wasm32")))]
+mod tests {
+
+    use super::*;
+
+    const RED: wgpu::Color = wgpu::Color {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_surface() {
+        let (device, queue) = test_device();
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let view = hstack((
+            rectangle()
+                .color(Color::new(0.0, 0.0, 1.0, 1.0))
+                .size([20.0, 20.0])
+                .tap(|_| ()),
+            spacer(),
+        ));
+        let mut surface = RuiSurface::new(device.clone(), queue.clone(), format, view);
+        surface.resize(40, 20, 1.0);
+        surface.update();
+
+        // The host's frame, then the UI over it.
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("host frame"),
+            size: wgpu::Extent3d {
+                width: 40,
+                height: 20,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let target = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(RED),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        queue.submit(Some(encoder.finish()));
+        surface.render(&target);
+
+        let pixels = read_pixels(&device, &queue, &texture, [0, 0], [40, 20]);
+        let pixel = |x: usize, y: usize| &pixels[(y * 40 + x) * 4..][..4];
+        assert_eq!(pixel(10, 10), [0, 0, 255, 255]);
+        assert_eq!(pixel(30, 10), [255, 0, 0, 255]);
+
+        // Clicks on the UI are used, others are left for the app.
+        let click = |surface: &mut RuiSurface<_>, x: f32| {
+            let position = LocalPoint::new(x, 10.0);
+            let down = surface.process(&Event::TouchBegin { id: 0, position });
+            let up = surface.process(&Event::TouchEnd { id: 0, position });
+            down && up
+        };
+        assert!(click(&mut surface, 10.0));
+        assert!(!click(&mut surface, 30.0));
+        assert!(!surface.process(&Event::Key(KeyEvent::press(Key::Character('a')))));
+    }
+}
@@ -25,14 +25,52 @@ pub enum Event {
         position: LocalPoint,
     },
 
+    /// Mouse wheel or trackpad scroll. `delta` is how far the content
+    /// under the pointer should move, in points.
+    Scroll {
+        position: LocalPoint,
+        delta: LocalOffset,
+    },
+
+    /// Files dragged from another app were dropped.
+    DropFiles {
+        position: LocalPoint,
+        paths: Vec<std::path::PathBuf>,
+    },
+
     /// Menu command.
     Command(String),
 
-    /// Key press.
-    Key(Key),
+    /// A key going down, repeating while held, or going up.
+    Key(KeyEvent),
+
+    /// Input method (IME) composition, for text which can't be typed
+    /// one key at a time.
+    Ime(ImeEvent),
 
     /// Animation.
     Anim,
+
+    /// Request from assistive technology, like a screen reader, for the
+    /// view with the `target` node to do something.
+    Access(accesskit::ActionRequest),
+}
+
+/// Input method events, sent to views which asked for IME input with
+/// `Context::allow_ime`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImeEvent {
+    /// Text being composed, which should be shown at the cursor but isn't
+    /// part of the document yet. Empty when composition ends. The cursor
+    /// is a range of character indices into the text, if the input method
+    /// wants one shown.
+    Preedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+
+    /// Finished text to insert at the cursor.
+    Commit(String),
 }
 
 impl Event {
@@ -42,6 +80,30 @@ impl Event {
             Event::TouchBegin { position, .. } => *position += offset,
             Event::TouchMove { position, .. } => *position += offset,
             Event::TouchEnd { position, .. } => *position += offset,
+            Event::Scroll { position, .. } => *position += offset,
+            Event::DropFiles { position, .. } => *position += offset,
+            _ => (),
+        }
+        event
+    }
+
+    /// Maps touch positions with `xform`, as into a transformed view.
+    pub fn transform(&self, xform: &LocalTransform) -> Event {
+        let mut event = self.clone();
+        match &mut event {
+            Event::TouchBegin { position, .. } => *position = xform.transform_point(*position),
+            Event::TouchMove {
+                position, delta, ..
+            } => {
+                *position = xform.transform_point(*position);
+                *delta = xform.transform_vector(*delta);
+            }
+            Event::TouchEnd { position, .. } => *position = xform.transform_point(*position),
+            Event::Scroll { position, delta } => {
+                *position = xform.transform_point(*position);
+                *delta = xform.transform_vector(*delta);
+            }
+            Event::DropFiles { position, .. } => *position = xform.transform_point(*position),
             _ => (),
         }
         event
@@ -55,7 +117,7 @@ pub enum MouseButton {
     Center,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct KeyboardModifiers {
     pub shift: bool,
     pub control: bool,
@@ -63,7 +125,80 @@ pub struct KeyboardModifiers {
     pub command: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+impl KeyboardModifiers {
+    /// Is the platform's shortcut modifier down? That's command on macOS
+    /// and control elsewhere, as for Ctrl+C or Cmd+click.
+    pub fn primary(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.command
+        } else {
+            self.control
+        }
+    }
+}
+
+/// Whether a key went down or up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyPhase {
+    Down,
+    Up,
+}
+
+/// A key going down or up, for `Event::Key`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+
+    /// The modifier keys held at the time.
+    pub mods: KeyboardModifiers,
+
+    pub phase: KeyPhase,
+
+    /// How many times the key has repeated since it went down, from being
+    /// held. Zero for the first press and for the key going up.
+    pub repeat: u32,
+}
+
+impl KeyEvent {
+    /// `key` going down, with no modifiers held.
+    pub fn press(key: Key) -> Self {
+        Self {
+            key,
+            mods: KeyboardModifiers::default(),
+            phase: KeyPhase::Down,
+            repeat: 0,
+        }
+    }
+
+    /// `key` going up.
+    pub fn release(key: Key) -> Self {
+        Self {
+            phase: KeyPhase::Up,
+            ..Self::press(key)
+        }
+    }
+
+    pub fn mods(self, mods: KeyboardModifiers) -> Self {
+        Self { mods, ..self }
+    }
+
+    pub fn repeat(self, repeat: u32) -> Self {
+        Self { repeat, ..self }
+    }
+
+    /// The key, if it went down or repeated. Most views act on these and
+    /// ignore keys going up.
+    pub fn pressed(&self) -> Option<Key> {
+        (self.phase == KeyPhase::Down).then_some(self.key)
+    }
+
+    /// Is this a repeat from the key being held, rather than a new press?
+    pub fn is_repeat(&self) -> bool {
+        self.repeat > 0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Key {
     Character(char),
 
@@ -0,0 +1,551 @@
+//! Fonts registered by the app, and the `Font` text is drawn with.
+//!
+//! Text is laid out with fontdue, in vger's built-in face or a registered
+//! one, and drawn from rui's glyph atlas, a quad a glyph. See `glyphs`.
+//!
+//! Registering a face only checks its tables. Its glyphs are parsed when
+//! text first uses it, and the atlas is made when text is first drawn, so
+//! registering many at startup is cheap.
+
+use crate::*;
+use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle as GlyphStyle};
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Faces added with `register_font`.
+static FONTS: Mutex<Vec<Arc<RegisteredFace>>> = Mutex::new(Vec::new());
+
+/// Counts calls to `register_font`, so text laid out before can be laid
+/// out again with the new faces.
+static FONTS_CHANGED: AtomicU64 = AtomicU64::new(0);
+
+/// Tells faces apart in the glyph atlas.
+static NEXT_FACE_ID: AtomicU64 = AtomicU64::new(1);
+
+pub use vger::LineMetrics;
+
+/// Registered faces are laid out at this many pixels per point, which
+/// keeps glyph positions within a fraction of a point, whatever the
+/// display's scale. Glyphs are rasterized for the display.
+const LAYOUT_SCALE: f32 = 4.0;
+
+/// How much synthesized bold thickens glyphs, as a fraction of their size.
+const BOLD_WIDTH: f32 = 1.0 / 30.0;
+
+/// How far synthesized italics slant, as a fraction of height.
+const ITALIC_SLANT: f32 = 0.2;
+
+/// Error from registering a font.
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "couldn't read font: {}", err),
+            FontError::Parse(err) => write!(f, "couldn't parse font: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Upright or italic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum FontStyle {
+    #[default]
+    Normal,
+    Italic,
+}
+
+/// The font text is drawn with. Set it for the views inside a view with
+/// the `font` modifier.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Font {
+    /// A family added with `register_font`, or `None` for the built-in
+    /// face. Families which aren't registered use the built-in face too.
+    pub family: Option<String>,
+    pub size: u32,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            family: None,
+            size: Text::DEFAULT_SIZE,
+            weight: FontWeight::Regular,
+            style: FontStyle::Normal,
+        }
+    }
+}
+
+/// Adds a TrueType or OpenType face to `family`, for the `font` modifier.
+/// Call it at startup, before the views using it are laid out.
+///
+/// A family can have a face for each weight and style. Text in a weight
+/// or style without its own face uses the closest one, emboldened or
+/// slanted to fit, preferring the right weight, as faking bold looks
+/// worse than faking italics. Registering a face again replaces it.
+///
+/// Returns an error if the data isn't a face. Its glyphs are parsed when
+/// text first uses it.
+pub fn register_font(
+    family: &str,
+    weight: FontWeight,
+    style: FontStyle,
+    data: impl Into<Vec<u8>>,
+) -> Result<(), FontError> {
+    let data = data.into();
+    // Only checks the tables, so it's quick. Glyphs wait until the face
+    // is used.
+    if let Err(err) = ttf_parser::Face::from_slice(&data, 0) {
+        return Err(FontError::Parse(err.to_string()));
+    }
+    let face = RegisteredFace {
+        family: family.to_string(),
+        weight,
+        style,
+        data: Mutex::new(data),
+        face: OnceLock::new(),
+    };
+
+    let mut fonts = FONTS.lock().unwrap();
+    fonts.retain(|f| !(f.family == family && f.weight == weight && f.style == style));
+    fonts.push(Arc::new(face));
+    FONTS_CHANGED.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Changes each time a font is registered.
+pub(crate) fn font_generation() -> u64 {
+    FONTS_CHANGED.load(Ordering::Relaxed)
+}
+
+/// Reads a TrueType or OpenType file and registers it, as with
+/// `register_font`.
+pub fn register_font_file(
+    family: &str,
+    weight: FontWeight,
+    style: FontStyle,
+    path: impl AsRef<Path>,
+) -> Result<(), FontError> {
+    let data = std::fs::read(path).map_err(FontError::Io)?;
+    register_font(family, weight, style, data)
+}
+
+/// A face added with `register_font`.
+struct RegisteredFace {
+    family: String,
+    weight: FontWeight,
+    style: FontStyle,
+
+    /// The file, until it's parsed.
+    data: Mutex<Vec<u8>>,
+
+    /// The parsed face, or `None` if it couldn't be parsed.
+    face: OnceLock<Option<Arc<FontFace>>>,
+}
+
+impl RegisteredFace {
+    /// Parses the face the first time it's used.
+    fn face(&self) -> Option<Arc<FontFace>> {
+        self.face
+            .get_or_init(|| {
+                let data = std::mem::take(&mut *self.data.lock().unwrap());
+                match fontdue::Font::from_bytes(data, fontdue::FontSettings::default()) {
+                    Ok(font) => Some(Arc::new(FontFace::new(font, self.weight, self.style))),
+                    Err(err) => {
+                        println!("couldn't parse font {}: {}", self.family, err);
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+}
+
+/// A parsed face.
+pub(crate) struct FontFace {
+    /// Tells the face's glyphs apart in the atlas.
+    pub(crate) id: u64,
+
+    weight: FontWeight,
+    style: FontStyle,
+
+    pub(crate) font: fontdue::Font,
+}
+
+impl FontFace {
+    pub(crate) fn new(font: fontdue::Font, weight: FontWeight, style: FontStyle) -> Self {
+        Self {
+            id: NEXT_FACE_ID.fetch_add(1, Ordering::Relaxed),
+            weight,
+            style,
+            font,
+        }
+    }
+
+    /// Lays out text like vger does, in points with y up from the top
+    /// line's baseline, positioned to within a fraction of a point.
+    fn layout(self: &Arc<Self>, text: &str, size: u32, max_width: Option<f32>) -> TextLayout {
+        let font = &self.font;
+        let px = size as f32 * LAYOUT_SCALE;
+        let mut layout = Layout::new(CoordinateSystem::PositiveYUp);
+        layout.reset(&LayoutSettings {
+            max_width: max_width.map(|w| w * LAYOUT_SCALE),
+            ..LayoutSettings::default()
+        });
+        layout.append(&[font], &GlyphStyle::new(text, px, 0));
+
+        let s = 1.0 / LAYOUT_SCALE;
+        let (mut glyphs, mut origins) = (vec![], vec![]);
+        for glyph in layout.glyphs() {
+            let bounds = font.metrics_indexed(glyph.key.glyph_index, px).bounds;
+            // Spaces and line breaks have nothing to draw.
+            origins.push(glyph.char_data.rasterize().then(|| {
+                (
+                    glyph.key.glyph_index,
+                    LocalPoint::new(glyph.x - bounds.xmin, glyph.y - bounds.ymin.floor()) * s,
+                )
+            }));
+            glyphs.push(
+                LocalRect::new(
+                    [glyph.x, glyph.y].into(),
+                    [glyph.width as f32, glyph.height as f32].into(),
+                )
+                .scale(s, s),
+            );
+        }
+
+        let mut lines = vec![];
+        for line in layout.lines().into_iter().flatten() {
+            let mut bounds = LocalRect::zero();
+            for glyph in &glyphs[line.glyph_start..line.glyph_end] {
+                bounds = bounds.union(glyph);
+            }
+            lines.push(LineMetrics {
+                glyph_start: line.glyph_start,
+                glyph_end: line.glyph_end,
+                bounds,
+            });
+        }
+        TextLayout {
+            glyphs,
+            lines,
+            face: Some(self.clone()),
+            origins,
+        }
+    }
+}
+
+impl Font {
+    pub fn new(family: &str, size: u32, weight: FontWeight, style: FontStyle) -> Self {
+        Self {
+            family: Some(family.to_string()),
+            size,
+            weight,
+            style,
+        }
+    }
+
+    /// The registered face closest to the font, if its family has any.
+    /// Faces which can't be parsed are left to the built-in face.
+    fn face(&self) -> Option<Arc<FontFace>> {
+        let family = self.family.as_ref()?;
+        let registered = FONTS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|face| face.family == *family)
+            .max_by_key(|face| {
+                (face.weight == self.weight) as u32 * 2 + (face.style == self.style) as u32
+            })
+            .cloned()?;
+        registered.face()
+    }
+
+    /// Is the font bolder or more slanted than the face drawing it?
+    fn synthesized(&self, face: &FontFace) -> (bool, bool) {
+        (
+            self.weight == FontWeight::Bold && face.weight != FontWeight::Bold,
+            self.style == FontStyle::Italic && face.style != FontStyle::Italic,
+        )
+    }
+
+    /// Measures text during layout, keeping the layout for drawing.
+    pub(crate) fn measure(
+        &self,
+        args: &mut LayoutArgs,
+        text: &str,
+        max_width: Option<f32>,
+    ) -> LocalRect {
+        let layout = args.cx.text_layout(self, text, max_width);
+        match layout.face {
+            Some(_) => layout.bounds(),
+            // Without a face, measure as the caller asked.
+            None => (args.text_bounds)(text, self.size, max_width),
+        }
+    }
+
+    /// Glyph positions and line metrics together, laying out once, with
+    /// `builtin` if the font has no registered face. Without either, as in
+    /// a `Harness`, the layout is estimated.
+    pub(crate) fn layout(
+        &self,
+        builtin: Option<&Arc<FontFace>>,
+        text: &str,
+        max_width: Option<f32>,
+    ) -> TextLayout {
+        match self.face().or_else(|| builtin.cloned()) {
+            Some(face) => face.layout(text, self.size, max_width),
+            None => approximate_text_layout(text, self.size),
+        }
+    }
+
+    /// Like `Vger::text`, but laid out with `Context::text_layout`, so the
+    /// text isn't laid out again each frame.
+    pub(crate) fn draw(
+        &self,
+        cx: &mut Context,
+        painter: &mut Painter,
+        text: &str,
+        color: Color,
+        max_width: Option<f32>,
+    ) {
+        let layout = cx.text_layout(self, text, max_width);
+        let glyphs = 0..layout.glyphs.len();
+        self.draw_glyphs(cx, painter, &layout, glyphs, color);
+    }
+
+    /// Draws some of the glyphs of text laid out with the font.
+    pub(crate) fn draw_glyphs(
+        &self,
+        cx: &mut Context,
+        painter: &mut Painter,
+        layout: &Rc<TextLayout>,
+        glyphs: Range<usize>,
+        color: Color,
+    ) {
+        let Some(face) = &layout.face else {
+            return;
+        };
+        let (bold, italic) = self.synthesized(face);
+        let scale = cx.scale_factor();
+        let px = (self.size as f32 * scale).round() as u32;
+        let embolden = if bold {
+            (px as f32 * BOLD_WIDTH).round().max(1.0) as u32
+        } else {
+            0
+        };
+
+        let mut xform = painter.current_transform();
+        if italic {
+            let slant = euclid::Transform2D::<f32, LocalSpace, LocalSpace>::new(
+                1.0,
+                0.0,
+                ITALIC_SLANT,
+                1.0,
+                0.0,
+                0.0,
+            );
+            xform = slant.then(&xform);
+        }
+
+        // Glyphs land on whole pixels, a pixel wider when moved between
+        // them, and bold ones are wider still.
+        let margin = (embolden as f32 + 2.0) / scale;
+        let local = rect_bounds(layout.glyphs[glyphs.clone()].iter().copied());
+        let bounds = xform.outer_transformed_rect(&local.inflate(margin, margin));
+        let clip = cx.pending_clip(painter);
+        let marker = cx.gpu_markers.current();
+        cx.draw_glyph_run(
+            painter,
+            GlyphRun {
+                layout: layout.clone(),
+                range: glyphs,
+                px,
+                embolden,
+                snap: cx.text_pixel_snapping,
+                xform,
+                color,
+                bounds,
+                clip,
+                marker,
+            },
+        );
+    }
+}
+
+impl Context {
+    /// Measures text the way `text` lays it out, so views can size
+    /// themselves around text without drawing it. Returns the size of the
+    /// glyphs' bounds, and each line's glyphs and bounds in points with y
+    /// up from the first line's baseline.
+    ///
+    /// Until the first update, and in a `Harness`, the built-in face is
+    /// estimated as it is for layout there.
+    pub fn measure_text(
+        &self,
+        text: &str,
+        font: &Font,
+        max_width: Option<f32>,
+    ) -> (LocalSize, Vec<LineMetrics>) {
+        let layout = self.text_layout(font, text, max_width);
+        (layout.bounds().size, layout.lines.clone())
+    }
+
+    /// Takes vger's built-in face from `vger` the first time.
+    pub(crate) fn init_builtin_face(&mut self, vger: &Vger) {
+        if self.builtin_face.is_none() {
+            self.builtin_face = Some(Arc::new(FontFace::new(
+                vger.glyph_cache.font.clone(),
+                FontWeight::Regular,
+                FontStyle::Normal,
+            )));
+        }
+    }
+}
+
+/// The smallest rect around `rects`, or zero if there are none.
+pub(crate) fn rect_bounds(mut rects: impl Iterator<Item = LocalRect>) -> LocalRect {
+    let Some(first) = rects.next() else {
+        return LocalRect::zero();
+    };
+    let (min, max) = rects.fold((first.min(), first.max()), |(min, max), rect| {
+        (min.min(rect.min()), max.max(rect.max()))
+    });
+    LocalRect::new(min, (max - min).to_size())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const TEST_FONT: &[u8] = include_bytes!("../examples/fonts/AtkinsonHyperlegible-Regular.ttf");
+
+    #[test]
+    fn test_register_font() {
+        assert!(matches!(
+            register_font(
+                "Broken",
+                FontWeight::Regular,
+                FontStyle::Normal,
+                vec![0u8; 16]
+            ),
+            Err(FontError::Parse(_))
+        ));
+
+        // Broken faces are found out too, not just files which aren't fonts.
+        let mut data = b"OTTO".to_vec();
+        data.resize(64, 0);
+        assert!(matches!(
+            register_font("Truncated", FontWeight::Regular, FontStyle::Normal, data),
+            Err(FontError::Parse(_))
+        ));
+        let truncated = Font::new("Truncated", 20, FontWeight::Regular, FontStyle::Normal);
+        assert!(truncated.face().is_none());
+
+        register_font(
+            "Test Sans",
+            FontWeight::Regular,
+            FontStyle::Normal,
+            TEST_FONT,
+        )
+        .unwrap();
+        register_font("Test Sans", FontWeight::Bold, FontStyle::Normal, TEST_FONT).unwrap();
+        let parsed = || {
+            FONTS
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|face| face.family == "Test Sans" && face.face.get().is_some())
+                .count()
+        };
+        assert_eq!(parsed(), 0);
+        let font = Font::new("Test Sans", 20, FontWeight::Bold, FontStyle::Italic);
+        let face = font.face().unwrap();
+        assert_eq!(face.weight, FontWeight::Bold);
+        // Only the face used was parsed.
+        assert_eq!(parsed(), 1);
+        // Only the slant is synthesized.
+        assert_eq!(font.synthesized(&face), (false, true));
+
+        let layout = face.layout("ab\ncd", 20, None);
+        assert_eq!(layout.glyphs.len(), 5);
+        assert_eq!(layout.lines.len(), 2);
+        let (metrics, coverage) = face
+            .font
+            .rasterize_indexed(layout.origins[0].unwrap().0, 40.0);
+        assert!(metrics.width > 0 && coverage.iter().any(|&c| c > 0));
+
+        // Larger text is wider.
+        let width = |size| face.layout("hello", size, None).bounds().width();
+        assert!(width(40) > width(20) * 1.8);
+    }
+
+    #[test]
+    fn test_font_modifier() {
+        register_font(
+            "Test Serif",
+            FontWeight::Regular,
+            FontStyle::Normal,
+            TEST_FONT,
+        )
+        .unwrap();
+        let mut h = Harness::new(vstack((text("hello"), text("hello").font_size(40))).font(
+            "Test Serif",
+            20,
+            FontWeight::Regular,
+            FontStyle::Normal,
+        ));
+        let font = Font::new("Test Serif", 20, FontWeight::Regular, FontStyle::Normal);
+        let expected = font.face().unwrap().layout("hello", 20, None).bounds().size;
+
+        // Measured with the registered face, not the harness's estimate.
+        let texts = h.texts();
+        assert_eq!(texts[0].1.size, expected.into());
+        assert!(texts[1].1.width() > expected.width * 1.8);
+    }
+
+    #[test]
+    fn test_measure_text() {
+        register_font(
+            "Test Mono",
+            FontWeight::Regular,
+            FontStyle::Normal,
+            TEST_FONT,
+        )
+        .unwrap();
+        let mut h = Harness::new(text("hello"));
+        let laid_out = h.texts()[0].1.size;
+        let cx = h.context();
+
+        // The same size the text view was laid out with.
+        let (size, lines) = cx.measure_text("hello", &Font::default(), None);
+        assert_eq!(size, laid_out.into());
+        assert_eq!(lines.len(), 1);
+
+        let font = Font::new("Test Mono", 20, FontWeight::Regular, FontStyle::Normal);
+        let (size, lines) = cx.measure_text("hello", &font, None);
+        assert_eq!(
+            size,
+            font.face().unwrap().layout("hello", 20, None).bounds().size
+        );
+        assert_eq!(lines.len(), 1);
+
+        // Wrapping makes more lines, no wider than asked.
+        let (wrapped, lines) = cx.measure_text("hello hello hello", &font, Some(size.width * 1.5));
+        assert_eq!(lines.len(), 3);
+        assert!(wrapped.width <= size.width * 1.5);
+    }
+}
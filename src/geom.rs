@@ -0,0 +1,299 @@
+//! Geometry types.
+//!
+//! Views are laid out with the `Local*` and `World*` types, which are
+//! aliases for the renderer's euclid types. `Point`, `Vector`, `Size`,
+//! `Rect` and `Insets` are plain crate-owned equivalents for public APIs
+//! that shouldn't depend on the renderer, like modifiers. They convert to
+//! and from the `Local*` types, and from arrays and tuples, with
+//! `From`/`Into`.
+
+use crate::LayoutDirection;
+use euclid::{Point2D, Rect as ERect, Size2D, Transform2D, Vector2D};
+
+pub type LocalSpace = vger::defs::LocalSpace;
+pub type WorldSpace = vger::defs::WorldSpace;
+pub type LocalRect = ERect<f32, LocalSpace>;
+pub type LocalOffset = Vector2D<f32, LocalSpace>;
+pub type LocalSize = Size2D<f32, LocalSpace>;
+pub type LocalPoint = Point2D<f32, LocalSpace>;
+pub type WorldRect = ERect<f32, WorldSpace>;
+pub type WorldPoint = Point2D<f32, WorldSpace>;
+pub type LocalToWorld = Transform2D<f32, LocalSpace, WorldSpace>;
+pub type WorldToLocal = Transform2D<f32, WorldSpace, LocalSpace>;
+pub type LocalTransform = Transform2D<f32, LocalSpace, LocalSpace>;
+
+/// A point in a view's local coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A distance along each axis, like how far a view is offset.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Width and height of a view.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size {
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// An axis-aligned rectangle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl Rect {
+    pub const fn new(origin: Point, size: Size) -> Self {
+        Self { origin, size }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.size.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.size.height
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(
+            self.origin.x + self.size.width / 2.0,
+            self.origin.y + self.size.height / 2.0,
+        )
+    }
+
+    pub fn contains(&self, pt: Point) -> bool {
+        pt.x >= self.origin.x
+            && pt.y >= self.origin.y
+            && pt.x < self.origin.x + self.size.width
+            && pt.y < self.origin.y + self.size.height
+    }
+
+    /// Shrinks the rectangle by `insets`. The size won't go below zero.
+    pub fn inset(&self, insets: Insets) -> Rect {
+        Rect::new(
+            Point::new(self.origin.x + insets.left, self.origin.y + insets.bottom),
+            Size::new(
+                (self.size.width - insets.horizontal()).max(0.0),
+                (self.size.height - insets.vertical()).max(0.0),
+            ),
+        )
+    }
+}
+
+/// Distances from each edge of a rectangle, for example for padding.
+///
+/// Note that rui's y axis points up, so `bottom` is the edge at the
+/// smaller y coordinate.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Insets {
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Same inset on every edge.
+    pub const fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    /// `horizontal` on the left and right, `vertical` on the top and bottom.
+    pub const fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Total of the left and right insets.
+    pub fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    /// Total of the top and bottom insets.
+    pub fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+}
+
+/// Like `Insets`, with leading and trailing edges instead of left and
+/// right. Leading is the left edge when laying out left to right, and the
+/// right edge when right to left. See `LayoutDirection`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DirectionalInsets {
+    pub top: f32,
+    pub leading: f32,
+    pub bottom: f32,
+    pub trailing: f32,
+}
+
+impl DirectionalInsets {
+    pub const fn new(top: f32, leading: f32, bottom: f32, trailing: f32) -> Self {
+        Self {
+            top,
+            leading,
+            bottom,
+            trailing,
+        }
+    }
+
+    /// The insets for each edge, laying out in `direction`.
+    pub fn resolve(&self, direction: LayoutDirection) -> Insets {
+        let (left, right) = match direction {
+            LayoutDirection::LeftToRight => (self.leading, self.trailing),
+            LayoutDirection::RightToLeft => (self.trailing, self.leading),
+        };
+        Insets::new(self.top, right, self.bottom, left)
+    }
+}
+
+impl From<f32> for Insets {
+    fn from(inset: f32) -> Self {
+        Insets::uniform(inset)
+    }
+}
+
+impl From<[f32; 2]> for Point {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<[f32; 2]> for Vector {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Vector::new(x, y)
+    }
+}
+
+impl From<(f32, f32)> for Vector {
+    fn from((x, y): (f32, f32)) -> Self {
+        Vector::new(x, y)
+    }
+}
+
+impl From<[f32; 2]> for Size {
+    fn from([width, height]: [f32; 2]) -> Self {
+        Size::new(width, height)
+    }
+}
+
+impl From<(f32, f32)> for Size {
+    fn from((width, height): (f32, f32)) -> Self {
+        Size::new(width, height)
+    }
+}
+
+impl From<LocalPoint> for Point {
+    fn from(p: LocalPoint) -> Self {
+        Point::new(p.x, p.y)
+    }
+}
+
+impl From<Point> for LocalPoint {
+    fn from(p: Point) -> Self {
+        LocalPoint::new(p.x, p.y)
+    }
+}
+
+impl From<LocalOffset> for Vector {
+    fn from(v: LocalOffset) -> Self {
+        Vector::new(v.x, v.y)
+    }
+}
+
+impl From<Vector> for LocalOffset {
+    fn from(v: Vector) -> Self {
+        LocalOffset::new(v.x, v.y)
+    }
+}
+
+impl From<LocalSize> for Size {
+    fn from(s: LocalSize) -> Self {
+        Size::new(s.width, s.height)
+    }
+}
+
+impl From<Size> for LocalSize {
+    fn from(s: Size) -> Self {
+        LocalSize::new(s.width, s.height)
+    }
+}
+
+impl From<LocalRect> for Rect {
+    fn from(r: LocalRect) -> Self {
+        Rect::new(r.origin.into(), r.size.into())
+    }
+}
+
+impl From<Rect> for LocalRect {
+    fn from(r: Rect) -> Self {
+        LocalRect::new(r.origin.into(), r.size.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rect_conversion() {
+        let r = Rect::new(Point::new(1.0, 2.0), Size::new(3.0, 4.0));
+        let lr: LocalRect = r.into();
+        assert_eq!(lr, euclid::rect(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(Rect::from(lr), r);
+        assert_eq!(Point::from(lr.center()), r.center());
+
+        let offset: LocalOffset = Vector::from([1.0, 2.0]).into();
+        assert_eq!(offset, LocalOffset::new(1.0, 2.0));
+        assert_eq!(Size::from((3.0, 4.0)), Size::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn test_inset() {
+        let r = Rect::new(Point::new(0.0, 0.0), Size::new(10.0, 10.0));
+        let r = r.inset(Insets::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(r, Rect::new(Point::new(4.0, 3.0), Size::new(4.0, 6.0)));
+        assert_eq!(r.inset(Insets::uniform(5.0)).size, Size::new(0.0, 0.0));
+    }
+}
@@ -0,0 +1,712 @@
+//! Drawing text laid out by `Font` from a glyph atlas.
+//!
+//! Glyphs are rasterized with fontdue into an atlas the first time
+//! they're drawn at a size, and drawn as one quad each, between vger's
+//! passes like images. The atlas is made when text is first drawn, and
+//! cleared at the end of a frame which filled it.
+//!
+//! Glyphs sit on whole pixels so they aren't resampled, with the outline
+//! moved inside the bitmap to the nearest of `SUBPIXEL_POSITIONS`, which
+//! keeps letter spacing even at small sizes. With LCD antialiasing,
+//! upright glyphs are covered per color channel and blended one channel
+//! at a time, using the text color as the blend constant.
+
+use crate::*;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+
+/// Width and height of the atlas, in pixels.
+const ATLAS_SIZE: u32 = 2048;
+
+/// Pixels between glyphs in the atlas, so filtering doesn't pick up
+/// their neighbours.
+const ATLAS_PADDING: i32 = 2;
+
+/// Floats per glyph quad: rect, atlas rect, color, and transform.
+const GLYPH_FLOATS: usize = 20;
+
+/// Horizontal positions within a pixel glyphs are rasterized at.
+const SUBPIXEL_POSITIONS: u32 = 4;
+
+/// Order of the color subpixels across a display's pixels, for LCD
+/// antialiasing. See `WindowOptions::lcd_text`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubpixelOrder {
+    /// Red on the left, as on most displays.
+    Rgb,
+    Bgr,
+}
+
+/// Glyphs of laid out text to draw, collected while drawing.
+pub(crate) struct GlyphRun {
+    pub layout: Rc<TextLayout>,
+
+    /// Which of the layout's glyphs to draw.
+    pub range: Range<usize>,
+
+    /// Size the glyphs are rasterized at, in pixels.
+    pub px: u32,
+
+    /// Pixels to thicken glyphs by, for synthesized bold.
+    pub embolden: u32,
+
+    /// Put glyphs on whole pixels? See `Context::set_text_pixel_snapping`.
+    pub snap: bool,
+
+    /// From the layout's coordinates to those of the layer it's drawn in.
+    pub xform: LocalToWorld,
+
+    pub color: Color,
+
+    /// Bounds of the glyphs, in the same coordinates as `clip`.
+    pub bounds: WorldRect,
+
+    /// Innermost clip the text was drawn in, in the coordinates of the
+    /// layer.
+    pub clip: Option<WorldRect>,
+
+    /// Debug group the text was drawn in.
+    pub marker: Option<String>,
+}
+
+/// Where a glyph is in the atlas.
+#[derive(Clone, Copy)]
+struct AtlasGlyph {
+    rect: rect_packer::Rect,
+
+    /// Offset of the bottom left pixel from the glyph's origin.
+    xmin: i32,
+    ymin: i32,
+}
+
+/// Face, glyph index, size in pixels, emboldening, subpixel position, and
+/// whether it's covered per color channel.
+type GlyphKey = (u64, u16, u32, u32, u32, bool);
+
+const GLYPH_SHADER: &str = r#"
+struct GlyphUniforms {
+    screen: vec2<f32>,
+    height: f32,
+    scale: f32,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: GlyphUniforms;
+@group(0) @binding(1) var atlas_texture: texture_2d<f32>;
+@group(0) @binding(2) var atlas_sampler: sampler;
+
+struct GlyphInstance {
+    @location(0) rect: vec4<f32>,
+    @location(1) uv: vec4<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) linear: vec4<f32>,
+    @location(4) offset: vec4<f32>,
+};
+
+struct GlyphVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn glyph_vs(@builtin(vertex_index) index: u32, glyph: GlyphInstance) -> GlyphVertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let p = glyph.rect.xy + corner * glyph.rect.zw;
+    let world = vec2<f32>(
+        glyph.linear.x * p.x + glyph.linear.z * p.y + glyph.offset.x,
+        glyph.linear.y * p.x + glyph.linear.w * p.y + glyph.offset.y
+    );
+    // World space is y-up in points, the target is y-down in pixels.
+    let px = vec2<f32>(world.x, uniforms.height - world.y) * uniforms.scale;
+    var out: GlyphVertexOutput;
+    out.position = vec4<f32>(
+        px.x / uniforms.screen.x * 2.0 - 1.0,
+        1.0 - px.y / uniforms.screen.y * 2.0,
+        0.0,
+        1.0
+    );
+    // Atlas rows go down from the top of the glyph.
+    out.uv = glyph.uv.xy + vec2<f32>(corner.x, 1.0 - corner.y) * glyph.uv.zw;
+    out.color = glyph.color;
+    return out;
+}
+
+@fragment
+fn glyph_fs(in: GlyphVertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas_texture, atlas_sampler, in.uv).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
+
+// Blended with the text color as the constant, so each channel mixes
+// with the target by its own coverage.
+@fragment
+fn glyph_lcd_fs(in: GlyphVertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas_texture, atlas_sampler, in.uv).rgb * in.color.a;
+    return vec4<f32>(coverage, max(coverage.r, max(coverage.g, coverage.b)));
+}
+"#;
+
+/// Renders text drawn with `Font`. Created lazily the first time text is
+/// drawn.
+pub(crate) struct GlyphRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+
+    /// Subpixel order of the display, if text is LCD antialiased, with
+    /// its pipeline. The atlas then has a byte per channel.
+    lcd: Option<(SubpixelOrder, wgpu::RenderPipeline)>,
+
+    atlas: wgpu::Texture,
+    atlas_view: wgpu::TextureView,
+    packer: rect_packer::Packer,
+
+    /// Glyphs in the atlas, or `None` for those with no pixels.
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+
+    /// Did a glyph not fit in the atlas this frame?
+    full: bool,
+}
+
+fn atlas_packer() -> rect_packer::Packer {
+    rect_packer::Packer::new(rect_packer::Config {
+        width: ATLAS_SIZE as i32,
+        height: ATLAS_SIZE as i32,
+        border_padding: ATLAS_PADDING,
+        rectangle_padding: ATLAS_PADDING,
+    })
+}
+
+/// Can text drawn into `format` be blended per color channel?
+fn lcd_format(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+    matches!(
+        format,
+        Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb | Rgb10a2Unorm | Rgba16Float
+    )
+}
+
+impl GlyphRenderer {
+    /// Makes a renderer drawing into `format`, LCD antialiasing text for
+    /// a display with subpixels in `lcd` order if the format has the
+    /// channels for it.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        lcd: Option<SubpixelOrder>,
+    ) -> Self {
+        let lcd = lcd.filter(|_| lcd_format(format));
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rui glyph bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rui glyph pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rui glyph shader"),
+            source: wgpu::ShaderSource::Wgsl(GLYPH_SHADER.into()),
+        });
+
+        let attributes = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+        ];
+        let make_pipeline = |entry_point, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("rui glyph pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "glyph_vs",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: (GLYPH_FLOATS * 4) as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &attributes,
+                    }],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            })
+        };
+        let pipeline = make_pipeline("glyph_fs", wgpu::BlendState::ALPHA_BLENDING);
+        let lcd = lcd.map(|order| {
+            let blend = wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Constant,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            };
+            (order, make_pipeline("glyph_lcd_fs", blend))
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rui glyph sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let atlas = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rui glyph atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: if lcd.is_some() {
+                wgpu::TextureFormat::Rgba8Unorm
+            } else {
+                wgpu::TextureFormat::R8Unorm
+            },
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            bind_group_layout,
+            pipeline,
+            sampler,
+            lcd,
+            atlas,
+            atlas_view,
+            packer: atlas_packer(),
+            glyphs: HashMap::new(),
+            full: false,
+        }
+    }
+
+    /// Where glyph `index` of `face` is in the atlas, rasterizing it
+    /// there the first time it's drawn at `px` pixels, `subpixel`
+    /// `SUBPIXEL_POSITIONS` to the right of a whole pixel, and per color
+    /// channel if `lcd`.
+    #[allow(clippy::too_many_arguments)]
+    fn glyph(
+        &mut self,
+        queue: &wgpu::Queue,
+        face: &FontFace,
+        index: u16,
+        px: u32,
+        embolden: u32,
+        subpixel: u32,
+        lcd: bool,
+    ) -> Option<AtlasGlyph> {
+        let key = (face.id, index, px, embolden, subpixel, lcd);
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return *glyph;
+        }
+
+        let (metrics, mut coverage) = if lcd {
+            face.font.rasterize_indexed_subpixel(index, px as f32)
+        } else {
+            face.font.rasterize_indexed(index, px as f32)
+        };
+        if metrics.width == 0 || metrics.height == 0 {
+            self.glyphs.insert(key, None);
+            return None;
+        }
+        let channels = if lcd { 3 } else { 1 };
+        let (mut width, height) = (metrics.width, metrics.height);
+        if embolden > 0 {
+            let by = embolden as usize * channels;
+            coverage = embolden_coverage(&coverage, width * channels, by);
+            width += embolden as usize;
+        }
+        if subpixel > 0 {
+            let offset = subpixel as f32 / SUBPIXEL_POSITIONS as f32;
+            coverage = shift_coverage(&coverage, width, channels, offset);
+            width += 1;
+        }
+        let (texel, coverage) = match self.lcd {
+            Some((order, _)) => (4, atlas_texels(&coverage, channels, order)),
+            None => (1, coverage),
+        };
+
+        // Left out until the atlas is cleared, if there's no room.
+        let Some(rect) = self.packer.pack(width as i32, height as i32, false) else {
+            self.full = true;
+            return None;
+        };
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.atlas,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: rect.x as u32,
+                    y: rect.y as u32,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &coverage,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some((width * texel) as u32),
+                rows_per_image: Some(height as u32),
+            },
+            wgpu::Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+        let glyph = Some(AtlasGlyph {
+            rect,
+            xmin: metrics.xmin,
+            ymin: metrics.ymin,
+        });
+        self.glyphs.insert(key, glyph);
+        glyph
+    }
+
+    /// Clears the atlas if it filled up during the frame. Returns whether
+    /// it did, in which case glyphs were left out and the frame should be
+    /// drawn again.
+    pub fn end_frame(&mut self) -> bool {
+        if !std::mem::take(&mut self.full) {
+            return false;
+        }
+        self.packer = atlas_packer();
+        self.glyphs.clear();
+        true
+    }
+
+    /// Draws the runs over `target`.
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        runs: &[GlyphRun],
+        size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        let window = WorldRect::new(WorldPoint::zero(), size);
+        let atlas = 1.0 / ATLAS_SIZE as f32;
+        let mut data: Vec<f32> = vec![];
+        let mut draws = vec![];
+        for run in runs {
+            let Some(face) = &run.layout.face else {
+                continue;
+            };
+            let clip = run.clip.unwrap_or(window);
+            let start = data.len() / GLYPH_FLOATS;
+            let m = &run.xform;
+            // Channels only line up with the display's subpixels upright.
+            let upright = m.m11 == 1.0 && m.m22 == 1.0 && m.m12 == 0.0 && m.m21 == 0.0;
+            let lcd = self.lcd.is_some() && upright;
+            for &(index, origin) in run.layout.origins[run.range.clone()].iter().flatten() {
+                let at = m.transform_point(origin) * scale;
+                let (corner, subpixel) = place_glyph([at.x, at.y], run.snap);
+                let Some(glyph) =
+                    self.glyph(queue, face, index, run.px, run.embolden, subpixel, lcd)
+                else {
+                    continue;
+                };
+                let offset = LocalOffset::new(
+                    corner[0] - at.x + glyph.xmin as f32,
+                    corner[1] - at.y + glyph.ymin as f32,
+                );
+                let rect = LocalRect::new(
+                    origin + offset / scale,
+                    LocalSize::new(glyph.rect.width as f32, glyph.rect.height as f32) / scale,
+                );
+                if !m.outer_transformed_rect(&rect).intersects(&clip) {
+                    continue;
+                }
+                let c = run.color;
+                data.extend([
+                    rect.origin.x,
+                    rect.origin.y,
+                    rect.size.width,
+                    rect.size.height,
+                    glyph.rect.x as f32 * atlas,
+                    glyph.rect.y as f32 * atlas,
+                    glyph.rect.width as f32 * atlas,
+                    glyph.rect.height as f32 * atlas,
+                    c.r,
+                    c.g,
+                    c.b,
+                    c.a,
+                    m.m11,
+                    m.m12,
+                    m.m21,
+                    m.m22,
+                    m.m31,
+                    m.m32,
+                    0.0,
+                    0.0,
+                ]);
+            }
+            let end = data.len() / GLYPH_FLOATS;
+            if end > start {
+                draws.push((start as u32..end as u32, clip, &run.marker, lcd, run.color));
+            }
+        }
+        if draws.is_empty() {
+            return;
+        }
+
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let instances = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("rui glyph instances"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let uniforms = [size.width * scale, size.height * scale, size.height, scale];
+        let bytes: Vec<u8> = uniforms.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("rui glyph uniforms"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rui glyph bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui glyph encoder"),
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rui glyph pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_bind_group(0, &bind_group, &[]);
+            rpass.set_vertex_buffer(0, instances.slice(..));
+            for (range, clip, marker, lcd, c) in draws {
+                let Some((x, y, w, h)) = scissor_px(&clip, size, scale) else {
+                    continue;
+                };
+                if let Some(marker) = marker {
+                    rpass.push_debug_group(marker);
+                }
+                match &self.lcd {
+                    Some((_, pipeline)) if lcd => {
+                        rpass.set_pipeline(pipeline);
+                        rpass.set_blend_constant(wgpu::Color {
+                            r: c.r as f64,
+                            g: c.g as f64,
+                            b: c.b as f64,
+                            a: c.a as f64,
+                        });
+                    }
+                    _ => rpass.set_pipeline(&self.pipeline),
+                }
+                rpass.set_scissor_rect(x, y, w, h);
+                rpass.draw(0..4, range);
+                if marker.is_some() {
+                    rpass.pop_debug_group();
+                }
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Thickens glyph coverage `width` pixels wide by `by` pixels to the
+/// right, for synthesized bold, so it's still one quad.
+fn embolden_coverage(coverage: &[u8], width: usize, by: usize) -> Vec<u8> {
+    let mut bold = vec![];
+    for row in coverage.chunks(width) {
+        for x in 0..width + by {
+            let last = x.min(width - 1);
+            bold.extend(row[x.saturating_sub(by)..=last].iter().max());
+        }
+    }
+    bold
+}
+
+/// Where to put the bottom left corner of a glyph's bitmap, and which of
+/// the `SUBPIXEL_POSITIONS` to rasterize it at, for a glyph whose origin
+/// is `at` in device pixels. Snapped glyphs go on whole pixels, rounding
+/// to the row and to the nearest subpixel position across. Otherwise the
+/// bitmap is put exactly where the glyph goes, and is resampled.
+fn place_glyph(at: [f32; 2], snap: bool) -> ([f32; 2], u32) {
+    if !snap {
+        return (at, 0);
+    }
+    let mut x = at[0].floor();
+    let mut subpixel = ((at[0] - x) * SUBPIXEL_POSITIONS as f32).round() as u32;
+    if subpixel == SUBPIXEL_POSITIONS {
+        x += 1.0;
+        subpixel = 0;
+    }
+    ([x, at[1].round()], subpixel)
+}
+
+/// Moves glyph coverage `width` pixels wide of `channels` bytes each
+/// `offset` pixels to the right, which makes it a pixel wider.
+fn shift_coverage(coverage: &[u8], width: usize, channels: usize, offset: f32) -> Vec<u8> {
+    let row = width * channels;
+    let shift = offset * channels as f32;
+    let whole = shift.floor();
+    let fract = shift - whole;
+    let mut shifted = vec![];
+    for samples in coverage.chunks(row) {
+        let sample = |i: isize| {
+            if i < 0 || i as usize >= row {
+                0.0
+            } else {
+                samples[i as usize] as f32
+            }
+        };
+        for i in 0..(row + channels) as isize {
+            let i = i - whole as isize;
+            let value = sample(i) * (1.0 - fract) + sample(i - 1) * fract;
+            shifted.push(value.round() as u8);
+        }
+    }
+    shifted
+}
+
+/// RGBA texels for the atlas from coverage of `channels` bytes per pixel:
+/// gray in every channel, or color channels in the display's `order`.
+fn atlas_texels(coverage: &[u8], channels: usize, order: SubpixelOrder) -> Vec<u8> {
+    let mut texels = Vec::with_capacity(coverage.len() / channels * 4);
+    for pixel in coverage.chunks(channels) {
+        let (r, g, b) = match pixel {
+            [c] => (*c, *c, *c),
+            [r, g, b] => (*r, *g, *b),
+            _ => unreachable!(),
+        };
+        let (r, b) = match order {
+            SubpixelOrder::Rgb => (r, b),
+            SubpixelOrder::Bgr => (b, r),
+        };
+        texels.extend([r, g, b, r.max(g).max(b)]);
+    }
+    texels
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_embolden_coverage() {
+        // Two rows, three pixels wide, thickened by two.
+        let coverage = [0, 255, 0, 10, 0, 20];
+        assert_eq!(
+            embolden_coverage(&coverage, 3, 2),
+            vec![0, 255, 255, 255, 0, 10, 10, 20, 20, 20]
+        );
+    }
+
+    #[test]
+    fn test_place_glyph() {
+        assert_eq!(place_glyph([10.3, 4.4], true), ([10.0, 4.0], 1));
+        assert_eq!(place_glyph([10.6, 4.6], true), ([10.0, 5.0], 2));
+        // Close enough to the next pixel to go there.
+        assert_eq!(place_glyph([10.9, 4.0], true), ([11.0, 4.0], 0));
+        assert_eq!(place_glyph([10.3, 4.4], false), ([10.3, 4.4], 0));
+    }
+
+    #[test]
+    fn test_shift_coverage() {
+        // Half a pixel splits each pixel's coverage with the next.
+        assert_eq!(shift_coverage(&[200, 100], 2, 1, 0.5), vec![100, 150, 50]);
+        // A third of an LCD pixel is one channel over.
+        assert_eq!(
+            shift_coverage(&[30, 60, 90], 1, 3, 1.0 / 3.0),
+            vec![0, 30, 60, 90, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_atlas_texels() {
+        assert_eq!(
+            atlas_texels(&[10, 20], 1, SubpixelOrder::Rgb),
+            vec![10, 10, 10, 10, 20, 20, 20, 20]
+        );
+        assert_eq!(
+            atlas_texels(&[10, 20, 30], 3, SubpixelOrder::Bgr),
+            vec![30, 20, 10, 30]
+        );
+    }
+}
@@ -0,0 +1,601 @@
+use crate::*;
+use std::time::Duration;
+
+/// Runs a view without a window or GPU, for tests.
+///
+/// Text is measured with approximate metrics, so positions won't match
+/// what's drawn on screen exactly, but layout and event handling are the
+/// same as in a real app.
+pub struct Harness<V> {
+    view: V,
+    cx: Context,
+    window_size: LocalSize,
+    damage: Option<WorldRect>,
+
+    /// Where the mouse last was, for `hover`.
+    mouse: LocalPoint,
+
+    /// Fingers down, for `touch_begin` and friends.
+    touches: TouchSlots,
+}
+
+/// Approximate text metrics: fixed width glyphs, no wrapping.
+pub(crate) fn approximate_text_bounds(text: &str, size: u32, _max_width: Option<f32>) -> LocalRect {
+    let size = size as f32;
+    let width = text.chars().count() as f32 * size * 0.6;
+    LocalRect::new(
+        LocalPoint::new(0.0, -0.2 * size),
+        LocalSize::new(width, size),
+    )
+}
+
+/// Approximate glyph positions, as for `approximate_text_bounds`.
+pub(crate) fn approximate_text_layout(text: &str, size: u32) -> TextLayout {
+    let bounds = approximate_text_bounds(text, size, None);
+    let count = text.chars().count();
+    // Ends on the bounds exactly, so the glyphs measure the same.
+    let x = |i: usize| {
+        if i == count {
+            bounds.width()
+        } else {
+            bounds.width() * i as f32 / count as f32
+        }
+    };
+    TextLayout {
+        glyphs: (0..count)
+            .map(|i| {
+                LocalRect::new(
+                    [x(i), bounds.min_y()].into(),
+                    [x(i + 1) - x(i), bounds.height()].into(),
+                )
+            })
+            .collect(),
+        lines: vec![LineMetrics {
+            glyph_start: 0,
+            glyph_end: count,
+            bounds,
+        }],
+        face: None,
+        origins: vec![],
+    }
+}
+
+impl<V: View> Harness<V> {
+    /// Creates a harness with an 800x600 window.
+    pub fn new(view: V) -> Self {
+        Self::with_size(view, Size::new(800.0, 600.0))
+    }
+
+    pub fn with_size(view: V, window_size: impl Into<Size>) -> Self {
+        let mut harness = Self {
+            view,
+            cx: Context::new(),
+            window_size: window_size.into().into(),
+            damage: None,
+            mouse: LocalPoint::zero(),
+            touches: TouchSlots::default(),
+        };
+        harness.cx.keep_view_list = true;
+        harness.update();
+        harness
+    }
+
+    /// The harness's `Context`, for inspecting or modifying state.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.cx
+    }
+
+    /// Runs animations and updates layout, as the event loop does after
+    /// each batch of events.
+    pub fn update(&mut self) {
+        self.cx.update_with_text_bounds(
+            &self.view,
+            &mut approximate_text_bounds,
+            self.window_size.cast_unit(),
+        );
+        self.cx.enable_dirty = false;
+        self.cx
+            .layout_root(&self.view, self.window_size, &mut approximate_text_bounds);
+        self.cx.enable_dirty = true;
+
+        // Find focusable views for Tab, as drawing would.
+        self.cx.focus_chain.clear();
+        self.cx.find_texts(&self.view);
+
+        self.damage = self.cx.take_damage();
+    }
+
+    /// Runs an update for each frame in `duration`. Animations step a
+    /// sixtieth of a second per update, as if running at 60fps.
+    pub fn advance(&mut self, duration: Duration) {
+        let frames = (duration.as_secs_f64() * 60.0).ceil() as usize;
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    /// Resizes the window, then updates.
+    pub fn resize(&mut self, window_size: impl Into<Size>) {
+        self.window_size = window_size.into().into();
+        self.update();
+    }
+
+    /// Changes the display's pixels per point, as when the window moves
+    /// to another monitor, then updates. The window's size in points
+    /// stays the same.
+    pub fn set_scale_factor(&mut self, scale: f32) {
+        self.cx.set_scale_factor(scale);
+        self.update();
+    }
+
+    /// The values of each `state` holding an `S`, in the order the views
+    /// appear.
+    pub fn states<S: Clone + 'static>(&self) -> Vec<S> {
+        let mut states: Vec<(&IdPath, S)> = self
+            .cx
+            .view_ids
+            .iter()
+            .filter_map(|(path, id)| {
+                let holder = self.cx.state_map.get(id)?;
+                let state = holder.state.downcast_ref::<S>()?;
+                Some((path, state.clone()))
+            })
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(b.0));
+        states.into_iter().map(|(_, state)| state).collect()
+    }
+
+    /// The part of the window the last update would redraw, in window
+    /// coordinates, or `None` if it would redraw everything.
+    pub fn damage(&self) -> Option<Rect> {
+        self.damage.map(|rect| Rect::from(rect.cast_unit()))
+    }
+
+    /// Makes the next update lay out and rebuild everything.
+    pub fn invalidate(&mut self) {
+        self.cx.deps.clear();
+        self.cx.dirty = true;
+        self.cx.full_redraw = true;
+    }
+
+    /// Sends an event, then updates.
+    pub fn event(&mut self, event: Event) {
+        self.cx.process(&self.view, &event);
+        self.update();
+    }
+
+    /// All text on screen with its rect in window coordinates, in drawing order.
+    pub fn texts(&mut self) -> Vec<(String, Rect)> {
+        self.cx
+            .find_texts(&self.view)
+            .into_iter()
+            .map(|(_, text, rect)| (text, Rect::from(rect.cast_unit())))
+            .collect()
+    }
+
+    /// Returns the rect of the first text view showing exactly `text`.
+    pub fn find_text(&mut self, text: &str) -> Option<Rect> {
+        self.texts()
+            .into_iter()
+            .find(|(t, _)| t == text)
+            .map(|(_, rect)| rect)
+    }
+
+    /// Is there a text view showing exactly `text`?
+    pub fn has_text(&mut self, text: &str) -> bool {
+        self.find_text(text).is_some()
+    }
+
+    /// Presses and releases the left mouse button at a point in window
+    /// coordinates.
+    pub fn click_at(&mut self, position: Point) {
+        let position = position.into();
+        self.mouse = position;
+        self.cx.mouse_button = Some(MouseButton::Left);
+        self.event(Event::TouchBegin { id: 0, position });
+        self.cx.mouse_button = None;
+        self.event(Event::TouchEnd { id: 0, position });
+    }
+
+    /// Presses the left mouse button at `from`, moves to `to` in a few
+    /// steps, and releases it, in window coordinates.
+    pub fn drag(&mut self, from: Point, to: Point) {
+        const STEPS: usize = 4;
+        let from: LocalPoint = from.into();
+        let to: LocalPoint = to.into();
+        let step = (to - from) / STEPS as f32;
+
+        self.cx.mouse_button = Some(MouseButton::Left);
+        self.event(Event::TouchBegin {
+            id: 0,
+            position: from,
+        });
+        for i in 1..=STEPS {
+            self.event(Event::TouchMove {
+                id: 0,
+                position: from + step * i as f32,
+                delta: step,
+            });
+        }
+        self.cx.mouse_button = None;
+        self.event(Event::TouchEnd {
+            id: 0,
+            position: to,
+        });
+        self.mouse = to;
+    }
+
+    /// Puts a finger down at a point in window coordinates. `finger` tells
+    /// fingers apart, like a platform's touch id. The first finger down
+    /// acts like the mouse.
+    pub fn touch_begin(&mut self, finger: u64, position: Point) {
+        let position = position.into();
+        if let Some(id) = self.touches.begin(finger, position) {
+            self.event(Event::TouchBegin { id, position });
+        }
+    }
+
+    /// Moves a finger which is down to a point in window coordinates.
+    pub fn touch_move(&mut self, finger: u64, position: Point) {
+        let position = position.into();
+        if let Some((id, delta)) = self.touches.move_to(finger, position) {
+            self.event(Event::TouchMove {
+                id,
+                position,
+                delta,
+            });
+        }
+    }
+
+    /// Sets how hard clicks, drags and fingers press from now on, from 0
+    /// to 1, like a pen on a tablet.
+    pub fn set_pressure(&mut self, pressure: f32) {
+        self.cx.pressures = [pressure; 16];
+    }
+
+    /// Lifts a finger.
+    pub fn touch_end(&mut self, finger: u64) {
+        if let Some((id, position)) = self.touches.end(finger) {
+            self.event(Event::TouchEnd { id, position });
+        }
+    }
+
+    /// Moves the mouse to a point in window coordinates, without pressing
+    /// any buttons.
+    pub fn hover(&mut self, position: Point) {
+        let position = position.into();
+        let delta = position - self.mouse;
+        self.mouse = position;
+        self.event(Event::TouchMove {
+            id: 0,
+            position,
+            delta,
+        });
+    }
+
+    /// Drops files from another app at a point in window coordinates.
+    pub fn drop_files(&mut self, position: Point, paths: Vec<std::path::PathBuf>) {
+        self.event(Event::DropFiles {
+            position: position.into(),
+            paths,
+        });
+    }
+
+    /// Turns the mouse wheel over a point in window coordinates. `delta`
+    /// is how far content should move, as in `Event::Scroll`.
+    pub fn scroll(&mut self, position: Point, delta: impl Into<LocalOffset>) {
+        self.event(Event::Scroll {
+            position: position.into(),
+            delta: delta.into(),
+        });
+    }
+
+    /// Clicks the center of the text view showing `text`.
+    ///
+    /// Panics if there isn't one.
+    pub fn click(&mut self, text: &str) {
+        match self.find_text(text) {
+            Some(rect) => self.click_at(rect.center()),
+            None => panic!("no text {:?} to click, found {:?}", text, self.text_list()),
+        }
+    }
+
+    /// Sends a key going down and then up, with the modifiers in
+    /// `Context::key_mods`.
+    pub fn key(&mut self, key: Key) {
+        self.hold_key(key, 0);
+    }
+
+    /// Sends a key going down, repeating `repeats` times as if held, and
+    /// going up.
+    pub fn hold_key(&mut self, key: Key, repeats: u32) {
+        let mods = self.cx.key_mods;
+        for repeat in 0..=repeats {
+            self.event(Event::Key(KeyEvent::press(key).mods(mods).repeat(repeat)));
+        }
+        self.event(Event::Key(KeyEvent::release(key).mods(mods)));
+    }
+
+    /// Sends a key press with modifier keys held.
+    pub fn key_with_mods(&mut self, key: Key, mods: KeyboardModifiers) {
+        let previous = self.cx.key_mods;
+        self.cx.key_mods = mods;
+        self.key(key);
+        self.cx.key_mods = previous;
+    }
+
+    /// Sends a key press for each character of `text`, as if typed.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key(match c {
+                ' ' => Key::Space,
+                '\n' => Key::Enter,
+                '\t' => Key::Tab,
+                c => Key::Character(c),
+            });
+        }
+    }
+
+    /// Panics unless a text view shows exactly `text`.
+    pub fn expect_text(&mut self, text: &str) {
+        if !self.has_text(text) {
+            panic!("expected text {:?}, found {:?}", text, self.text_list());
+        }
+    }
+
+    /// Panics if a text view shows exactly `text`.
+    pub fn expect_no_text(&mut self, text: &str) {
+        if self.has_text(text) {
+            panic!("didn't expect text {:?}", text);
+        }
+    }
+
+    /// The views, with the strings text views show. See
+    /// `Context::view_tree`.
+    pub fn view_tree(&mut self) -> Vec<ViewNode> {
+        let texts = self
+            .cx
+            .find_texts(&self.view)
+            .into_iter()
+            .map(|(path, text, _)| (path, text))
+            .collect();
+        self.cx.view_tree_with_texts(&texts)
+    }
+
+    fn text_list(&mut self) -> Vec<String> {
+        self.texts().into_iter().map(|(text, _)| text).collect()
+    }
+}
+
+/// Describes an interaction flow as a series of steps run on a `Harness`.
+/// Evaluates to the harness, so more checks can follow.
+///
+/// ```
+/// # use rui::*;
+/// let counter = state(
+///     || 0,
+///     |count, cx| {
+///         vstack((
+///             format!("count: {}", cx[count]),
+///             button("increment", move |cx| cx[count] += 1),
+///         ))
+///     },
+/// );
+///
+/// interaction_test! { Harness::new(counter);
+///     expect_text "count: 0";
+///     click "increment";
+///     click "increment";
+///     expect_text "count: 2";
+/// };
+/// ```
+///
+/// Steps are `click "text"`, `type "text"`, `key Key` (for example
+/// `key Tab` to move the focus), `expect_text "text"`,
+/// `expect_no_text "text"` and `update`.
+#[macro_export]
+macro_rules! interaction_test {
+    ($harness:expr; $($steps:tt)*) => {{
+        let mut harness = $harness;
+        $crate::interaction_test!(@steps harness; $($steps)*);
+        harness
+    }};
+    (@steps $h:ident; ) => {};
+    (@steps $h:ident; click $text:expr; $($rest:tt)*) => {
+        $h.click($text);
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+    (@steps $h:ident; type $text:expr; $($rest:tt)*) => {
+        $h.type_text($text);
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+    (@steps $h:ident; key $key:ident; $($rest:tt)*) => {
+        $h.key($crate::Key::$key);
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+    (@steps $h:ident; expect_text $text:expr; $($rest:tt)*) => {
+        $h.expect_text($text);
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+    (@steps $h:ident; expect_no_text $text:expr; $($rest:tt)*) => {
+        $h.expect_no_text($text);
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+    (@steps $h:ident; update; $($rest:tt)*) => {
+        $h.update();
+        $crate::interaction_test!(@steps $h; $($rest)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_find_text() {
+        let mut h = Harness::new(vstack((text("top"), text("bottom"))));
+        let top = h.find_text("top").unwrap();
+        let bottom = h.find_text("bottom").unwrap();
+
+        // y is up, and the stack is centered in the window.
+        assert!(top.origin.y > bottom.origin.y);
+        assert!(top.origin.x > 0.0);
+        assert!(h.find_text("middle").is_none());
+    }
+
+    #[test]
+    fn test_typing() {
+        let editor = state(String::new, |s, cx| {
+            vstack((
+                text_editor(s).size([200.0, 30.0]),
+                text(&cx[s]).padding(Auto),
+            ))
+        });
+
+        interaction_test! { Harness::new(editor);
+            key Tab;
+            type "hi there";
+            expect_text "hi there";
+            key Backspace;
+            expect_text "hi ther";
+        };
+    }
+
+    #[test]
+    fn test_damage() {
+        let swatch = state(
+            || false,
+            |on, cx| {
+                rectangle()
+                    .color(if cx[on] {
+                        RED_HIGHLIGHT
+                    } else {
+                        CONTROL_BACKGROUND
+                    })
+                    .tap(move |cx| cx[on] = !cx[on])
+                    .size([100.0, 50.0])
+            },
+        );
+        let bar = state(
+            || false,
+            |wide, cx| {
+                rectangle()
+                    .tap(move |cx| cx[wide] = true)
+                    .size([if cx[wide] { 100.0 } else { 50.0 }, 50.0])
+            },
+        );
+        let view = vstack((swatch, bar));
+        let mut h = Harness::with_size(view, Size::new(200.0, 200.0));
+
+        // Only the swatch changed color.
+        h.click_at(Point::new(100.0, 125.0));
+        let damage = h.damage().unwrap();
+        assert_eq!(
+            damage,
+            Rect::new(Point::new(50.0, 100.0), Size::new(100.0, 50.0))
+        );
+
+        // Nothing changed.
+        h.update();
+        assert_eq!(h.damage(), Some(Rect::default()));
+
+        // The bar grew, which changes layout.
+        h.click_at(Point::new(100.0, 75.0));
+        assert_eq!(h.damage(), None);
+    }
+
+    #[test]
+    fn test_key_mods() {
+        let editor = state(String::new, |s, cx| {
+            vstack((
+                text_editor(s).size([200.0, 30.0]),
+                text(&cx[s]).padding(Auto),
+            ))
+        });
+        let mut h = Harness::new(editor);
+        h.key(Key::Tab);
+        h.type_text("hello");
+
+        // Shortcuts aren't typed.
+        let control = KeyboardModifiers {
+            control: true,
+            ..Default::default()
+        };
+        h.key_with_mods(Key::Character('z'), control);
+        h.key(Key::Character('!'));
+        h.expect_text("hello!");
+        assert!(!h.context().key_mods.control);
+        assert_eq!(h.states::<String>(), vec!["hello!".to_string()]);
+    }
+
+    #[test]
+    fn test_advance() {
+        let view = state(
+            || 0.0f32,
+            |x, cx| {
+                vstack((
+                    text(&format!("{}", cx[x].round())),
+                    button("go", move |cx| {
+                        cx.animate_state(x, 100.0, Curve::Linear, 1.0)
+                    }),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("go");
+        h.advance(Duration::from_millis(500));
+        let x = h.states::<f32>()[0];
+        assert!(x > 40.0 && x < 60.0);
+
+        h.advance(Duration::from_secs(1));
+        h.expect_text("100");
+        assert!(!h.context().is_animating());
+    }
+
+    #[test]
+    fn test_resize_and_hover() {
+        let view = state(
+            || false,
+            |hovered, cx| {
+                vstack((
+                    text(if cx[hovered] { "over" } else { "away" }),
+                    rectangle()
+                        .size([50.0, 50.0])
+                        .hover(move |cx, inside| cx[hovered] = inside),
+                ))
+            },
+        );
+        let mut h = Harness::with_size(view, Size::new(200.0, 200.0));
+        let before = h.find_text("away").unwrap();
+        h.resize(Size::new(400.0, 400.0));
+        let after = h.find_text("away").unwrap();
+        assert!(after.center().x > before.center().x);
+
+        let tree = h.view_tree();
+        let square = tree
+            .iter()
+            .find_map(|root| root.find(&|node| node.type_name == "Rectangle"))
+            .unwrap();
+        let [x, y, w, height] = square.frame.unwrap();
+        h.hover(Point::new(x + w / 2.0, y + height / 2.0));
+        h.expect_text("over");
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        let mut h = Harness::new(vstack((text("hello"), button("ok", |_| ()))));
+        let before = h.find_text("hello").unwrap();
+        assert_eq!(h.context().scale_factor(), 1.0);
+
+        // Moving to a HiDPI display redraws everything, but layout stays
+        // in points.
+        h.set_scale_factor(2.0);
+        assert_eq!(h.context().scale_factor(), 2.0);
+        assert_eq!(h.damage(), None);
+        assert_eq!(h.find_text("hello").unwrap(), before);
+
+        h.set_scale_factor(2.0);
+        assert!(h.damage().is_some());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Identifies work scheduled with `Context::schedule_idle`, so it can be
+/// cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdleHandle(u64);
+
+/// Returned by each chunk of idle work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdleStatus {
+    /// Run another chunk when there's time.
+    Continue,
+    Done,
+}
+
+/// Passed to idle work so it can size its chunks.
+pub struct IdleDeadline {
+    deadline: Instant,
+}
+
+impl IdleDeadline {
+    pub fn time_remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Has the idle period run out? Long-running chunks should check this
+    /// and return `IdleStatus::Continue` to pick up later.
+    pub fn did_timeout(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+type IdleWork = Box<dyn FnMut(&mut Context, &IdleDeadline) -> IdleStatus>;
+
+/// Work waiting for idle time, run round-robin.
+#[derive(Default)]
+pub(crate) struct IdleQueue {
+    next_id: u64,
+    work: VecDeque<(IdleHandle, IdleWork)>,
+}
+
+impl IdleQueue {
+    pub(crate) fn push(&mut self, work: IdleWork) -> IdleHandle {
+        let handle = IdleHandle(self.next_id);
+        self.next_id += 1;
+        self.work.push_back((handle, work));
+        handle
+    }
+
+    pub(crate) fn cancel(&mut self, handle: IdleHandle) {
+        self.work.retain(|(h, _)| *h != handle);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.work.is_empty()
+    }
+}
+
+/// How long a frame can take before the UI counts as busy.
+pub(crate) const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+impl Context {
+    /// Runs `work` in chunks when frames finish with time to spare, for
+    /// things like prefetching images or warming caches. Each call to
+    /// `work` should do a small amount and return `IdleStatus::Continue`
+    /// if there's more to do.
+    ///
+    /// While the UI is busy, idle work waits. A chunk which is running
+    /// when its idle period ends won't be interrupted, so check
+    /// `IdleDeadline::did_timeout` in long chunks.
+    pub fn schedule_idle(
+        &mut self,
+        work: impl FnMut(&mut Context, &IdleDeadline) -> IdleStatus + 'static,
+    ) -> IdleHandle {
+        self.idle.push(Box::new(work))
+    }
+
+    /// Stops idle work from running again.
+    pub fn cancel_idle(&mut self, handle: IdleHandle) {
+        self.idle.cancel(handle)
+    }
+
+    pub fn has_idle_work(&self) -> bool {
+        !self.idle.is_empty()
+    }
+
+    /// Runs chunks of idle work until `budget` is used up.
+    pub(crate) fn run_idle(&mut self, budget: Duration) {
+        let deadline = IdleDeadline {
+            deadline: Instant::now() + budget,
+        };
+        // Run each piece of work at most once, so newly scheduled work
+        // waits for the next idle period.
+        let mut count = self.idle.work.len();
+        while count > 0 && !deadline.did_timeout() {
+            count -= 1;
+            let (handle, mut work) = match self.idle.work.pop_front() {
+                Some(w) => w,
+                None => break,
+            };
+            if work(self, &deadline) == IdleStatus::Continue {
+                self.idle.work.push_back((handle, work));
+            }
+        }
+    }
+
+    /// Gives idle work whatever's left of the frame budget after the last
+    /// update and render. Does nothing if the UI is busy.
+    pub(crate) fn run_idle_after_frame(&mut self) {
+        let spent = self.frame_timings.update + self.frame_timings.render;
+        if spent < FRAME_BUDGET {
+            self.run_idle(FRAME_BUDGET - spent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_idle_chunks() {
+        let mut cx = Context::new();
+        let chunks = Rc::new(Cell::new(0));
+        let c = chunks.clone();
+        cx.schedule_idle(move |_, _| {
+            c.set(c.get() + 1);
+            if c.get() < 3 {
+                IdleStatus::Continue
+            } else {
+                IdleStatus::Done
+            }
+        });
+
+        cx.run_idle(Duration::from_secs(1));
+        assert_eq!(chunks.get(), 1);
+        cx.run_idle(Duration::from_secs(1));
+        cx.run_idle(Duration::from_secs(1));
+        assert_eq!(chunks.get(), 3);
+        assert!(!cx.has_idle_work());
+    }
+
+    #[test]
+    fn test_idle_busy_and_cancel() {
+        let mut cx = Context::new();
+        let chunks = Rc::new(Cell::new(0));
+        let c = chunks.clone();
+        let handle = cx.schedule_idle(move |_, _| {
+            c.set(c.get() + 1);
+            IdleStatus::Continue
+        });
+
+        // No time left in the frame.
+        cx.frame_timings.render = FRAME_BUDGET;
+        cx.run_idle_after_frame();
+        assert_eq!(chunks.get(), 0);
+
+        cx.frame_timings.render = Duration::ZERO;
+        cx.run_idle_after_frame();
+        assert_eq!(chunks.get(), 1);
+
+        cx.cancel_idle(handle);
+        cx.run_idle(Duration::from_secs(1));
+        assert_eq!(chunks.get(), 1);
+    }
+}
@@ -0,0 +1,740 @@
+use crate::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use wgpu::util::DeviceExt;
+
+static NEXT_IMAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Error from loading or decoding an image.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Decode(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "couldn't read image: {}", err),
+            ImageError::Decode(err) => write!(f, "couldn't decode image: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// Decoded RGBA8 pixels. Cheap to clone, and clones share a texture.
+#[derive(Clone)]
+pub struct ImageData {
+    id: u64,
+    width: u32,
+    height: u32,
+    pixels: Arc<[u8]>,
+}
+
+impl ImageData {
+    /// Wraps `width * height` RGBA8 pixels, in rows from the top.
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "expected {}x{} RGBA8 pixels",
+            width,
+            height
+        );
+        Self {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels: pixels.into(),
+        }
+    }
+
+    /// Decodes a PNG or JPEG. Needs the `images` feature.
+    #[cfg(feature = "images")]
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        let image =
+            ::image::load_from_memory(bytes).map_err(|err| ImageError::Decode(err.to_string()))?;
+        let rgba = image.into_rgba8();
+        let (width, height) = rgba.dimensions();
+        Ok(Self::from_rgba(width, height, rgba.into_raw()))
+    }
+
+    /// Decodes a PNG or JPEG. Needs the `images` feature.
+    #[cfg(not(feature = "images"))]
+    pub fn decode(_bytes: &[u8]) -> Result<Self, ImageError> {
+        Err(ImageError::Decode(
+            "rui was built without the images feature".into(),
+        ))
+    }
+
+    /// Reads and decodes a PNG or JPEG file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        let bytes = std::fs::read(path).map_err(ImageError::Io)?;
+        Self::decode(&bytes)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Size in pixels.
+    pub fn size(&self) -> LocalSize {
+        LocalSize::new(self.width as f32, self.height as f32)
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+/// Images are the same if they share pixels, as clones do.
+impl PartialEq for ImageData {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ImageData({}x{})", self.width, self.height)
+    }
+}
+
+/// Where an `image` comes from.
+#[derive(Clone, Debug)]
+pub enum ImageSource {
+    /// A PNG or JPEG file.
+    Path(PathBuf),
+
+    /// An encoded PNG or JPEG, with a hash of its contents.
+    Bytes(Arc<[u8]>, u64),
+
+    /// Already decoded pixels.
+    Data(ImageData),
+}
+
+impl ImageSource {
+    pub fn bytes(bytes: impl Into<Arc<[u8]>>) -> Self {
+        let bytes = bytes.into();
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        ImageSource::Bytes(bytes, hasher.finish())
+    }
+
+    /// Reads and decodes the image. This can be slow for large files, so
+    /// use `async_image` to keep it off the main thread.
+    pub fn load(&self) -> Result<ImageData, ImageError> {
+        match self {
+            ImageSource::Path(path) => ImageData::open(path),
+            ImageSource::Bytes(bytes, _) => ImageData::decode(bytes),
+            ImageSource::Data(data) => Ok(data.clone()),
+        }
+    }
+
+    /// Loads the image for drawing, logging why if it can't be.
+    pub(crate) fn load_for_view(&self) -> Option<ImageData> {
+        match self.load() {
+            Ok(image) => Some(image),
+            Err(err) => {
+                println!("rui: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Identifies the source, so views can tell when it changes.
+    pub(crate) fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match self {
+            ImageSource::Path(path) => (0, path).hash(&mut hasher),
+            ImageSource::Bytes(_, hash) => (1, hash).hash(&mut hasher),
+            ImageSource::Data(data) => (2, data.id).hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+}
+
+impl From<&str> for ImageSource {
+    fn from(path: &str) -> Self {
+        ImageSource::Path(path.into())
+    }
+}
+
+impl From<String> for ImageSource {
+    fn from(path: String) -> Self {
+        ImageSource::Path(path.into())
+    }
+}
+
+impl From<&Path> for ImageSource {
+    fn from(path: &Path) -> Self {
+        ImageSource::Path(path.into())
+    }
+}
+
+impl From<PathBuf> for ImageSource {
+    fn from(path: PathBuf) -> Self {
+        ImageSource::Path(path)
+    }
+}
+
+impl From<Vec<u8>> for ImageSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        ImageSource::bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for ImageSource {
+    fn from(bytes: &[u8]) -> Self {
+        ImageSource::bytes(bytes)
+    }
+}
+
+/// For `include_bytes!`.
+impl<const N: usize> From<&[u8; N]> for ImageSource {
+    fn from(bytes: &[u8; N]) -> Self {
+        ImageSource::bytes(&bytes[..])
+    }
+}
+
+impl From<ImageData> for ImageSource {
+    fn from(data: ImageData) -> Self {
+        ImageSource::Data(data)
+    }
+}
+
+/// How an image is sized to fit its view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContentMode {
+    /// Scale to fit inside the view, keeping the aspect ratio. This may
+    /// leave empty space on two sides.
+    #[default]
+    Fit,
+
+    /// Scale to cover the view, keeping the aspect ratio. This crops the
+    /// image on two sides.
+    Fill,
+
+    /// Scale to exactly the size of the view.
+    Stretch,
+}
+
+/// Where to draw an image of `image` pixels in `bounds`, and which part
+/// of it to show, as `[u0, v0, u1, v1]` texture coordinates from the
+/// image's top-left.
+pub(crate) fn content_rects(
+    mode: ContentMode,
+    image: LocalSize,
+    bounds: LocalRect,
+) -> (LocalRect, [f32; 4]) {
+    let full = [0.0, 0.0, 1.0, 1.0];
+    if image.is_empty() || bounds.is_empty() {
+        return (LocalRect::new(bounds.origin, LocalSize::zero()), full);
+    }
+
+    let sx = bounds.width() / image.width;
+    let sy = bounds.height() / image.height;
+    match mode {
+        ContentMode::Stretch => (bounds, full),
+        ContentMode::Fit => {
+            let size = image * sx.min(sy);
+            let origin = bounds.center() - size.to_vector() / 2.0;
+            (LocalRect::new(origin, size), full)
+        }
+        ContentMode::Fill => {
+            let scale = sx.max(sy);
+            let fx = sx / scale;
+            let fy = sy / scale;
+            (
+                bounds,
+                [
+                    (1.0 - fx) / 2.0,
+                    (1.0 - fy) / 2.0,
+                    (1.0 + fx) / 2.0,
+                    (1.0 + fy) / 2.0,
+                ],
+            )
+        }
+    }
+}
+
+/// An image to be drawn during rendering, collected while drawing.
+pub(crate) struct ImageRequest {
+    pub image: ImageData,
+
+    /// Where the image goes, in the coordinates of the layer it's drawn
+    /// in. A layer's `Vger` already starts at the layer's origin, so this
+    /// isn't offset by where the layer goes again.
+    pub rect: WorldRect,
+    pub uv: [f32; 4],
+
+    /// Innermost clip the image was drawn in, in the same coordinates as
+    /// `rect`.
+    pub clip: Option<WorldRect>,
+
+    /// Debug group the image was drawn in.
+    pub marker: Option<String>,
+
+    /// Rounded rect to mask the image to, and its corner radius, in the
+    /// same coordinates as `rect`.
+    pub mask: Option<(WorldRect, f32)>,
+
+    /// Radial gradient to tint the image with, for `Paint::RadialGradient`.
+    pub gradient: Option<RadialFill>,
+}
+
+/// A radial gradient drawn as an image, in the coordinates of the image's
+/// `rect`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RadialFill {
+    pub center: WorldPoint,
+    pub radius: f32,
+    pub inner_color: Color,
+    pub outer_color: Color,
+}
+
+/// What radial gradients are drawn over.
+pub(crate) fn white_image() -> ImageData {
+    static WHITE: OnceLock<ImageData> = OnceLock::new();
+    WHITE
+        .get_or_init(|| ImageData::from_rgba(1, 1, vec![255; 4]))
+        .clone()
+}
+
+const IMAGE_SHADER: &str = r#"
+struct ImageUniforms {
+    rect: vec4<f32>,
+    uv: vec4<f32>,
+    screen: vec2<f32>,
+    // Mask corner radius, and gradient radius if there's a gradient.
+    radii: vec2<f32>,
+    // Mask rect, as min and max corners.
+    mask: vec4<f32>,
+    center: vec4<f32>,
+    inner_color: vec4<f32>,
+    outer_color: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: ImageUniforms;
+@group(0) @binding(1) var image_texture: texture_2d<f32>;
+@group(0) @binding(2) var image_sampler: sampler;
+
+struct ImageVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+};
+
+@vertex
+fn image_vs(@builtin(vertex_index) index: u32) -> ImageVertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let px = uniforms.rect.xy + corner * uniforms.rect.zw;
+    var out: ImageVertexOutput;
+    out.position = vec4<f32>(
+        px.x / uniforms.screen.x * 2.0 - 1.0,
+        1.0 - px.y / uniforms.screen.y * 2.0,
+        0.0,
+        1.0
+    );
+    out.corner = corner;
+    return out;
+}
+
+@fragment
+fn image_fs(in: ImageVertexOutput) -> @location(0) vec4<f32> {
+    let uv = mix(uniforms.uv.xy, uniforms.uv.zw, in.corner);
+    var color = textureSample(image_texture, image_sampler, uv);
+    let px = in.position.xy;
+
+    if uniforms.radii.y > 0.0 {
+        let t = clamp(distance(px, uniforms.center.xy) / uniforms.radii.y, 0.0, 1.0);
+        color = color * mix(uniforms.inner_color, uniforms.outer_color, t);
+    }
+
+    // Distance outside the rounded rect, antialiased over a pixel.
+    let half = (uniforms.mask.zw - uniforms.mask.xy) / 2.0;
+    let r = min(uniforms.radii.x, min(half.x, half.y));
+    let q = abs(px - (uniforms.mask.xy + uniforms.mask.zw) / 2.0) - half + vec2<f32>(r, r);
+    let d = length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0) - r;
+    color.a = color.a * clamp(0.5 - d, 0.0, 1.0);
+    return color;
+}
+"#;
+
+/// Renders `image` views. Created lazily the first time an image is drawn.
+pub(crate) struct ImageRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    textures: HashMap<u64, wgpu::TextureView>,
+
+    /// Images drawn since the last `end_frame`.
+    drawn: HashSet<u64>,
+}
+
+impl ImageRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rui image bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rui image pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rui image shader"),
+            source: wgpu::ShaderSource::Wgsl(IMAGE_SHADER.into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rui image pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "image_vs",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "image_fs",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rui image sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+            sampler,
+            textures: HashMap::new(),
+            drawn: HashSet::new(),
+        }
+    }
+
+    /// Uploads `image` the first time it's drawn.
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, image: &ImageData) {
+        if self.textures.contains_key(&image.id) {
+            return;
+        }
+
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("rui image"),
+                size: wgpu::Extent3d {
+                    width: image.width,
+                    height: image.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            &image.pixels,
+        );
+        self.textures.insert(
+            image.id,
+            texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        );
+    }
+
+    /// Frees textures for images which weren't drawn this frame.
+    pub fn end_frame(&mut self) {
+        let drawn = std::mem::take(&mut self.drawn);
+        self.textures.retain(|id, _| drawn.contains(id));
+    }
+
+    /// Draws the images over `target`.
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        requests: &[ImageRequest],
+        window_size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        if requests.is_empty() {
+            return;
+        }
+
+        for request in requests {
+            self.upload(device, queue, &request.image);
+            self.drawn.insert(request.image.id);
+        }
+
+        let screen = [window_size.width * scale, window_size.height * scale];
+        let bind_groups: Vec<_> = requests
+            .iter()
+            .map(|request| {
+                // World space is y-up in points, the frame is y-down in pixels.
+                let r = request.rect;
+                let px = |p: WorldPoint| [p.x * scale, (window_size.height - p.y) * scale];
+                // Without a mask, mask to a bit more than the image.
+                let (mask, radius) = request.mask.unwrap_or((r.inflate(1.0, 1.0), 0.0));
+                let [x0, y1] = px(mask.min());
+                let [x1, y0] = px(mask.max());
+                let gradient = request.gradient.unwrap_or(RadialFill {
+                    center: WorldPoint::zero(),
+                    radius: 0.0,
+                    inner_color: Color::WHITE,
+                    outer_color: Color::WHITE,
+                });
+                let [cx, cy] = px(gradient.center);
+                let (inner, outer) = (gradient.inner_color, gradient.outer_color);
+                let data = [
+                    r.min_x() * scale,
+                    (window_size.height - r.max_y()) * scale,
+                    r.width() * scale,
+                    r.height() * scale,
+                    request.uv[0],
+                    request.uv[1],
+                    request.uv[2],
+                    request.uv[3],
+                    screen[0],
+                    screen[1],
+                    radius * scale,
+                    gradient.radius * scale,
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                    cx,
+                    cy,
+                    0.0,
+                    0.0,
+                    inner.r,
+                    inner.g,
+                    inner.b,
+                    inner.a,
+                    outer.r,
+                    outer.g,
+                    outer.b,
+                    outer.a,
+                ];
+                let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_ne_bytes()).collect();
+                let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("rui image uniforms"),
+                    contents: &bytes,
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("rui image bind group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(
+                                &self.textures[&request.image.id],
+                            ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                })
+            })
+            .collect();
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui image encoder"),
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rui image pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(&self.pipeline);
+            let window = WorldRect::new(WorldPoint::zero(), window_size);
+            for (request, bind_group) in requests.iter().zip(&bind_groups) {
+                let clip = request.clip.unwrap_or(window);
+                let (x, y, w, h) = match scissor_px(&clip, window_size, scale) {
+                    Some(scissor) => scissor,
+                    None => continue,
+                };
+                if let Some(marker) = &request.marker {
+                    rpass.push_debug_group(marker);
+                }
+                rpass.set_scissor_rect(x, y, w, h);
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.draw(0..4, 0..1);
+                if request.marker.is_some() {
+                    rpass.pop_debug_group();
+                }
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Pixel scissor rect for `clip`, within a target of `size` points drawn
+/// at `scale`, or `None` if nothing would be drawn.
+pub(crate) fn scissor_px(
+    clip: &WorldRect,
+    size: euclid::Size2D<f32, WorldSpace>,
+    scale: f32,
+) -> Option<(u32, u32, u32, u32)> {
+    // World space is y-up in points, targets are y-down in pixels.
+    let x0 = (clip.min_x() * scale).max(0.0).floor();
+    let y0 = ((size.height - clip.max_y()) * scale).max(0.0).floor();
+    let x1 = (clip.max_x() * scale).min(size.width * scale).ceil();
+    let y1 = ((size.height - clip.min_y()) * scale)
+        .min(size.height * scale)
+        .ceil();
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some((x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> LocalRect {
+        LocalRect::new(LocalPoint::new(x, y), LocalSize::new(w, h))
+    }
+
+    #[test]
+    fn test_content_rects() {
+        let image = LocalSize::new(200.0, 100.0);
+        let bounds = rect(0.0, 0.0, 100.0, 100.0);
+
+        let (dest, uv) = content_rects(ContentMode::Fit, image, bounds);
+        assert_eq!(dest, rect(0.0, 25.0, 100.0, 50.0));
+        assert_eq!(uv, [0.0, 0.0, 1.0, 1.0]);
+
+        let (dest, uv) = content_rects(ContentMode::Fill, image, bounds);
+        assert_eq!(dest, bounds);
+        assert_eq!(uv, [0.25, 0.0, 0.75, 1.0]);
+
+        let (dest, uv) = content_rects(ContentMode::Stretch, image, bounds);
+        assert_eq!(dest, bounds);
+        assert_eq!(uv, [0.0, 0.0, 1.0, 1.0]);
+
+        let (dest, _) = content_rects(ContentMode::Fit, LocalSize::zero(), bounds);
+        assert!(dest.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn test_decode() {
+        let mut png = vec![];
+        ::image::RgbaImage::from_pixel(3, 2, ::image::Rgba([255, 0, 0, 255]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                ::image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+
+        let data = ImageSource::from(png.clone()).load().unwrap();
+        assert_eq!((data.width(), data.height()), (3, 2));
+        assert_eq!(&data.pixels()[..4], &[255, 0, 0, 255]);
+
+        // Same bytes, same key.
+        assert_eq!(
+            ImageSource::from(png.clone()).key(),
+            ImageSource::from(&png[..]).key()
+        );
+
+        assert!(matches!(
+            ImageSource::from(&b"not an image"[..]).load(),
+            Err(ImageError::Decode(_))
+        ));
+        assert!(matches!(
+            ImageSource::from("/does/not/exist.png").load(),
+            Err(ImageError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_scissor_px() {
+        let size = euclid::Size2D::new(100.0, 50.0);
+        let clip = WorldRect::new(WorldPoint::new(10.0, 0.0), euclid::Size2D::new(20.0, 10.0));
+        // The bottom of the window is the bottom of the target.
+        assert_eq!(scissor_px(&clip, size, 2.0), Some((20, 80, 40, 20)));
+
+        let outside = clip.translate(euclid::Vector2D::new(200.0, 0.0));
+        assert_eq!(scissor_px(&outside, size, 2.0), None);
+    }
+}
@@ -0,0 +1,1203 @@
+//! Offscreen layers for the `shadow`, `opacity`, `blur` and
+//! `corner_radius` modifiers.
+//!
+//! A layered view's subtree is drawn with a separate `Vger` into its own
+//! texture, which is composited into the frame once the subtree is drawn,
+//! in order with the rest of the frame: the `Painter` ends vger's pass
+//! there, so views drawn after the layer go over it. Layers nest: a layer
+//! inside another is composited into its parent's texture. Images are
+//! drawn between vger's passes the same way.
+//!
+//! `cache_key` keeps its layer between frames, and composites it again
+//! instead of drawing its subtree while nothing has changed. `sketch`
+//! keeps its strokes the same way, and draws new ones over the kept layer.
+
+use crate::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use wgpu::util::DeviceExt;
+
+/// How a layer is composited.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LayerStyle {
+    pub opacity: f32,
+
+    /// Blur radius in points.
+    pub blur: f32,
+
+    pub shadow: Option<Shadow>,
+
+    /// Clips the layer to its view's bounds, with corners rounded by this
+    /// radius in points.
+    pub corner_radius: Option<f32>,
+
+    /// Drawn over everything else in the enclosing layer, and over the
+    /// clips of views around it, as for popovers and drag previews.
+    pub floating: bool,
+}
+
+impl Default for LayerStyle {
+    fn default() -> Self {
+        Self {
+            opacity: 1.0,
+            blur: 0.0,
+            shadow: None,
+            corner_radius: None,
+            floating: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Shadow {
+    /// Blur radius in points.
+    pub radius: f32,
+    pub offset: LocalOffset,
+    pub color: Color,
+}
+
+impl LayerStyle {
+    /// How far the layer's pixels can spread beyond its view, in points.
+    fn margin(&self) -> f32 {
+        let shadow = self
+            .shadow
+            .map_or(0.0, |s| s.radius + s.offset.x.abs().max(s.offset.y.abs()));
+        self.blur.max(shadow)
+    }
+}
+
+/// A rendered layer waiting to be composited.
+struct LayerRequest {
+    /// Shared with the cache if the layer is kept for later frames.
+    texture: Rc<LayerTexture>,
+
+    /// Where the texture goes, in the coordinates of the enclosing layer.
+    area: WorldRect,
+
+    style: LayerStyle,
+
+    /// Shadow offset in pixels, y-down.
+    shadow_offset: [f32; 2],
+
+    /// Innermost clip the view was drawn in, if any.
+    clip: Option<WorldRect>,
+
+    /// Rounded rect the layer is masked to, and its corner radius, in
+    /// the coordinates of the enclosing layer.
+    mask: Option<(WorldRect, f32)>,
+
+    /// Debug group the layer was drawn in.
+    marker: Option<String>,
+}
+
+/// Drawn by rui rather than vger, after what vger drew before it.
+enum Pending {
+    Image(ImageRequest),
+    Layer(LayerRequest),
+    Glyphs(GlyphRun),
+    Particles(ParticleBatch),
+}
+
+impl Pending {
+    /// What the item covers, in the coordinates of the layer.
+    fn bounds(&self) -> WorldRect {
+        match self {
+            Pending::Image(image) => image.rect,
+            Pending::Layer(request) => request.area,
+            Pending::Glyphs(run) => run.bounds,
+            Pending::Particles(batch) => batch.bounds,
+        }
+    }
+}
+
+/// A layer being drawn.
+struct LayerScope {
+    /// Where the layer goes, in the coordinates of the enclosing layer.
+    /// The layer's own coordinates start at its bottom left.
+    area: WorldRect,
+
+    /// What the layer is drawn into. The frame only has one once an image
+    /// or layer is drawn in it.
+    target: Option<LayerTexture>,
+
+    /// Images, nested layers and text drawn since vger's pass began, in
+    /// order. See `Painter` for where they go among vger's shapes.
+    pending: Vec<Pending>,
+
+    /// Floating layers, which go over everything else once this one's
+    /// drawn.
+    floating: Vec<LayerRequest>,
+
+    /// Clips entered within this layer, innermost last.
+    clips: Vec<WorldRect>,
+
+    mask: Option<(WorldRect, f32)>,
+
+    /// Was the layer cut off by the edge of the enclosing layer?
+    clipped: bool,
+}
+
+/// A layer kept by `cache_key` from an earlier frame.
+struct CachedLayer {
+    key: u64,
+    texture: Rc<LayerTexture>,
+
+    /// Where the layer went, and the transform it was drawn with.
+    area: WorldRect,
+    xform: LocalToWorld,
+
+    /// Cut off layers are only reused in the same place.
+    clipped: bool,
+
+    /// Composited this frame, so it's kept for the next.
+    used: bool,
+}
+
+struct LayerTexture {
+    /// Shared with the `Painter`, to end passes early on.
+    view: Arc<wgpu::TextureView>,
+    width: u32,
+    height: u32,
+}
+
+const LAYER_SHADER: &str = r#"
+struct LayerUniforms {
+    rect: vec4<f32>,
+    screen: vec2<f32>,
+    opacity: f32,
+    blur: f32,
+    shadow_color: vec4<f32>,
+    shadow_offset: vec2<f32>,
+    shadow_blur: f32,
+    shadow_pass: f32,
+    mask: vec4<f32>,
+    mask_radius: f32,
+    pad0: f32,
+    pad1: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: LayerUniforms;
+@group(0) @binding(1) var layer_texture: texture_2d<f32>;
+@group(0) @binding(2) var layer_sampler: sampler;
+
+struct LayerVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn layer_vs(@builtin(vertex_index) index: u32) -> LayerVertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let px = uniforms.rect.xy + corner * uniforms.rect.zw;
+    var out: LayerVertexOutput;
+    out.position = vec4<f32>(
+        px.x / uniforms.screen.x * 2.0 - 1.0,
+        1.0 - px.y / uniforms.screen.y * 2.0,
+        0.0,
+        1.0
+    );
+    out.uv = corner;
+    return out;
+}
+
+// Gaussian blur over a 9x9 grid of taps spread across `radius` pixels.
+fn blurred(uv: vec2<f32>, radius: f32) -> vec4<f32> {
+    if radius < 0.5 {
+        return textureSampleLevel(layer_texture, layer_sampler, uv, 0.0);
+    }
+    let step = radius / 4.0 / vec2<f32>(textureDimensions(layer_texture));
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -4; i <= 4; i = i + 1) {
+        for (var j = -4; j <= 4; j = j + 1) {
+            let d = vec2<f32>(f32(i), f32(j));
+            let w = exp(-dot(d, d) / 8.0);
+            sum += w * textureSampleLevel(layer_texture, layer_sampler, uv + d * step, 0.0);
+            total += w;
+        }
+    }
+    return sum / total;
+}
+
+// Coverage of the rounded rect mask at pixel `p`, antialiased over a pixel.
+fn mask_coverage(p: vec2<f32>) -> f32 {
+    let r = uniforms.mask_radius;
+    if r < 0.0 {
+        return 1.0;
+    }
+    let half = uniforms.mask.zw * 0.5;
+    let q = abs(p - uniforms.mask.xy - half) - half + vec2<f32>(r);
+    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+    return clamp(0.5 - d, 0.0, 1.0);
+}
+
+@fragment
+fn layer_fs(in: LayerVertexOutput) -> @location(0) vec4<f32> {
+    // Layers are premultiplied, having been drawn over transparent black.
+    if uniforms.shadow_pass > 0.5 {
+        let size = vec2<f32>(textureDimensions(layer_texture));
+        let a = blurred(in.uv - uniforms.shadow_offset / size, uniforms.shadow_blur).a;
+        let c = uniforms.shadow_color;
+        return vec4<f32>(c.rgb * c.a, c.a) * a;
+    }
+    return blurred(in.uv, uniforms.blur) * uniforms.opacity * mask_coverage(in.position.xy);
+}
+"#;
+
+/// Draws and composites layers.
+pub(crate) struct LayerRenderer {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    format: wgpu::TextureFormat,
+    scale: f32,
+
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+
+    /// Vgers for drawing layers, one per level of nesting.
+    vgers: Vec<Vger>,
+
+    /// Layers being drawn. The first is the frame.
+    scopes: Vec<LayerScope>,
+
+    /// Textures which can be reused, and those used this frame.
+    free: Vec<LayerTexture>,
+    used: Vec<LayerTexture>,
+
+    /// Layers kept by `cache_key` views.
+    cache: HashMap<ViewId, CachedLayer>,
+
+    /// Time spent rendering passes while the frame was drawn.
+    pass_time: Duration,
+}
+
+impl LayerRenderer {
+    pub fn new(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rui layer bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rui layer pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rui layer shader"),
+            source: wgpu::ShaderSource::Wgsl(LAYER_SHADER.into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rui layer pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "layer_vs",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "layer_fs",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rui layer sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            device,
+            queue,
+            format,
+            scale: 1.0,
+            bind_group_layout,
+            pipeline,
+            sampler,
+            vgers: vec![],
+            scopes: vec![],
+            free: vec![],
+            used: vec![],
+            cache: HashMap::new(),
+            pass_time: Duration::ZERO,
+        }
+    }
+
+    /// Starts a frame drawn at `scale` pixels per point.
+    pub fn begin_frame(&mut self, window_size: euclid::Size2D<f32, WorldSpace>, scale: f32) {
+        if scale != self.scale {
+            self.cache.clear();
+        }
+        self.scale = scale;
+        self.pass_time = Duration::ZERO;
+        self.scopes.clear();
+        self.scopes.push(LayerScope {
+            area: WorldRect::new(WorldPoint::zero(), window_size),
+            target: None,
+            pending: vec![],
+            floating: vec![],
+            clips: vec![],
+            mask: None,
+            clipped: false,
+        });
+    }
+
+    /// Time spent rendering passes since the frame began, which was
+    /// counted as drawing.
+    pub fn pass_time(&self) -> Duration {
+        self.pass_time
+    }
+
+    fn texture(&mut self, width: u32, height: u32) -> LayerTexture {
+        if let Some(i) = self
+            .free
+            .iter()
+            .position(|t| t.width == width && t.height == height)
+        {
+            return self.free.swap_remove(i);
+        }
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rui layer"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        LayerTexture {
+            view: Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default())),
+            width,
+            height,
+        }
+    }
+
+    /// A transparent texture for a layer of `size` points.
+    fn cleared_texture(&mut self, size: euclid::Size2D<f32, WorldSpace>) -> LayerTexture {
+        let texture = self.texture(
+            (size.width * self.scale).round() as u32,
+            (size.height * self.scale).round() as u32,
+        );
+        self.clear(&texture.view);
+        texture
+    }
+
+    fn clear(&self, target: &wgpu::TextureView) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("rui layer encoder"),
+            });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rui layer clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Composites the requests into `target`, which covers `area`.
+    fn composite(
+        &mut self,
+        target: &wgpu::TextureView,
+        requests: Vec<LayerRequest>,
+        area: WorldRect,
+    ) {
+        if requests.is_empty() {
+            return;
+        }
+        let scale = self.scale;
+        let screen = [area.width() * scale, area.height() * scale];
+
+        // World space is y-up in points, targets are y-down in pixels.
+        let to_px = |r: &WorldRect| {
+            [
+                (r.min_x() - area.min_x()) * scale,
+                (area.max_y() - r.max_y()) * scale,
+                r.width() * scale,
+                r.height() * scale,
+            ]
+        };
+
+        let mut draws = vec![];
+        for request in &requests {
+            let rect = to_px(&request.area);
+            let scissor = match request.clip {
+                Some(clip) => match clip.intersection(&request.area) {
+                    Some(clip) => to_px(&clip),
+                    None => continue,
+                },
+                None => rect,
+            };
+            let style = &request.style;
+            let mask = match request.mask {
+                Some((rect, radius)) => {
+                    let [x, y, w, h] = to_px(&rect);
+                    [x, y, w, h, radius * scale]
+                }
+                None => [0.0, 0.0, 0.0, 0.0, -1.0],
+            };
+            let mut passes = vec![];
+            if let Some(shadow) = &style.shadow {
+                passes.push([
+                    shadow.color.r,
+                    shadow.color.g,
+                    shadow.color.b,
+                    shadow.color.a,
+                    request.shadow_offset[0],
+                    request.shadow_offset[1],
+                    shadow.radius * scale,
+                    1.0,
+                ]);
+            }
+            passes.push([0.0; 8]);
+            for pass in passes {
+                let mut data = [0.0f32; 24];
+                data[0..4].copy_from_slice(&rect);
+                data[4..6].copy_from_slice(&screen);
+                data[6] = style.opacity;
+                data[7] = style.blur * scale;
+                data[8..16].copy_from_slice(&pass);
+                data[16..21].copy_from_slice(&mask);
+                draws.push((
+                    scissor,
+                    request.marker.as_deref(),
+                    self.bind_group(&request.texture, &data),
+                ));
+            }
+        }
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("rui layer encoder"),
+            });
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rui layer pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            rpass.set_pipeline(&self.pipeline);
+            for (scissor, marker, bind_group) in &draws {
+                let x0 = scissor[0].max(0.0).floor();
+                let y0 = scissor[1].max(0.0).floor();
+                let x1 = (scissor[0] + scissor[2]).min(screen[0]).ceil();
+                let y1 = (scissor[1] + scissor[3]).min(screen[1]).ceil();
+                if x1 <= x0 || y1 <= y0 {
+                    continue;
+                }
+                if let Some(marker) = marker {
+                    rpass.push_debug_group(marker);
+                }
+                rpass.set_scissor_rect(x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32);
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.draw(0..4, 0..1);
+                if marker.is_some() {
+                    rpass.pop_debug_group();
+                }
+            }
+        }
+        self.queue.submit(Some(encoder.finish()));
+
+        // Cached textures stay with the cache.
+        self.used.extend(
+            requests
+                .into_iter()
+                .filter_map(|request| Rc::try_unwrap(request.texture).ok()),
+        );
+    }
+
+    /// Replaces the contents of `target`, which is `size` points, with
+    /// those of `source`, which is the same size.
+    fn copy(
+        &mut self,
+        source: Rc<LayerTexture>,
+        target: &wgpu::TextureView,
+        size: euclid::Size2D<f32, WorldSpace>,
+    ) {
+        self.clear(target);
+
+        let area = WorldRect::new(WorldPoint::zero(), size);
+        let request = LayerRequest {
+            texture: source,
+            area,
+            style: LayerStyle::default(),
+            shadow_offset: [0.0, 0.0],
+            clip: None,
+            mask: None,
+            marker: None,
+        };
+        self.composite(target, vec![request], area);
+    }
+
+    fn bind_group(&self, texture: &LayerTexture, data: &[f32; 24]) -> wgpu::BindGroup {
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rui layer uniforms"),
+                contents: &bytes,
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rui layer bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+
+    /// Composites the frame's own layer, if it has one, into `target`,
+    /// and frees textures which weren't used this frame.
+    pub fn end_frame(&mut self, target: &wgpu::TextureView) {
+        let frame = self.scopes.drain(..).next();
+        if let Some(mut scope) = frame {
+            let bounds = WorldRect::new(WorldPoint::zero(), scope.area.size);
+            let mut requests = vec![];
+            if let Some(texture) = scope.target.take() {
+                requests.push(LayerRequest {
+                    texture: Rc::new(texture),
+                    area: bounds,
+                    style: LayerStyle::default(),
+                    shadow_offset: [0.0, 0.0],
+                    clip: None,
+                    mask: None,
+                    marker: None,
+                });
+            }
+            requests.append(&mut scope.floating);
+            self.composite(target, requests, bounds);
+        }
+        self.free = std::mem::take(&mut self.used);
+
+        self.cache
+            .retain(|_, cached| std::mem::take(&mut cached.used));
+    }
+}
+
+/// Splits the linear part of a transform into a rotation by `phi`, a
+/// scale, and a rotation by `theta`, which vger can apply in that order.
+fn decompose(m: &LocalToWorld) -> (f32, LocalOffset, f32) {
+    let (a, b, c, d) = (m.m11, m.m21, m.m12, m.m22);
+    let e = (a + d) / 2.0;
+    let f = (a - d) / 2.0;
+    let g = (c + b) / 2.0;
+    let h = (c - b) / 2.0;
+    let q = (e * e + h * h).sqrt();
+    let r = (f * f + g * g).sqrt();
+    let a1 = g.atan2(f);
+    let a2 = h.atan2(e);
+    (
+        (a2 + a1) / 2.0,
+        LocalOffset::new(q + r, q - r),
+        (a2 - a1) / 2.0,
+    )
+}
+
+/// Gives `vger` the transform `m`, after its current one.
+pub(crate) fn apply_transform(vger: &mut Vger, m: &LocalToWorld) {
+    let (phi, scale, theta) = decompose(m);
+    vger.translate([m.m31, m.m32]);
+    vger.rotate(phi);
+    vger.scale(scale);
+    vger.rotate(theta);
+}
+
+impl Context {
+    /// Are we drawing into a layer rather than the frame?
+    pub(crate) fn in_layer(&self) -> bool {
+        self.layer_renderer
+            .as_ref()
+            .is_some_and(|layers| layers.scopes.len() > 1)
+    }
+
+    /// Starts drawing a layer for a view with bounds `rect` and transform
+    /// `xform`. Returns the `Painter` to draw it with, or `None` if layers
+    /// can't be drawn, in which case the view should be drawn as usual.
+    pub(crate) fn begin_layer(
+        &mut self,
+        rect: LocalRect,
+        xform: LocalToWorld,
+        style: &LayerStyle,
+    ) -> Option<Painter<'static>> {
+        let layers = self.layer_renderer.as_mut()?;
+        if layers.scopes.is_empty() {
+            return None;
+        }
+
+        // Snap to pixels so the layer isn't resampled.
+        let scale = layers.scale;
+        let margin = style.margin();
+        let r = xform
+            .outer_transformed_rect(&rect)
+            .inflate(margin, margin)
+            .scale(scale, scale)
+            .round_out()
+            .scale(1.0 / scale, 1.0 / scale);
+        let bounds = WorldRect::new(WorldPoint::zero(), layers.scopes.last().unwrap().area.size);
+        let area = r.intersection(&bounds)?;
+        if area.width() * scale < 1.0 || area.height() * scale < 1.0 {
+            return None;
+        }
+
+        // Scale the radius with the view, so corners stay round under
+        // transforms.
+        let mask = style.corner_radius.map(|radius| {
+            let bounds = xform.outer_transformed_rect(&rect);
+            (bounds, radius * bounds.width() / rect.width().max(1e-6))
+        });
+
+        let mut vger = match layers.vgers.pop() {
+            Some(vger) => vger,
+            None => Vger::new(layers.device.clone(), layers.queue.clone(), layers.format),
+        };
+        vger.begin(area.width(), area.height(), scale);
+        vger.translate([-area.min_x(), -area.min_y()]);
+        apply_transform(&mut vger, &xform);
+
+        let target = layers.cleared_texture(area.size);
+        layers.scopes.push(LayerScope {
+            area,
+            target: Some(target),
+            pending: vec![],
+            floating: vec![],
+            clips: vec![],
+            mask,
+            clipped: area != r,
+        });
+        let mut painter = Painter::layer(vger, area.size, scale);
+        painter.set_label(self.gpu_markers.current());
+        Some(painter)
+    }
+
+    /// Like `begin_layer`, for a view which keeps its layer with
+    /// `end_cached_layer`, but starting from the layer kept for `vid` if it
+    /// was drawn with `key` in the same place, so the view need only draw
+    /// what it added since. Returns the `Painter` and whether it started
+    /// from the kept layer.
+    pub(crate) fn resume_layer(
+        &mut self,
+        vid: ViewId,
+        key: Option<u64>,
+        rect: LocalRect,
+        xform: LocalToWorld,
+    ) -> Option<(Painter<'static>, bool)> {
+        // Replaced once the layer ends, either way.
+        let kept = self.layer_renderer.as_mut()?.cache.remove(&vid);
+        let painter = self.begin_layer(rect, xform, &LayerStyle::default())?;
+
+        let layers = self.layer_renderer.as_mut()?;
+        let scope = layers.scopes.last_mut()?;
+        let base = kept.filter(|kept| {
+            Some(kept.key) == key
+                && kept.xform == xform
+                && kept.area == scope.area
+                && !kept.clipped
+                && !scope.clipped
+        });
+        let resumed = base.is_some();
+        if let Some(base) = base {
+            let size = scope.area.size;
+            let target = scope.target.take().unwrap();
+            layers.copy(base.texture, &target.view, size);
+            layers.scopes.last_mut().unwrap().target = Some(target);
+        }
+        Some((painter, resumed))
+    }
+
+    /// Renders the layer started by `begin_layer`, once `layer` has drawn
+    /// it, and composites it after what `parent` has drawn.
+    pub(crate) fn end_layer(
+        &mut self,
+        parent: &mut Painter,
+        layer: Painter,
+        xform: LocalToWorld,
+        style: LayerStyle,
+    ) {
+        self.finish_layer(parent, layer, xform, style);
+    }
+
+    /// Like `end_layer`, but keeps the layer for `replay_layer` to use in
+    /// later frames, while `key` stays the same.
+    pub(crate) fn end_cached_layer(
+        &mut self,
+        parent: &mut Painter,
+        layer: Painter,
+        xform: LocalToWorld,
+        vid: ViewId,
+        key: u64,
+    ) {
+        let (texture, area, clipped) =
+            self.finish_layer(parent, layer, xform, LayerStyle::default());
+        let layers = self.layer_renderer.as_mut().unwrap();
+        layers.cache.insert(
+            vid,
+            CachedLayer {
+                key,
+                texture,
+                area,
+                xform,
+                clipped,
+                used: true,
+            },
+        );
+    }
+
+    /// Composites the layer kept for `vid` again after what `parent` has
+    /// drawn, instead of drawing it, if it was drawn with `key` and a
+    /// transform at most a whole number of pixels away from `xform`.
+    /// Returns whether it did.
+    pub(crate) fn replay_layer(
+        &mut self,
+        parent: &mut Painter,
+        vid: ViewId,
+        key: u64,
+        xform: LocalToWorld,
+    ) -> bool {
+        let marker = self.gpu_markers.current();
+        let Some(layers) = self.layer_renderer.as_mut() else {
+            return false;
+        };
+        let scale = layers.scale;
+        let (Some(cached), Some(scope)) = (layers.cache.get_mut(&vid), layers.scopes.last()) else {
+            return false;
+        };
+
+        let moved = LocalOffset::new(xform.m31 - cached.xform.m31, xform.m32 - cached.xform.m32);
+        let whole_pixels = |d: f32| ((d * scale) - (d * scale).round()).abs() < 1e-3;
+        let same_shape = xform.m11 == cached.xform.m11
+            && xform.m12 == cached.xform.m12
+            && xform.m21 == cached.xform.m21
+            && xform.m22 == cached.xform.m22;
+        let area = cached.area.translate(moved.cast_unit());
+        let bounds = WorldRect::new(WorldPoint::zero(), scope.area.size);
+        if cached.key != key
+            || !same_shape
+            || !whole_pixels(moved.x)
+            || !whole_pixels(moved.y)
+            || (cached.clipped && moved != LocalOffset::zero())
+            || !(cached.clipped || bounds.contains_rect(&area))
+        {
+            return false;
+        }
+
+        cached.used = true;
+        let request = LayerRequest {
+            texture: cached.texture.clone(),
+            area,
+            style: LayerStyle::default(),
+            shadow_offset: [0.0, 0.0],
+            clip: scope.clips.last().copied(),
+            mask: None,
+            marker,
+        };
+        self.draw_pending(parent, Pending::Layer(request));
+        true
+    }
+
+    /// Renders the current layer and composites it after what `parent`
+    /// has drawn, returning its texture, where it went, and whether it was
+    /// cut off.
+    fn finish_layer(
+        &mut self,
+        parent: &mut Painter,
+        mut layer: Painter,
+        xform: LocalToWorld,
+        style: LayerStyle,
+    ) -> (Rc<LayerTexture>, WorldRect, bool) {
+        self.end_pass(&mut layer);
+        let layers = self.layer_renderer.as_mut().unwrap();
+        layers.vgers.extend(layer.into_layer_vger());
+        let mut scope = layers.scopes.pop().unwrap();
+        let scale = layers.scale;
+
+        let area = scope.area;
+        let texture = scope.target.take().unwrap();
+        let floating = std::mem::take(&mut scope.floating);
+        let bounds = WorldRect::new(WorldPoint::zero(), area.size);
+        layers.composite(&texture.view, floating, bounds);
+        let texture = Rc::new(texture);
+        let offset = xform.transform_vector(style.shadow.map_or(LocalOffset::zero(), |s| s.offset));
+        let marker = self.gpu_markers.current();
+        let clip = layers
+            .scopes
+            .last()
+            .and_then(|parent| parent.clips.last().copied());
+        let request = LayerRequest {
+            texture: texture.clone(),
+            area,
+            style,
+            shadow_offset: [offset.x * scale, -offset.y * scale],
+            clip: clip.filter(|_| !style.floating),
+            mask: scope.mask,
+            marker,
+        };
+        if style.floating {
+            if let Some(parent) = layers.scopes.last_mut() {
+                parent.floating.push(request);
+            }
+        } else {
+            self.draw_pending(parent, Pending::Layer(request));
+        }
+        (texture, area, scope.clipped)
+    }
+
+    /// Draws `image` after what `painter` has drawn, and before what it
+    /// draws next.
+    pub(crate) fn draw_image(&mut self, painter: &mut Painter, image: ImageRequest) {
+        self.draw_pending(painter, Pending::Image(image));
+    }
+
+    /// Draws `run` after what `painter` has drawn, and before what it
+    /// draws next over it.
+    pub(crate) fn draw_glyph_run(&mut self, painter: &mut Painter, run: GlyphRun) {
+        self.draw_pending(painter, Pending::Glyphs(run));
+    }
+
+    /// Draws `batch` after what `painter` has drawn, and before what it
+    /// draws next over it.
+    pub(crate) fn draw_particles(&mut self, painter: &mut Painter, batch: ParticleBatch) {
+        self.draw_pending(painter, Pending::Particles(batch));
+    }
+
+    /// Innermost clip of what's drawn with `painter` now, in the
+    /// coordinates of the layer, for items drawn between its passes.
+    pub(crate) fn pending_clip(&self, painter: &Painter) -> Option<WorldRect> {
+        match (self.current_clip(), painter.scissor_bounds()) {
+            (Some(clip), Some(scissor)) => Some(
+                clip.intersection(&scissor)
+                    .unwrap_or_else(|| WorldRect::new(clip.origin, euclid::Size2D::zero())),
+            ),
+            (clip, scissor) => clip.or(scissor),
+        }
+    }
+
+    fn draw_pending(&mut self, painter: &mut Painter, item: Pending) {
+        let Some(layers) = self.layer_renderer.as_mut() else {
+            return;
+        };
+        let Some(scope) = layers.scopes.last() else {
+            return;
+        };
+        if scope.target.is_none() {
+            let texture = layers.cleared_texture(scope.area.size);
+            layers.scopes.last_mut().unwrap().target = Some(texture);
+        }
+        if painter.untracked() {
+            // There's no telling what the item would go over.
+            self.end_pass(painter);
+        } else if painter.ended_early() {
+            // What's queued goes under the pass begun since, and the item
+            // over it.
+            self.render_pending();
+            painter.clear_queue();
+        }
+        let layers = self.layer_renderer.as_mut().unwrap();
+        let scope = layers.scopes.last_mut().unwrap();
+        painter.queue(item.bounds(), scope.target.as_ref().unwrap().view.clone());
+        scope.pending.push(item);
+    }
+
+    /// Renders the current layer's pending images, layers, text and
+    /// particles over its texture, in order.
+    fn render_pending(&mut self) {
+        let layers = self.layer_renderer.as_mut().unwrap();
+        let scope = layers.scopes.last_mut().unwrap();
+        let pending = std::mem::take(&mut scope.pending);
+        let Some(target) = scope.target.as_ref().map(|target| target.view.clone()) else {
+            return;
+        };
+        let start = Stopwatch::start();
+        let size = scope.area.size;
+        let bounds = WorldRect::new(WorldPoint::zero(), size);
+
+        // Draws runs of images, of layers, of text, and of particles, in
+        // order.
+        let (mut images, mut requests, mut runs) = (vec![], vec![], vec![]);
+        let mut batches = vec![];
+        let mut pending = pending.into_iter().peekable();
+        while let Some(item) = pending.next() {
+            let kind = std::mem::discriminant(&item);
+            match item {
+                Pending::Image(image) => images.push(image),
+                Pending::Layer(request) => requests.push(request),
+                Pending::Glyphs(run) => runs.push(run),
+                Pending::Particles(batch) => batches.push(batch),
+            }
+            if pending.peek().map(std::mem::discriminant) == Some(kind) {
+                continue;
+            }
+            if !images.is_empty() {
+                self.image_renderer
+                    .get_or_insert_with(|| ImageRenderer::new(&layers.device, layers.format))
+                    .draw(
+                        &layers.device,
+                        &layers.queue,
+                        &target,
+                        &images,
+                        size,
+                        layers.scale,
+                    );
+                images.clear();
+            }
+            if !requests.is_empty() {
+                layers.composite(&target, std::mem::take(&mut requests), bounds);
+            }
+            if !runs.is_empty() {
+                let lcd = self.lcd_text;
+                self.glyph_renderer
+                    .get_or_insert_with(|| GlyphRenderer::new(&layers.device, layers.format, lcd))
+                    .draw(
+                        &layers.device,
+                        &layers.queue,
+                        &target,
+                        &runs,
+                        size,
+                        layers.scale,
+                    );
+                runs.clear();
+            }
+            if !batches.is_empty() {
+                self.particle_renderer
+                    .get_or_insert_with(|| ParticleRenderer::new(&layers.device, layers.format))
+                    .draw(
+                        &layers.device,
+                        &layers.queue,
+                        &target,
+                        &batches,
+                        size,
+                        layers.scale,
+                    );
+                batches.clear();
+            }
+        }
+        layers.pass_time += start.elapsed();
+    }
+
+    /// Renders what `painter` has drawn since its pass began, and the
+    /// current layer's pending images, layers, text and particles, into
+    /// its texture. Does nothing if the layer has no texture, as for a
+    /// frame without anything drawn by rui, which vger draws into the
+    /// frame's target itself. Otherwise `LayerRenderer::end_frame`
+    /// composites the frame's texture.
+    pub(crate) fn end_pass(&mut self, painter: &mut Painter) {
+        let Some(target) = self
+            .layer_renderer
+            .as_ref()
+            .and_then(|layers| layers.scopes.last())
+            .and_then(|scope| scope.target.as_ref())
+            .map(|target| target.view.clone())
+        else {
+            return;
+        };
+        let queued_after = painter.queued_after();
+        if !queued_after {
+            self.render_pending();
+        }
+        let start = Stopwatch::start();
+        painter.end_pass(&target);
+        let elapsed = start.elapsed() + painter.take_pass_time();
+        if queued_after {
+            self.render_pending();
+        }
+        self.layer_renderer.as_mut().unwrap().pass_time += elapsed;
+    }
+
+    /// Renders what `painter` has drawn so far, so what it draws next goes
+    /// in a pass of its own. Gives the frame a texture, if it has none and
+    /// anything's been drawn.
+    pub(crate) fn split_pass(&mut self, painter: &mut Painter) {
+        let Some(layers) = self.layer_renderer.as_mut() else {
+            return;
+        };
+        let Some(scope) = layers.scopes.last() else {
+            return;
+        };
+        if !painter.drawn() {
+            return;
+        }
+        if scope.target.is_none() {
+            let texture = layers.cleared_texture(scope.area.size);
+            layers.scopes.last_mut().unwrap().target = Some(texture);
+        }
+        self.end_pass(painter);
+    }
+
+    /// Records a clip entered while drawing, in the current `Vger`'s
+    /// coordinates, which images and layers drawn within it are clipped to.
+    pub(crate) fn push_clip(&mut self, rect: WorldRect) {
+        if let Some(scope) = self
+            .layer_renderer
+            .as_mut()
+            .and_then(|layers| layers.scopes.last_mut())
+        {
+            let clip = match scope.clips.last() {
+                Some(outer) => outer
+                    .intersection(&rect)
+                    .unwrap_or_else(|| WorldRect::new(rect.origin, euclid::Size2D::zero())),
+                None => rect,
+            };
+            scope.clips.push(clip);
+        }
+    }
+
+    /// The innermost clip being drawn in, from `push_clip`.
+    pub(crate) fn current_clip(&self) -> Option<WorldRect> {
+        self.layer_renderer
+            .as_ref()?
+            .scopes
+            .last()?
+            .clips
+            .last()
+            .copied()
+    }
+
+    pub(crate) fn pop_clip(&mut self) {
+        if let Some(scope) = self
+            .layer_renderer
+            .as_mut()
+            .and_then(|layers| layers.scopes.last_mut())
+        {
+            scope.clips.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_decompose() {
+        let m = LocalToWorld::identity()
+            .pre_rotate(euclid::Angle::radians(0.3))
+            .pre_scale(2.0, 0.5)
+            .pre_rotate(euclid::Angle::radians(-1.1));
+        let (phi, scale, theta) = decompose(&m);
+        let n = LocalToWorld::identity()
+            .pre_rotate(euclid::Angle::radians(phi))
+            .pre_scale(scale.x, scale.y)
+            .pre_rotate(euclid::Angle::radians(theta));
+        let p = LocalPoint::new(3.0, -2.0);
+        assert!((m.transform_point(p) - n.transform_point(p)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_layer_margin() {
+        let style = LayerStyle {
+            blur: 2.0,
+            shadow: Some(Shadow {
+                radius: 4.0,
+                offset: LocalOffset::new(1.0, -3.0),
+                color: BLACK,
+            }),
+            ..LayerStyle::default()
+        };
+        assert_eq!(style.margin(), 7.0);
+        assert_eq!(LayerStyle::default().margin(), 0.0);
+    }
+
+    #[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_layer_draw_order() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let blue = Color::new(0.0, 0.0, 1.0, 1.0);
+
+        // The square is drawn after the layer, so it goes over it.
+        let view = zstack((
+            rectangle().color(red).opacity(0.5),
+            rectangle().color(blue).size([10.0, 10.0]),
+        ));
+        let image = test_render(&view, Size::new(40.0, 20.0));
+        assert_eq!(image.get_pixel(5, 15).0, [0, 0, 255, 255]);
+        let [r, g, b, _] = image.get_pixel(35, 2).0;
+        assert!(r > 100 && g < 100 && b < 100, "layer not composited");
+    }
+}
@@ -3,6 +3,43 @@ pub trait Lens<T, U>: Clone + Copy + 'static {
     fn focus_mut<'a>(&self, data: &'a mut T) -> &'a mut U;
 }
 
+/// Lens built from a pair of closures. See `lens`.
+#[derive(Clone, Copy)]
+pub struct FnLens<F, G> {
+    focus: F,
+    focus_mut: G,
+}
+
+impl<T, U, F, G> Lens<T, U> for FnLens<F, G>
+where
+    F: Fn(&T) -> &U + Clone + Copy + 'static,
+    G: Fn(&mut T) -> &mut U + Clone + Copy + 'static,
+{
+    fn focus<'a>(&self, data: &'a T) -> &'a U {
+        (self.focus)(data)
+    }
+    fn focus_mut<'a>(&self, data: &'a mut T) -> &'a mut U {
+        (self.focus_mut)(data)
+    }
+}
+
+/// Creates a lens from closures, as an alternative to `make_lens!`.
+///
+/// ```
+/// # use rui::*;
+/// struct MyState {
+///     value: f32,
+/// }
+/// let l = lens(|s: &MyState| &s.value, |s: &mut MyState| &mut s.value);
+/// ```
+pub fn lens<T, U, F, G>(focus: F, focus_mut: G) -> FnLens<F, G>
+where
+    F: Fn(&T) -> &U + Clone + Copy + 'static,
+    G: Fn(&mut T) -> &mut U + Clone + Copy + 'static,
+{
+    FnLens { focus, focus_mut }
+}
+
 #[macro_export]
 macro_rules! make_lens {
     ($lens_name: ident, $from: ty, $to: ty, $field: ident) => {
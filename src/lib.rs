@@ -1,12 +1,15 @@
 // #![feature(type_alias_impl_trait)]
 
-use vger::color::*;
-use vger::{LineMetrics, PaintIndex, Vger};
+use vger::{PaintIndex, Vger};
 
-#[cfg(feature = "winit")]
 #[macro_use]
 extern crate lazy_static;
 
+// So `#[derive(Model)]` works inside rui too.
+extern crate self as rui;
+
+pub use rui_derive::Model;
+
 mod view;
 pub use view::*;
 
@@ -25,6 +28,9 @@ pub use lens::*;
 mod binding;
 pub use binding::*;
 
+pub mod geom;
+pub use geom::*;
+
 mod context;
 pub use context::*;
 
@@ -34,12 +40,116 @@ pub use views::*;
 mod paint;
 pub use paint::*;
 
+mod attributed_text;
+pub use attributed_text::*;
+
+mod font;
+pub use font::*;
+
+mod text_layout;
+pub(crate) use text_layout::*;
+
+mod localization;
+pub use localization::*;
+
+mod effect;
+pub use effect::*;
+
+mod capture;
+pub use capture::*;
+
+mod markers;
+pub(crate) use markers::*;
+
+mod images;
+pub use images::*;
+
+mod glyphs;
+pub use glyphs::SubpixelOrder;
+pub(crate) use glyphs::*;
+
+mod layers;
+pub(crate) use layers::*;
+
+mod painter;
+pub use painter::*;
+
+mod view_tree;
+pub use view_tree::*;
+
+#[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+mod snapshot;
+#[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+pub use snapshot::*;
+
+mod idle;
+pub use idle::*;
+
+mod task;
+pub(crate) use task::*;
+
+mod pacing;
+#[cfg(feature = "winit")]
+pub(crate) use pacing::*;
+
+mod animate;
+pub use animate::*;
+
+mod profile;
+pub use profile::*;
+
+mod strict;
+pub use strict::*;
+
+mod observe;
+pub use observe::*;
+
+mod app;
+pub use app::*;
+
+mod menu;
+pub use menu::*;
+
+mod access;
+pub(crate) use access::*;
+
+mod touch;
+pub(crate) use touch::*;
+
+mod dialog;
+pub use dialog::*;
+
+mod harness;
+pub use harness::*;
+
+#[cfg(feature = "bench")]
+mod bench;
+#[cfg(feature = "bench")]
+pub use bench::*;
+
+mod script;
+pub use script::*;
+
+mod remote;
+pub use remote::*;
+
+#[cfg(all(feature = "devtools", not(target_arch = "wasm32")))]
+mod devtools;
+#[cfg(all(feature = "devtools", not(target_arch = "wasm32")))]
+pub use devtools::*;
+
+mod persist;
+pub use persist::*;
+
 mod modifiers;
 pub use modifiers::*;
 
 mod colors;
 pub use colors::*;
 
+mod theme;
+pub use theme::*;
+
 mod align;
 pub use align::*;
 
@@ -52,6 +162,9 @@ mod winit_event_loop;
 #[cfg(feature = "winit")]
 pub use winit_event_loop::*;
 
+mod embed;
+pub use embed::*;
+
 // See https://rust-lang.github.io/api-guidelines/future-proofing.html
 pub(crate) mod private {
     pub trait Sealed {}
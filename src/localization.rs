@@ -0,0 +1,330 @@
+//! Strings in the user's language, and which way their language is
+//! written.
+
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Languages written right to left.
+const RTL_LANGUAGES: [&str; 10] = ["ar", "ckb", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+/// Error from loading a `Bundle`.
+#[derive(Debug)]
+pub enum LocalizationError {
+    Io(std::io::Error),
+
+    /// A line which isn't a comment or `key = value`, counting from 1.
+    Parse(usize),
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalizationError::Io(err) => write!(f, "couldn't read strings: {}", err),
+            LocalizationError::Parse(line) => {
+                write!(f, "line {} isn't a comment or `key = value`", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocalizationError {}
+
+/// Which way rows of views run, for the views inside a view with
+/// `layout_direction` or `localization`. Right to left puts the first
+/// view of an `hstack` on the right, and swaps leading and trailing
+/// padding.
+///
+/// rui doesn't reorder the characters of right-to-left text yet, so text
+/// is still drawn, and the arrow keys move through it, left to right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LayoutDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+impl LayoutDirection {
+    /// The direction a locale like `"he"` or `"ar-EG"` is written in.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or("");
+        if RTL_LANGUAGES.contains(&language.to_lowercase().as_str()) {
+            LayoutDirection::RightToLeft
+        } else {
+            LayoutDirection::LeftToRight
+        }
+    }
+
+    pub fn is_rtl(self) -> bool {
+        self == LayoutDirection::RightToLeft
+    }
+}
+
+/// Strings for one locale, by key.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bundle {
+    locale: String,
+    strings: HashMap<String, String>,
+}
+
+impl Bundle {
+    pub fn new(locale: &str) -> Self {
+        Self {
+            locale: locale.to_string(),
+            strings: HashMap::new(),
+        }
+    }
+
+    /// Reads strings from lines of `key = value`. Blank lines and lines
+    /// starting with `#` are skipped, and `\n` in a value is a line break.
+    pub fn parse(locale: &str, source: &str) -> Result<Self, LocalizationError> {
+        let mut bundle = Self::new(locale);
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .ok_or(LocalizationError::Parse(i + 1))?;
+            bundle.insert(key.trim(), &value.trim().replace("\\n", "\n"));
+        }
+        Ok(bundle)
+    }
+
+    /// Reads a file of strings, as with `parse`.
+    pub fn open(locale: &str, path: impl AsRef<Path>) -> Result<Self, LocalizationError> {
+        let source = std::fs::read_to_string(path).map_err(LocalizationError::Io)?;
+        Self::parse(locale, &source)
+    }
+
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.strings.insert(key.to_string(), value.to_string());
+    }
+
+    /// Adds a string, for building bundles in code.
+    pub fn string(mut self, key: &str, value: &str) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(|s| s.as_str())
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+}
+
+/// Bundles for each locale the app supports, and which one to show. Pass
+/// it to the `localization` modifier, and look up strings with
+/// `localized_text` or `Context::localized`.
+///
+/// Strings missing from the locale's bundle come from its language's
+/// bundle, so `"fr-CA"` falls back to `"fr"`, then from the fallback
+/// locale, and finally show their key. Cheap to clone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Localization {
+    bundles: Rc<HashMap<String, Bundle>>,
+    locale: String,
+    fallback: String,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::new("en")
+    }
+}
+
+impl Localization {
+    /// No bundles yet, falling back to `fallback`'s strings.
+    pub fn new(fallback: &str) -> Self {
+        Self {
+            bundles: Rc::new(HashMap::new()),
+            locale: fallback.to_string(),
+            fallback: fallback.to_string(),
+        }
+    }
+
+    pub fn bundle(mut self, bundle: Bundle) -> Self {
+        Rc::make_mut(&mut self.bundles).insert(bundle.locale.clone(), bundle);
+        self
+    }
+
+    /// Shows strings for `locale`. The bundles are shared, so switching is
+    /// cheap.
+    pub fn with_locale(&self, locale: &str) -> Self {
+        Self {
+            locale: locale.to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Which way the locale's language is written.
+    pub fn direction(&self) -> LayoutDirection {
+        LayoutDirection::for_locale(&self.locale)
+    }
+
+    /// Locales supported, sorted.
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.bundles.keys().map(|l| l.as_str()).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// The string for `key`, or the key itself if no bundle has it.
+    pub fn string(&self, key: &str) -> String {
+        let language = self.locale.split(['-', '_']).next().unwrap_or("");
+        [self.locale.as_str(), language, self.fallback.as_str()]
+            .iter()
+            .filter_map(|locale| self.bundles.get(*locale)?.get(key))
+            .next()
+            .unwrap_or(key)
+            .to_string()
+    }
+
+    /// The string for `key`, with each `{name}` in it replaced by the
+    /// value for `name` in `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut s = self.string(key);
+        for (name, value) in args {
+            s = s.replace(&format!("{{{}}}", name), value);
+        }
+        s
+    }
+}
+
+impl Context {
+    /// The string for `key` in the environment's `Localization`.
+    pub fn localized(&mut self, key: &str) -> String {
+        self.init_env(&Localization::default).string(key)
+    }
+
+    /// Which way rows of views run here. See `LayoutDirection`.
+    pub fn layout_direction(&mut self) -> LayoutDirection {
+        self.init_env(&LayoutDirection::default)
+    }
+}
+
+/// Shows the string for `key` in the environment's `Localization`.
+pub fn localized_text(key: &str) -> impl View {
+    let key = key.to_string();
+    env(move |l10n: Localization, _| text(&l10n.string(&key)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn l10n() -> Localization {
+        Localization::new("en")
+            .bundle(
+                Bundle::parse(
+                    "en",
+                    "# Greetings\nhello = Hello\n\nwelcome = Welcome, {name}!\nquit = Quit",
+                )
+                .unwrap(),
+            )
+            .bundle(Bundle::new("fr").string("hello", "Bonjour"))
+            .bundle(Bundle::new("fr-CA").string("quit", "Quitter"))
+            .bundle(Bundle::new("he").string("hello", "שלום"))
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert!(matches!(
+            Bundle::parse("en", "hello = Hello\noops"),
+            Err(LocalizationError::Parse(2))
+        ));
+
+        let l10n = l10n().with_locale("fr-CA");
+        assert_eq!(l10n.string("quit"), "Quitter");
+        assert_eq!(l10n.string("hello"), "Bonjour");
+        assert_eq!(l10n.string("welcome"), "Welcome, {name}!");
+        assert_eq!(l10n.string("missing"), "missing");
+        assert_eq!(l10n.format("welcome", &[("name", "Ada")]), "Welcome, Ada!");
+        assert_eq!(l10n.locales(), vec!["en", "fr", "fr-CA", "he"]);
+
+        assert_eq!(l10n.direction(), LayoutDirection::LeftToRight);
+        assert_eq!(
+            l10n.with_locale("he").direction(),
+            LayoutDirection::RightToLeft
+        );
+        assert!(LayoutDirection::for_locale("ar_EG").is_rtl());
+    }
+
+    #[test]
+    fn test_localized_text() {
+        let l10n = l10n();
+        let mut h = Harness::new(state(
+            || "en".to_string(),
+            move |locale, cx| {
+                let l10n = l10n.with_locale(&cx[locale]);
+                vstack((
+                    localized_text("hello"),
+                    button("he", move |cx| cx[locale] = "he".into()),
+                ))
+                .localization(l10n)
+            },
+        ));
+        h.expect_text("Hello");
+        h.click("he");
+        h.expect_text("שלום");
+    }
+
+    /// Where "one" and "two" are laid out.
+    fn positions(view: impl View) -> (Rect, Rect) {
+        let mut h = Harness::new(view);
+        (h.find_text("one").unwrap(), h.find_text("two").unwrap())
+    }
+
+    #[test]
+    fn test_rtl_layout() {
+        let row = || {
+            hstack((
+                text("one"),
+                text("two").padding(DirectionalInsets::new(0.0, 30.0, 0.0, 0.0)),
+            ))
+        };
+
+        // Leading padding is between the two either way.
+        let (one, two) = positions(row());
+        assert!(two.origin.x - (one.origin.x + one.width()) >= 30.0);
+
+        let (one, two) = positions(row().layout_direction(LayoutDirection::RightToLeft));
+        assert!(one.origin.x - (two.origin.x + two.width()) >= 30.0);
+    }
+
+    #[test]
+    fn test_rtl_caret() {
+        let editor = state(String::new, |s, cx| {
+            vstack((
+                text_editor(s).size([200.0, 30.0]),
+                text(&cx[s]).padding(Auto),
+            ))
+            .layout_direction(LayoutDirection::RightToLeft)
+        });
+
+        // Text is still laid out left to right, so the arrows move the
+        // way they're pointing.
+        interaction_test! { Harness::new(editor);
+            key Tab;
+            type "ab";
+            key ArrowLeft;
+            type "c";
+            expect_text "acb";
+            key ArrowRight;
+            type "d";
+            expect_text "acbd";
+        };
+    }
+}
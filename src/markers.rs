@@ -0,0 +1,101 @@
+//! GPU debug groups, so frame captures in RenderDoc and other GPU
+//! debuggers show which views drew what. Enable with the `gpu-markers`
+//! feature. Widgets with an accessibility role get a group automatically,
+//! and the `debug_group` modifier names any other subtree.
+//!
+//! What rui renders itself, like images, layers and effects, is drawn
+//! within a debug group named for the innermost group around it. vger
+//! renders its shapes in passes of its own, which can't be given groups,
+//! so with markers enabled vger's pass ends wherever a group does, and
+//! each pass is labelled with the group it drew.
+
+use crate::*;
+
+/// Debug groups entered while drawing a frame.
+pub(crate) struct GpuMarkers {
+    enabled: bool,
+    stack: Vec<String>,
+}
+
+impl GpuMarkers {
+    pub fn new() -> Self {
+        Self::with_enabled(cfg!(feature = "gpu-markers"))
+    }
+
+    pub(crate) fn with_enabled(enabled: bool) -> Self {
+        Self {
+            enabled,
+            stack: vec![],
+        }
+    }
+
+    /// Enters a group. `name` is only called when markers are enabled.
+    pub fn push(&mut self, name: impl FnOnce() -> String) {
+        if self.enabled {
+            self.stack.push(name());
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if self.enabled {
+            self.stack.pop();
+        }
+    }
+
+    /// Path of the innermost group, like `sidebar/Button`, for labelling
+    /// draws rui makes itself, such as images and effects.
+    pub fn current(&self) -> Option<String> {
+        if self.stack.is_empty() {
+            None
+        } else {
+            Some(self.stack.join("/"))
+        }
+    }
+
+    /// Forgets groups a view left open, once the frame is drawn.
+    pub fn end_frame(&mut self) {
+        self.stack.clear();
+    }
+}
+
+impl Context {
+    /// Enters a debug group while drawing with `painter`.
+    pub(crate) fn push_gpu_marker(&mut self, painter: &mut Painter, name: impl FnOnce() -> String) {
+        if self.gpu_markers.enabled {
+            self.split_pass(painter);
+            self.gpu_markers.push(name);
+            painter.set_label(self.gpu_markers.current());
+        }
+    }
+
+    /// Leaves the group entered last.
+    pub(crate) fn pop_gpu_marker(&mut self, painter: &mut Painter) {
+        if self.gpu_markers.enabled {
+            self.split_pass(painter);
+            self.gpu_markers.pop();
+            painter.set_label(self.gpu_markers.current());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_marker_groups() {
+        let mut markers = GpuMarkers::with_enabled(true);
+        markers.push(|| "sidebar".into());
+        markers.push(|| "Button".into());
+        assert_eq!(markers.current().as_deref(), Some("sidebar/Button"));
+        markers.pop();
+        assert_eq!(markers.current().as_deref(), Some("sidebar"));
+        markers.end_frame();
+        assert_eq!(markers.current(), None);
+
+        let mut markers = GpuMarkers::with_enabled(false);
+        markers.push(|| panic!("name computed while disabled"));
+        assert_eq!(markers.current(), None);
+    }
+}
@@ -0,0 +1,736 @@
+use crate::*;
+use std::any::Any;
+use std::rc::Rc;
+
+type MenuAction = Rc<dyn Fn(&mut Context)>;
+
+/// An entry in a `menu`.
+#[derive(Clone)]
+pub struct MenuItem {
+    title: String,
+    key: Option<HotKey>,
+    action: MenuAction,
+}
+
+/// Calls `action` when the item is chosen.
+pub fn menu_item(title: &str, action: impl Fn(&mut Context) + 'static) -> MenuItem {
+    MenuItem {
+        title: title.into(),
+        key: None,
+        action: Rc::new(action),
+    }
+}
+
+impl MenuItem {
+    /// Chooses the item when `key` is pressed with command (on macOS) or
+    /// control.
+    pub fn hotkey(self, key: HotKey) -> Self {
+        Self {
+            key: Some(key),
+            ..self
+        }
+    }
+}
+
+/// A menu in the menu bar, like "File" or "Edit".
+#[derive(Clone)]
+pub struct Menu {
+    title: String,
+    items: Vec<MenuItem>,
+}
+
+pub fn menu(title: &str, items: Vec<MenuItem>) -> Menu {
+    Menu {
+        title: title.into(),
+        items,
+    }
+}
+
+/// Menus for an app. Add them with `App::menu_bar`.
+///
+/// The winit backend shows them in the platform's menu bar on macOS and
+/// Windows. Elsewhere, or with backends which don't call
+/// `set_native_menus`, the menu bar is drawn at the top of the window.
+/// Each item is also an app command named "Menu:Item".
+///
+/// ```no_run
+/// # use rui::*;
+/// App::new(text("hello"))
+///     .menu_bar(menu_bar(vec![menu(
+///         "File",
+///         vec![menu_item("Open", |_| println!("open")).hotkey(HotKey::KeyO)],
+///     )]))
+///     .run();
+/// ```
+pub struct MenuBar {
+    menus: Vec<Menu>,
+}
+
+pub fn menu_bar(menus: Vec<Menu>) -> MenuBar {
+    MenuBar { menus }
+}
+
+impl AppConfig {
+    /// Adds menus. Items of menus with the same title as ones already
+    /// added are appended to those.
+    pub fn menu_bar(&mut self, bar: MenuBar) -> &mut Self {
+        for menu in bar.menus {
+            for item in &menu.items {
+                let action = item.action.clone();
+                self.command(
+                    &format!("{}:{}", menu.title, item.title),
+                    item.key,
+                    move |cx| action(cx),
+                );
+            }
+            match self.menus.iter_mut().find(|m| m.title == menu.title) {
+                Some(existing) => existing.items.extend(menu.items),
+                None => self.menus.push(menu),
+            }
+        }
+        self
+    }
+}
+
+impl Context {
+    /// Backends which show app commands in a native menu bar call this, so
+    /// `menu_bar` menus aren't drawn in the window too.
+    pub fn set_native_menus(&mut self, native: bool) {
+        self.native_menus = native;
+        self.set_dirty();
+    }
+}
+
+const MENU_BAR_HEIGHT: f32 = 24.0;
+const MENU_ITEM_HEIGHT: f32 = 22.0;
+const MENU_TEXT_SIZE: u32 = 14;
+
+/// Space either side of titles and items.
+const MENU_PADDING: f32 = 10.0;
+
+const MENU_MIN_WIDTH: f32 = 120.0;
+
+/// Space between an item's title and its hotkey.
+const HOTKEY_SPACE: f32 = 24.0;
+
+fn hotkey_label(key: HotKey) -> String {
+    let letter = format!("{:?}", key);
+    let letter = letter.trim_start_matches("Key");
+    if cfg!(target_os = "macos") {
+        format!("Cmd+{}", letter)
+    } else {
+        format!("Ctrl+{}", letter)
+    }
+}
+
+/// Is `key` the letter of `hotkey`, with the modifier menus use?
+fn is_hotkey(hotkey: HotKey, key: &Key, mods: KeyboardModifiers) -> bool {
+    match key {
+        Key::Character(c) if mods.primary() => {
+            format!("Key{}", c.to_ascii_uppercase()) == format!("{:?}", hotkey)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct MenuBarState {
+    /// The menu showing its items.
+    open: Option<usize>,
+
+    /// The item of the open menu under the mouse.
+    hover: Option<usize>,
+
+    /// Where each title starts and ends along the bar.
+    titles: Vec<(f32, f32)>,
+
+    /// Width of each menu's items.
+    widths: Vec<f32>,
+
+    size: LocalSize,
+}
+
+fn menu_bar_state(vid: ViewId, cx: &mut Context) -> &mut MenuBarState {
+    cx.init_state(vid, &MenuBarState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Draws an app's menus above its root view, when there are no native
+/// menus.
+pub(crate) struct MenuBarView<V> {
+    pub(crate) child: V,
+    pub(crate) menus: Vec<Menu>,
+}
+
+impl<V> MenuBarView<V>
+where
+    V: View,
+{
+    fn is_shown(&self, cx: &Context) -> bool {
+        !self.menus.is_empty() && !cx.native_menus
+    }
+
+    fn title_rect(state: &MenuBarState, i: usize) -> LocalRect {
+        let (start, end) = state.titles[i];
+        LocalRect::new(
+            [start, state.size.height - MENU_BAR_HEIGHT].into(),
+            [end - start, MENU_BAR_HEIGHT].into(),
+        )
+    }
+
+    fn item_rect(state: &MenuBarState, menu: usize, item: usize) -> LocalRect {
+        LocalRect::new(
+            [
+                state.titles[menu].0,
+                state.size.height - MENU_BAR_HEIGHT - (item + 1) as f32 * MENU_ITEM_HEIGHT,
+            ]
+            .into(),
+            [state.widths[menu], MENU_ITEM_HEIGHT].into(),
+        )
+    }
+
+    fn title_at(state: &MenuBarState, pt: LocalPoint) -> Option<usize> {
+        (0..state.titles.len()).find(|i| Self::title_rect(state, *i).contains(pt))
+    }
+
+    fn item_at(&self, state: &MenuBarState, pt: LocalPoint) -> Option<usize> {
+        let menu = state.open?;
+        (0..self.menus[menu].items.len()).find(|i| Self::item_rect(state, menu, *i).contains(pt))
+    }
+
+    fn in_bar(state: &MenuBarState, pt: LocalPoint) -> bool {
+        pt.y >= state.size.height - MENU_BAR_HEIGHT
+    }
+
+    fn set_state(vid: ViewId, state: MenuBarState, cx: &mut Context) {
+        cx[StateHandle::<MenuBarState>::new(vid)] = state;
+    }
+
+    fn draw_label(
+        cx: &Context,
+        vger: &mut Painter,
+        label: &str,
+        x: f32,
+        rect: LocalRect,
+        color: Color,
+    ) {
+        let bounds = vger.text_bounds(label, MENU_TEXT_SIZE, None);
+        vger.save();
+        vger.translate([
+            x - bounds.origin.x,
+            rect.min_y() + (rect.height() - bounds.height()) / 2.0 - bounds.origin.y,
+        ]);
+        cx.snap_text(vger);
+        vger.text(label, MENU_TEXT_SIZE, color, None);
+        vger.restore();
+    }
+}
+
+impl<V> View for MenuBarView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if self.is_shown(cx) {
+            let vid = cx.view_id(path);
+            let mut state = menu_bar_state(vid, cx).clone();
+            match event {
+                Event::TouchBegin { id, position } => {
+                    if Self::in_bar(&state, *position) {
+                        let title = Self::title_at(&state, *position);
+                        state.open = if state.open == title { None } else { title };
+                        state.hover = None;
+                        Self::set_state(vid, state, cx);
+                        cx.touches[*id] = vid;
+                        return;
+                    }
+                    if let Some(menu) = state.open {
+                        // Clicking outside the open menu just closes it.
+                        let item = self.item_at(&state, *position);
+                        state.open = None;
+                        state.hover = None;
+                        Self::set_state(vid, state, cx);
+                        cx.touches[*id] = vid;
+                        if let Some(item) = item {
+                            (self.menus[menu].items[item].action)(cx);
+                        }
+                        return;
+                    }
+                }
+                Event::TouchMove { position, .. } if state.open.is_some() => {
+                    let hover = self.item_at(&state, *position);
+                    if hover != state.hover {
+                        state.hover = hover;
+                        Self::set_state(vid, state, cx);
+                    }
+                }
+                Event::TouchEnd { id, .. } if cx.touches[*id] == vid => {
+                    cx.touches[*id] = ViewId::default();
+                    return;
+                }
+                Event::Key(k) if k.pressed() == Some(Key::Escape) && state.open.is_some() => {
+                    state.open = None;
+                    Self::set_state(vid, state, cx);
+                    return;
+                }
+                _ => (),
+            }
+        }
+        // Native menus may only label hotkeys, as on Windows, so they're
+        // handled here either way.
+        if let Event::Key(k) = event {
+            if k.pressed().is_some() && !k.is_repeat() {
+                let item = self.menus.iter().flat_map(|menu| &menu.items).find(|item| {
+                    item.key
+                        .is_some_and(|hotkey| is_hotkey(hotkey, &k.key, k.mods))
+                });
+                if let Some(item) = item {
+                    (item.action)(cx);
+                    return;
+                }
+            }
+        }
+        let offset = cx.get_layout(path).offset;
+        path.push(0);
+        self.child
+            .process(&event.offset(-offset), path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let offset = args.cx.get_layout(path).offset;
+        args.vger.save();
+        args.vger.translate(offset);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+        args.vger.restore();
+
+        if !self.is_shown(args.cx) {
+            return;
+        }
+        let vid = args.cx.view_id(path);
+        let state = menu_bar_state(vid, args.cx).clone();
+        let theme = args.cx.theme();
+        let vger = &mut args.vger;
+
+        let bar = LocalRect::new(
+            [0.0, state.size.height - MENU_BAR_HEIGHT].into(),
+            [state.size.width, MENU_BAR_HEIGHT].into(),
+        );
+        let paint = vger.color_paint(theme.control_background);
+        vger.fill_rect(bar, 0.0, paint);
+
+        for (i, menu) in self.menus.iter().enumerate() {
+            let rect = Self::title_rect(&state, i);
+            if state.open == Some(i) {
+                let paint = vger.color_paint(theme.accent_background);
+                vger.fill_rect(rect, 0.0, paint);
+            }
+            Self::draw_label(
+                args.cx,
+                vger,
+                &menu.title,
+                rect.min_x() + MENU_PADDING,
+                rect,
+                theme.text,
+            );
+        }
+
+        if let Some(open) = state.open {
+            let items = &self.menus[open].items;
+            let first = Self::item_rect(&state, open, 0);
+            let dropdown = LocalRect::new(
+                [
+                    first.min_x(),
+                    first.max_y() - items.len() as f32 * MENU_ITEM_HEIGHT,
+                ]
+                .into(),
+                [first.width(), items.len() as f32 * MENU_ITEM_HEIGHT].into(),
+            );
+            let paint = vger.color_paint(theme.button_background);
+            vger.fill_rect(dropdown, 0.0, paint);
+
+            for (i, item) in items.iter().enumerate() {
+                let rect = Self::item_rect(&state, open, i);
+                let background = if state.hover == Some(i) {
+                    let paint = vger.color_paint(theme.accent);
+                    vger.fill_rect(rect, 0.0, paint);
+                    theme.accent
+                } else {
+                    theme.button_background
+                };
+                let color = theme.foreground_on(background);
+                Self::draw_label(
+                    args.cx,
+                    vger,
+                    &item.title,
+                    rect.min_x() + MENU_PADDING,
+                    rect,
+                    color,
+                );
+                if let Some(key) = item.key {
+                    let label = hotkey_label(key);
+                    let width = vger.text_bounds(&label, MENU_TEXT_SIZE, None).width();
+                    let x = rect.max_x() - MENU_PADDING - width;
+                    Self::draw_label(args.cx, vger, &label, x, rect, color);
+                }
+            }
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let size = args.sz;
+        let shown = self.is_shown(args.cx);
+        let bar_height = if shown { MENU_BAR_HEIGHT } else { 0.0 };
+
+        path.push(0);
+        let child_size = LocalSize::new(size.width, (size.height - bar_height).max(0.0));
+        let child_size = self.child.layout(path, &mut args.size(child_size));
+        path.pop();
+        // Keep the child just under the bar.
+        let child_offset = [0.0, size.height - bar_height - child_size.height];
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: child_offset.into(),
+            },
+        );
+        if !shown {
+            return size;
+        }
+
+        let measure = |args: &mut LayoutArgs, label: &str| {
+            (args.text_bounds)(label, MENU_TEXT_SIZE, None).width()
+        };
+        let mut state = MenuBarState {
+            size,
+            ..Default::default()
+        };
+        let mut x = 0.0;
+        for menu in &self.menus {
+            let width = measure(args, &menu.title) + 2.0 * MENU_PADDING;
+            state.titles.push((x, x + width));
+            x += width;
+
+            let mut width: f32 = 0.0;
+            for item in &menu.items {
+                let mut item_width = measure(args, &item.title);
+                if let Some(key) = item.key {
+                    item_width += HOTKEY_SPACE + measure(args, &hotkey_label(key));
+                }
+                width = width.max(item_width);
+            }
+            state
+                .widths
+                .push((width + 2.0 * MENU_PADDING).max(MENU_MIN_WIDTH));
+        }
+
+        let vid = args.cx.view_id(path);
+        let previous = menu_bar_state(vid, args.cx);
+        state.open = previous.open;
+        state.hover = previous.hover;
+        *previous = state.clone();
+
+        // Give titles and items layouts, so they can be found like text
+        // views.
+        for (i, menu) in self.menus.iter().enumerate() {
+            path.push(i as u64 + 1);
+            let rect = Self::title_rect(&state, i);
+            args.cx.update_layout(
+                path,
+                LayoutBox {
+                    rect: LocalRect::new(LocalPoint::zero(), rect.size),
+                    offset: rect.origin.to_vector(),
+                },
+            );
+            if state.open == Some(i) {
+                for j in 0..menu.items.len() {
+                    path.push(j as u64);
+                    let rect = Self::item_rect(&state, i, j);
+                    args.cx.update_layout(
+                        path,
+                        LayoutBox {
+                            rect: LocalRect::new(LocalPoint::zero(), rect.size),
+                            offset: rect.origin.to_vector(),
+                        },
+                    );
+                    path.pop();
+                }
+            }
+            path.pop();
+        }
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(path, rect, xform);
+        }
+        let offset = cx.get_layout(path).offset;
+        path.push(0);
+        self.child.dirty(path, xform.pre_translate(offset), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if !self.is_shown(cx) {
+            path.push(0);
+            let id = self.child.hittest(path, pt, cx);
+            path.pop();
+            return id;
+        }
+
+        let vid = cx.view_id(path);
+        let state = menu_bar_state(vid, cx).clone();
+        for (i, menu) in self.menus.iter().enumerate() {
+            path.push(i as u64 + 1);
+            let rect = Self::title_rect(&state, i);
+            cx.probe_text(path, &menu.title, pt - rect.origin.to_vector());
+            if state.open == Some(i) {
+                for (j, item) in menu.items.iter().enumerate() {
+                    path.push(j as u64);
+                    let rect = Self::item_rect(&state, i, j);
+                    cx.probe_text(path, &item.title, pt - rect.origin.to_vector());
+                    path.pop();
+                }
+            }
+            path.pop();
+        }
+        if Self::in_bar(&state, pt) || self.item_at(&state, pt).is_some() {
+            return Some(vid);
+        }
+
+        let offset = cx.get_layout(path).offset;
+        path.push(0);
+        let id = self.child.hittest(path, pt - offset, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let vid = cx.view_id(path);
+        map.push(vid);
+        let open = cx
+            .state_map
+            .get(&vid)
+            .and_then(|holder| holder.state.downcast_ref::<MenuBarState>())
+            .and_then(|state| state.open);
+        for (i, menu) in self.menus.iter().enumerate() {
+            path.push(i as u64 + 1);
+            map.push(cx.view_id(path));
+            if open == Some(i) {
+                for j in 0..menu.items.len() {
+                    path.push(j as u64);
+                    map.push(cx.view_id(path));
+                    path.pop();
+                }
+            }
+            path.pop();
+        }
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for MenuBarView<V> {}
+
+/// Menus in the platform's menu bar, with muda. Each item's id is its
+/// command's name, which is sent to the main window when it's chosen.
+#[cfg(all(feature = "winit", any(target_os = "macos", target_os = "windows")))]
+pub(crate) mod native {
+
+    use super::*;
+    use muda::accelerator::{Accelerator, Code, CMD_OR_CTRL};
+    use std::sync::Mutex;
+
+    lazy_static! {
+        /// Commands of items chosen since the event loop last looked.
+        static ref CHOSEN: Mutex<Vec<String>> = Mutex::new(vec![]);
+    }
+
+    pub(crate) struct NativeMenus {
+        bar: muda::Menu,
+    }
+
+    impl NativeMenus {
+        /// Builds the platform's menus for `menus`. On macOS the first menu
+        /// is the app's, titled `app_name`, with the usual items.
+        pub fn new(menus: &[Menu], app_name: &str) -> Result<Self, muda::Error> {
+            let bar = muda::Menu::new();
+
+            #[cfg(target_os = "macos")]
+            {
+                use muda::PredefinedMenuItem;
+                bar.append(&muda::Submenu::with_items(
+                    app_name,
+                    true,
+                    &[
+                        &PredefinedMenuItem::hide(None),
+                        &PredefinedMenuItem::hide_others(None),
+                        &PredefinedMenuItem::show_all(None),
+                        &PredefinedMenuItem::separator(),
+                        &PredefinedMenuItem::quit(None),
+                    ],
+                )?)?;
+            }
+            #[cfg(not(target_os = "macos"))]
+            let _ = app_name;
+
+            for menu in menus {
+                let submenu = muda::Submenu::new(&menu.title, true);
+                for item in &menu.items {
+                    let accelerator = item.key.and_then(|key| {
+                        let code = format!("{:?}", key).parse::<Code>().ok()?;
+                        Some(Accelerator::new(Some(CMD_OR_CTRL), code))
+                    });
+                    submenu.append(&muda::MenuItem::with_id(
+                        format!("{}:{}", menu.title, item.title),
+                        &item.title,
+                        true,
+                        accelerator,
+                    ))?;
+                }
+                bar.append(&submenu)?;
+            }
+
+            muda::MenuEvent::set_event_handler(Some(|event: muda::MenuEvent| {
+                CHOSEN.lock().unwrap().push(event.id.0);
+                wake_event_loop();
+            }));
+            Ok(Self { bar })
+        }
+
+        /// Shows the menus: in `window` on Windows, and as the app's menus
+        /// on macOS, where they replace winit's.
+        pub fn show(&self, window: &winit::window::Window) -> Result<(), muda::Error> {
+            #[cfg(target_os = "windows")]
+            {
+                use winit::platform::windows::WindowExtWindows;
+                self.bar.init_for_hwnd(window.hwnd())
+            }
+            #[cfg(target_os = "macos")]
+            {
+                let _ = window;
+                self.bar.init_for_nsapp();
+                Ok(())
+            }
+        }
+    }
+
+    /// Takes the commands of items chosen since last called.
+    pub(crate) fn take_chosen() -> Vec<String> {
+        std::mem::take(&mut *CHOSEN.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+
+    fn app(log: Rc<RefCell<Vec<&'static str>>>) -> impl View {
+        let (open, save) = (log.clone(), log);
+        App::new(text("content"))
+            .menu_bar(menu_bar(vec![menu(
+                "File",
+                vec![
+                    menu_item("Open", move |_| open.borrow_mut().push("open")).hotkey(HotKey::KeyO),
+                    menu_item("Save", move |_| save.borrow_mut().push("save")),
+                ],
+            )]))
+            .build()
+            .0
+    }
+
+    #[test]
+    fn test_menu_bar() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut h = Harness::new(app(log.clone()));
+        h.expect_text("content");
+        h.expect_no_text("Open");
+
+        h.click("File");
+        h.expect_text("Open");
+        h.click("Save");
+        h.expect_no_text("Open");
+        assert_eq!(*log.borrow(), vec!["save"]);
+
+        // The content sits under the bar.
+        let file = h.find_text("File").unwrap();
+        let content = h.find_text("content").unwrap();
+        assert!(content.center().y < file.center().y);
+
+        let mods = KeyboardModifiers {
+            control: true,
+            command: true,
+            ..Default::default()
+        };
+        h.key_with_mods(Key::Character('o'), mods);
+        assert_eq!(*log.borrow(), vec!["save", "open"]);
+    }
+
+    #[test]
+    fn test_native_menus() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut h = Harness::new(app(log.clone()));
+        h.context().set_native_menus(true);
+        h.update();
+        h.expect_no_text("File");
+        h.expect_text("content");
+
+        // Hotkeys still work, for native menus which only label them.
+        let mods = KeyboardModifiers {
+            control: true,
+            command: true,
+            ..Default::default()
+        };
+        h.key_with_mods(Key::Character('o'), mods);
+        assert_eq!(*log.borrow(), vec!["open"]);
+
+        let view = app(log.clone());
+        let mut cx = Context::new();
+        let mut cmds = vec![];
+        cx.commands(&view, &mut cmds);
+        let paths: Vec<_> = cmds.iter().map(|cmd| cmd.path.as_str()).collect();
+        assert_eq!(paths, vec!["File:Open", "File:Save"]);
+        assert_eq!(cmds[0].key, Some(HotKey::KeyO));
+
+        cx.process(&view, &Event::Command("File:Open".into()));
+        assert_eq!(*log.borrow(), vec!["open", "open"]);
+    }
+}
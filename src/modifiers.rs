@@ -1,6 +1,51 @@
 use crate::*;
 use accesskit::Role;
 
+/// Applies modifiers to a view in order, so
+/// `modifiers!(view; padding: Auto, flex)` is
+/// `view.padding(Auto).flex()`. Each modifier is a method name, with an
+/// argument after a colon if it takes one.
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(modifiers!(text("hello");
+///     font_size: 24,
+///     padding: Auto,
+///     background: rectangle().color(AZURE_HIGHLIGHT),
+/// ));
+/// ```
+#[macro_export]
+macro_rules! modifiers {
+    ($view:expr; $($modifier:ident $(: $arg:expr)?),* $(,)?) => {
+        $view$(.$modifier($($arg)?))*
+    };
+}
+
+/// A reusable bundle of modifiers, applied with `.modifier(m)`.
+///
+/// The body wraps the view it's applied to in other views, so it's laid
+/// out, drawn and gets events just like a chain of built-in modifiers.
+///
+/// ```no_run
+/// # use rui::*;
+/// struct Card;
+///
+/// impl ViewModifier for Card {
+///     fn body(self, content: impl View) -> impl View {
+///         content
+///             .padding(Auto)
+///             .background(rectangle().corner_radius(5.0).color(AZURE_HIGHLIGHT))
+///     }
+/// }
+///
+/// rui(text("hello").modifier(Card));
+/// ```
+pub trait ViewModifier {
+    /// Builds the modified view from `content`, the view the modifier is
+    /// applied to.
+    fn body(self, content: impl View) -> impl View;
+}
+
 /// Modifiers common to all views.
 pub trait Modifiers: View + Sized {
     /// Calls a closure after rendering with context and delta time.
@@ -13,6 +58,11 @@ pub trait Modifiers: View + Sized {
         Background::new(self, background)
     }
 
+    /// Fills the view's bounds with a gradient or other paint behind it.
+    fn background_gradient(self, paint: impl Into<Paint>) -> Background<Self, Rectangle> {
+        self.background(rectangle().fill(paint))
+    }
+
     /// Adds a menu command.
     fn command<F: Fn(&mut Context) + 'static>(
         self,
@@ -36,6 +86,15 @@ pub trait Modifiers: View + Sized {
         Drag::new(self, f)
     }
 
+    /// Calls a function in response to a drag. Version which passes the
+    /// touch position in local coordinates instead of a delta.
+    fn drag_p<F: Fn(&mut Context, LocalPoint, GestureState, Option<MouseButton>) + 'static>(
+        self,
+        f: F,
+    ) -> DragP<Self, F> {
+        DragP::new(self, f)
+    }
+
     /// Calls a function in response to a drag. Version which passes in a binding.
     fn drag_s<
         T: 'static,
@@ -49,11 +108,57 @@ pub trait Modifiers: View + Sized {
         DragS::new(self, s, f)
     }
 
+    /// Lets the view be dragged onto views with `on_drop_target`, which
+    /// are given a clone of `payload`. While dragging, a copy of the view
+    /// follows the pointer. Unlike with `draggable(view)`, the view itself
+    /// stays where it is.
+    fn draggable<T: Clone + 'static>(self, payload: T) -> DragSource<Self, T> {
+        DragSource::new(self, payload)
+    }
+
+    /// Applies a custom WGSL shader to the view's rendered pixels.
+    fn effect(self, effect: ShaderEffect) -> EffectView<Self> {
+        EffectView::new(self, effect)
+    }
+
+    /// Shows a pointer shape while the mouse is over the view, like
+    /// `CursorIcon::Text` for text which can be selected.
+    fn cursor(self, icon: CursorIcon) -> CursorView<Self> {
+        CursorView::new(self, icon)
+    }
+
     /// Calls a function in response to a mouse hovering.
     fn hover<F: Fn(&mut Context, bool) + 'static>(self, f: F) -> Hover<Self, F> {
         Hover::new(self, f)
     }
 
+    /// Sets the font for text inside the view, from a family added with
+    /// `register_font`. Text with its own `font_size` keeps that size.
+    fn font(
+        self,
+        family: &str,
+        size: u32,
+        weight: FontWeight,
+        style: FontStyle,
+    ) -> SetenvView<Self, Font> {
+        self.env(Font::new(family, size, weight, style))
+    }
+
+    /// Lays out the views inside left to right or right to left.
+    fn layout_direction(self, direction: LayoutDirection) -> SetenvView<Self, LayoutDirection> {
+        self.env(direction)
+    }
+
+    /// Shows strings from `localization` inside the view, laid out in the
+    /// direction its locale is written.
+    fn localization(
+        self,
+        localization: Localization,
+    ) -> SetenvView<SetenvView<Self, LayoutDirection>, Localization> {
+        let direction = localization.direction();
+        self.env(direction).env(localization)
+    }
+
     /// Add an environment value.
     fn env<E: Clone + 'static>(self, value: E) -> SetenvView<Self, E> {
         SetenvView::new(self, value)
@@ -75,29 +180,185 @@ pub trait Modifiers: View + Sized {
         Geom::new(self, f)
     }
 
-    /// Responds to keyboard events
-    fn key<F: Fn(&mut Context, Key) + 'static>(self, f: F) -> KeyView<Self, F> {
+    /// Calls `f` with the view's size after layout, when it changes. `f`
+    /// can change state, unlike in layout itself.
+    fn on_size_change<F: Fn(&mut Context, Size) + 'static>(self, f: F) -> OnSizeChange<Self, F> {
+        OnSizeChange::new(self, f)
+    }
+
+    /// Calls `f` when the view is added to the tree, including when the
+    /// window first shows it. `f` can change state, as for starting a
+    /// timer or loading something the view shows.
+    fn on_appear<F: Fn(&mut Context) + 'static>(self, f: F) -> Lifecycle<Self> {
+        Lifecycle::new(self, LifecyclePhase::Appear, f)
+    }
+
+    /// Calls `f` when the view is taken out of the tree, as when going
+    /// back from a screen, before the state of the views inside is
+    /// dropped. Its counterpart for releasing what `on_appear` set up.
+    fn on_disappear<F: Fn(&mut Context) + 'static>(self, f: F) -> Lifecycle<Self> {
+        Lifecycle::new(self, LifecyclePhase::Disappear, f)
+    }
+
+    /// Calls `f` with the files dragged from other apps and dropped on
+    /// the view.
+    fn on_drop<F: Fn(&mut Context, &[std::path::PathBuf]) + 'static>(
+        self,
+        f: F,
+    ) -> FileDrop<Self, F> {
+        FileDrop::new(self, f)
+    }
+
+    /// Calls `f` with the payload of a view dragged with `draggable` and
+    /// dropped on this one. Only payloads of type `T` are accepted.
+    fn on_drop_target<T: Clone + 'static, F: Fn(&mut Context, T) + 'static>(
+        self,
+        f: F,
+    ) -> DropTarget<Self, T, F> {
+        DropTarget::new(self, f)
+    }
+
+    /// Responds to keyboard events: keys going down, repeating while held,
+    /// and going up. `KeyEvent::pressed` picks out presses.
+    fn key<F: Fn(&mut Context, KeyEvent) + 'static>(self, f: F) -> KeyView<Self, F> {
         KeyView::new(self, f)
     }
 
+    /// Calls a function with input method events. See `Context::allow_ime`.
+    fn ime<F: Fn(&mut Context, &ImeEvent) + 'static>(self, f: F) -> ImeView<Self, F> {
+        ImeView::new(self, f)
+    }
+
     /// Applies an offset to the view in local space.
-    fn offset<Off: Into<LocalOffset>>(self, offset: Off) -> Offset<Self> {
-        Offset::new(self, offset.into())
+    fn offset<Off: Into<Vector>>(self, offset: Off) -> Offset<Self> {
+        Offset::new(self, offset.into().into())
     }
 
-    /// Adds space around a view. Can be either `Auto` or `Px(number_of_pixels)`
+    /// Rotates the view counterclockwise by `angle` radians around its
+    /// center, or the point given with `anchor`. Layout is unchanged.
+    fn rotate(self, angle: f32) -> TransformView<Self> {
+        TransformView::rotate(self, angle)
+    }
+
+    /// Scales the view by `factor` around its center, or the point given
+    /// with `anchor`. Layout is unchanged.
+    fn scale(self, factor: f32) -> TransformView<Self> {
+        TransformView::scale(self, [factor, factor])
+    }
+
+    /// Adds space around a view. Can be `Auto`, a number of pixels, `Insets` for each edge, or
+    /// `DirectionalInsets` for leading and trailing edges.
     fn padding(self, param: impl Into<PaddingParam>) -> Padding<Self> {
         Padding::new(self, param.into())
     }
 
+    /// Identifies the view by `key` instead of by where it is, so its
+    /// state, and that of the views inside, goes with it when it moves,
+    /// like when rows are reordered or it's moved into another stack.
+    ///
+    /// Keys should be unique among the views with ids inside the same view
+    /// with an id, or the whole window. Put it on the outermost view of
+    /// what moves.
+    fn id<K: std::hash::Hash>(self, key: K) -> IdView<Self> {
+        IdView::new(self, hh(&key))
+    }
+
     /// Specify an accessiblity role.
     fn role(self, role: Role) -> RoleView<Self> {
         RoleView::new(self, role)
     }
 
+    /// Lays out and draws the view again only when `key` changes, or state
+    /// inside it does. Until then its layout is kept, and it's drawn once
+    /// into a layer which is composited again in later frames.
+    ///
+    /// Anything else the view shows, like captured state from around it,
+    /// should go in `key`. Hover and focus highlights inside don't update
+    /// either, so this suits large, mostly static subtrees.
+    fn cache_key<K: std::hash::Hash>(self, key: K) -> CacheKey<Self> {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        CacheKey::new(self, hasher.finish())
+    }
+
+    /// Names the view's draws in GPU frame captures, with the
+    /// `gpu-markers` feature. Does nothing otherwise.
+    fn debug_group(self, name: &str) -> DebugGroupView<Self> {
+        DebugGroupView::new(self, name.into())
+    }
+
+    /// Draws a shadow of the view behind it, blurred by `radius` points and
+    /// moved by `offset`.
+    ///
+    /// The view is drawn offscreen and composited over whatever is drawn
+    /// outside it, as with `opacity` and `blur`.
+    fn shadow(self, radius: f32, offset: impl Into<Vector>, color: Color) -> LayerView<Self> {
+        LayerView::new(
+            self,
+            LayerStyle {
+                shadow: Some(Shadow {
+                    radius,
+                    offset: offset.into().into(),
+                    color,
+                }),
+                ..LayerStyle::default()
+            },
+        )
+    }
+
+    /// Makes the view translucent. The view is faded as a whole, so its
+    /// overlapping parts don't show through each other.
+    fn opacity(self, opacity: f32) -> LayerView<Self> {
+        LayerView::new(
+            self,
+            LayerStyle {
+                opacity,
+                ..LayerStyle::default()
+            },
+        )
+    }
+
+    /// Blurs the view by `radius` points.
+    fn blur(self, radius: f32) -> LayerView<Self> {
+        LayerView::new(
+            self,
+            LayerStyle {
+                blur: radius,
+                ..LayerStyle::default()
+            },
+        )
+    }
+
+    /// Clips the view to its bounds with rounded corners, for both
+    /// drawing and hit-testing.
+    fn corner_radius(self, radius: f32) -> LayerView<Self> {
+        LayerView::new(
+            self,
+            LayerStyle {
+                corner_radius: Some(radius),
+                ..LayerStyle::default()
+            },
+        )
+    }
+
     /// Constrains the size of a view.
-    fn size<Sz: Into<LocalSize>>(self, size: Sz) -> Size<Self> {
-        Size::new(self, size.into())
+    fn size<Sz: Into<Size>>(self, size: Sz) -> SizeView<Self> {
+        SizeView::new(self, size.into().into())
+    }
+
+    /// Calls a function as two fingers pinch or pan on a touch screen.
+    /// Takes both touches from views inside once the second goes down.
+    fn pinch<F: Fn(&mut Context, PinchGesture, GestureState) + 'static>(
+        self,
+        f: F,
+    ) -> Pinch<Self, F> {
+        Pinch::new(self, f)
+    }
+
+    /// Shows `text` in a small label after the mouse rests over the view.
+    fn tooltip(self, text: impl Into<String>) -> Tooltip<Self> {
+        Tooltip::new(self, text.into())
     }
 
     /// Calls a function in response to a tap.
@@ -116,6 +377,12 @@ pub trait Modifiers: View + Sized {
         TitleView::new(self, title)
     }
 
+    /// Presses on the view, but not on controls within it, move the
+    /// window. For windows without decorations.
+    fn window_drag_area(self) -> WindowDragView<Self> {
+        WindowDragView::new(self)
+    }
+
     /// Handle an action from a child view.
     fn handle<A: 'static, A2: 'static, F: Fn(&mut Context, &A) -> A2 + 'static>(
         self,
@@ -124,10 +391,69 @@ pub trait Modifiers: View + Sized {
         Handle::new(self, handler)
     }
 
-    /// Clip to bounds.
+    /// Clips drawing, including images, and hit-testing to the view's
+    /// bounds.
     fn clip(self) -> Clip<Self> {
         Clip::new(self)
     }
+
+    /// Applies a `ViewModifier`.
+    fn modifier(self, modifier: impl ViewModifier) -> impl View {
+        modifier.body(self)
+    }
 }
 
 impl<V: View> Modifiers for V {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_modifiers_macro() {
+        let mut chained = Harness::new(text("hi").padding(10.0).offset([5.0, 0.0]));
+        let mut modified = Harness::new(modifiers!(text("hi");
+            padding: 10.0,
+            offset: [5.0, 0.0],
+        ));
+        assert_eq!(modified.find_text("hi"), chained.find_text("hi"));
+    }
+
+    /// Shows a count above the view, and counts taps on it.
+    struct Badge {
+        count: StateHandle<usize>,
+        shown: usize,
+    }
+
+    impl ViewModifier for Badge {
+        fn body(self, content: impl View) -> impl View {
+            let count = self.count;
+            vstack((
+                text(&format!("{} new", self.shown)),
+                content.tap(move |cx| cx[count] += 1),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_view_modifier() {
+        let view = state(
+            || 0,
+            |count, cx| {
+                let shown = cx[count];
+                text("inbox")
+                    .padding(Auto)
+                    .background(rectangle())
+                    .modifier(Badge { count, shown })
+            },
+        );
+        let mut h = Harness::new(view);
+        let inbox = h.find_text("inbox").unwrap();
+        assert!(h.find_text("0 new").unwrap().origin.y > inbox.origin.y);
+
+        h.click("inbox");
+        h.click("inbox");
+        h.expect_text("2 new");
+    }
+}
@@ -0,0 +1,260 @@
+//! Callbacks for when `state` changes, for logging, undo history or
+//! keeping an outside store in sync.
+
+use crate::*;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Rounds of callbacks run in one update, when callbacks change state
+/// which has callbacks of its own. Changes after that wait for the next
+/// update, so callbacks which keep changing each other can't hang the app.
+const MAX_NOTIFY_ROUNDS: usize = 8;
+
+/// A `state` which was written, passed to middleware.
+pub struct StateChange<'a> {
+    /// The `state` view the value belongs to. `StateHandle::new` with this
+    /// id reaches the value, if it's of the right type.
+    pub id: ViewId,
+
+    /// Name of the value's type.
+    pub type_name: &'static str,
+
+    /// The new value. Downcast it to the state's type to read it.
+    pub value: &'a dyn Any,
+}
+
+type ObserverFunc = Rc<dyn Fn(&mut Context)>;
+type MiddlewareFunc = Rc<dyn Fn(&StateChange)>;
+
+/// A callback from `on_state_change`.
+struct Observer {
+    /// The `state` whose body added the callback, or the default id if it
+    /// was added outside any.
+    observer: ViewId,
+
+    /// The state observed.
+    state: ViewId,
+
+    func: ObserverFunc,
+}
+
+/// Bookkeeping for `on_state_change` and `add_state_middleware`.
+#[derive(Default)]
+pub(crate) struct StateObservers {
+    /// In a `RefCell`, so `state` bodies can add callbacks. In the order
+    /// added, so callbacks run in a predictable order.
+    observers: RefCell<Vec<Observer>>,
+    middleware: Vec<MiddlewareFunc>,
+
+    /// The `state` whose body is running.
+    body: Cell<ViewId>,
+
+    /// State written since callbacks last ran, in the order first written.
+    changed: Vec<ViewId>,
+}
+
+impl StateObservers {
+    pub(crate) fn note_change(&mut self, id: ViewId) {
+        if self.observers.borrow().is_empty() && self.middleware.is_empty() {
+            return;
+        }
+        if !self.changed.contains(&id) {
+            self.changed.push(id);
+        }
+    }
+
+    /// Forgets callbacks for state which has gone away, and callbacks
+    /// added by views which have, since they may use their own state.
+    pub(crate) fn retain(&mut self, keep: impl Fn(&ViewId) -> bool) {
+        self.observers
+            .get_mut()
+            .retain(|o| keep(&o.state) && (o.observer.is_default() || keep(&o.observer)));
+        self.changed.retain(|id| keep(id));
+    }
+
+    /// Notes that the body of the state `id` is running. Pass the result
+    /// to `exit_body` once it has.
+    pub(crate) fn enter_body(&self, id: ViewId) -> ViewId {
+        self.body.replace(id)
+    }
+
+    pub(crate) fn exit_body(&self, outer: ViewId) {
+        self.body.set(outer);
+    }
+}
+
+impl Context {
+    /// Calls `f` with the new value whenever the state of `handle` is
+    /// written, once per update however many times it was written. The
+    /// value may be the same as before, since writing is all that's seen.
+    ///
+    /// Calling this again from the body of the same `state` for the same
+    /// state replaces the callback, so it can be called each time the body
+    /// runs. The callback is dropped along with the state, or with the
+    /// `state` whose body added it.
+    pub fn on_state_change<S: Clone + 'static>(
+        &self,
+        handle: StateHandle<S>,
+        f: impl Fn(&mut Context, &S) + 'static,
+    ) {
+        let observer = self.observers.body.get();
+        let func: ObserverFunc = Rc::new(move |cx| {
+            // A copy, so the callback can use the `Context`.
+            let value = cx
+                .state_map
+                .get(&handle.id)
+                .and_then(|holder| holder.state.downcast_ref::<S>())
+                .cloned();
+            if let Some(value) = value {
+                f(cx, &value)
+            }
+        });
+        let mut observers = self.observers.observers.borrow_mut();
+        match observers
+            .iter_mut()
+            .find(|o| o.observer == observer && o.state == handle.id)
+        {
+            Some(o) => o.func = func,
+            None => observers.push(Observer {
+                observer,
+                state: handle.id,
+                func,
+            }),
+        }
+    }
+
+    /// Calls `f` whenever any `state` is written, after the callbacks from
+    /// `on_state_change`, for things like logging every change. Add it
+    /// once, for example in `AppConfig::on_start`.
+    pub fn add_state_middleware(&mut self, f: impl Fn(&StateChange) + 'static) {
+        self.observers.middleware.push(Rc::new(f));
+    }
+
+    /// Runs the callbacks for state written since they last ran. Called
+    /// each update before layout, and again for state the callbacks write.
+    pub(crate) fn notify_state_changes(&mut self) {
+        for _ in 0..MAX_NOTIFY_ROUNDS {
+            let changed = std::mem::take(&mut self.observers.changed);
+            if changed.is_empty() {
+                return;
+            }
+            for id in changed {
+                let funcs: Vec<ObserverFunc> = self
+                    .observers
+                    .observers
+                    .borrow()
+                    .iter()
+                    .filter(|o| o.state == id)
+                    .map(|o| o.func.clone())
+                    .collect();
+                for f in funcs {
+                    f(self);
+                }
+                let middleware = self.observers.middleware.clone();
+                if let Some(holder) = self.state_map.get(&id) {
+                    let change = StateChange {
+                        id,
+                        type_name: holder.type_name,
+                        value: holder.state.as_ref(),
+                    };
+                    for f in &middleware {
+                        f(&change);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn counter() -> impl View {
+        state(
+            || 0,
+            |count, _| {
+                state(Vec::<i32>::new, move |history, cx| {
+                    // Records each value written, doubled.
+                    cx.on_state_change(count, move |cx, n| cx[history].push(*n * 2));
+                    vstack((
+                        button("add", move |cx| cx[count] += 1),
+                        text(&format!("{:?}", cx[history])),
+                    ))
+                })
+            },
+        )
+    }
+
+    #[test]
+    fn test_on_state_change() {
+        let mut h = Harness::new(counter());
+        h.expect_text("[]");
+        h.click("add");
+        h.expect_text("[2]");
+        h.click("add");
+        h.expect_text("[2, 4]");
+    }
+
+    /// Records each value of `count` written, with `label`.
+    fn history(count: StateHandle<i32>, label: &'static str) -> impl View {
+        state(Vec::<i32>::new, move |history, cx| {
+            cx.on_state_change(count, move |cx, n| cx[history].push(*n));
+            text(&format!("{} {:?}", label, cx[history]))
+        })
+    }
+
+    #[test]
+    fn test_observers() {
+        let view = state(
+            || 0,
+            |count, _| {
+                state(
+                    || true,
+                    move |shown, cx| {
+                        vstack((
+                            button("add", move |cx| cx[count] += 1),
+                            button("hide", move |cx| cx[shown] = false),
+                            optional_view(cx[shown].then(|| history(count, "a"))),
+                            history(count, "b"),
+                        ))
+                    },
+                )
+            },
+        );
+        let mut h = Harness::new(view);
+        // Each view observing the same state gets its own callback.
+        h.click("add");
+        h.expect_text("a [1]");
+        h.expect_text("b [1]");
+
+        // The callback goes away with the view which added it, while the
+        // state it observed lives on.
+        h.click("hide");
+        h.click("add");
+        h.expect_text("b [1, 2]");
+        assert!(!h.has_text("a [1]"));
+    }
+
+    #[test]
+    fn test_state_middleware() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut h = Harness::new(counter());
+        let l = log.clone();
+        h.context().add_state_middleware(move |change| {
+            if let Some(n) = change.value.downcast_ref::<i32>() {
+                l.borrow_mut().push(format!("{} = {}", change.type_name, n));
+            }
+        });
+        h.click("add");
+        assert_eq!(*log.borrow(), vec!["i32 = 1"]);
+
+        // Changes made by callbacks reach the middleware too.
+        assert_eq!(h.states::<Vec<i32>>(), vec![vec![2]]);
+        h.click("add");
+        assert_eq!(log.borrow().len(), 2);
+        h.expect_text("[2, 4]");
+    }
+}
@@ -0,0 +1,113 @@
+//! Deciding when to run frames, so idle UIs don't use the CPU or GPU.
+
+use crate::*;
+#[cfg(feature = "winit")]
+use std::time::{Duration, Instant};
+
+impl Context {
+    /// Asks for another frame even though no state changed, as for drawing
+    /// which depends on something outside rui. From another thread, use
+    /// `on_main(|cx| cx.request_redraw())`.
+    pub fn request_redraw(&mut self) {
+        self.redraw_requested = true;
+        self.full_redraw = true;
+    }
+
+    /// Does the UI want another frame without waiting for events? That's
+    /// when a state animation is running, a redraw was requested, or the
+    /// last update changed something, since a view updating on each
+    /// `Event::Anim` will change it again. Otherwise the event loop sleeps
+    /// until there's input.
+    pub fn needs_frame(&self) -> bool {
+        self.wants_frame || self.is_animating()
+    }
+}
+
+/// Spaces frames out to a maximum frame rate. See
+/// `WindowOptions::max_fps`.
+#[cfg(feature = "winit")]
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FramePacer {
+    /// Shortest time between frames, if limited.
+    interval: Option<Duration>,
+
+    /// When the last frame started.
+    last_frame: Option<Instant>,
+}
+
+#[cfg(feature = "winit")]
+impl FramePacer {
+    pub(crate) fn new(max_fps: Option<f32>) -> Self {
+        Self {
+            interval: max_fps
+                .filter(|fps| *fps > 0.0)
+                .map(|fps| Duration::from_secs_f32(1.0 / fps)),
+            last_frame: None,
+        }
+    }
+
+    pub(crate) fn frame_started(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+
+    /// When the next frame may start, or None for right away.
+    pub(crate) fn next_frame(&self, now: Instant) -> Option<Instant> {
+        let next = self.last_frame? + self.interval?;
+        (next > now).then_some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_needs_frame() {
+        let mut h = Harness::new(state(
+            || 0,
+            |count, cx| {
+                vstack((
+                    format!("{}", cx[count]),
+                    button("add", move |cx| cx[count] += 1),
+                    button("redraw", |cx| cx.request_redraw()),
+                ))
+            },
+        ));
+        h.update();
+        assert!(!h.context().needs_frame());
+
+        // One more frame after a change, in case it keeps changing.
+        h.click("add");
+        assert!(h.context().needs_frame());
+        h.update();
+        assert!(!h.context().needs_frame());
+
+        h.click("redraw");
+        assert!(h.context().needs_frame());
+        h.update();
+        assert!(!h.context().needs_frame());
+
+        // A spinner changes every frame.
+        let mut h = Harness::new(spinner());
+        h.advance(Duration::from_secs(1));
+        assert!(h.context().needs_frame());
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_frame_pacer() {
+        let now = std::time::Instant::now();
+        let mut pacer = FramePacer::new(None);
+        pacer.frame_started(now);
+        assert_eq!(pacer.next_frame(now), None);
+
+        let mut pacer = FramePacer::new(Some(10.0));
+        assert_eq!(pacer.next_frame(now), None);
+        pacer.frame_started(now);
+        let next = pacer.next_frame(now).unwrap();
+        assert!(((next - now).as_secs_f32() - 0.1).abs() < 0.001);
+        assert_eq!(pacer.next_frame(now + Duration::from_millis(200)), None);
+    }
+}
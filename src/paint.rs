@@ -1,30 +1,326 @@
 use crate::*;
 
 /// Specifies how a region should be filled.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Paint {
     /// Fill a region with a solid color.
     Color(Color),
 
-    /// Fill a region with a linear gradient between two colors.
+    /// Fill a region with a linear gradient between two colors, from
+    /// `start` to `end` in local coordinates.
     Gradient {
         start: LocalPoint,
         end: LocalPoint,
         inner_color: Color,
         outer_color: Color,
     },
+
+    /// Fill a region with a linear gradient between two colors. `start` and
+    /// `end` are relative to the region's bounds, from `(0, 0)` at the
+    /// bottom left to `(1, 1)` at the top right, so the gradient follows
+    /// the region as it's resized.
+    LinearGradient {
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+    },
+
+    /// Fill a region with a radial gradient between two colors. `center`
+    /// is relative to the region's bounds, like `LinearGradient`. `radius`
+    /// is relative to half the region's shorter side, so a gradient
+    /// centered at `(0.5, 0.5)` with radius `1` touches the nearest edges.
+    RadialGradient {
+        center: LocalPoint,
+        radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    },
+
+    /// Fill a region with an image, fitted to the region's bounds with
+    /// `mode`.
+    Image { image: ImageData, mode: ContentMode },
 }
 
 impl Paint {
+    /// The color the paint looks like on average, for picking text to go
+    /// over it.
+    pub fn average_color(&self) -> Color {
+        match self {
+            Paint::Color(color) => *color,
+            Paint::Gradient {
+                inner_color,
+                outer_color,
+                ..
+            }
+            | Paint::LinearGradient {
+                inner_color,
+                outer_color,
+                ..
+            }
+            | Paint::RadialGradient {
+                inner_color,
+                outer_color,
+                ..
+            } => inner_color.lerp(outer_color, 0.5),
+            Paint::Image { image, .. } => average_pixel(image),
+        }
+    }
+
+    /// A linear gradient from `inner_color` at `start` to `outer_color` at
+    /// `end`, relative to the bounds of what's painted.
+    pub fn linear(
+        start: impl Into<LocalPoint>,
+        end: impl Into<LocalPoint>,
+        inner_color: Color,
+        outer_color: Color,
+    ) -> Self {
+        Paint::LinearGradient {
+            start: start.into(),
+            end: end.into(),
+            inner_color,
+            outer_color,
+        }
+    }
+
+    /// A linear gradient from `top` to `bottom`, the usual way to give a
+    /// control some depth.
+    pub fn vertical(top: Color, bottom: Color) -> Self {
+        Paint::linear([0.0, 1.0], [0.0, 0.0], top, bottom)
+    }
+
+    /// A radial gradient from `inner_color` at `center` to `outer_color`
+    /// at `radius`, relative to the bounds of what's painted.
+    pub fn radial(
+        center: impl Into<LocalPoint>,
+        radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    ) -> Self {
+        Paint::RadialGradient {
+            center: center.into(),
+            radius,
+            inner_color,
+            outer_color,
+        }
+    }
+
+    /// An image stretched, fitted or cropped to the bounds of what's
+    /// painted.
+    pub fn image(image: ImageData, mode: ContentMode) -> Self {
+        Paint::Image { image, mode }
+    }
+
+    /// Converts to a vger paint. Paints relative to a region's bounds are
+    /// relative to the unit square here, so use `vger_paint_in` for those.
     pub fn vger_paint(&self, vger: &mut Vger) -> PaintIndex {
+        self.vger_paint_in(vger, LocalRect::new(LocalPoint::zero(), [1.0, 1.0].into()))
+    }
+
+    /// Converts to a vger paint for a region with the given bounds. vger
+    /// can't draw radial gradients or images, so those give their average
+    /// color here. Shapes draw them properly.
+    pub fn vger_paint_in(&self, vger: &mut Vger, bounds: LocalRect) -> PaintIndex {
+        self.make(vger, bounds)
+    }
+
+    /// Like `vger_paint_in`, made with the `Painter`, so it still works after
+    /// the `Painter` ends vger's pass.
+    pub(crate) fn painter_paint(&self, painter: &mut Painter, bounds: LocalRect) -> PaintIndex {
+        self.make(painter, bounds)
+    }
+
+    fn make(&self, target: &mut impl MakePaint, bounds: LocalRect) -> PaintIndex {
         match self {
-            Paint::Color(color) => vger.color_paint(*color),
+            Paint::Color(color) => target.color(*color),
             Paint::Gradient {
                 start,
                 end,
                 inner_color,
                 outer_color,
-            } => vger.linear_gradient(*start, *end, *inner_color, *outer_color, 0.0),
+            } => target.linear(*start, *end, *inner_color, *outer_color),
+            Paint::LinearGradient {
+                start,
+                end,
+                inner_color,
+                outer_color,
+            } => target.linear(
+                relative(bounds, *start),
+                relative(bounds, *end),
+                *inner_color,
+                *outer_color,
+            ),
+            Paint::RadialGradient { .. } | Paint::Image { .. } => {
+                target.color(self.average_color())
+            }
+        }
+    }
+
+    /// Fills a region with bounds `bounds`, calling `fill` to draw the
+    /// region itself with a vger paint.
+    ///
+    /// vger can't draw radial gradients or images, so those are drawn like
+    /// `image` views, masked to `shape`: a rounded rect and its corner
+    /// radius. Regions which aren't rounded rects, and frames drawn
+    /// without a GPU, get the paint's average color instead.
+    pub(crate) fn fill(
+        &self,
+        cx: &mut Context,
+        vger: &mut Painter,
+        bounds: LocalRect,
+        shape: Option<(LocalRect, f32)>,
+        fill: impl FnOnce(&mut Painter, PaintIndex),
+    ) {
+        let shape = match shape {
+            Some(shape) if cx.layer_renderer.is_some() => shape,
+            _ => {
+                let paint = self.painter_paint(vger, bounds);
+                fill(vger, paint);
+                return;
+            }
+        };
+
+        let xform = vger.current_transform();
+        let (image, dest, uv, gradient) = match self {
+            Paint::RadialGradient {
+                center,
+                radius,
+                inner_color,
+                outer_color,
+            } => {
+                let center = relative(bounds, *center);
+                let radius = radius * bounds.width().min(bounds.height()) / 2.0;
+                let edge = center + LocalOffset::new(radius, 0.0);
+                let center = xform.transform_point(center);
+                let gradient = RadialFill {
+                    center,
+                    radius: xform.transform_point(edge).distance_to(center),
+                    inner_color: *inner_color,
+                    outer_color: *outer_color,
+                };
+                (white_image(), bounds, [0.0, 0.0, 1.0, 1.0], Some(gradient))
+            }
+            Paint::Image { image, mode } => {
+                let (dest, uv) = content_rects(*mode, image.size(), bounds);
+                (image.clone(), dest, uv, None)
+            }
+            _ => {
+                let paint = self.painter_paint(vger, bounds);
+                fill(vger, paint);
+                return;
+            }
+        };
+        if dest.is_empty() {
+            return;
+        }
+
+        // Scale the radius with the shape, as layers do.
+        let (rect, radius) = shape;
+        let mask = xform.outer_transformed_rect(&rect);
+        let radius = radius * mask.width() / rect.width().max(1e-6);
+        let request = ImageRequest {
+            image,
+            rect: xform.outer_transformed_rect(&dest),
+            uv,
+            clip: cx.current_clip(),
+            marker: cx.gpu_markers.current(),
+            mask: Some((mask, radius)),
+            gradient,
+        };
+        cx.draw_image(vger, request);
+    }
+}
+
+/// Makes paints for `Paint`, with a `Vger` or a `Painter`.
+trait MakePaint {
+    fn color(&mut self, color: Color) -> PaintIndex;
+    fn linear(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner: Color,
+        outer: Color,
+    ) -> PaintIndex;
+}
+
+impl MakePaint for Vger {
+    fn color(&mut self, color: Color) -> PaintIndex {
+        self.color_paint(color)
+    }
+    fn linear(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner: Color,
+        outer: Color,
+    ) -> PaintIndex {
+        self.linear_gradient(start, end, inner, outer, 0.0)
+    }
+}
+
+impl MakePaint for Painter<'_> {
+    fn color(&mut self, color: Color) -> PaintIndex {
+        self.color_paint(color)
+    }
+    fn linear(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner: Color,
+        outer: Color,
+    ) -> PaintIndex {
+        self.linear_gradient(start, end, inner, outer, 0.0)
+    }
+}
+
+/// Maps a point relative to `bounds` to local coordinates.
+fn relative(bounds: LocalRect, p: LocalPoint) -> LocalPoint {
+    bounds.origin + LocalOffset::new(p.x * bounds.width(), p.y * bounds.height())
+}
+
+/// Mean color of up to about a thousand of `image`'s pixels, spread
+/// evenly through it.
+fn average_pixel(image: &ImageData) -> Color {
+    let count = (image.width() * image.height()) as usize;
+    if count == 0 {
+        return CLEAR_COLOR;
+    }
+    let step = count.div_ceil(1024);
+    let mut sum = [0.0; 4];
+    let mut n = 0.0;
+    for pixel in image.pixels().chunks(4).step_by(step) {
+        for (sum, channel) in sum.iter_mut().zip(pixel) {
+            *sum += *channel as f32 / 255.0;
         }
+        n += 1.0;
+    }
+    // Images are sRGB, colors are linear.
+    Color::new(
+        srgb_to_linear(sum[0] / n),
+        srgb_to_linear(sum[1] / n),
+        srgb_to_linear(sum[2] / n),
+        sum[3] / n,
+    )
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Color(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_average_pixel() {
+        let image = ImageData::from_rgba(2, 1, vec![255, 0, 0, 255, 255, 0, 0, 0]);
+        let c = Paint::image(image, ContentMode::Fill).average_color();
+        assert_eq!((c.r, c.g, c.a), (1.0, 0.0, 0.5));
+
+        let empty = ImageData::from_rgba(0, 0, vec![]);
+        assert_eq!(average_pixel(&empty), CLEAR_COLOR);
     }
 }
@@ -0,0 +1,653 @@
+//! What views draw with.
+//!
+//! vger draws everything it's given in one pass, but images, layers and
+//! text are drawn by rui, and have to go between vger's shapes to keep
+//! draw order. So a `Painter` can end vger's pass partway through a
+//! frame, and start a new one where it left off.
+//!
+//! Ending a pass for everything rui draws would mean a pass for each
+//! label, so what rui draws is queued after the pass instead, and vger's
+//! shapes which don't overlap it join the pass as if drawn before it. A
+//! shape which does overlap ends the pass early. That needs the bounds of
+//! everything drawn, so the `Painter` has its own drawing methods; what's
+//! drawn on the `Vger` it derefs to is assumed to overlap.
+
+use crate::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How far past their bounds shapes are antialiased, in points.
+const EDGE: f32 = 1.0;
+
+/// Saved transform and scissor, to set up again in the next pass.
+#[derive(Clone, Copy)]
+struct PainterState {
+    xform: LocalToWorld,
+
+    /// Scissor rect, and the transform it was set with.
+    scissor: Option<(LocalRect, LocalToWorld)>,
+}
+
+/// A paint made in this pass, to make again in the next, so its index
+/// still works there.
+#[derive(Clone, Copy)]
+enum PaintDesc {
+    Color(Color),
+    LinearGradient {
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    },
+}
+
+enum PainterVger<'a> {
+    /// The window's `Vger`.
+    Frame(&'a mut Vger),
+
+    /// A `Vger` from the layer renderer, returned once the layer ends.
+    Layer(Box<Vger>),
+}
+
+/// A `Vger` for drawing a frame or layer in passes, so images, layers and
+/// text can be drawn between vger's shapes. Derefs to the `Vger`, but
+/// shapes drawn with the `Painter`'s own methods needn't end the pass.
+pub struct Painter<'a> {
+    vger: PainterVger<'a>,
+    size: euclid::Size2D<f32, WorldSpace>,
+    device_px_ratio: f32,
+
+    /// States saved by `save`, outermost first.
+    saved: Vec<PainterState>,
+    scissor: Option<(LocalRect, LocalToWorld)>,
+
+    /// Has anything been drawn since the last pass ended?
+    drawn: bool,
+
+    /// Paints made since the pass began.
+    paints: Vec<PaintDesc>,
+
+    /// Points of the path being drawn, for its bounds.
+    path: Vec<LocalPoint>,
+
+    /// Bounds of what rui queued since the pass began, in the coordinates
+    /// of the frame or layer.
+    queued: Vec<WorldRect>,
+
+    /// What the queue is rendered over.
+    target: Option<Arc<wgpu::TextureView>>,
+
+    /// Did a shape end the pass early? Then what's queued goes under the
+    /// pass begun since.
+    ended_early: bool,
+
+    /// Has the `Vger` been drawn on directly since the pass began, so
+    /// what it drew and the paints it made aren't known?
+    untracked: bool,
+
+    /// Time spent ending passes early.
+    pass_time: Duration,
+
+    /// Names vger's passes, after the innermost GPU debug group.
+    label: Option<String>,
+}
+
+impl<'a> Painter<'a> {
+    /// Draws with `vger`, which has begun a frame of `size` points.
+    pub(crate) fn new(
+        vger: &'a mut Vger,
+        size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) -> Self {
+        Self::with_vger(PainterVger::Frame(vger), size, scale)
+    }
+
+    fn with_vger(vger: PainterVger<'a>, size: euclid::Size2D<f32, WorldSpace>, scale: f32) -> Self {
+        Self {
+            vger,
+            size,
+            device_px_ratio: scale,
+            saved: vec![],
+            scissor: None,
+            drawn: false,
+            paints: vec![],
+            path: vec![],
+            queued: vec![],
+            target: None,
+            ended_early: false,
+            untracked: false,
+            pass_time: Duration::ZERO,
+            label: None,
+        }
+    }
+
+    /// Saves the transform and scissor rect.
+    pub fn save(&mut self) {
+        self.saved.push(PainterState {
+            xform: self.vger().current_transform(),
+            scissor: self.scissor,
+        });
+        self.vger_mut().save();
+    }
+
+    /// Restores the transform and scissor rect saved last.
+    pub fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.scissor = state.scissor;
+        }
+        self.vger_mut().restore();
+    }
+
+    /// Sets the scissor rect, in the current coordinates.
+    pub fn scissor(&mut self, rect: LocalRect) {
+        self.scissor = Some((rect, self.vger().current_transform()));
+        self.vger_mut().scissor(rect);
+    }
+
+    pub fn reset_scissor(&mut self) {
+        self.scissor = None;
+        self.vger_mut().reset_scissor();
+    }
+
+    pub fn translate<Vec: Into<LocalOffset>>(&mut self, offset: Vec) {
+        self.vger_mut().translate(offset);
+    }
+
+    pub fn scale<Vec: Into<LocalOffset>>(&mut self, scale: Vec) {
+        self.vger_mut().scale(scale);
+    }
+
+    pub fn rotate(&mut self, theta: f32) {
+        self.vger_mut().rotate(theta);
+    }
+
+    /// Solid color paint.
+    pub fn color_paint(&mut self, color: Color) -> PaintIndex {
+        self.paints.push(PaintDesc::Color(color));
+        self.vger_mut().color_paint(color)
+    }
+
+    /// Linear gradient paint.
+    pub fn linear_gradient<Pt: Into<LocalPoint>>(
+        &mut self,
+        start: Pt,
+        end: Pt,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex {
+        let (start, end) = (start.into(), end.into());
+        self.paints.push(PaintDesc::LinearGradient {
+            start,
+            end,
+            inner_color,
+            outer_color,
+            glow,
+        });
+        self.vger_mut()
+            .linear_gradient(start, end, inner_color, outer_color, glow)
+    }
+
+    /// Fills a circle.
+    pub fn fill_circle<Pt: Into<LocalPoint>>(
+        &mut self,
+        center: Pt,
+        radius: f32,
+        paint_index: PaintIndex,
+    ) {
+        let center = center.into();
+        self.shape(|_| around(center, radius));
+        self.vger_mut().fill_circle(center, radius, paint_index);
+    }
+
+    /// Strokes an arc.
+    pub fn stroke_arc<Pt: Into<LocalPoint>>(
+        &mut self,
+        center: Pt,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint_index: PaintIndex,
+    ) {
+        let center = center.into();
+        self.shape(|_| around(center, radius + width));
+        self.vger_mut()
+            .stroke_arc(center, radius, width, rotation, aperture, paint_index);
+    }
+
+    /// Fills a rectangle.
+    pub fn fill_rect<Rect: Into<LocalRect>>(
+        &mut self,
+        rect: Rect,
+        radius: f32,
+        paint_index: PaintIndex,
+    ) {
+        let rect = rect.into();
+        self.shape(|_| rect);
+        self.vger_mut().fill_rect(rect, radius, paint_index);
+    }
+
+    /// Strokes a rectangle.
+    pub fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint_index: PaintIndex,
+    ) {
+        self.shape(|_| LocalRect::from_points([min, max]).inflate(width, width));
+        self.vger_mut()
+            .stroke_rect(min, max, radius, width, paint_index);
+    }
+
+    /// Strokes a line segment.
+    pub fn stroke_segment<Pt: Into<LocalPoint>>(
+        &mut self,
+        a: Pt,
+        b: Pt,
+        width: f32,
+        paint_index: PaintIndex,
+    ) {
+        let (a, b) = (a.into(), b.into());
+        self.shape(|_| LocalRect::from_points([a, b]).inflate(width, width));
+        self.vger_mut().stroke_segment(a, b, width, paint_index);
+    }
+
+    /// Strokes a quadratic bezier segment.
+    pub fn stroke_bezier<Pt: Into<LocalPoint>>(
+        &mut self,
+        a: Pt,
+        b: Pt,
+        c: Pt,
+        width: f32,
+        paint_index: PaintIndex,
+    ) {
+        let (a, b, c) = (a.into(), b.into(), c.into());
+        self.shape(|_| LocalRect::from_points([a, b, c]).inflate(width, width));
+        self.vger_mut().stroke_bezier(a, b, c, width, paint_index);
+    }
+
+    /// Moves the pen to a point, for `fill`.
+    pub fn move_to<Pt: Into<LocalPoint>>(&mut self, p: Pt) {
+        let p = p.into();
+        self.path.push(p);
+        self.vger_mut().move_to(p);
+    }
+
+    /// Adds a quadratic curve to a point to the path, for `fill`.
+    pub fn quad_to<Pt: Into<LocalPoint>>(&mut self, b: Pt, c: Pt) {
+        let (b, c) = (b.into(), c.into());
+        self.path.extend([b, c]);
+        self.vger_mut().quad_to(b, c);
+    }
+
+    /// Fills the path.
+    pub fn fill(&mut self, paint_index: PaintIndex) {
+        let path = std::mem::take(&mut self.path);
+        self.shape(|_| LocalRect::from_points(path));
+        self.vger_mut().fill(paint_index);
+    }
+
+    /// Draws text with vger's built-in face.
+    pub fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>) {
+        self.shape(|vger| vger.text_bounds(text, size, max_width));
+        // vger makes a paint for the text.
+        self.paints.push(PaintDesc::Color(color));
+        self.vger_mut().text(text, size, color, max_width);
+    }
+
+    /// Bounds of text drawn with `text`.
+    pub fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        self.vger_mut().text_bounds(text, size, max_width)
+    }
+
+    /// Where `text` puts each glyph.
+    pub fn glyph_positions(
+        &mut self,
+        text: &str,
+        size: u32,
+        max_width: Option<f32>,
+    ) -> Vec<LocalRect> {
+        self.vger_mut().glyph_positions(text, size, max_width)
+    }
+
+    /// The lines `text` breaks text into.
+    pub fn line_metrics(
+        &mut self,
+        text: &str,
+        size: u32,
+        max_width: Option<f32>,
+    ) -> Vec<LineMetrics> {
+        self.vger_mut().line_metrics(text, size, max_width)
+    }
+
+    /// The scissor rect, in the coordinates of the frame or layer.
+    pub(crate) fn scissor_bounds(&self) -> Option<WorldRect> {
+        self.scissor
+            .map(|(rect, xform)| xform.outer_transformed_rect(&rect))
+    }
+
+    /// Gets ready to draw a shape within the rect `bounds` gives, in the
+    /// current coordinates, ending the pass first if what's queued after
+    /// it would be under the shape.
+    fn shape(&mut self, bounds: impl FnOnce(&mut Vger) -> LocalRect) {
+        if self.queued_after() {
+            let vger = self.vger_mut();
+            let rect = bounds(vger);
+            let rect = vger
+                .current_transform()
+                .outer_transformed_rect(&rect)
+                .inflate(EDGE, EDGE);
+            if self.queued.iter().any(|queued| queued.intersects(&rect)) {
+                self.end_pass_early();
+            }
+        }
+        self.drawn = true;
+    }
+
+    /// Renders the pass, leaving what's queued to go over it rendered
+    /// once the next pass is.
+    fn end_pass_early(&mut self) {
+        let start = Stopwatch::start();
+        if let Some(target) = self.target.clone() {
+            self.encode(&target);
+        }
+        self.ended_early = true;
+        self.pass_time += start.elapsed();
+    }
+
+    /// Has anything been drawn since the last pass ended?
+    pub(crate) fn drawn(&self) -> bool {
+        self.drawn
+    }
+
+    /// Is what's queued to be rendered after the pass, rather than before
+    /// the one a shape began by ending it early?
+    pub(crate) fn queued_after(&self) -> bool {
+        !self.queued.is_empty() && !self.ended_early
+    }
+
+    /// Did a shape end the pass early, so anything queued now would have
+    /// to go over the pass begun since, not under it?
+    pub(crate) fn ended_early(&self) -> bool {
+        self.ended_early
+    }
+
+    /// Has the `Vger` been drawn on directly since the pass began? Then
+    /// nothing can be queued after the pass, as there's no telling what
+    /// would be drawn over it.
+    pub(crate) fn untracked(&self) -> bool {
+        self.untracked
+    }
+
+    /// Notes that rui queued something within `bounds`, in the coordinates
+    /// of the frame or layer, to render over `target` after the pass.
+    pub(crate) fn queue(&mut self, bounds: WorldRect, target: Arc<wgpu::TextureView>) {
+        self.queued.push(bounds);
+        self.target = Some(target);
+    }
+
+    /// Forgets the queue, once it's rendered.
+    pub(crate) fn clear_queue(&mut self) {
+        self.queued.clear();
+        self.ended_early = false;
+    }
+
+    /// Labels the passes begun from now on. See `Context::push_gpu_marker`.
+    pub(crate) fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// Time spent rendering passes ended early, since asked last.
+    pub(crate) fn take_pass_time(&mut self) -> Duration {
+        std::mem::take(&mut self.pass_time)
+    }
+
+    /// Renders what's been drawn so far over `target`, and starts the next
+    /// pass with the same transforms, scissor rects and paints.
+    pub(crate) fn end_pass(&mut self, target: &wgpu::TextureView) {
+        self.encode(target);
+        self.clear_queue();
+    }
+
+    fn encode(&mut self, target: &wgpu::TextureView) {
+        if !self.drawn {
+            return;
+        }
+        self.drawn = false;
+
+        let current = PainterState {
+            xform: self.vger().current_transform(),
+            scissor: self.scissor,
+        };
+        let (size, scale) = (self.size, self.device_px_ratio);
+        let saved = self.saved.clone();
+        let label = self.label.clone();
+        let vger = self.vger_mut();
+        vger.encode(&wgpu::RenderPassDescriptor {
+            label: Some(label.as_deref().unwrap_or("rui vector content")),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        vger.begin(size.width, size.height, scale);
+        let mut xform = LocalToWorld::identity();
+        for state in &saved {
+            xform = restore_state(vger, xform, state);
+            vger.save();
+        }
+        restore_state(vger, xform, &current);
+
+        // Paints made on the `Vger` itself would throw the indices off.
+        if std::mem::take(&mut self.untracked) {
+            self.paints.clear();
+        } else {
+            let paints = std::mem::take(&mut self.paints);
+            for paint in &paints {
+                let vger = self.vger_mut();
+                match *paint {
+                    PaintDesc::Color(color) => vger.color_paint(color),
+                    PaintDesc::LinearGradient {
+                        start,
+                        end,
+                        inner_color,
+                        outer_color,
+                        glow,
+                    } => vger.linear_gradient(start, end, inner_color, outer_color, glow),
+                };
+            }
+            self.paints = paints;
+        }
+    }
+
+    /// The layer's `Vger`, once it's been drawn.
+    pub(crate) fn into_layer_vger(self) -> Option<Vger> {
+        match self.vger {
+            PainterVger::Frame(_) => None,
+            PainterVger::Layer(vger) => Some(*vger),
+        }
+    }
+
+    fn vger(&self) -> &Vger {
+        match &self.vger {
+            PainterVger::Frame(vger) => vger,
+            PainterVger::Layer(vger) => vger,
+        }
+    }
+
+    fn vger_mut(&mut self) -> &mut Vger {
+        match &mut self.vger {
+            PainterVger::Frame(vger) => vger,
+            PainterVger::Layer(vger) => vger,
+        }
+    }
+}
+
+impl Painter<'static> {
+    /// Draws a layer of `size` points with `vger`, which has begun it.
+    pub(crate) fn layer(vger: Vger, size: euclid::Size2D<f32, WorldSpace>, scale: f32) -> Self {
+        Self::with_vger(PainterVger::Layer(Box::new(vger)), size, scale)
+    }
+}
+
+/// The square around a circle.
+fn around(center: LocalPoint, radius: f32) -> LocalRect {
+    LocalRect::new(center, LocalSize::zero()).inflate(radius, radius)
+}
+
+/// Moves `vger` from transform `from` to the state's, setting its scissor
+/// rect on the way. Returns the state's transform.
+fn restore_state(vger: &mut Vger, from: LocalToWorld, state: &PainterState) -> LocalToWorld {
+    let mut xform = from;
+    if let Some((rect, scissor_xform)) = state.scissor {
+        xform = move_transform(vger, xform, scissor_xform);
+        vger.scissor(rect);
+    }
+    move_transform(vger, xform, state.xform)
+}
+
+/// Changes `vger`'s transform from `from` to `to`.
+fn move_transform(vger: &mut Vger, from: LocalToWorld, to: LocalToWorld) -> LocalToWorld {
+    if from != to {
+        if let Some(inverse) = from.inverse() {
+            apply_transform(vger, &to.then(&inverse).with_destination());
+        }
+    }
+    to
+}
+
+impl<'a> std::ops::Deref for Painter<'a> {
+    type Target = Vger;
+
+    fn deref(&self) -> &Vger {
+        self.vger()
+    }
+}
+
+impl<'a> std::ops::DerefMut for Painter<'a> {
+    fn deref_mut(&mut self) -> &mut Vger {
+        // Whatever's drawn could overlap what's queued.
+        if self.queued_after() {
+            self.end_pass_early();
+        }
+        self.drawn = true;
+        self.untracked = true;
+        self.vger_mut()
+    }
+}
+
+#[cfg(all(test, feature = "images", not(target_arch = "wasm32")))]
+mod tests {
+
+    use super::*;
+
+    /// A `Vger` and an 8x8 target to draw it into.
+    fn vger_and_target() -> (Vger, Arc<wgpu::TextureView>) {
+        let (device, queue) = test_device();
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("painter target"),
+            size: wgpu::Extent3d {
+                width: 8,
+                height: 8,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let target = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut vger = Vger::new(device, queue, format);
+        vger.begin(8.0, 8.0, 1.0);
+        (vger, Arc::new(target))
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_end_pass() {
+        let (mut vger, target) = vger_and_target();
+        let mut painter = Painter::new(&mut vger, [8.0, 8.0].into(), 1.0);
+        painter.translate([1.0, 2.0]);
+        painter.save();
+        painter.scale([2.0, 3.0]);
+        painter.scissor(LocalRect::new(LocalPoint::zero(), [2.0, 2.0].into()));
+        painter.save();
+        painter.rotate(0.5);
+        let paint = painter.color_paint(BLACK);
+        painter.fill_rect(
+            LocalRect::new(LocalPoint::zero(), [1.0, 1.0].into()),
+            0.0,
+            paint,
+        );
+        let inner = painter.current_transform();
+
+        assert!(painter.drawn());
+        painter.end_pass(&target);
+        assert!(!painter.drawn());
+
+        let close = |a: LocalToWorld, b: LocalToWorld| {
+            a.to_array()
+                .iter()
+                .zip(b.to_array())
+                .all(|(a, b)| (a - b).abs() < 1e-4)
+        };
+        assert!(close(painter.current_transform(), inner));
+        painter.restore();
+        painter.restore();
+        assert!(close(
+            painter.current_transform(),
+            LocalToWorld::translation(1.0, 2.0)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_queue() {
+        let (mut vger, target) = vger_and_target();
+        let mut painter = Painter::new(&mut vger, [8.0, 8.0].into(), 1.0);
+        let paint = painter.color_paint(BLACK);
+        painter.fill_rect(
+            LocalRect::new([0.0, 0.0].into(), [2.0, 2.0].into()),
+            0.0,
+            paint,
+        );
+        painter.queue(WorldRect::new([0.0, 0.0].into(), [2.0, 2.0].into()), target);
+        assert!(painter.queued_after());
+
+        // Shapes clear of what's queued join the pass.
+        painter.fill_rect(
+            LocalRect::new([5.0, 5.0].into(), [2.0, 2.0].into()),
+            0.0,
+            paint,
+        );
+        assert!(!painter.ended_early());
+
+        // Shapes over it end the pass, and its paints still work after.
+        painter.translate([1.0, 1.0]);
+        painter.fill_circle([0.0, 0.0], 1.0, paint);
+        assert!(painter.ended_early());
+        assert!(!painter.queued_after());
+        assert_eq!(painter.paints.len(), 1);
+        assert!(!painter.untracked());
+
+        // Drawing on the `Vger` itself can't be tracked.
+        painter.clear_queue();
+        let _ = painter.color_paint(BLACK);
+        let _: &mut Vger = &mut painter;
+        assert!(painter.untracked());
+    }
+}
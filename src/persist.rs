@@ -0,0 +1,217 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long values must stay the same before they're written, so a value
+/// which changes every frame, like a slider being dragged, isn't written
+/// every frame.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest values go unwritten while they keep changing.
+const MAX_SAVE_DELAY: Duration = Duration::from_secs(5);
+
+enum WriterMessage {
+    Write(PathBuf, String),
+
+    /// Write what's waiting now, then reply.
+    Flush(mpsc::Sender<()>),
+}
+
+/// Values saved by `persistent_state`, keyed by the name passed to it.
+#[derive(Default)]
+pub(crate) struct PersistentStore {
+    path: Option<PathBuf>,
+    values: HashMap<String, serde_json::Value>,
+    dirty: bool,
+
+    /// Writes the file on a thread of its own, so the UI doesn't wait for
+    /// the disk. Started by the first save.
+    writer: Option<mpsc::Sender<WriterMessage>>,
+}
+
+impl PersistentStore {
+    /// Loads values from `path`. A missing or unreadable file results in
+    /// an empty store, so defaults are used.
+    pub fn load(path: &Path) -> Self {
+        let values = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            values,
+            dirty: false,
+            writer: None,
+        }
+    }
+
+    #[cfg(feature = "devtools")]
+    pub fn values(&self) -> &HashMap<String, serde_json::Value> {
+        &self.values
+    }
+
+    pub fn get<S: DeserializeOwned>(&self, key: &str) -> Option<S> {
+        self.values
+            .get(key)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn set<S: Serialize>(&mut self, key: &str, value: &S) {
+        if let Ok(value) = serde_json::to_value(value) {
+            if self.values.get(key) != Some(&value) {
+                self.values.insert(key.to_string(), value);
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Hands the values to the writer if anything has changed since the
+    /// last save. They're written once they stop changing.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        if let (true, Some(path)) = (self.dirty, &self.path) {
+            let contents = serde_json::to_string_pretty(&self.values)?;
+            let path = path.clone();
+            self.dirty = false;
+
+            // There are no threads to write on.
+            if cfg!(target_arch = "wasm32") {
+                return write(&path, &contents);
+            }
+            let writer = self.writer.get_or_insert_with(|| {
+                let (tx, rx) = mpsc::channel();
+                std::thread::spawn(move || run_writer(rx));
+                tx
+            });
+            if let Err(mpsc::SendError(WriterMessage::Write(path, contents))) =
+                writer.send(WriterMessage::Write(path, contents))
+            {
+                self.writer = None;
+                return write(&path, &contents);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves, and waits for the values to be written, as when the app
+    /// quits.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.save()?;
+        if let Some(writer) = &self.writer {
+            let (tx, rx) = mpsc::channel();
+            if writer.send(WriterMessage::Flush(tx)).is_ok() {
+                let _ = rx.recv();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)
+}
+
+/// Writes the latest values once none have come for `SAVE_DELAY`, or
+/// `MAX_SAVE_DELAY` after the first unwritten ones.
+fn run_writer(rx: mpsc::Receiver<WriterMessage>) {
+    let mut waiting: Option<(PathBuf, String, Instant)> = None;
+    let flush = |waiting: &mut Option<(PathBuf, String, Instant)>| {
+        if let Some((path, contents, _)) = waiting.take() {
+            if let Err(err) = write(&path, &contents) {
+                println!("failed to save persistent state: {}", err);
+            }
+        }
+    };
+    loop {
+        let message = match &waiting {
+            None => match rx.recv() {
+                Ok(message) => message,
+                Err(_) => return,
+            },
+            Some(_) => match rx.recv_timeout(SAVE_DELAY) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    flush(&mut waiting);
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    flush(&mut waiting);
+                    return;
+                }
+            },
+        };
+        match message {
+            WriterMessage::Write(path, contents) => {
+                let since = waiting
+                    .as_ref()
+                    .map_or_else(Instant::now, |(_, _, since)| *since);
+                waiting = Some((path, contents, since));
+                if since.elapsed() >= MAX_SAVE_DELAY {
+                    flush(&mut waiting);
+                }
+            }
+            WriterMessage::Flush(done) => {
+                flush(&mut waiting);
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Where persistent state is stored unless the app calls
+/// `Context::set_persistence_path`. This is a file named after the
+/// executable in the platform's per-user config directory.
+pub fn default_persistence_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let name = exe.file_stem()?.to_string_lossy().into_owned();
+
+    let dir = if cfg!(target_os = "windows") {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support")
+    } else if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME") {
+        PathBuf::from(dir)
+    } else {
+        PathBuf::from(std::env::var_os("HOME")?).join(".config")
+    };
+
+    Some(dir.join(name).join("state.json"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("rui-persist-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = PersistentStore::load(&path);
+        assert_eq!(store.get::<f32>("value"), None);
+
+        store.set("value", &0.5f32);
+        store.set("name", &"rui".to_string());
+        store.save().unwrap();
+
+        // Changes come together, after a pause.
+        store.set("value", &0.75f32);
+        store.save().unwrap();
+        assert!(!path.exists());
+        std::thread::sleep(SAVE_DELAY * 2);
+        assert!(path.exists());
+
+        store.set("value", &1.0f32);
+        store.flush().unwrap();
+        let loaded = PersistentStore::load(&path);
+        assert_eq!(loaded.get::<f32>("value"), Some(1.0));
+        assert_eq!(loaded.get::<String>("name"), Some("rui".to_string()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,375 @@
+use crate::*;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// A part of the work done for each frame. See `FrameTimings`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    /// Rebuilding view bodies while updating: running animations, finding
+    /// live state, accessibility and dirty rects.
+    Build,
+    Layout,
+    /// Recording vger commands.
+    Draw,
+    /// Encoding and submitting GPU work, and presenting the frame.
+    Gpu,
+}
+
+impl FramePhase {
+    pub fn name(self) -> &'static str {
+        match self {
+            FramePhase::Build => "build",
+            FramePhase::Layout => "layout",
+            FramePhase::Draw => "draw",
+            FramePhase::Gpu => "gpu",
+        }
+    }
+}
+
+type SpanHook = Box<dyn FnMut(FramePhase, Duration)>;
+type StartupHook = Box<dyn FnOnce(StartupTimings)>;
+
+/// How long each phase of starting up took, until the first frame was
+/// presented. See `Context::set_startup_hook`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StartupTimings {
+    /// Creating the window.
+    pub window: Duration,
+
+    /// Creating the surface and finding an adapter and device. Includes
+    /// creating the wgpu instance, unless one was passed to
+    /// `App::wgpu_instance` or another window already made it.
+    pub gpu: Duration,
+
+    /// Creating vger, which builds its pipelines and glyph atlas.
+    pub renderer: Duration,
+
+    /// Updating and laying out before the first frame.
+    pub update: Duration,
+
+    /// Drawing and presenting the first frame.
+    pub first_frame: Duration,
+
+    /// Everything until the first frame was presented: from `App::run`
+    /// with the winit backend, or from creating the `Context` otherwise.
+    pub total: Duration,
+}
+
+/// Times phases of a frame. Always zero on wasm, which has no clock.
+#[derive(Clone, Copy)]
+pub(crate) struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now(),
+        }
+    }
+
+    /// A stopwatch which has been running for `elapsed` already.
+    pub(crate) fn started_ago(elapsed: Duration) -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let _ = elapsed;
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now()
+                .checked_sub(elapsed)
+                .unwrap_or_else(Instant::now),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Settings and bookkeeping for the profiling overlay.
+pub(crate) struct Profiler {
+    pub(crate) overlay: bool,
+    hook: Option<SpanHook>,
+
+    /// When the last frame was drawn.
+    last_frame: Option<Stopwatch>,
+
+    /// Frames per second, smoothed.
+    fps: f32,
+
+    /// When startup began.
+    launch: Stopwatch,
+
+    /// Phases of startup measured so far.
+    startup: StartupTimings,
+
+    /// Set once the first frame has been presented.
+    started: bool,
+
+    startup_hook: Option<StartupHook>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            overlay: false,
+            hook: None,
+            last_frame: None,
+            fps: 0.0,
+            launch: Stopwatch::start(),
+            startup: StartupTimings::default(),
+            started: false,
+            startup_hook: None,
+        }
+    }
+}
+
+/// Size of the overlay's text.
+const OVERLAY_TEXT_SIZE: u32 = 12;
+
+impl Context {
+    /// Shows frame rate, how long each phase of the last frame took, and
+    /// how many states and views there are, over the top left of the
+    /// window. Shift-F12 toggles it too.
+    pub fn set_profiling_overlay(&mut self, overlay: bool) {
+        self.profiler.overlay = overlay;
+        self.set_dirty();
+    }
+
+    pub fn profiling_overlay(&self) -> bool {
+        self.profiler.overlay
+    }
+
+    /// Calls `hook` with each phase of each frame as it finishes, to export
+    /// spans to `tracing` or another profiler.
+    pub fn set_span_hook(&mut self, hook: impl FnMut(FramePhase, Duration) + 'static) {
+        self.profiler.hook = Some(Box::new(hook));
+    }
+
+    /// Records how long a phase took. Layout happens both when updating and
+    /// rendering, so that adds up.
+    pub(crate) fn record_span(&mut self, phase: FramePhase, duration: Duration) {
+        let timings = &mut self.frame_timings;
+        match phase {
+            FramePhase::Build => timings.build = duration,
+            FramePhase::Layout => timings.layout += duration,
+            FramePhase::Draw => timings.draw = duration,
+            FramePhase::Gpu => timings.gpu = duration,
+        }
+        if let Some(hook) = &mut self.profiler.hook {
+            hook(phase, duration);
+        }
+    }
+
+    /// How long starting up took, once the first frame has been presented.
+    pub fn startup_timings(&self) -> Option<StartupTimings> {
+        Some(self.profiler.startup).filter(|_| self.profiler.started)
+    }
+
+    /// Calls `hook` once the first frame has been presented, with how long
+    /// each phase of starting up took, to check against a first frame
+    /// budget. Call it from `AppConfig::on_start`. Backends other than
+    /// winit can only report the phases from creating the `Context` on.
+    pub fn set_startup_hook(&mut self, hook: impl FnOnce(StartupTimings) + 'static) {
+        match self.startup_timings() {
+            Some(timings) => hook(timings),
+            None => self.profiler.startup_hook = Some(Box::new(hook)),
+        }
+    }
+
+    /// Starts timing startup from `launch` rather than from creating the
+    /// `Context`, with the phases the backend went through before that.
+    #[cfg(feature = "winit")]
+    pub(crate) fn begin_startup(&mut self, launch: Stopwatch, timings: StartupTimings) {
+        self.profiler.launch = launch;
+        self.profiler.startup = timings;
+    }
+
+    /// Adds time spent updating, if the first frame hasn't been presented.
+    pub(crate) fn record_startup_update(&mut self, duration: Duration) {
+        if !self.profiler.started {
+            self.profiler.startup.update += duration;
+        }
+    }
+
+    /// Notes that a frame was presented. The first one ends startup.
+    pub(crate) fn finish_startup(&mut self, first_frame: Duration) {
+        let p = &mut self.profiler;
+        if p.started {
+            return;
+        }
+        p.started = true;
+        p.startup.first_frame = first_frame;
+        p.startup.total = p.launch.elapsed();
+        if let Some(hook) = p.startup_hook.take() {
+            hook(p.startup);
+        }
+    }
+
+    /// Notes that a frame is being drawn, for the frame rate.
+    pub(crate) fn note_frame(&mut self) {
+        if let Some(last) = &self.profiler.last_frame {
+            let interval = last.elapsed();
+            self.frame_timings.interval = interval;
+            let secs = interval.as_secs_f32();
+            if secs > 0.0 {
+                let fps = 1.0 / secs;
+                let p = &mut self.profiler;
+                p.fps = if p.fps == 0.0 {
+                    fps
+                } else {
+                    p.fps + (fps - p.fps) * 0.1
+                };
+            }
+        }
+        self.profiler.last_frame = Some(Stopwatch::start());
+    }
+
+    /// The lines the overlay shows.
+    pub(crate) fn overlay_lines(&self) -> Vec<String> {
+        let t = self.frame_timings;
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let mut lines = vec![
+            format!(
+                "{:.0} fps, {:.1} ms",
+                self.profiler.fps,
+                ms(t.update + t.render)
+            ),
+            format!("build {:.2} ms", ms(t.build)),
+            format!("layout {:.2} ms", ms(t.layout)),
+            format!("draw {:.2} ms", ms(t.draw)),
+            format!("gpu {:.2} ms", ms(t.gpu)),
+            format!(
+                "{} states, {} views",
+                self.state_map.len(),
+                self.layout.len()
+            ),
+        ];
+        if let Some(startup) = self.startup_timings() {
+            lines.push(format!("startup {:.1} ms", ms(startup.total)));
+        }
+        lines
+    }
+
+    /// Draws the overlay in world coordinates.
+    pub(crate) fn draw_overlay(
+        &self,
+        vger: &mut Vger,
+        window_size: euclid::Size2D<f32, WorldSpace>,
+    ) {
+        const PADDING: f32 = 6.0;
+        let lines = self.overlay_lines();
+        let line_height = OVERLAY_TEXT_SIZE as f32 * 1.4;
+        let width = lines
+            .iter()
+            .map(|line| vger.text_bounds(line, OVERLAY_TEXT_SIZE, None).width())
+            .fold(0.0, f32::max);
+        let height = line_height * lines.len() as f32;
+
+        let top = window_size.height - PADDING;
+        let background = LocalRect::new(
+            [PADDING, top - height - 2.0 * PADDING].into(),
+            [width + 2.0 * PADDING, height + 2.0 * PADDING].into(),
+        );
+        let paint = vger.color_paint(Color::new(0.0, 0.0, 0.0, 0.75));
+        vger.fill_rect(background, 4.0, paint);
+
+        for (i, line) in lines.iter().enumerate() {
+            let origin = vger.text_bounds(line, OVERLAY_TEXT_SIZE, None).origin;
+            let y = top - PADDING - line_height * (i + 1) as f32;
+            vger.save();
+            vger.translate([2.0 * PADDING - origin.x, y - origin.y]);
+            vger.text(
+                line,
+                OVERLAY_TEXT_SIZE,
+                Color::new(1.0, 1.0, 1.0, 1.0),
+                None,
+            );
+            vger.restore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_span_hook() {
+        let spans = Rc::new(RefCell::new(vec![]));
+        let s = spans.clone();
+        let mut h = Harness::new(state(
+            || 0,
+            |n, cx| button(format!("{}", cx[n]), move |cx| cx[n] += 1),
+        ));
+        h.context()
+            .set_span_hook(move |phase, _| s.borrow_mut().push(phase));
+
+        h.click("0");
+        let spans = spans.borrow();
+        assert!(spans.contains(&FramePhase::Build));
+        assert!(spans.contains(&FramePhase::Layout));
+    }
+
+    #[test]
+    fn test_layout_timing_resets() {
+        let mut h = Harness::new(state(
+            || 0,
+            |n, cx| button(format!("{}", cx[n]), move |cx| cx[n] += 1),
+        ));
+        h.click("0");
+        // An update with nothing to lay out doesn't carry the last one's.
+        h.update();
+        assert_eq!(h.context().frame_timings().layout, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_overlay_toggle() {
+        let mut h = Harness::new(text("hi"));
+        assert!(!h.context().profiling_overlay());
+
+        h.context().key_mods.shift = true;
+        h.key(Key::F12);
+        assert!(h.context().profiling_overlay());
+
+        let lines = h.context().overlay_lines();
+        assert!(lines.iter().any(|line| line.ends_with("views")));
+    }
+
+    #[test]
+    fn test_startup_hook() {
+        let reported = Rc::new(RefCell::new(None));
+        let r = reported.clone();
+        let mut h = Harness::new(text("hi"));
+        let cx = h.context();
+        cx.set_startup_hook(move |timings| *r.borrow_mut() = Some(timings));
+        cx.record_startup_update(Duration::from_millis(3));
+        assert_eq!(cx.startup_timings(), None);
+
+        cx.finish_startup(Duration::from_millis(5));
+        let timings = reported.borrow().unwrap();
+        assert!(timings.update >= Duration::from_millis(3));
+        assert_eq!(timings.first_frame, Duration::from_millis(5));
+        assert!(timings.total > Duration::ZERO);
+        assert_eq!(cx.startup_timings(), Some(timings));
+
+        // Later frames and updates don't change it.
+        cx.record_startup_update(Duration::from_millis(3));
+        cx.finish_startup(Duration::from_millis(1));
+        assert_eq!(cx.startup_timings(), Some(timings));
+        assert!(cx.overlay_lines().iter().any(|l| l.starts_with("startup")));
+    }
+}
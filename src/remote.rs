@@ -0,0 +1,440 @@
+//! Experimental: views described by data, for UI sent at runtime by a
+//! server or plugin.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A serializable description of a view tree, rendered with `remote_view`.
+///
+/// In JSON, each node is an object tagged with its `"type"`:
+///
+/// ```
+/// # use rui::*;
+/// let desc = ViewDescription::from_json(r#"
+///     { "type": "vstack", "children": [
+///         { "type": "text", "text": "Volume" },
+///         { "type": "slider", "id": "volume", "initial": 0.5 },
+///         { "type": "button", "label": "Apply", "action": "apply" }
+///     ] }
+/// "#).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ViewDescription {
+    Text {
+        text: String,
+        #[serde(default)]
+        size: Option<u32>,
+    },
+
+    /// Emits a `RemoteAction` with the id `action` when tapped.
+    Button {
+        label: String,
+        action: String,
+    },
+
+    /// A toggle whose value is kept locally under `id`.
+    Toggle {
+        id: String,
+        #[serde(default)]
+        initial: bool,
+    },
+
+    /// A slider from 0 to 1 whose value is kept locally under `id`.
+    Slider {
+        id: String,
+        #[serde(default)]
+        initial: f32,
+    },
+
+    Vstack {
+        children: Vec<ViewDescription>,
+    },
+    Hstack {
+        children: Vec<ViewDescription>,
+    },
+    Zstack {
+        children: Vec<ViewDescription>,
+    },
+    Spacer,
+    Padding {
+        padding: f32,
+        child: Box<ViewDescription>,
+    },
+}
+
+impl ViewDescription {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Calls `f` on each toggle and slider, in order.
+    fn foreach_control(&self, f: &mut impl FnMut(&ViewDescription)) {
+        match self {
+            ViewDescription::Toggle { .. } | ViewDescription::Slider { .. } => f(self),
+            ViewDescription::Vstack { children }
+            | ViewDescription::Hstack { children }
+            | ViewDescription::Zstack { children } => {
+                for child in children {
+                    child.foreach_control(f)
+                }
+            }
+            ViewDescription::Padding { child, .. } => child.foreach_control(f),
+            _ => (),
+        }
+    }
+}
+
+/// Action emitted by a button in a `remote_view`. Handle it with
+/// `.handle(|cx, action: &RemoteAction| ...)`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteAction {
+    /// The button's `action`.
+    pub id: String,
+
+    /// Current values of the toggles and sliders, by id.
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+/// Local state for the controls in a `remote_view`, by control id.
+#[derive(Default)]
+struct RemoteValues {
+    bools: HashMap<String, bool>,
+    floats: HashMap<String, f32>,
+
+    /// Ids of the description's controls, in order, so bindings can find
+    /// their control's value from where it is in the description.
+    order: Vec<String>,
+}
+
+impl RemoteValues {
+    fn new(desc: &ViewDescription) -> Self {
+        let mut values = Self::default();
+        values.sync(desc);
+        values
+    }
+
+    /// Catches up with a description which isn't the one the values were
+    /// made for. Controls it adds start at their initial values, and
+    /// values of controls it dropped are kept in case they come back.
+    fn sync(&mut self, desc: &ViewDescription) {
+        let mut order = vec![];
+        desc.foreach_control(&mut |control| match control {
+            ViewDescription::Toggle { id, initial } => {
+                self.bools.entry(id.clone()).or_insert(*initial);
+                order.push(id.clone());
+            }
+            ViewDescription::Slider { id, initial } => {
+                self.floats.entry(id.clone()).or_insert(*initial);
+                order.push(id.clone());
+            }
+            _ => (),
+        });
+        self.order = order;
+    }
+
+    /// Were the values synced with `desc`, or one with the same controls?
+    fn is_current(&self, desc: &ViewDescription) -> bool {
+        let mut i = 0;
+        let mut current = true;
+        desc.foreach_control(&mut |control| {
+            let (id, present) = match control {
+                ViewDescription::Toggle { id, .. } => (id, self.bools.contains_key(id)),
+                ViewDescription::Slider { id, .. } => (id, self.floats.contains_key(id)),
+                _ => return,
+            };
+            current &= present && self.order.get(i) == Some(id);
+            i += 1;
+        });
+        current && i == self.order.len()
+    }
+
+    fn snapshot(&self, desc: &ViewDescription) -> HashMap<String, serde_json::Value> {
+        let mut map = HashMap::new();
+        desc.foreach_control(&mut |control| match control {
+            ViewDescription::Toggle { id, .. } => {
+                if let Some(value) = self.bools.get(id) {
+                    map.insert(id.clone(), (*value).into());
+                }
+            }
+            ViewDescription::Slider { id, .. } => {
+                if let Some(value) = self.floats.get(id) {
+                    map.insert(id.clone(), (*value).into());
+                }
+            }
+            _ => (),
+        });
+        map
+    }
+}
+
+fn remote_values(vid: ViewId, cx: &mut Context) -> &mut RemoteValues {
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+fn build(
+    desc: &ViewDescription,
+    root: &Rc<ViewDescription>,
+    values: StateHandle<RemoteValues>,
+    control: &mut usize,
+) -> AnyView {
+    match desc {
+        ViewDescription::Text { text: t, size } => {
+            AnyView::new(text(t).font_size(size.unwrap_or(Text::DEFAULT_SIZE)))
+        }
+        ViewDescription::Button { label, action } => {
+            let id = action.clone();
+            let root = root.clone();
+            AnyView::new(button(text(label), move |cx| RemoteAction {
+                id: id.clone(),
+                values: cx[values].snapshot(&root),
+            }))
+        }
+        ViewDescription::Toggle { .. } => {
+            let i = *control;
+            *control += 1;
+            AnyView::new(toggle(binding(
+                move |cx| {
+                    let values = &cx[values];
+                    &values.bools[&values.order[i]]
+                },
+                move |cx| {
+                    let values = &mut cx[values];
+                    values.bools.get_mut(&values.order[i]).unwrap()
+                },
+            )))
+        }
+        ViewDescription::Slider { .. } => {
+            let i = *control;
+            *control += 1;
+            AnyView::new(hslider(binding(
+                move |cx| {
+                    let values = &cx[values];
+                    &values.floats[&values.order[i]]
+                },
+                move |cx| {
+                    let values = &mut cx[values];
+                    values.floats.get_mut(&values.order[i]).unwrap()
+                },
+            )))
+        }
+        ViewDescription::Vstack { children } => {
+            AnyView::new(vstack(build_all(children, root, values, control)))
+        }
+        ViewDescription::Hstack { children } => {
+            AnyView::new(hstack(build_all(children, root, values, control)))
+        }
+        ViewDescription::Zstack { children } => {
+            AnyView::new(zstack(build_all(children, root, values, control)))
+        }
+        ViewDescription::Spacer => AnyView::new(spacer()),
+        ViewDescription::Padding { padding, child } => {
+            AnyView::new(build(child, root, values, control).padding(*padding))
+        }
+    }
+}
+
+fn build_all(
+    children: &[ViewDescription],
+    root: &Rc<ViewDescription>,
+    values: StateHandle<RemoteValues>,
+    control: &mut usize,
+) -> Vec<AnyView> {
+    children
+        .iter()
+        .map(|c| build(c, root, values, control))
+        .collect()
+}
+
+/// The views built from a description, which brings the local values up
+/// to date with the description before its controls read them.
+struct RemoteBody {
+    desc: Rc<ViewDescription>,
+    values: StateHandle<RemoteValues>,
+    child: AnyView,
+}
+
+impl RemoteBody {
+    /// Adds values for controls the description gained, without marking
+    /// the state as changed.
+    fn sync(&self, cx: &mut Context) {
+        let values = remote_values(self.values.id, cx);
+        if !values.is_current(&self.desc) {
+            values.sync(&self.desc);
+        }
+    }
+}
+
+impl View for RemoteBody {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        self.sync(cx);
+        self.child.process(event, path, cx, actions);
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        self.sync(args.cx);
+        self.child.draw(path, args);
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        self.sync(args.cx);
+        self.child.layout(path, args)
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        self.sync(cx);
+        self.child.dirty(path, xform, cx);
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        self.sync(cx);
+        self.child.hittest(path, pt, cx)
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        self.sync(cx);
+        self.child.commands(path, cx, cmds);
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        self.sync(cx);
+        self.child.gc_view(path, cx, map);
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        self.sync(cx);
+        self.child.access(path, cx, nodes)
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.child.is_flexible()
+    }
+}
+
+impl private::Sealed for RemoteBody {}
+
+/// Renders a `ViewDescription`. Toggles and sliders keep their values
+/// locally, by id, and buttons emit a `RemoteAction` carrying those values.
+///
+/// When the description changes, controls keep their values by id, so
+/// they may be added, removed or moved around.
+pub fn remote_view(desc: ViewDescription) -> impl View {
+    let desc = Rc::new(desc);
+    let initial = desc.clone();
+    state(
+        move || RemoteValues::new(&initial),
+        move |values, _| RemoteBody {
+            desc: desc.clone(),
+            values,
+            child: build(&desc, &desc, values, &mut 0),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const DESC: &str = r#"
+        { "type": "vstack", "children": [
+            { "type": "toggle", "id": "enabled" },
+            { "type": "slider", "id": "volume", "initial": 0.5 },
+            { "type": "padding", "padding": 5, "child":
+                { "type": "button", "label": "apply", "action": "apply" } }
+        ] }
+    "#;
+
+    #[test]
+    fn test_round_trip() {
+        let desc = ViewDescription::from_json(DESC).unwrap();
+        assert_eq!(ViewDescription::from_json(&desc.to_json()).unwrap(), desc);
+        assert!(ViewDescription::from_json(r#"{ "type": "bogus" }"#).is_err());
+    }
+
+    #[test]
+    fn test_remote_action() {
+        let desc = ViewDescription::from_json(DESC).unwrap();
+        let view = state(String::new, move |last, cx| {
+            vstack((
+                remote_view(desc.clone()).handle(move |cx, action: &RemoteAction| {
+                    let values = &action.values;
+                    cx[last] = format!("{} {} {}", action.id, values["enabled"], values["volume"]);
+                }),
+                text(&cx[last]),
+            ))
+        });
+
+        let mut harness = Harness::new(view);
+        harness.click("apply");
+        harness.expect_text("apply false 0.5");
+    }
+
+    #[test]
+    fn test_description_change() {
+        let first = ViewDescription::from_json(DESC).unwrap();
+        // Adds a control and moves the others around.
+        let second = ViewDescription::from_json(
+            r#"
+            { "type": "vstack", "children": [
+                { "type": "slider", "id": "balance", "initial": 0.25 },
+                { "type": "slider", "id": "volume" },
+                { "type": "hstack", "children": [
+                    { "type": "toggle", "id": "enabled" },
+                    { "type": "button", "label": "apply", "action": "apply" }
+                ] }
+            ] }
+        "#,
+        )
+        .unwrap();
+        let view = state(
+            || (false, String::new()),
+            move |s, cx| {
+                let desc = if cx[s].0 { &second } else { &first };
+                vstack((
+                    button("switch", move |cx| cx[s].0 = true),
+                    remote_view(desc.clone()).handle(move |cx, action: &RemoteAction| {
+                        let v = &action.values;
+                        cx[s].1 = format!("{} {} {}", v["enabled"], v["volume"], v["balance"]);
+                    }),
+                    text(&cx[s].1),
+                ))
+            },
+        );
+
+        let mut harness = Harness::new(view);
+        for holder in harness.context().state_map.values_mut() {
+            if let Some(values) = holder.state.downcast_mut::<RemoteValues>() {
+                values.bools.insert("enabled".into(), true);
+            }
+        }
+        harness.click("switch");
+        harness.click("apply");
+        // Values follow their controls' ids, and new controls start where
+        // the description says.
+        harness.expect_text("true 0.5 0.25");
+    }
+}
@@ -0,0 +1,544 @@
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+#[cfg(feature = "rhai")]
+mod rhai;
+#[cfg(feature = "rhai")]
+pub use self::rhai::*;
+
+/// Values passed between rui and a scripting engine.
+///
+/// Scripts describe their UI as a tree of these. A node is either a
+/// string, which is shown as text, or an object whose `"type"` names a
+/// widget in the `WidgetRegistry`. The other fields are the widget's
+/// properties, and `"children"` holds its child nodes:
+///
+/// ```json
+/// { "type": "vstack", "children": [
+///     "Hello",
+///     { "type": "button", "label": "Tap", "on_tap": "tapped" }
+/// ] }
+/// ```
+///
+/// Callbacks are named by string and run with `ScriptEngine::call`.
+pub type ScriptValue = serde_json::Value;
+
+/// Errors from building a view out of a script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    /// A node's `"type"` isn't in the registry.
+    UnknownWidget(String),
+
+    /// A node isn't a string or an object with a `"type"`.
+    InvalidNode(String),
+
+    /// Reported by the scripting engine.
+    Engine(String),
+
+    /// A script file couldn't be read.
+    Io(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::UnknownWidget(name) => write!(f, "unknown widget: {}", name),
+            ScriptError::InvalidNode(node) => write!(f, "invalid node: {}", node),
+            ScriptError::Engine(msg) => write!(f, "script error: {}", msg),
+            ScriptError::Io(msg) => write!(f, "couldn't read script: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Implemented by an embedded interpreter so it can drive a `script_view`.
+pub trait ScriptEngine: 'static {
+    /// Describes the UI for the engine's current state.
+    fn render(&self) -> Result<ScriptValue, ScriptError>;
+
+    /// Runs a callback named in the tree returned by `render`.
+    fn call(&mut self, callback: &str, args: &[ScriptValue]) -> Result<(), ScriptError>;
+
+    /// Replaces the script with `source`, keeping the script's state, for
+    /// `script_file_view`. Returns false if the engine can't do that, in
+    /// which case a new engine is made and the script's state starts over.
+    fn reload(&mut self, _source: &str) -> Result<bool, ScriptError> {
+        Ok(false)
+    }
+}
+
+type EngineFn = dyn Fn(&str) -> Result<Box<dyn ScriptEngine>, ScriptError>;
+
+/// A script file reloaded when it changes.
+struct ScriptWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+
+    /// When the file was last checked.
+    checked: Option<Stopwatch>,
+
+    /// Makes an engine for the file's contents.
+    engine: Rc<EngineFn>,
+}
+
+/// State for `script_view`.
+struct ScriptState {
+    /// None if a script file hasn't loaded yet.
+    engine: Option<Box<dyn ScriptEngine>>,
+
+    /// Last error from a callback or loading, shown instead of the UI.
+    error: Option<ScriptError>,
+
+    /// Set by `script_file_view`.
+    watch: Option<ScriptWatch>,
+}
+
+impl ScriptState {
+    /// Loads the watched file, into the current engine if there is one.
+    fn load_file(&mut self) {
+        let watch = match &self.watch {
+            Some(watch) => watch,
+            None => return,
+        };
+        let source = match std::fs::read_to_string(&watch.path) {
+            Ok(source) => source,
+            Err(err) => {
+                self.error = Some(ScriptError::Io(format!(
+                    "{}: {}",
+                    watch.path.display(),
+                    err
+                )));
+                return;
+            }
+        };
+        let reloaded = match &mut self.engine {
+            Some(engine) => engine.reload(&source),
+            None => Ok(false),
+        };
+        let result = match reloaded {
+            Ok(true) => Ok(()),
+            Ok(false) => (watch.engine)(&source).map(|engine| self.engine = Some(engine)),
+            Err(err) => Err(err),
+        };
+        self.error = result.err();
+    }
+}
+
+/// Reloads the state's script file if it's changed. Keeps the views'
+/// state, since the rebuilt views have the same ids where they're the same
+/// kind of view.
+fn poll_script_file(state: StateHandle<ScriptState>, cx: &mut Context) {
+    cx.watching_scripts = true;
+
+    // Checking the file doesn't change what's shown, so it doesn't dirty
+    // the state.
+    let watch = match cx
+        .state_map
+        .get_mut(&state.id)
+        .and_then(|holder| holder.state.downcast_mut::<ScriptState>())
+        .and_then(|s| s.watch.as_mut())
+    {
+        Some(watch) => watch,
+        None => return,
+    };
+    if watch
+        .checked
+        .as_ref()
+        .is_some_and(|checked| checked.elapsed() < FILE_POLL_INTERVAL)
+    {
+        return;
+    }
+    watch.checked = Some(Stopwatch::start());
+
+    let modified = file_modified(&watch.path);
+    if modified == watch.modified {
+        return;
+    }
+    watch.modified = modified;
+    cx[state].load_file();
+}
+
+/// A callback into the script, for use in widget constructors.
+#[derive(Clone)]
+pub struct ScriptCallback {
+    state: StateHandle<ScriptState>,
+    name: String,
+}
+
+impl ScriptCallback {
+    pub fn call(&self, cx: &mut Context, args: &[ScriptValue]) {
+        let result = match &mut cx[self.state].engine {
+            Some(engine) => engine.call(&self.name, args),
+            None => Ok(()),
+        };
+        if let Err(err) = result {
+            cx[self.state].error = Some(err);
+        }
+    }
+}
+
+/// Properties and children of a node, passed to widget constructors.
+pub struct WidgetArgs<'a> {
+    pub props: &'a serde_json::Map<String, ScriptValue>,
+    pub children: Vec<AnyView>,
+    state: StateHandle<ScriptState>,
+}
+
+impl<'a> WidgetArgs<'a> {
+    pub fn str(&self, key: &str) -> Option<&str> {
+        self.props.get(key).and_then(|v| v.as_str())
+    }
+
+    pub fn f32(&self, key: &str) -> Option<f32> {
+        self.props
+            .get(key)
+            .and_then(|v| v.as_f64())
+            .map(|v| v as f32)
+    }
+
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        self.props.get(key).and_then(|v| v.as_bool())
+    }
+
+    /// The callback named by the property `key`, if any.
+    pub fn callback(&self, key: &str) -> Option<ScriptCallback> {
+        self.str(key).map(|name| ScriptCallback {
+            state: self.state,
+            name: name.to_string(),
+        })
+    }
+}
+
+type WidgetFn = dyn Fn(WidgetArgs) -> Result<AnyView, ScriptError>;
+
+/// Widgets which scripts can construct, by name.
+///
+/// `WidgetRegistry::new` includes `text`, `button`, `vstack`, `hstack`,
+/// `zstack` and `spacer`. Any node can also have a `"padding"` property.
+#[derive(Clone)]
+pub struct WidgetRegistry {
+    widgets: HashMap<String, Rc<WidgetFn>>,
+}
+
+impl Default for WidgetRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WidgetRegistry {
+    /// A registry with the built-in widgets.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register("text", |args| {
+            let size = args
+                .f32("size")
+                .map(|s| s as u32)
+                .unwrap_or(Text::DEFAULT_SIZE);
+            Ok(AnyView::new(
+                text(args.str("text").unwrap_or_default()).font_size(size),
+            ))
+        });
+        registry.register("button", |args| {
+            let label = text(args.str("label").unwrap_or_default());
+            let on_tap = args.callback("on_tap");
+            Ok(AnyView::new(button(label, move |cx| {
+                if let Some(on_tap) = &on_tap {
+                    on_tap.call(cx, &[]);
+                }
+            })))
+        });
+        registry.register("vstack", |args| Ok(AnyView::new(vstack(args.children))));
+        registry.register("hstack", |args| Ok(AnyView::new(hstack(args.children))));
+        registry.register("zstack", |args| Ok(AnyView::new(zstack(args.children))));
+        registry.register("spacer", |_| Ok(AnyView::new(spacer())));
+        registry
+    }
+
+    /// A registry without any widgets.
+    pub fn empty() -> Self {
+        Self {
+            widgets: HashMap::new(),
+        }
+    }
+
+    /// Adds or replaces a widget.
+    pub fn register(
+        &mut self,
+        name: &str,
+        f: impl Fn(WidgetArgs) -> Result<AnyView, ScriptError> + 'static,
+    ) -> &mut Self {
+        self.widgets.insert(name.to_string(), Rc::new(f));
+        self
+    }
+
+    fn build(
+        &self,
+        node: &ScriptValue,
+        state: StateHandle<ScriptState>,
+    ) -> Result<AnyView, ScriptError> {
+        let props = match node {
+            ScriptValue::String(s) => return Ok(AnyView::new(text(s))),
+            ScriptValue::Object(props) => props,
+            _ => return Err(ScriptError::InvalidNode(node.to_string())),
+        };
+        let name = props
+            .get("type")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| ScriptError::InvalidNode(node.to_string()))?;
+        let widget = self
+            .widgets
+            .get(name)
+            .ok_or_else(|| ScriptError::UnknownWidget(name.to_string()))?;
+
+        let children = match props.get("children") {
+            Some(ScriptValue::Array(nodes)) => nodes
+                .iter()
+                .map(|n| self.build(n, state))
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(n) => vec![self.build(n, state)?],
+            None => vec![],
+        };
+
+        let view = widget(WidgetArgs {
+            props,
+            children,
+            state,
+        })?;
+
+        Ok(match props.get("padding").and_then(|p| p.as_f64()) {
+            Some(p) => AnyView::new(view.padding(p as f32)),
+            None => view,
+        })
+    }
+
+    /// Builds the UI for a script's state, or shows its error.
+    fn view(&self, state: StateHandle<ScriptState>, cx: &Context) -> AnyView {
+        let result = match (&cx[state].error, &cx[state].engine) {
+            (Some(err), _) => Err(err.clone()),
+            (None, Some(engine)) => engine.render(),
+            (None, None) => return AnyView::new(EmptyView {}),
+        };
+        match result.and_then(|node| self.build(&node, state)) {
+            Ok(view) => view,
+            Err(err) => AnyView::new(text(&err.to_string()).color(RED_HIGHLIGHT)),
+        }
+    }
+}
+
+/// Shows the UI described by a scripting engine, built from widgets in
+/// `registry`. The UI is rebuilt whenever a callback changes the engine's
+/// state. Errors are shown in place of the UI.
+pub fn script_view<E: ScriptEngine>(
+    registry: WidgetRegistry,
+    engine: impl Fn() -> E + 'static,
+) -> impl View {
+    state(
+        move || ScriptState {
+            engine: Some(Box::new(engine())),
+            error: None,
+            watch: None,
+        },
+        move |state, cx| registry.view(state, cx),
+    )
+}
+
+/// Like `script_view`, but with the script read from the file at `path`
+/// and passed to `engine`.
+///
+/// In debug builds, the file is reloaded whenever it changes, without
+/// restarting the app. The state of the views it builds is kept, and so is
+/// the script's own state if the engine supports `ScriptEngine::reload`,
+/// so UI can be worked on in place. An edit which doesn't load is shown in
+/// place of the UI until it's fixed.
+pub fn script_file_view<E: ScriptEngine>(
+    registry: WidgetRegistry,
+    path: impl Into<PathBuf>,
+    engine: impl Fn(&str) -> Result<E, ScriptError> + 'static,
+) -> impl View {
+    let path = path.into();
+    let engine: Rc<EngineFn> = Rc::new(move |source: &str| {
+        engine(source).map(|engine| Box::new(engine) as Box<dyn ScriptEngine>)
+    });
+    state(
+        move || {
+            let mut state = ScriptState {
+                engine: None,
+                error: None,
+                watch: Some(ScriptWatch {
+                    modified: file_modified(&path),
+                    path: path.clone(),
+                    checked: Some(Stopwatch::start()),
+                    engine: engine.clone(),
+                }),
+            };
+            state.load_file();
+            if !cfg!(debug_assertions) {
+                state.watch = None;
+            }
+            state
+        },
+        move |state, cx| {
+            registry
+                .view(state, cx)
+                .anim(move |cx, _| poll_script_file(state, cx))
+        },
+    )
+}
+
+impl Context {
+    /// Is a `script_file_view` watching its file for changes?
+    pub fn is_watching_scripts(&self) -> bool {
+        self.watching_scripts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::json;
+
+    /// A counter written "in script".
+    struct Counter {
+        count: i64,
+    }
+
+    impl ScriptEngine for Counter {
+        fn render(&self) -> Result<ScriptValue, ScriptError> {
+            Ok(json!({ "type": "vstack", "padding": 10, "children": [
+                format!("count: {}", self.count),
+                { "type": "button", "label": "increment", "on_tap": "increment" },
+                { "type": "button", "label": "fail", "on_tap": "missing" },
+            ]}))
+        }
+
+        fn call(&mut self, callback: &str, _args: &[ScriptValue]) -> Result<(), ScriptError> {
+            match callback {
+                "increment" => {
+                    self.count += 1;
+                    Ok(())
+                }
+                _ => Err(ScriptError::Engine(format!("no function {}", callback))),
+            }
+        }
+    }
+
+    #[test]
+    fn test_script_view() {
+        let mut harness = Harness::new(script_view(WidgetRegistry::new(), || Counter { count: 0 }));
+        harness.expect_text("count: 0");
+
+        harness.click("increment");
+        harness.expect_text("count: 1");
+
+        harness.click("fail");
+        harness.expect_text("script error: no function missing");
+    }
+
+    #[test]
+    fn test_unknown_widget() {
+        struct Bad;
+        impl ScriptEngine for Bad {
+            fn render(&self) -> Result<ScriptValue, ScriptError> {
+                Ok(json!({ "type": "slider" }))
+            }
+            fn call(&mut self, _: &str, _: &[ScriptValue]) -> Result<(), ScriptError> {
+                Ok(())
+            }
+        }
+
+        let mut harness = Harness::new(script_view(WidgetRegistry::new(), || Bad));
+        harness.expect_text("unknown widget: slider");
+    }
+
+    /// Shows its script, a label, with a count which survives reloading.
+    struct Labeled {
+        label: String,
+        count: i64,
+    }
+
+    impl ScriptEngine for Labeled {
+        fn render(&self) -> Result<ScriptValue, ScriptError> {
+            Ok(json!({ "type": "vstack", "children": [
+                format!("{}: {}", self.label, self.count),
+                { "type": "button", "label": "increment", "on_tap": "increment" },
+            ]}))
+        }
+
+        fn call(&mut self, _callback: &str, _args: &[ScriptValue]) -> Result<(), ScriptError> {
+            self.count += 1;
+            Ok(())
+        }
+
+        fn reload(&mut self, source: &str) -> Result<bool, ScriptError> {
+            self.label = labeled(source)?.label;
+            Ok(true)
+        }
+    }
+
+    fn labeled(source: &str) -> Result<Labeled, ScriptError> {
+        match source.trim() {
+            "" => Err(ScriptError::Engine("empty label".into())),
+            label => Ok(Labeled {
+                label: label.to_string(),
+                count: 0,
+            }),
+        }
+    }
+
+    /// Checks for changes on the next update, however soon.
+    fn edit_script(harness: &mut Harness<impl View>, path: &std::path::Path, source: &str) {
+        std::fs::write(path, source).unwrap();
+        for holder in harness.context().state_map.values_mut() {
+            if let Some(state) = holder.state.downcast_mut::<ScriptState>() {
+                let watch = state.watch.as_mut().unwrap();
+                watch.modified = None;
+                watch.checked = None;
+            }
+        }
+        harness.update();
+    }
+
+    #[test]
+    fn test_script_file_reload() {
+        let path = std::env::temp_dir().join(format!("rui-script-{}.txt", std::process::id()));
+        std::fs::write(&path, "count").unwrap();
+
+        let mut harness = Harness::new(script_file_view(WidgetRegistry::new(), &path, labeled));
+        assert!(harness.context().is_watching_scripts());
+        harness.click("increment");
+        harness.expect_text("count: 1");
+
+        edit_script(&mut harness, &path, "taps");
+        harness.expect_text("taps: 1");
+
+        // A bad edit is shown until it's fixed, without losing the count.
+        edit_script(&mut harness, &path, "");
+        harness.expect_text("script error: empty label");
+        edit_script(&mut harness, &path, "clicks");
+        harness.expect_text("clicks: 1");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_script_file() {
+        let path = std::env::temp_dir().join(format!("rui-missing-{}.txt", std::process::id()));
+        let mut harness = Harness::new(script_file_view(WidgetRegistry::new(), &path, labeled));
+        assert!(harness
+            .texts()
+            .iter()
+            .any(|(text, _)| text.starts_with("couldn't read script: ")));
+
+        // Loads once the file appears.
+        edit_script(&mut harness, &path, "count");
+        harness.expect_text("count: 0");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+use super::*;
+use ::rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use std::cell::RefCell;
+
+/// Runs a [rhai](https://rhai.rs) script as a `ScriptEngine`.
+///
+/// The script defines a `view()` function returning the UI tree, and
+/// functions for each callback. Functions share state through `this`,
+/// which is set by an optional `init()` function:
+///
+/// ```
+/// # use rui::*;
+/// let script = r#"
+///     fn init() { #{ count: 0 } }
+///     fn view() {
+///         #{ type: "button", label: `count: ${this.count}`, on_tap: "increment" }
+///     }
+///     fn increment() { this.count += 1; }
+/// "#;
+/// let view = script_view(WidgetRegistry::new(), move || RhaiEngine::new(script).unwrap());
+/// ```
+pub struct RhaiEngine {
+    engine: Engine,
+    ast: AST,
+    scope: RefCell<Scope<'static>>,
+    this: RefCell<Dynamic>,
+}
+
+fn engine_error(err: impl std::fmt::Display) -> ScriptError {
+    ScriptError::Engine(err.to_string())
+}
+
+impl RhaiEngine {
+    pub fn new(script: &str) -> Result<Self, ScriptError> {
+        Self::with_engine(Engine::new(), script)
+    }
+
+    /// Uses an engine with custom functions or settings.
+    pub fn with_engine(engine: Engine, script: &str) -> Result<Self, ScriptError> {
+        let ast = engine.compile(script).map_err(engine_error)?;
+        let has_init = ast.iter_functions().any(|f| f.name == "init");
+        let mut scope = Scope::new();
+        let this = if has_init {
+            engine
+                .call_fn::<Dynamic>(&mut scope, &ast, "init", ())
+                .map_err(engine_error)?
+        } else {
+            Dynamic::UNIT
+        };
+        Ok(Self {
+            engine,
+            ast,
+            scope: RefCell::new(scope),
+            this: RefCell::new(this),
+        })
+    }
+
+    fn call_fn(&self, name: &str, args: Vec<Dynamic>) -> Result<Dynamic, ScriptError> {
+        let mut this = self.this.borrow_mut();
+        let options = CallFnOptions::new().bind_this_ptr(&mut this);
+        self.engine
+            .call_fn_with_options(options, &mut self.scope.borrow_mut(), &self.ast, name, args)
+            .map_err(engine_error)
+    }
+}
+
+impl ScriptEngine for RhaiEngine {
+    fn render(&self) -> Result<ScriptValue, ScriptError> {
+        let tree = self.call_fn("view", vec![])?;
+        ::rhai::serde::from_dynamic(&tree).map_err(engine_error)
+    }
+
+    fn call(&mut self, callback: &str, args: &[ScriptValue]) -> Result<(), ScriptError> {
+        let args = args
+            .iter()
+            .map(::rhai::serde::to_dynamic)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(engine_error)?;
+        self.call_fn(callback, args).map(|_| ())
+    }
+
+    /// Keeps `this`, so `init` doesn't run again.
+    fn reload(&mut self, source: &str) -> Result<bool, ScriptError> {
+        self.ast = self.engine.compile(source).map_err(engine_error)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rhai_counter() {
+        let script = r#"
+            fn init() { #{ count: 0 } }
+            fn view() {
+                #{ type: "vstack", children: [
+                    `count: ${this.count}`,
+                    #{ type: "button", label: "increment", on_tap: "increment" },
+                ] }
+            }
+            fn increment() { this.count += 1; }
+        "#;
+        let mut harness = Harness::new(script_view(WidgetRegistry::new(), move || {
+            RhaiEngine::new(script).unwrap()
+        }));
+        harness.expect_text("count: 0");
+        harness.click("increment");
+        harness.expect_text("count: 1");
+    }
+
+    #[test]
+    fn test_rhai_reload() {
+        let mut engine =
+            RhaiEngine::new("fn init() { #{ count: 1 } } fn view() { `count: ${this.count}` }")
+                .unwrap();
+        assert!(engine
+            .reload("fn view() { `taps: ${this.count}` }")
+            .unwrap());
+        assert_eq!(engine.render().unwrap(), "taps: 1");
+        assert!(engine.reload("fn view() {").is_err());
+    }
+}
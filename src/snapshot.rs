@@ -0,0 +1,404 @@
+//! Rendering views offscreen, for golden image tests.
+
+use crate::*;
+use std::fmt;
+use std::sync::Arc;
+
+/// Error from `render_to_image`.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// There's no GPU adapter, not even a software one.
+    NoAdapter,
+    Device(wgpu::RequestDeviceError),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NoAdapter => write!(f, "no GPU adapter to render with"),
+            SnapshotError::Device(err) => write!(f, "couldn't get a GPU device: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Snapshots are sRGB, like most window surfaces, so colors match what's
+/// shown on screen.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+pub(crate) async fn request_device() -> Result<(wgpu::Device, wgpu::Queue), SnapshotError> {
+    let backends = wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all);
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    let adapter =
+        match wgpu::util::initialize_adapter_from_env_or_default(&instance, backends, None).await {
+            Some(adapter) => adapter,
+            // Try a software renderer, as on CI machines without GPUs.
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter: true,
+                    compatible_surface: None,
+                })
+                .await
+                .ok_or(SnapshotError::NoAdapter)?,
+        };
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("rui snapshot"),
+                features: wgpu::Features::default(),
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        )
+        .await
+        .map_err(SnapshotError::Device)
+}
+
+/// Lays out and draws `view` in a window of `size` points, without opening
+/// one, and reads back the pixels. Compare them with an image saved
+/// earlier to catch changes in how views look.
+///
+/// This sets up a GPU device each time, so it's slow. Without a GPU, it
+/// uses a software adapter if the system has one, like lavapipe or WARP.
+pub fn render_to_image(
+    view: &impl View,
+    size: impl Into<Size>,
+) -> Result<::image::RgbaImage, SnapshotError> {
+    render_to_image_with_scale(view, size, 1.0)
+}
+
+/// Like `render_to_image`, with `scale` pixels per point, to check how
+/// views look on high resolution screens.
+pub fn render_to_image_with_scale(
+    view: &impl View,
+    size: impl Into<Size>,
+    scale: f32,
+) -> Result<::image::RgbaImage, SnapshotError> {
+    let size: Size = size.into();
+    let width = (size.width * scale).ceil().max(1.0) as u32;
+    let height = (size.height * scale).ceil().max(1.0) as u32;
+
+    let (device, queue) = futures::executor::block_on(request_device())?;
+    let (device, queue) = (Arc::new(device), Arc::new(queue));
+    let mut vger = Vger::new(device.clone(), queue.clone(), FORMAT);
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("rui snapshot"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let window_size = [size.width, size.height].into();
+    let mut cx = Context::new();
+    cx.layer_renderer = Some(LayerRenderer::new(device.clone(), queue.clone(), FORMAT));
+    // There's no last frame to draw over.
+    cx.set_partial_redraw(false);
+    cx.set_scale_factor(scale);
+    cx.update(view, &mut vger, window_size);
+    cx.draw_frame(
+        FrameTarget {
+            device: &device,
+            queue: &queue,
+            view: &texture_view,
+            texture: Some(&texture),
+            width,
+            height,
+            format: FORMAT,
+        },
+        view,
+        &mut vger,
+        window_size,
+        scale,
+    );
+
+    let pixels = read_pixels(&device, &queue, &texture, [0, 0], [width, height]);
+    Ok(::image::RgbaImage::from_raw(width, height, pixels).unwrap())
+}
+
+/// What a GPU test got from `result`, panicking if it couldn't render.
+/// Tests which render are `#[ignore]`d, as not every machine has an
+/// adapter, and run with `cargo test -- --ignored` on one that does.
+#[cfg(test)]
+pub(crate) fn expect_gpu<T>(result: Result<T, SnapshotError>) -> T {
+    result.unwrap_or_else(|err| panic!("{}, which GPU tests need", err))
+}
+
+/// A device for a GPU test. See `expect_gpu`.
+#[cfg(test)]
+pub(crate) fn test_device() -> (Arc<wgpu::Device>, Arc<wgpu::Queue>) {
+    let (device, queue) = expect_gpu(futures::executor::block_on(request_device()));
+    (Arc::new(device), Arc::new(queue))
+}
+
+/// `render_to_image` for a GPU test. See `expect_gpu`.
+#[cfg(test)]
+pub(crate) fn test_render(view: &impl View, size: impl Into<Size>) -> ::image::RgbaImage {
+    expect_gpu(render_to_image(view, size))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_render_to_image() {
+        let view = hstack((
+            rectangle().color(Color::new(1.0, 0.0, 0.0, 1.0)),
+            rectangle().color(Color::new(0.0, 0.0, 1.0, 1.0)),
+        ));
+        let image = test_render(&view, Size::new(40.0, 20.0));
+        assert_eq!(image.dimensions(), (40, 20));
+
+        assert_eq!(image.get_pixel(5, 10).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(35, 10).0, [0, 0, 255, 255]);
+    }
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_lcd_text() {
+        let view = text("Illumination")
+            .font_size(12)
+            .color(Color::new(0.0, 0.0, 0.0, 1.0))
+            .background(rectangle().color(Color::WHITE))
+            .on_appear(|cx| cx.set_lcd_text(Some(SubpixelOrder::Rgb)));
+        let image = test_render(&view, Size::new(100.0, 20.0));
+        // Edges of stems cover some channels more than others.
+        let fringed = image.pixels().any(|p| {
+            let [r, g, b, _] = p.0;
+            r.abs_diff(b) > 40 || r.abs_diff(g) > 40
+        });
+        assert!(fringed);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_radial_gradient() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let view = circle().fill(Paint::radial([0.5, 0.5], 1.0, Color::WHITE, red));
+        let image = test_render(&view, Size::new(20.0, 20.0));
+        // White in the middle, fading to red at the edge.
+        let [r, g, _, _] = image.get_pixel(10, 10).0;
+        assert!(r > 240 && g > 240);
+        let [r, edge, _, _] = image.get_pixel(10, 1).0;
+        assert!(r > 240 && edge < 150);
+        let [_, g, _, _] = image.get_pixel(10, 5).0;
+        assert!(g > edge && g < 240);
+        // The corners are outside the circle.
+        assert_eq!(image.get_pixel(0, 0).0, image.get_pixel(19, 19).0);
+        assert!(image.get_pixel(0, 0).0[0] < 100);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_capture() {
+        let (device, queue) = test_device();
+        let mut vger = Vger::new(device.clone(), queue.clone(), FORMAT);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 40,
+                height: 20,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let view = hstack((
+            rectangle().color(Color::new(1.0, 0.0, 0.0, 1.0)),
+            rectangle().color(Color::new(0.0, 0.0, 1.0, 1.0)),
+        ));
+        let window_size = [40.0, 20.0].into();
+        let mut cx = Context::new();
+        cx.set_partial_redraw(false);
+        cx.request_capture([25.0, 10.0].into(), 10);
+
+        // The capture is read without waiting, in a later frame.
+        let mut frames = 0;
+        while cx.window_capture().is_none() {
+            assert!(frames < 10);
+            cx.update(&view, &mut vger, window_size);
+            cx.draw_frame(
+                FrameTarget {
+                    device: &device,
+                    queue: &queue,
+                    view: &texture_view,
+                    texture: None,
+                    width: 40,
+                    height: 20,
+                    format: FORMAT,
+                },
+                &view,
+                &mut vger,
+                window_size,
+                1.0,
+            );
+            assert!(cx.window_capture().is_some() || cx.pending_capture.is_some());
+            device.poll(wgpu::Maintain::Wait);
+            frames += 1;
+        }
+        assert!(frames > 1);
+        let capture = cx.window_capture().unwrap();
+        let blue = capture.color().unwrap();
+        assert_eq!((blue.r, blue.b), (0.0, 1.0));
+        let red = capture.color_at(-10, 0).unwrap();
+        assert_eq!((red.r, red.b), (1.0, 0.0));
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_gpu_markers() {
+        let (device, queue) = test_device();
+        let mut vger = Vger::new(device.clone(), queue.clone(), FORMAT);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 40,
+                height: 20,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Each group ends vger's pass, and what's drawn after goes over it.
+        let view = zstack((
+            rectangle()
+                .color(Color::new(1.0, 0.0, 0.0, 1.0))
+                .debug_group("under"),
+            rectangle()
+                .color(Color::new(0.0, 0.0, 1.0, 1.0))
+                .size([20.0, 20.0])
+                .debug_group("over"),
+        ));
+        let window_size = [40.0, 20.0].into();
+        let mut cx = Context::new();
+        cx.layer_renderer = Some(LayerRenderer::new(device.clone(), queue.clone(), FORMAT));
+        cx.gpu_markers = GpuMarkers::with_enabled(true);
+        cx.set_partial_redraw(false);
+        cx.update(&view, &mut vger, window_size);
+        cx.draw_frame(
+            FrameTarget {
+                device: &device,
+                queue: &queue,
+                view: &texture_view,
+                texture: Some(&texture),
+                width: 40,
+                height: 20,
+                format: FORMAT,
+            },
+            &view,
+            &mut vger,
+            window_size,
+            1.0,
+        );
+        assert_eq!(cx.gpu_markers.current(), None);
+
+        let pixels = read_pixels(&device, &queue, &texture, [0, 0], [40, 20]);
+        let image = ::image::RgbaImage::from_raw(40, 20, pixels).unwrap();
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(30, 10).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_partial_redraw() {
+        let (device, queue) = test_device();
+        let mut vger = Vger::new(device.clone(), queue.clone(), FORMAT);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 40,
+                height: 20,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let view = hstack((
+            state(
+                || false,
+                |on, cx| {
+                    let blue = if cx[on] { 1.0 } else { 0.0 };
+                    rectangle()
+                        .color(Color::new(1.0 - blue, 0.0, blue, 1.0))
+                        .tap(move |cx| cx[on] = true)
+                },
+            ),
+            rectangle().color(Color::new(0.0, 1.0, 0.0, 1.0)),
+        ));
+        let window_size = [40.0, 20.0].into();
+        let mut cx = Context::new();
+        cx.layer_renderer = Some(LayerRenderer::new(device.clone(), queue.clone(), FORMAT));
+        let frame = |cx: &mut Context, vger: &mut Vger| {
+            cx.update(&view, vger, window_size);
+            cx.draw_frame(
+                FrameTarget {
+                    device: &device,
+                    queue: &queue,
+                    view: &texture_view,
+                    texture: Some(&texture),
+                    width: 40,
+                    height: 20,
+                    format: FORMAT,
+                },
+                &view,
+                vger,
+                window_size,
+                1.0,
+            );
+        };
+
+        // The first frame is drawn straight into the target, and kept, so
+        // an update which changes nothing doesn't need another.
+        frame(&mut cx, &mut vger);
+        cx.update(&view, &mut vger, window_size);
+        assert!(cx.can_skip_frame(40, 20));
+
+        // Only the left rectangle is redrawn, over the kept frame.
+        cx.mouse_button = Some(MouseButton::Left);
+        let position = [10.0, 10.0].into();
+        cx.process(&view, &Event::TouchBegin { id: 0, position });
+        cx.process(&view, &Event::TouchEnd { id: 0, position });
+        cx.update(&view, &mut vger, window_size);
+        assert!(!cx.can_skip_frame(40, 20));
+        frame(&mut cx, &mut vger);
+
+        let pixels = read_pixels(&device, &queue, &texture, [0, 0], [40, 20]);
+        let image = ::image::RgbaImage::from_raw(40, 20, pixels).unwrap();
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(30, 10).0, [0, 255, 0, 255]);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How many frames in a row a view can rebuild, without any input, before
+/// strict mode reports it.
+const REBUILD_STORM_FRAMES: usize = 60;
+
+/// A view which strict mode found rebuilding every frame. See
+/// `Context::set_strict_mode`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebuildReport {
+    /// The `state` view which kept rebuilding.
+    pub view: ViewId,
+
+    /// The state it depends on which was changed every frame.
+    pub dependency: ViewId,
+
+    /// Type of the dependency's value.
+    pub state_type: &'static str,
+
+    /// Frames in a row the view rebuilt before being reported.
+    pub frames: usize,
+}
+
+impl fmt::Display for RebuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "view {:?} rebuilt for {} frames without input, because state {:?} ({}) changed every frame",
+            self.view, self.frames, self.dependency, self.state_type
+        )
+    }
+}
+
+/// A view's run of consecutive rebuilds.
+struct RebuildRun {
+    dependency: ViewId,
+    last_frame: u64,
+    frames: usize,
+}
+
+/// Bookkeeping for strict mode.
+#[derive(Default)]
+pub(crate) struct StrictMode {
+    frame: u64,
+
+    /// Last frame which had an input event. Events arrive before the
+    /// update for their frame.
+    input_frame: Option<u64>,
+
+    runs: HashMap<ViewId, RebuildRun>,
+    reports: Vec<RebuildReport>,
+
+    /// States written by `animate_state` and timelines this frame, which
+    /// are meant to change every frame.
+    animated: HashSet<ViewId>,
+    animating: bool,
+
+    /// Each dependency's value when it last caused a rebuild, for states
+    /// which can be inspected. See `Context::inspect_state`.
+    values: HashMap<ViewId, String>,
+}
+
+impl Context {
+    /// Strict mode watches for views which rebuild every frame when
+    /// nothing is happening, usually because state is written every frame,
+    /// for example in an `anim` callback, even though its value hasn't
+    /// changed. Each offender is printed once and recorded in
+    /// `rebuild_reports`.
+    ///
+    /// Animations started with `animate_state` or `play_timeline` aren't
+    /// reported. Nor are writes which change the value, for state types
+    /// which can be compared; see `inspect_state`.
+    ///
+    /// This adds some overhead, so it's meant for debugging.
+    pub fn set_strict_mode(&mut self, strict: bool) {
+        self.strict = if strict {
+            Some(StrictMode::default())
+        } else {
+            None
+        };
+    }
+
+    /// Rebuild storms found by strict mode.
+    pub fn rebuild_reports(&self) -> &[RebuildReport] {
+        match &self.strict {
+            Some(strict) => &strict.reports,
+            None => &[],
+        }
+    }
+
+    pub(crate) fn strict_begin_frame(&mut self) {
+        if let Some(strict) = &mut self.strict {
+            strict.frame += 1;
+            strict.animated.clear();
+            let state_map = &self.state_map;
+            strict.values.retain(|id, _| state_map.contains_key(id));
+        }
+    }
+
+    pub(crate) fn strict_set_animating(&mut self, animating: bool) {
+        if let Some(strict) = &mut self.strict {
+            strict.animating = animating;
+        }
+    }
+
+    pub(crate) fn strict_note_write(&mut self, id: ViewId) {
+        if let Some(strict) = &mut self.strict {
+            if strict.animating {
+                strict.animated.insert(id);
+            }
+        }
+    }
+
+    pub(crate) fn strict_note_input(&mut self) {
+        if let Some(strict) = &mut self.strict {
+            strict.input_frame = Some(strict.frame + 1);
+        }
+    }
+
+    /// Called when the `state` view `id` rebuilds because `dependency`
+    /// is dirty.
+    pub(crate) fn strict_note_rebuild(&mut self, id: ViewId, dependency: ViewId) {
+        if self.strict.is_none() {
+            return;
+        }
+        let value = self.debug_state(dependency);
+        let strict = self.strict.as_mut().unwrap();
+        let frame = strict.frame;
+
+        // Rebuilding in response to input, or to a value which really
+        // changed, is expected.
+        let changed = match value {
+            Some(value) => strict.values.insert(dependency, value.clone()) != Some(value),
+            None => false,
+        };
+        if strict.input_frame == Some(frame) || strict.animated.contains(&dependency) || changed {
+            strict.runs.remove(&id);
+            return;
+        }
+
+        let run = strict.runs.entry(id).or_insert(RebuildRun {
+            dependency,
+            last_frame: frame,
+            frames: 0,
+        });
+        if run.last_frame == frame && run.frames > 0 {
+            // Already counted this frame.
+            return;
+        }
+        if run.last_frame + 1 == frame && run.dependency == dependency {
+            run.frames += 1;
+        } else {
+            run.dependency = dependency;
+            run.frames = 1;
+        }
+        run.last_frame = frame;
+
+        if run.frames == REBUILD_STORM_FRAMES {
+            let report = RebuildReport {
+                view: id,
+                dependency,
+                state_type: self
+                    .state_map
+                    .get(&dependency)
+                    .map(|holder| holder.type_name)
+                    .unwrap_or("unknown"),
+                frames: REBUILD_STORM_FRAMES,
+            };
+            println!("rui strict mode: {}", report);
+            if let Some(strict) = &mut self.strict {
+                strict.reports.push(report);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Sets its state every frame, whether or not it has changed.
+    fn resetting(check: bool) -> impl View {
+        state(
+            || 0.0f32,
+            move |value, cx| {
+                text(&format!("{}", cx[value])).anim(move |cx, _| {
+                    if !check || cx[value] != 1.0 {
+                        cx[value] = 1.0;
+                    }
+                })
+            },
+        )
+    }
+
+    #[test]
+    fn test_rebuild_storm() {
+        let mut harness = Harness::new(resetting(false));
+        harness.context().set_strict_mode(true);
+        for _ in 0..REBUILD_STORM_FRAMES + 1 {
+            harness.update();
+        }
+        let reports = harness.context().rebuild_reports().to_vec();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].state_type, "f32");
+        assert_eq!(reports[0].view, reports[0].dependency);
+    }
+
+    #[test]
+    fn test_animations_exempt() {
+        // Animating a value changes it every frame, on purpose.
+        let view = state(
+            || 0.0f32,
+            |value, cx| {
+                text(&format!("{}", cx[value])).anim(move |cx, _| {
+                    if !cx.is_animating() {
+                        cx.play_timeline(
+                            value,
+                            timeline().at(0.0, 0.0).at(1.0, 1.0).repeat(Repeat::Loop),
+                        );
+                    }
+                })
+            },
+        );
+        let mut harness = Harness::new(view);
+        harness.context().set_strict_mode(true);
+        for _ in 0..2 * REBUILD_STORM_FRAMES {
+            harness.update();
+        }
+        assert!(harness.context().rebuild_reports().is_empty());
+
+        // As does counting frames.
+        let view = state(
+            || 0u64,
+            |frame, cx| text(&format!("{}", cx[frame])).anim(move |cx, _| cx[frame] += 1),
+        );
+        let mut harness = Harness::new(view);
+        harness.context().set_strict_mode(true);
+        for _ in 0..2 * REBUILD_STORM_FRAMES {
+            harness.update();
+        }
+        assert!(harness.context().rebuild_reports().is_empty());
+    }
+
+    #[test]
+    fn test_no_storm() {
+        let mut harness = Harness::new(resetting(true));
+        harness.context().set_strict_mode(true);
+        for _ in 0..2 * REBUILD_STORM_FRAMES {
+            harness.update();
+        }
+        assert!(harness.context().rebuild_reports().is_empty());
+
+        // Input resets the count.
+        let mut harness = Harness::new(resetting(false));
+        harness.context().set_strict_mode(true);
+        for _ in 0..2 * REBUILD_STORM_FRAMES {
+            harness.key(Key::Space);
+        }
+        assert!(harness.context().rebuild_reports().is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::*;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Runs on the main thread once a spawned future finishes.
+type Finish = Box<dyn FnOnce(&mut Context) + Send>;
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static! {
+    /// Runs futures spawned by views, like `load`'s fetches.
+    static ref THREAD_POOL: futures::executor::ThreadPool = futures::executor::ThreadPool::builder()
+        .name_prefix("rui-task-")
+        .create()
+        .expect("couldn't start rui's thread pool");
+}
+
+/// Futures which have finished, waiting for the next update to hand
+/// their results to views.
+#[derive(Clone, Default)]
+pub(crate) struct TaskQueue(Arc<Mutex<Vec<Finish>>>);
+
+impl Context {
+    /// Runs `future` on rui's thread pool, or the page's event loop on the
+    /// web, then calls what it returns with this `Context` on the next
+    /// update.
+    pub(crate) fn spawn<F, R>(&mut self, future: F)
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: FnOnce(&mut Context) + Send + 'static,
+    {
+        let queue = self.tasks.clone();
+        let task = async move {
+            let finish = future.await;
+            queue.0.lock().unwrap().push(Box::new(finish));
+
+            #[cfg(feature = "winit")]
+            wake_event_loop();
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        THREAD_POOL.spawn_ok(task);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(task);
+    }
+
+    /// Hands the results of finished futures to views.
+    pub(crate) fn run_finished_tasks(&mut self) {
+        let finished = std::mem::take(&mut *self.tasks.0.lock().unwrap());
+        for finish in finished {
+            finish(self);
+        }
+    }
+}
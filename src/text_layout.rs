@@ -0,0 +1,199 @@
+//! Keeping laid out text between frames, so text views don't shape the
+//! same text again each time they're drawn.
+
+use crate::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Where each glyph of some text goes, and its lines, in points with y up
+/// from the first line's baseline.
+#[derive(Clone)]
+pub(crate) struct TextLayout {
+    pub(crate) glyphs: Vec<LocalRect>,
+    pub(crate) lines: Vec<LineMetrics>,
+
+    /// The face to draw the glyphs with, or `None` if the layout is only
+    /// an estimate, as in a `Harness`.
+    pub(crate) face: Option<Arc<FontFace>>,
+
+    /// Each glyph's index in the face, and where its bitmap's bottom left
+    /// corner goes, or `None` for spaces and line breaks.
+    pub(crate) origins: Vec<Option<(u16, LocalPoint)>>,
+}
+
+impl TextLayout {
+    /// The bounds of the glyphs, like `Vger::text_bounds`.
+    pub(crate) fn bounds(&self) -> LocalRect {
+        rect_bounds(self.glyphs.iter().copied())
+    }
+}
+
+/// What text was laid out with.
+#[derive(Clone, PartialEq)]
+struct TextLayoutKey {
+    text: String,
+    font: Font,
+    max_width: Option<u32>,
+}
+
+struct CachedLayout {
+    key: TextLayoutKey,
+    layout: Rc<TextLayout>,
+    used: bool,
+}
+
+/// Layouts by a hash of their key. Layouts which go unused from one
+/// frame drawn to the next are dropped.
+#[derive(Default)]
+pub(crate) struct TextLayoutCache {
+    layouts: HashMap<u64, Vec<CachedLayout>>,
+
+    /// `font_generation` when the layouts were made.
+    fonts: u64,
+}
+
+impl TextLayoutCache {
+    /// The layout of `text`, made by `compute` if it isn't cached.
+    fn get(
+        &mut self,
+        text: &str,
+        font: &Font,
+        max_width: Option<f32>,
+        compute: impl FnOnce() -> TextLayout,
+    ) -> Rc<TextLayout> {
+        let max_width = max_width.map(f32::to_bits);
+        let hash = hh(&(text, font, max_width));
+        let matches = |key: &TextLayoutKey| {
+            key.text == text && key.font == *font && key.max_width == max_width
+        };
+
+        let bucket = self.layouts.entry(hash).or_default();
+        if let Some(cached) = bucket.iter_mut().find(|cached| matches(&cached.key)) {
+            cached.used = true;
+            return cached.layout.clone();
+        }
+        let layout = Rc::new(compute());
+        bucket.push(CachedLayout {
+            key: TextLayoutKey {
+                text: text.to_string(),
+                font: font.clone(),
+                max_width,
+            },
+            layout: layout.clone(),
+            used: true,
+        });
+        layout
+    }
+
+    /// Drops layouts which haven't been used since the last time.
+    pub(crate) fn evict_unused(&mut self) {
+        self.layouts.retain(|_, bucket| {
+            bucket.retain(|cached| cached.used);
+            for cached in bucket.iter_mut() {
+                cached.used = false;
+            }
+            !bucket.is_empty()
+        });
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.layouts.values().map(Vec::len).sum()
+    }
+}
+
+impl Context {
+    /// Lays out `text` with `font`, broken into lines at `max_width`, or
+    /// returns the layout from the last time it was laid out the same way.
+    /// Shared by all text views, and by measuring, so neither laying out
+    /// nor drawing a large document again shapes it again.
+    pub(crate) fn text_layout(
+        &self,
+        font: &Font,
+        text: &str,
+        max_width: Option<f32>,
+    ) -> Rc<TextLayout> {
+        let mut layouts = self.text_layouts.borrow_mut();
+        // A newly registered face may draw the text instead.
+        if layouts.fonts != font_generation() {
+            *layouts = TextLayoutCache {
+                layouts: HashMap::new(),
+                fonts: font_generation(),
+            };
+        }
+        layouts.get(text, font, max_width, || {
+            font.layout(self.builtin_face.as_ref(), text, max_width)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn layout(width: f32) -> TextLayout {
+        TextLayout {
+            glyphs: vec![
+                LocalRect::new([0.0, 0.0].into(), [width, 10.0].into()),
+                LocalRect::new([width, -2.0].into(), [width, 10.0].into()),
+            ],
+            lines: vec![],
+            face: None,
+            origins: vec![],
+        }
+    }
+
+    #[test]
+    fn test_text_layout_cache() {
+        let mut cache = TextLayoutCache::default();
+        let font = Font::default();
+        let computed = std::cell::Cell::new(0);
+        let get = |cache: &mut TextLayoutCache, text, max_width| {
+            cache.get(text, &font, max_width, || {
+                computed.set(computed.get() + 1);
+                layout(5.0)
+            })
+        };
+
+        let first = get(&mut cache, "hello", None);
+        assert_eq!(
+            first.bounds(),
+            LocalRect::new([0.0, -2.0].into(), [10.0, 12.0].into())
+        );
+        get(&mut cache, "hello", None);
+        get(&mut cache, "hello", Some(100.0));
+        get(&mut cache, "world", None);
+        assert_eq!(cache.len(), 3);
+
+        // Only what was used since the last frame is kept.
+        cache.evict_unused();
+        get(&mut cache, "hello", None);
+        cache.evict_unused();
+        assert_eq!(cache.len(), 1);
+        get(&mut cache, "hello", None);
+        assert_eq!(computed.get(), 3);
+
+        let empty = TextLayout {
+            glyphs: vec![],
+            lines: vec![],
+            face: None,
+            origins: vec![],
+        };
+        assert_eq!(empty.bounds(), LocalRect::zero());
+    }
+
+    #[test]
+    fn test_measure_keeps_layout() {
+        let mut h = Harness::new(vstack((text("hello"), text("world"))));
+        let layouts = |h: &mut Harness<_>| h.context().text_layouts.borrow().len();
+        assert_eq!(layouts(&mut h), 2);
+
+        // Updates without a frame between them, as when the mouse moves.
+        h.hover(Point::new(5.0, 5.0));
+        h.update();
+        h.update();
+        assert_eq!(layouts(&mut h), 2);
+    }
+}
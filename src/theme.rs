@@ -0,0 +1,350 @@
+use crate::*;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Whether a theme is meant for a light or dark appearance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// Colors used by the built-in views.
+///
+/// The theme is stored in the environment, so it can be overridden for
+/// a subtree with `.env(theme)` and read with `env(|theme: Theme, cx| ...)`
+/// or `cx.theme()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub scheme: ColorScheme,
+
+    /// Color for text and labels.
+    pub text: Color,
+
+    /// Window background.
+    pub background: Color,
+
+    /// Highlight color for active controls.
+    pub accent: Color,
+
+    /// Subdued version of the accent, used behind active controls.
+    pub accent_background: Color,
+
+    /// Background of buttons.
+    pub button_background: Color,
+
+    /// Background of buttons while the mouse is over them.
+    pub button_hover: Color,
+
+    /// Background of inactive controls such as toggles, knobs and slider tracks.
+    pub control_background: Color,
+
+    /// Foreground of inactive controls.
+    pub control_foreground: Color,
+
+    /// Text insertion point.
+    pub cursor: Color,
+}
+
+impl Theme {
+    /// The original rui look.
+    pub const DARK: Theme = Theme {
+        scheme: ColorScheme::Dark,
+        text: TEXT_COLOR,
+        background: BLACK,
+        accent: AZURE_HIGHLIGHT,
+        accent_background: AZURE_HIGHLIGHT_BACKGROUND,
+        button_background: BUTTON_BACKGROUND_COLOR,
+        button_hover: BUTTON_HOVER_COLOR,
+        control_background: CONTROL_BACKGROUND,
+        control_foreground: MEDIUM_GRAY,
+        cursor: Color::MAGENTA,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        scheme: ColorScheme::Light,
+        text: LIGHT_TEXT_COLOR,
+        background: LIGHT_BACKGROUND,
+        accent: AZURE_HIGHLIGHT_DARK,
+        accent_background: LIGHT_AZURE_HIGHLIGHT_BACKGROUND,
+        button_background: LIGHT_BUTTON_BACKGROUND_COLOR,
+        button_hover: LIGHT_BUTTON_HOVER_COLOR,
+        control_background: LIGHT_CONTROL_BACKGROUND,
+        control_foreground: MEDIUM_GRAY,
+        cursor: AZURE_HIGHLIGHT_DARK,
+    };
+
+    /// Returns the built-in theme for a color scheme.
+    pub fn for_scheme(scheme: ColorScheme) -> Theme {
+        match scheme {
+            ColorScheme::Light => Theme::LIGHT,
+            ColorScheme::Dark => Theme::DARK,
+        }
+    }
+}
+
+/// Error from loading a theme file.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "couldn't read theme: {}", err),
+            ThemeError::Parse(err) => write!(f, "couldn't parse theme: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl Theme {
+    /// Parses a theme from JSON like `{"scheme": "light", "accent":
+    /// "#0A84FF"}`. Colors are named after `Theme`'s fields, and written
+    /// `#RRGGBB` or `#RRGGBBAA`. Colors left out come from the built-in
+    /// theme for the scheme, which is dark by default.
+    pub fn from_json(json: &str) -> Result<Theme, ThemeError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| ThemeError::Parse(err.to_string()))?;
+        let fields = value
+            .as_object()
+            .ok_or_else(|| ThemeError::Parse("expected an object".into()))?;
+
+        let mut theme = match fields.get("scheme").map(|scheme| scheme.as_str()) {
+            None => Theme::DARK,
+            Some(Some("dark")) => Theme::DARK,
+            Some(Some("light")) => Theme::LIGHT,
+            Some(_) => {
+                return Err(ThemeError::Parse(
+                    "scheme should be \"light\" or \"dark\"".into(),
+                ))
+            }
+        };
+        for (name, value) in fields {
+            if name == "scheme" {
+                continue;
+            }
+            let color = theme
+                .color_mut(name)
+                .ok_or_else(|| ThemeError::Parse(format!("unknown color {:?}", name)))?;
+            let hex = value
+                .as_str()
+                .ok_or_else(|| ThemeError::Parse(format!("{} should be a string", name)))?;
+            *color =
+                Color::hex(hex).map_err(|err| ThemeError::Parse(format!("{}: {}", name, err)))?;
+        }
+        Ok(theme)
+    }
+
+    /// Reads a theme file. See `from_json`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeError> {
+        let json = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+        Theme::from_json(&json)
+    }
+
+    /// A color for text on `background`, like a badge or a button with a
+    /// custom color. That's the theme's text color if it's readable there,
+    /// otherwise the theme's background color if that is, otherwise black
+    /// or white. Readable means the WCAG AA contrast ratio of 4.5.
+    ///
+    /// Translucent backgrounds are taken to be over the theme's background.
+    pub fn foreground_on(&self, background: Color) -> Color {
+        const READABLE: f32 = 4.5;
+        let background = background.over(self.background);
+        [self.text, self.background]
+            .iter()
+            .copied()
+            .find(|color| color.contrast_ratio(background) >= READABLE)
+            .unwrap_or_else(|| background.contrasting())
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "text" => &mut self.text,
+            "background" => &mut self.background,
+            "accent" => &mut self.accent,
+            "accent_background" => &mut self.accent_background,
+            "button_background" => &mut self.button_background,
+            "button_hover" => &mut self.button_hover,
+            "control_background" => &mut self.control_background,
+            "control_foreground" => &mut self.control_foreground,
+            "cursor" => &mut self.cursor,
+            _ => return None,
+        })
+    }
+}
+
+/// How often watched theme and script files are checked for changes.
+pub(crate) const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A theme file being reloaded when it changes.
+pub(crate) struct ThemeWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+
+    /// When the file was last checked.
+    pub(crate) checked: Option<Stopwatch>,
+}
+
+pub(crate) fn file_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Context {
+    /// Loads a theme file and switches to it. See `Theme::from_json`.
+    ///
+    /// In debug builds, the file is reloaded whenever it changes, until
+    /// `set_theme` or `follow_system_theme` is called, so colors can be
+    /// tweaked while the app runs.
+    pub fn load_theme_file(&mut self, path: impl Into<PathBuf>) -> Result<(), ThemeError> {
+        let path = path.into();
+        let theme = Theme::load(&path)?;
+        self.set_theme(theme);
+        if cfg!(debug_assertions) {
+            self.theme_watch = Some(ThemeWatch {
+                modified: file_modified(&path),
+                path,
+                checked: Some(Stopwatch::start()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Is a theme file being watched for changes?
+    pub fn is_watching_theme(&self) -> bool {
+        self.theme_watch.is_some()
+    }
+
+    /// Reloads the watched theme file if it's changed. Keeps the current
+    /// theme if the new file doesn't parse, so half-saved edits don't
+    /// flash the default colors.
+    pub(crate) fn poll_theme_file(&mut self) {
+        let watch = match &mut self.theme_watch {
+            Some(watch) => watch,
+            None => return,
+        };
+        if watch
+            .checked
+            .as_ref()
+            .is_some_and(|checked| checked.elapsed() < FILE_POLL_INTERVAL)
+        {
+            return;
+        }
+        watch.checked = Some(Stopwatch::start());
+
+        let modified = file_modified(&watch.path);
+        if modified == watch.modified {
+            return;
+        }
+        watch.modified = modified;
+        match Theme::load(&watch.path) {
+            Ok(theme) => self.apply_theme(theme),
+            Err(err) => println!("{}", err),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+/// Reads the current theme from the environment.
+pub fn theme<V: View, F: Fn(Theme, &mut Context) -> V + 'static>(f: F) -> impl View {
+    env(f)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_set_theme() {
+        let mut cx = Context::new();
+        assert_eq!(cx.theme(), Theme::DARK);
+
+        cx.set_system_color_scheme(Some(ColorScheme::Light));
+        assert_eq!(cx.theme(), Theme::LIGHT);
+
+        // An explicit theme stops following the system.
+        cx.set_theme(Theme::DARK);
+        cx.set_system_color_scheme(Some(ColorScheme::Light));
+        assert_eq!(cx.theme(), Theme::DARK);
+
+        cx.follow_system_theme();
+        assert_eq!(cx.theme(), Theme::LIGHT);
+    }
+
+    #[test]
+    fn test_foreground_on() {
+        let theme = Theme::DARK;
+        assert_eq!(theme.foreground_on(theme.button_background), theme.text);
+        assert_eq!(theme.foreground_on(Color::WHITE), theme.background);
+        assert_eq!(
+            Theme::LIGHT.foreground_on(Color::hex("#0040A0").unwrap()),
+            Theme::LIGHT.background
+        );
+
+        // Neither is readable on mid gray, but black is better than white.
+        let gray = Color::hex("#777777").unwrap();
+        let theme = Theme {
+            text: gray,
+            background: gray,
+            ..Theme::DARK
+        };
+        assert_eq!(theme.foreground_on(gray), BLACK);
+
+        // Mostly transparent, so mostly the theme's background.
+        assert_eq!(
+            Theme::DARK.foreground_on(Color::WHITE.alpha(0.1)),
+            Theme::DARK.text
+        );
+    }
+
+    #[test]
+    fn test_theme_json() {
+        let theme = Theme::from_json(r##"{"scheme": "light", "accent": "#FF0000"}"##).unwrap();
+        assert_eq!(theme.scheme, ColorScheme::Light);
+        assert_eq!(theme.accent, Color::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(theme.background, Theme::LIGHT.background);
+
+        assert_eq!(Theme::from_json("{}").unwrap(), Theme::DARK);
+        assert!(Theme::from_json(r##"{"acent": "#FF0000"}"##).is_err());
+        assert!(Theme::from_json(r##"{"accent": "red"}"##).is_err());
+        assert!(Theme::from_json(r##"{"scheme": "blue"}"##).is_err());
+    }
+
+    #[test]
+    fn test_theme_file_reload() {
+        let path = std::env::temp_dir().join(format!("rui-theme-{}.json", std::process::id()));
+        std::fs::write(&path, r##"{"background": "#102030"}"##).unwrap();
+
+        let mut cx = Context::new();
+        cx.load_theme_file(&path).unwrap();
+        assert_eq!(cx.theme().background, Color::hex("#102030").unwrap());
+        assert!(cx.is_watching_theme());
+
+        // A file which doesn't parse keeps the last theme.
+        std::fs::write(&path, r##"{"background": "#1020"##).unwrap();
+        cx.theme_watch.as_mut().unwrap().modified = None;
+        cx.theme_watch.as_mut().unwrap().checked = None;
+        cx.poll_theme_file();
+        assert_eq!(cx.theme().background, Color::hex("#102030").unwrap());
+
+        std::fs::write(&path, r##"{"scheme": "light"}"##).unwrap();
+        cx.theme_watch.as_mut().unwrap().modified = None;
+        cx.theme_watch.as_mut().unwrap().checked = None;
+        cx.poll_theme_file();
+        assert_eq!(cx.theme(), Theme::LIGHT);
+
+        cx.set_theme(Theme::DARK);
+        assert!(!cx.is_watching_theme());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use crate::*;
+
+/// Hands out the ids touches have in `Event`s, for platform touch ids.
+///
+/// The first finger down gets id 0, which the mouse also uses, so a
+/// single touch taps and drags just like clicking. Fingers after that get
+/// the lowest free id. `ACCESS_TOUCH` and above are left for synthesized
+/// touches.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TouchSlots {
+    /// Platform id and last position of the touch in each slot.
+    slots: [Option<(u64, LocalPoint)>; ACCESS_TOUCH],
+}
+
+impl TouchSlots {
+    /// Gives a new touch an id, or `None` if there are too many fingers
+    /// down.
+    pub fn begin(&mut self, touch: u64, position: LocalPoint) -> Option<usize> {
+        let slot = self.slots.iter().position(|slot| slot.is_none())?;
+        self.slots[slot] = Some((touch, position));
+        Some(slot)
+    }
+
+    /// The id of a touch which moved, and how far it moved.
+    pub fn move_to(&mut self, touch: u64, position: LocalPoint) -> Option<(usize, LocalOffset)> {
+        let slot = self.find(touch)?;
+        let (_, previous) = self.slots[slot].replace((touch, position)).unwrap();
+        Some((slot, position - previous))
+    }
+
+    /// Frees the id of a touch which lifted or was cancelled, returning
+    /// it with the touch's last position.
+    pub fn end(&mut self, touch: u64) -> Option<(usize, LocalPoint)> {
+        let slot = self.find(touch)?;
+        let (_, position) = self.slots[slot].take().unwrap();
+        Some((slot, position))
+    }
+
+    fn find(&self, touch: u64) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| matches!(slot, Some((id, _)) if *id == touch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_touch_slots() {
+        let mut slots = TouchSlots::default();
+        let p = LocalPoint::new(10.0, 10.0);
+        assert_eq!(slots.begin(7, p), Some(0));
+        assert_eq!(slots.begin(3, p), Some(1));
+        assert_eq!(
+            slots.move_to(3, LocalPoint::new(12.0, 9.0)),
+            Some((1, LocalOffset::new(2.0, -1.0)))
+        );
+
+        // The first free id is reused.
+        assert_eq!(slots.end(7), Some((0, p)));
+        assert_eq!(slots.begin(9, p), Some(0));
+        assert_eq!(slots.move_to(7, p), None);
+
+        for touch in 10..100 {
+            slots.begin(touch, p);
+        }
+        assert_eq!(slots.begin(100, p), None);
+    }
+}
@@ -1,9 +1,9 @@
 use crate::*;
 use std::any::{Any, TypeId};
 
-pub struct DrawArgs<'a> {
+pub struct DrawArgs<'a, 'b> {
     pub cx: &'a mut Context,
-    pub vger: &'a mut Vger,
+    pub vger: &'a mut Painter<'b>,
 }
 
 pub struct LayoutArgs<'a> {
@@ -13,7 +13,7 @@ pub struct LayoutArgs<'a> {
 }
 
 impl<'a> LayoutArgs<'a> {
-    pub fn size(&mut self, sz: LocalSize) -> LayoutArgs {
+    pub fn size(&mut self, sz: LocalSize) -> LayoutArgs<'_> {
         LayoutArgs {
             sz,
             cx: self.cx,
@@ -48,6 +48,14 @@ pub trait View: private::Sealed + 'static {
     /// Push onto map if the view stores layout or state info.
     fn gc(&self, _path: &mut IdPath, _cx: &mut Context, _map: &mut Vec<ViewId>) {}
 
+    /// Runs `gc`, first noting the view's path and type for
+    /// `Context::view_tree`. Containers call this on their children, rather
+    /// than `gc`.
+    fn gc_view(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        cx.note_view(path, std::any::type_name::<Self>());
+        self.gc(path, cx, map);
+    }
+
     /// Returns the topmost view which the point intersects.
     fn hittest(&self, _path: &mut IdPath, _pt: LocalPoint, _cx: &mut Context) -> Option<ViewId> {
         None
@@ -0,0 +1,365 @@
+//! Structured dumps of the view tree, for tests and the devtools protocol,
+//! and the inspector overlay which shows them.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A view, with the views within it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewNode {
+    pub path: IdPath,
+    pub id: Option<u64>,
+
+    /// The view's type, without module paths or type parameters, like
+    /// `Text` or `ScrollView`.
+    pub type_name: String,
+
+    /// Origin and size, in the view's local coordinates. Views which don't
+    /// store a layout, like most modifiers, have none.
+    pub rect: Option<[f32; 4]>,
+
+    /// Origin and size in the window, adding up the offsets of the views
+    /// around it. Scrolling and transforms aren't included.
+    pub frame: Option<[f32; 4]>,
+
+    /// Offset from the parent's coordinates.
+    pub offset: Option<[f32; 2]>,
+
+    /// The string shown, for text views.
+    pub text: Option<String>,
+
+    /// The view's `state`, if it has one.
+    pub state: Option<StateSummary>,
+
+    pub focused: bool,
+    pub children: Vec<ViewNode>,
+}
+
+/// What's known about a `state` without knowing its type.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSummary {
+    pub type_name: String,
+    pub dirty: bool,
+
+    /// The value, formatted with `Debug`, if its type is registered with
+    /// `Context::inspect_state`.
+    pub value: Option<String>,
+}
+
+impl ViewNode {
+    /// Finds the first node, depth first, for which `f` is true.
+    pub fn find(&self, f: &impl Fn(&ViewNode) -> bool) -> Option<&ViewNode> {
+        if f(self) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(f))
+    }
+
+    /// Finds the text view showing exactly `text`.
+    pub fn find_text(&self, text: &str) -> Option<&ViewNode> {
+        self.find(&|node| node.text.as_deref() == Some(text))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        write!(f, "{} {:?}", self.type_name, self.path)?;
+        if let Some([x, y, w, h]) = self.rect {
+            write!(f, " {}x{} at ({}, {})", w, h, x, y)?;
+        }
+        if let Some(id) = self.id {
+            write!(f, " id {}", id)?;
+        }
+        if let Some(text) = &self.text {
+            write!(f, " {:?}", text)?;
+        }
+        if let Some(state) = &self.state {
+            write!(f, " state {}", state.type_name)?;
+            if let Some(value) = &state.value {
+                write!(f, " = {}", value)?;
+            }
+            if state.dirty {
+                write!(f, " (dirty)")?;
+            }
+        }
+        if self.focused {
+            write!(f, " focused")?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.write(f, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// The innermost node whose frame contains `pt`, preferring later
+    /// siblings, which are drawn on top.
+    pub fn node_at(&self, pt: WorldPoint) -> Option<&ViewNode> {
+        let inner = self
+            .children
+            .iter()
+            .rev()
+            .find_map(|child| child.node_at(pt));
+        let contains = self
+            .frame
+            .is_some_and(|[x, y, w, h]| WorldRect::new([x, y].into(), [w, h].into()).contains(pt));
+        inner.or(if contains { Some(self) } else { None })
+    }
+}
+
+/// Prints the subtree, one view per line, indented by depth.
+impl fmt::Display for ViewNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl Context {
+    /// The views in the last update, nested as they are in the view tree.
+    /// Only kept while the inspector or devtools is on, and in a `Harness`.
+    ///
+    /// Text is only known to `Harness::view_tree`.
+    pub fn view_tree(&self) -> Vec<ViewNode> {
+        self.view_tree_with_texts(&HashMap::new())
+    }
+
+    pub(crate) fn view_tree_with_texts(&self, texts: &HashMap<IdPath, String>) -> Vec<ViewNode> {
+        let mut roots = vec![];
+        let mut stack: Vec<ViewNode> = vec![];
+        for (path, name) in &self.view_list {
+            // gc visits each view right after its ancestors. Views which
+            // don't add to the path, like `Remote`, nest inside their parent.
+            while stack
+                .last()
+                .is_some_and(|node| !path.starts_with(&node.path))
+            {
+                attach(&mut stack, &mut roots);
+            }
+            stack.push(self.view_node(path, name, texts));
+        }
+        while !stack.is_empty() {
+            attach(&mut stack, &mut roots);
+        }
+        roots
+    }
+
+    fn view_node(&self, path: &IdPath, name: &str, texts: &HashMap<IdPath, String>) -> ViewNode {
+        let layout = self.layout.get(path);
+        let id = self.view_ids.get(path).copied();
+        ViewNode {
+            path: path.clone(),
+            id: id.map(|id| id.id),
+            type_name: short_type_name(name),
+            rect: layout.map(|layout| {
+                let r = layout.rect;
+                [r.origin.x, r.origin.y, r.size.width, r.size.height]
+            }),
+            frame: layout.map(|layout| {
+                let f = self.frame(path, layout);
+                [f.origin.x, f.origin.y, f.size.width, f.size.height]
+            }),
+            offset: layout.map(|layout| [layout.offset.x, layout.offset.y]),
+            text: texts.get(path).cloned(),
+            state: id.and_then(|id| {
+                self.state_map.get(&id).map(|holder| StateSummary {
+                    type_name: holder.type_name.into(),
+                    dirty: holder.dirty,
+                    value: self.debug_state(id),
+                })
+            }),
+            focused: id.is_some() && id == self.focused_id,
+            children: vec![],
+        }
+    }
+
+    /// Where the view at `path` is in the window, ignoring scrolling and
+    /// transforms.
+    fn frame(&self, path: &IdPath, layout: &LayoutBox) -> WorldRect {
+        let offset = (1..=path.len())
+            .filter_map(|len| self.layout.get(&path[..len]))
+            .fold(self.root_offset, |offset, layout| offset + layout.offset);
+        layout.rect.translate(offset).cast_unit()
+    }
+
+    /// Outlines the view under the mouse, and shows its type and size, to
+    /// find where views are and which view is which. Shift-F11 toggles it
+    /// too.
+    pub fn set_inspector(&mut self, inspector: bool) {
+        self.inspector.enabled = inspector;
+        self.set_dirty();
+    }
+
+    pub fn inspector(&self) -> bool {
+        self.inspector.enabled
+    }
+
+    /// The view the inspector is highlighting.
+    pub fn inspected_view(&self) -> Option<ViewNode> {
+        let pt = self.inspector.hover?;
+        self.view_tree()
+            .iter()
+            .rev()
+            .find_map(|root| root.node_at(pt))
+            .cloned()
+    }
+
+    /// Follows the mouse while the inspector is on.
+    pub(crate) fn inspect_event(&mut self, event: &Event) {
+        if !self.inspector.enabled {
+            return;
+        }
+        if let Event::TouchBegin { position, .. } | Event::TouchMove { position, .. } = event {
+            self.inspector.hover = Some(position.cast_unit());
+            self.set_dirty();
+        }
+    }
+
+    /// Draws the inspector in world coordinates.
+    pub(crate) fn draw_inspector(&self, vger: &mut Painter) {
+        const PADDING: f32 = 4.0;
+        let node = match self.inspected_view() {
+            Some(node) => node,
+            None => return,
+        };
+        let [x, y, w, h] = match node.frame {
+            Some(frame) => frame,
+            None => return,
+        };
+        let outline = vger.color_paint(AZURE_HIGHLIGHT);
+        vger.stroke_rect([x, y].into(), [x + w, y + h].into(), 0.0, 2.0, outline);
+
+        let label = format!("{} {}x{}", node.type_name, w, h);
+        let bounds = vger.text_bounds(&label, INSPECTOR_TEXT_SIZE, None);
+
+        // Below the view, unless that's off the bottom of the window.
+        let height = bounds.height() + 2.0 * PADDING;
+        let bottom = if y - height >= 0.0 { y - height } else { y + h };
+        let background = LocalRect::new(
+            [x, bottom].into(),
+            [bounds.width() + 2.0 * PADDING, height].into(),
+        );
+        let paint = vger.color_paint(Color::new(0.0, 0.0, 0.0, 0.75));
+        vger.fill_rect(background, 2.0, paint);
+
+        vger.save();
+        vger.translate([
+            x + PADDING - bounds.origin.x,
+            bottom + PADDING - bounds.origin.y,
+        ]);
+        vger.text(
+            &label,
+            INSPECTOR_TEXT_SIZE,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+            None,
+        );
+        vger.restore();
+    }
+}
+
+/// Settings for the inspector overlay.
+#[derive(Default)]
+pub(crate) struct Inspector {
+    pub(crate) enabled: bool,
+
+    /// Where the mouse last was.
+    hover: Option<WorldPoint>,
+}
+
+/// Size of the inspector's labels.
+const INSPECTOR_TEXT_SIZE: u32 = 12;
+
+/// `rui::views::text::Text` to `Text`, `Stack<(Text, Button)>` to `Stack`.
+fn short_type_name(name: &str) -> String {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name).into()
+}
+
+/// Pops the innermost node into its parent, or the roots.
+fn attach(stack: &mut Vec<ViewNode>, roots: &mut Vec<ViewNode>) {
+    let node = stack.pop().unwrap();
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_view_tree() {
+        let view = state(
+            || 1,
+            |count, cx| {
+                vstack((
+                    text(&format!("count {}", cx[count])),
+                    button("add", move |cx| cx[count] += 1),
+                ))
+            },
+        );
+        let mut harness = Harness::new(view);
+        let tree = harness.view_tree();
+        assert_eq!(tree.len(), 1);
+
+        assert_eq!(tree[0].type_name, "StateView");
+        let node = tree[0].find_text("count 1").unwrap();
+        assert!(node.rect.unwrap()[2] > 0.0);
+        assert_eq!(node.type_name, "Text");
+        assert!(tree[0].find_text("add").is_some());
+
+        // Views without a layout are included too.
+        let stack = &tree[0].children[0];
+        assert_eq!(stack.type_name, "Stack");
+        assert_eq!(stack.rect, None);
+        assert_eq!(stack.children.len(), 2);
+
+        let state = tree[0].state.as_ref().unwrap();
+        assert_eq!(state.type_name, "i32");
+        assert_eq!(state.value.as_deref(), Some("1"));
+
+        // Round trips through JSON for the devtools protocol.
+        let json = serde_json::to_string(&tree).unwrap();
+        let parsed: Vec<ViewNode> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, tree);
+
+        let dump = tree[0].to_string();
+        assert!(dump.contains("\"count 1\""));
+        assert!(dump.contains("state i32 = 1"));
+    }
+
+    #[test]
+    fn test_inspector() {
+        let view = hstack((
+            rectangle().size([50.0, 50.0]),
+            rectangle().size([50.0, 50.0]),
+        ));
+        let mut h = Harness::new(view);
+        let tree = h.view_tree();
+        assert_eq!(tree.len(), 1);
+        let sizes = &tree[0].children;
+        assert_eq!(sizes.len(), 2);
+        let second = &sizes[1].children[0];
+        assert_eq!(second.type_name, "Rectangle");
+        let [x, y, w, height] = second.frame.unwrap();
+        assert_eq!([w, height], [50.0, 50.0]);
+        assert_eq!(x, sizes[0].children[0].frame.unwrap()[0] + 50.0);
+        assert!(second.to_string().starts_with("Rectangle"));
+
+        h.context().key_mods.shift = true;
+        h.key(Key::F11);
+        h.context().key_mods.shift = false;
+        assert!(h.context().inspector());
+
+        let position = [x + w / 2.0, y + height / 2.0].into();
+        h.event(Event::TouchMove {
+            id: 0,
+            position,
+            delta: LocalOffset::zero(),
+        });
+        let inspected = h.context().inspected_view().unwrap();
+        assert_eq!(inspected.path, second.path);
+    }
+}
@@ -1,9 +1,9 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
-/// `ViewId` is a unique identifier for a view. We're using a u64 and hashing
-/// under the assumption there won't be collisions. The underlying u64 is a function
-/// of the path down the view tree.
+/// `ViewId` is a unique identifier for a view. Ids are handed out in order
+/// for each new path down the view tree, so two views never share one. Views
+/// marked with the `id` modifier keep theirs when their path changes.
 #[derive(Copy, Clone, Default, Eq, PartialEq, Hash, Debug)]
 pub struct ViewId {
     pub id: u64,
@@ -29,7 +29,8 @@ where
         actions: &mut Vec<Box<dyn Any>>,
     ) {
         if let Event::Anim = event {
-            (self.func)(cx, 1.0 / 60.0) // XXX: assume 60fps for now.
+            let dt = cx.anim_interval();
+            (self.func)(cx, dt)
         }
 
         path.push(0);
@@ -72,7 +73,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         map.push(cx.view_id(path));
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -6,6 +6,9 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 /// Struct for `any_view`
+///
+/// Views inside are identified by their type, so their state is dropped
+/// when a different type of view is shown.
 pub struct AnyView {
     child: Box<dyn View>,
 }
@@ -17,13 +20,13 @@ impl AnyView {
         }
     }
 
-    fn id(&self) -> TypeId {
+    fn child_tid(&self) -> TypeId {
         self.child.tid()
     }
 
     fn id_hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
-        self.id().hash(&mut hasher);
+        self.child_tid().hash(&mut hasher);
         hasher.finish()
     }
 }
@@ -64,6 +67,10 @@ impl View for AnyView {
         path.pop();
     }
 
+    fn is_flexible(&self) -> bool {
+        self.child.is_flexible()
+    }
+
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
         path.push(self.id_hash());
         let vid = self.child.hittest(path, pt, cx);
@@ -79,7 +86,7 @@ impl View for AnyView {
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(self.id_hash());
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -96,7 +103,8 @@ impl View for AnyView {
     }
 }
 
-/// Switches between views according to a boolean.
+/// Erases the type of a view, so different types of views can be returned
+/// from the arms of a `match`.
 pub fn any_view(view: impl View) -> AnyView {
     AnyView {
         child: Box::new(view),
@@ -131,4 +139,29 @@ mod tests {
         let b = any_view(rectangle());
         assert_ne!(a.tid(), b.tid());
     }
+
+    #[test]
+    fn test_any_view_match() {
+        let view = state(
+            || 0,
+            |screen, cx| {
+                let body = match cx[screen] {
+                    0 => any_view(button("next", move |cx| cx[screen] = 1)),
+                    1 => any_view(hstack((text("settings"), spacer()))),
+                    _ => any_view(EmptyView {}),
+                };
+                vstack((text("title"), body))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("next");
+        h.expect_text("settings");
+        h.expect_no_text("next");
+    }
+
+    #[test]
+    fn test_any_view_flexible() {
+        assert!(any_view(spacer()).is_flexible());
+        assert!(!any_view(text("hi")).is_flexible());
+    }
 }
@@ -56,6 +56,12 @@ where
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if cx.text_probe.is_some() {
+            // Let the probe find text in the foreground.
+            path.push(0);
+            self.child.hittest(path, pt, cx);
+            path.pop();
+        }
         path.push(1);
         let vid = self.background.hittest(path, pt, cx);
         path.pop();
@@ -73,10 +79,10 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
         path.push(1);
-        self.background.gc(path, cx, map);
+        self.background.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -3,6 +3,28 @@ use accesskit::Role;
 
 pub const BUTTON_CORNER_RADIUS: f32 = 5.0;
 
+/// Overrides how buttons in a subtree are painted, to give them gradients
+/// or other non-flat looks. Set it with `.env(ButtonStyle { .. })`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ButtonStyle {
+    /// Background of buttons. Defaults to the theme's `button_background`.
+    pub background: Option<Paint>,
+
+    /// Background of buttons while the mouse is over them. Defaults to the
+    /// theme's `button_hover`.
+    pub hover: Option<Paint>,
+}
+
+impl ButtonStyle {
+    /// Buttons shaded from `top` to `bottom`, lightening while hovered.
+    pub fn gradient(top: Color, bottom: Color) -> Self {
+        Self {
+            background: Some(Paint::vertical(top, bottom)),
+            hover: Some(Paint::vertical(top.lighten(0.1), bottom.lighten(0.1))),
+        }
+    }
+}
+
 /// Calls a function when the button is tapped.
 pub fn button<A: 'static, F: Fn(&mut Context) -> A + 'static + Clone>(
     view: impl View + Clone,
@@ -10,22 +32,39 @@ pub fn button<A: 'static, F: Fn(&mut Context) -> A + 'static + Clone>(
 ) -> impl View {
     state(
         || false,
-        move |hovering, cx| {
+        move |hovering, _| {
+            let view = view.clone();
             let f = f.clone();
-            view.clone()
-                .padding(Auto)
-                .background(rectangle().corner_radius(BUTTON_CORNER_RADIUS).color(
-                    if cx[hovering] {
-                        BUTTON_HOVER_COLOR
-                    } else {
-                        BUTTON_BACKGROUND_COLOR
-                    },
-                ))
-                .tap(move |cx| f(cx))
-                .hover(move |cx, inside| {
-                    cx[hovering] = inside;
-                })
-                .role(Role::Button)
+            env(move |style: ButtonStyle, cx| {
+                let f = f.clone();
+                let theme = cx.theme();
+                let background = if cx[hovering] {
+                    style.hover.unwrap_or_else(|| theme.button_hover.into())
+                } else {
+                    style
+                        .background
+                        .unwrap_or_else(|| theme.button_background.into())
+                };
+                // Keep the label readable on custom backgrounds.
+                let label_theme = Theme {
+                    text: theme.foreground_on(background.average_color()),
+                    ..theme
+                };
+                view.clone()
+                    .env(label_theme)
+                    .padding(Auto)
+                    .background(
+                        rectangle()
+                            .corner_radius(BUTTON_CORNER_RADIUS)
+                            .fill(background),
+                    )
+                    .tap(move |cx| f(cx))
+                    .hover(move |cx, inside| {
+                        cx[hovering] = inside;
+                    })
+                    .cursor(CursorIcon::Pointer)
+                    .role(Role::Button)
+            })
         },
     )
 }
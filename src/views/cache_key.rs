@@ -0,0 +1,194 @@
+use crate::*;
+use std::any::Any;
+
+/// The key a subtree was last laid out for, and how.
+#[derive(Clone, Copy, Debug, Default)]
+struct CacheState {
+    key: u64,
+
+    /// Counts layouts, so the layer is drawn again after each.
+    generation: u64,
+
+    /// Size proposed and returned when last laid out.
+    layout: Option<(LocalSize, LocalSize)>,
+}
+
+fn cache_state(vid: ViewId, cx: &mut Context) -> &mut CacheState {
+    cx.init_state(vid, &CacheState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for the `cache_key` modifier.
+pub struct CacheKey<V> {
+    child: V,
+    key: u64,
+}
+
+impl<V> CacheKey<V>
+where
+    V: View,
+{
+    pub fn new(child: V, key: u64) -> Self {
+        Self { child, key }
+    }
+}
+
+impl<V> View for CacheKey<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let rect = args.cx.get_layout(path).rect;
+        let xform = args.vger.current_transform();
+        let state = *cache_state(vid, args.cx);
+        let key = state.key ^ state.generation.rotate_left(32);
+
+        if args.cx.replay_layer(args.vger, vid, key, xform) {
+            return;
+        }
+
+        path.push(0);
+        match args.cx.begin_layer(rect, xform, &LayerStyle::default()) {
+            Some(mut layer) => {
+                self.child.draw(
+                    path,
+                    &mut DrawArgs {
+                        cx: args.cx,
+                        vger: &mut layer,
+                    },
+                );
+                args.cx.end_cached_layer(args.vger, layer, xform, vid, key);
+            }
+            None => self.child.draw(path, args),
+        }
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let id = args.cx.view_id(path);
+        let proposed = args.sz;
+        let state = *cache_state(id, args.cx);
+
+        // As with `memo`, skip layout unless the key or state inside
+        // changed.
+        if let (Some((last_proposed, size)), Some(deps)) = (state.layout, args.cx.deps.get(&id)) {
+            let dirty = deps
+                .iter()
+                .any(|dep| args.cx.state_map.get(dep).is_some_and(|h| h.dirty));
+            if state.key == self.key && last_proposed == proposed && !dirty {
+                return size;
+            }
+        }
+
+        path.push(0);
+        let size = self.child.layout(path, args);
+        let mut deps = vec![];
+        self.child.gc(path, args.cx, &mut deps);
+        path.pop();
+
+        args.cx.deps.insert(id, deps);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        let state = cache_state(id, args.cx);
+        state.key = self.key;
+        state.generation += 1;
+        state.layout = Some((proposed, size));
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for CacheKey<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let view = state(
+            || (0, 0),
+            |s, cx| {
+                let (count, other) = cx[s];
+                vstack((
+                    text(&format!("count {}", count)).cache_key(count),
+                    text(&format!("other {}", other)),
+                    button("count", move |cx| cx[s].0 += 1),
+                    button("other", move |cx| cx[s].1 += 1),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        assert_eq!(h.states::<CacheState>()[0].generation, 1);
+
+        h.click("other");
+        h.click("other");
+        assert!(h.has_text("other 2"));
+        assert_eq!(h.states::<CacheState>()[0].generation, 1);
+
+        h.click("count");
+        assert!(h.has_text("count 1"));
+        assert_eq!(h.states::<CacheState>()[0].generation, 2);
+    }
+}
@@ -45,8 +45,63 @@ where
 }
 
 /// Canvas for GPU drawing with Vger. See https://github.com/audulus/vger-rs.
+/// For drawing on with the mouse or a pen, see `sketch`.
 pub fn canvas<F: Fn(&mut Context, LocalRect, &mut Vger) + 'static>(f: F) -> impl View {
     Canvas { func: f }
 }
 
 impl<F> private::Sealed for Canvas<F> {}
+
+/// Like `Canvas`, but drawing with the `Painter`, so rui's own text and
+/// shapes drawn with its methods needn't end vger's pass.
+#[derive(Clone)]
+pub(crate) struct PainterCanvas<F> {
+    func: F,
+}
+
+impl<F> View for PainterCanvas<F>
+where
+    F: Fn(&mut Context, LocalRect, &mut Painter) + 'static,
+{
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+
+        args.vger.save();
+        (self.func)(args.cx, rect, args.vger);
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        args.sz
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let rect = cx.get_layout(path).rect;
+
+        if rect.contains(pt) {
+            Some(cx.view_id(path))
+        } else {
+            None
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+}
+
+/// A `canvas` drawing with the `Painter`, for rui's own views.
+pub(crate) fn painter_canvas<F: Fn(&mut Context, LocalRect, &mut Painter) + 'static>(
+    f: F,
+) -> impl View {
+    PainterCanvas { func: f }
+}
+
+impl<F> private::Sealed for PainterCanvas<F> {}
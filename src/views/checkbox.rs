@@ -0,0 +1,201 @@
+use crate::*;
+use accesskit::Role;
+
+pub const CHECKBOX_SIZE: f32 = 18.0;
+
+/// Whether a checkbox is checked. `Mixed` is for a box over a group of
+/// others, only some of which are checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CheckState {
+    #[default]
+    Unchecked,
+    Checked,
+    Mixed,
+}
+
+impl CheckState {
+    /// The state of a "select all" box over items which are each checked
+    /// or not. With no items, it's unchecked.
+    pub fn of(checked: impl IntoIterator<Item = bool>) -> Self {
+        let (mut any, mut all) = (false, true);
+        for c in checked {
+            any |= c;
+            all &= c;
+        }
+        match (any, all) {
+            (true, true) => CheckState::Checked,
+            (true, false) => CheckState::Mixed,
+            _ => CheckState::Unchecked,
+        }
+    }
+
+    /// What clicking changes the box to. Mixed boxes become checked,
+    /// so clicking a "select all" box selects everything.
+    pub fn next(self) -> Self {
+        match self {
+            CheckState::Checked => CheckState::Unchecked,
+            _ => CheckState::Checked,
+        }
+    }
+}
+
+impl From<bool> for CheckState {
+    fn from(checked: bool) -> Self {
+        if checked {
+            CheckState::Checked
+        } else {
+            CheckState::Unchecked
+        }
+    }
+}
+
+fn check_box(
+    check: CheckState,
+    f: impl Fn(&mut Context, CheckState) + 'static,
+    cx: &Context,
+) -> impl View {
+    let theme = cx.theme();
+    let background = if check == CheckState::Unchecked {
+        theme.control_background
+    } else {
+        theme.accent_background
+    };
+    let mark = theme.foreground_on(background);
+    zstack((
+        rectangle()
+            .color(background)
+            .corner_radius(4.0)
+            .size([CHECKBOX_SIZE, CHECKBOX_SIZE])
+            .tap(move |cx| f(cx, check.next())),
+        painter_canvas(move |_, rect, vger| {
+            let paint = vger.color_paint(mark);
+            let c = rect.center();
+            let s = rect.width() / 4.0;
+            match check {
+                CheckState::Checked => {
+                    let corner = c + LocalOffset::new(-s * 0.3, -s);
+                    vger.stroke_segment(c + LocalOffset::new(-s, 0.0), corner, 1.0, paint);
+                    vger.stroke_segment(corner, c + LocalOffset::new(s, s), 1.0, paint);
+                }
+                CheckState::Mixed => {
+                    let d = LocalOffset::new(s, 0.0);
+                    vger.stroke_segment(c - d, c + d, 1.0, paint);
+                }
+                CheckState::Unchecked => (),
+            }
+        })
+        .size([CHECKBOX_SIZE, CHECKBOX_SIZE]),
+    ))
+    .role(Role::CheckBox)
+    .checked(match check {
+        CheckState::Unchecked => accesskit::CheckedState::False,
+        CheckState::Checked => accesskit::CheckedState::True,
+        CheckState::Mixed => accesskit::CheckedState::Mixed,
+    })
+}
+
+/// Checkbox.
+pub fn checkbox(checked: impl Binding<bool>) -> impl View {
+    state(
+        || (),
+        move |_, cx| {
+            let check = CheckState::from(*checked.get(cx));
+            check_box(
+                check,
+                move |cx, check| checked.set(cx, check == CheckState::Checked),
+                cx,
+            )
+        },
+    )
+}
+
+/// Checkbox which can also show `CheckState::Mixed`, as for a "select
+/// all" box over a list. Clicking calls `f` with the new state: mixed and
+/// unchecked boxes become checked, and checked boxes unchecked.
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(state(
+///     || vec![true, false, true],
+///     |items, cx| {
+///         let check = CheckState::of(cx[items].clone());
+///         vstack((
+///             tri_state_checkbox(check, move |cx, check| {
+///                 for item in cx[items].iter_mut() {
+///                     *item = check == CheckState::Checked;
+///                 }
+///             }),
+///             format!("{:?}", cx[items]),
+///         ))
+///     },
+/// ));
+/// ```
+pub fn tri_state_checkbox(
+    check: CheckState,
+    f: impl Fn(&mut Context, CheckState) + Clone + 'static,
+) -> impl View {
+    state(|| (), move |_, cx| check_box(check, f.clone(), cx))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn checked_state(view: &impl View, cx: &mut Context) -> Option<accesskit::CheckedState> {
+        let mut nodes = vec![];
+        view.access(&mut vec![0], cx, &mut nodes);
+        nodes
+            .iter()
+            .find(|(_, node)| node.role() == Role::CheckBox)
+            .and_then(|(_, node)| node.checked_state())
+    }
+
+    #[test]
+    fn test_check_state_of() {
+        assert_eq!(CheckState::of([true, true]), CheckState::Checked);
+        assert_eq!(CheckState::of([true, false]), CheckState::Mixed);
+        assert_eq!(CheckState::of([false, false]), CheckState::Unchecked);
+        assert_eq!(CheckState::of([]), CheckState::Unchecked);
+        assert_eq!(CheckState::Mixed.next(), CheckState::Checked);
+    }
+
+    #[test]
+    fn test_checkbox() {
+        let mut h = Harness::with_size(
+            state(|| false, |s, _| checkbox(s)),
+            Size::new(CHECKBOX_SIZE, CHECKBOX_SIZE),
+        );
+        let center = Point::new(CHECKBOX_SIZE / 2.0, CHECKBOX_SIZE / 2.0);
+        h.click_at(center);
+        assert_eq!(h.states::<bool>(), vec![true]);
+        h.click_at(center);
+        assert_eq!(h.states::<bool>(), vec![false]);
+    }
+
+    #[test]
+    fn test_select_all() {
+        let view = state(
+            || vec![true, false],
+            move |items, cx| {
+                let check = CheckState::of(cx[items].clone());
+                tri_state_checkbox(check, move |cx, check| {
+                    let all = check == CheckState::Checked;
+                    cx[items].iter_mut().for_each(|item| *item = all);
+                })
+            },
+        );
+        let mut cx = Context::new();
+        assert_eq!(
+            checked_state(&view, &mut cx),
+            Some(accesskit::CheckedState::Mixed)
+        );
+
+        let mut h = Harness::with_size(view, Size::new(CHECKBOX_SIZE, CHECKBOX_SIZE));
+        let center = Point::new(CHECKBOX_SIZE / 2.0, CHECKBOX_SIZE / 2.0);
+        h.click_at(center);
+        assert_eq!(h.states::<Vec<bool>>(), vec![vec![true, true]]);
+        h.click_at(center);
+        assert_eq!(h.states::<Vec<bool>>(), vec![vec![false, false]]);
+    }
+}
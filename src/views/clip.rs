@@ -29,6 +29,12 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
+        // Touches outside the clip don't reach the child.
+        if let Event::TouchBegin { position, .. } = event {
+            if !self.geom(path, cx).contains(*position) {
+                return;
+            }
+        }
         path.push(0);
         self.child.process(event, path, cx, actions);
         path.pop();
@@ -39,9 +45,12 @@ where
 
         args.vger.save();
         args.vger.scissor(rect);
+        let world_rect = args.vger.current_transform().outer_transformed_rect(&rect);
+        args.cx.push_clip(world_rect);
         path.push(0);
         self.child.draw(path, args);
         path.pop();
+        args.cx.pop_clip();
         args.vger.restore();
     }
 
@@ -83,7 +92,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         map.push(cx.view_id(path));
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
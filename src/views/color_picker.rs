@@ -0,0 +1,679 @@
+use crate::*;
+use std::any::Any;
+
+const SWATCH_WIDTH: f32 = 40.0;
+const SWATCH_HEIGHT: f32 = 24.0;
+const SWATCH_RADIUS: f32 = 4.0;
+
+/// Width of the controls in the popover.
+const CONTROL_WIDTH: f32 = 180.0;
+
+/// Height of the saturation and value square.
+const PLANE_HEIGHT: f32 = 120.0;
+
+/// Height of the hue and alpha strips.
+const STRIP_HEIGHT: f32 = 12.0;
+
+const FIELD_HEIGHT: f32 = 24.0;
+
+/// Padding around each control in the popover, and around all of them.
+const POPOVER_SPACING: f32 = 4.0;
+
+/// Gap between the swatch and the popover.
+const POPOVER_GAP: f32 = 4.0;
+
+/// Distance kept from the edges of the window.
+const POPOVER_MARGIN: f32 = 4.0;
+
+/// Size of the squares behind translucent colors.
+const CHECKER_SIZE: f32 = 4.0;
+
+#[derive(Clone, Default)]
+struct PickerState {
+    open: bool,
+
+    /// Hue, saturation and value being edited. Kept apart from the color,
+    /// so grays don't lose their hue.
+    hsv: (f32, f32, f32),
+
+    /// The bound color `hsv` and `hex` were last made from.
+    synced: Option<Color>,
+
+    /// Text of the hex field, which is only read on Enter.
+    hex: String,
+
+    /// The eyedropper beside the hex field.
+    eyedropper: EyedropperState,
+
+    popover_size: LocalSize,
+}
+
+impl PickerState {
+    fn sync(&mut self, color: Color) {
+        let (h, s, v) = color.to_hsv();
+        let h = if s > 0.0 && v > 0.0 { h } else { self.hsv.0 };
+        self.hsv = (h, s, v);
+        self.synced = Some(color);
+        self.hex = color.to_hex();
+    }
+}
+
+fn picker_state(vid: ViewId, cx: &mut Context) -> &mut PickerState {
+    cx.init_state(vid, &PickerState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Sets the bound color from hue, saturation and value, keeping its alpha.
+fn set_hsv(
+    color: impl Binding<Color>,
+    s: StateHandle<PickerState>,
+    hsv: (f32, f32, f32),
+    cx: &mut Context,
+) {
+    let c = Color::hsv(hsv.0, hsv.1, hsv.2).alpha(color.get(cx).a);
+    color.set(cx, c);
+    cx[s].sync(c);
+    // Saturation or value may be zero, which loses the hue.
+    cx[s].hsv = hsv;
+}
+
+fn set_color(color: impl Binding<Color>, s: StateHandle<PickerState>, c: Color, cx: &mut Context) {
+    color.set(cx, c);
+    cx[s].sync(c);
+}
+
+/// Fraction of the way across `length` a position is.
+fn fraction(x: f32, length: f32) -> f32 {
+    (x / length).clamp(0.0, 1.0)
+}
+
+/// Squares which show through translucent colors.
+fn draw_checkers(rect: LocalRect, vger: &mut Vger) {
+    let light = vger.color_paint(Color::gray(0.8));
+    let dark = vger.color_paint(Color::gray(0.5));
+    vger.fill_rect(rect, 0.0, light);
+    let columns = (rect.width() / CHECKER_SIZE).ceil() as usize;
+    let rows = (rect.height() / CHECKER_SIZE).ceil() as usize;
+    for row in 0..rows {
+        for column in (row % 2..columns).step_by(2) {
+            let origin = rect.origin
+                + LocalOffset::new(column as f32 * CHECKER_SIZE, row as f32 * CHECKER_SIZE);
+            let size = LocalSize::new(
+                CHECKER_SIZE.min(rect.max_x() - origin.x),
+                CHECKER_SIZE.min(rect.max_y() - origin.y),
+            );
+            vger.fill_rect(LocalRect::new(origin, size), 0.0, dark);
+        }
+    }
+}
+
+/// A line across a strip where its value is.
+fn draw_marker(rect: LocalRect, x: f32, vger: &mut Vger) {
+    let x = rect.min_x() + x * rect.width();
+    let paint = vger.color_paint(Color::WHITE);
+    vger.stroke_rect(
+        [x - 2.0, rect.min_y() - 1.0].into(),
+        [x + 2.0, rect.max_y() + 1.0].into(),
+        1.0,
+        1.0,
+        paint,
+    );
+}
+
+/// Struct for `color_picker`.
+struct ColorPicker<B> {
+    color: B,
+}
+
+impl<B> ColorPicker<B>
+where
+    B: Binding<Color>,
+{
+    /// Catches up with changes to the bound color made elsewhere.
+    fn sync(&self, vid: ViewId, cx: &mut Context) {
+        let color = *self.color.get(cx);
+        if picker_state(vid, cx).synced != Some(color) {
+            cx[StateHandle::<PickerState>::new(vid)].sync(color);
+        }
+    }
+
+    fn popover(&self, vid: ViewId, cx: &Context) -> impl View {
+        let s = StateHandle::<PickerState>::new(vid);
+        let color = self.color;
+
+        let plane = canvas(move |cx, rect, vger| {
+            let (h, sat, val) = cx[s].hsv;
+            let paint = vger.color_paint(Color::hsv(h, 1.0, 1.0));
+            vger.fill_rect(rect, 0.0, paint);
+            let paint = vger.linear_gradient(
+                [rect.min_x(), 0.0],
+                [rect.max_x(), 0.0],
+                Color::WHITE,
+                Color::WHITE.alpha(0.0),
+                0.0,
+            );
+            vger.fill_rect(rect, 0.0, paint);
+            let paint = vger.linear_gradient(
+                [0.0, rect.min_y()],
+                [0.0, rect.max_y()],
+                BLACK,
+                BLACK.alpha(0.0),
+                0.0,
+            );
+            vger.fill_rect(rect, 0.0, paint);
+
+            let center = rect.origin + LocalOffset::new(sat * rect.width(), val * rect.height());
+            let paint = vger.color_paint(Color::hsv(h, sat, val).contrasting());
+            vger.stroke_arc(center, 4.0, 1.0, 0.0, std::f32::consts::PI, paint);
+        })
+        .drag_p(move |cx, pt, _, _| {
+            let h = cx[s].hsv.0;
+            let hsv = (
+                h,
+                fraction(pt.x, CONTROL_WIDTH),
+                fraction(pt.y, PLANE_HEIGHT),
+            );
+            set_hsv(color, s, hsv, cx)
+        })
+        .size([CONTROL_WIDTH, PLANE_HEIGHT]);
+
+        let hue = canvas(move |cx, rect, vger| {
+            let step = rect.width() / 6.0;
+            for i in 0..6 {
+                let x = rect.min_x() + i as f32 * step;
+                let paint = vger.linear_gradient(
+                    [x, 0.0],
+                    [x + step, 0.0],
+                    Color::hsv(i as f32 * 60.0, 1.0, 1.0),
+                    Color::hsv((i + 1) as f32 * 60.0, 1.0, 1.0),
+                    0.0,
+                );
+                let segment =
+                    LocalRect::new([x, rect.min_y()].into(), [step, rect.height()].into());
+                vger.fill_rect(segment, 0.0, paint);
+            }
+            draw_marker(rect, cx[s].hsv.0 / 360.0, vger);
+        })
+        .drag_p(move |cx, pt, _, _| {
+            let (_, sat, val) = cx[s].hsv;
+            set_hsv(
+                color,
+                s,
+                (fraction(pt.x, CONTROL_WIDTH) * 360.0, sat, val),
+                cx,
+            )
+        })
+        .size([CONTROL_WIDTH, STRIP_HEIGHT]);
+
+        let alpha = canvas(move |cx, rect, vger| {
+            let c = *color.get(cx);
+            draw_checkers(rect, vger);
+            let paint = vger.linear_gradient(
+                [rect.min_x(), 0.0],
+                [rect.max_x(), 0.0],
+                c.alpha(0.0),
+                c.alpha(1.0),
+                0.0,
+            );
+            vger.fill_rect(rect, 0.0, paint);
+            draw_marker(rect, c.a, vger);
+        })
+        .drag_p(move |cx, pt, _, _| {
+            let c = color.get(cx).alpha(fraction(pt.x, CONTROL_WIDTH));
+            set_color(color, s, c, cx)
+        })
+        .size([CONTROL_WIDTH, STRIP_HEIGHT]);
+
+        let hex = text_field(s.map(lens(
+            |s: &PickerState| &s.hex,
+            |s: &mut PickerState| &mut s.hex,
+        )))
+        .on_submit(move |cx| match Color::hex(cx[s].hex.trim()) {
+            Ok(c) => set_color(color, s, c, cx),
+            Err(_) => {
+                let c = *color.get(cx);
+                cx[s].hex = c.to_hex();
+            }
+        })
+        .size([
+            CONTROL_WIDTH - FIELD_HEIGHT - 2.0 * POPOVER_SPACING,
+            FIELD_HEIGHT,
+        ]);
+
+        let eyedropper = Eyedropper {
+            color,
+            state: s.map(lens(
+                |s: &PickerState| &s.eyedropper,
+                |s: &mut PickerState| &mut s.eyedropper,
+            )),
+            size: FIELD_HEIGHT,
+        };
+
+        vstack((
+            plane.padding(POPOVER_SPACING),
+            hue.padding(POPOVER_SPACING),
+            alpha.padding(POPOVER_SPACING),
+            hstack((
+                hex.padding(POPOVER_SPACING),
+                eyedropper.padding(POPOVER_SPACING),
+            )),
+        ))
+        .padding(POPOVER_SPACING)
+        .background(
+            rectangle()
+                .corner_radius(SWATCH_RADIUS)
+                .color(cx.theme().control_background),
+        )
+    }
+
+    /// Where the popover goes: below the swatch, or above it if there's
+    /// no room below, and moved to stay inside `bounds`.
+    fn place_popover(size: LocalSize, bounds: LocalRect) -> LocalOffset {
+        let bounds = bounds.inflate(-POPOVER_MARGIN, -POPOVER_MARGIN);
+        let mut offset = LocalOffset::new(0.0, -POPOVER_GAP - size.height);
+        if offset.y < bounds.min_y() {
+            offset.y = SWATCH_HEIGHT + POPOVER_GAP;
+        }
+        offset.x = offset
+            .x
+            .min(bounds.max_x() - size.width)
+            .max(bounds.min_x());
+        offset
+    }
+
+    fn swatch_rect() -> LocalRect {
+        LocalRect::new(LocalPoint::zero(), [SWATCH_WIDTH, SWATCH_HEIGHT].into())
+    }
+}
+
+impl<B> View for ColorPicker<B>
+where
+    B: Binding<Color>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let state = picker_state(vid, cx).clone();
+        if !state.open {
+            if let Event::TouchBegin { position, .. } = event {
+                if Self::swatch_rect().contains(*position) {
+                    self.sync(vid, cx);
+                    cx[StateHandle::<PickerState>::new(vid)].open = true;
+                }
+            }
+            return;
+        }
+
+        if let Event::Anim = event {
+            self.sync(vid, cx);
+        }
+
+        path.push(0);
+        let offset = cx.get_layout(path).offset;
+        self.popover(vid, cx)
+            .process(&event.offset(-offset), path, cx, actions);
+        path.pop();
+
+        // Clicks and Escape while picking are the eyedropper's.
+        let close = !state.eyedropper.picking
+            && match event {
+                Event::TouchBegin { position, .. } => {
+                    let popover = LocalRect::new(offset.to_point(), state.popover_size);
+                    !popover.contains(*position)
+                }
+                Event::Key(k) => k.pressed() == Some(Key::Escape),
+                _ => false,
+            };
+        if close {
+            cx[StateHandle::<PickerState>::new(vid)].open = false;
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let color = *self.color.get(args.cx);
+        let theme = args.cx.theme();
+        let swatch = Self::swatch_rect();
+
+        args.vger.save();
+        args.vger.scissor(swatch);
+        draw_checkers(swatch, args.vger);
+        args.vger.restore();
+        let paint = args.vger.color_paint(color);
+        args.vger.fill_rect(swatch, SWATCH_RADIUS, paint);
+        let border = args.vger.color_paint(theme.control_foreground);
+        args.vger
+            .stroke_rect(swatch.min(), swatch.max(), SWATCH_RADIUS, 1.0, border);
+
+        let state = picker_state(vid, args.cx).clone();
+        if !state.open {
+            return;
+        }
+
+        let xform = args.vger.current_transform();
+        let window = WorldRect::new(WorldPoint::zero(), args.cx.window_size);
+        let bounds = match xform.inverse() {
+            Some(inverse) => inverse.outer_transformed_rect(&window),
+            None => return,
+        };
+        let offset = Self::place_popover(state.popover_size, bounds);
+        path.push(0);
+        // Process and hittest use where it was last drawn.
+        args.cx.set_layout_offset(path, offset);
+
+        let rect = LocalRect::new(offset.to_point(), state.popover_size);
+        let style = LayerStyle {
+            shadow: Some(Shadow {
+                radius: 8.0,
+                offset: [0.0, -4.0].into(),
+                color: Color::new(0.0, 0.0, 0.0, 0.3),
+            }),
+            floating: true,
+            ..LayerStyle::default()
+        };
+        let popover = self.popover(vid, args.cx);
+        // The eyedropper's loupe goes outside the popover, which a layer
+        // would clip.
+        let layer = if state.eyedropper.picking {
+            None
+        } else {
+            args.cx.begin_layer(rect, xform, &style)
+        };
+        match layer {
+            Some(mut layer) => {
+                layer.translate(offset);
+                popover.draw(
+                    path,
+                    &mut DrawArgs {
+                        cx: args.cx,
+                        vger: &mut layer,
+                    },
+                );
+                args.cx.end_layer(args.vger, layer, xform, style);
+            }
+            None => {
+                args.vger.save();
+                args.vger.translate(offset);
+                popover.draw(path, args);
+                args.vger.restore();
+            }
+        }
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        if picker_state(vid, args.cx).open {
+            path.push(0);
+            let offset = args.cx.get_layout(path).offset;
+            let popover = self.popover(vid, args.cx);
+            // Each control has padding all round, inside the popover's.
+            let controls = LocalSize::new(
+                CONTROL_WIDTH,
+                PLANE_HEIGHT + 2.0 * STRIP_HEIGHT + FIELD_HEIGHT,
+            );
+            let padding = LocalSize::new(4.0, 10.0) * POPOVER_SPACING;
+            let size = popover.layout(path, &mut args.size(controls + padding));
+            // Below the swatch until it's drawn somewhere else.
+            let offset = if offset == LocalOffset::zero() {
+                LocalOffset::new(0.0, -POPOVER_GAP - size.height)
+            } else {
+                offset
+            };
+            args.cx.set_layout_offset(path, offset);
+            path.pop();
+            picker_state(vid, args.cx).popover_size = size;
+        }
+
+        let size = Self::swatch_rect().size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        let state = picker_state(vid, cx).clone();
+        path.push(0);
+        let offset = cx.get_layout(path).offset;
+        path.pop();
+
+        // The eyedropper's loupe could be anywhere, so leave it to a full
+        // redraw.
+        if state.eyedropper.picking {
+            return;
+        }
+
+        // The popover is outside the swatch's bounds.
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            let popover = LocalRect::new(offset.to_point(), state.popover_size);
+            cx.add_dirty_rect(path, Self::swatch_rect().union(&popover), xform);
+        } else if state.open {
+            path.push(0);
+            self.popover(vid, cx)
+                .dirty(path, xform.pre_translate(offset), cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let vid = cx.view_id(path);
+        let mut hit = Self::swatch_rect().contains(pt).then_some(vid);
+        if picker_state(vid, cx).open {
+            path.push(0);
+            let offset = cx.get_layout(path).offset;
+            hit = self.popover(vid, cx).hittest(path, pt - offset, cx).or(hit);
+            path.pop();
+        }
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let vid = cx.view_id(path);
+        if picker_state(vid, cx).open {
+            path.push(0);
+            self.popover(vid, cx).commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let vid = cx.view_id(path);
+        map.push(vid);
+        if picker_state(vid, cx).open {
+            path.push(0);
+            self.popover(vid, cx).gc_view(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let vid = cx.view_id(path);
+        let mut children = vec![];
+        if picker_state(vid, cx).open {
+            path.push(0);
+            children.extend(self.popover(vid, cx).access(path, cx, nodes));
+            path.pop();
+        }
+
+        let aid = vid.access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::ColorWell);
+        builder.set_value(self.color.get(cx).to_hex());
+        builder.set_children(children);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl<B> private::Sealed for ColorPicker<B> {}
+
+/// A swatch showing `color`. Clicking it opens a popover for picking
+/// hue, saturation and value, with a strip for alpha and a field for
+/// typing a hex color like `#FF8000`, applied on Enter, and an
+/// `eyedropper` for picking a color from the window. Clicking outside the
+/// popover or pressing Escape closes it.
+pub fn color_picker(color: impl Binding<Color>) -> impl View {
+    ColorPicker { color }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Window frames of the views of a type, in the order they're found.
+    fn frames(h: &mut Harness<impl View>, type_name: &str) -> Vec<LocalRect> {
+        fn visit(node: &ViewNode, type_name: &str, frames: &mut Vec<LocalRect>) {
+            if let (true, Some([x, y, width, height])) = (node.type_name == type_name, node.frame) {
+                frames.push(LocalRect::new([x, y].into(), [width, height].into()));
+            }
+            for child in &node.children {
+                visit(child, type_name, frames);
+            }
+        }
+        let mut frames = vec![];
+        for node in h.view_tree() {
+            visit(&node, type_name, &mut frames);
+        }
+        frames
+    }
+
+    fn color(h: &Harness<impl View>) -> Color {
+        h.states::<Color>()[0]
+    }
+
+    fn assert_close(a: Color, b: Color) {
+        let close = |x: f32, y: f32| (x - y).abs() < 0.02;
+        assert!(
+            close(a.r, b.r) && close(a.g, b.g) && close(a.b, b.b) && close(a.a, b.a),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    fn picker() -> impl View {
+        state(
+            || Color::new(1.0, 0.0, 0.0, 1.0),
+            |c, cx| vstack((color_picker(c), text(&cx[c].to_hex()))),
+        )
+    }
+
+    #[test]
+    fn test_color_picker() {
+        let mut h = Harness::new(picker());
+        let swatch = frames(&mut h, "ColorPicker")[0];
+        assert!(frames(&mut h, "Canvas").is_empty());
+
+        h.click_at(swatch.center().into());
+        // Frames leave out padding, which adds up to this for the controls.
+        let padding = LocalOffset::new(2.0, 2.0) * POPOVER_SPACING;
+        let canvases: Vec<_> = frames(&mut h, "Canvas")
+            .iter()
+            .map(|frame| frame.translate(padding))
+            .collect();
+        let plane = canvases[0];
+        let (hue, alpha) = (canvases[1], canvases[2]);
+        assert_eq!(plane.height(), PLANE_HEIGHT);
+        assert_eq!(hue.height(), STRIP_HEIGHT);
+
+        // The popover's below the swatch.
+        assert!(plane.max_y() < swatch.min_y());
+
+        h.click_at(plane.center().into());
+        assert_close(color(&h), Color::hsv(0.0, 0.5, 0.5));
+        h.click_at(hue.center().into());
+        assert_close(color(&h), Color::hsv(180.0, 0.5, 0.5));
+        h.click_at(Point::new(
+            alpha.min_x() + alpha.width() / 4.0,
+            alpha.center().y,
+        ));
+        assert_close(color(&h), Color::hsv(180.0, 0.5, 0.5).alpha(0.25));
+        assert_eq!(h.states::<PickerState>()[0].hex, color(&h).to_hex());
+
+        // Grays keep their hue.
+        h.click_at(Point::new(plane.min_x() + 1.0, plane.center().y));
+        h.click_at(Point::new(plane.max_x() - 1.0, plane.center().y));
+        assert!((color(&h).to_hsv().0 - 180.0).abs() < 1.0);
+
+        // Clicking outside closes it.
+        h.click_at(Point::new(swatch.max_x() + 100.0, swatch.center().y));
+        assert!(frames(&mut h, "Canvas").is_empty());
+    }
+
+    #[test]
+    fn test_color_picker_hex() {
+        let mut h = Harness::new(picker());
+        let swatch = frames(&mut h, "ColorPicker")[0];
+        h.click_at(swatch.center().into());
+
+        h.key(Key::Tab);
+        h.key(Key::End);
+        for _ in 0..7 {
+            h.key(Key::Backspace);
+        }
+        h.type_text("#00FF00\n");
+        h.expect_text("#00FF00");
+
+        // Bad hex goes back to the color.
+        h.type_text("zz\n");
+        assert_eq!(h.states::<PickerState>()[0].hex, "#00FF00");
+
+        h.key(Key::Escape);
+        assert!(frames(&mut h, "Canvas").is_empty());
+    }
+
+    #[test]
+    fn test_color_picker_eyedropper() {
+        let mut h = Harness::new(picker());
+        let swatch = frames(&mut h, "ColorPicker")[0];
+        h.click_at(swatch.center().into());
+        h.context().capture_supported = true;
+        h.context().capture = Some(WindowCapture {
+            center: WorldPoint::zero(),
+            radius: 0,
+            colors: vec![Some(Color::new(0.0, 0.0, 1.0, 1.0))],
+            generation: 1,
+        });
+
+        let padding = LocalOffset::new(2.0, 2.0) * POPOVER_SPACING;
+        let dropper = frames(&mut h, "Eyedropper")[0].translate(padding);
+        assert_eq!(dropper.height(), FIELD_HEIGHT);
+
+        // Escape stops picking, leaving the popover open.
+        h.click_at(dropper.center().into());
+        h.key(Key::Escape);
+        assert!(!frames(&mut h, "Canvas").is_empty());
+
+        // Picking outside the popover doesn't close it.
+        let outside = Point::new(swatch.max_x() + 100.0, swatch.center().y);
+        h.click_at(dropper.center().into());
+        h.click_at(outside);
+        h.expect_text("#0000FF");
+        assert!(!frames(&mut h, "Canvas").is_empty());
+        assert_eq!(h.states::<PickerState>()[0].hex, "#0000FF");
+
+        h.click_at(outside);
+        assert!(frames(&mut h, "Canvas").is_empty());
+    }
+}
@@ -77,7 +77,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -315,7 +315,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -57,6 +57,26 @@ where
         }
     }
 
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        if self.cond {
+            path.push(0);
+            self.if_true.dirty(path, xform, cx);
+            path.pop();
+        } else {
+            path.push(1);
+            self.if_false.dirty(path, xform, cx);
+            path.pop();
+        }
+    }
+
+    fn is_flexible(&self) -> bool {
+        if self.cond {
+            self.if_true.is_flexible()
+        } else {
+            self.if_false.is_flexible()
+        }
+    }
+
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
         if self.cond {
             path.push(0);
@@ -86,11 +106,11 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         if self.cond {
             path.push(0);
-            self.if_true.gc(path, cx, map);
+            self.if_true.gc_view(path, cx, map);
             path.pop();
         } else {
             path.push(1);
-            self.if_false.gc(path, cx, map);
+            self.if_false.gc_view(path, cx, map);
             path.pop();
         }
     }
@@ -125,3 +145,168 @@ pub fn cond(cond: bool, if_true: impl View, if_false: impl View) -> impl View {
         if_false,
     }
 }
+
+/// Like `cond`, but type-erased, so the result can be returned alongside
+/// other `AnyView`s. The two views are told apart even if they're the
+/// same type, so state isn't carried from one to the other.
+pub fn if_view(cond: bool, if_true: impl View, if_false: impl View) -> AnyView {
+    any_view(Cond {
+        cond,
+        if_true,
+        if_false,
+    })
+}
+
+/// Struct for `optional_view`
+pub struct OptionalView<V> {
+    view: Option<V>,
+}
+
+impl<V> View for OptionalView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Some(view) = &self.view {
+            path.push(0);
+            view.process(event, path, cx, actions);
+            path.pop();
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        if let Some(view) = &self.view {
+            path.push(0);
+            view.draw(path, args);
+            path.pop();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        match &self.view {
+            Some(view) => {
+                path.push(0);
+                let sz = view.layout(path, args);
+                path.pop();
+                sz
+            }
+            None => LocalSize::zero(),
+        }
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        if let Some(view) = &self.view {
+            path.push(0);
+            view.dirty(path, xform, cx);
+            path.pop();
+        }
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.view.as_ref().is_some_and(|view| view.is_flexible())
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let view = self.view.as_ref()?;
+        path.push(0);
+        let id = view.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        if let Some(view) = &self.view {
+            path.push(0);
+            view.commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        if let Some(view) = &self.view {
+            path.push(0);
+            view.gc_view(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let view = self.view.as_ref()?;
+        path.push(0);
+        let node_id = view.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for OptionalView<V> {}
+
+/// Shows nothing and takes no space when `None`, so a view can be left
+/// out with `optional_view(flag.then(|| view))`.
+pub fn optional_view(view: Option<impl View>) -> impl View {
+    OptionalView { view }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn counter() -> impl View {
+        state(
+            || 0,
+            |n, cx| button(text(&format!("count {}", cx[n])), move |cx| cx[n] += 1),
+        )
+    }
+
+    #[test]
+    fn test_if_view() {
+        let view = state(
+            || true,
+            |first, cx| {
+                vstack((
+                    if_view(cx[first], counter(), counter()),
+                    button("swap", move |cx| cx[first] = !cx[first]),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("count 0");
+        h.expect_text("count 1");
+
+        // Both branches are counters, but the count isn't carried over.
+        h.click("swap");
+        h.expect_text("count 0");
+    }
+
+    #[test]
+    fn test_optional_view() {
+        let view = state(
+            || false,
+            |shown, cx| {
+                vstack((
+                    optional_view(cx[shown].then(|| text("details"))),
+                    button("more", move |cx| cx[shown] = true),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        let button = h.find_text("more").unwrap();
+        h.expect_no_text("details");
+
+        h.click("more");
+        h.expect_text("details");
+        assert!(h.find_text("more").unwrap().origin.y < button.origin.y);
+    }
+}
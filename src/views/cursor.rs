@@ -0,0 +1,188 @@
+use crate::*;
+use std::any::Any;
+
+/// Shape of the mouse pointer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorIcon {
+    /// The platform's usual arrow.
+    #[default]
+    Default,
+    /// I-beam, for text which can be selected or edited.
+    Text,
+    /// Pointing hand, for buttons and links.
+    Pointer,
+    /// Open hand, for things which can be dragged.
+    Grab,
+    /// Closed hand, while dragging.
+    Grabbing,
+    /// Left and right arrows, for handles between columns.
+    ResizeHorizontal,
+    /// Up and down arrows, for handles between rows.
+    ResizeVertical,
+    /// Diagonal arrows, for grips in the bottom right corner.
+    ResizeDiagonal,
+    Crosshair,
+    NotAllowed,
+    Move,
+}
+
+impl Context {
+    /// Sets the mouse pointer's shape until the mouse next moves, as for
+    /// a view which draws its own regions. `.cursor` is simpler for
+    /// whole views.
+    pub fn set_cursor(&mut self, icon: CursorIcon) {
+        self.cursor = Some(icon);
+    }
+
+    /// The pointer shape asked for by the views under the mouse.
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor.unwrap_or_default()
+    }
+}
+
+/// Struct for the `cursor` modifier.
+pub struct CursorView<V> {
+    child: V,
+    icon: CursorIcon,
+}
+
+impl<V> CursorView<V>
+where
+    V: View,
+{
+    pub fn new(child: V, icon: CursorIcon) -> Self {
+        Self { child, icon }
+    }
+}
+
+impl<V> View for CursorView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        // Children go first so the innermost view's cursor wins. While a
+        // button is held, the cursor stays as it was when pressed. Text
+        // isn't hit by the mouse, so this checks the view's bounds.
+        if let Event::TouchMove { position, .. } = &event {
+            if cx.mouse_button.is_none()
+                && cx.cursor.is_none()
+                && cx.get_layout(path).rect.contains(*position)
+            {
+                cx.cursor = Some(self.icon);
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for CursorView<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let mut h = Harness::new(hstack((
+            button("ok", |_| ()),
+            text("plain"),
+            text("select me").cursor(CursorIcon::Text),
+        )));
+        h.update();
+        let ok = h.find_text("ok").unwrap().center();
+        let plain = h.find_text("plain").unwrap().center();
+        let select = h.find_text("select me").unwrap().center();
+
+        h.hover(ok);
+        assert_eq!(h.context().cursor(), CursorIcon::Pointer);
+        h.hover(select);
+        assert_eq!(h.context().cursor(), CursorIcon::Text);
+        h.hover(plain);
+        assert_eq!(h.context().cursor(), CursorIcon::Default);
+    }
+
+    #[test]
+    fn test_innermost_cursor() {
+        let mut h = Harness::new(
+            text("inner")
+                .cursor(CursorIcon::Crosshair)
+                .padding(Auto)
+                .background(rectangle())
+                .cursor(CursorIcon::Move),
+        );
+        h.update();
+        let inner = h.find_text("inner").unwrap();
+        h.hover(inner.center());
+        assert_eq!(h.context().cursor(), CursorIcon::Crosshair);
+        h.hover(Point::new(inner.origin.x - 2.0, inner.origin.y - 2.0));
+        assert_eq!(h.context().cursor(), CursorIcon::Move);
+    }
+}
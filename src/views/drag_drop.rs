@@ -0,0 +1,583 @@
+use crate::*;
+use std::any::Any;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// How opaque the copy of a view following the pointer is.
+const PREVIEW_OPACITY: f32 = 0.8;
+
+/// A drag started by a view with `draggable`.
+pub(crate) struct DragSession {
+    payload: Rc<dyn Any>,
+    touch: usize,
+}
+
+/// Drags between views with `draggable` and `on_drop_target`, and files
+/// dropped on the window.
+#[derive(Default)]
+pub(crate) struct DragDrop {
+    session: Option<DragSession>,
+
+    /// Whether a view took the drop in the event being processed, so
+    /// views around it don't.
+    accepted: bool,
+}
+
+impl Context {
+    /// Is a view being dragged with `draggable`?
+    pub fn is_dragging(&self) -> bool {
+        self.drag.session.is_some()
+    }
+
+    /// Called before processing each event.
+    pub(crate) fn begin_drop(&mut self) {
+        self.drag.accepted = false;
+    }
+
+    /// Called after processing each event. Drop targets see the release
+    /// wherever they are in the tree, so the drag ends only after.
+    pub(crate) fn end_drop(&mut self, event: &Event) {
+        if let Event::TouchEnd { id, .. } = event {
+            if self.drag.session.as_ref().is_some_and(|s| s.touch == *id) {
+                self.drag.session = None;
+            }
+        }
+    }
+
+    /// The payload being dragged by touch `id`, if it's a `T`.
+    fn drag_payload<T: 'static>(&self, id: usize) -> Option<&T> {
+        self.drag
+            .session
+            .as_ref()
+            .filter(|s| s.touch == id)
+            .and_then(|s| s.payload.downcast_ref())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct DragSourceState {
+    touch: Option<usize>,
+    start: LocalPoint,
+    position: LocalPoint,
+
+    /// Whether the touch has moved far enough to start dragging.
+    dragging: bool,
+}
+
+fn drag_source_state(vid: ViewId, cx: &mut Context) -> &mut DragSourceState {
+    cx.init_state(vid, &DragSourceState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for the `draggable` modifier.
+pub struct DragSource<V, T> {
+    child: V,
+    payload: T,
+}
+
+impl<V, T> DragSource<V, T>
+where
+    V: View,
+    T: Clone + 'static,
+{
+    pub fn new(child: V, payload: T) -> Self {
+        Self { child, payload }
+    }
+
+    fn drag(&self, vid: ViewId, id: usize, position: LocalPoint, cx: &mut Context) {
+        let mut state = *drag_source_state(vid, cx);
+        if state.touch != Some(id) {
+            return;
+        }
+        state.position = position;
+
+        if !state.dragging {
+            // Views inside see the touch first, so a scroll view which
+            // wants it has taken it by now.
+            let start = (position - state.start).length() > SCROLL_SLOP
+                && cx.scroll_claims[id].is_default()
+                && !cx.is_dragging();
+            if !start {
+                *drag_source_state(vid, cx) = state;
+                return;
+            }
+            state.dragging = true;
+            cx.touches[id] = vid;
+            cx.scroll_claims[id] = vid;
+            cx.drag.session = Some(DragSession {
+                payload: Rc::new(self.payload.clone()),
+                touch: id,
+            });
+        }
+        cx[StateHandle::<DragSourceState>::new(vid)] = state;
+    }
+
+    fn end_drag(&self, vid: ViewId, id: usize, cx: &mut Context) {
+        let state = *drag_source_state(vid, cx);
+        if state.touch != Some(id) {
+            return;
+        }
+        if state.dragging {
+            if cx.scroll_claims[id] == vid {
+                cx.scroll_claims[id] = ViewId::default();
+            }
+            if cx.touches[id] == vid {
+                cx.touches[id] = ViewId::default();
+            }
+            cx[StateHandle::<DragSourceState>::new(vid)] = DragSourceState::default();
+        } else {
+            *drag_source_state(vid, cx) = DragSourceState::default();
+        }
+    }
+
+    /// Draws the child again where it's been dragged to, over everything
+    /// else when layers are available.
+    fn draw_preview(&self, offset: LocalOffset, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect.translate(offset);
+        let xform = args.vger.current_transform();
+        let style = LayerStyle {
+            opacity: PREVIEW_OPACITY,
+            shadow: Some(Shadow {
+                radius: 8.0,
+                offset: [0.0, -4.0].into(),
+                color: Color::new(0.0, 0.0, 0.0, 0.3),
+            }),
+            floating: true,
+            ..LayerStyle::default()
+        };
+
+        path.push(0);
+        match args.cx.begin_layer(rect, xform, &style) {
+            Some(mut layer) => {
+                layer.translate(offset);
+                self.child.draw(
+                    path,
+                    &mut DrawArgs {
+                        cx: args.cx,
+                        vger: &mut layer,
+                    },
+                );
+                args.cx.end_layer(args.vger, layer, xform, style);
+            }
+            None => {
+                args.vger.save();
+                args.vger.translate(offset);
+                self.child.draw(path, args);
+                args.vger.restore();
+            }
+        }
+        path.pop();
+    }
+}
+
+impl<V, T> View for DragSource<V, T>
+where
+    V: View,
+    T: Clone + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        if let Event::TouchBegin { id, position } = event {
+            if self.hittest(path, *position, cx).is_some() {
+                *drag_source_state(vid, cx) = DragSourceState {
+                    touch: Some(*id),
+                    start: *position,
+                    position: *position,
+                    dragging: false,
+                };
+            }
+        }
+
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        match event {
+            Event::TouchMove { id, position, .. } => self.drag(vid, *id, *position, cx),
+            Event::TouchEnd { id, .. } => self.end_drag(vid, *id, cx),
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+
+        let vid = args.cx.view_id(path);
+        let state = *drag_source_state(vid, args.cx);
+        if state.dragging {
+            self.draw_preview(state.position - state.start, path, args);
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let size = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, T> private::Sealed for DragSource<V, T> {}
+
+type HoverCallback = Box<dyn Fn(&mut Context, bool)>;
+
+/// Struct for the `on_drop_target` modifier.
+pub struct DropTarget<V, T, F> {
+    child: V,
+    func: F,
+    on_hover: Option<HoverCallback>,
+    phantom: PhantomData<fn(T)>,
+}
+
+impl<V, T, F> DropTarget<V, T, F>
+where
+    V: View,
+    T: Clone + 'static,
+    F: Fn(&mut Context, T) + 'static,
+{
+    pub fn new(child: V, func: F) -> Self {
+        Self {
+            child,
+            func,
+            on_hover: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Calls `f` when a payload this view would accept is dragged over
+    /// it, and when it leaves or is dropped, as for highlighting.
+    pub fn on_hover(self, f: impl Fn(&mut Context, bool) + 'static) -> Self {
+        Self {
+            on_hover: Some(Box::new(f)),
+            ..self
+        }
+    }
+}
+
+impl<V, T, F> View for DropTarget<V, T, F>
+where
+    V: View,
+    T: Clone + 'static,
+    F: Fn(&mut Context, T) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        // Targets inside this one get the first chance at a drop.
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        let (id, position, released) = match event {
+            Event::TouchMove { id, position, .. } => (*id, *position, false),
+            Event::TouchEnd { id, position } => (*id, *position, true),
+            _ => return,
+        };
+        let over =
+            cx.drag_payload::<T>(id).is_some() && cx.get_layout(path).rect.contains(position);
+
+        let vid = cx.view_id(path);
+        cx.init_state(vid, &|| false);
+        let hovered = over && !released;
+        let state = StateHandle::<bool>::new(vid);
+        if cx[state] != hovered {
+            cx[state] = hovered;
+            if let Some(f) = &self.on_hover {
+                f(cx, hovered);
+            }
+        }
+
+        if over && released && !cx.drag.accepted {
+            cx.drag.accepted = true;
+            let payload = cx.drag_payload::<T>(id).unwrap().clone();
+            (self.func)(cx, payload);
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let size = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, T, F> private::Sealed for DropTarget<V, T, F> {}
+
+/// Struct for the `on_drop` modifier.
+pub struct FileDrop<V, F> {
+    child: V,
+    func: F,
+}
+
+impl<V, F> FileDrop<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, &[PathBuf]) + 'static,
+{
+    pub fn new(child: V, func: F) -> Self {
+        Self { child, func }
+    }
+}
+
+impl<V, F> View for FileDrop<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, &[PathBuf]) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        if let Event::DropFiles { position, paths } = event {
+            if !cx.drag.accepted && cx.get_layout(path).rect.contains(*position) {
+                cx.drag.accepted = true;
+                (self.func)(cx, paths);
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let size = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, F> private::Sealed for FileDrop<V, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+
+    fn card(name: &'static str) -> impl View {
+        zstack((rectangle(), text(name))).size([100.0, 100.0])
+    }
+
+    #[test]
+    fn test_drag_to_target() {
+        let dropped = Rc::new(RefCell::new(vec![]));
+        let hovers = Rc::new(RefCell::new(vec![]));
+        let (d, h) = (dropped.clone(), hovers.clone());
+        let view = hstack((
+            card("source").draggable(42),
+            card("words").draggable("words"),
+            card("target")
+                .on_drop_target(move |_, n: i32| d.borrow_mut().push(n))
+                .on_hover(move |_, hovered| h.borrow_mut().push(hovered)),
+        ));
+        let mut harness = Harness::with_size(view, Size::new(300.0, 100.0));
+        let source = harness.find_text("source").unwrap().center();
+        let words = harness.find_text("words").unwrap().center();
+        let target = harness.find_text("target").unwrap().center();
+
+        harness.drag(source, target);
+        assert_eq!(*dropped.borrow(), vec![42]);
+        assert_eq!(*hovers.borrow(), vec![true, false]);
+        assert!(!harness.context().is_dragging());
+
+        // Payloads of other types aren't accepted.
+        harness.drag(words, target);
+        assert_eq!(*dropped.borrow(), vec![42]);
+
+        // Nor are drops elsewhere.
+        harness.drag(source, words);
+        assert_eq!(*dropped.borrow(), vec![42]);
+    }
+
+    #[test]
+    fn test_drop_files() {
+        let dropped = Rc::new(RefCell::new(vec![]));
+        let d = dropped.clone();
+        let view = vstack((
+            card("files").on_drop(move |_, paths| d.borrow_mut().extend_from_slice(paths)),
+            card("elsewhere"),
+        ));
+        let mut harness = Harness::with_size(view, Size::new(100.0, 200.0));
+        let files = harness.find_text("files").unwrap().center();
+        let elsewhere = harness.find_text("elsewhere").unwrap().center();
+
+        let paths = vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")];
+        harness.drop_files(elsewhere, paths.clone());
+        assert!(dropped.borrow().is_empty());
+        harness.drop_files(files, paths.clone());
+        assert_eq!(*dropped.borrow(), paths);
+    }
+}
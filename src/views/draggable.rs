@@ -0,0 +1,545 @@
+use crate::*;
+use std::any::Any;
+
+/// How far past its bounds a view can be pulled, relative to its size.
+/// Matches the feel of iOS scroll views.
+const RUBBER_BAND: f32 = 0.55;
+
+/// How far ahead of a release to look for a snap point, in seconds of
+/// the touch's velocity, so flicks carry on to the next one.
+const PROJECTION: f32 = 0.15;
+
+/// Within these, a spring counts as settled.
+const SETTLE_DISTANCE: f32 = 0.5;
+const SETTLE_SPEED: f32 = 5.0;
+
+/// A touch being followed by a draggable view.
+#[derive(Clone, Copy, Debug)]
+struct DragTouch {
+    id: usize,
+
+    /// Total movement since the touch began.
+    moved: LocalOffset,
+
+    /// Whether the view took the touch, once it's moved past the slop.
+    claimed: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DraggableState {
+    /// Where the child is drawn, relative to where it was laid out.
+    pub(crate) offset: LocalOffset,
+
+    /// Where the touch would have put the child, without rubber-banding.
+    raw: LocalOffset,
+
+    /// In points per second.
+    velocity: LocalOffset,
+
+    /// Movement since the last animation frame, for the velocity.
+    pending: LocalOffset,
+
+    touch: Option<DragTouch>,
+
+    /// Where the spring is pulling the child, once released.
+    target: Option<LocalOffset>,
+
+    size: LocalSize,
+}
+
+type SnapCallback = Box<dyn Fn(&mut Context, usize)>;
+
+/// Works out the bounds and snap points from the child's size, for views
+/// built on `draggable` whose limits depend on the space they're given.
+pub(crate) type FitCallback = Box<dyn Fn(&Context, LocalSize) -> (LocalRect, Vec<LocalOffset>)>;
+
+/// Struct for `draggable`.
+pub struct Draggable<V> {
+    child: V,
+    bounds: Option<LocalRect>,
+    snap_points: Vec<LocalOffset>,
+    stiffness: f32,
+    damping: f32,
+    on_snap: Option<SnapCallback>,
+    fit: Option<FitCallback>,
+}
+
+/// Lets `child` be dragged around, then springs it back inside its
+/// bounds, or to the nearest snap point, when let go.
+///
+/// Views inside see touches until they've moved a few points, so buttons
+/// still work. Scroll views inside take drags along their axis first.
+pub fn draggable<V: View>(child: V) -> Draggable<V> {
+    Draggable {
+        child,
+        bounds: None,
+        snap_points: vec![],
+        stiffness: 300.0,
+        damping: 30.0,
+        on_snap: None,
+        fit: None,
+    }
+}
+
+/// Pulls `x` past `min..=max` with resistance which grows with distance,
+/// never getting further than `size` beyond.
+fn rubber_band(x: f32, min: f32, max: f32, size: f32) -> f32 {
+    let size = size.max(1.0);
+    let band = |excess: f32| (1.0 - 1.0 / (excess * RUBBER_BAND / size + 1.0)) * size;
+    if x < min {
+        min - band(min - x)
+    } else if x > max {
+        max + band(x - max)
+    } else {
+        x
+    }
+}
+
+impl DraggableState {
+    /// Is the child following a touch or springing somewhere?
+    pub(crate) fn is_moving(&self) -> bool {
+        self.touch.is_some() || self.target.is_some()
+    }
+}
+
+pub(crate) fn draggable_state(vid: ViewId, cx: &mut Context) -> &mut DraggableState {
+    cx.init_state(vid, &DraggableState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Springs the draggable view `vid` to `target`, dropping any touch it was
+/// following.
+pub(crate) fn spring_to(vid: ViewId, target: LocalOffset, cx: &mut Context) {
+    let mut state = *draggable_state(vid, cx);
+    state.touch = None;
+    state.target = Some(target);
+    cx[StateHandle::<DraggableState>::new(vid)] = state;
+}
+
+impl<V: View> Draggable<V> {
+    /// Keeps the child's offset within `bounds` once released. A bounds
+    /// with no width or height only moves along the other axis.
+    pub fn bounds(self, bounds: LocalRect) -> Self {
+        Self {
+            bounds: Some(bounds),
+            ..self
+        }
+    }
+
+    /// Offsets the child settles at when released, picking the one nearest
+    /// to where the release was heading.
+    pub fn snap_points(self, snap_points: Vec<LocalOffset>) -> Self {
+        Self {
+            snap_points,
+            ..self
+        }
+    }
+
+    /// Sets how stiff the spring is, and how quickly it stops bouncing.
+    /// Critical damping is `2 * stiffness.sqrt()`.
+    pub fn spring(self, stiffness: f32, damping: f32) -> Self {
+        Self {
+            stiffness,
+            damping,
+            ..self
+        }
+    }
+
+    /// Calls `f` with the index of the snap point the child settles at.
+    pub fn on_snap(self, f: impl Fn(&mut Context, usize) + 'static) -> Self {
+        Self {
+            on_snap: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Replaces the bounds and snap points with ones worked out by `fit`.
+    pub(crate) fn fit(
+        self,
+        fit: impl Fn(&Context, LocalSize) -> (LocalRect, Vec<LocalOffset>) + 'static,
+    ) -> Self {
+        Self {
+            fit: Some(Box::new(fit)),
+            ..self
+        }
+    }
+
+    /// The bounds and snap points for a child of `size`.
+    fn limits(&self, size: LocalSize, cx: &Context) -> (Option<LocalRect>, Vec<LocalOffset>) {
+        match &self.fit {
+            Some(fit) => {
+                let (bounds, snap_points) = fit(cx, size);
+                (Some(bounds), snap_points)
+            }
+            None => (self.bounds, self.snap_points.clone()),
+        }
+    }
+
+    /// Can a touch which moved by `delta` drag us? Drags across a locked
+    /// axis are left for views around us.
+    fn can_drag(bounds: Option<LocalRect>, delta: LocalOffset) -> bool {
+        match bounds {
+            Some(b) if b.width() == 0.0 => delta.y.abs() > delta.x.abs(),
+            Some(b) if b.height() == 0.0 => delta.x.abs() > delta.y.abs(),
+            _ => true,
+        }
+    }
+
+    fn banded(bounds: Option<LocalRect>, raw: LocalOffset, size: LocalSize) -> LocalOffset {
+        match bounds {
+            Some(b) => [
+                rubber_band(raw.x, b.min_x(), b.max_x(), size.width),
+                rubber_band(raw.y, b.min_y(), b.max_y(), size.height),
+            ]
+            .into(),
+            None => raw,
+        }
+    }
+
+    fn clamped(bounds: Option<LocalRect>, offset: LocalOffset) -> LocalOffset {
+        match bounds {
+            Some(b) => [
+                offset.x.clamp(b.min_x(), b.max_x()),
+                offset.y.clamp(b.min_y(), b.max_y()),
+            ]
+            .into(),
+            None => offset,
+        }
+    }
+
+    /// Where to settle after a release, and which snap point that is.
+    fn settle_target(
+        bounds: Option<LocalRect>,
+        snap_points: &[LocalOffset],
+        offset: LocalOffset,
+        velocity: LocalOffset,
+    ) -> (LocalOffset, Option<usize>) {
+        let projected = Self::clamped(bounds, offset + velocity * PROJECTION);
+        let nearest = snap_points
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                let da = (**a - projected).square_length();
+                let db = (**b - projected).square_length();
+                da.total_cmp(&db)
+            })
+            .map(|(i, p)| (i, *p));
+        match nearest {
+            Some((i, p)) => (Self::clamped(bounds, p), Some(i)),
+            None => (Self::clamped(bounds, offset), None),
+        }
+    }
+
+    fn drag(&self, vid: ViewId, id: usize, delta: LocalOffset, cx: &mut Context) {
+        let state = draggable_state(vid, cx);
+        let touch = match &mut state.touch {
+            Some(touch) if touch.id == id => touch,
+            _ => return,
+        };
+        touch.moved += delta;
+
+        let moved = match touch.claimed {
+            Some(true) => delta,
+            None if touch.moved.length() > SCROLL_SLOP => {
+                let moved = touch.moved;
+                // Views within this one see the move first, so an inner
+                // scroll view which wants the touch has taken it by now.
+                let (bounds, _) = self.limits(state.size, cx);
+                let claim = cx.scroll_claims[id].is_default() && Self::can_drag(bounds, moved);
+                let state = draggable_state(vid, cx);
+                state.touch = state.touch.map(|touch| DragTouch {
+                    claimed: Some(claim),
+                    ..touch
+                });
+                if !claim {
+                    return;
+                }
+                cx.scroll_claims[id] = vid;
+                cx.touches[id] = vid;
+                moved
+            }
+            _ => return,
+        };
+
+        let mut state = *draggable_state(vid, cx);
+        let (bounds, _) = self.limits(state.size, cx);
+        state.raw += moved;
+        state.pending += moved;
+        state.offset = Self::banded(bounds, state.raw, state.size);
+        cx[StateHandle::<DraggableState>::new(vid)] = state;
+    }
+
+    fn end_drag(&self, vid: ViewId, id: usize, cx: &mut Context) {
+        let state = draggable_state(vid, cx);
+        let touch = match state.touch {
+            Some(touch) if touch.id == id => touch,
+            _ => return,
+        };
+        state.touch = None;
+        let state = *state;
+
+        if touch.claimed == Some(true) {
+            let (bounds, snap_points) = self.limits(state.size, cx);
+            let (target, snap) =
+                Self::settle_target(bounds, &snap_points, state.offset, state.velocity);
+            cx[StateHandle::<DraggableState>::new(vid)].target = Some(target);
+            if let (Some(i), Some(f)) = (snap, &self.on_snap) {
+                f(cx, i);
+            }
+        }
+        if cx.scroll_claims[id] == vid {
+            cx.scroll_claims[id] = ViewId::default();
+            if cx.touches[id] == vid {
+                cx.touches[id] = ViewId::default();
+            }
+        }
+    }
+
+    /// Measures velocity while dragged, and runs the spring when not.
+    fn animate(&self, vid: ViewId, cx: &mut Context) {
+        const STEPS: usize = 4;
+        let frame = cx.anim_interval();
+
+        let mut state = *draggable_state(vid, cx);
+        if state.touch.is_some_and(|touch| touch.claimed == Some(true)) {
+            let velocity = state.pending / frame;
+            draggable_state(vid, cx).velocity = (state.velocity + velocity) * 0.5;
+            draggable_state(vid, cx).pending = LocalOffset::zero();
+            return;
+        }
+
+        let target = match state.target {
+            Some(target) => target,
+            None => return,
+        };
+        let dt = frame / STEPS as f32;
+        for _ in 0..STEPS {
+            let force = (target - state.offset) * self.stiffness - state.velocity * self.damping;
+            state.velocity += force * dt;
+            state.offset += state.velocity * dt;
+        }
+        if (target - state.offset).length() < SETTLE_DISTANCE
+            && state.velocity.length() < SETTLE_SPEED
+        {
+            state.offset = target;
+            state.velocity = LocalOffset::zero();
+            state.target = None;
+        }
+        state.raw = state.offset;
+        cx[StateHandle::<DraggableState>::new(vid)] = state;
+    }
+}
+
+impl<V> View for Draggable<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let offset = draggable_state(vid, cx).offset;
+
+        if let Event::TouchBegin { id, position } = event {
+            if self.hittest(path, *position, cx).is_some() {
+                // Catch the child if it's still moving.
+                let state = draggable_state(vid, cx);
+                state.touch = Some(DragTouch {
+                    id: *id,
+                    moved: LocalOffset::zero(),
+                    claimed: None,
+                });
+                state.target = None;
+                state.raw = state.offset;
+                state.velocity = LocalOffset::zero();
+                state.pending = LocalOffset::zero();
+            }
+        }
+
+        path.push(0);
+        self.child
+            .process(&event.offset(-offset), path, cx, actions);
+        path.pop();
+
+        match event {
+            Event::TouchMove { id, delta, .. } => self.drag(vid, *id, *delta, cx),
+            Event::TouchEnd { id, .. } => self.end_drag(vid, *id, cx),
+            Event::Anim => self.animate(vid, cx),
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let offset = draggable_state(vid, args.cx).offset;
+        args.vger.save();
+        args.vger.translate(offset);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        path.push(0);
+        let size = self.child.layout(path, args);
+        path.pop();
+
+        draggable_state(vid, args.cx).size = size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        let offset = draggable_state(vid, cx).offset;
+        path.push(0);
+        self.child.dirty(path, xform.pre_translate(offset), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let vid = cx.view_id(path);
+        let offset = draggable_state(vid, cx).offset;
+        path.push(0);
+        let id = self.child.hittest(path, pt - offset, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for Draggable<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn card() -> impl View {
+        zstack((rectangle(), text("card"))).size([100.0, 100.0])
+    }
+
+    fn card_y(harness: &mut Harness<impl View>) -> f32 {
+        harness.find_text("card").unwrap().center().y.round()
+    }
+
+    fn settle(harness: &mut Harness<impl View>) {
+        for _ in 0..120 {
+            harness.update();
+        }
+    }
+
+    #[test]
+    fn test_rubber_band() {
+        assert_eq!(rubber_band(50.0, 0.0, 100.0, 100.0), 50.0);
+        let pulled = rubber_band(200.0, 0.0, 100.0, 100.0);
+        assert!(pulled > 100.0 && pulled < 200.0);
+        assert!(rubber_band(10_000.0, 0.0, 100.0, 100.0) < 200.0);
+        let below = rubber_band(-100.0, 0.0, 100.0, 100.0);
+        assert!((below - (100.0 - pulled)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_drag_bounds() {
+        let bounds = LocalRect::new([0.0, 0.0].into(), [0.0, 100.0].into());
+        let view = draggable(card()).bounds(bounds);
+        let mut harness = Harness::with_size(view, Size::new(400.0, 400.0));
+        let start = card_y(&mut harness);
+
+        // Pulled past the top, the card lags behind the touch.
+        let from = Point::new(200.0, start);
+        harness.drag(from, Point::new(200.0, start + 200.0));
+        let released = card_y(&mut harness);
+        assert!(released > start + 100.0 && released < start + 200.0);
+
+        // Then springs back inside its bounds.
+        settle(&mut harness);
+        assert_eq!(card_y(&mut harness), start + 100.0);
+
+        // Sideways drags don't move it.
+        let from = Point::new(200.0, start + 100.0);
+        harness.drag(from, Point::new(300.0, start + 100.0));
+        settle(&mut harness);
+        assert_eq!(card_y(&mut harness), start + 100.0);
+    }
+
+    #[test]
+    fn test_snap_points() {
+        let snapped = Rc::new(Cell::new(None));
+        let s = snapped.clone();
+        let view = draggable(card())
+            .bounds(LocalRect::new([0.0, 0.0].into(), [0.0, 200.0].into()))
+            .snap_points(vec![
+                [0.0, 0.0].into(),
+                [0.0, 100.0].into(),
+                [0.0, 200.0].into(),
+            ])
+            .on_snap(move |_, i| s.set(Some(i)));
+        let mut harness = Harness::with_size(view, Size::new(400.0, 400.0));
+        let start = card_y(&mut harness);
+
+        // Drag up 70 points and hold still before letting go.
+        let position = LocalPoint::new(200.0, start);
+        harness.context().mouse_button = Some(MouseButton::Left);
+        harness.event(Event::TouchBegin { id: 0, position });
+        for i in 1..=7 {
+            harness.event(Event::TouchMove {
+                id: 0,
+                position: position + LocalOffset::new(0.0, 10.0 * i as f32),
+                delta: [0.0, 10.0].into(),
+            });
+        }
+        for _ in 0..10 {
+            harness.update();
+        }
+        harness.context().mouse_button = None;
+        harness.event(Event::TouchEnd { id: 0, position });
+
+        settle(&mut harness);
+        assert_eq!(snapped.get(), Some(1));
+        assert_eq!(card_y(&mut harness), start + 100.0);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::*;
+use std::any::Any;
+
+/// Struct for the `effect` modifier.
+pub struct EffectView<V> {
+    child: V,
+    effect: ShaderEffect,
+}
+
+impl<V> EffectView<V>
+where
+    V: View,
+{
+    pub fn new(child: V, effect: ShaderEffect) -> Self {
+        Self { child, effect }
+    }
+}
+
+impl<V> View for EffectView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Anim = event {
+            // Advance the effect's clock. This keeps the UI redrawing
+            // while the effect is on screen.
+            let id = cx.view_id(path);
+            cx.init_state(id, &|| 0.0f32);
+            let dt = cx.anim_interval();
+            cx[StateHandle::<f32>::new(id)] += dt;
+        }
+
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+
+        let id = args.cx.view_id(path);
+        args.cx.init_state(id, &|| 0.0f32);
+        let time = args.cx[StateHandle::<f32>::new(id)];
+
+        let rect = args.cx.get_layout(path).rect;
+        let world_rect = args.vger.current_transform().outer_transformed_rect(&rect);
+
+        args.cx.effect_requests.push(EffectRequest {
+            effect: self.effect.clone(),
+            rect: world_rect,
+            time,
+            marker: args.cx.gpu_markers.current(),
+        });
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for EffectView<V> {}
@@ -60,7 +60,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         map.push(cx.view_id(path));
         path.push(0);
-        (self.func)(cx.init_env(&S::default), cx).gc(path, cx, map);
+        (self.func)(cx.init_env(&S::default), cx).gc_view(path, cx, map);
         path.pop();
     }
 
@@ -170,7 +170,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         let old = cx.set_env(&self.env_val);
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
         old.and_then(|s| cx.set_env(&s));
     }
@@ -0,0 +1,265 @@
+use crate::*;
+use std::any::Any;
+
+/// Size of the swatch of a lone `eyedropper`.
+const EYEDROPPER_SIZE: f32 = 30.0;
+
+/// Pixels shown on each side of the cursor in the loupe.
+const LOUPE_RADIUS: u32 = 5;
+
+/// Size of each magnified pixel.
+const LOUPE_CELL: f32 = 8.0;
+
+/// Offset of the loupe from the cursor, so it doesn't cover the pixels
+/// being sampled.
+const LOUPE_OFFSET: f32 = 16.0;
+
+#[derive(Clone, Default)]
+pub(crate) struct EyedropperState {
+    /// Is the next click picking a color?
+    pub(crate) picking: bool,
+
+    /// Cursor position while picking.
+    pos: Option<LocalPoint>,
+
+    /// Where the last capture was requested, in window coordinates.
+    requested: Option<WorldPoint>,
+
+    /// Generation of the last capture drawn, to redraw when a new one
+    /// arrives.
+    seen: u64,
+}
+
+/// Struct for `eyedropper`. The color picker keeps the state itself, to
+/// see when it's picking.
+pub(crate) struct Eyedropper<B, S> {
+    pub(crate) color: B,
+    pub(crate) state: S,
+
+    /// Width and height of the swatch.
+    pub(crate) size: f32,
+}
+
+impl<B, S> Eyedropper<B, S>
+where
+    S: Binding<EyedropperState>,
+{
+    fn stop(&self, cx: &mut Context) {
+        let s = self.state.get_mut(cx);
+        s.picking = false;
+        s.pos = None;
+        s.requested = None;
+        // Clear the loupe, wherever it was.
+        cx.full_redraw = true;
+    }
+
+    fn draw_loupe(
+        &self,
+        pos: LocalPoint,
+        capture: &WindowCapture,
+        vger: &mut Painter,
+        theme: &Theme,
+    ) {
+        let r = capture.radius() as i32;
+        let size = (2 * r + 1) as f32 * LOUPE_CELL;
+        let origin = pos + LocalOffset::new(LOUPE_OFFSET, LOUPE_OFFSET);
+        let frame = LocalRect::new(origin, [size, size].into());
+
+        let background = vger.color_paint(theme.background);
+        vger.fill_rect(frame, 0.0, background);
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if let Some(color) = capture.color_at(dx, dy) {
+                    // Capture rows run down, local y runs up.
+                    let cell = LocalRect::new(
+                        origin
+                            + LocalOffset::new(
+                                (dx + r) as f32 * LOUPE_CELL,
+                                (r - dy) as f32 * LOUPE_CELL,
+                            ),
+                        [LOUPE_CELL, LOUPE_CELL].into(),
+                    );
+                    let paint = vger.color_paint(color);
+                    vger.fill_rect(cell, 0.0, paint);
+                }
+            }
+        }
+
+        let paint = vger.color_paint(theme.text);
+        let center = origin + LocalOffset::new(r as f32 * LOUPE_CELL, r as f32 * LOUPE_CELL);
+        vger.stroke_rect(
+            center,
+            center + LocalOffset::new(LOUPE_CELL, LOUPE_CELL),
+            0.0,
+            1.0,
+            paint,
+        );
+        vger.stroke_rect(frame.min(), frame.max(), 0.0, 1.0, paint);
+    }
+}
+
+impl<B, S> View for Eyedropper<B, S>
+where
+    B: Binding<Color>,
+    S: Binding<EyedropperState>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        _actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let s = self.state;
+        let picking = s.get(cx).picking;
+        match event {
+            Event::Anim if picking => {
+                let generation = cx.window_capture().map_or(0, |c| c.generation);
+                if generation != s.get(cx).seen {
+                    s.get_mut(cx).seen = generation;
+                }
+            }
+            Event::TouchBegin { position, .. } => {
+                if picking {
+                    if let Some(color) = cx.window_capture().and_then(|c| c.color()) {
+                        self.color.with_mut(cx, |c| *c = color);
+                    }
+                    self.stop(cx);
+                } else if cx.get_layout(path).rect.contains(*position) && cx.can_capture_window() {
+                    let s = s.get_mut(cx);
+                    s.picking = true;
+                    s.pos = Some(*position);
+                }
+            }
+            Event::TouchMove { position, .. } if picking => {
+                s.get_mut(cx).pos = Some(*position);
+            }
+            Event::Key(k) if picking && k.pressed() == Some(Key::Escape) => self.stop(cx),
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        let theme = args.cx.theme();
+        let s = self.state;
+        let vger = &mut args.vger;
+
+        let paint = vger.color_paint(*self.color.get(args.cx));
+        vger.fill_rect(rect, 4.0, paint);
+        let state = s.get(args.cx).clone();
+        let border = vger.color_paint(if state.picking {
+            theme.accent
+        } else {
+            theme.control_background
+        });
+        vger.stroke_rect(rect.min(), rect.max(), 4.0, 1.0, border);
+
+        let pos = match state.pos {
+            Some(pos) if state.picking => pos,
+            _ => return,
+        };
+        // Writes while drawing don't cause a redraw.
+        let world = vger.current_transform().transform_point(pos);
+        if state.requested != Some(world) {
+            args.cx.request_capture(world, LOUPE_RADIUS);
+            s.get_mut(args.cx).requested = Some(world);
+        }
+        if let Some(capture) = args.cx.window_capture().cloned() {
+            self.draw_loupe(pos, &capture, vger, &theme);
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let sz = LocalSize::new(self.size, self.size);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        sz
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if cx.get_layout(path).rect.contains(pt) {
+            Some(cx.view_id(path))
+        } else {
+            None
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let aid = cx.view_id(path).access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::Button);
+        builder.set_name("eyedropper");
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl<B, S> private::Sealed for Eyedropper<B, S> {}
+
+/// A swatch showing `color`. Clicking it starts picking: a loupe follows
+/// the cursor showing the magnified pixels under it, and the next click
+/// sets `color` to the pixel under the cursor. Escape cancels.
+///
+/// Colors can only be picked from the app's own window, not from the rest
+/// of the screen, on GPUs which allow reading back frames. Elsewhere
+/// clicking the swatch does nothing. See `Context::can_capture_window`.
+/// `color_picker` has one in its popover.
+pub fn eyedropper(color: impl Binding<Color>) -> impl View {
+    state(EyedropperState::default, move |s, _| Eyedropper {
+        color,
+        state: s,
+        size: EYEDROPPER_SIZE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_eyedropper_pick() {
+        let view = state(
+            || Color::WHITE,
+            |color, cx| vstack((eyedropper(color), text(&format!("red {}", cx[color].r)))),
+        );
+        let mut harness = Harness::new(view);
+        let swatch = harness.find_text("red 1").unwrap().center();
+        let swatch = Point::new(swatch.x, swatch.y + 24.0);
+
+        // Without capture support, clicking doesn't start picking.
+        harness.click_at(swatch);
+        harness.context().capture = Some(WindowCapture {
+            center: WorldPoint::zero(),
+            radius: 0,
+            colors: vec![Some(Color::gray(0.25))],
+            generation: 1,
+        });
+        harness.click_at(Point::new(10.0, 10.0));
+        harness.expect_text("red 1");
+
+        harness.context().capture_supported = true;
+        harness.click_at(swatch);
+        harness.key(Key::Escape);
+        harness.click_at(Point::new(10.0, 10.0));
+        harness.expect_text("red 1");
+
+        harness.click_at(swatch);
+        harness.click_at(Point::new(10.0, 10.0));
+        harness.expect_text("red 0.25");
+    }
+}
@@ -9,7 +9,7 @@ pub struct Focus<F> {
 impl<V, F> View for Focus<F>
 where
     V: View,
-    F: Fn(bool) -> V + 'static,
+    F: Fn(ViewId, bool) -> V + 'static,
 {
     fn process(
         &self,
@@ -20,51 +20,81 @@ where
     ) {
         let vid = cx.view_id(path);
         match &event {
-            Event::TouchBegin { id: _, position } => {
-                if self.hittest(path, *position, cx).is_some() {
-                    cx.focused_id = Some(vid);
-                    cx.set_dirty();
-                }
+            Event::TouchBegin { id: _, position }
+                if self.hittest(path, *position, cx).is_some() =>
+            {
+                cx.focused_id = Some(vid);
+                cx.focus_visible = false;
+                cx.set_dirty();
             }
-            Event::Key(Key::Escape) => {
-                if cx.focused_id == Some(vid) {
-                    cx.focused_id = None;
-                    cx.set_dirty();
-                }
+            Event::Key(k) if k.pressed() == Some(Key::Escape) && cx.focused_id == Some(vid) => {
+                cx.focused_id = None;
+                cx.set_dirty();
             }
             _ => (),
         }
         path.push(0);
-        (self.func)(Some(vid) == cx.focused_id).process(event, path, cx, actions);
+        (self.func)(vid, Some(vid) == cx.focused_id).process(event, path, cx, actions);
         path.pop();
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let id = args.cx.view_id(path);
+        args.cx.focus_chain.push(id);
+        let has_focus = Some(id) == args.cx.focused_id;
+
         path.push(0);
-        (self.func)(Some(id) == args.cx.focused_id).draw(path, args);
+        (self.func)(id, has_focus).draw(path, args);
         path.pop();
+
+        if has_focus && args.cx.focus_visible {
+            let ring = args.cx.init_env(&FocusRing::default);
+            if ring.visible {
+                let rect = args
+                    .cx
+                    .get_layout(path)
+                    .rect
+                    .inflate(ring.padding, ring.padding);
+                let color = ring.color.unwrap_or(args.cx.theme().accent);
+                let paint = args.vger.color_paint(color);
+                args.vger
+                    .stroke_rect(rect.min(), rect.max(), ring.radius, ring.width, paint);
+            }
+        }
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
         let id = args.cx.view_id(path);
         path.push(0);
-        let sz = (self.func)(Some(id) == args.cx.focused_id).layout(path, args);
+        let sz = (self.func)(id, Some(id) == args.cx.focused_id).layout(path, args);
         path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
         sz
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
         let id = cx.view_id(path);
         path.push(0);
-        (self.func)(Some(id) == cx.focused_id).dirty(path, xform, cx);
+        (self.func)(id, Some(id) == cx.focused_id).dirty(path, xform, cx);
         path.pop();
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
         let id = cx.view_id(path);
+        if cx.text_probe.is_some() {
+            // Without drawing, this is how headless runs find focusable views.
+            cx.focus_chain.push(id);
+        }
         path.push(0);
-        let vid = (self.func)(Some(id) == cx.focused_id).hittest(path, pt, cx);
+        let vid = (self.func)(id, Some(id) == cx.focused_id).hittest(path, pt, cx);
         path.pop();
         vid
     }
@@ -72,14 +102,14 @@ where
     fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
         let id = cx.view_id(path);
         path.push(0);
-        (self.func)(Some(id) == cx.focused_id).commands(path, cx, cmds);
+        (self.func)(id, Some(id) == cx.focused_id).commands(path, cx, cmds);
         path.pop();
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         let id = cx.view_id(path);
         path.push(0);
-        (self.func)(Some(id) == cx.focused_id).gc(path, cx, map);
+        (self.func)(id, Some(id) == cx.focused_id).gc_view(path, cx, map);
         path.pop();
     }
 
@@ -91,16 +121,102 @@ where
     ) -> Option<accesskit::NodeId> {
         let id = cx.view_id(path);
         path.push(0);
-        let node_id = (self.func)(Some(id) == cx.focused_id).access(path, cx, nodes);
+        let node_id = (self.func)(id, Some(id) == cx.focused_id).access(path, cx, nodes);
         path.pop();
+        if let Some(node_id) = node_id {
+            cx.access.focusable.insert(node_id, id);
+        }
         node_id
     }
 }
 
 impl<F> private::Sealed for Focus<F> {}
 
+/// Appearance of the ring drawn around a view when it receives focus
+/// from the keyboard. Set it for a subtree with `.env(FocusRing { .. })`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusRing {
+    /// Color of the ring. `None` uses the theme's accent color.
+    pub color: Option<Color>,
+
+    /// Stroke width.
+    pub width: f32,
+
+    /// Corner radius.
+    pub radius: f32,
+
+    /// Distance between the view and the ring.
+    pub padding: f32,
+
+    /// Draw the ring at all?
+    pub visible: bool,
+}
+
+impl Default for FocusRing {
+    fn default() -> Self {
+        Self {
+            color: None,
+            width: 2.0,
+            radius: 4.0,
+            padding: 2.0,
+            visible: true,
+        }
+    }
+}
+
 /// Calls calls a function with true if the view subtree returned
 /// by the function has the keyboard focus.
+///
+/// Focusable views take part in Tab and Shift+Tab traversal, in the
+/// order they're drawn.
 pub fn focus<V: View, F: Fn(bool) -> V + 'static>(f: F) -> impl View {
+    Focus {
+        func: move |_, has_focus| f(has_focus),
+    }
+}
+
+/// Like `focus`, but also passes the id of the focusable view, which
+/// can be used with `Context::request_focus`.
+pub fn focus_id<V: View, F: Fn(ViewId, bool) -> V + 'static>(f: F) -> impl View {
     Focus { func: f }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_focus_traversal() {
+        let mut cx = Context::new();
+        let ids: Vec<ViewId> = (1..4).map(|id| ViewId { id }).collect();
+        cx.focus_chain = ids.clone();
+
+        cx.process(&EmptyView {}, &Event::Key(KeyEvent::press(Key::Tab)));
+        assert_eq!(cx.focused_view(), Some(ids[0]));
+
+        cx.focus_next();
+        cx.focus_next();
+        assert_eq!(cx.focused_view(), Some(ids[2]));
+
+        // Wraps around.
+        cx.focus_next();
+        assert_eq!(cx.focused_view(), Some(ids[0]));
+
+        let shift = KeyboardModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        cx.process(
+            &EmptyView {},
+            &Event::Key(KeyEvent::press(Key::Tab).mods(shift)),
+        );
+        assert_eq!(cx.focused_view(), Some(ids[2]));
+
+        cx.request_focus(ids[1]);
+        assert_eq!(cx.focused_view(), Some(ids[1]));
+
+        cx.clear_focus();
+        assert_eq!(cx.focused_view(), None);
+    }
+}
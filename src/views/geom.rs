@@ -1,5 +1,6 @@
 use crate::*;
 use std::any::Any;
+use std::rc::Rc;
 
 /// Struct for the `geom` modifier.
 pub struct Geom<V, F> {
@@ -70,7 +71,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         map.push(cx.view_id(path));
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -98,3 +99,298 @@ where
         Self { child, func: f }
     }
 }
+
+/// Struct for `geometry_reader`.
+pub struct GeometryReader<F> {
+    func: F,
+}
+
+impl<V, F> GeometryReader<F>
+where
+    V: View,
+    F: Fn(Size, Point, &Context) -> V + 'static,
+{
+    /// The child for the size given in the last layout, and the position
+    /// found after it.
+    fn child(&self, path: &IdPath, cx: &Context) -> V {
+        let position = cx.geometry_positions.get(path).copied();
+        let position = position.unwrap_or_default();
+        (self.func)(
+            cx.get_layout(path).rect.size.into(),
+            Point::new(position.x, position.y),
+            cx,
+        )
+    }
+}
+
+impl<V, F> View for GeometryReader<F>
+where
+    V: View,
+    F: Fn(Size, Point, &Context) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let child = self.child(path, cx);
+        path.push(0);
+        child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let child = self.child(path, args.cx);
+        path.push(0);
+        child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        // Take all the space on offer, and build the child for it.
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        let child = self.child(path, args.cx);
+        path.push(0);
+        child.layout(path, args);
+        path.pop();
+        args.sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        // Where views end up is only known once the whole tree is laid out,
+        // so if the reader moved, build the child again for where it is.
+        let position = xform.transform_point(LocalPoint::zero()) + cx.root_offset.cast_unit();
+        if cx.geometry_positions.insert(path.clone(), position) != Some(position) {
+            cx.layout_callbacks.push(Box::new(|cx| cx.set_dirty()));
+        }
+
+        let child = self.child(path, cx);
+        path.push(0);
+        child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let child = self.child(path, cx);
+        path.push(0);
+        let id = child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let child = self.child(path, cx);
+        path.push(0);
+        child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        let child = self.child(path, cx);
+        path.push(0);
+        child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let child = self.child(path, cx);
+        path.push(0);
+        let node_id = child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<F> private::Sealed for GeometryReader<F> {}
+
+/// Builds a view for the size it's given in layout and where it is in the
+/// window. The reader takes all the space it's offered, like `spacer`, and
+/// calls `f` with its size and the window position of its origin whenever
+/// it's laid out, drawn or handles an event.
+///
+/// Before the first layout the size is zero. Positions are found after
+/// layout, so when the reader moves it's built again in another update.
+/// Scrolling and transforms are included.
+pub fn geometry_reader<V: View, F: Fn(Size, Point, &Context) -> V + 'static>(
+    f: F,
+) -> GeometryReader<F> {
+    GeometryReader { func: f }
+}
+
+/// Struct for the `on_size_change` modifier.
+pub struct OnSizeChange<V, F> {
+    child: V,
+    func: Rc<F>,
+}
+
+impl<V, F> OnSizeChange<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, Size) + 'static,
+{
+    pub fn new(child: V, f: F) -> Self {
+        Self {
+            child,
+            func: Rc::new(f),
+        }
+    }
+}
+
+impl<V, F> View for OnSizeChange<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, Size) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        let previous = args.cx.layout.get(path).map(|b| b.rect.size);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        // State can't change during layout, so report the size after.
+        if previous != Some(sz) {
+            let func = self.func.clone();
+            args.cx
+                .layout_callbacks
+                .push(Box::new(move |cx| (func)(cx, sz.into())));
+        }
+
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, F> private::Sealed for OnSizeChange<V, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn adaptive() -> impl View {
+        geometry_reader(|size, _, _| {
+            if size.width > 400.0 {
+                text("wide")
+            } else {
+                text("narrow")
+            }
+        })
+    }
+
+    #[test]
+    fn test_geometry_reader() {
+        let mut harness = Harness::with_size(adaptive(), Size::new(300.0, 200.0));
+        harness.expect_text("narrow");
+
+        let mut harness = Harness::with_size(adaptive(), Size::new(500.0, 200.0));
+        harness.expect_text("wide");
+    }
+
+    #[test]
+    fn test_geometry_reader_position() {
+        let view = hstack((
+            rectangle().size([100.0, 50.0]),
+            geometry_reader(|_, position, _| text(&format!("at {}", position.x))),
+        ));
+        let mut harness = Harness::with_size(view, Size::new(300.0, 50.0));
+        harness.update();
+        harness.expect_text("at 100");
+    }
+
+    #[test]
+    fn test_on_size_change() {
+        let view = state(
+            || 0.0,
+            |width, cx| {
+                vstack((
+                    text(&format!("width {}", cx[width])),
+                    rectangle().on_size_change(move |cx, size| cx[width] = size.width),
+                ))
+            },
+        );
+        let mut harness = Harness::with_size(view, Size::new(300.0, 200.0));
+        harness.update();
+        harness.expect_text("width 300");
+    }
+}
@@ -0,0 +1,114 @@
+use crate::*;
+
+const DRAG_HANDLE_WIDTH: f32 = 16.0;
+const DRAG_HANDLE_HEIGHT: f32 = 24.0;
+const RESIZE_GRIP_SIZE: f32 = 16.0;
+
+const GRIP_DOT_SPACING: f32 = 5.0;
+const GRIP_DOT_RADIUS: f32 = 1.5;
+const GRIP_LINE_SPACING: f32 = 4.0;
+
+/// Color for the marks on grips, quieter than text.
+fn grip_color(cx: &Context) -> Color {
+    let mut color = cx.theme().text;
+    color.a *= 0.4;
+    color
+}
+
+/// A grip for moving things around, like a panel or floating window.
+///
+/// `f` gets how far the pointer moved since it was last called, with
+/// `GestureState::Began` when pressed and `Ended` when released. The
+/// handle keeps the pointer until it's released, even once it's moved off
+/// the handle. Shows an open hand cursor, closed while dragging.
+pub fn drag_handle(f: impl Fn(&mut Context, LocalOffset, GestureState) + 'static) -> impl View {
+    painter_canvas(|cx, rect, vger| {
+        let paint = vger.color_paint(grip_color(cx));
+        let c = rect.center();
+        for column in [-0.5, 0.5] {
+            for row in [-1.0, 0.0, 1.0] {
+                vger.fill_circle(
+                    [
+                        c.x + column * GRIP_DOT_SPACING,
+                        c.y + row * GRIP_DOT_SPACING,
+                    ],
+                    GRIP_DOT_RADIUS,
+                    paint,
+                );
+            }
+        }
+    })
+    .size([DRAG_HANDLE_WIDTH, DRAG_HANDLE_HEIGHT])
+    .drag(move |cx, delta, state, _| {
+        cx.set_cursor(match state {
+            GestureState::Ended => CursorIcon::Grab,
+            _ => CursorIcon::Grabbing,
+        });
+        f(cx, delta, state)
+    })
+    .cursor(CursorIcon::Grab)
+}
+
+/// A grip for the bottom right corner of something resizable, like a
+/// card or floating window.
+///
+/// `f` gets how much the pointer has grown the size since it was last
+/// called: wider to the right and taller downwards. Clamping the size is
+/// left to `f`. Like `drag_handle`, the grip keeps the pointer until it's
+/// released.
+pub fn resize_grip(f: impl Fn(&mut Context, LocalSize, GestureState) + 'static) -> impl View {
+    painter_canvas(|cx, rect, vger| {
+        let paint = vger.color_paint(grip_color(cx));
+        for i in 1..=3 {
+            let d = i as f32 * GRIP_LINE_SPACING;
+            vger.stroke_segment(
+                [rect.max_x() - d, rect.min_y()],
+                [rect.max_x(), rect.min_y() + d],
+                1.0,
+                paint,
+            );
+        }
+    })
+    .size([RESIZE_GRIP_SIZE, RESIZE_GRIP_SIZE])
+    .drag(move |cx, delta, state, _| f(cx, LocalSize::new(delta.x, -delta.y), state))
+    .cursor(CursorIcon::ResizeDiagonal)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_drag_handle() {
+        let moved = std::rc::Rc::new(std::cell::Cell::new(LocalOffset::zero()));
+        let m = moved.clone();
+        let mut h = Harness::with_size(
+            drag_handle(move |_, delta, _| m.set(m.get() + delta)),
+            Size::new(DRAG_HANDLE_WIDTH, DRAG_HANDLE_HEIGHT),
+        );
+
+        h.hover(Point::new(8.0, 12.0));
+        assert_eq!(h.context().cursor(), CursorIcon::Grab);
+
+        // The handle keeps following once the pointer leaves it.
+        h.drag(Point::new(8.0, 12.0), Point::new(48.0, 32.0));
+        assert_eq!(moved.get(), LocalOffset::new(40.0, 20.0));
+        assert_eq!(h.context().cursor(), CursorIcon::Grab);
+    }
+
+    #[test]
+    fn test_resize_grip() {
+        let mut h = Harness::with_size(
+            state(
+                || LocalSize::new(100.0, 100.0),
+                |size, _| resize_grip(move |cx, delta, _| cx[size] += delta),
+            ),
+            Size::new(RESIZE_GRIP_SIZE, RESIZE_GRIP_SIZE),
+        );
+
+        // Dragging right and down grows both ways.
+        h.drag(Point::new(8.0, 8.0), Point::new(18.0, -2.0));
+        assert_eq!(h.states::<LocalSize>(), vec![LocalSize::new(110.0, 110.0)]);
+    }
+}
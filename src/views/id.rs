@@ -0,0 +1,270 @@
+use crate::*;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+
+/// Struct for the `id` modifier.
+pub struct IdView<V> {
+    child: V,
+    key: u64,
+}
+
+impl<V> IdView<V>
+where
+    V: View,
+{
+    pub fn new(child: V, key: u64) -> Self {
+        Self { child, key }
+    }
+}
+
+impl<V> View for IdView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(self.key);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(self.key);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(self.key);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(self.key);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(self.key);
+        let hit_id = self.child.hittest(path, pt, cx);
+        path.pop();
+        hit_id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(self.key);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(self.key);
+        let scope = cx.keyed_ids.enter(self.key, path);
+        self.child.gc_view(path, cx, map);
+        cx.keyed_ids.leave(scope);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(self.key);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.child.is_flexible()
+    }
+}
+
+impl<V> private::Sealed for IdView<V> {}
+
+/// Where each view with an `id` was, so their state can follow them when
+/// they move.
+#[derive(Default)]
+pub(crate) struct KeyedIds {
+    /// Ids seen during the update's gc, with where they were, or None
+    /// outside of it.
+    seen: Option<Vec<(u64, IdPath)>>,
+
+    /// The id of the view with an id around the one being visited, scoping
+    /// the ids inside it.
+    scope: u64,
+
+    /// Where each id was after the last update.
+    paths: HashMap<u64, IdPath>,
+
+    /// Ids used by more than one view, already warned about.
+    pub(crate) duplicates: HashSet<u64>,
+}
+
+impl KeyedIds {
+    /// Notes a view with `key` at `path`, returning the scope to restore.
+    fn enter(&mut self, key: u64, path: &IdPath) -> u64 {
+        let scoped = hh(&(self.scope, key));
+        if let Some(seen) = &mut self.seen {
+            seen.push((scoped, path.clone()));
+        }
+        std::mem::replace(&mut self.scope, scoped)
+    }
+
+    fn leave(&mut self, scope: u64) {
+        self.scope = scope;
+    }
+}
+
+impl Context {
+    /// Starts noting where views with ids are, for `migrate_keyed_ids`.
+    pub(crate) fn begin_keyed_ids(&mut self) {
+        self.keyed_ids.seen = Some(vec![]);
+        self.keyed_ids.scope = 0;
+    }
+
+    /// Moves the ViewIds of views with ids which are somewhere else in the
+    /// tree since the last update to where they are now, so their state,
+    /// focus and so on go with them. Returns true if any moved.
+    pub(crate) fn migrate_keyed_ids(&mut self) -> bool {
+        let seen = self.keyed_ids.seen.take().unwrap_or_default();
+
+        let mut found = HashMap::<u64, Vec<IdPath>>::new();
+        for (key, path) in seen {
+            found.entry(key).or_default().push(path);
+        }
+
+        let mut moved = false;
+        let mut paths = HashMap::new();
+        for (key, mut at) in found {
+            if at.len() > 1 {
+                // Leave them where they are, since there's no telling which
+                // is which.
+                if self.keyed_ids.duplicates.insert(key) {
+                    at.sort();
+                    println!("rui: views at {:?} have the same id", at);
+                }
+                continue;
+            }
+            let path = at.pop().unwrap();
+            if let Some(old) = self.keyed_ids.paths.get(&key).cloned() {
+                if old != path {
+                    self.move_view_ids(&old, &path);
+                    moved = true;
+                }
+            }
+            paths.insert(key, path);
+        }
+        self.keyed_ids.paths = paths;
+        moved
+    }
+
+    /// Gives the views under `to` the ViewIds of those under `from`.
+    fn move_view_ids(&mut self, from: &IdPath, to: &IdPath) {
+        let inside: Vec<_> = self
+            .view_ids
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .cloned()
+            .collect();
+        for path in inside {
+            let id = self.view_ids.remove(&path).unwrap();
+            let mut new_path = to.clone();
+            new_path.extend_from_slice(&path[from.len()..]);
+            self.view_ids.insert(new_path, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn counter(name: &'static str) -> impl View {
+        state(
+            || 0,
+            move |count, cx| button(format!("{} {}", name, cx[count]), move |cx| cx[count] += 1),
+        )
+    }
+
+    #[test]
+    fn test_id_reorder() {
+        let view = state(
+            || false,
+            |flipped, cx| {
+                let (a, b) = (counter("a").id("a"), counter("b").id("b"));
+                let flip = button("flip", move |cx| cx[flipped] = !cx[flipped]);
+                if cx[flipped] {
+                    vstack((b, a, flip))
+                } else {
+                    vstack((a, b, flip))
+                }
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("a 0");
+        h.click("a 1");
+        h.click("b 0");
+        h.expect_text("a 2");
+        h.expect_text("b 1");
+
+        // The counts stay with their views, instead of their places.
+        h.click("flip");
+        h.expect_text("a 2");
+        h.expect_text("b 1");
+        h.click("a 2");
+        h.expect_text("a 3");
+        h.click("flip");
+        h.expect_text("a 3");
+        h.expect_text("b 1");
+    }
+
+    #[test]
+    fn test_id_reparent() {
+        let view = state(
+            || false,
+            |row, cx| {
+                let c = counter("count").id(7);
+                let toggle = button("move", move |cx| cx[row] = !cx[row]);
+                if cx[row] {
+                    any_view(hstack((text("row"), c, toggle)))
+                } else {
+                    any_view(vstack((c, toggle)))
+                }
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("count 0");
+        h.click("move");
+        h.expect_text("row");
+        h.expect_text("count 1");
+        h.click("count 1");
+        h.click("move");
+        h.expect_text("count 2");
+
+        // Only the moved state is kept.
+        assert_eq!(h.states::<i32>(), vec![2]);
+    }
+
+    #[test]
+    fn test_id_scopes() {
+        // Ids need only be unique among views with the same id around them.
+        let list = |name| vstack((counter(name).id(0), counter(name).id(1))).id(name);
+        let mut h = Harness::new(hstack((list("left"), list("right"))));
+        assert!(h.context().keyed_ids.duplicates.is_empty());
+
+        let mut h = Harness::new(vstack((counter("a").id(0), counter("b").id(0))));
+        assert_eq!(h.context().keyed_ids.duplicates.len(), 1);
+    }
+}
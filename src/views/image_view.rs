@@ -0,0 +1,231 @@
+use crate::*;
+
+#[derive(Clone, Default)]
+pub struct ImageOptions {
+    mode: ContentMode,
+}
+
+pub trait ImageMods: View + Sized {
+    fn content_mode(self, mode: ContentMode) -> Self;
+}
+
+/// The decoded image for an `ImageView`, and which source it came from.
+struct ImageState {
+    key: u64,
+    image: Option<ImageData>,
+}
+
+/// Struct for `image`.
+struct ImageView {
+    source: ImageSource,
+    opts: ImageOptions,
+}
+
+impl ImageView {
+    /// Decodes the image the first time it's needed, and again if the
+    /// source changes.
+    fn image(&self, path: &IdPath, cx: &mut Context) -> Option<ImageData> {
+        let id = cx.view_id(path);
+        let key = self.source.key();
+        let handle = StateHandle::<ImageState>::new(id);
+        if !cx.state_map.contains_key(&id) || cx[handle].key != key {
+            let image = self.source.load_for_view();
+            cx.set_state(id, ImageState { key, image });
+        }
+        cx[handle].image.clone()
+    }
+}
+
+impl View for ImageView {
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let image = match self.image(path, args.cx) {
+            Some(image) => image,
+            None => return,
+        };
+
+        let rect = args.cx.get_layout(path).rect;
+        let (dest, uv) = content_rects(self.opts.mode, image.size(), rect);
+        if dest.is_empty() {
+            return;
+        }
+
+        if !args.cx.is_damaged(&dest, &args.vger.current_transform()) {
+            return;
+        }
+
+        let world_rect = args.vger.current_transform().outer_transformed_rect(&dest);
+        let request = ImageRequest {
+            image,
+            rect: world_rect,
+            uv,
+            clip: args.cx.current_clip(),
+            marker: args.cx.gpu_markers.current(),
+            mask: None,
+            gradient: None,
+        };
+        args.cx.draw_image(args.vger, request);
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        args.sz
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if cx.get_layout(path).rect.contains(pt) {
+            Some(cx.view_id(path))
+        } else {
+            None
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let aid = cx.view_id(path).access_id();
+        let builder = accesskit::NodeBuilder::new(accesskit::Role::Image);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl private::Sealed for ImageView {}
+
+/// Shows a PNG or JPEG, from a path, encoded bytes or `ImageData`. The
+/// image expands to fill available space, and is scaled according to its
+/// `content_mode`.
+///
+/// The image is decoded on the main thread the first time it's drawn. For
+/// large images, or files which may be slow to read, use `async_image`.
+pub fn image(source: impl Into<ImageSource>) -> impl ImageMods {
+    let source = source.into();
+    modview(move |opts: ImageOptions, _| ImageView {
+        source: source.clone(),
+        opts,
+    })
+}
+
+/// Like `image`, but reads and decodes on a background thread, showing
+/// `placeholder` until it's ready. The image is loaded once, so changing
+/// `source` later has no effect.
+pub fn async_image(
+    source: impl Into<ImageSource>,
+    placeholder: impl View + Clone,
+) -> impl ImageMods {
+    let source = source.into();
+    modview(move |opts: ImageOptions, _| {
+        let source = source.clone();
+        suspense(
+            move || {
+                let source = source.clone();
+                let opts = opts.clone();
+                load(
+                    move || {
+                        let source = source.clone();
+                        async move { source.load() }
+                    },
+                    move |image: &ImageData, _| ImageView {
+                        source: image.clone().into(),
+                        opts: opts.clone(),
+                    },
+                )
+            },
+            placeholder.clone(),
+        )
+    })
+}
+
+impl<F> ImageMods for ModView<ImageOptions, F>
+where
+    ModView<ImageOptions, F>: View,
+{
+    fn content_mode(self, mode: ContentMode) -> Self {
+        let mut opts = self.value;
+        opts.mode = mode;
+        ModView {
+            func: self.func,
+            value: opts,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "images"))]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn red_png() -> Vec<u8> {
+        let mut png = vec![];
+        ::image::RgbaImage::from_pixel(4, 2, ::image::Rgba([255, 0, 0, 255]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                ::image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_image_tap() {
+        let taps = Rc::new(Cell::new(0));
+        let t = taps.clone();
+        let view = image(red_png())
+            .content_mode(ContentMode::Fill)
+            .corner_radius(4.0)
+            .tap(move |_| t.set(t.get() + 1));
+        let mut harness = Harness::with_size(view, Size::new(100.0, 50.0));
+        harness.click_at(Point::new(50.0, 25.0));
+        assert_eq!(taps.get(), 1);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_image_draw_order() {
+        // The square is drawn after the image, so it goes over it.
+        let view = zstack((
+            image(red_png()).content_mode(ContentMode::Fill),
+            rectangle()
+                .color(Color::new(0.0, 0.0, 1.0, 1.0))
+                .size([10.0, 10.0]),
+        ));
+        let image = test_render(&view, Size::new(40.0, 20.0));
+        assert_eq!(image.get_pixel(5, 15).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(35, 2).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_async_image() {
+        use std::time::{Duration, Instant};
+
+        let taps = Rc::new(Cell::new(0));
+        let t = taps.clone();
+        let view = async_image(red_png(), text("loading")).tap(move |_| t.set(t.get() + 1));
+        let mut harness = Harness::with_size(view, Size::new(100.0, 50.0));
+        harness.expect_text("loading");
+
+        let start = Instant::now();
+        while harness.has_text("loading") {
+            assert!(start.elapsed() < Duration::from_secs(1), "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+            harness.update();
+        }
+        harness.click_at(Point::new(50.0, 25.0));
+        assert_eq!(taps.get(), 1);
+    }
+}
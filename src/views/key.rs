@@ -10,7 +10,7 @@ pub struct KeyView<V, F> {
 impl<V, F, A> KeyView<V, F>
 where
     V: View,
-    F: Fn(&mut Context, Key) -> A + 'static,
+    F: Fn(&mut Context, KeyEvent) -> A + 'static,
 {
     pub fn new(v: V, f: F) -> Self {
         KeyView { child: v, func: f }
@@ -20,7 +20,7 @@ where
 impl<V, F, A> View for KeyView<V, F>
 where
     V: View,
-    F: Fn(&mut Context, Key) -> A + 'static,
+    F: Fn(&mut Context, KeyEvent) -> A + 'static,
     A: 'static,
 {
     fn process(
@@ -31,7 +31,7 @@ where
         actions: &mut Vec<Box<dyn Any>>,
     ) {
         if let Event::Key(key) = &event {
-            actions.push(Box::new((self.func)(cx, key.clone())));
+            actions.push(Box::new((self.func)(cx, *key)));
         }
     }
 
@@ -69,7 +69,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
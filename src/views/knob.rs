@@ -16,15 +16,16 @@ pub fn knob(value: impl Binding<f32>) -> impl View {
                 *v = (*v + (delta.x + delta.y) / 400.0).clamp(0.0, 1.0)
             })
             .grab_cursor(),
-        canvas(move |cx, sz, vger| {
+        painter_canvas(move |cx, sz, vger| {
             let c = sz.center();
             let r = sz.width().min(sz.height()) / 2.0;
+            let theme = cx.theme();
 
-            let paint = vger.color_paint(CONTROL_BACKGROUND);
+            let paint = vger.color_paint(theme.control_background);
 
             vger.stroke_arc(c, r, 2.0, 0.0, std::f32::consts::PI, paint);
 
-            let paint = vger.color_paint(AZURE_HIGHLIGHT);
+            let paint = vger.color_paint(theme.accent);
             let a0 = lerp(*value.get(cx), THETA_MAX, THETA_MIN);
             let a1 = THETA_MAX;
 
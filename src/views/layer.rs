@@ -0,0 +1,193 @@
+use crate::*;
+use std::any::Any;
+
+/// Struct for the `shadow`, `opacity`, `blur` and `corner_radius` modifiers.
+pub struct LayerView<V> {
+    child: V,
+    style: LayerStyle,
+}
+
+impl<V> LayerView<V>
+where
+    V: View,
+{
+    pub(crate) fn new(child: V, style: LayerStyle) -> Self {
+        Self { child, style }
+    }
+}
+
+impl<V> View for LayerView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        // Touches outside the rounded corners don't reach the child.
+        if let (Event::TouchBegin { position, .. }, Some(radius)) =
+            (event, self.style.corner_radius)
+        {
+            if !rounded_rect_contains(cx.get_layout(path).rect, radius, *position) {
+                return;
+            }
+        }
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        let xform = args.vger.current_transform();
+
+        path.push(0);
+        match args.cx.begin_layer(rect, xform, &self.style) {
+            Some(mut layer) => {
+                self.child.draw(
+                    path,
+                    &mut DrawArgs {
+                        cx: args.cx,
+                        vger: &mut layer,
+                    },
+                );
+                args.cx.end_layer(args.vger, layer, xform, self.style);
+            }
+            None => match self.style.corner_radius {
+                // Without layers, clip square corners rather than none.
+                Some(_) => {
+                    args.vger.save();
+                    args.vger.scissor(rect);
+                    args.cx.push_clip(xform.outer_transformed_rect(&rect));
+                    self.child.draw(path, args);
+                    args.cx.pop_clip();
+                    args.vger.restore();
+                }
+                None => self.child.draw(path, args),
+            },
+        }
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if let Some(radius) = self.style.corner_radius {
+            if !rounded_rect_contains(cx.get_layout(path).rect, radius, pt) {
+                return None;
+            }
+        }
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for LayerView<V> {}
+
+/// Is `pt` within `rect` with corners rounded by `radius`?
+fn rounded_rect_contains(rect: LocalRect, radius: f32, pt: LocalPoint) -> bool {
+    if !rect.contains(pt) {
+        return false;
+    }
+    let radius = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    let inner = rect.inflate(-radius, -radius);
+    let nearest = LocalPoint::new(
+        pt.x.clamp(inner.min_x(), inner.max_x()),
+        pt.y.clamp(inner.min_y(), inner.max_y()),
+    );
+    (pt - nearest).length() <= radius
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_layer_passthrough() {
+        // Without a GPU, layered views draw and handle events as usual.
+        let view = state(
+            || 0,
+            |count, cx| {
+                vstack((
+                    text(&format!("count {}", cx[count])).opacity(0.5),
+                    button("add", move |cx| cx[count] += 1)
+                        .shadow(4.0, [0.0, -2.0], BLACK)
+                        .blur(1.0),
+                ))
+            },
+        );
+        let mut harness = Harness::new(view);
+        harness.click("add");
+        harness.expect_text("count 1");
+    }
+
+    #[test]
+    fn test_corner_radius_hittest() {
+        let taps = Rc::new(Cell::new(0));
+        let t = taps.clone();
+        let view = rectangle()
+            .tap(move |_| t.set(t.get() + 1))
+            .corner_radius(20.0);
+        let mut harness = Harness::with_size(view, Size::new(100.0, 100.0));
+        harness.click_at(Point::new(50.0, 50.0));
+        harness.click_at(Point::new(10.0, 50.0));
+        assert_eq!(taps.get(), 2);
+
+        // Outside the rounded corner.
+        harness.click_at(Point::new(3.0, 3.0));
+        harness.click_at(Point::new(97.0, 97.0));
+        assert_eq!(taps.get(), 2);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::*;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+type LifecycleFunc = Rc<dyn Fn(&mut Context)>;
+
+/// Which change `on_appear` or `on_disappear` is waiting for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LifecyclePhase {
+    Appear,
+    Disappear,
+}
+
+/// Struct for the `on_appear` and `on_disappear` modifiers.
+pub struct Lifecycle<V> {
+    child: V,
+    phase: LifecyclePhase,
+    func: LifecycleFunc,
+}
+
+impl<V> Lifecycle<V>
+where
+    V: View,
+{
+    pub fn new(child: V, phase: LifecyclePhase, f: impl Fn(&mut Context) + 'static) -> Self {
+        Self {
+            child,
+            phase,
+            func: Rc::new(f),
+        }
+    }
+}
+
+impl<V> View for Lifecycle<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let hit_id = self.child.hittest(path, pt, cx);
+        path.pop();
+        hit_id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let id = cx.view_id(path);
+        map.push(id);
+        cx.lifecycles
+            .visited
+            .insert(id, (self.phase, self.func.clone()));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.child.is_flexible()
+    }
+}
+
+impl<V> private::Sealed for Lifecycle<V> {}
+
+/// Callbacks of `on_appear` and `on_disappear` views.
+#[derive(Default)]
+pub(crate) struct Lifecycles {
+    /// Views reached by gc since the last update.
+    visited: HashMap<ViewId, (LifecyclePhase, LifecycleFunc)>,
+
+    /// Views which are in the tree, with their latest callbacks.
+    shown: HashMap<ViewId, (LifecyclePhase, LifecycleFunc)>,
+}
+
+impl Context {
+    /// Calls `on_disappear` for views which aren't in `keep` any more, then
+    /// `on_appear` for those which are new. Called after gc, while the
+    /// state of views which are gone can still be read.
+    pub(crate) fn run_lifecycles(&mut self, keep: &HashSet<ViewId>) {
+        let visited = std::mem::take(&mut self.lifecycles.visited);
+
+        let mut gone: Vec<_> = self
+            .lifecycles
+            .shown
+            .keys()
+            .filter(|id| !keep.contains(id))
+            .copied()
+            .collect();
+        gone.sort_by_key(|id| id.id);
+        for id in gone {
+            let (phase, func) = self.lifecycles.shown.remove(&id).unwrap();
+            if phase == LifecyclePhase::Disappear {
+                func(self);
+            }
+        }
+
+        let mut appeared = vec![];
+        for (id, hook) in visited {
+            if !keep.contains(&id) {
+                continue;
+            }
+            if self.lifecycles.shown.insert(id, hook.clone()).is_none() {
+                appeared.push((id, hook));
+            }
+        }
+        // In the order views were made, which is outside in.
+        appeared.sort_by_key(|(id, _)| id.id);
+        for (_, (phase, func)) in appeared {
+            if phase == LifecyclePhase::Appear {
+                func(self);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_lifecycle() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let l = log.clone();
+        let view = state(
+            || true,
+            move |shown, cx| {
+                let (l1, l2) = (l.clone(), l.clone());
+                vstack((
+                    button("toggle", move |cx| cx[shown] = !cx[shown]),
+                    cond(
+                        cx[shown],
+                        text("details")
+                            .on_appear(move |_| l1.borrow_mut().push("appear"))
+                            .on_disappear(move |_| l2.borrow_mut().push("disappear")),
+                        EmptyView {},
+                    ),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        assert_eq!(*log.borrow(), vec!["appear"]);
+        let ids = h.context().view_ids.len();
+
+        // Updates without changes to the tree don't call them again.
+        h.click("toggle");
+        assert_eq!(*log.borrow(), vec!["appear", "disappear"]);
+        assert!(h.context().view_ids.len() < ids);
+        h.click("toggle");
+        h.update();
+        assert_eq!(*log.borrow(), vec!["appear", "disappear", "appear"]);
+    }
+
+    #[test]
+    fn test_disappear_reads_state() {
+        // Like stopping a timer kept in the view's state.
+        let view = state(
+            || 0,
+            |total, cx| {
+                hstack((
+                    text(&format!("total {}", cx[total])),
+                    cond(
+                        cx[total] == 0,
+                        state(
+                            || 5,
+                            move |count, _| {
+                                button("close", move |cx| cx[total] = -1).on_disappear(move |cx| {
+                                    let n = cx[count];
+                                    cx[total] = n;
+                                })
+                            },
+                        ),
+                        EmptyView {},
+                    ),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        h.click("close");
+        h.update();
+        h.expect_text("total 5");
+        assert_eq!(h.states::<i32>(), vec![5]);
+    }
+}
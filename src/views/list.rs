@@ -1,5 +1,6 @@
 use crate::*;
 use std::any::Any;
+use std::collections::HashSet;
 use std::hash::Hash;
 
 pub enum ListOrientation {
@@ -8,57 +9,213 @@ pub enum ListOrientation {
     Z,
 }
 
-pub struct List<ID, F> {
-    orientation: ListOrientation,
-    ids: Vec<ID>,
-    func: F,
+/// Reads and writes a list's selection by row.
+pub(crate) trait ListSelection<ID> {
+    fn selected(&self, cx: &Context, ids: &[ID]) -> Vec<bool>;
+
+    /// Replaces the selection.
+    fn select(&self, cx: &mut Context, ids: &[ID], selected: &[bool]);
 }
 
-impl<ID, V, F> View for List<ID, F>
+pub(crate) struct SetBinding<B>(pub(crate) B);
+
+impl<ID, B> ListSelection<ID> for SetBinding<B>
 where
-    ID: Hash + 'static,
-    V: View,
-    F: Fn(&ID) -> V + 'static,
+    ID: Clone + Eq + Hash + 'static,
+    B: Binding<HashSet<ID>>,
 {
-    fn process(
-        &self,
-        event: &Event,
-        path: &mut IdPath,
-        cx: &mut Context,
-        actions: &mut Vec<Box<dyn Any>>,
-    ) {
-        for child in self.ids.iter().rev() {
-            path.push(hh(child));
-            let offset = cx.get_layout(path).offset;
-            ((self.func)(child)).process(&event.offset(-offset), path, cx, actions);
-            path.pop();
+    fn selected(&self, cx: &Context, ids: &[ID]) -> Vec<bool> {
+        let set = self.0.get(cx);
+        ids.iter().map(|id| set.contains(id)).collect()
+    }
+
+    fn select(&self, cx: &mut Context, ids: &[ID], selected: &[bool]) {
+        let set: HashSet<ID> = ids
+            .iter()
+            .zip(selected)
+            .filter(|(_, s)| **s)
+            .map(|(id, _)| id.clone())
+            .collect();
+        // Leave the state alone if nothing changed, so it isn't redrawn.
+        if *self.0.get(cx) != set {
+            self.0.set(cx, set);
         }
     }
+}
 
-    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        for child in &self.ids {
-            path.push(hh(child));
-            let offset = args.cx.get_layout(path).offset;
+/// A touch dragging out a selection.
+#[derive(Clone)]
+struct DragSelect {
+    touch: usize,
 
-            args.vger.save();
+    /// What was selected before the drag.
+    base: Vec<bool>,
 
-            args.vger.translate(offset);
+    /// Row the touch was last over.
+    row: usize,
+}
 
-            ((self.func)(child)).draw(path, args);
+/// How clicks and drags are selecting rows, for lists and tables.
+#[derive(Clone, Default)]
+pub(crate) struct RowSelection {
+    /// Row which shift-clicks and drags select from.
+    anchor: Option<usize>,
 
-            args.vger.restore();
-            path.pop();
+    drag: Option<DragSelect>,
+
+    /// Was the list clicked last? Select all only applies then.
+    active: bool,
+}
+
+#[derive(Clone, Default)]
+struct SelectState {
+    /// Bounds of each row from the last layout.
+    rows: Vec<LocalRect>,
+
+    select: RowSelection,
+}
+
+/// Updates `selection` for an event. `row` is the row under the touch,
+/// for touch events, and `state` finds the bookkeeping in `cx`.
+pub(crate) fn select_rows<ID>(
+    selection: &dyn ListSelection<ID>,
+    ids: &[ID],
+    row: Option<usize>,
+    event: &Event,
+    state: impl Fn(&mut Context) -> &mut RowSelection,
+    cx: &mut Context,
+) {
+    let range = |a: usize, b: usize, base: &[bool]| {
+        let mut selected = base.to_vec();
+        selected[a.min(b)..=a.max(b)].fill(true);
+        selected
+    };
+    // Rows come from the last layout, so they may be stale if the list
+    // has shrunk since.
+    let row = row.filter(|row| *row < ids.len());
+    let active = state(cx).active;
+
+    match event {
+        Event::TouchBegin { id, .. } => {
+            state(cx).active = row.is_some();
+            let row = match row {
+                Some(row) => row,
+                None => return,
+            };
+
+            let mods = cx.key_mods;
+            let current = selection.selected(cx, ids);
+            let base = if mods.primary() {
+                current.clone()
+            } else {
+                vec![false; ids.len()]
+            };
+            let state = state(cx);
+            let anchor = match state.anchor {
+                Some(anchor) if mods.shift && anchor < ids.len() => anchor,
+                _ => row,
+            };
+            state.anchor = Some(anchor);
+            state.drag = Some(DragSelect {
+                touch: *id,
+                base: base.clone(),
+                row,
+            });
+
+            let selected = if mods.shift {
+                range(anchor, row, &base)
+            } else if mods.primary() {
+                let mut toggled = current;
+                toggled[row] = !toggled[row];
+                toggled
+            } else {
+                range(row, row, &base)
+            };
+            selection.select(cx, ids, &selected);
+        }
+        Event::TouchMove { id, .. } => {
+            let state = state(cx);
+            let (anchor, drag, row) = match (state.anchor, &mut state.drag, row) {
+                (Some(anchor), Some(drag), Some(row)) if drag.touch == *id => (anchor, drag, row),
+                _ => return,
+            };
+            // Wait for the touch to reach another row, so a
+            // Ctrl-click which wobbles doesn't undo itself.
+            if row == drag.row || anchor >= drag.base.len() || row >= drag.base.len() {
+                return;
+            }
+            drag.row = row;
+            let selected = range(anchor, row, &drag.base);
+            selection.select(cx, ids, &selected);
+        }
+        Event::TouchEnd { id, .. } => {
+            let state = state(cx);
+            if state.drag.as_ref().is_some_and(|d| d.touch == *id) {
+                state.drag = None;
+            }
         }
+        Event::Key(k) if k.pressed() == Some(Key::Character('a')) && k.mods.primary() && active => {
+            selection.select(cx, ids, &vec![true; ids.len()]);
+        }
+        _ => (),
     }
+}
 
-    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+fn select_state(vid: ViewId, cx: &mut Context) -> &mut SelectState {
+    cx.init_state(vid, &SelectState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+pub struct List<ID, F> {
+    orientation: ListOrientation,
+    ids: Vec<ID>,
+    func: F,
+    selection: Option<Box<dyn ListSelection<ID>>>,
+}
+
+impl<ID, V, F> List<ID, F>
+where
+    ID: Clone + Eq + Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    /// Lets rows be selected by clicking them, keeping the ids of the
+    /// selected rows in `selection`. Shift-click selects a range, and
+    /// Ctrl-click (Cmd-click on macOS) adds or removes a row. Dragging
+    /// selects the rows dragged over, and Ctrl+A (Cmd+A) selects all
+    /// rows once the list has been clicked.
+    pub fn selection(self, selection: impl Binding<HashSet<ID>>) -> Self {
+        Self {
+            selection: Some(Box::new(SetBinding(selection))),
+            ..self
+        }
+    }
+}
+
+impl<ID, V, F> List<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    /// Lays out the children, adding the bounds of each row to `rows`.
+    fn layout_rows(
+        &self,
+        path: &mut IdPath,
+        args: &mut LayoutArgs,
+        rows: &mut Vec<LocalRect>,
+    ) -> LocalSize {
         match self.orientation {
             ListOrientation::Horizontal => {
                 let n = self.ids.len() as f32;
                 let proposed_child_size = LocalSize::new(args.sz.width / n, args.sz.height);
 
-                let mut sizes = Vec::<LocalSize>::new();
-                sizes.reserve(self.ids.len());
+                let mut sizes = Vec::<LocalSize>::with_capacity(self.ids.len());
 
                 let mut width_sum = 0.0;
                 for child in &self.ids {
@@ -76,18 +233,28 @@ where
                     max_height = size.height.max(max_height)
                 }
 
+                // Right to left, the first child goes on the right.
+                let rtl = args.cx.layout_direction().is_rtl();
                 let mut x = 0.0;
-                for c in 0..self.ids.len() {
-                    path.push(hh(&self.ids[c]));
-                    let child_size = sizes[c];
+                for (child, child_size) in self.ids.iter().zip(sizes.iter().copied()) {
+                    path.push(hh(child));
+                    let left = if rtl {
+                        width_sum - x - child_size.width
+                    } else {
+                        x
+                    };
 
                     let child_offset = align_v(
                         LocalRect::new(LocalPoint::origin(), child_size),
-                        LocalRect::new([x, 0.0].into(), [child_size.width, max_height].into()),
+                        LocalRect::new([left, 0.0].into(), [child_size.width, max_height].into()),
                         VAlignment::Middle,
                     );
 
                     args.cx.set_layout_offset(path, child_offset);
+                    rows.push(LocalRect::new(
+                        [left, 0.0].into(),
+                        [child_size.width, max_height].into(),
+                    ));
 
                     path.pop();
 
@@ -100,8 +267,7 @@ where
                 let n = self.ids.len() as f32;
                 let proposed_child_size = LocalSize::new(args.sz.width, args.sz.height / n);
 
-                let mut sizes = Vec::<LocalSize>::new();
-                sizes.reserve(self.ids.len());
+                let mut sizes = Vec::<LocalSize>::with_capacity(self.ids.len());
 
                 let mut height_sum = 0.0;
                 for child in &self.ids {
@@ -120,9 +286,8 @@ where
                 }
 
                 let mut y = height_sum;
-                for c in 0..self.ids.len() {
-                    path.push(hh(&self.ids[c]));
-                    let child_size = sizes[c];
+                for (child, child_size) in self.ids.iter().zip(sizes.iter().copied()) {
+                    path.push(hh(child));
 
                     let child_offset = align_h(
                         LocalRect::new(LocalPoint::origin(), child_size),
@@ -134,6 +299,10 @@ where
                     );
 
                     args.cx.set_layout_offset(path, child_offset);
+                    rows.push(LocalRect::new(
+                        [0.0, y - child_size.height].into(),
+                        [max_width, child_size.height].into(),
+                    ));
                     path.pop();
 
                     y -= child_size.height;
@@ -145,6 +314,7 @@ where
                 for child in &self.ids {
                     path.push(hh(child));
                     ((self.func)(child)).layout(path, args);
+                    rows.push(LocalRect::new(LocalPoint::origin(), args.sz));
                     path.pop();
                 }
                 args.sz
@@ -152,6 +322,99 @@ where
         }
     }
 
+    fn row_at(&self, vid: ViewId, position: LocalPoint, cx: &mut Context) -> Option<usize> {
+        select_state(vid, cx)
+            .rows
+            .iter()
+            .rposition(|r| r.contains(position))
+    }
+
+    fn select(
+        &self,
+        selection: &dyn ListSelection<ID>,
+        event: &Event,
+        vid: ViewId,
+        cx: &mut Context,
+    ) {
+        let row = match event {
+            Event::TouchBegin { position, .. } | Event::TouchMove { position, .. } => {
+                self.row_at(vid, *position, cx)
+            }
+            _ => None,
+        };
+        select_rows(
+            selection,
+            &self.ids,
+            row,
+            event,
+            |cx| &mut select_state(vid, cx).select,
+            cx,
+        );
+    }
+}
+
+impl<ID, V, F> View for List<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        for child in self.ids.iter().rev() {
+            path.push(hh(child));
+            let offset = cx.get_layout(path).offset;
+            ((self.func)(child)).process(&event.offset(-offset), path, cx, actions);
+            path.pop();
+        }
+
+        if let Some(selection) = &self.selection {
+            let vid = cx.view_id(path);
+            self.select(selection.as_ref(), event, vid, cx);
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        if let Some(selection) = &self.selection {
+            let vid = args.cx.view_id(path);
+            let rows = select_state(vid, args.cx).rows.clone();
+            let paint = args.vger.color_paint(args.cx.theme().accent_background);
+            let selected = selection.selected(args.cx, &self.ids);
+            for (rect, _) in rows.iter().zip(selected).filter(|(_, s)| *s) {
+                args.vger.fill_rect(*rect, 0.0, paint);
+            }
+        }
+
+        for child in &self.ids {
+            path.push(hh(child));
+            let offset = args.cx.get_layout(path).offset;
+
+            args.vger.save();
+
+            args.vger.translate(offset);
+
+            ((self.func)(child)).draw(path, args);
+
+            args.vger.restore();
+            path.pop();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let mut rows = Vec::with_capacity(self.ids.len());
+        let size = self.layout_rows(path, args, &mut rows);
+        if self.selection.is_some() {
+            let vid = args.cx.view_id(path);
+            select_state(vid, args.cx).rows = rows;
+        }
+        size
+    }
+
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
         for child in &self.ids {
             path.push(hh(child));
@@ -189,7 +452,7 @@ where
         for child in &self.ids {
             path.push(hh(child));
             map.push(cx.view_id(path));
-            ((self.func)(child)).gc(path, cx, map);
+            ((self.func)(child)).gc_view(path, cx, map);
             path.pop();
         }
     }
@@ -246,6 +509,7 @@ pub fn list<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) ->
         orientation: ListOrientation::Vertical,
         ids,
         func: f,
+        selection: None,
     }
 }
 
@@ -254,6 +518,7 @@ pub fn hlist<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) -
         orientation: ListOrientation::Horizontal,
         ids,
         func: f,
+        selection: None,
     }
 }
 
@@ -262,5 +527,111 @@ pub fn zlist<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) -
         orientation: ListOrientation::Z,
         ids,
         func: f,
+        selection: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn selectable() -> impl View {
+        state(HashSet::<usize>::new, |selection, _| {
+            list(vec![0, 1, 2, 3], |i| format!("row {}", i)).selection(selection)
+        })
+    }
+
+    fn selected(harness: &Harness<impl View>) -> Vec<usize> {
+        let mut selected: Vec<usize> = harness.states::<HashSet<usize>>()[0]
+            .iter()
+            .copied()
+            .collect();
+        selected.sort();
+        selected
+    }
+
+    fn click_with(harness: &mut Harness<impl View>, text: &str, mods: KeyboardModifiers) {
+        harness.context().key_mods = mods;
+        harness.click(text);
+        harness.context().key_mods = KeyboardModifiers::default();
+    }
+
+    fn primary() -> KeyboardModifiers {
+        KeyboardModifiers {
+            control: !cfg!(target_os = "macos"),
+            command: cfg!(target_os = "macos"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_click_select() {
+        let mut h = Harness::new(selectable());
+        h.click("row 1");
+        assert_eq!(selected(&h), vec![1]);
+        h.click("row 2");
+        assert_eq!(selected(&h), vec![2]);
+
+        click_with(&mut h, "row 0", primary());
+        assert_eq!(selected(&h), vec![0, 2]);
+        click_with(&mut h, "row 2", primary());
+        assert_eq!(selected(&h), vec![0]);
+
+        let shift = KeyboardModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        // Ranges start from the last row clicked.
+        click_with(&mut h, "row 3", shift);
+        assert_eq!(selected(&h), vec![2, 3]);
+        h.click("row 1");
+        click_with(&mut h, "row 0", shift);
+        assert_eq!(selected(&h), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_select_all() {
+        let mut h = Harness::new(selectable());
+        // Nothing happens before the list is clicked.
+        h.key_with_mods(Key::Character('a'), primary());
+        assert_eq!(selected(&h), Vec::<usize>::new());
+
+        h.click("row 1");
+        h.key_with_mods(Key::Character('a'), primary());
+        assert_eq!(selected(&h), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_stale_row() {
+        let mut cx = Context::new();
+        let set = cx.view_id(&vec![0]);
+        let vid = cx.view_id(&vec![1]);
+        cx.init_state(set, &HashSet::<usize>::new);
+        let selection = SetBinding(StateHandle::<HashSet<usize>>::new(set));
+        cx.key_mods = primary();
+        // A row from before the list shrank to two rows is ignored.
+        let begin = Event::TouchBegin {
+            id: 0,
+            position: LocalPoint::zero(),
+        };
+        select_rows(
+            &selection,
+            &[0, 1],
+            Some(3),
+            &begin,
+            |cx| &mut select_state(vid, cx).select,
+            &mut cx,
+        );
+        assert!(selection.0.get(&cx).is_empty());
+    }
+
+    #[test]
+    fn test_drag_select() {
+        let mut h = Harness::new(selectable());
+        let from = h.find_text("row 0").unwrap().center();
+        let to = h.find_text("row 2").unwrap().center();
+        h.drag(from, to);
+        assert_eq!(selected(&h), vec![0, 1, 2]);
     }
 }
@@ -82,7 +82,7 @@ where
         cx.set_state(id, self.value.clone());
         map.push(id);
         path.push(0);
-        (self.func)(StateHandle::new(id), cx).gc(path, cx, map);
+        (self.func)(StateHandle::new(id), cx).gc_view(path, cx, map);
         path.pop();
     }
 
@@ -0,0 +1,223 @@
+use crate::*;
+use std::any::Any;
+use std::rc::Rc;
+
+/// The body built for some dependencies, and how it was last laid out.
+struct MemoState<D, V> {
+    deps: D,
+    body: Rc<V>,
+
+    /// Size proposed and returned when last laid out.
+    layout: Option<(LocalSize, LocalSize)>,
+}
+
+/// Struct for `memo`.
+pub struct MemoView<D, F> {
+    deps: D,
+    func: F,
+}
+
+impl<D, V, F> MemoView<D, F>
+where
+    D: PartialEq + Clone + 'static,
+    V: View,
+    F: Fn() -> V + 'static,
+{
+    fn state<'a>(&self, path: &IdPath, cx: &'a mut Context) -> &'a mut MemoState<D, V> {
+        let id = cx.view_id(path);
+        let current = cx
+            .state_map
+            .get(&id)
+            .and_then(|holder| holder.state.downcast_ref::<MemoState<D, V>>())
+            .is_some_and(|state| state.deps == self.deps);
+
+        if !current {
+            cx.set_state(
+                id,
+                MemoState {
+                    deps: self.deps.clone(),
+                    body: Rc::new((self.func)()),
+                    layout: None,
+                },
+            );
+        }
+
+        cx.state_map
+            .get_mut(&id)
+            .unwrap()
+            .state
+            .downcast_mut()
+            .unwrap()
+    }
+
+    fn body(&self, path: &IdPath, cx: &mut Context) -> Rc<V> {
+        self.state(path, cx).body.clone()
+    }
+}
+
+impl<D, V, F> View for MemoView<D, F>
+where
+    D: PartialEq + Clone + 'static,
+    V: View,
+    F: Fn() -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let body = self.body(path, cx);
+        path.push(0);
+        body.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let body = self.body(path, args.cx);
+        path.push(0);
+        body.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let id = args.cx.view_id(path);
+        let proposed = args.sz;
+        let (body, layout) = {
+            let state = self.state(path, args.cx);
+            (state.body.clone(), state.layout)
+        };
+
+        // Like `state`, skip layout unless state inside the body changed.
+        // The body only depends on `deps`, so state around it doesn't
+        // matter.
+        if let (Some((last_proposed, size)), Some(deps)) = (layout, args.cx.deps.get(&id)) {
+            let dirty = deps
+                .iter()
+                .any(|dep| args.cx.state_map.get(dep).is_some_and(|h| h.dirty));
+            if last_proposed == proposed && !dirty {
+                return size;
+            }
+        }
+
+        path.push(0);
+        let size = body.layout(path, args);
+        let mut deps = vec![];
+        body.gc(path, args.cx, &mut deps);
+        path.pop();
+
+        args.cx.deps.insert(id, deps);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        self.state(path, args.cx).layout = Some((proposed, size));
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let body = self.body(path, cx);
+        path.push(0);
+        body.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let body = self.body(path, cx);
+        path.push(0);
+        let id = body.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let body = self.body(path, cx);
+        path.push(0);
+        body.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let body = self.body(path, cx);
+        map.push(cx.view_id(path));
+        path.push(0);
+        body.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let body = self.body(path, cx);
+        path.push(0);
+        let node_id = body.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<D, F> private::Sealed for MemoView<D, F> {}
+
+/// Builds the view returned by `body` once, and again only when `deps`
+/// changes. Until then, the body isn't rebuilt or laid out again when
+/// state elsewhere in the app changes. State inside the body, and state
+/// around it, still update it as usual.
+///
+/// `body` should only depend on `deps`, so anything else it captures is
+/// the same each time `memo` is called. Memoized views aren't flexible in
+/// stacks, so size them or put spacers around them.
+pub fn memo<D, V, F>(deps: D, body: F) -> MemoView<D, F>
+where
+    D: PartialEq + Clone + 'static,
+    V: View,
+    F: Fn() -> V + 'static,
+{
+    MemoView { deps, func: body }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_memo() {
+        let builds = Rc::new(Cell::new(0));
+        let b = builds.clone();
+        let view = state(
+            || (0, 0),
+            move |s, cx| {
+                let (count, other) = cx[s];
+                let b = b.clone();
+                vstack((
+                    memo(count, move || {
+                        b.set(b.get() + 1);
+                        text(&format!("count {}", count))
+                    }),
+                    text(&format!("other {}", other)),
+                    button("count", move |cx| cx[s].0 += 1),
+                    button("other", move |cx| cx[s].1 += 1),
+                ))
+            },
+        );
+        let mut h = Harness::new(view);
+        assert_eq!(builds.get(), 1);
+
+        h.click("other");
+        h.click("other");
+        assert!(h.has_text("other 2"));
+        assert_eq!(builds.get(), 1);
+
+        h.click("count");
+        assert!(h.has_text("count 1"));
+        assert_eq!(builds.get(), 2);
+    }
+}
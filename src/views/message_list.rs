@@ -0,0 +1,581 @@
+use crate::*;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// How far from the bottom a message list must be scrolled before it
+/// offers to jump back to the latest message.
+const JUMP_THRESHOLD: f32 = 1.0;
+
+/// Gap between the jump pill and the bottom of the list.
+const JUMP_MARGIN: f32 = 12.0;
+
+/// How far appearing messages rise into place.
+const APPEAR_RISE: f32 = 12.0;
+
+#[derive(Default)]
+struct MessageColumnState {
+    laid_out: bool,
+
+    /// Hash of the newest message, when last laid out.
+    last: Option<u64>,
+
+    /// Height of messages added after `last` in the latest layout.
+    appended: f32,
+
+    sizes: HashMap<u64, LocalSize>,
+    known: HashSet<u64>,
+
+    /// Seconds since each appearing message was added.
+    appearing: HashMap<u64, f32>,
+}
+
+fn column_state(vid: ViewId, cx: &mut Context) -> &mut MessageColumnState {
+    cx.init_state(vid, &MessageColumnState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Messages stacked oldest to newest, top to bottom.
+pub struct MessageColumn<ID, F> {
+    ids: Vec<ID>,
+    func: F,
+
+    /// Seconds new messages take to appear, or zero for no animation.
+    appear: f32,
+}
+
+impl<ID, V, F> MessageColumn<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    /// How far along its appear animation a message is, from 0 to 1.
+    fn appear_progress(&self, vid: ViewId, id: u64, cx: &mut Context) -> f32 {
+        match column_state(vid, cx).appearing.get(&id) {
+            Some(age) => {
+                let t = (age / self.appear).min(1.0);
+                1.0 - (1.0 - t) * (1.0 - t)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+impl<ID, V, F> View for MessageColumn<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        if let Event::Anim = event {
+            if !column_state(vid, cx).appearing.is_empty() {
+                let appear = self.appear;
+                let dt = cx.anim_interval();
+                cx[StateHandle::<MessageColumnState>::new(vid)]
+                    .appearing
+                    .retain(|_, age| {
+                        *age += dt;
+                        *age < appear
+                    });
+            }
+        }
+
+        for child in self.ids.iter().rev() {
+            path.push(hh(child));
+            let offset = cx.get_layout(path).offset;
+            ((self.func)(child)).process(&event.offset(-offset), path, cx, actions);
+            path.pop();
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        for child in &self.ids {
+            let id = hh(child);
+            let progress = self.appear_progress(vid, id, args.cx);
+            let size = column_state(vid, args.cx).sizes.get(&id).copied();
+
+            path.push(id);
+            let offset = args.cx.get_layout(path).offset;
+            args.vger.save();
+            args.vger
+                .translate(offset - LocalOffset::new(0.0, (1.0 - progress) * APPEAR_RISE));
+
+            let view = (self.func)(child);
+            match size {
+                Some(size) if progress < 1.0 => {
+                    let rect = LocalRect::new(LocalPoint::zero(), size);
+                    let xform = args.vger.current_transform();
+                    let style = LayerStyle {
+                        opacity: progress,
+                        ..Default::default()
+                    };
+                    match args.cx.begin_layer(rect, xform, &style) {
+                        Some(mut layer) => {
+                            view.draw(
+                                path,
+                                &mut DrawArgs {
+                                    cx: args.cx,
+                                    vger: &mut layer,
+                                },
+                            );
+                            args.cx.end_layer(args.vger, layer, xform, style);
+                        }
+                        None => view.draw(path, args),
+                    }
+                }
+                _ => view.draw(path, args),
+            }
+
+            args.vger.restore();
+            path.pop();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        let proposed = LocalSize::new(args.sz.width, f32::INFINITY);
+
+        let mut sizes = Vec::with_capacity(self.ids.len());
+        for child in &self.ids {
+            path.push(hh(child));
+            sizes.push(((self.func)(child)).layout(path, &mut args.size(proposed)));
+            path.pop();
+        }
+
+        let height: f32 = sizes.iter().map(|sz| sz.height).sum();
+        let width = if args.sz.width.is_finite() {
+            args.sz.width
+        } else {
+            sizes.iter().map(|sz| sz.width).fold(0.0, f32::max)
+        };
+
+        // Our y axis points up, so the oldest message goes at the top.
+        let mut y = height;
+        for (child, size) in self.ids.iter().zip(&sizes) {
+            y -= size.height;
+            path.push(hh(child));
+            args.cx.set_layout_offset(path, [0.0, y].into());
+            path.pop();
+        }
+
+        let hashes: Vec<u64> = self.ids.iter().map(hh).collect();
+        let appear = self.appear > 0.0;
+        let state = column_state(vid, args.cx);
+
+        state.appended = match state
+            .last
+            .and_then(|last| hashes.iter().position(|h| *h == last))
+        {
+            Some(i) => sizes[i + 1..].iter().map(|sz| sz.height).sum(),
+            None => 0.0,
+        };
+        state.last = hashes.last().copied();
+
+        // Messages shown from the start don't animate.
+        if appear && state.laid_out {
+            for h in &hashes {
+                if !state.known.contains(h) {
+                    state.appearing.insert(*h, 0.0);
+                }
+            }
+        }
+        state.known = hashes.iter().copied().collect();
+        let known = &state.known;
+        state.appearing.retain(|h, _| known.contains(h));
+        state.sizes = hashes.iter().copied().zip(sizes.iter().copied()).collect();
+        state.laid_out = true;
+
+        let size = LocalSize::new(width, height);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            // Messages are fading in.
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(path, rect, xform);
+            return;
+        }
+        for child in &self.ids {
+            path.push(hh(child));
+            let offset = cx.get_layout(path).offset;
+            ((self.func)(child)).dirty(path, xform.pre_translate(offset), cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let mut hit = None;
+        for child in &self.ids {
+            path.push(hh(child));
+            let offset = cx.get_layout(path).offset;
+            if let Some(h) = ((self.func)(child)).hittest(path, pt - offset, cx) {
+                hit = Some(h)
+            }
+            path.pop();
+        }
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        for child in &self.ids {
+            path.push(hh(child));
+            ((self.func)(child)).commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        for child in &self.ids {
+            path.push(hh(child));
+            map.push(cx.view_id(path));
+            ((self.func)(child)).gc_view(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::List);
+
+        let children: Vec<accesskit::NodeId> = self
+            .ids
+            .iter()
+            .filter_map(|child| {
+                path.push(hh(child));
+                let node_id = ((self.func)(child)).access(path, cx, nodes);
+                path.pop();
+                node_id
+            })
+            .collect();
+
+        builder.set_children(children);
+        nodes.push((
+            cx.view_id(path).access_id(),
+            builder.build(&mut cx.access_node_classes),
+        ));
+        Some(cx.view_id(path).access_id())
+    }
+}
+
+impl<ID, F> private::Sealed for MessageColumn<ID, F> {}
+
+/// Struct for `message_list`.
+pub struct MessageList<ID, F> {
+    scroll: ScrollView<MessageColumn<ID, F>>,
+}
+
+/// A scrolling list of messages, oldest first, for chats and logs.
+///
+/// The list starts at the bottom and stays there as messages are added.
+/// Scrolled up, it keeps the messages in view where they are as newer
+/// messages arrive below or older ones are loaded above, and shows a
+/// "Jump to latest" button.
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(message_list(vec![1, 2, 3], |i| text(&format!("message {}", i))));
+/// ```
+pub fn message_list<ID: Hash + 'static, V: View, F: Fn(&ID) -> V + 'static>(
+    ids: Vec<ID>,
+    f: F,
+) -> MessageList<ID, F> {
+    MessageList {
+        scroll: vscroll(MessageColumn {
+            ids,
+            func: f,
+            appear: 0.0,
+        }),
+    }
+}
+
+impl<ID, V, F> MessageList<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    /// Fades new messages in and raises them into place over `duration`
+    /// seconds. Messages shown when the list first appears don't animate.
+    pub fn animate_appear(mut self, duration: f32) -> Self {
+        self.scroll.content_mut().appear = duration;
+        self
+    }
+
+    fn scroll_id(&self, path: &mut IdPath, cx: &mut Context) -> ViewId {
+        path.push(0);
+        let vid = cx.view_id(path);
+        path.pop();
+        vid
+    }
+
+    fn jump_visible(&self, path: &mut IdPath, cx: &mut Context) -> bool {
+        let vid = self.scroll_id(path, cx);
+        scroll_distance_from_bottom(vid, cx) > JUMP_THRESHOLD
+    }
+
+    fn jump(&self, path: &mut IdPath, cx: &mut Context) -> impl View {
+        let vid = self.scroll_id(path, cx);
+        button("Jump to latest", move |cx| scroll_to_bottom(vid, cx))
+    }
+}
+
+impl<ID, V, F> View for MessageList<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if self.jump_visible(path, cx) {
+            let jump = self.jump(path, cx);
+            path.push(1);
+            let offset = cx.get_layout(path).offset;
+            jump.process(&event.offset(-offset), path, cx, actions);
+            path.pop();
+        }
+        path.push(0);
+        self.scroll.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.scroll.draw(path, args);
+        path.pop();
+
+        if self.jump_visible(path, args.cx) {
+            let jump = self.jump(path, args.cx);
+            path.push(1);
+            args.vger.save();
+            args.vger.translate(args.cx.get_layout(path).offset);
+            jump.draw(path, args);
+            args.vger.restore();
+            path.pop();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = self.scroll_id(path, args.cx);
+        let distance = scroll_distance_from_bottom(vid, args.cx);
+
+        path.push(0);
+        let size = self.scroll.layout(path, args);
+        path.push(0);
+        let column = args.cx.view_id(path);
+        path.pop();
+        path.pop();
+
+        // At the bottom, follow new messages. Scrolled up, hold still.
+        let distance = if distance > JUMP_THRESHOLD {
+            distance + column_state(column, args.cx).appended
+        } else {
+            0.0
+        };
+        set_scroll_distance_from_bottom(vid, distance, args.cx);
+
+        let jump = self.jump(path, args.cx);
+        path.push(1);
+        let jump_size = jump.layout(path, args);
+        args.cx.set_layout_offset(
+            path,
+            [(size.width - jump_size.width) / 2.0, JUMP_MARGIN].into(),
+        );
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.scroll.dirty(path, xform, cx);
+        path.pop();
+
+        if self.jump_visible(path, cx) {
+            let jump = self.jump(path, cx);
+            path.push(1);
+            let offset = cx.get_layout(path).offset;
+            jump.dirty(path, xform.pre_translate(offset), cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if self.jump_visible(path, cx) {
+            let jump = self.jump(path, cx);
+            path.push(1);
+            let offset = cx.get_layout(path).offset;
+            let hit = jump.hittest(path, pt - offset, cx);
+            path.pop();
+            if hit.is_some() {
+                return hit;
+            }
+        }
+        path.push(0);
+        let hit = self.scroll.hittest(path, pt, cx);
+        path.pop();
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.scroll.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.scroll.gc_view(path, cx, map);
+        path.pop();
+
+        let jump = self.jump(path, cx);
+        path.push(1);
+        jump.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.scroll.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<ID, F> private::Sealed for MessageList<ID, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Ten messages and buttons to add newer and older ones.
+    fn chat(appear: f32) -> Harness<impl View> {
+        let view = state(
+            || (0..10).collect::<Vec<i32>>(),
+            move |messages, cx| {
+                vstack((
+                    message_list(cx[messages].clone(), |i| {
+                        text(&format!("message {}", i)).size([200.0, 40.0])
+                    })
+                    .animate_appear(appear),
+                    hstack((
+                        button("send", move |cx| {
+                            let next = cx[messages].last().unwrap() + 1;
+                            cx[messages].push(next);
+                        }),
+                        button("older", move |cx| {
+                            let first = cx[messages][0] - 1;
+                            cx[messages].insert(0, first);
+                        }),
+                    )),
+                ))
+            },
+        );
+        Harness::with_size(view, Size::new(200.0, 300.0))
+    }
+
+    /// Center of a text view, to the nearest point so moves add up
+    /// exactly.
+    fn center(harness: &mut Harness<impl View>, text: &str) -> Point {
+        let c = harness.find_text(text).unwrap().center();
+        Point::new(c.x.round(), c.y.round())
+    }
+
+    #[test]
+    fn test_message_list_anchoring() {
+        let mut harness = chat(0.0);
+
+        // Starts at the bottom, and follows new messages there.
+        let bottom = center(&mut harness, "message 9");
+        assert!(center(&mut harness, "message 0").y > 300.0);
+        harness.click("send");
+        assert_eq!(center(&mut harness, "message 10").y, bottom.y);
+        assert!(!harness.has_text("Jump to latest"));
+
+        // Older messages load above without moving what's shown.
+        harness.click("older");
+        assert_eq!(center(&mut harness, "message 10").y, bottom.y);
+
+        // Scrolled up, new messages arrive below what's shown.
+        harness.drag(bottom, Point::new(bottom.x, bottom.y - 80.0));
+        let shown = center(&mut harness, "message 10");
+        assert_eq!(shown.y, bottom.y - 80.0);
+        assert!(harness.has_text("Jump to latest"));
+        harness.click("send");
+        assert_eq!(center(&mut harness, "message 10"), shown);
+
+        harness.click("Jump to latest");
+        assert_eq!(center(&mut harness, "message 11").y, bottom.y);
+        assert!(!harness.has_text("Jump to latest"));
+    }
+
+    #[test]
+    fn test_message_list_appear() {
+        fn appearing(harness: &mut Harness<impl View>) -> usize {
+            harness
+                .context()
+                .state_map
+                .values()
+                .filter_map(|holder| holder.state.downcast_ref::<MessageColumnState>())
+                .map(|state| state.appearing.len())
+                .sum()
+        }
+
+        let mut harness = chat(0.25);
+        assert_eq!(appearing(&mut harness), 0);
+
+        harness.click("send");
+        assert_eq!(appearing(&mut harness), 1);
+        for _ in 0..30 {
+            harness.update();
+        }
+        assert_eq!(appearing(&mut harness), 0);
+    }
+}
@@ -6,65 +6,138 @@ mod background;
 pub use background::*;
 mod button;
 pub use button::*;
+mod cache_key;
+pub use cache_key::*;
 mod canvas;
 pub use canvas::*;
+mod checkbox;
+pub use checkbox::*;
+mod color_picker;
+pub use color_picker::*;
 mod clip;
 pub use clip::*;
 mod command;
 pub use command::*;
 mod cond;
 pub use cond::*;
+mod debug_group;
+pub use debug_group::*;
 mod drag;
 pub use drag::*;
+mod cursor;
+pub use cursor::*;
+mod drag_drop;
+pub use drag_drop::*;
+mod draggable;
+pub use draggable::*;
+mod effect;
+pub use effect::*;
 mod emptyview;
 pub use emptyview::*;
 mod env;
 pub use env::*;
+mod eyedropper;
+pub use eyedropper::*;
 mod flex;
 pub use flex::*;
 mod focus;
 pub use focus::*;
 mod geom;
 pub use geom::*;
+mod grip;
+pub use grip::*;
 mod handle;
 pub use handle::*;
 mod hover;
 pub use hover::*;
+mod id;
+pub use id::*;
+mod image_view;
+pub use image_view::*;
+mod ime;
+pub use ime::*;
 mod key;
 pub use key::*;
 mod knob;
 pub use knob::*;
+mod layer;
+pub use layer::*;
+mod lifecycle;
+pub use lifecycle::*;
 mod list;
 pub use list::*;
 mod map;
 pub use map::*;
+mod memo;
+pub use memo::*;
+mod message_list;
+pub use message_list::*;
+mod model;
+pub use model::*;
 mod modview;
 pub use modview::*;
+mod navigation;
+pub use navigation::*;
 mod offset;
 pub use offset::*;
+
+mod tabs;
+pub use tabs::*;
+mod tooltip;
+pub use tooltip::*;
+mod transform;
+pub use transform::*;
 mod padding;
 pub use padding::*;
+mod pinch;
+pub use pinch::*;
+mod particles;
+pub use particles::*;
+mod persistent_state;
+pub use persistent_state::*;
+mod progress;
+pub use progress::*;
 mod redux;
 pub use redux::*;
+mod rich_text;
+pub use rich_text::*;
 mod role;
 pub use role::*;
+mod scroll;
+pub use scroll::*;
 mod shapes;
 pub use shapes::*;
+mod sheet;
+pub use sheet::*;
 mod size;
 pub use size::*;
+mod sketch;
+pub use sketch::*;
 mod slider;
 pub use slider::*;
 mod spacer;
 pub use spacer::*;
+mod split;
+pub use split::*;
+mod table;
+pub use table::*;
 mod stack;
 pub use stack::*;
 mod stack_layout;
 mod state;
 pub use state::*;
+mod suspense;
+pub use suspense::*;
+mod store;
+pub use store::*;
 mod tap;
 pub use tap::*;
+mod text_access;
+pub(crate) use text_access::*;
 mod text_editor;
 pub use text_editor::*;
+mod text_field;
+pub use text_field::*;
 mod text;
 pub use text::*;
 mod toggle;
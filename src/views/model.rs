@@ -0,0 +1,283 @@
+use crate::*;
+use std::any::Any;
+
+/// A struct whose fields are each kept in their own state, so changing
+/// one only invalidates the views which read it. Derive it with
+/// `#[derive(Model)]` and show it with `model`.
+pub trait Model: Sized + 'static {
+    /// Handles to the state of each field. The derive calls this
+    /// `<Name>Fields`.
+    type Fields: Copy + 'static;
+
+    /// Gets a handle for each field, in order.
+    fn fields(fields: &mut ModelFields) -> Self::Fields;
+
+    /// Moves each field into its state, in the same order as `fields`.
+    fn store(self, fields: &mut ModelFields);
+}
+
+/// Finds the state of each field of a `Model`. Used by
+/// `#[derive(Model)]`.
+pub struct ModelFields<'a> {
+    cx: &'a mut Context,
+    path: &'a mut IdPath,
+    ids: Vec<ViewId>,
+}
+
+impl<'a> ModelFields<'a> {
+    fn new(cx: &'a mut Context, path: &'a mut IdPath) -> Self {
+        Self {
+            cx,
+            path,
+            ids: vec![],
+        }
+    }
+
+    fn next_id(&mut self) -> ViewId {
+        // The model's body is the first child.
+        self.path.push(self.ids.len() as u64 + 1);
+        let id = self.cx.view_id(self.path);
+        self.path.pop();
+        self.ids.push(id);
+        id
+    }
+
+    /// The next field's state.
+    pub fn field<T: 'static>(&mut self) -> StateHandle<T> {
+        StateHandle::new(self.next_id())
+    }
+
+    /// Sets the next field's state.
+    pub fn store<T: 'static>(&mut self, value: T) {
+        let id = self.next_id();
+        self.cx.set_state(id, value);
+    }
+}
+
+/// Struct for `model`.
+pub struct ModelView<D, F> {
+    default: D,
+    func: F,
+}
+
+/// Like `state`, but keeps each field of the model in its own state.
+/// Views read fields through the handles passed to `f`, and a
+/// `state` or `model` view reading a field is only invalidated when that
+/// field changes.
+///
+/// ```no_run
+/// # use rui::*;
+/// #[derive(Model)]
+/// struct Doc {
+///     title: String,
+///     count: i32,
+/// }
+///
+/// rui(model(
+///     || Doc { title: "untitled".into(), count: 0 },
+///     |doc, _| {
+///         vstack((
+///             text_field(doc.title),
+///             // Only this is redrawn when the count changes.
+///             with_ref(doc.count, |count| format!("{}", count)),
+///             button("increment", move |cx| cx[doc.count] += 1),
+///         ))
+///     },
+/// ));
+/// ```
+pub fn model<M, V, D, F>(initial: D, f: F) -> ModelView<D, F>
+where
+    M: Model,
+    V: View,
+    D: Fn() -> M + 'static,
+    F: Fn(M::Fields, &Context) -> V + 'static,
+{
+    ModelView {
+        default: initial,
+        func: f,
+    }
+}
+
+impl<M, V, D, F> ModelView<D, F>
+where
+    M: Model,
+    V: View,
+    D: Fn() -> M + 'static,
+    F: Fn(M::Fields, &Context) -> V + 'static,
+{
+    /// Handles to the fields, and their ids, storing the default model
+    /// the first time.
+    fn fields(&self, path: &mut IdPath, cx: &mut Context) -> (ViewId, M::Fields, Vec<ViewId>) {
+        let id = cx.view_id(path);
+        if !cx.state_map.contains_key(&id) {
+            cx.set_state(id, ());
+            (self.default)().store(&mut ModelFields::new(cx, path));
+        }
+        let mut fields = ModelFields::new(cx, path);
+        let handles = M::fields(&mut fields);
+        (id, handles, fields.ids)
+    }
+
+    fn body(&self, path: &mut IdPath, cx: &mut Context) -> V {
+        let (_, fields, _) = self.fields(path, cx);
+        (self.func)(fields, cx)
+    }
+}
+
+impl<M, V, D, F> View for ModelView<D, F>
+where
+    M: Model,
+    V: View,
+    D: Fn() -> M + 'static,
+    F: Fn(M::Fields, &Context) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let view = self.body(path, cx);
+        path.push(0);
+        view.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let (id, fields, _) = self.fields(path, args.cx);
+        let reads = args.cx.begin_reads();
+        path.push(0);
+        (self.func)(fields, args.cx).draw(path, args);
+        path.pop();
+        args.cx.end_reads(id, reads);
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let (id, fields, _) = self.fields(path, args.cx);
+        let reads = args.cx.begin_reads();
+        path.push(0);
+        let size = (self.func)(fields, args.cx).layout(path, args);
+        path.pop();
+        args.cx.end_reads(id, reads);
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let (id, fields, _) = self.fields(path, cx);
+        if !cx.add_read_damage(id, path, xform) {
+            path.push(0);
+            (self.func)(fields, cx).dirty(path, xform, cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let view = self.body(path, cx);
+        path.push(0);
+        let id = view.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let view = self.body(path, cx);
+        path.push(0);
+        view.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let (id, fields, ids) = self.fields(path, cx);
+        map.push(id);
+        map.extend(ids);
+        if let Some(reads) = cx.body_reads.get(&id) {
+            map.extend_from_slice(reads);
+        }
+        path.push(0);
+        (self.func)(fields, cx).gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let view = self.body(path, cx);
+        path.push(0);
+        let node_id = view.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<D, F> private::Sealed for ModelView<D, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(Model)]
+    struct Doc {
+        title: String,
+        count: i32,
+    }
+
+    fn doc() -> Doc {
+        Doc {
+            title: "untitled".into(),
+            count: 0,
+        }
+    }
+
+    #[test]
+    fn test_model_fields() {
+        let mut h = Harness::new(model(doc, |doc, cx| {
+            vstack((
+                cx[doc.title].clone(),
+                button("rename", move |cx| cx[doc.title] = "notes".into()),
+                button("increment", move |cx| cx[doc.count] += 1),
+                format!("count {}", cx[doc.count]),
+            ))
+        }));
+        h.expect_text("untitled");
+        h.click("rename");
+        h.click("increment");
+        h.click("increment");
+        h.expect_text("notes");
+        h.expect_text("count 2");
+        assert_eq!(h.states::<i32>(), vec![2]);
+    }
+
+    #[test]
+    fn test_model_invalidation() {
+        let mut h = Harness::new(model(doc, |doc, _| {
+            vstack((
+                with_ref(doc.title, |title| text(title)).padding(Auto),
+                with_ref(doc.count, |count| format!("count {}", count)).padding(Auto),
+                button("increment", move |cx| cx[doc.count] += 1),
+            ))
+        }));
+        // Readers only learn what they read once laid out.
+        h.update();
+        let title = h.find_text("untitled").unwrap();
+
+        h.click("increment");
+        h.expect_text("count 1");
+        let count = h.find_text("count 1").unwrap();
+        let damage = h.damage().expect("only the count should be redrawn");
+        assert!(damage.contains(count.center()));
+        assert!(!damage.contains(title.center()));
+    }
+}
@@ -59,7 +59,7 @@ where
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         map.push(cx.view_id(path));
         path.push(0);
-        (self.func)(self.value.clone(), cx).gc(path, cx, map);
+        (self.func)(self.value.clone(), cx).gc_view(path, cx, map);
         path.pop();
     }
 
@@ -0,0 +1,529 @@
+use crate::*;
+use std::any::Any;
+use std::hash::Hash;
+
+/// How long a slide between destinations takes, in seconds.
+const SLIDE_DURATION: f32 = 0.3;
+
+/// How far the view underneath moves while another slides over it,
+/// relative to the width.
+const PARALLAX: f32 = 0.3;
+
+#[derive(Clone, Debug)]
+struct NavState<R> {
+    /// How many destinations were pushed when last seen, or `None` before
+    /// the first frame.
+    depth: Option<usize>,
+
+    /// The top destination when last seen, so it can slide out once
+    /// popped.
+    top: Option<R>,
+
+    /// A popped destination still sliding out, and how deep it was.
+    leaving: Option<(usize, R)>,
+
+    /// How far through the slide, from 0 to 1.
+    progress: f32,
+
+    /// Where the bar went in the last layout.
+    bar: LocalOffset,
+
+    /// Space for destinations, under the bar.
+    size: LocalSize,
+}
+
+impl<R> Default for NavState<R> {
+    fn default() -> Self {
+        Self {
+            depth: None,
+            top: None,
+            leaving: None,
+            progress: 1.0,
+            bar: LocalOffset::zero(),
+            size: LocalSize::zero(),
+        }
+    }
+}
+
+fn nav_state<R: 'static>(vid: ViewId, cx: &mut Context) -> &mut NavState<R> {
+    cx.init_state(vid, &NavState::<R>::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for `navigation_stack`.
+pub struct NavigationStack<R, B, V0, F> {
+    routes: B,
+    root: V0,
+    destination: F,
+    slide: bool,
+    phantom: std::marker::PhantomData<R>,
+}
+
+/// Shows `root`, or the view `destination` makes for the last of `routes`
+/// if there are any. Push a route to go to its destination.
+///
+/// Above destinations is a bar with a back button, which pops the last
+/// route. Escape and Alt+Left do the same while no text is being edited.
+/// Each destination has its own state, kept while its route is in
+/// `routes` and dropped once it's popped, so pushing a route again starts
+/// fresh.
+pub fn navigation_stack<R, B, V0, V, F>(
+    routes: B,
+    root: V0,
+    destination: F,
+) -> NavigationStack<R, B, V0, F>
+where
+    R: Clone + Hash + 'static,
+    B: Binding<Vec<R>>,
+    V0: View,
+    V: View,
+    F: Fn(&R) -> V + 'static,
+{
+    NavigationStack {
+        routes,
+        root,
+        destination,
+        slide: false,
+        phantom: Default::default(),
+    }
+}
+
+impl<R, B, V0, V, F> NavigationStack<R, B, V0, F>
+where
+    R: Clone + Hash + 'static,
+    B: Binding<Vec<R>>,
+    V0: View,
+    V: View,
+    F: Fn(&R) -> V + 'static,
+{
+    /// Slides destinations in from the trailing edge when pushed, and back
+    /// out when popped.
+    pub fn slide_transition(self) -> Self {
+        Self {
+            slide: true,
+            ..self
+        }
+    }
+
+    fn bar(&self) -> impl View {
+        let routes = self.routes;
+        hstack((
+            button("Back", move |cx| {
+                routes.with_mut(cx, |routes| {
+                    routes.pop();
+                })
+            }),
+            spacer(),
+        ))
+        .padding(Auto)
+    }
+
+    fn has_bar(&self, cx: &Context) -> bool {
+        !self.routes.get(cx).is_empty()
+    }
+
+    /// Calls `f` with the root, for `None`, or the destination for a route
+    /// and how deep it is.
+    fn with_level<T>(
+        &self,
+        level: &Option<(usize, R)>,
+        path: &mut IdPath,
+        f: impl FnOnce(&dyn View, &mut IdPath) -> T,
+    ) -> T {
+        match level {
+            None => {
+                path.push(0);
+                let t = f(&self.root, path);
+                path.pop();
+                t
+            }
+            Some((depth, route)) => {
+                path.push(hh(&(depth, route)));
+                let t = f(&(self.destination)(route), path);
+                path.pop();
+                t
+            }
+        }
+    }
+
+    /// What's shown and where, from the bottom up. The last is the one
+    /// which gets events.
+    fn layers(&self, vid: ViewId, cx: &mut Context) -> Vec<(Option<(usize, R)>, LocalOffset)> {
+        let routes = self.routes.get(cx).clone();
+        let depth = routes.len();
+        let top = routes.last().map(|route| (depth, route.clone()));
+        let rtl = cx.layout_direction().is_rtl();
+
+        let state = nav_state::<R>(vid, cx);
+        if state.progress >= 1.0 {
+            return vec![(top, LocalOffset::zero())];
+        }
+        let t = state.progress * state.progress * (3.0 - 2.0 * state.progress);
+        let width = if rtl {
+            -state.size.width
+        } else {
+            state.size.width
+        };
+        match state.leaving.clone() {
+            Some(leaving) => vec![
+                (top, [-PARALLAX * width * (1.0 - t), 0.0].into()),
+                (Some(leaving), [width * t, 0.0].into()),
+            ],
+            None => {
+                let below = (depth >= 2).then(|| (depth - 1, routes[depth - 2].clone()));
+                vec![
+                    (below, [-PARALLAX * width * t, 0.0].into()),
+                    (top, [width * (1.0 - t), 0.0].into()),
+                ]
+            }
+        }
+    }
+
+    /// Notices routes being pushed and popped, and runs the slide.
+    fn animate(&self, vid: ViewId, cx: &mut Context) {
+        let dt = cx.anim_interval();
+
+        let routes = self.routes.get(cx);
+        let depth = routes.len();
+        let top = routes.last().cloned();
+
+        let state = nav_state::<R>(vid, cx);
+        let previous = state.depth.replace(depth);
+        let old_top = std::mem::replace(&mut state.top, top);
+        let mut moving = state.progress < 1.0;
+
+        if let Some(previous) = previous.filter(|previous| *previous != depth) {
+            if self.slide && state.size.width > 0.0 {
+                state.progress = 0.0;
+                state.leaving = old_top.filter(|_| depth < previous).map(|r| (previous, r));
+                moving = true;
+            }
+        }
+
+        if moving {
+            let mut state = nav_state::<R>(vid, cx).clone();
+            state.progress = (state.progress + dt / SLIDE_DURATION).min(1.0);
+            if state.progress >= 1.0 {
+                state.leaving = None;
+            }
+            cx[StateHandle::<NavState<R>>::new(vid)] = state;
+        }
+    }
+}
+
+impl<R, B, V0, V, F> View for NavigationStack<R, B, V0, F>
+where
+    R: Clone + Hash + 'static,
+    B: Binding<Vec<R>>,
+    V0: View,
+    V: View,
+    F: Fn(&R) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        match event {
+            Event::Anim => self.animate(vid, cx),
+            // Not on repeats, so holding a key goes back one screen.
+            Event::Key(k) if self.has_bar(cx) && cx.focused_id.is_none() && !k.is_repeat() => {
+                let back = match k.pressed() {
+                    Some(Key::Escape) => true,
+                    Some(Key::ArrowLeft) => k.mods.alt,
+                    _ => false,
+                };
+                if back {
+                    self.routes.with_mut(cx, |routes| {
+                        routes.pop();
+                    });
+                    return;
+                }
+            }
+            _ => (),
+        }
+
+        if self.has_bar(cx) {
+            let bar = nav_state::<R>(vid, cx).bar;
+            path.push(1);
+            self.bar().process(&event.offset(-bar), path, cx, actions);
+            path.pop();
+        }
+
+        if let Some((level, offset)) = self.layers(vid, cx).pop() {
+            self.with_level(&level, path, |view, path| {
+                view.process(&event.offset(-offset), path, cx, actions)
+            });
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let layers = self.layers(vid, args.cx);
+        let state = nav_state::<R>(vid, args.cx);
+        let (bar, size) = (state.bar, state.size);
+
+        // Keep sliding views inside.
+        let sliding = layers.len() > 1;
+        if sliding {
+            let rect = LocalRect::new(LocalPoint::zero(), size);
+            args.vger.save();
+            args.vger.scissor(rect);
+            let world_rect = args.vger.current_transform().outer_transformed_rect(&rect);
+            args.cx.push_clip(world_rect);
+        }
+        for (level, offset) in &layers {
+            args.vger.save();
+            args.vger.translate(*offset);
+            self.with_level(level, path, |view, path| view.draw(path, args));
+            args.vger.restore();
+        }
+        if sliding {
+            args.cx.pop_clip();
+            args.vger.restore();
+        }
+
+        if self.has_bar(args.cx) {
+            args.vger.save();
+            args.vger.translate(bar);
+            path.push(1);
+            self.bar().draw(path, args);
+            path.pop();
+            args.vger.restore();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        let size = args.sz;
+
+        let mut bar_height = 0.0;
+        if self.has_bar(args.cx) {
+            path.push(1);
+            bar_height = self.bar().layout(path, args).height;
+            path.pop();
+        }
+
+        let content_size = LocalSize::new(size.width, (size.height - bar_height).max(0.0));
+        for (level, _) in self.layers(vid, args.cx) {
+            self.with_level(&level, path, |view, path| {
+                view.layout(path, &mut args.size(content_size))
+            });
+        }
+
+        let state = nav_state::<R>(vid, args.cx);
+        state.bar = [0.0, content_size.height].into();
+        state.size = content_size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        if self.has_bar(cx) {
+            let bar = nav_state::<R>(vid, cx).bar;
+            path.push(1);
+            self.bar().dirty(path, xform.pre_translate(bar), cx);
+            path.pop();
+        }
+        for (level, offset) in self.layers(vid, cx) {
+            self.with_level(&level, path, |view, path| {
+                view.dirty(path, xform.pre_translate(offset), cx)
+            });
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let vid = cx.view_id(path);
+        let mut hit = None;
+        for (level, offset) in self.layers(vid, cx) {
+            hit = self
+                .with_level(&level, path, |view, path| {
+                    view.hittest(path, pt - offset, cx)
+                })
+                .or(hit);
+        }
+        if self.has_bar(cx) {
+            let bar = nav_state::<R>(vid, cx).bar;
+            path.push(1);
+            hit = self.bar().hittest(path, pt - bar, cx).or(hit);
+            path.pop();
+        }
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let vid = cx.view_id(path);
+        if let Some((level, _)) = self.layers(vid, cx).pop() {
+            self.with_level(&level, path, |view, path| view.commands(path, cx, cmds));
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let vid = cx.view_id(path);
+        map.push(vid);
+        path.push(1);
+        self.bar().gc_view(path, cx, map);
+        path.pop();
+
+        // Keep the state of everything in the stack, and of a destination
+        // until it's finished sliding out.
+        let routes = self.routes.get(cx).clone();
+        let leaving = nav_state::<R>(vid, cx).leaving.clone();
+        let levels = std::iter::once(None)
+            .chain(
+                routes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, r)| Some((i + 1, r))),
+            )
+            .chain(leaving.map(Some));
+        for level in levels {
+            self.with_level(&level, path, |view, path| view.gc_view(path, cx, map));
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let vid = cx.view_id(path);
+        let mut children = vec![];
+        if self.has_bar(cx) {
+            path.push(1);
+            children.extend(self.bar().access(path, cx, nodes));
+            path.pop();
+        }
+        if let Some((level, _)) = self.layers(vid, cx).pop() {
+            children
+                .extend(self.with_level(&level, path, |view, path| view.access(path, cx, nodes)));
+        }
+
+        let aid = vid.access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::Group);
+        builder.set_children(children);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<R, B, V0, F> private::Sealed for NavigationStack<R, B, V0, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn app(slide: bool) -> impl View {
+        state(Vec::<String>::new, move |routes, _| {
+            let stack = navigation_stack(
+                routes,
+                vstack((
+                    button("open a", move |cx| cx[routes].push("a".into())),
+                    button("open b", move |cx| cx[routes].push("b".into())),
+                )),
+                move |route: &String| {
+                    let route = route.clone();
+                    state(
+                        || 0,
+                        move |n, cx| {
+                            vstack((
+                                text(&format!("{} {}", route, cx[n])),
+                                button("more", move |cx| cx[n] += 1),
+                                button("deeper", move |cx| cx[routes].push("c".into())),
+                            ))
+                        },
+                    )
+                },
+            );
+            if slide {
+                any_view(stack.slide_transition())
+            } else {
+                any_view(stack)
+            }
+        })
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut h = Harness::new(app(false));
+        h.click("open a");
+        h.expect_text("a 0");
+        h.expect_no_text("open a");
+
+        // Back and forth, a destination starts again.
+        h.click("more");
+        h.expect_text("a 1");
+        h.click("Back");
+        h.expect_text("open a");
+        h.click("open a");
+        h.expect_text("a 0");
+
+        // Those underneath keep their state.
+        h.click("more");
+        h.click("deeper");
+        h.expect_text("c 0");
+        // Holding escape only goes back once.
+        h.hold_key(Key::Escape, 3);
+        h.expect_text("a 1");
+
+        h.key_with_mods(
+            Key::ArrowLeft,
+            KeyboardModifiers {
+                alt: true,
+                ..Default::default()
+            },
+        );
+        h.expect_text("open b");
+        h.key(Key::Escape);
+        h.expect_text("open b");
+    }
+
+    #[test]
+    fn test_slide() {
+        let mut h = Harness::new(app(true));
+        let button = h.find_text("open a").unwrap();
+        h.click("open a");
+        h.update();
+
+        // The destination comes in from the right, over the root.
+        let a = h.find_text("a 0").unwrap();
+        assert!(a.origin.x > button.origin.x);
+        for _ in 0..30 {
+            h.update();
+        }
+        h.expect_no_text("open a");
+        let settled = h.find_text("a 0").unwrap();
+        assert!(settled.origin.x < a.origin.x);
+
+        // It slides back out when popped.
+        h.click("Back");
+        h.update();
+        h.expect_text("open a");
+        assert!(h.find_text("a 0").unwrap().origin.x > settled.origin.x);
+        for _ in 0..30 {
+            h.update();
+        }
+        h.expect_no_text("a 0");
+    }
+}
@@ -4,7 +4,14 @@ use std::any::Any;
 /// Struct for the `padding` modifier.
 pub struct Padding<V> {
     child: V,
-    padding: f32,
+    padding: Edges,
+}
+
+/// Padding for each edge, or for leading and trailing ones which depend
+/// on the layout direction.
+enum Edges {
+    Fixed(Insets),
+    Directional(DirectionalInsets),
 }
 
 impl<V> View for Padding<V>
@@ -18,7 +25,7 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
-        let off = LocalOffset::new(self.padding, self.padding);
+        let off = self.offset(cx);
         path.push(0);
         self.child.process(&event.offset(-off), path, cx, actions);
         path.pop();
@@ -26,7 +33,8 @@ where
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         args.vger.save();
-        args.vger.translate([self.padding, self.padding]);
+        let off = self.offset(args.cx);
+        args.vger.translate(off);
         path.push(0);
         self.child.draw(path, args);
         path.pop();
@@ -35,29 +43,23 @@ where
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
         path.push(0);
-        let child_size = self.child.layout(
-            path,
-            &mut args.size(args.sz - [2.0 * self.padding, 2.0 * self.padding].into()),
-        );
+        let total = self.total(args.cx);
+        let child_size = self.child.layout(path, &mut args.size(args.sz - total));
         path.pop();
-        child_size + LocalSize::new(2.0 * self.padding, 2.0 * self.padding)
+        child_size + total
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
         path.push(0);
-        self.child.dirty(
-            path,
-            xform.pre_translate([self.padding, self.padding].into()),
-            cx,
-        );
+        let off = self.offset(cx);
+        self.child.dirty(path, xform.pre_translate(off), cx);
         path.pop();
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
         path.push(0);
-        let hit_id =
-            self.child
-                .hittest(path, pt - LocalOffset::new(self.padding, self.padding), cx);
+        let off = self.offset(cx);
+        let hit_id = self.child.hittest(path, pt - off, cx);
         path.pop();
         hit_id
     }
@@ -70,7 +72,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -90,6 +92,8 @@ where
 pub enum PaddingParam {
     Auto,
     Px(f32),
+    Insets(Insets),
+    Directional(DirectionalInsets),
 }
 pub struct Auto;
 impl From<Auto> for PaddingParam {
@@ -102,6 +106,17 @@ impl From<f32> for PaddingParam {
         PaddingParam::Px(val)
     }
 }
+impl From<Insets> for PaddingParam {
+    fn from(val: Insets) -> Self {
+        PaddingParam::Insets(val)
+    }
+}
+
+impl From<DirectionalInsets> for PaddingParam {
+    fn from(val: DirectionalInsets) -> Self {
+        PaddingParam::Directional(val)
+    }
+}
 
 impl<V> Padding<V>
 where
@@ -111,11 +126,32 @@ where
         Self {
             child,
             padding: match param {
-                PaddingParam::Auto => 5.0,
-                PaddingParam::Px(px) => px,
+                PaddingParam::Auto => Edges::Fixed(Insets::uniform(5.0)),
+                PaddingParam::Px(px) => Edges::Fixed(Insets::uniform(px)),
+                PaddingParam::Insets(insets) => Edges::Fixed(insets),
+                PaddingParam::Directional(insets) => Edges::Directional(insets),
             },
         }
     }
+
+    fn insets(&self, cx: &mut Context) -> Insets {
+        match &self.padding {
+            Edges::Fixed(insets) => *insets,
+            Edges::Directional(insets) => insets.resolve(cx.layout_direction()),
+        }
+    }
+
+    /// Offset of the child from our origin.
+    fn offset(&self, cx: &mut Context) -> LocalOffset {
+        let insets = self.insets(cx);
+        LocalOffset::new(insets.left, insets.bottom)
+    }
+
+    /// Space taken up by the padding.
+    fn total(&self, cx: &mut Context) -> LocalSize {
+        let insets = self.insets(cx);
+        LocalSize::new(insets.horizontal(), insets.vertical())
+    }
 }
 
 impl<V> private::Sealed for Padding<V> {}
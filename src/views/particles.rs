@@ -0,0 +1,593 @@
+use crate::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use wgpu::util::DeviceExt;
+
+/// Bursts made so far, so each one without a set seed gets its own.
+static BURSTS: AtomicU32 = AtomicU32::new(0);
+
+/// Floats per particle instance: center, radius and pixels per point,
+/// color, and transform.
+const PARTICLE_FLOATS: usize = 16;
+
+/// Configuration for `particles`.
+#[derive(Clone)]
+pub struct ParticleConfig {
+    /// Number of particles emitted per burst.
+    pub count: usize,
+
+    /// How long each particle lives, in seconds.
+    pub lifetime: f32,
+
+    /// Initial speed of particles, in points per second.
+    pub speed: f32,
+
+    /// Downward acceleration, in points per second squared.
+    pub gravity: f32,
+
+    /// Radius of each particle.
+    pub size: f32,
+
+    /// Particles pick a color from this list.
+    pub colors: Vec<Color>,
+
+    /// Emit a new burst once all particles have died.
+    pub repeat: bool,
+
+    /// Seed for where particles go, for bursts which look the same each
+    /// time. Without one, each burst is different.
+    pub seed: Option<u32>,
+}
+
+impl Default for ParticleConfig {
+    fn default() -> Self {
+        Self {
+            count: 100,
+            lifetime: 2.0,
+            speed: 300.0,
+            gravity: 400.0,
+            size: 3.0,
+            colors: vec![RED_HIGHLIGHT, AZURE_HIGHLIGHT, GREEN_HIGHLIGHT, TEXT_COLOR],
+            repeat: false,
+            seed: None,
+        }
+    }
+}
+
+impl ParticleConfig {
+    /// Sets the number of particles per burst.
+    pub fn count(self, count: usize) -> Self {
+        Self { count, ..self }
+    }
+
+    /// Sets the lifetime of each particle in seconds.
+    pub fn lifetime(self, lifetime: f32) -> Self {
+        Self { lifetime, ..self }
+    }
+
+    /// Sets the initial particle speed.
+    pub fn speed(self, speed: f32) -> Self {
+        Self { speed, ..self }
+    }
+
+    /// Sets the downward acceleration.
+    pub fn gravity(self, gravity: f32) -> Self {
+        Self { gravity, ..self }
+    }
+
+    /// Sets the particle radius.
+    pub fn size(self, size: f32) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Sets the palette particles are drawn from.
+    pub fn colors(self, colors: Vec<Color>) -> Self {
+        Self { colors, ..self }
+    }
+
+    /// Keep emitting bursts instead of stopping after the first.
+    pub fn repeat(self) -> Self {
+        Self {
+            repeat: true,
+            ..self
+        }
+    }
+
+    /// Sets the seed for where particles go.
+    pub fn seed(self, seed: u32) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    /// Position relative to the emitter.
+    position: LocalOffset,
+    velocity: LocalOffset,
+    age: f32,
+    color: Color,
+}
+
+/// Simulation state for `particles`.
+struct ParticleSystem {
+    config: ParticleConfig,
+    particles: Vec<Particle>,
+    seed: u32,
+}
+
+impl ParticleSystem {
+    fn new(config: ParticleConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| {
+            // Spread the burst count out, as nearby xorshift seeds start
+            // out alike.
+            let n = BURSTS.fetch_add(1, Ordering::Relaxed);
+            n.wrapping_add(1).wrapping_mul(0x9E37_79B9)
+        });
+        let mut system = Self {
+            config,
+            particles: vec![],
+            // Xorshift gets stuck at zero.
+            seed: seed.max(1),
+        };
+        system.emit();
+        system
+    }
+
+    /// Cheap xorshift so we don't need a dependency on rand.
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed as f32) / (u32::MAX as f32)
+    }
+
+    fn emit(&mut self) {
+        self.particles.clear();
+        for _ in 0..self.config.count {
+            // Fire upwards in a cone.
+            let theta = std::f32::consts::FRAC_PI_2 + (self.random() - 0.5) * 1.5;
+            let speed = self.config.speed * (0.5 + 0.5 * self.random());
+            let color = if self.config.colors.is_empty() {
+                TEXT_COLOR
+            } else {
+                let i = (self.random() * self.config.colors.len() as f32) as usize;
+                self.config.colors[i.min(self.config.colors.len() - 1)]
+            };
+            self.particles.push(Particle {
+                position: LocalOffset::zero(),
+                velocity: LocalOffset::new(theta.cos(), theta.sin()) * speed,
+                age: 0.0,
+                color,
+            });
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// The live particles around `center`, faded with age, to draw with
+    /// `xform`, or `None` if there aren't any.
+    fn batch(&self, center: LocalPoint, xform: LocalToWorld) -> Option<ParticleBatch> {
+        if self.particles.is_empty() {
+            return None;
+        }
+        let lifetime = self.config.lifetime;
+        let radius = self.config.size;
+        let particles: Vec<_> = self
+            .particles
+            .iter()
+            .map(|p| {
+                let fade = 1.0 - (p.age / lifetime).min(1.0);
+                (center + p.position, p.color.alpha(p.color.a * fade))
+            })
+            .collect();
+        // Antialiasing goes a little past the radius.
+        let bounds = rect_bounds(particles.iter().map(|(center, _)| {
+            LocalRect::new(*center, LocalSize::zero()).inflate(radius + 1.0, radius + 1.0)
+        }));
+        Some(ParticleBatch {
+            particles,
+            radius,
+            xform,
+            bounds: xform.outer_transformed_rect(&bounds),
+            clip: None,
+            marker: None,
+        })
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f32) {
+        for p in &mut self.particles {
+            p.velocity.y -= self.config.gravity * dt;
+            p.position += p.velocity * dt;
+            p.age += dt;
+        }
+
+        let lifetime = self.config.lifetime;
+        self.particles.retain(|p| p.age < lifetime);
+
+        if self.particles.is_empty() && self.config.repeat {
+            self.emit();
+        }
+    }
+}
+
+/// Particle effect for confetti and other flourishes. Particles are
+/// emitted from the center of the view and fall under gravity.
+///
+/// Particles are drawn as instances of one quad, in a single draw call
+/// for each `particles` view between vger's passes.
+pub fn particles(config: ParticleConfig) -> impl View {
+    state(
+        move || ParticleSystem::new(config.clone()),
+        move |system, _| {
+            painter_canvas(move |cx, rect, vger| {
+                let xform = vger.current_transform();
+                let Some(batch) = cx[system].batch(rect.center(), xform) else {
+                    return;
+                };
+                let batch = ParticleBatch {
+                    clip: cx.pending_clip(vger),
+                    marker: cx.gpu_markers.current(),
+                    ..batch
+                };
+                cx.draw_particles(vger, batch);
+            })
+            .anim(move |cx, dt| {
+                if !cx[system].is_done() {
+                    cx[system].step(dt);
+                }
+            })
+        },
+    )
+}
+
+/// Particles of a `particles` view to draw, collected while drawing.
+pub(crate) struct ParticleBatch {
+    /// Center and color of each particle, in the view's coordinates.
+    pub particles: Vec<(LocalPoint, Color)>,
+
+    pub radius: f32,
+
+    /// From the view's coordinates to those of the layer it's drawn in.
+    pub xform: LocalToWorld,
+
+    /// Bounds of the particles, in the same coordinates as `clip`.
+    pub bounds: WorldRect,
+
+    /// Innermost clip the particles were drawn in, in the coordinates of
+    /// the layer.
+    pub clip: Option<WorldRect>,
+
+    /// Debug group the particles were drawn in.
+    pub marker: Option<String>,
+}
+
+const PARTICLE_SHADER: &str = r#"
+struct ParticleUniforms {
+    screen: vec2<f32>,
+    height: f32,
+    scale: f32,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: ParticleUniforms;
+
+struct ParticleInstance {
+    // Center, radius, and pixels per point of the view.
+    @location(0) circle: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) linear: vec4<f32>,
+    @location(3) offset: vec4<f32>,
+};
+
+struct ParticleVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // From the center, in pixels.
+    @location(0) local: vec2<f32>,
+    @location(1) radius: f32,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn particle_vs(@builtin(vertex_index) index: u32, particle: ParticleInstance) -> ParticleVertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u)) * 2.0 - 1.0;
+    // A pixel past the radius, for antialiasing.
+    let extent = particle.circle.z + 1.0 / particle.circle.w;
+    let p = particle.circle.xy + corner * extent;
+    let world = vec2<f32>(
+        particle.linear.x * p.x + particle.linear.z * p.y + particle.offset.x,
+        particle.linear.y * p.x + particle.linear.w * p.y + particle.offset.y
+    );
+    // World space is y-up in points, the target is y-down in pixels.
+    let px = vec2<f32>(world.x, uniforms.height - world.y) * uniforms.scale;
+    var out: ParticleVertexOutput;
+    out.position = vec4<f32>(
+        px.x / uniforms.screen.x * 2.0 - 1.0,
+        1.0 - px.y / uniforms.screen.y * 2.0,
+        0.0,
+        1.0
+    );
+    out.local = corner * extent * particle.circle.w;
+    out.radius = particle.circle.z * particle.circle.w;
+    out.color = particle.color;
+    return out;
+}
+
+@fragment
+fn particle_fs(in: ParticleVertexOutput) -> @location(0) vec4<f32> {
+    // Distance outside the circle, antialiased over a pixel.
+    let d = length(in.local) - in.radius;
+    return vec4<f32>(in.color.rgb, in.color.a * clamp(0.5 - d, 0.0, 1.0));
+}
+"#;
+
+/// Renders `particles` views. Created lazily the first time particles
+/// are drawn.
+pub(crate) struct ParticleRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ParticleRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rui particle bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rui particle pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rui particle shader"),
+            source: wgpu::ShaderSource::Wgsl(PARTICLE_SHADER.into()),
+        });
+
+        let attributes = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+        ];
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rui particle pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "particle_vs",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: (PARTICLE_FLOATS * 4) as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &attributes,
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "particle_fs",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Draws the batches over `target`, from one instance buffer.
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        batches: &[ParticleBatch],
+        size: euclid::Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        let window = WorldRect::new(WorldPoint::zero(), size);
+        let mut data: Vec<f32> = vec![];
+        let mut draws = vec![];
+        for batch in batches {
+            let clip = batch.clip.unwrap_or(window);
+            if !batch.bounds.intersects(&clip) {
+                continue;
+            }
+            let start = data.len() / PARTICLE_FLOATS;
+            let m = &batch.xform;
+            let pixels = scale * (m.m11 * m.m22 - m.m12 * m.m21).abs().sqrt();
+            if pixels == 0.0 {
+                continue;
+            }
+            for (center, c) in &batch.particles {
+                data.extend([
+                    center.x,
+                    center.y,
+                    batch.radius,
+                    pixels,
+                    c.r,
+                    c.g,
+                    c.b,
+                    c.a,
+                    m.m11,
+                    m.m12,
+                    m.m21,
+                    m.m22,
+                    m.m31,
+                    m.m32,
+                    0.0,
+                    0.0,
+                ]);
+            }
+            let end = data.len() / PARTICLE_FLOATS;
+            if end > start {
+                draws.push((start as u32..end as u32, clip, &batch.marker));
+            }
+        }
+        if draws.is_empty() {
+            return;
+        }
+
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let instances = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("rui particle instances"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let uniforms = [size.width * scale, size.height * scale, size.height, scale];
+        let bytes: Vec<u8> = uniforms.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("rui particle uniforms"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rui particle bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui particle encoder"),
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rui particle pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, &bind_group, &[]);
+            rpass.set_vertex_buffer(0, instances.slice(..));
+            for (range, clip, marker) in draws {
+                let Some((x, y, w, h)) = scissor_px(&clip, size, scale) else {
+                    continue;
+                };
+                if let Some(marker) = marker {
+                    rpass.push_debug_group(marker);
+                }
+                rpass.set_scissor_rect(x, y, w, h);
+                rpass.draw(0..4, range);
+                if marker.is_some() {
+                    rpass.pop_debug_group();
+                }
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_particles_expire() {
+        let mut system = ParticleSystem::new(ParticleConfig::default().count(10).lifetime(1.0));
+        assert_eq!(system.particles.len(), 10);
+
+        system.step(0.5);
+        assert_eq!(system.particles.len(), 10);
+
+        system.step(0.6);
+        assert!(system.is_done());
+    }
+
+    #[test]
+    fn test_particles_seed() {
+        let positions = |config: ParticleConfig| {
+            let mut system = ParticleSystem::new(config.count(10));
+            system.step(0.1);
+            system
+                .particles
+                .iter()
+                .map(|p| p.position)
+                .collect::<Vec<_>>()
+        };
+        let config = ParticleConfig::default();
+        assert_ne!(positions(config.clone()), positions(config.clone()));
+        assert_eq!(positions(config.clone().seed(7)), positions(config.seed(7)));
+    }
+
+    #[test]
+    fn test_particles_batch() {
+        let system = ParticleSystem::new(ParticleConfig::default().count(3).size(2.0));
+        let xform = LocalToWorld::translation(1.0, 0.0);
+        let batch = system.batch(LocalPoint::new(10.0, 20.0), xform).unwrap();
+        assert_eq!(batch.particles.len(), 3);
+        assert_eq!(batch.particles[0].0, LocalPoint::new(10.0, 20.0));
+        assert_eq!(
+            batch.bounds,
+            WorldRect::new(WorldPoint::new(8.0, 17.0), euclid::Size2D::new(6.0, 6.0))
+        );
+
+        let mut system = system;
+        system.step(10.0);
+        assert!(system.batch(LocalPoint::zero(), xform).is_none());
+    }
+
+    #[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_particles_draw() {
+        // A burst starts out at the center of the view.
+        let view = particles(
+            ParticleConfig::default()
+                .count(5)
+                .size(4.0)
+                .colors(vec![Color::new(0.0, 0.0, 1.0, 1.0)])
+                .seed(1),
+        );
+        let image = test_render(&view, Size::new(40.0, 40.0));
+        assert_eq!(image.get_pixel(20, 20).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(10, 10).0[2], 0);
+    }
+
+    #[test]
+    fn test_particles_repeat() {
+        let mut system =
+            ParticleSystem::new(ParticleConfig::default().count(10).lifetime(1.0).repeat());
+
+        system.step(1.1);
+        assert_eq!(system.particles.len(), 10);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::Any;
+
+struct PersistentStateView<D, F> {
+    key: String,
+    state: StateView<D, F>,
+}
+
+impl<S, V, D, F> PersistentStateView<D, F>
+where
+    V: View,
+    S: Serialize + DeserializeOwned + 'static,
+    D: Fn() -> S + 'static,
+    F: Fn(StateHandle<S>, &Context) -> V + 'static,
+{
+    /// Initializes the state from the store if it hasn't been created yet.
+    fn restore(&self, path: &IdPath, cx: &mut Context) {
+        let id = cx.view_id(path);
+        if !cx.state_map.contains_key(&id) {
+            let value = cx
+                .persistent
+                .get::<S>(&self.key)
+                .unwrap_or_else(&self.state.default);
+            cx.set_state(id, value);
+        }
+    }
+
+    /// Copies the state into the store if it has changed.
+    fn store(&self, path: &IdPath, cx: &mut Context) {
+        let id = cx.view_id(path);
+        if cx.is_dirty(id) {
+            let value = serde_json::to_value(cx.get(StateHandle::<S>::new(id)));
+            if let Ok(value) = value {
+                cx.persistent.set(&self.key, &value);
+            }
+        }
+    }
+}
+
+impl<S, V, D, F> View for PersistentStateView<D, F>
+where
+    V: View,
+    S: Serialize + DeserializeOwned + 'static,
+    D: Fn() -> S + 'static,
+    F: Fn(StateHandle<S>, &Context) -> V + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        self.restore(path, cx);
+        self.state.process(event, path, cx, actions);
+        self.store(path, cx);
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        self.restore(path, args.cx);
+        self.state.draw(path, args);
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        self.restore(path, args.cx);
+        let sz = self.state.layout(path, args);
+        self.store(path, args.cx);
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        self.restore(path, cx);
+        self.state.dirty(path, xform, cx);
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        self.restore(path, cx);
+        self.state.hittest(path, pt, cx)
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        self.restore(path, cx);
+        self.state.commands(path, cx, cmds);
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        self.restore(path, cx);
+        self.state.gc_view(path, cx, map);
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        self.restore(path, cx);
+        self.state.access(path, cx, nodes)
+    }
+}
+
+impl<D, F> private::Sealed for PersistentStateView<D, F> {}
+
+/// Like `state`, but the value is saved to disk when it changes and
+/// restored the next time the app starts.
+///
+/// `key` identifies the value in the saved file, so it must be unique
+/// within the app. If there's no saved value, or it can't be read as an
+/// `S`, `initial` is used.
+///
+/// See `Context::set_persistence_path` for where values are stored.
+pub fn persistent_state<
+    S: Serialize + DeserializeOwned + 'static,
+    V: View,
+    D: Fn() -> S + 'static,
+    F: Fn(StateHandle<S>, &Context) -> V + 'static,
+>(
+    key: &str,
+    initial: D,
+    f: F,
+) -> impl View {
+    PersistentStateView {
+        key: key.to_string(),
+        state: StateView {
+            default: initial,
+            func: f,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_persistent_state() {
+        let mut cx = Context::new();
+        cx.persistent.set("count", &3);
+
+        let ui = persistent_state(
+            "count",
+            || 0,
+            |count, _| button("increment", move |cx| cx[count] += 1),
+        );
+
+        let mut path = vec![0];
+        let mut actions = vec![];
+        ui.process(&Event::Anim, &mut path, &mut cx, &mut actions);
+
+        // The saved value is used instead of the default.
+        let count = StateHandle::<i32>::new(cx.view_id(&path));
+        assert_eq!(cx[count], 3);
+
+        cx[count] += 1;
+        ui.process(&Event::Anim, &mut path, &mut cx, &mut actions);
+
+        assert_eq!(cx.persistent.get::<i32>("count"), Some(4));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::*;
+use std::any::Any;
+
+/// Two fingers moving together, as reported by the `pinch` gesture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PinchGesture {
+    /// Distance between the fingers, relative to when the gesture began.
+    pub scale: f32,
+
+    /// How far the point between the fingers moved since the last call,
+    /// for panning with two fingers.
+    pub delta: LocalOffset,
+
+    /// The point between the fingers, in the view's coordinates.
+    pub center: LocalPoint,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct PinchState {
+    /// Touches which went down in the view, and where they are.
+    touches: [Option<(usize, LocalPoint)>; 2],
+
+    /// Distance between the fingers when the gesture began.
+    start_distance: f32,
+
+    /// Where the point between the fingers was last reported.
+    center: LocalPoint,
+
+    active: bool,
+}
+
+impl PinchState {
+    fn slot(&self, id: usize) -> Option<usize> {
+        self.touches
+            .iter()
+            .position(|t| matches!(t, Some((i, _)) if *i == id))
+    }
+
+    /// Center and distance of the two fingers, if both are down.
+    fn fingers(&self) -> Option<(LocalPoint, f32)> {
+        match self.touches {
+            [Some((_, a)), Some((_, b))] => Some((a.lerp(b, 0.5), (b - a).length())),
+            _ => None,
+        }
+    }
+}
+
+fn pinch_state(vid: ViewId, cx: &mut Context) -> &mut PinchState {
+    cx.init_state(vid, &PinchState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for the `pinch` gesture.
+pub struct Pinch<V, F> {
+    child: V,
+    func: F,
+}
+
+impl<V, F> Pinch<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, PinchGesture, GestureState) + 'static,
+{
+    pub fn new(child: V, func: F) -> Self {
+        Self { child, func }
+    }
+}
+
+impl<V, F> View for Pinch<V, F>
+where
+    V: View,
+    F: Fn(&mut Context, PinchGesture, GestureState) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        let vid = cx.view_id(path);
+        match event {
+            Event::TouchBegin { id, position } if cx.get_layout(path).rect.contains(*position) => {
+                let state = pinch_state(vid, cx);
+                if state.active {
+                    return;
+                }
+                if let Some(slot) = state.touches.iter().position(|t| t.is_none()) {
+                    state.touches[slot] = Some((*id, *position));
+                }
+                if let Some((center, distance)) = state.fingers() {
+                    state.active = true;
+                    state.start_distance = distance.max(1.0);
+                    state.center = center;
+
+                    // Take both touches, so views inside stop following
+                    // the first finger and don't see it lift as a tap.
+                    let touches = state.touches;
+                    for (id, _) in touches.iter().flatten() {
+                        cx.touches[*id] = vid;
+                    }
+                    let gesture = PinchGesture {
+                        scale: 1.0,
+                        delta: LocalOffset::zero(),
+                        center,
+                    };
+                    (self.func)(cx, gesture, GestureState::Began);
+                }
+            }
+            Event::TouchMove { id, position, .. } => {
+                let state = pinch_state(vid, cx);
+                let Some(slot) = state.slot(*id) else {
+                    return;
+                };
+                state.touches[slot] = Some((*id, *position));
+                if !state.active {
+                    return;
+                }
+                let (center, distance) = state.fingers().unwrap();
+                let gesture = PinchGesture {
+                    scale: distance / state.start_distance,
+                    delta: center - state.center,
+                    center,
+                };
+                state.center = center;
+                (self.func)(cx, gesture, GestureState::Changed);
+            }
+            Event::TouchEnd { id, .. } => {
+                if cx.touches[*id] == vid {
+                    cx.touches[*id] = ViewId::default();
+                }
+                let state = pinch_state(vid, cx);
+                let Some(slot) = state.slot(*id) else {
+                    return;
+                };
+                let ended = state
+                    .fingers()
+                    .filter(|_| state.active)
+                    .map(|(center, distance)| PinchGesture {
+                        scale: distance / state.start_distance,
+                        delta: LocalOffset::zero(),
+                        center,
+                    });
+                state.touches[slot] = None;
+                // The other finger stays ours until it lifts too.
+                state.active = false;
+                if let Some(gesture) = ended {
+                    (self.func)(cx, gesture, GestureState::Ended);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, F> private::Sealed for Pinch<V, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_pinch() {
+        let mut h = Harness::with_size(
+            state(
+                || (1.0f32, LocalOffset::zero(), 0),
+                |zoom, _| {
+                    button("tap", move |cx| cx[zoom].2 += 1)
+                        .padding(Auto)
+                        .background(rectangle())
+                        .pinch(move |cx, gesture, state| {
+                            if state == GestureState::Changed {
+                                cx[zoom].0 = gesture.scale;
+                                cx[zoom].1 += gesture.delta;
+                            }
+                        })
+                },
+            ),
+            Size::new(200.0, 200.0),
+        );
+        let tap = h.find_text("tap").unwrap().center();
+
+        // Spread two fingers to twice the distance, moving right.
+        h.touch_begin(1, tap);
+        h.touch_begin(2, Point::new(tap.x + 10.0, tap.y));
+        h.touch_move(1, Point::new(tap.x - 5.0 + 20.0, tap.y));
+        h.touch_move(2, Point::new(tap.x + 15.0 + 20.0, tap.y));
+        h.touch_end(1);
+        h.touch_end(2);
+
+        let (scale, pan, taps) = h.states::<(f32, LocalOffset, i32)>()[0];
+        assert_eq!(scale, 2.0);
+        assert_eq!(pan, LocalOffset::new(20.0, 0.0));
+        // The button under the first finger didn't fire.
+        assert_eq!(taps, 0);
+
+        // One finger still taps.
+        h.touch_begin(3, tap);
+        h.touch_end(3);
+        assert_eq!(h.states::<(f32, LocalOffset, i32)>()[0].2, 1);
+    }
+}
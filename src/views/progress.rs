@@ -0,0 +1,302 @@
+use crate::*;
+use accesskit::Role;
+use std::any::Any;
+use std::f32::consts::PI;
+
+const BAR_HEIGHT: f32 = 4.0;
+
+/// Width of a bar when there's no width to fill.
+const BAR_DEFAULT_WIDTH: f32 = 100.0;
+
+const SPINNER_SIZE: f32 = 20.0;
+const SPINNER_LINE_WIDTH: f32 = 2.0;
+
+/// Seconds for a spinner to go round once.
+const SPINNER_PERIOD: f32 = 1.0;
+
+/// How much of the circle a spinner's arc covers.
+const SPINNER_ARC: f32 = 0.25;
+
+pub trait ProgressMods: View + Sized {
+    /// Color of the part done, or the spinning arc. Uses the theme's
+    /// accent color if not set.
+    fn fill_color(self, color: Color) -> Self;
+
+    /// Color behind the fill. Uses the theme's control background if not
+    /// set.
+    fn track_color(self, color: Color) -> Self;
+}
+
+#[derive(Clone, Copy, Default)]
+struct ProgressColors {
+    fill: Option<Color>,
+    track: Option<Color>,
+}
+
+impl ProgressColors {
+    fn paints(&self, cx: &Context, vger: &mut Painter) -> (PaintIndex, PaintIndex) {
+        let theme = cx.theme();
+        (
+            vger.color_paint(self.fill.unwrap_or(theme.accent)),
+            vger.color_paint(self.track.unwrap_or(theme.control_background)),
+        )
+    }
+}
+
+/// Struct for `progress_bar`.
+#[derive(Clone)]
+pub struct ProgressBar {
+    fraction: f32,
+    colors: ProgressColors,
+}
+
+/// A bar filled `fraction` of the way across, from 0 to 1, for tasks
+/// which know how much is left. Fills the width it's offered.
+pub fn progress_bar(fraction: f32) -> ProgressBar {
+    ProgressBar {
+        fraction: if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        },
+        colors: ProgressColors::default(),
+    }
+}
+
+impl ProgressMods for ProgressBar {
+    fn fill_color(mut self, color: Color) -> Self {
+        self.colors.fill = Some(color);
+        self
+    }
+
+    fn track_color(mut self, color: Color) -> Self {
+        self.colors.track = Some(color);
+        self
+    }
+}
+
+impl View for ProgressBar {
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        let (fill, track) = self.colors.paints(args.cx, args.vger);
+        let radius = rect.height() / 2.0;
+        args.vger.fill_rect(rect, radius, track);
+        if self.fraction > 0.0 {
+            let mut done = rect;
+            done.size.width *= self.fraction;
+            args.vger.fill_rect(done, radius, fill);
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let width = if args.sz.width.is_finite() {
+            args.sz.width
+        } else {
+            BAR_DEFAULT_WIDTH
+        };
+        let size = LocalSize::new(width, BAR_HEIGHT.min(args.sz.height));
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let rect = cx.get_layout(path).rect;
+        rect.contains(pt).then(|| cx.view_id(path))
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let aid = cx.view_id(path).access_id();
+        let mut builder = accesskit::NodeBuilder::new(Role::ProgressIndicator);
+        builder.set_numeric_value(self.fraction as f64);
+        builder.set_min_numeric_value(0.0);
+        builder.set_max_numeric_value(1.0);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl private::Sealed for ProgressBar {}
+
+/// How far round a spinner is, from 0 to 1.
+#[derive(Clone, Copy, Default)]
+struct SpinnerPhase(f32);
+
+/// Struct for `spinner`.
+#[derive(Clone)]
+pub struct Spinner {
+    colors: ProgressColors,
+}
+
+/// An arc going round and round, for tasks which don't know how much is
+/// left, such as a `load` inside a `suspense`. Keeps the window redrawing
+/// while it's shown.
+pub fn spinner() -> Spinner {
+    Spinner {
+        colors: ProgressColors::default(),
+    }
+}
+
+impl ProgressMods for Spinner {
+    fn fill_color(mut self, color: Color) -> Self {
+        self.colors.fill = Some(color);
+        self
+    }
+
+    fn track_color(mut self, color: Color) -> Self {
+        self.colors.track = Some(color);
+        self
+    }
+}
+
+impl View for Spinner {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        _actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Anim = event {
+            let s = StateHandle::<SpinnerPhase>::new(cx.view_id(path));
+            cx.init_state(s.id, &SpinnerPhase::default);
+            let phase = (cx[s].0 + cx.anim_interval() / SPINNER_PERIOD).fract();
+            cx[s].0 = phase;
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let rect = args.cx.get_layout(path).rect;
+        let s = StateHandle::<SpinnerPhase>::new(vid);
+        args.cx.init_state(vid, &SpinnerPhase::default);
+        let phase = args.cx[s].0;
+        let (fill, track) = self.colors.paints(args.cx, args.vger);
+
+        let center = rect.center();
+        let radius = (rect.width().min(rect.height()) - SPINNER_LINE_WIDTH) / 2.0;
+        args.vger
+            .stroke_arc(center, radius, SPINNER_LINE_WIDTH, 0.0, PI, track);
+
+        // Clockwise, so against the angle.
+        let rotation = PI / 2.0 - 2.0 * PI * phase;
+        args.vger.stroke_arc(
+            center,
+            radius,
+            SPINNER_LINE_WIDTH,
+            rotation,
+            SPINNER_ARC * PI,
+            fill,
+        );
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let side = SPINNER_SIZE.min(args.sz.width).min(args.sz.height);
+        let size = LocalSize::new(side, side);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        // Only the spinner needs redrawing as it goes round.
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(path, rect, xform);
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let rect = cx.get_layout(path).rect;
+        rect.contains(pt).then(|| cx.view_id(path))
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let aid = cx.view_id(path).access_id();
+        let builder = accesskit::NodeBuilder::new(Role::ProgressIndicator);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl private::Sealed for Spinner {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_progress_bar_layout() {
+        let mut cx = Context::new();
+        let mut path = vec![0];
+        let mut layout = |view: &ProgressBar, sz: LocalSize| {
+            view.layout(
+                &mut path,
+                &mut LayoutArgs {
+                    sz,
+                    cx: &mut cx,
+                    text_bounds: &mut |_, _, _| LocalRect::zero(),
+                },
+            )
+        };
+
+        let bar = progress_bar(1.5).fill_color(RED_HIGHLIGHT);
+        assert_eq!(bar.fraction, 1.0);
+        assert_eq!(
+            layout(&bar, [300.0, 200.0].into()),
+            LocalSize::new(300.0, BAR_HEIGHT)
+        );
+        assert_eq!(
+            layout(&progress_bar(f32::NAN), [f32::INFINITY, 200.0].into()),
+            LocalSize::new(BAR_DEFAULT_WIDTH, BAR_HEIGHT)
+        );
+    }
+
+    #[test]
+    fn test_spinner() {
+        let mut h = Harness::new(vstack((
+            spinner().track_color(CLEAR_COLOR),
+            text("loading"),
+        )));
+        let phase = |h: &Harness<_>| h.states::<SpinnerPhase>()[0].0;
+        let start = phase(&h);
+
+        h.advance(std::time::Duration::from_secs_f32(SPINNER_PERIOD / 4.0));
+        assert!((phase(&h) - start - 0.25).abs() < 0.01);
+
+        // Each frame only redraws the spinner.
+        let damage = h.damage().unwrap();
+        assert!(damage.width() <= SPINNER_SIZE + 2.0);
+        assert!(damage.height() <= SPINNER_SIZE + 2.0);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// A word, space or attachment placed by `layout_rich_text`.
+#[derive(Clone, Debug, PartialEq)]
+struct Fragment {
+    text: String,
+    style: TextStyle,
+    is_space: bool,
+    attachment: Option<Attachment>,
+    x: f32,
+    width: f32,
+    /// Distance of the baseline from the top of the view.
+    baseline: f32,
+}
+
+const LINE_SPACING: f32 = 1.2;
+
+/// Splits a word starting at byte `offset` of `text` into pieces of text
+/// and attachments. Lines can break between pieces.
+fn word_pieces<'a>(
+    text: &'a AttributedText,
+    word: &'a str,
+    offset: usize,
+) -> Vec<(&'a str, Option<&'a Attachment>)> {
+    let mut pieces = vec![];
+    let mut start = 0;
+    for (i, _) in word.match_indices(ATTACHMENT_CHAR) {
+        if let Some(attachment) = text.attachment_at(offset + i) {
+            if start < i {
+                pieces.push((&word[start..i], None));
+            }
+            let end = i + ATTACHMENT_CHAR.len_utf8();
+            pieces.push((&word[i..end], Some(attachment)));
+            start = end;
+        }
+    }
+    if start < word.len() {
+        pieces.push((&word[start..], None));
+    }
+    pieces
+}
+
+/// Breaks attributed text into lines no wider than `max_width`, wrapping
+/// at spaces and around attachments. Returns the placed fragments and the
+/// size of the text.
+fn layout_rich_text(
+    text: &AttributedText,
+    max_width: f32,
+    measure: &mut dyn FnMut(&str, u32, Option<f32>) -> LocalRect,
+) -> (Vec<Fragment>, LocalSize) {
+    let mut fragments: Vec<Fragment> = vec![];
+    let mut line_start = 0;
+    let mut line_height = 0.0f32;
+    let mut x = 0.0;
+    let mut top = 0.0;
+    let mut width = 0.0f32;
+
+    let mut end_line = |fragments: &mut Vec<Fragment>, line_start: usize, line_height: f32| {
+        // Trailing spaces don't take up room.
+        while fragments.len() > line_start && fragments.last().unwrap().is_space {
+            fragments.pop();
+        }
+        let line_width = fragments
+            .last()
+            .filter(|_| fragments.len() > line_start)
+            .map(|f| f.x + f.width)
+            .unwrap_or(0.0);
+        width = width.max(line_width);
+        let height = if line_height > 0.0 {
+            line_height
+        } else {
+            Text::DEFAULT_SIZE as f32
+        };
+        for f in &mut fragments[line_start..] {
+            f.baseline = top + height;
+        }
+        top += height * LINE_SPACING;
+    };
+
+    let mut offset = 0;
+    for run in text.runs() {
+        let style = run.style;
+        let space_width =
+            measure("x x", style.size, None).max_x() - measure("xx", style.size, None).max_x();
+
+        for (i, line) in run.text.split('\n').enumerate() {
+            if i > 0 {
+                end_line(&mut fragments, line_start, line_height);
+                line_start = fragments.len();
+                line_height = 0.0;
+                x = 0.0;
+                offset += 1;
+            }
+
+            for (j, word) in line.split(' ').enumerate() {
+                if j > 0 {
+                    offset += 1;
+                    if fragments.len() > line_start {
+                        fragments.push(Fragment {
+                            text: " ".into(),
+                            style,
+                            is_space: true,
+                            attachment: None,
+                            x,
+                            width: space_width,
+                            baseline: 0.0,
+                        });
+                        x += space_width;
+                    }
+                }
+                let word_offset = offset;
+                offset += word.len();
+
+                for (piece, attachment) in word_pieces(text, word, word_offset) {
+                    let (piece_width, piece_height) = match attachment {
+                        Some(a) => (a.size.width, a.size.height),
+                        None => (measure(piece, style.size, None).max_x(), style.size as f32),
+                    };
+                    let has_words = fragments[line_start..].iter().any(|f| !f.is_space);
+                    if has_words && x + piece_width > max_width {
+                        end_line(&mut fragments, line_start, line_height);
+                        line_start = fragments.len();
+                        line_height = 0.0;
+                        x = 0.0;
+                    }
+
+                    fragments.push(Fragment {
+                        text: piece.into(),
+                        style,
+                        is_space: false,
+                        attachment: attachment.cloned(),
+                        x,
+                        width: piece_width,
+                        baseline: 0.0,
+                    });
+                    x += piece_width;
+                    line_height = line_height.max(piece_height);
+                }
+            }
+        }
+    }
+    end_line(&mut fragments, line_start, line_height);
+
+    (fragments, LocalSize::new(width, top))
+}
+
+/// Struct for `rich_text`.
+pub struct RichText {
+    text: AttributedText,
+}
+
+/// Decoded attachment images for a `RichText`, by source key.
+type AttachmentImages = HashMap<u64, Option<ImageData>>;
+
+impl RichText {
+    /// Decodes attachment images the first time they're drawn.
+    fn attachment_image(
+        &self,
+        path: &IdPath,
+        cx: &mut Context,
+        attachment: &Attachment,
+    ) -> Option<ImageData> {
+        let id = cx.view_id(path);
+        if !cx.state_map.contains_key(&id) {
+            cx.set_state(id, AttachmentImages::new());
+        }
+        let handle = StateHandle::<AttachmentImages>::new(id);
+        cx[handle]
+            .entry(attachment.image.key())
+            .or_insert_with(|| attachment.image.load_for_view())
+            .clone()
+    }
+
+    fn draw_attachment(
+        &self,
+        path: &IdPath,
+        args: &mut DrawArgs,
+        attachment: &Attachment,
+        origin: LocalPoint,
+    ) {
+        let image = match self.attachment_image(path, args.cx, attachment) {
+            Some(image) => image,
+            None => return,
+        };
+        let bounds = LocalRect::new(origin, attachment.size);
+        let (dest, uv) = content_rects(ContentMode::Fit, image.size(), bounds);
+        if dest.is_empty() {
+            return;
+        }
+        let request = ImageRequest {
+            image,
+            rect: args.vger.current_transform().outer_transformed_rect(&dest),
+            uv,
+            clip: args.cx.current_clip(),
+            marker: args.cx.gpu_markers.current(),
+            mask: None,
+            gradient: None,
+        };
+        args.cx.draw_image(args.vger, request);
+    }
+}
+
+impl View for RichText {
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        if !args.cx.is_damaged(&rect, &args.vger.current_transform()) {
+            return;
+        }
+        let default_color = args.cx.theme().text;
+        let vger = &mut args.vger;
+        let (fragments, _) = layout_rich_text(&self.text, rect.width(), &mut |s, size, w| {
+            vger.text_bounds(s, size, w)
+        });
+
+        for f in &fragments {
+            let size = f.style.size as f32;
+            let color = f.style.color.unwrap_or(default_color);
+            let y = rect.height() - f.baseline;
+
+            if let Some(attachment) = &f.attachment {
+                self.draw_attachment(path, args, attachment, [f.x, y].into());
+                continue;
+            }
+
+            let vger = &mut args.vger;
+            if !f.is_space {
+                vger.save();
+                vger.translate([f.x, y]);
+                args.cx.snap_text(vger);
+                vger.text(&f.text, f.style.size, color, None);
+                if f.style.weight == FontWeight::Bold {
+                    // We only have one font, so embolden by overdrawing.
+                    vger.translate([size / 30.0, 0.0]);
+                    vger.text(&f.text, f.style.size, color, None);
+                }
+                vger.restore();
+            }
+
+            if f.style.underline {
+                let thickness = (size / 14.0).max(1.0);
+                let paint = vger.color_paint(color);
+                vger.fill_rect(
+                    LocalRect::new(
+                        [f.x, y - 0.1 * size - thickness].into(),
+                        [f.width, thickness].into(),
+                    ),
+                    0.0,
+                    paint,
+                );
+            }
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let (_, size) = layout_rich_text(&self.text, args.sz.width, args.text_bounds);
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        cx.probe_text(path, &self.text.plain_text(), pt);
+        None
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        if !self.text.attachments().is_empty() {
+            map.push(cx.view_id(path));
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let aid = cx.view_id(path).access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::LabelText);
+        builder.set_name(self.text.plain_text());
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl private::Sealed for RichText {}
+
+/// Shows text with mixed sizes, weights, colors and underlines, and inline
+/// images. The text wraps to the width offered by the parent.
+pub fn rich_text(text: impl Into<AttributedText>) -> RichText {
+    RichText { text: text.into() }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Each character is half the font size wide.
+    fn measure(text: &str, size: u32, _max_width: Option<f32>) -> LocalRect {
+        let width = text.chars().count() as f32 * size as f32 * 0.5;
+        LocalRect::new(LocalPoint::zero(), LocalSize::new(width, size as f32))
+    }
+
+    fn words(fragments: &[Fragment]) -> Vec<(&str, f32, f32)> {
+        fragments
+            .iter()
+            .filter(|f| !f.is_space)
+            .map(|f| (f.text.as_str(), f.x, f.baseline))
+            .collect()
+    }
+
+    #[test]
+    fn test_rich_text_runs() {
+        let text = AttributedText::new()
+            .run("ab ", TextStyle::default().size(10))
+            .run("cd", TextStyle::default().size(20));
+        let (fragments, size) = layout_rich_text(&text, 1000.0, &mut measure);
+
+        assert_eq!(words(&fragments), [("ab", 0.0, 20.0), ("cd", 15.0, 20.0)]);
+        assert_eq!(size, LocalSize::new(35.0, 24.0));
+    }
+
+    #[test]
+    fn test_rich_text_wraps() {
+        let text = AttributedText::from("aa bb cc").style_range(3..5, |s| s.size(10));
+        let default = Text::DEFAULT_SIZE as f32;
+        let (fragments, size) = layout_rich_text(&text, 2.5 * default, &mut measure);
+
+        // "bb" is small enough to fit next to "aa".
+        assert_eq!(
+            words(&fragments),
+            [
+                ("aa", 0.0, default),
+                ("bb", 1.5 * default, default),
+                ("cc", 0.0, default * LINE_SPACING + default),
+            ]
+        );
+        assert_eq!(size.width, 1.5 * default + 10.0);
+        assert_eq!(size.height, 2.0 * default * LINE_SPACING);
+    }
+
+    #[test]
+    fn test_rich_text_attachments() {
+        let image = ImageData::from_rgba(1, 1, vec![0; 4]);
+        let text = AttributedText::from("ab")
+            .attachment(image.clone(), [40.0, 30.0])
+            .run("c d", TextStyle::default())
+            .attachment(image, [40.0, 30.0]);
+        let default = Text::DEFAULT_SIZE as f32;
+        let (fragments, size) = layout_rich_text(&text, default + 40.0, &mut measure);
+
+        let second = 30.0 * LINE_SPACING + default;
+
+        // Attachments are placed like glyphs which lines can break around,
+        // and make their lines taller.
+        let placed: Vec<_> = fragments
+            .iter()
+            .filter(|f| !f.is_space)
+            .map(|f| (f.attachment.is_some(), f.x, f.baseline))
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                (false, 0.0, 30.0),
+                (true, default, 30.0),
+                (false, 0.0, second),
+                (false, default, second),
+                (
+                    true,
+                    0.0,
+                    30.0 * LINE_SPACING + default * LINE_SPACING + 30.0
+                ),
+            ]
+        );
+        assert_eq!(size.width, default + 40.0);
+    }
+
+    #[test]
+    fn test_rich_text_newlines() {
+        let (fragments, size) =
+            layout_rich_text(&AttributedText::from("a\n\nb"), 1000.0, &mut measure);
+        assert_eq!(words(&fragments).len(), 2);
+        assert_eq!(size.height, 3.0 * Text::DEFAULT_SIZE as f32 * LINE_SPACING);
+    }
+}
@@ -1,11 +1,17 @@
 use crate::*;
-use accesskit::Role;
+use accesskit::{Action, ActionRequest, Role};
 use std::any::Any;
 
+type ActionHandler = Box<dyn Fn(&mut Context, &ActionRequest)>;
+
 /// Struct for the `role` modifier.
 pub struct RoleView<V> {
     child: V,
     role: Role,
+    checked: Option<accesskit::CheckedState>,
+    /// Value, minimum and maximum.
+    numeric_value: Option<(f64, f64, f64)>,
+    handlers: Vec<(Action, ActionHandler)>,
 }
 
 impl<V> RoleView<V>
@@ -13,7 +19,60 @@ where
     V: View,
 {
     pub fn new(v: V, role: Role) -> Self {
-        Self { child: v, role }
+        Self {
+            child: v,
+            role,
+            checked: None,
+            numeric_value: None,
+            handlers: vec![],
+        }
+    }
+
+    /// Also reports whether the view is checked, as for checkboxes.
+    pub(crate) fn checked(self, checked: accesskit::CheckedState) -> Self {
+        Self {
+            checked: Some(checked),
+            ..self
+        }
+    }
+
+    /// Also reports a value in a range, as for sliders.
+    pub(crate) fn numeric_value(self, value: f64, min: f64, max: f64) -> Self {
+        Self {
+            numeric_value: Some((value, min, max)),
+            ..self
+        }
+    }
+
+    /// Calls `f` when assistive technology, like a screen reader, asks
+    /// the view to do `action`. Views with pressable roles, like buttons,
+    /// are already tapped for `Action::Default`.
+    pub fn on_action(
+        mut self,
+        action: Action,
+        f: impl Fn(&mut Context, &ActionRequest) + 'static,
+    ) -> Self {
+        self.handlers.push((action, Box::new(f)));
+        self
+    }
+
+    /// Taps the middle of the view.
+    fn press(&self, path: &mut IdPath, cx: &mut Context, actions: &mut Vec<Box<dyn Any>>) {
+        let position = cx.get_layout(path).rect.center();
+        path.push(0);
+        for event in [
+            Event::TouchBegin {
+                id: ACCESS_TOUCH,
+                position,
+            },
+            Event::TouchEnd {
+                id: ACCESS_TOUCH,
+                position,
+            },
+        ] {
+            self.child.process(&event, path, cx, actions);
+        }
+        path.pop();
     }
 }
 
@@ -28,21 +87,43 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
+        if let Event::Access(request) = event {
+            if request.target == cx.view_id(path).access_id() {
+                match self.handlers.iter().find(|(a, _)| *a == request.action) {
+                    Some((_, f)) => f(cx, request),
+                    None if request.action == Action::Default && is_pressable(self.role) => {
+                        self.press(path, cx, actions)
+                    }
+                    None => (),
+                }
+            }
+        }
         path.push(0);
         self.child.process(event, path, cx, actions);
         path.pop();
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        args.cx
+            .push_gpu_marker(args.vger, || format!("{:?}", self.role));
         path.push(0);
         self.child.draw(path, args);
         path.pop();
+        args.cx.pop_gpu_marker(args.vger);
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
         path.push(0);
         let sz = self.child.layout(path, args);
         path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
         sz
     }
 
@@ -67,7 +148,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -77,6 +158,7 @@ where
         cx: &mut Context,
         nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
     ) -> Option<accesskit::NodeId> {
+        let start = nodes.len();
         path.push(0);
         let child_aid = self.child.access(path, cx, nodes);
         path.pop();
@@ -86,6 +168,25 @@ where
             Some(cid) => vec![cid],
             None => vec![],
         });
+        if is_pressable(self.role) {
+            let name = contents_name(&nodes[start..]);
+            if !name.is_empty() {
+                builder.set_name(name);
+            }
+            builder.add_action(Action::Default);
+        }
+        if let Some(checked) = self.checked {
+            builder.set_checked_state(checked);
+        }
+        if let Some((value, min, max)) = self.numeric_value {
+            builder.set_numeric_value(value);
+            builder.set_min_numeric_value(min);
+            builder.set_max_numeric_value(max);
+        }
+        for (action, _) in &self.handlers {
+            builder.add_action(*action);
+        }
+        take_focusable(child_aid, aid, &mut builder, cx);
         nodes.push((aid, builder.build(&mut cx.access_node_classes)));
         Some(aid)
     }
@@ -0,0 +1,500 @@
+use crate::*;
+use std::any::Any;
+
+/// How far a touch moves before a scroll view decides whether it's
+/// scrolling, so taps with a little wobble still reach buttons.
+pub(crate) const SCROLL_SLOP: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScrollAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl ScrollAxis {
+    /// Is `delta` more along this axis than across it?
+    fn dominant(self, delta: LocalOffset) -> bool {
+        match self {
+            ScrollAxis::Horizontal => delta.x.abs() > delta.y.abs(),
+            ScrollAxis::Vertical => delta.y.abs() > delta.x.abs(),
+        }
+    }
+}
+
+/// A touch being followed by a scroll view.
+#[derive(Clone, Copy, Debug)]
+struct ScrollDrag {
+    id: usize,
+
+    /// Total movement since the touch began.
+    moved: LocalOffset,
+
+    /// Whether the view took the touch, once it's moved past the slop.
+    claimed: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ScrollState {
+    /// Distance scrolled from the content's top left. Never negative.
+    offset: LocalOffset,
+    content: LocalSize,
+    viewport: LocalSize,
+    drag: Option<ScrollDrag>,
+}
+
+impl ScrollState {
+    fn max_offset(&self) -> LocalOffset {
+        (self.content - self.viewport)
+            .to_vector()
+            .max([0.0, 0.0].into())
+    }
+
+    /// Where the content's origin is in the viewport. Content starts at
+    /// the top, since our y axis points up.
+    fn content_origin(&self) -> LocalOffset {
+        [
+            -self.offset.x,
+            self.viewport.height - self.content.height + self.offset.y,
+        ]
+        .into()
+    }
+
+    /// The offset after moving the content by `delta`, along `axis` only.
+    fn scrolled(&self, axis: ScrollAxis, delta: LocalOffset) -> LocalOffset {
+        let max = self.max_offset();
+        match axis {
+            ScrollAxis::Horizontal => {
+                [(self.offset.x - delta.x).clamp(0.0, max.x), self.offset.y].into()
+            }
+            ScrollAxis::Vertical => {
+                [self.offset.x, (self.offset.y + delta.y).clamp(0.0, max.y)].into()
+            }
+        }
+    }
+
+    /// Would moving the content by `delta` scroll along `axis`? Movement
+    /// mostly across the axis, or past the end of the content, is left
+    /// for scroll views around this one.
+    fn can_scroll(&self, axis: ScrollAxis, delta: LocalOffset) -> bool {
+        axis.dominant(delta) && self.scrolled(axis, delta) != self.offset
+    }
+}
+
+/// Struct for `hscroll` and `vscroll`.
+pub struct ScrollView<V> {
+    child: V,
+    axis: ScrollAxis,
+}
+
+/// Scrolls `view` left and right when it's wider than the space given.
+pub fn hscroll<V: View>(view: V) -> ScrollView<V> {
+    ScrollView {
+        child: view,
+        axis: ScrollAxis::Horizontal,
+    }
+}
+
+/// Scrolls `view` up and down when it's taller than the space given.
+///
+/// Scroll views can be nested. A drag goes to the innermost scroll view
+/// which scrolls along the drag's direction and hasn't reached the end of
+/// its content, so a horizontal `hscroll` in a `vscroll` takes sideways
+/// drags and leaves the rest to the list around it. Wheel events are
+/// shared out the same way. Once a scroll view takes a touch, views
+/// within it stop seeing the touch, so buttons under a drag don't fire.
+pub fn vscroll<V: View>(view: V) -> ScrollView<V> {
+    ScrollView {
+        child: view,
+        axis: ScrollAxis::Vertical,
+    }
+}
+
+/// Scroll state is bookkeeping which only affects drawing, so reading and
+/// updating it doesn't mark anything dirty. See `ScrollView::scroll_to`.
+fn scroll_state(vid: ViewId, cx: &mut Context) -> &mut ScrollState {
+    cx.init_state(vid, &ScrollState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// How far the vertical scroll view `vid` is from the bottom of its
+/// content, as it was last laid out.
+pub(crate) fn scroll_distance_from_bottom(vid: ViewId, cx: &mut Context) -> f32 {
+    let state = scroll_state(vid, cx);
+    state.max_offset().y - state.offset.y
+}
+
+/// Scrolls to `distance` from the bottom of the content, without marking
+/// anything dirty, for views adjusting scroll views during layout.
+pub(crate) fn set_scroll_distance_from_bottom(vid: ViewId, distance: f32, cx: &mut Context) {
+    let state = scroll_state(vid, cx);
+    let max = state.max_offset().y;
+    state.offset.y = (max - distance).clamp(0.0, max);
+}
+
+/// Scrolls to the bottom of the content.
+pub(crate) fn scroll_to_bottom(vid: ViewId, cx: &mut Context) {
+    let max = scroll_state(vid, cx).max_offset().y;
+    if scroll_state(vid, cx).offset.y != max {
+        cx[StateHandle::<ScrollState>::new(vid)].offset.y = max;
+    }
+}
+
+impl<V> ScrollView<V>
+where
+    V: View,
+{
+    pub(crate) fn content_mut(&mut self) -> &mut V {
+        &mut self.child
+    }
+
+    fn scroll_to(&self, vid: ViewId, offset: LocalOffset, cx: &mut Context) {
+        if scroll_state(vid, cx).offset != offset {
+            cx[StateHandle::<ScrollState>::new(vid)].offset = offset;
+        }
+    }
+
+    fn drag(&self, vid: ViewId, id: usize, delta: LocalOffset, cx: &mut Context) {
+        let axis = self.axis;
+        let state = scroll_state(vid, cx);
+        let drag = match &mut state.drag {
+            Some(drag) if drag.id == id => drag,
+            _ => return,
+        };
+        drag.moved += delta;
+
+        match drag.claimed {
+            Some(true) => {
+                let offset = state.scrolled(axis, delta);
+                self.scroll_to(vid, offset, cx);
+            }
+            None if drag.moved.length() > SCROLL_SLOP => {
+                let moved = drag.moved;
+                // Views within this one see the move first, so an inner
+                // scroll view which wants the touch has taken it by now.
+                let claim = cx.scroll_claims[id].is_default() && {
+                    let state = scroll_state(vid, cx);
+                    state.can_scroll(axis, moved)
+                };
+                let state = scroll_state(vid, cx);
+                state.drag = state.drag.map(|drag| ScrollDrag {
+                    claimed: Some(claim),
+                    ..drag
+                });
+                if claim {
+                    cx.scroll_claims[id] = vid;
+                    cx.touches[id] = vid;
+                    let offset = scroll_state(vid, cx).scrolled(axis, moved);
+                    self.scroll_to(vid, offset, cx);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn end_drag(&self, vid: ViewId, id: usize, cx: &mut Context) {
+        let state = scroll_state(vid, cx);
+        if state.drag.is_some_and(|drag| drag.id == id) {
+            state.drag = None;
+        }
+        if cx.scroll_claims[id] == vid {
+            cx.scroll_claims[id] = ViewId::default();
+            if cx.touches[id] == vid {
+                cx.touches[id] = ViewId::default();
+            }
+        }
+    }
+}
+
+impl<V> View for ScrollView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let rect = cx.get_layout(path).rect;
+
+        match event {
+            // Touches and wheels outside the viewport don't reach the
+            // content.
+            Event::TouchBegin { position, .. } | Event::Scroll { position, .. }
+                if !rect.contains(*position) =>
+            {
+                return;
+            }
+            Event::TouchBegin { id, .. } => {
+                cx.scroll_claims[*id] = ViewId::default();
+                scroll_state(vid, cx).drag = Some(ScrollDrag {
+                    id: *id,
+                    moved: LocalOffset::zero(),
+                    claimed: None,
+                });
+            }
+            _ => (),
+        }
+
+        let origin = scroll_state(vid, cx).content_origin();
+        path.push(0);
+        self.child
+            .process(&event.offset(-origin), path, cx, actions);
+        path.pop();
+
+        match event {
+            Event::TouchMove { id, delta, .. } => self.drag(vid, *id, *delta, cx),
+            Event::TouchEnd { id, .. } => self.end_drag(vid, *id, cx),
+            Event::Scroll { delta, .. } if !cx.scroll_consumed => {
+                let state = scroll_state(vid, cx);
+                if state.can_scroll(self.axis, *delta) {
+                    let offset = state.scrolled(self.axis, *delta);
+                    self.scroll_to(vid, offset, cx);
+                    cx.scroll_consumed = true;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let rect = args.cx.get_layout(path).rect;
+        let origin = scroll_state(vid, args.cx).content_origin();
+
+        args.vger.save();
+        args.vger.scissor(rect);
+        let world_rect = args.vger.current_transform().outer_transformed_rect(&rect);
+        args.cx.push_clip(world_rect);
+        args.vger.translate(origin);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+        args.cx.pop_clip();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+
+        // Content gets all the room it wants along the axis. Content
+        // which fills whatever it's offered, like a stack with a spacer,
+        // gets the space we were given instead.
+        let proposed = match self.axis {
+            ScrollAxis::Horizontal => LocalSize::new(f32::INFINITY, args.sz.height),
+            ScrollAxis::Vertical => LocalSize::new(args.sz.width, f32::INFINITY),
+        };
+        path.push(0);
+        let mut content = self.child.layout(path, &mut args.size(proposed));
+        if !(content.width.is_finite() && content.height.is_finite()) {
+            content = self.child.layout(path, args);
+        }
+        path.pop();
+
+        // Fill the space given, unless it's unbounded, as in another
+        // scroll view.
+        let fill = |given: f32, wanted: f32| if given.is_finite() { given } else { wanted };
+        let viewport = LocalSize::new(
+            fill(args.sz.width, content.width),
+            fill(args.sz.height, content.height),
+        );
+
+        let state = scroll_state(vid, args.cx);
+        state.content = content;
+        state.viewport = viewport;
+        // Content may have shrunk since we scrolled.
+        state.offset = state.offset.min(state.max_offset());
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), viewport),
+                offset: LocalOffset::zero(),
+            },
+        );
+        viewport
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            // Scrolled, so everything we show moved.
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(path, rect, xform);
+            return;
+        }
+        let origin = scroll_state(vid, cx).content_origin();
+        path.push(0);
+        self.child.dirty(path, xform.pre_translate(origin), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        // Text probes, for tests, see content scrolled out of view too.
+        if cx.text_probe.is_none() && !cx.get_layout(path).rect.contains(pt) {
+            return None;
+        }
+        let vid = cx.view_id(path);
+        let origin = scroll_state(vid, cx).content_origin();
+        path.push(0);
+        let id = self.child.hittest(path, pt - origin, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for ScrollView<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn card(name: &str, taps: &Rc<Cell<i32>>) -> impl View {
+        let taps = taps.clone();
+        zstack((rectangle(), text(name)))
+            .size([100.0, 50.0])
+            .tap(move |_| taps.set(taps.get() + 1))
+    }
+
+    fn row(i: usize) -> impl View {
+        text(&format!("row {}", i)).size([200.0, 50.0])
+    }
+
+    /// A carousel of four cards above ten rows, in a 200x200 window.
+    fn feed(taps: &Rc<Cell<i32>>) -> Harness<impl View> {
+        let view = vscroll(vstack((
+            hscroll(hstack((
+                card("a", taps),
+                card("b", taps),
+                card("c", taps),
+                card("d", taps),
+            ))),
+            list((0..10).collect(), |i| row(*i)),
+        )));
+        Harness::with_size(view, Size::new(200.0, 200.0))
+    }
+
+    /// Center of a text view, to the nearest point so moves add up
+    /// exactly.
+    fn center(harness: &mut Harness<impl View>, text: &str) -> Point {
+        let c = harness.find_text(text).unwrap().center();
+        Point::new(c.x.round(), c.y.round())
+    }
+
+    #[test]
+    fn test_scroll_arbitration() {
+        let taps = Rc::new(Cell::new(0));
+        let mut harness = feed(&taps);
+        let b = center(&mut harness, "b");
+        let row = center(&mut harness, "row 0");
+        assert!(harness.find_text("row 9").unwrap().origin.y < 0.0);
+
+        // Sideways drags scroll the carousel, and don't tap the card.
+        harness.drag(b, Point::new(b.x - 80.0, b.y + 10.0));
+        assert_eq!(center(&mut harness, "b").x, b.x - 80.0);
+        assert_eq!(center(&mut harness, "row 0"), row);
+        assert_eq!(taps.get(), 0);
+
+        // A carousel back at its start leaves further drags that way to
+        // the list, which doesn't scroll sideways.
+        let a = center(&mut harness, "a");
+        let b = center(&mut harness, "b");
+        harness.drag(b, Point::new(b.x + 120.0, b.y));
+        harness.drag(b, Point::new(b.x + 120.0, b.y));
+        assert_eq!(center(&mut harness, "a").x, a.x + 80.0);
+        assert_eq!(center(&mut harness, "row 0"), row);
+
+        // Nothing scrolled that time, so the card kept the touch.
+        assert_eq!(taps.get(), 1);
+        let a = center(&mut harness, "a");
+        harness.click_at(a);
+        assert_eq!(taps.get(), 2);
+
+        // Vertical drags on the carousel scroll the list.
+        harness.drag(a, Point::new(a.x + 10.0, a.y + 100.0));
+        assert_eq!(center(&mut harness, "a"), Point::new(a.x, a.y + 100.0));
+        assert_eq!(center(&mut harness, "row 0").y, row.y + 100.0);
+    }
+
+    #[test]
+    fn test_scroll_wheel() {
+        let taps = Rc::new(Cell::new(0));
+        let mut harness = feed(&taps);
+        let a = center(&mut harness, "a");
+        let row = center(&mut harness, "row 0");
+
+        // The carousel only has 200 points to scroll.
+        harness.scroll(a, [-300.0, 0.0]);
+        assert_eq!(center(&mut harness, "a").x, a.x - 200.0);
+
+        // Only the carousel is redrawn.
+        let damage = harness.damage().unwrap();
+        assert_eq!(
+            damage,
+            Rect::new(Point::new(0.0, 150.0), Size::new(200.0, 50.0))
+        );
+
+        // Vertical wheels over the carousel go to the list, which stops at
+        // the end of its content.
+        harness.scroll(a, [0.0, 1000.0]);
+        assert_eq!(center(&mut harness, "row 0").y, row.y + 350.0);
+        assert_eq!(harness.find_text("row 9").unwrap().origin.y, 0.0);
+    }
+
+    #[test]
+    fn test_nested_vscroll() {
+        // An inner list scrolls until it runs out, then the outer one does.
+        let view = vscroll(vstack((
+            vscroll(list((0..4).collect(), |i| row(*i))).size([200.0, 100.0]),
+            list((10..16).collect(), |i| row(*i)),
+        )));
+        let mut harness = Harness::with_size(view, Size::new(200.0, 200.0));
+        let top = center(&mut harness, "row 0");
+        let outer = center(&mut harness, "row 10");
+
+        harness.drag(top, Point::new(top.x, top.y + 50.0));
+        assert_eq!(center(&mut harness, "row 0").y, top.y + 50.0);
+        assert_eq!(center(&mut harness, "row 10"), outer);
+
+        // The inner list has 100 points to scroll, 50 of which are left.
+        harness.drag(top, Point::new(top.x, top.y + 50.0));
+        harness.drag(top, Point::new(top.x, top.y + 50.0));
+        assert_eq!(center(&mut harness, "row 0").y, top.y + 150.0);
+        assert_eq!(center(&mut harness, "row 10").y, outer.y + 50.0);
+    }
+}
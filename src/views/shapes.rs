@@ -1,9 +1,58 @@
 use crate::*;
 
+/// How a shape is painted. Shapes are filled by default. `stroke` outlines
+/// them instead, unless they're also given a `fill`, which is drawn under
+/// the stroke.
+#[derive(Clone)]
+struct ShapeStyle {
+    /// Fill set with `fill`.
+    fill: Option<Paint>,
+    stroke: Option<(f32, Paint)>,
+}
+
+impl ShapeStyle {
+    fn new() -> Self {
+        Self {
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    /// The paint to fill the shape with, if any.
+    fn fill_paint(&self) -> Option<Paint> {
+        match (&self.fill, &self.stroke) {
+            (Some(fill), _) => Some(fill.clone()),
+            (None, None) => Some(Paint::Color(Color::CYAN)),
+            (None, Some(_)) => None,
+        }
+    }
+
+    fn fill(self, paint: Paint) -> Self {
+        Self {
+            fill: Some(paint),
+            ..self
+        }
+    }
+
+    fn stroke(self, width: f32, paint: Paint) -> Self {
+        Self {
+            stroke: Some((width, paint)),
+            ..self
+        }
+    }
+
+    /// Does the shape, with bounds `rect`, need redrawing this frame?
+    fn is_damaged(&self, rect: &LocalRect, args: &DrawArgs) -> bool {
+        let width = self.stroke.as_ref().map_or(0.0, |(width, _)| *width);
+        args.cx
+            .is_damaged(&rect.inflate(width, width), &args.vger.current_transform())
+    }
+}
+
 /// Struct for `circle`.
 #[derive(Clone)]
 pub struct Circle {
-    paint: Paint,
+    style: ShapeStyle,
 }
 
 impl Circle {
@@ -14,8 +63,20 @@ impl Circle {
     }
 
     pub fn color(self, color: Color) -> Circle {
+        self.fill(color)
+    }
+
+    /// Fills the circle with a color, gradient or image.
+    pub fn fill(self, paint: impl Into<Paint>) -> Circle {
         Circle {
-            paint: Paint::Color(color),
+            style: self.style.fill(paint.into()),
+        }
+    }
+
+    /// Outlines the circle, over its fill if it has one.
+    pub fn stroke(self, width: f32, paint: impl Into<Paint>) -> Circle {
+        Circle {
+            style: self.style.stroke(width, paint.into()),
         }
     }
 }
@@ -24,9 +85,25 @@ impl View for Circle {
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let (center, radius) = self.geom(path, args.cx);
 
+        let bounds = LocalRect::new(
+            center - LocalOffset::new(radius, radius),
+            [2.0 * radius, 2.0 * radius].into(),
+        );
+        if !self.style.is_damaged(&bounds, args) {
+            return;
+        }
+
         let vger = &mut args.vger;
-        let paint = self.paint.vger_paint(vger);
-        vger.fill_circle(center, radius, paint);
+        if let Some(fill) = self.style.fill_paint() {
+            let shape = Some((bounds, radius));
+            fill.fill(args.cx, vger, bounds, shape, |vger, paint| {
+                vger.fill_circle(center, radius, paint)
+            });
+        }
+        if let Some((width, stroke)) = &self.style.stroke {
+            let paint = stroke.painter_paint(vger, bounds);
+            vger.stroke_arc(center, radius, *width, 0.0, std::f32::consts::PI, paint);
+        }
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
@@ -60,7 +137,7 @@ impl private::Sealed for Circle {}
 /// Renders a circle which expands to fill available space.
 pub fn circle() -> Circle {
     Circle {
-        paint: Paint::Color(Color::CYAN),
+        style: ShapeStyle::new(),
     }
 }
 
@@ -68,7 +145,7 @@ pub fn circle() -> Circle {
 #[derive(Clone)]
 pub struct Rectangle {
     corner_radius: f32,
-    paint: Paint,
+    style: ShapeStyle,
 }
 
 impl Rectangle {
@@ -78,9 +155,22 @@ impl Rectangle {
 
     /// Sets the fill color for the rectangle.
     pub fn color(self, color: Color) -> Rectangle {
+        self.fill(color)
+    }
+
+    /// Fills the rectangle with a color, gradient or image.
+    pub fn fill(self, paint: impl Into<Paint>) -> Rectangle {
+        Rectangle {
+            corner_radius: self.corner_radius,
+            style: self.style.fill(paint.into()),
+        }
+    }
+
+    /// Outlines the rectangle, over its fill if it has one.
+    pub fn stroke(self, width: f32, paint: impl Into<Paint>) -> Rectangle {
         Rectangle {
             corner_radius: self.corner_radius,
-            paint: Paint::Color(color),
+            style: self.style.stroke(width, paint.into()),
         }
     }
 
@@ -88,7 +178,7 @@ impl Rectangle {
     pub fn corner_radius(self, radius: f32) -> Rectangle {
         Rectangle {
             corner_radius: radius,
-            paint: self.paint,
+            style: self.style,
         }
     }
 }
@@ -96,10 +186,21 @@ impl Rectangle {
 impl View for Rectangle {
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let rect = self.geom(path, args.cx);
+        if !self.style.is_damaged(&rect, args) {
+            return;
+        }
 
         let vger = &mut args.vger;
-        let paint = self.paint.vger_paint(vger);
-        vger.fill_rect(rect, self.corner_radius, paint);
+        if let Some(fill) = self.style.fill_paint() {
+            let shape = Some((rect, self.corner_radius));
+            fill.fill(args.cx, vger, rect, shape, |vger, paint| {
+                vger.fill_rect(rect, self.corner_radius, paint)
+            });
+        }
+        if let Some((width, stroke)) = &self.style.stroke {
+            let paint = stroke.painter_paint(vger, rect);
+            vger.stroke_rect(rect.min(), rect.max(), self.corner_radius, *width, paint);
+        }
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
@@ -134,6 +235,326 @@ impl private::Sealed for Rectangle {}
 pub fn rectangle() -> Rectangle {
     Rectangle {
         corner_radius: 0.0,
-        paint: Paint::Color(Color::CYAN),
+        style: ShapeStyle::new(),
+    }
+}
+
+/// Same as `rectangle`.
+pub fn rect() -> Rectangle {
+    rectangle()
+}
+
+/// Renders a rectangle with rounded corners which expands to fill
+/// available space.
+pub fn rounded_rect(radius: f32) -> Rectangle {
+    rectangle().corner_radius(radius)
+}
+
+/// A subpath of a `PathBuilder`.
+#[derive(Clone, Debug, PartialEq)]
+struct Contour {
+    start: LocalPoint,
+
+    /// Quadratic segments as (control, end). Lines are marked so they can
+    /// be stroked as segments.
+    segments: Vec<(LocalPoint, LocalPoint, bool)>,
+    closed: bool,
+}
+
+impl Contour {
+    /// Segments as (start, control, end, is_line), including the closing
+    /// line if the contour is closed.
+    fn quads(&self) -> Vec<(LocalPoint, LocalPoint, LocalPoint, bool)> {
+        let mut quads = vec![];
+        let mut pen = self.start;
+        for &(b, c, line) in &self.segments {
+            quads.push((pen, b, c, line));
+            pen = c;
+        }
+        if self.closed && pen != self.start {
+            quads.push((pen, pen.lerp(self.start, 0.5), self.start, true));
+        }
+        quads
+    }
+}
+
+/// Builds the outline for `path`, in the path view's local coordinates.
+#[derive(Clone, Debug, Default)]
+pub struct PathBuilder {
+    contours: Vec<Contour>,
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new subpath at `p`.
+    pub fn move_to(&mut self, p: impl Into<LocalPoint>) -> &mut Self {
+        self.contours.push(Contour {
+            start: p.into(),
+            segments: vec![],
+            closed: false,
+        });
+        self
+    }
+
+    fn pen(&self) -> LocalPoint {
+        match self.contours.last() {
+            Some(contour) => contour.segments.last().map_or(contour.start, |s| s.1),
+            None => LocalPoint::zero(),
+        }
+    }
+
+    fn segment(&mut self, b: LocalPoint, c: LocalPoint, line: bool) {
+        if self.contours.last().is_none_or(|c| c.closed) {
+            self.move_to(self.pen());
+        }
+        self.contours
+            .last_mut()
+            .unwrap()
+            .segments
+            .push((b, c, line));
+    }
+
+    /// Adds a straight line to `p`.
+    pub fn line_to(&mut self, p: impl Into<LocalPoint>) -> &mut Self {
+        let p = p.into();
+        self.segment(self.pen().lerp(p, 0.5), p, true);
+        self
+    }
+
+    /// Adds a quadratic curve to `p`, bending towards `control`.
+    pub fn quad_to(
+        &mut self,
+        control: impl Into<LocalPoint>,
+        p: impl Into<LocalPoint>,
+    ) -> &mut Self {
+        self.segment(control.into(), p.into(), false);
+        self
+    }
+
+    /// Closes the current subpath with a line back to its start.
+    pub fn close(&mut self) -> &mut Self {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.closed = true;
+        }
+        self
+    }
+
+    /// Even-odd test against the filled path, with curves flattened.
+    fn contains(&self, pt: LocalPoint) -> bool {
+        const STEPS: usize = 8;
+        let mut inside = false;
+        for contour in &self.contours {
+            let mut points = vec![contour.start];
+            for (a, b, c, line) in contour.quads() {
+                if line {
+                    points.push(c);
+                    continue;
+                }
+                for i in 1..=STEPS {
+                    let t = i as f32 / STEPS as f32;
+                    points.push(a.lerp(b, t).lerp(b.lerp(c, t), t));
+                }
+            }
+            // Fills close open contours implicitly.
+            for i in 0..points.len() {
+                let p = points[i];
+                let q = points[(i + 1) % points.len()];
+                if (p.y > pt.y) != (q.y > pt.y)
+                    && pt.x < p.x + (pt.y - p.y) / (q.y - p.y) * (q.x - p.x)
+                {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+/// Struct for `path`.
+#[derive(Clone)]
+pub struct PathShape<F> {
+    build: F,
+    style: ShapeStyle,
+}
+
+impl<F> PathShape<F>
+where
+    F: Fn(&mut PathBuilder, LocalRect),
+{
+    fn geom(&self, path: &IdPath, cx: &mut Context) -> PathBuilder {
+        let rect = cx.get_layout(path).rect;
+        let mut builder = PathBuilder::new();
+        (self.build)(&mut builder, rect);
+        builder
+    }
+
+    /// Fills the path with a color or linear gradient. Radial gradients
+    /// and images give their average color, since paths can't mask them.
+    pub fn fill(self, paint: impl Into<Paint>) -> Self {
+        Self {
+            build: self.build,
+            style: self.style.fill(paint.into()),
+        }
+    }
+
+    /// Outlines the path, over its fill if it has one.
+    pub fn stroke(self, width: f32, paint: impl Into<Paint>) -> Self {
+        Self {
+            build: self.build,
+            style: self.style.stroke(width, paint.into()),
+        }
+    }
+}
+
+impl<F> View for PathShape<F>
+where
+    F: Fn(&mut PathBuilder, LocalRect) + 'static,
+{
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        if !self.style.is_damaged(&rect, args) {
+            return;
+        }
+        let builder = self.geom(path, args.cx);
+
+        let vger = &mut args.vger;
+        if let Some(fill) = self.style.fill_paint() {
+            fill.fill(args.cx, vger, rect, None, |vger, paint| {
+                for contour in &builder.contours {
+                    vger.move_to(contour.start);
+                    for (_, b, c, _) in contour.quads() {
+                        vger.quad_to(b, c);
+                    }
+                }
+                vger.fill(paint);
+            });
+        }
+        if let Some((width, stroke)) = &self.style.stroke {
+            let paint = stroke.painter_paint(vger, rect);
+            for contour in &builder.contours {
+                for (a, b, c, line) in contour.quads() {
+                    if line {
+                        vger.stroke_segment(a, c, *width, paint);
+                    } else {
+                        vger.stroke_bezier(a, b, c, *width, paint);
+                    }
+                }
+            }
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        args.sz
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if self.geom(path, cx).contains(pt) {
+            Some(cx.view_id(path))
+        } else {
+            None
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+}
+
+impl<F> private::Sealed for PathShape<F> {}
+
+/// Renders a shape outlined by `build`, which is called with a
+/// `PathBuilder` and the view's rect, so the shape can scale with the space
+/// available. For example, a triangle:
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(path(|p, rect| {
+///     p.move_to([rect.min_x(), rect.min_y()])
+///         .line_to([rect.max_x(), rect.min_y()])
+///         .line_to([rect.center().x, rect.max_y()])
+///         .close();
+/// }));
+/// ```
+pub fn path<F: Fn(&mut PathBuilder, LocalRect) + 'static>(build: F) -> PathShape<F> {
+    PathShape {
+        build,
+        style: ShapeStyle::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn triangle(p: &mut PathBuilder, rect: LocalRect) {
+        p.move_to([rect.min_x(), rect.min_y()])
+            .line_to([rect.max_x(), rect.min_y()])
+            .line_to([rect.center().x, rect.max_y()])
+            .close();
+    }
+
+    #[test]
+    fn test_path_builder() {
+        let mut p = PathBuilder::new();
+        triangle(
+            &mut p,
+            LocalRect::new(LocalPoint::zero(), [100.0, 100.0].into()),
+        );
+        let quads = p.contours[0].quads();
+        assert_eq!(quads.len(), 3);
+        assert!(quads.iter().all(|q| q.3));
+        assert_eq!(quads[2].2, LocalPoint::zero());
+
+        assert!(p.contains([50.0, 10.0].into()));
+        assert!(!p.contains([5.0, 90.0].into()));
+
+        // A curve bulging up past the chord.
+        let mut p = PathBuilder::new();
+        p.move_to([0.0, 0.0]).quad_to([50.0, 100.0], [100.0, 0.0]);
+        assert!(p.contains([50.0, 40.0].into()));
+        assert!(!p.contains([50.0, 60.0].into()));
+    }
+
+    #[test]
+    fn test_shape_styles() {
+        assert!(circle().style.fill_paint().is_some());
+
+        let outlined = rounded_rect(4.0).stroke(2.0, RED_HIGHLIGHT);
+        assert!(outlined.style.fill_paint().is_none());
+        assert_eq!(outlined.corner_radius, 4.0);
+
+        // Either order fills and strokes.
+        let both = circle().stroke(2.0, RED_HIGHLIGHT).fill(AZURE_HIGHLIGHT);
+        assert!(both.style.fill_paint().is_some());
+        assert!(both.style.stroke.is_some());
+        let both = circle().fill(AZURE_HIGHLIGHT).stroke(2.0, RED_HIGHLIGHT);
+        assert!(both.style.fill_paint().is_some());
+        assert!(both.style.stroke.is_some());
+    }
+
+    #[test]
+    fn test_path_hittest() {
+        let taps = Rc::new(Cell::new(0));
+        let t = taps.clone();
+        let view = path(triangle).tap(move |_| t.set(t.get() + 1));
+        let mut harness = Harness::with_size(view, Size::new(100.0, 100.0));
+
+        harness.click_at(Point::new(5.0, 90.0));
+        assert_eq!(taps.get(), 0);
+        harness.click_at(Point::new(50.0, 10.0));
+        assert_eq!(taps.get(), 1);
     }
 }
@@ -0,0 +1,515 @@
+use crate::*;
+use std::any::Any;
+
+/// A height a bottom sheet can rest at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Detent {
+    /// Points above the bottom of the window.
+    Height(f32),
+
+    /// Fraction of the height available, which is the window's less its
+    /// top safe area.
+    Fraction(f32),
+}
+
+impl Detent {
+    /// Half the available height.
+    pub const MEDIUM: Detent = Detent::Fraction(0.5);
+
+    /// All of the available height.
+    pub const LARGE: Detent = Detent::Fraction(1.0);
+
+    fn resolve(self, available: f32) -> f32 {
+        match self {
+            Detent::Height(height) => height.min(available),
+            Detent::Fraction(fraction) => fraction.clamp(0.0, 1.0) * available,
+        }
+    }
+}
+
+/// How dark the views behind get when the sheet is at its tallest detent.
+const DIMMING: f32 = 0.4;
+
+const CORNER_RADIUS: f32 = 10.0;
+
+/// The bar at the top of the sheet which shows it can be dragged, and the
+/// room left for it above the content.
+const GRABBER_SIZE: [f32; 2] = [36.0, 5.0];
+const GRABBER_SPACE: f32 = 16.0;
+
+/// A critically damped spring, so the sheet doesn't bounce.
+const STIFFNESS: f32 = 300.0;
+const DAMPING: f32 = 34.6;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SheetState {
+    /// The binding when last seen, to notice it changing.
+    presented: bool,
+}
+
+pub(crate) fn sheet_state(vid: ViewId, cx: &mut Context) -> &mut SheetState {
+    cx.init_state(vid, &SheetState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Heights of `detents` in `size`, shortest first.
+fn detent_heights(detents: &[Detent], size: LocalSize, cx: &Context) -> Vec<f32> {
+    let available = (size.height - cx.safe_area().top).max(0.0);
+    let mut heights: Vec<f32> = detents
+        .iter()
+        .map(|detent| detent.resolve(available))
+        .filter(|height| *height > 0.0)
+        .collect();
+    heights.sort_by(f32::total_cmp);
+    heights.dedup();
+    if heights.is_empty() {
+        heights.push(available);
+    }
+    heights
+}
+
+/// The space a sheet was last given, which is the same for the panel.
+fn laid_out_size(path: &IdPath, cx: &Context) -> LocalSize {
+    cx.layout
+        .get(path)
+        .map(|layout| layout.rect.size)
+        .unwrap_or_default()
+}
+
+fn tallest(detents: &[Detent], size: LocalSize, cx: &Context) -> f32 {
+    *detent_heights(detents, size, cx).last().unwrap()
+}
+
+/// The sheet itself, which `draggable` moves up by its height. Its top is
+/// at the bottom of the space it's given.
+struct SheetPanel<V> {
+    detents: Vec<Detent>,
+    content: V,
+}
+
+impl<V: View> SheetPanel<V> {
+    /// Where the content goes, so its top stays just under the grabber.
+    fn content_offset(&self, size: LocalSize, cx: &Context) -> LocalOffset {
+        let safe = cx.safe_area();
+        [safe.left, safe.bottom - tallest(&self.detents, size, cx)].into()
+    }
+}
+
+impl<V: View> View for SheetPanel<V> {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let size = laid_out_size(path, cx);
+        let offset = self.content_offset(size, cx);
+        path.push(0);
+        self.content
+            .process(&event.offset(-offset), path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let size = laid_out_size(path, args.cx);
+        let theme = args.cx.theme();
+        let vger = &mut args.vger;
+
+        // Round the top corners, and go on far enough below that the
+        // bottom never shows, even pulled past the tallest detent.
+        let sheet = LocalRect::new(
+            [0.0, -2.0 * size.height].into(),
+            [size.width, 2.0 * size.height].into(),
+        );
+        let paint = vger.color_paint(theme.background);
+        vger.fill_rect(sheet, CORNER_RADIUS, paint);
+
+        let [width, height] = GRABBER_SIZE;
+        let grabber = LocalRect::new(
+            [(size.width - width) / 2.0, -(GRABBER_SPACE + height) / 2.0].into(),
+            [width, height].into(),
+        );
+        let paint = vger.color_paint(theme.control_background);
+        vger.fill_rect(grabber, height / 2.0, paint);
+
+        let offset = self.content_offset(size, args.cx);
+        args.vger.save();
+        args.vger.translate(offset);
+        path.push(0);
+        self.content.draw(path, args);
+        path.pop();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let size = args.sz;
+        let safe = args.cx.safe_area();
+        let tallest = tallest(&self.detents, size, args.cx);
+        let content_size = LocalSize::new(
+            (size.width - safe.horizontal()).max(0.0),
+            (tallest - GRABBER_SPACE - safe.bottom).max(0.0),
+        );
+        path.push(0);
+        self.content.layout(path, &mut args.size(content_size));
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let size = laid_out_size(path, cx);
+        let offset = self.content_offset(size, cx);
+        path.push(0);
+        self.content.dirty(path, xform.pre_translate(offset), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let size = laid_out_size(path, cx);
+        if pt.y >= 0.0 || pt.x < 0.0 || pt.x > size.width {
+            return None;
+        }
+        let vid = cx.view_id(path);
+        let offset = self.content_offset(size, cx);
+        path.push(0);
+        let id = self.content.hittest(path, pt - offset, cx);
+        path.pop();
+        Some(id.unwrap_or(vid))
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.content.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.content.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.content.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for SheetPanel<V> {}
+
+/// Struct for `bottom_sheet`.
+pub struct BottomSheet<B, V> {
+    is_presented: B,
+    detents: Vec<Detent>,
+    panel: Draggable<SheetPanel<V>>,
+}
+
+/// Slides `content` up from the bottom of the window while `is_presented`
+/// is true, dimming the views behind it. Put it last in a `zstack` over
+/// the rest of the UI.
+///
+/// The sheet rests at the shortest of `detents` when presented, and can be
+/// dragged between them. Swiping it down below half the shortest dismisses
+/// it, setting `is_presented` to false. Content is laid out at the tallest
+/// detent, and kept clear of the window's safe area.
+pub fn bottom_sheet<B, V>(is_presented: B, detents: Vec<Detent>, content: V) -> BottomSheet<B, V>
+where
+    B: Binding<bool>,
+    V: View,
+{
+    let fit_detents = detents.clone();
+    let panel = draggable(SheetPanel {
+        detents: detents.clone(),
+        content,
+    })
+    .spring(STIFFNESS, DAMPING)
+    .fit(move |cx, size| {
+        // Dismissed is the first snap point, so the sheet goes there when
+        // flicked below half the shortest detent.
+        let heights = detent_heights(&fit_detents, size, cx);
+        let tallest = *heights.last().unwrap();
+        let bounds = LocalRect::new(LocalPoint::zero(), [0.0, tallest].into());
+        let snap_points = std::iter::once(0.0)
+            .chain(heights)
+            .map(|height| [0.0, height].into())
+            .collect();
+        (bounds, snap_points)
+    })
+    .on_snap(move |cx, i| {
+        if i == 0 {
+            is_presented.set(cx, false);
+        }
+    });
+    BottomSheet {
+        is_presented,
+        detents,
+        panel,
+    }
+}
+
+impl<B, V> BottomSheet<B, V>
+where
+    B: Binding<bool>,
+    V: View,
+{
+    fn panel_id(path: &mut IdPath, cx: &mut Context) -> ViewId {
+        path.push(0);
+        let vid = cx.view_id(path);
+        path.pop();
+        vid
+    }
+
+    /// How far the top of the sheet is above the bottom of the window.
+    fn height(path: &mut IdPath, cx: &mut Context) -> f32 {
+        let vid = Self::panel_id(path, cx);
+        draggable_state(vid, cx).offset.y
+    }
+
+    fn is_visible(path: &mut IdPath, cx: &mut Context) -> bool {
+        let vid = Self::panel_id(path, cx);
+        let state = draggable_state(vid, cx);
+        state.offset.y > 0.0 || state.is_moving()
+    }
+
+    /// Springs the sheet to the shortest detent, or away, when the binding
+    /// changes.
+    fn follow_binding(&self, path: &mut IdPath, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        let presented = *self.is_presented.get(cx);
+        if presented == sheet_state(vid, cx).presented {
+            return;
+        }
+        sheet_state(vid, cx).presented = presented;
+
+        let panel = Self::panel_id(path, cx);
+        let height = if presented {
+            let size = laid_out_size(path, cx);
+            detent_heights(&self.detents, size, cx)[0]
+        } else {
+            0.0
+        };
+        spring_to(panel, [0.0, height].into(), cx);
+    }
+}
+
+impl<B, V> View for BottomSheet<B, V>
+where
+    B: Binding<bool>,
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Anim = event {
+            self.follow_binding(path, cx);
+        }
+        if Self::is_visible(path, cx) {
+            path.push(0);
+            self.panel.process(event, path, cx, actions);
+            path.pop();
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let height = Self::height(path, args.cx);
+        if height <= 0.0 {
+            return;
+        }
+        let size = laid_out_size(path, args.cx);
+        let tallest = tallest(&self.detents, size, args.cx);
+
+        let dimming = DIMMING * (height / tallest).min(1.0);
+        let paint = args.vger.color_paint(Color::new(0.0, 0.0, 0.0, dimming));
+        args.vger
+            .fill_rect(LocalRect::new(LocalPoint::zero(), size), 0.0, paint);
+
+        path.push(0);
+        self.panel.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let size = self.panel.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.panel.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if Self::height(path, cx) <= 0.0 {
+            return None;
+        }
+        path.push(0);
+        let id = self.panel.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.panel.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.panel.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        if !Self::is_visible(path, cx) {
+            return None;
+        }
+        path.push(0);
+        let node_id = self.panel.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<B, V> private::Sealed for BottomSheet<B, V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn app() -> impl View {
+        state(
+            || false,
+            |presented, cx| {
+                zstack((
+                    vstack((
+                        text(if cx[presented] { "presented" } else { "hidden" }),
+                        button("show", move |cx| cx[presented] = true),
+                        spacer(),
+                    )),
+                    bottom_sheet(
+                        presented,
+                        vec![Detent::Height(100.0), Detent::LARGE],
+                        vstack((text("sheet"), spacer())),
+                    ),
+                ))
+            },
+        )
+    }
+
+    fn settle(harness: &mut Harness<impl View>) {
+        for _ in 0..120 {
+            harness.update();
+        }
+    }
+
+    fn sheet_y(harness: &mut Harness<impl View>) -> f32 {
+        harness.find_text("sheet").unwrap().center().y.round()
+    }
+
+    #[test]
+    fn test_detents() {
+        let mut harness = Harness::with_size(app(), Size::new(400.0, 400.0));
+        harness
+            .context()
+            .set_safe_area(Insets::new(50.0, 0.0, 0.0, 0.0));
+        harness.click("show");
+        settle(&mut harness);
+        assert!(harness.has_text("presented"));
+
+        // Rests at the shortest detent, with the text under the grabber.
+        let low = sheet_y(&mut harness);
+        assert!(low < 100.0 - GRABBER_SPACE && low > 50.0);
+
+        // Dragged up, it goes to the tallest, which is under the safe area.
+        let from = Point::new(200.0, low - 5.0);
+        harness.drag(from, Point::new(200.0, low + 200.0));
+        settle(&mut harness);
+        assert_eq!(sheet_y(&mut harness), low + 250.0);
+    }
+
+    #[test]
+    fn test_swipe_to_dismiss() {
+        let mut harness = Harness::with_size(app(), Size::new(400.0, 400.0));
+        harness.click("show");
+        settle(&mut harness);
+
+        let y = sheet_y(&mut harness);
+        harness.drag(Point::new(200.0, y - 5.0), Point::new(200.0, 0.0));
+        assert!(harness.has_text("hidden"));
+        settle(&mut harness);
+        assert!(harness.find_text("sheet").is_none());
+    }
+
+    #[test]
+    fn test_small_drag_returns() {
+        let mut harness = Harness::with_size(app(), Size::new(400.0, 400.0));
+        harness.click("show");
+        settle(&mut harness);
+
+        // Released moving slowly, not far enough down to dismiss.
+        let y = sheet_y(&mut harness);
+        let position = LocalPoint::new(200.0, y - 5.0);
+        harness.context().mouse_button = Some(MouseButton::Left);
+        harness.event(Event::TouchBegin { id: 0, position });
+        harness.event(Event::TouchMove {
+            id: 0,
+            position: position - LocalOffset::new(0.0, 30.0),
+            delta: [0.0, -30.0].into(),
+        });
+        settle(&mut harness);
+        harness.context().mouse_button = None;
+        harness.event(Event::TouchEnd { id: 0, position });
+
+        settle(&mut harness);
+        assert!(harness.has_text("presented"));
+        assert_eq!(sheet_y(&mut harness), y);
+    }
+}
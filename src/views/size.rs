@@ -2,7 +2,7 @@ use crate::*;
 use std::any::Any;
 
 /// Struct for the `size` modifier.
-pub struct Size<V> {
+pub struct SizeView<V> {
     /// Child view tree.
     child: V,
 
@@ -10,7 +10,7 @@ pub struct Size<V> {
     size: LocalSize,
 }
 
-impl<V> View for Size<V>
+impl<V> View for SizeView<V>
 where
     V: View,
 {
@@ -60,7 +60,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -77,9 +77,9 @@ where
     }
 }
 
-impl<V> private::Sealed for Size<V> {}
+impl<V> private::Sealed for SizeView<V> {}
 
-impl<V> Size<V>
+impl<V> SizeView<V>
 where
     V: View,
 {
@@ -0,0 +1,599 @@
+use crate::*;
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_LINEAGE: AtomicU64 = AtomicU64::new(1);
+
+fn next_lineage() -> u64 {
+    NEXT_LINEAGE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A point along a `Stroke`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokePoint {
+    pub position: LocalPoint,
+
+    /// How hard the pointer pressed, from 0 to 1. See `Context::pressure`.
+    pub pressure: f32,
+}
+
+/// A line drawn on a `sketch` with the mouse, a finger or a pen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub points: Vec<StrokePoint>,
+    pub color: Color,
+
+    /// Width in points where the pointer pressed hardest. Lighter parts
+    /// are thinner.
+    pub width: f32,
+}
+
+impl Stroke {
+    /// The area the stroke covers.
+    pub fn bounds(&self) -> LocalRect {
+        self.bounds_from(0)
+    }
+
+    /// The area covered by the stroke from point `start` on, including
+    /// the segment joining it to the point before.
+    fn bounds_from(&self, start: usize) -> LocalRect {
+        let points = &self.points[start.saturating_sub(1).min(self.points.len())..];
+        if points.is_empty() {
+            return LocalRect::zero();
+        }
+        // Allow for antialiasing.
+        let r = self.width / 2.0 + 1.0;
+        LocalRect::from_points(points.iter().map(|p| p.position)).inflate(r, r)
+    }
+
+    fn width_at(&self, pressure: f32) -> f32 {
+        self.width * pressure.clamp(0.1, 1.0)
+    }
+
+    /// Draws the stroke as `sketch` does, as for showing strokes in a
+    /// `canvas`.
+    pub fn draw(&self, vger: &mut Vger) {
+        let paint = vger.color_paint(self.color);
+        match self.points.as_slice() {
+            [] => (),
+            [p] => capsule(
+                vger,
+                p.position,
+                p.position,
+                self.width_at(p.pressure),
+                paint,
+            ),
+            points => {
+                for pair in points.windows(2) {
+                    let pressure = (pair[0].pressure + pair[1].pressure) / 2.0;
+                    let width = self.width_at(pressure);
+                    capsule(vger, pair[0].position, pair[1].position, width, paint);
+                }
+            }
+        }
+    }
+}
+
+/// Fills a line from `a` to `b` with round ends, so segments of strokes
+/// join up smoothly.
+fn capsule(vger: &mut Vger, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex) {
+    let d = b - a;
+    let (length, r) = (d.length(), width / 2.0);
+    vger.save();
+    vger.translate(a.to_vector() + d / 2.0);
+    vger.rotate(d.y.atan2(d.x));
+    let rect = LocalRect::new(
+        [-length / 2.0 - r, -r].into(),
+        [length + width, width].into(),
+    );
+    vger.fill_rect(rect, r, paint);
+    vger.restore();
+}
+
+/// The strokes drawn on a `sketch`, oldest first. Keep one in state and
+/// pass `sketch` a binding to it.
+///
+/// Strokes are only added at the end, so `sketch` can draw those added
+/// since the last frame over what it drew before, instead of drawing the
+/// whole drawing again.
+#[derive(Debug)]
+pub struct Drawing {
+    strokes: Vec<Stroke>,
+
+    /// Changes whenever strokes are removed, so while it stays the same,
+    /// strokes drawn before are still the first ones.
+    lineage: u64,
+}
+
+impl Default for Drawing {
+    fn default() -> Self {
+        Self {
+            strokes: vec![],
+            lineage: next_lineage(),
+        }
+    }
+}
+
+impl Clone for Drawing {
+    /// Clones get a lineage of their own, since they can go their own
+    /// ways, as for undo history.
+    fn clone(&self) -> Self {
+        Self {
+            strokes: self.strokes.clone(),
+            lineage: next_lineage(),
+        }
+    }
+}
+
+impl Drawing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from `strokes`, as for a drawing saved earlier.
+    pub fn from_strokes(strokes: Vec<Stroke>) -> Self {
+        Self {
+            strokes,
+            lineage: next_lineage(),
+        }
+    }
+
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+
+    /// Adds a stroke over the others.
+    pub fn push(&mut self, stroke: Stroke) {
+        self.strokes.push(stroke);
+    }
+
+    /// Removes the newest stroke, for undo.
+    pub fn pop(&mut self) -> Option<Stroke> {
+        let stroke = self.strokes.pop();
+        if stroke.is_some() {
+            self.lineage = next_lineage();
+        }
+        stroke
+    }
+
+    pub fn clear(&mut self) {
+        self.strokes.clear();
+        self.lineage = next_lineage();
+    }
+
+    /// Identifies what's drawn, for layers.
+    fn key(&self) -> (u64, usize) {
+        (self.lineage, self.strokes.len())
+    }
+
+    /// Draws the strokes on the theme's background, in an image of `size`
+    /// points with `scale` pixels per point, as for saving a whiteboard.
+    /// Points go from the bottom left, as in the `sketch`.
+    #[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+    pub fn render_to_image(
+        &self,
+        size: impl Into<Size>,
+        scale: f32,
+    ) -> Result<::image::RgbaImage, SnapshotError> {
+        let strokes = self.strokes.clone();
+        let view = canvas(move |_, _, vger| {
+            for stroke in &strokes {
+                stroke.draw(vger);
+            }
+        });
+        render_to_image_with_scale(&view, size, scale)
+    }
+}
+
+/// Strokes being drawn, and what the kept layer shows.
+#[derive(Clone, Debug, Default)]
+struct SketchState {
+    /// Strokes which haven't ended, with the touches drawing them.
+    live: Vec<(usize, Stroke)>,
+
+    /// What the live strokes added since the last dirty pass.
+    added: Option<LocalRect>,
+
+    /// The drawing's lineage and number of strokes when the layer was
+    /// drawn.
+    drawn: Option<(u64, usize)>,
+}
+
+impl SketchState {
+    fn add(&mut self, rect: LocalRect) {
+        self.added = Some(self.added.map_or(rect, |added| added.union(&rect)));
+    }
+}
+
+fn sketch_state(vid: ViewId, cx: &mut Context) -> &mut SketchState {
+    cx.init_state(vid, &SketchState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for `sketch`.
+pub struct Sketch<B> {
+    drawing: B,
+    color: Option<Color>,
+    width: f32,
+}
+
+impl<B> Sketch<B> {
+    /// Color of new strokes. The theme's text color by default.
+    pub fn stroke_color(self, color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    /// Width of new strokes in points, where pressed hardest.
+    pub fn stroke_width(self, width: f32) -> Self {
+        Self { width, ..self }
+    }
+}
+
+impl<B> View for Sketch<B>
+where
+    B: Binding<Drawing>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        _actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let s = StateHandle::<SketchState>::new(vid);
+        cx.init_state(vid, &SketchState::default);
+        match event {
+            Event::TouchBegin { id, position }
+                if cx.touches[*id].is_default() && self.hittest(path, *position, cx).is_some() =>
+            {
+                cx.touches[*id] = vid;
+                let stroke = Stroke {
+                    points: vec![StrokePoint {
+                        position: *position,
+                        pressure: cx.pressure(*id),
+                    }],
+                    color: self.color.unwrap_or(cx.theme().text),
+                    width: self.width,
+                };
+                cx[s].add(stroke.bounds());
+                cx[s].live.push((*id, stroke));
+            }
+            Event::TouchMove { id, position, .. } if cx.touches[*id] == vid => {
+                let point = StrokePoint {
+                    position: *position,
+                    pressure: cx.pressure(*id),
+                };
+                let state = &mut cx[s];
+                let added =
+                    state
+                        .live
+                        .iter_mut()
+                        .find(|(touch, _)| touch == id)
+                        .map(|(_, stroke)| {
+                            stroke.points.push(point);
+                            stroke.bounds_from(stroke.points.len() - 1)
+                        });
+                if let Some(added) = added {
+                    state.add(added);
+                }
+            }
+            Event::TouchEnd { id, .. } if cx.touches[*id] == vid => {
+                cx.touches[*id] = ViewId::default();
+                let state = &mut cx[s];
+                if let Some(i) = state.live.iter().position(|(touch, _)| touch == id) {
+                    let (_, stroke) = state.live.remove(i);
+                    state.add(stroke.bounds());
+                    self.drawing.get_mut(cx).push(stroke);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let rect = args.cx.get_layout(path).rect;
+        let xform = args.vger.current_transform();
+        let key = self.drawing.get(args.cx).key();
+        let layer_key = hh(&key);
+
+        args.vger.save();
+        args.vger.scissor(rect);
+
+        if !args.cx.replay_layer(args.vger, vid, layer_key, xform) {
+            // The strokes drawn last time are still there, unless some were
+            // removed since.
+            let drawn = sketch_state(vid, args.cx)
+                .drawn
+                .filter(|(lineage, count)| *lineage == key.0 && *count <= key.1);
+            match args
+                .cx
+                .resume_layer(vid, drawn.map(|drawn| hh(&drawn)), rect, xform)
+            {
+                Some((mut layer, resumed)) => {
+                    let start = drawn.filter(|_| resumed).map_or(0, |(_, count)| count);
+                    for stroke in &self.drawing.get(args.cx).strokes[start..] {
+                        stroke.draw(&mut layer);
+                    }
+                    args.cx
+                        .end_cached_layer(args.vger, layer, xform, vid, layer_key);
+                    sketch_state(vid, args.cx).drawn = Some(key);
+                }
+                None => {
+                    for stroke in &self.drawing.get(args.cx).strokes {
+                        stroke.draw(args.vger);
+                    }
+                }
+            }
+        }
+
+        for (_, stroke) in &sketch_state(vid, args.cx).live {
+            stroke.draw(args.vger);
+        }
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        args.sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        // Only what the live strokes added needs redrawing as they go.
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            let rect = cx.get_layout(path).rect;
+            let added = sketch_state(vid, cx).added.take().unwrap_or(rect);
+            cx.add_dirty_rect(path, added.intersection(&rect).unwrap_or_default(), xform);
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let rect = cx.get_layout(path).rect;
+        rect.contains(pt).then(|| cx.view_id(path))
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+}
+
+/// A surface to draw on with the mouse, fingers or a pen, for whiteboards
+/// and annotations. Strokes are added to `drawing` as they end. Thinner
+/// where the pointer presses lightly, on screens and tablets which tell.
+pub fn sketch<B: Binding<Drawing>>(drawing: B) -> Sketch<B> {
+    Sketch {
+        drawing,
+        color: None,
+        width: 3.0,
+    }
+}
+
+impl<B> private::Sealed for Sketch<B> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const RED: Color = Color {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    fn drawing_pad() -> impl View {
+        state(Drawing::default, |drawing, _| {
+            sketch(drawing).stroke_color(RED).stroke_width(4.0)
+        })
+    }
+
+    #[test]
+    fn test_sketch() {
+        let mut h = Harness::new(drawing_pad());
+        h.set_pressure(0.5);
+        h.drag(Point::new(10.0, 10.0), Point::new(50.0, 10.0));
+
+        let drawing = &h.states::<Drawing>()[0];
+        assert_eq!(drawing.strokes().len(), 1);
+        let stroke = &drawing.strokes()[0];
+        assert_eq!(stroke.points.len(), 5);
+        assert_eq!(stroke.points[4].position, LocalPoint::new(50.0, 10.0));
+        assert!(stroke.points.iter().all(|p| p.pressure == 0.5));
+        assert_eq!((stroke.color, stroke.width), (RED, 4.0));
+        assert_eq!(
+            stroke.bounds(),
+            LocalRect::new([7.0, 7.0].into(), [46.0, 6.0].into())
+        );
+
+        // A stroke for each finger, added as it lifts.
+        h.touch_begin(1, Point::new(100.0, 100.0));
+        h.touch_begin(2, Point::new(200.0, 100.0));
+        h.touch_move(1, Point::new(110.0, 100.0));
+        h.touch_move(2, Point::new(210.0, 100.0));
+        h.touch_end(2);
+        assert_eq!(h.states::<Drawing>()[0].strokes().len(), 2);
+        h.touch_end(1);
+
+        let drawing = &h.states::<Drawing>()[0];
+        let starts: Vec<_> = drawing
+            .strokes()
+            .iter()
+            .map(|stroke| stroke.points[0].position.x)
+            .collect();
+        assert_eq!(starts, vec![10.0, 200.0, 100.0]);
+        assert!(h.states::<SketchState>()[0].live.is_empty());
+    }
+
+    #[test]
+    fn test_sketch_damage() {
+        let mut h = Harness::new(drawing_pad());
+        h.touch_begin(1, Point::new(100.0, 100.0));
+        h.touch_move(1, Point::new(110.0, 100.0));
+        h.touch_move(1, Point::new(120.0, 105.0));
+
+        // Only the newest segment is redrawn.
+        let damage = h.damage().unwrap();
+        assert!(damage.width() < 20.0 && damage.height() < 20.0);
+        assert!(damage.contains(Point::new(115.0, 102.0)));
+        assert!(!damage.contains(Point::new(102.0, 100.0)));
+    }
+
+    #[test]
+    fn test_drawing_lineage() {
+        let stroke = |x| Stroke {
+            points: vec![StrokePoint {
+                position: LocalPoint::new(x, 0.0),
+                pressure: 1.0,
+            }],
+            color: RED,
+            width: 1.0,
+        };
+        let mut drawing = Drawing::new();
+        let (lineage, _) = drawing.key();
+        drawing.push(stroke(0.0));
+        drawing.push(stroke(1.0));
+        assert_eq!(drawing.key(), (lineage, 2));
+
+        assert_eq!(drawing.pop(), Some(stroke(1.0)));
+        assert_ne!(drawing.key().0, lineage);
+        let (lineage, _) = drawing.key();
+        assert_ne!(drawing.clone().key().0, lineage);
+        drawing.clear();
+        assert!(drawing.is_empty());
+        assert_ne!(drawing.key().0, lineage);
+    }
+
+    #[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_drawing_render_to_image() {
+        let mut drawing = Drawing::new();
+        drawing.push(Stroke {
+            points: [[5.0, 10.0], [35.0, 10.0]]
+                .iter()
+                .map(|&p| StrokePoint {
+                    position: p.into(),
+                    pressure: 1.0,
+                })
+                .collect(),
+            color: RED,
+            width: 6.0,
+        });
+        let image = expect_gpu(drawing.render_to_image(Size::new(40.0, 20.0), 1.0));
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(20, 10).0, [255, 0, 0, 255]);
+        assert_ne!(image.get_pixel(20, 2).0, [255, 0, 0, 255]);
+    }
+
+    #[cfg(all(feature = "images", not(target_arch = "wasm32")))]
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn test_sketch_layer() {
+        let (device, queue) = test_device();
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let mut surface = RuiSurface::new(device.clone(), queue.clone(), format, drawing_pad());
+        surface.resize(40, 20, 1.0);
+        surface.update();
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("sketch frame"),
+            size: wgpu::Extent3d {
+                width: 40,
+                height: 20,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let target = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let stroke = |surface: &mut RuiSurface<_>, y: f32| {
+            let (from, to) = (LocalPoint::new(5.0, y), LocalPoint::new(35.0, y));
+            surface.process(&Event::TouchBegin {
+                id: 0,
+                position: from,
+            });
+            surface.process(&Event::TouchMove {
+                id: 0,
+                position: to,
+                delta: to - from,
+            });
+            surface.process(&Event::TouchEnd {
+                id: 0,
+                position: to,
+            });
+        };
+        let frame = |surface: &mut RuiSurface<_>| {
+            surface.update();
+            let mut encoder = device.create_command_encoder(&Default::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            queue.submit(Some(encoder.finish()));
+            surface.render(&target);
+            read_pixels(&device, &queue, &texture, [0, 0], [40, 20])
+        };
+        let red =
+            |pixels: &[u8], x: usize, y: usize| pixels[(y * 40 + x) * 4..][..4] == [255, 0, 0, 255];
+
+        stroke(&mut surface, 15.0);
+        let pixels = frame(&mut surface);
+        assert!(red(&pixels, 20, 5));
+        assert!(!red(&pixels, 20, 15));
+
+        // Only the new stroke is drawn, over the layer kept from before, so
+        // a change to the first one which didn't go through `Drawing` isn't.
+        for holder in surface.context().state_map.values_mut() {
+            if let Some(drawing) = holder.state.downcast_mut::<Drawing>() {
+                drawing.strokes[0].color = Color::new(0.0, 0.0, 1.0, 1.0);
+            }
+        }
+        stroke(&mut surface, 5.0);
+        let pixels = frame(&mut surface);
+        assert!(red(&pixels, 20, 5));
+        assert!(red(&pixels, 20, 15));
+
+        // Removing strokes draws the rest again.
+        for holder in surface.context().state_map.values_mut() {
+            if let Some(drawing) = holder.state.downcast_mut::<Drawing>() {
+                drawing.pop();
+            }
+        }
+        let pixels = frame(&mut surface);
+        assert_eq!(&pixels[(5 * 40 + 20) * 4..][..4], [0, 0, 255, 255]);
+        assert!(!red(&pixels, 20, 15));
+    }
+}
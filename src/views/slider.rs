@@ -1,33 +1,49 @@
 use crate::*;
+use accesskit::{Action, ActionData, Role};
 
 const SLIDER_WIDTH: f32 = 4.0;
 const SLIDER_THUMB_RADIUS: f32 = 10.0;
 
-#[derive(Clone, Copy)]
-pub struct SliderOptions {
-    thumb: Color,
-}
+/// How far assistive technology moves a slider in one step.
+const SLIDER_ACCESS_STEP: f32 = 0.1;
 
-impl Default for SliderOptions {
-    fn default() -> Self {
-        Self {
-            thumb: AZURE_HIGHLIGHT,
-        }
-    }
+#[derive(Clone, Copy, Default)]
+pub struct SliderOptions {
+    /// Uses the theme's accent color if not set.
+    thumb: Option<Color>,
 }
 
 pub trait SliderMods: View + Sized {
     fn thumb_color(self, color: Color) -> Self;
 }
 
+/// Reports the slider's value to assistive technology, which can move it.
+fn slider_role<V: View>(view: V, value: impl Binding<f32>, cx: &Context) -> RoleView<V> {
+    let set =
+        move |cx: &mut Context, v: f32| value.with_mut(cx, |value| *value = v.clamp(0.0, 1.0));
+    view.role(Role::Slider)
+        .numeric_value(*value.get(cx) as f64, 0.0, 1.0)
+        .on_action(Action::Increment, move |cx, _| {
+            set(cx, value.get(cx) + SLIDER_ACCESS_STEP)
+        })
+        .on_action(Action::Decrement, move |cx, _| {
+            set(cx, value.get(cx) - SLIDER_ACCESS_STEP)
+        })
+        .on_action(Action::SetValue, move |cx, request| {
+            if let Some(ActionData::NumericValue(v)) = request.data {
+                set(cx, v as f32)
+            }
+        })
+}
+
 /// Horizontal slider built from other Views.
 pub fn hslider(value: impl Binding<f32>) -> impl SliderMods {
-    modview(move |opts: SliderOptions, _| {
-        state(
+    modview(move |opts: SliderOptions, cx| {
+        let slider = state(
             || 0.0,
             move |width, cx| {
                 let w = cx[width];
-                canvas(move |cx, sz, vger| {
+                painter_canvas(move |cx, sz, vger| {
                     let c = sz.center();
 
                     let w = cx[width];
@@ -36,8 +52,9 @@ pub fn hslider(value: impl Binding<f32>) -> impl SliderMods {
                     let start_x = r;
                     let end_x = w - r;
                     let x = (1.0 - v) * start_x + v * (end_x);
+                    let theme = cx.theme();
 
-                    let paint = vger.color_paint(BUTTON_BACKGROUND_COLOR);
+                    let paint = vger.color_paint(theme.button_background);
                     vger.fill_rect(
                         euclid::rect(
                             start_x,
@@ -48,13 +65,13 @@ pub fn hslider(value: impl Binding<f32>) -> impl SliderMods {
                         0.0,
                         paint,
                     );
-                    let paint = vger.color_paint(AZURE_HIGHLIGHT_BACKGROUND);
+                    let paint = vger.color_paint(theme.accent_background);
                     vger.fill_rect(
                         euclid::rect(start_x, c.y - SLIDER_WIDTH / 2.0, x, SLIDER_WIDTH),
                         0.0,
                         paint,
                     );
-                    let paint = vger.color_paint(opts.thumb);
+                    let paint = vger.color_paint(opts.thumb.unwrap_or(theme.accent));
                     vger.fill_circle([x, c.y], r, paint);
                 })
                 .geom(move |cx, sz, _| {
@@ -63,11 +80,14 @@ pub fn hslider(value: impl Binding<f32>) -> impl SliderMods {
                     }
                 })
                 .drag_s(value, move |v, delta, _, _| {
-                    *v = (*v + delta.x / w).clamp(0.0, 1.0)
+                    // Nothing to drag along before the first layout.
+                    if w > 0.0 {
+                        *v = (*v + delta.x / w).clamp(0.0, 1.0)
+                    }
                 })
             },
-        )
-        .role(accesskit::Role::Slider)
+        );
+        slider_role(slider, value, cx)
     })
 }
 
@@ -77,7 +97,7 @@ where
 {
     fn thumb_color(self, color: Color) -> Self {
         let mut opts = self.value;
-        opts.thumb = color;
+        opts.thumb = Some(color);
         ModView {
             func: self.func,
             value: opts,
@@ -86,25 +106,24 @@ where
 }
 
 /// Vertical slider built from other Views.
-pub fn vslider(
-    value: f32,
-    set_value: impl Fn(&mut Context, f32) + 'static + Copy,
-) -> impl SliderMods {
-    modview(move |opts: SliderOptions, _| {
-        state(
+pub fn vslider(value: impl Binding<f32>) -> impl SliderMods {
+    modview(move |opts: SliderOptions, cx| {
+        let slider = state(
             || 0.0,
-            move |height, _| {
-                canvas(move |cx, sz, vger| {
+            move |height, cx| {
+                let h = cx[height];
+                painter_canvas(move |cx, sz, vger| {
                     let h = cx[height];
-                    let y = value * h;
+                    let y = value.get(cx) * h;
                     let c = sz.center();
-                    let paint = vger.color_paint(BUTTON_BACKGROUND_COLOR);
+                    let theme = cx.theme();
+                    let paint = vger.color_paint(theme.button_background);
                     vger.fill_rect(
                         euclid::rect(c.x - SLIDER_WIDTH / 2.0, 0.0, SLIDER_WIDTH, sz.height()),
                         0.0,
                         paint,
                     );
-                    let paint = vger.color_paint(opts.thumb);
+                    let paint = vger.color_paint(opts.thumb.unwrap_or(theme.accent));
                     vger.fill_circle([c.x, y], SLIDER_THUMB_RADIUS, paint);
                 })
                 .geom(move |cx, sz, _| {
@@ -112,10 +131,13 @@ pub fn vslider(
                         cx[height] = sz.height;
                     }
                 })
-                .drag(move |cx, delta, _, _| {
-                    (set_value)(cx, (value + delta.y / cx[height]).clamp(0.0, 1.0));
+                .drag_s(value, move |v, delta, _, _| {
+                    if h > 0.0 {
+                        *v = (*v + delta.y / h).clamp(0.0, 1.0)
+                    }
                 })
             },
-        )
+        );
+        slider_role(slider, value, cx)
     })
 }
@@ -0,0 +1,478 @@
+use crate::*;
+use std::any::Any;
+
+/// Width of the line drawn between the panes.
+const DIVIDER_WIDTH: f32 = 1.0;
+
+/// Width of the area around the divider which can be dragged.
+const DIVIDER_GRAB: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SplitAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl SplitAxis {
+    /// Length along the axis of a size or point.
+    fn main(self, v: [f32; 2]) -> f32 {
+        match self {
+            SplitAxis::Horizontal => v[0],
+            SplitAxis::Vertical => v[1],
+        }
+    }
+
+    fn size(self, main: f32, cross: f32) -> LocalSize {
+        match self {
+            SplitAxis::Horizontal => [main, cross].into(),
+            SplitAxis::Vertical => [cross, main].into(),
+        }
+    }
+
+    fn cursor(self) -> CursorIcon {
+        match self {
+            SplitAxis::Horizontal => CursorIcon::ResizeHorizontal,
+            SplitAxis::Vertical => CursorIcon::ResizeVertical,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct SplitState {
+    /// Share of the space given to the first pane, once the divider's been
+    /// dragged.
+    ratio: Option<f32>,
+
+    /// The touch dragging the divider, and how long it would make the
+    /// first pane without the minimum sizes.
+    touch: Option<(usize, f32)>,
+
+    /// Where the panes went in the last layout, and how long the first
+    /// is.
+    offsets: [LocalOffset; 2],
+    first: f32,
+
+    /// Middle of the divider along the axis, and the space it divides.
+    divider: f32,
+    size: LocalSize,
+}
+
+fn split_state(vid: ViewId, cx: &mut Context) -> &mut SplitState {
+    cx.init_state(vid, &SplitState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for `hsplit` and `vsplit`.
+pub struct Split<A, B> {
+    axis: SplitAxis,
+    first: A,
+    second: B,
+    ratio: f32,
+    min_sizes: [f32; 2],
+    key: Option<String>,
+}
+
+/// Puts `left` and `right` side by side, with a divider between them which
+/// can be dragged to give one more room.
+pub fn hsplit<A: View, B: View>(left: A, right: B) -> Split<A, B> {
+    Split::new(SplitAxis::Horizontal, left, right)
+}
+
+/// Puts `top` above `bottom`, with a divider between them which can be
+/// dragged to give one more room.
+pub fn vsplit<A: View, B: View>(top: A, bottom: B) -> Split<A, B> {
+    Split::new(SplitAxis::Vertical, top, bottom)
+}
+
+impl<A, B> Split<A, B>
+where
+    A: View,
+    B: View,
+{
+    fn new(axis: SplitAxis, first: A, second: B) -> Self {
+        Self {
+            axis,
+            first,
+            second,
+            ratio: 0.5,
+            min_sizes: [0.0, 0.0],
+            key: None,
+        }
+    }
+
+    /// Share of the space the first pane starts with, before the divider
+    /// is dragged. Half by default.
+    pub fn ratio(self, ratio: f32) -> Self {
+        Self {
+            ratio: ratio.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Keeps the panes at least this long along the split, left or top
+    /// first. If there isn't room for both, the first wins.
+    pub fn min_sizes(self, first: f32, second: f32) -> Self {
+        Self {
+            min_sizes: [first.max(0.0), second.max(0.0)],
+            ..self
+        }
+    }
+
+    /// Saves where the divider was dragged to under `key`, and puts it
+    /// back there the next time the app starts, as with
+    /// `persistent_state`.
+    pub fn persist(self, key: &str) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            ..self
+        }
+    }
+
+    /// Where the divider was dragged to, or was saved, or else where it
+    /// starts.
+    fn current_ratio(&self, vid: ViewId, cx: &mut Context) -> f32 {
+        split_state(vid, cx)
+            .ratio
+            .or_else(|| cx.persistent.get::<f32>(self.key.as_ref()?))
+            .unwrap_or(self.ratio)
+    }
+
+    /// Length of the first pane, out of `length` along the axis less the
+    /// divider.
+    fn first_length(&self, ratio: f32, length: f32) -> f32 {
+        let [min_first, min_second] = self.min_sizes;
+        (ratio * length)
+            .min(length - min_second)
+            .max(min_first)
+            .min(length)
+            .max(0.0)
+    }
+
+    /// Is a point on the divider, for dragging it?
+    fn on_divider(&self, state: &SplitState, pt: LocalPoint) -> bool {
+        let rect = LocalRect::new(LocalPoint::zero(), state.size);
+        rect.contains(pt)
+            && (self.axis.main(pt.to_array()) - state.divider).abs() <= DIVIDER_GRAB / 2.0
+    }
+
+    fn drag(&self, vid: ViewId, delta: LocalOffset, cx: &mut Context) {
+        let state = split_state(vid, cx);
+        let length = (self.axis.main(state.size.to_array()) - DIVIDER_WIDTH).max(0.0);
+        let Some((_, raw)) = &mut state.touch else {
+            return;
+        };
+        // The first pane is on top, which is further up the y axis.
+        *raw += match self.axis {
+            SplitAxis::Horizontal => delta.x,
+            SplitAxis::Vertical => -delta.y,
+        };
+        if length <= 0.0 {
+            return;
+        }
+        let ratio = self.first_length(*raw / length, length) / length;
+        if state.ratio != Some(ratio) {
+            cx[StateHandle::<SplitState>::new(vid)].ratio = Some(ratio);
+        }
+    }
+}
+
+impl<A, B> View for Split<A, B>
+where
+    A: View,
+    B: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let state = *split_state(vid, cx);
+        match event {
+            Event::TouchBegin { id, position } if self.on_divider(&state, *position) => {
+                split_state(vid, cx).touch = Some((*id, state.first));
+                cx.touches[*id] = vid;
+                cx.set_cursor(self.axis.cursor());
+                return;
+            }
+            Event::TouchMove { id, delta, .. } if state.touch.map(|t| t.0) == Some(*id) => {
+                self.drag(vid, *delta, cx);
+                cx.set_cursor(self.axis.cursor());
+                return;
+            }
+            Event::TouchEnd { id, .. } if state.touch.map(|t| t.0) == Some(*id) => {
+                split_state(vid, cx).touch = None;
+                if cx.touches[*id] == vid {
+                    cx.touches[*id] = ViewId::default();
+                }
+                if let (Some(key), Some(ratio)) = (&self.key, state.ratio) {
+                    cx.persistent.set(key, &ratio);
+                }
+                return;
+            }
+            _ => (),
+        }
+
+        path.push(0);
+        self.first
+            .process(&event.offset(-state.offsets[0]), path, cx, actions);
+        path.pop();
+        path.push(1);
+        self.second
+            .process(&event.offset(-state.offsets[1]), path, cx, actions);
+        path.pop();
+
+        // As with `cursor`, the panes' own cursors go first.
+        if let Event::TouchMove { position, .. } = event {
+            if cx.mouse_button.is_none()
+                && cx.cursor.is_none()
+                && self.on_divider(&state, *position)
+            {
+                cx.cursor = Some(self.axis.cursor());
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let state = *split_state(vid, args.cx);
+
+        for (i, offset) in state.offsets.iter().enumerate() {
+            args.vger.save();
+            args.vger.translate(*offset);
+            path.push(i as u64);
+            if i == 0 {
+                self.first.draw(path, args);
+            } else {
+                self.second.draw(path, args);
+            }
+            path.pop();
+            args.vger.restore();
+        }
+
+        let theme = args.cx.theme();
+        let color = if state.touch.is_some() {
+            theme.accent
+        } else {
+            theme.control_background
+        };
+        let divider = match self.axis {
+            SplitAxis::Horizontal => LocalRect::new(
+                [state.divider - DIVIDER_WIDTH / 2.0, 0.0].into(),
+                [DIVIDER_WIDTH, state.size.height].into(),
+            ),
+            SplitAxis::Vertical => LocalRect::new(
+                [0.0, state.divider - DIVIDER_WIDTH / 2.0].into(),
+                [state.size.width, DIVIDER_WIDTH].into(),
+            ),
+        };
+        let paint = args.vger.color_paint(color);
+        args.vger.fill_rect(divider, 0.0, paint);
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        let size = args.sz;
+        let ratio = self.current_ratio(vid, args.cx);
+
+        let main = self.axis.main(size.to_array());
+        let cross = match self.axis {
+            SplitAxis::Horizontal => size.height,
+            SplitAxis::Vertical => size.width,
+        };
+        let length = (main - DIVIDER_WIDTH).max(0.0);
+        let lengths = [
+            self.first_length(ratio, length),
+            length - self.first_length(ratio, length),
+        ];
+
+        path.push(0);
+        let first = self
+            .first
+            .layout(path, &mut args.size(self.axis.size(lengths[0], cross)));
+        path.pop();
+        path.push(1);
+        let second = self
+            .second
+            .layout(path, &mut args.size(self.axis.size(lengths[1], cross)));
+        path.pop();
+
+        // Center each view in its pane.
+        let pane_offset = |pane: LocalRect, child: LocalSize| {
+            pane.origin.to_vector() + (pane.size - child).to_vector() / 2.0
+        };
+        let (panes, divider) = match self.axis {
+            SplitAxis::Horizontal => (
+                [
+                    LocalRect::new(LocalPoint::zero(), [lengths[0], cross].into()),
+                    LocalRect::new(
+                        [lengths[0] + DIVIDER_WIDTH, 0.0].into(),
+                        [lengths[1], cross].into(),
+                    ),
+                ],
+                lengths[0] + DIVIDER_WIDTH / 2.0,
+            ),
+            SplitAxis::Vertical => (
+                [
+                    LocalRect::new(
+                        [0.0, lengths[1] + DIVIDER_WIDTH].into(),
+                        [cross, lengths[0]].into(),
+                    ),
+                    LocalRect::new(LocalPoint::zero(), [cross, lengths[1]].into()),
+                ],
+                lengths[1] + DIVIDER_WIDTH / 2.0,
+            ),
+        };
+
+        let state = split_state(vid, args.cx);
+        state.offsets = [pane_offset(panes[0], first), pane_offset(panes[1], second)];
+        state.first = lengths[0];
+        state.divider = divider;
+        state.size = size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        let offsets = split_state(vid, cx).offsets;
+        path.push(0);
+        self.first.dirty(path, xform.pre_translate(offsets[0]), cx);
+        path.pop();
+        path.push(1);
+        self.second.dirty(path, xform.pre_translate(offsets[1]), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let vid = cx.view_id(path);
+        let state = *split_state(vid, cx);
+        path.push(0);
+        let mut hit = self.first.hittest(path, pt - state.offsets[0], cx);
+        path.pop();
+        path.push(1);
+        hit = self.second.hittest(path, pt - state.offsets[1], cx).or(hit);
+        path.pop();
+        if self.on_divider(&state, pt) {
+            hit = Some(vid);
+        }
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.first.commands(path, cx, cmds);
+        path.pop();
+        path.push(1);
+        self.second.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.first.gc_view(path, cx, map);
+        path.pop();
+        path.push(1);
+        self.second.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let mut children = vec![];
+        path.push(0);
+        children.extend(self.first.access(path, cx, nodes));
+        path.pop();
+        path.push(1);
+        children.extend(self.second.access(path, cx, nodes));
+        path.pop();
+
+        let aid = cx.view_id(path).access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::Group);
+        builder.set_children(children);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<A, B> private::Sealed for Split<A, B> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn center_x(h: &mut Harness<impl View>, text: &str) -> f32 {
+        h.find_text(text).unwrap().center().x
+    }
+
+    #[test]
+    fn test_hsplit() {
+        let split = || hsplit(text("left"), text("right")).min_sizes(50.0, 100.0);
+        let mut h = Harness::with_size(split(), Size::new(401.0, 200.0));
+        assert_eq!(center_x(&mut h, "left"), 100.0);
+        assert_eq!(center_x(&mut h, "right"), 301.0);
+
+        h.hover(Point::new(200.0, 100.0));
+        assert_eq!(h.context().cursor(), CursorIcon::ResizeHorizontal);
+
+        h.drag(Point::new(200.0, 100.0), Point::new(100.0, 100.0));
+        assert_eq!(center_x(&mut h, "left"), 50.0);
+
+        // The right pane keeps its minimum.
+        h.drag(Point::new(100.0, 100.0), Point::new(390.0, 100.0));
+        assert_eq!(center_x(&mut h, "right"), 351.0);
+    }
+
+    #[test]
+    fn test_vsplit_persist() {
+        let split = || {
+            vsplit(text("top"), text("bottom"))
+                .ratio(0.25)
+                .persist("split")
+        };
+        let mut h = Harness::with_size(split(), Size::new(200.0, 401.0));
+        let top = h.find_text("top").unwrap().center().y;
+        let bottom = h.find_text("bottom").unwrap().center().y;
+        assert!(top > bottom);
+
+        // Down from a quarter of the way to halfway.
+        h.drag(Point::new(100.0, 300.5), Point::new(100.0, 200.5));
+        assert!(h.find_text("top").unwrap().center().y < top);
+        let ratio = h.context().persistent.get::<f32>("split").unwrap();
+        assert_eq!(ratio, 0.5);
+
+        let mut restored = Harness::with_size(split(), Size::new(200.0, 401.0));
+        restored.context().persistent.set("split", &ratio);
+        restored.invalidate();
+        restored.update();
+        let mut fresh = Harness::with_size(split(), Size::new(200.0, 401.0));
+        assert_eq!(
+            restored.find_text("top").unwrap().center().y,
+            h.find_text("top").unwrap().center().y
+        );
+        assert_eq!(fresh.find_text("top").unwrap().center().y, top);
+    }
+}
@@ -3,7 +3,8 @@ use crate::*;
 use std::any::Any;
 
 enum StackOrientation {
-    /// Views are stacked horizontally (right to left).
+    /// Views are stacked horizontally (left to right, unless the layout
+    /// direction is right to left).
     Horizontal,
 
     /// Views are stacked vertically (top to bottom).
@@ -121,8 +122,14 @@ impl<VT: ViewTuple + 'static, D: StackDirection + 'static> View for Stack<VT, D>
                     max_height = size.unwrap().height.max(max_height)
                 }
 
+                // Right to left, the first child goes on the right.
+                let rtl = args.cx.layout_direction().is_rtl();
+
                 for c in 0..(self.children.len() as u64) {
-                    let ab = intervals[c as usize];
+                    let mut ab = intervals[c as usize];
+                    if rtl {
+                        ab = (length - ab.1, length - ab.0);
+                    }
 
                     let child_offset = align_v(
                         LocalRect::new(LocalPoint::origin(), child_sizes[c as usize].unwrap()),
@@ -247,7 +254,7 @@ impl<VT: ViewTuple + 'static, D: StackDirection + 'static> View for Stack<VT, D>
         self.children.foreach_view(&mut |child| {
             path.push(c);
             map.push(cx.view_id(path));
-            child.gc(path, cx, map);
+            child.gc_view(path, cx, map);
             path.pop();
             c += 1;
         });
@@ -281,7 +288,7 @@ impl<VT: ViewTuple, D: StackDirection> Stack<VT, D> {
     pub fn new(children: VT) -> Self {
         Self {
             children,
-            phantom_direction: std::marker::PhantomData::default(),
+            phantom_direction: std::marker::PhantomData,
         }
     }
 
@@ -339,3 +346,54 @@ pub fn vstack<VT: ViewTuple + 'static>(children: VT) -> impl View {
 pub fn zstack<VT: ViewTuple + 'static>(children: VT) -> impl View {
     Stack::<VT, ZDirection>::new(children)
 }
+
+/// Vertical stack of the views listed, like `vstack((a, b, c))` without
+/// the extra parentheses.
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(vstack![text("one"), text("two"), text("three")]);
+/// ```
+#[macro_export]
+macro_rules! vstack {
+    ($($child:expr),* $(,)?) => {
+        $crate::vstack(($($child,)*))
+    };
+}
+
+/// Horizontal stack of the views listed. See `vstack!`.
+#[macro_export]
+macro_rules! hstack {
+    ($($child:expr),* $(,)?) => {
+        $crate::hstack(($($child,)*))
+    };
+}
+
+/// Stack of the views listed, overlaid. See `vstack!`.
+#[macro_export]
+macro_rules! zstack {
+    ($($child:expr),* $(,)?) => {
+        $crate::zstack(($($child,)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_stack_macros() {
+        let mut h = Harness::new(vstack![
+            text("top"),
+            hstack![text("left"), text("right"),],
+            zstack![text("one")],
+        ]);
+        let top = h.find_text("top").unwrap();
+        let left = h.find_text("left").unwrap();
+        let right = h.find_text("right").unwrap();
+        assert!(top.center().y > left.center().y);
+        assert!(left.center().x < right.center().x);
+        h.expect_text("one");
+    }
+}
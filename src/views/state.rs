@@ -42,9 +42,23 @@ impl<S: 'static> Binding<S> for StateHandle<S> {
 }
 
 #[derive(Clone)]
-struct StateView<D, F> {
-    default: D,
-    func: F,
+pub(crate) struct StateView<D, F> {
+    pub(crate) default: D,
+    pub(crate) func: F,
+}
+
+impl<D, F> StateView<D, F> {
+    /// Runs the body for the state `id`, noting whose body it is for
+    /// `on_state_change`.
+    fn body<S: 'static, V>(&self, id: ViewId, cx: &Context) -> V
+    where
+        F: Fn(StateHandle<S>, &Context) -> V,
+    {
+        let outer = cx.observers.enter_body(id);
+        let view = (self.func)(StateHandle::new(id), cx);
+        cx.observers.exit_body(outer);
+        view
+    }
 }
 
 impl<S, V, D, F> View for StateView<D, F>
@@ -64,16 +78,18 @@ where
         let id = cx.view_id(path);
         cx.init_state(id, &self.default);
         path.push(0);
-        (self.func)(StateHandle::new(id), cx).process(event, path, cx, actions);
+        self.body(id, cx).process(event, path, cx, actions);
         path.pop();
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let id = args.cx.view_id(path);
         args.cx.init_state(id, &self.default);
+        let reads = args.cx.begin_reads();
         path.push(0);
-        (self.func)(StateHandle::new(id), args.cx).draw(path, args);
+        self.body(id, args.cx).draw(path, args);
         path.pop();
+        args.cx.end_reads(id, reads);
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
@@ -84,30 +100,36 @@ where
         let mut compute_layout = true;
 
         if let Some(deps) = args.cx.deps.get(&id) {
-            let mut any_dirty = false;
+            let mut dirty_dep = None;
             for dep in deps {
                 if let Some(holder) = args.cx.state_map.get_mut(dep) {
                     if holder.dirty {
-                        any_dirty = true;
+                        dirty_dep = Some(*dep);
                         break;
                     }
                 }
             }
 
-            compute_layout = any_dirty;
+            compute_layout = dirty_dep.is_some();
+            if let Some(dep) = dirty_dep {
+                args.cx.strict_note_rebuild(id, dep);
+            }
         }
 
         if compute_layout {
             args.cx.id_stack.push(id);
 
-            let view = (self.func)(StateHandle::new(id), args.cx);
+            let reads = args.cx.begin_reads();
+            let view = self.body(id, args.cx);
 
             path.push(0);
             let child_size = view.layout(path, args);
+            args.cx.end_reads(id, reads);
 
             // Compute layout dependencies.
             let mut deps = vec![];
             deps.append(&mut args.cx.id_stack.clone());
+            deps.extend_from_slice(&args.cx.body_reads[&id]);
             view.gc(path, args.cx, &mut deps);
 
             path.pop();
@@ -132,22 +154,16 @@ where
         let holder = cx.state_map.entry(id).or_insert_with(|| StateHolder {
             state: Box::new((default)()),
             dirty: false,
+            type_name: std::any::type_name::<S>(),
         });
 
         if holder.dirty {
             // Add a region.
             let rect = cx.get_layout(path).rect;
-            let pts: [LocalPoint; 4] = [
-                rect.min(),
-                [rect.max_x(), rect.min_y()].into(),
-                [rect.min_x(), rect.max_y()].into(),
-                rect.max(),
-            ];
-            let world_pts = pts.map(|p| xform.transform_point(p));
-            cx.dirty_region.add_rect(WorldRect::from_points(world_pts));
-        } else {
+            cx.add_dirty_rect(path, rect, xform);
+        } else if !cx.add_read_damage(id, path, xform) {
             path.push(0);
-            (self.func)(StateHandle::new(id), cx).dirty(path, xform, cx);
+            self.body(id, cx).dirty(path, xform, cx);
             path.pop();
         }
     }
@@ -156,7 +172,7 @@ where
         let id = cx.view_id(path);
         cx.init_state(id, &self.default);
         path.push(0);
-        let hit_id = (self.func)(StateHandle::new(id), cx).hittest(path, pt, cx);
+        let hit_id = self.body(id, cx).hittest(path, pt, cx);
         path.pop();
         hit_id
     }
@@ -165,7 +181,7 @@ where
         let id = cx.view_id(path);
         cx.init_state(id, &self.default);
         path.push(0);
-        (self.func)(StateHandle::new(id), cx).commands(path, cx, cmds);
+        self.body(id, cx).commands(path, cx, cmds);
         path.pop();
     }
 
@@ -173,8 +189,13 @@ where
         let id = cx.view_id(path);
         cx.init_state(id, &self.default);
         map.push(id);
+        // So views around this one are laid out again when what it read
+        // changes.
+        if let Some(reads) = cx.body_reads.get(&id) {
+            map.extend_from_slice(reads);
+        }
         path.push(0);
-        (self.func)(StateHandle::new(id), cx).gc(path, cx, map);
+        self.body(id, cx).gc_view(path, cx, map);
         path.pop();
     }
 
@@ -187,7 +208,7 @@ where
         let id = cx.view_id(path);
         cx.init_state(id, &self.default);
         path.push(0);
-        let node_id = (self.func)(StateHandle::new(id), cx).access(path, cx, nodes);
+        let node_id = self.body(id, cx).access(path, cx, nodes);
         path.pop();
         node_id
     }
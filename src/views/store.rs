@@ -0,0 +1,312 @@
+use crate::*;
+use std::any::Any;
+use std::rc::Rc;
+
+/// The nearest enclosing `store` of an `S`, passed down in the
+/// environment.
+struct StoreContext<S>(Option<StateHandle<S>>);
+
+impl<S> Clone for StoreContext<S> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+impl<S> Default for StoreContext<S> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+/// Struct for `store`.
+pub struct Store<S, A, R, I, V> {
+    reducer: R,
+    initial: I,
+    content: V,
+    phantom: std::marker::PhantomData<(S, A)>,
+}
+
+impl<S, A, R, I, V> Store<S, A, R, I, V>
+where
+    S: 'static,
+    A: 'static,
+    R: Fn(&mut S, &A) + 'static,
+    I: Fn() -> S + 'static,
+    V: View,
+{
+    fn handle(&self, path: &IdPath, cx: &mut Context) -> StateHandle<S> {
+        let id = cx.view_id(path);
+        cx.init_state(id, &self.initial);
+        StateHandle::new(id)
+    }
+
+    /// Calls `f` with the store in the environment.
+    fn with_store<T>(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        f: impl FnOnce(&V, &mut IdPath, &mut Context) -> T,
+    ) -> T {
+        let handle = self.handle(path, cx);
+        let old = cx.set_env(&StoreContext(Some(handle)));
+        path.push(0);
+        let result = f(&self.content, path, cx);
+        path.pop();
+        old.and_then(|s| cx.set_env(&s));
+        result
+    }
+
+    fn reduce(&self, handle: StateHandle<S>, action: &A, cx: &mut Context) {
+        (self.reducer)(&mut cx[handle], action)
+    }
+}
+
+impl<S, A, R, I, V> View for Store<S, A, R, I, V>
+where
+    S: 'static,
+    A: 'static,
+    R: Fn(&mut S, &A) + 'static,
+    I: Fn() -> S + 'static,
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let mut child_actions = vec![];
+        self.with_store(path, cx, |content, path, cx| {
+            content.process(event, path, cx, &mut child_actions)
+        });
+
+        let handle = self.handle(path, cx);
+        for action in child_actions {
+            match action.downcast::<A>() {
+                Ok(action) => self.reduce(handle, &action, cx),
+                Err(action) => actions.push(action),
+            }
+        }
+        for action in cx.take_dispatched::<A>() {
+            self.reduce(handle, &action, cx);
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let (cx, vger) = (&mut *args.cx, &mut *args.vger);
+        self.with_store(path, cx, |content, path, cx| {
+            content.draw(path, &mut DrawArgs { cx, vger })
+        });
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let (sz, text_bounds) = (args.sz, &mut *args.text_bounds);
+        let size = self.with_store(path, args.cx, |content, path, cx| {
+            content.layout(
+                path,
+                &mut LayoutArgs {
+                    sz,
+                    cx,
+                    text_bounds,
+                },
+            )
+        });
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        self.with_store(path, cx, |content, path, cx| content.dirty(path, xform, cx));
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        self.with_store(path, cx, |content, path, cx| content.hittest(path, pt, cx))
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        self.with_store(path, cx, |content, path, cx| {
+            content.commands(path, cx, cmds)
+        });
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        self.with_store(path, cx, |content, path, cx| content.gc_view(path, cx, map));
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        self.with_store(path, cx, |content, path, cx| {
+            content.access(path, cx, nodes)
+        })
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.content.is_flexible()
+    }
+}
+
+impl<S, A, R, I, V> private::Sealed for Store<S, A, R, I, V> {}
+
+/// Keeps app state made by `initial` in one place, for the views in
+/// `content` to read with `connect`. The state only changes by `reducer`
+/// applying actions, which come from the views inside, like those made by
+/// `button_a` or returned from `tap`, or from `Context::dispatch`.
+///
+/// Other actions pass on to the views around the store, so stores with
+/// different action types can be nested.
+pub fn store<S, A, R, I, V>(reducer: R, initial: I, content: V) -> Store<S, A, R, I, V>
+where
+    S: 'static,
+    A: 'static,
+    R: Fn(&mut S, &A) + 'static,
+    I: Fn() -> S + 'static,
+    V: View,
+{
+    Store {
+        reducer,
+        initial,
+        content,
+        phantom: Default::default(),
+    }
+}
+
+/// Shows the part of the enclosing `store`'s state picked by `selector`.
+/// The view made by `f` is only rebuilt and laid out again when that
+/// part changes, so other changes to the store don't touch it. See
+/// `memo`.
+///
+/// Panics if there's no `store` of an `S` around it.
+pub fn connect<S, T, V, Sel, F>(selector: Sel, f: F) -> impl View
+where
+    S: 'static,
+    T: PartialEq + Clone + 'static,
+    V: View,
+    Sel: Fn(&S) -> T + 'static,
+    F: Fn(&T) -> V + 'static,
+{
+    let f = Rc::new(f);
+    env(move |store: StoreContext<S>, cx| {
+        let handle = store.0.unwrap_or_else(|| {
+            panic!(
+                "connect needs a store of {} around it",
+                std::any::type_name::<S>()
+            )
+        });
+        let slice = selector(&cx[handle]);
+        let f = f.clone();
+        memo(slice.clone(), move || f(&slice))
+    })
+}
+
+impl Context {
+    /// Sends an action to the nearest enclosing `store` whose reducer
+    /// takes actions of its type, for handlers which don't return actions,
+    /// like `on_main` callbacks. It's applied the next time the store
+    /// processes an event, which is before the next update.
+    pub fn dispatch<A: 'static>(&mut self, action: A) {
+        self.dispatched.push(Box::new(action));
+    }
+
+    pub(crate) fn take_dispatched<A: 'static>(&mut self) -> Vec<A> {
+        let (mine, others): (Vec<_>, Vec<_>) = std::mem::take(&mut self.dispatched)
+            .into_iter()
+            .partition(|action| (**action).is::<A>());
+        self.dispatched = others;
+        mine.into_iter()
+            .filter_map(|action| action.downcast().ok())
+            .map(|action| *action)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct AppState {
+        count: i32,
+        name: String,
+    }
+
+    #[derive(Clone)]
+    enum Action {
+        Increment,
+        Rename(&'static str),
+    }
+
+    fn reduce(state: &mut AppState, action: &Action) {
+        match action {
+            Action::Increment => state.count += 1,
+            Action::Rename(name) => state.name = name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_store() {
+        let builds = Rc::new(Cell::new(0));
+        let b = builds.clone();
+        let view = store(
+            reduce,
+            AppState::default,
+            vstack((
+                connect(
+                    |s: &AppState| s.count,
+                    move |count| {
+                        b.set(b.get() + 1);
+                        text(&format!("count {}", count))
+                    },
+                ),
+                connect(
+                    |s: &AppState| s.name.clone(),
+                    |name| text(&format!("name {:?}", name)),
+                ),
+                button_a("increment", Action::Increment),
+                button("rename", |cx| cx.dispatch(Action::Rename("ada"))),
+            )),
+        );
+        let mut h = Harness::new(view);
+        assert_eq!(builds.get(), 1);
+
+        h.click("increment");
+        h.expect_text("count 1");
+        assert_eq!(builds.get(), 2);
+
+        // Renaming doesn't rebuild the count.
+        h.click("rename");
+        h.expect_text("name \"ada\"");
+        h.expect_text("count 1");
+        assert_eq!(builds.get(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_from_main() {
+        let view = store(
+            reduce,
+            AppState::default,
+            connect(
+                |s: &AppState| s.count,
+                |count| text(&format!("count {}", count)),
+            ),
+        );
+        let mut h = Harness::new(view);
+        h.context().dispatch(Action::Increment);
+        h.context().dispatch(Action::Increment);
+        h.update();
+        h.expect_text("count 2");
+    }
+}
@@ -0,0 +1,399 @@
+use crate::*;
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Progress of a value being loaded by `load`.
+pub enum Loadable<T> {
+    NotStarted,
+    Loading,
+    Ready(T),
+    Failed(String),
+}
+
+/// Counts the loads still running inside a `suspense`.
+#[derive(Default)]
+struct SuspenseState {
+    pending: usize,
+    error: Option<String>,
+}
+
+/// The nearest enclosing `suspense`, passed down in the environment.
+#[derive(Clone, Default)]
+struct SuspenseBoundary(Option<StateHandle<SuspenseState>>);
+
+/// Struct for `load`. Starts the load the first time it's laid out.
+struct Load<T, L, V> {
+    state: StateHandle<Loadable<T>>,
+    boundary: SuspenseBoundary,
+    fetch: Arc<L>,
+    child: V,
+}
+
+impl<T, E, L, Fut, V> Load<T, L, V>
+where
+    T: Send + 'static,
+    E: std::fmt::Display,
+    L: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    V: View,
+{
+    fn start(&self, cx: &mut Context) {
+        if !matches!(cx[self.state], Loadable::NotStarted) {
+            return;
+        }
+        cx[self.state] = Loadable::Loading;
+
+        let boundary = self.boundary.0;
+        if let Some(b) = boundary {
+            cx[b].pending += 1;
+        }
+
+        let state = self.state;
+        let fetch = (self.fetch)();
+        cx.spawn(async move {
+            let result = match fetch.await {
+                Ok(value) => Loadable::Ready(value),
+                Err(err) => Loadable::Failed(err.to_string()),
+            };
+            move |cx: &mut Context| {
+                // The views may have gone away while we were loading.
+                if let Some(b) = boundary.filter(|b| cx.state_map.contains_key(&b.id)) {
+                    cx[b].pending -= 1;
+                    if let Loadable::Failed(err) = &result {
+                        cx[b].error.get_or_insert_with(|| err.clone());
+                    }
+                }
+                if cx.state_map.contains_key(&state.id) {
+                    cx[state] = result;
+                }
+            }
+        });
+    }
+}
+
+impl<T, E, L, Fut, V> View for Load<T, L, V>
+where
+    T: Send + 'static,
+    E: std::fmt::Display,
+    L: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        self.start(args.cx);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        self.start(args.cx);
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<T, L, V> private::Sealed for Load<T, L, V> {}
+
+/// Runs the future returned by `fetch` on rui's thread pool, or the
+/// page's event loop on the web, then shows `f` with the result.
+///
+/// Inside a `suspense`, the suspense shows its fallback until the value
+/// is ready. Otherwise nothing is shown while loading, and an error
+/// message if `fetch` fails.
+pub fn load<T, E, L, Fut, V, F>(fetch: L, f: F) -> impl View
+where
+    T: Send + 'static,
+    E: std::fmt::Display,
+    L: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    V: View,
+    F: Fn(&T, &Context) -> V + 'static,
+{
+    let fetch = Arc::new(fetch);
+    let f = Arc::new(f);
+    env(move |boundary: SuspenseBoundary, _| {
+        let fetch = fetch.clone();
+        let f = f.clone();
+        state(
+            || Loadable::<T>::NotStarted,
+            move |s, cx| {
+                let child = match &cx[s] {
+                    Loadable::Ready(value) => Some(AnyView::new(f(value, cx))),
+                    Loadable::Failed(err) if boundary.0.is_none() => {
+                        Some(AnyView::new(text(err).color(RED_HIGHLIGHT)))
+                    }
+                    _ => None,
+                };
+                Load {
+                    state: s,
+                    boundary: boundary.clone(),
+                    fetch: fetch.clone(),
+                    child: child.unwrap_or_else(|| AnyView::new(EmptyView {})),
+                }
+            },
+        )
+    })
+}
+
+/// Struct for `suspense`. Always lays out the content, so loads inside it
+/// start, but only shows it when `overlay` is `None`.
+struct Suspense<V> {
+    content: V,
+    overlay: Option<AnyView>,
+}
+
+impl<V: View> View for Suspense<V> {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        match &self.overlay {
+            Some(overlay) => {
+                path.push(1);
+                overlay.process(event, path, cx, actions);
+            }
+            None => {
+                path.push(0);
+                self.content.process(event, path, cx, actions);
+            }
+        }
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        match &self.overlay {
+            Some(overlay) => {
+                path.push(1);
+                overlay.draw(path, args);
+            }
+            None => {
+                path.push(0);
+                self.content.draw(path, args);
+            }
+        }
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let mut sz = self.content.layout(path, args);
+        path.pop();
+
+        if let Some(overlay) = &self.overlay {
+            path.push(1);
+            sz = overlay.layout(path, args);
+            path.pop();
+        }
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.content.dirty(path, xform, cx);
+        path.pop();
+        if let Some(overlay) = &self.overlay {
+            path.push(1);
+            overlay.dirty(path, xform, cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let id = match &self.overlay {
+            Some(overlay) => {
+                path.push(1);
+                overlay.hittest(path, pt, cx)
+            }
+            None => {
+                path.push(0);
+                self.content.hittest(path, pt, cx)
+            }
+        };
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        if self.overlay.is_none() {
+            path.push(0);
+            self.content.commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.content.gc_view(path, cx, map);
+        path.pop();
+        if let Some(overlay) = &self.overlay {
+            path.push(1);
+            overlay.gc_view(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let node_id = match &self.overlay {
+            Some(overlay) => {
+                path.push(1);
+                overlay.access(path, cx, nodes)
+            }
+            None => {
+                path.push(0);
+                self.content.access(path, cx, nodes)
+            }
+        };
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for Suspense<V> {}
+
+/// Shows `fallback` until every `load` inside `content` has finished, so
+/// a page with many fetches appears all at once. If any load fails, the
+/// first error is shown instead.
+pub fn suspense<V: View, F: Fn() -> V + 'static, FB: View + Clone>(
+    content: F,
+    fallback: FB,
+) -> impl View {
+    state(SuspenseState::default, move |s, cx| {
+        let overlay = match &cx[s].error {
+            Some(err) => Some(AnyView::new(text(err).color(RED_HIGHLIGHT))),
+            None if cx[s].pending > 0 => Some(AnyView::new(fallback.clone())),
+            None => None,
+        };
+        Suspense {
+            content: content().env(SuspenseBoundary(Some(s))),
+            overlay,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Updates until `done`, or panics after a second.
+    fn wait_until<V: View>(harness: &mut Harness<V>, done: impl Fn(&mut Harness<V>) -> bool) {
+        let start = Instant::now();
+        while !done(harness) {
+            assert!(start.elapsed() < Duration::from_secs(1), "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+            harness.update();
+        }
+    }
+
+    fn fetch_after(ms: u64, result: Result<&'static str, &'static str>) -> impl View {
+        load(
+            move || async move {
+                std::thread::sleep(Duration::from_millis(ms));
+                result
+            },
+            |value, _| text(value),
+        )
+    }
+
+    #[test]
+    fn test_suspense_waits_for_all() {
+        let view = suspense(
+            || vstack((fetch_after(10, Ok("first")), fetch_after(50, Ok("second")))),
+            text("loading"),
+        );
+        let mut harness = Harness::new(view);
+        harness.expect_text("loading");
+
+        wait_until(&mut harness, |h| !h.has_text("loading"));
+        harness.expect_text("first");
+        harness.expect_text("second");
+    }
+
+    #[test]
+    fn test_suspense_error() {
+        let view = suspense(
+            || {
+                vstack((
+                    fetch_after(0, Ok("fine")),
+                    fetch_after(0, Err("no network")),
+                ))
+            },
+            text("loading"),
+        );
+        let mut harness = Harness::new(view);
+
+        wait_until(&mut harness, |h| !h.has_text("loading"));
+        harness.expect_text("no network");
+        harness.expect_no_text("fine");
+    }
+}
@@ -0,0 +1,673 @@
+use crate::*;
+use std::any::Any;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::Range;
+
+const DEFAULT_COLUMN_WIDTH: f32 = 100.0;
+const MIN_COLUMN_WIDTH: f32 = 20.0;
+const DEFAULT_ROW_HEIGHT: f32 = 24.0;
+const HEADER_HEIGHT: f32 = 28.0;
+
+/// Space between a cell's contents and its left edge.
+const CELL_PADDING: f32 = 6.0;
+
+/// Width of the area around the edge between two headers which can be
+/// dragged to resize a column.
+const RESIZE_GRAB: f32 = 6.0;
+
+/// Size of the arrow in the header of the column a table is sorted by.
+const SORT_ARROW: f32 = 3.0;
+
+/// Which way a table is sorted by a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn reversed(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+}
+
+type CellFunc<R> = Box<dyn Fn(&R) -> AnyView>;
+
+/// A column of a `table`.
+pub struct Column<R> {
+    title: String,
+    width: f32,
+    cell: CellFunc<R>,
+}
+
+/// A column headed `title`, showing what `cell` makes for each row.
+pub fn column<R, V: View>(title: &str, cell: impl Fn(&R) -> V + 'static) -> Column<R> {
+    Column {
+        title: title.to_string(),
+        width: DEFAULT_COLUMN_WIDTH,
+        cell: Box::new(move |row| any_view(cell(row))),
+    }
+}
+
+impl<R> Column<R> {
+    /// How wide the column starts, before it's resized.
+    pub fn width(self, width: f32) -> Self {
+        Self {
+            width: width.max(MIN_COLUMN_WIDTH),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct TableState {
+    /// Widths of the columns once any has been resized.
+    widths: Vec<f32>,
+
+    /// Distance scrolled down from the first row.
+    scroll: f32,
+
+    size: LocalSize,
+
+    /// The column sorted by, once a header's been clicked.
+    sort: Option<(usize, SortOrder)>,
+
+    /// A touch resizing a column, and which.
+    resize: Option<(usize, usize)>,
+
+    /// A touch pressing a header, and which.
+    press: Option<(usize, usize)>,
+
+    select: RowSelection,
+}
+
+fn table_state(vid: ViewId, cx: &mut Context) -> &mut TableState {
+    cx.init_state(vid, &TableState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+type SortFunc = Box<dyn Fn(&mut Context, usize, SortOrder)>;
+
+/// Struct for `table`.
+pub struct Table<R> {
+    rows: Vec<R>,
+    columns: Vec<Column<R>>,
+    row_height: f32,
+    selection: Option<Box<dyn ListSelection<R>>>,
+    on_sort: Option<SortFunc>,
+}
+
+/// Shows `rows` in `columns`, under a header with each column's title.
+/// Columns are resized by dragging the edges between headers.
+///
+/// Only the rows which can be seen are built and laid out, so tables can
+/// have many rows. All rows are the same height, and cells scrolled out of
+/// view lose their state. The mouse wheel scrolls.
+pub fn table<R: Hash + 'static>(rows: Vec<R>, columns: Vec<Column<R>>) -> Table<R> {
+    Table {
+        rows,
+        columns,
+        row_height: DEFAULT_ROW_HEIGHT,
+        selection: None,
+        on_sort: None,
+    }
+}
+
+impl<R> Table<R>
+where
+    R: Clone + Eq + Hash + 'static,
+{
+    /// Lets rows be selected by clicking them, as with `List::selection`.
+    pub fn selection(self, selection: impl Binding<HashSet<R>>) -> Self {
+        Self {
+            selection: Some(Box::new(SetBinding(selection))),
+            ..self
+        }
+    }
+}
+
+impl<R> Table<R>
+where
+    R: Hash + 'static,
+{
+    pub fn row_height(self, height: f32) -> Self {
+        Self {
+            row_height: height.max(1.0),
+            ..self
+        }
+    }
+
+    /// Calls `f` with the index of a column and the order to sort by when
+    /// its header is clicked. Clicking the same header again reverses the
+    /// order. Sorting the rows is left to `f`.
+    pub fn on_sort(self, f: impl Fn(&mut Context, usize, SortOrder) + 'static) -> Self {
+        Self {
+            on_sort: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    fn widths(&self, state: &TableState) -> Vec<f32> {
+        if state.widths.len() == self.columns.len() {
+            state.widths.clone()
+        } else {
+            self.columns.iter().map(|c| c.width).collect()
+        }
+    }
+
+    /// Left edge of each column, and the right edge of the last.
+    fn edges(&self, state: &TableState) -> Vec<f32> {
+        let mut x = 0.0;
+        let mut edges = vec![0.0];
+        for width in self.widths(state) {
+            x += width;
+            edges.push(x);
+        }
+        edges
+    }
+
+    fn body_height(state: &TableState) -> f32 {
+        (state.size.height - HEADER_HEIGHT).max(0.0)
+    }
+
+    fn max_scroll(&self, state: &TableState) -> f32 {
+        (self.rows.len() as f32 * self.row_height - Self::body_height(state)).max(0.0)
+    }
+
+    /// Rows which can be seen.
+    fn visible(&self, state: &TableState) -> Range<usize> {
+        let first = (state.scroll / self.row_height).floor() as usize;
+        let last = ((state.scroll + Self::body_height(state)) / self.row_height).ceil() as usize;
+        first.min(self.rows.len())..last.min(self.rows.len())
+    }
+
+    /// Bounds of a row. The header is at the top, further up the y axis.
+    fn row_rect(&self, state: &TableState, row: usize) -> LocalRect {
+        let top = Self::body_height(state) + state.scroll - row as f32 * self.row_height;
+        LocalRect::new(
+            [0.0, top - self.row_height].into(),
+            [state.size.width, self.row_height].into(),
+        )
+    }
+
+    fn row_at(&self, state: &TableState, pt: LocalPoint) -> Option<usize> {
+        let body = Self::body_height(state);
+        if pt.y < 0.0 || pt.y >= body || pt.x < 0.0 || pt.x > state.size.width {
+            return None;
+        }
+        let row = ((body - pt.y + state.scroll) / self.row_height) as usize;
+        (row < self.rows.len()).then_some(row)
+    }
+
+    fn in_header(state: &TableState, pt: LocalPoint) -> bool {
+        let body = Self::body_height(state);
+        pt.y >= body && pt.y <= state.size.height && pt.x >= 0.0 && pt.x <= state.size.width
+    }
+
+    /// The column whose right edge is under a point in the header.
+    fn resize_edge(&self, state: &TableState, pt: LocalPoint) -> Option<usize> {
+        if !Self::in_header(state, pt) {
+            return None;
+        }
+        self.edges(state)[1..]
+            .iter()
+            .position(|x| (pt.x - x).abs() <= RESIZE_GRAB / 2.0)
+    }
+
+    fn header_at(&self, state: &TableState, pt: LocalPoint) -> Option<usize> {
+        if !Self::in_header(state, pt) {
+            return None;
+        }
+        self.edges(state)
+            .windows(2)
+            .position(|edge| pt.x >= edge[0] && pt.x < edge[1])
+    }
+
+    /// Calls `f` with each cell which can be seen, and its path.
+    fn for_each_cell(
+        &self,
+        state: &TableState,
+        path: &mut IdPath,
+        mut f: impl FnMut(usize, usize, &dyn View, &mut IdPath),
+    ) {
+        for row in self.visible(state) {
+            path.push(hh(&self.rows[row]));
+            for (i, column) in self.columns.iter().enumerate() {
+                path.push(i as u64);
+                f(row, i, &(column.cell)(&self.rows[row]), path);
+                path.pop();
+            }
+            path.pop();
+        }
+    }
+
+    /// Calls `f` with the title of each column, and its path.
+    fn for_each_title(&self, path: &mut IdPath, mut f: impl FnMut(usize, &dyn View, &mut IdPath)) {
+        path.push(0);
+        for (i, column) in self.columns.iter().enumerate() {
+            path.push(i as u64);
+            f(i, &text(&column.title), path);
+            path.pop();
+        }
+        path.pop();
+    }
+
+    fn header_event(
+        &self,
+        vid: ViewId,
+        state: &TableState,
+        event: &Event,
+        cx: &mut Context,
+    ) -> bool {
+        match event {
+            Event::TouchBegin { id, position } => {
+                if let Some(column) = self.resize_edge(state, *position) {
+                    table_state(vid, cx).resize = Some((*id, column));
+                    cx.set_cursor(CursorIcon::ResizeHorizontal);
+                } else if let Some(column) = self.header_at(state, *position) {
+                    table_state(vid, cx).press = Some((*id, column));
+                } else {
+                    return false;
+                }
+                cx.touches[*id] = vid;
+                true
+            }
+            Event::TouchMove { id, delta, .. } => match state.resize {
+                Some((touch, column)) if touch == *id => {
+                    let mut widths = self.widths(state);
+                    widths[column] = (widths[column] + delta.x).max(MIN_COLUMN_WIDTH);
+                    cx[StateHandle::<TableState>::new(vid)].widths = widths;
+                    cx.set_cursor(CursorIcon::ResizeHorizontal);
+                    true
+                }
+                _ => state.press.is_some_and(|(touch, _)| touch == *id),
+            },
+            Event::TouchEnd { id, position } => {
+                let resized = state.resize.is_some_and(|(touch, _)| touch == *id);
+                let pressed = state.press.filter(|(touch, _)| touch == id);
+                if !resized && pressed.is_none() {
+                    return false;
+                }
+                let table = table_state(vid, cx);
+                table.resize = None;
+                table.press = None;
+                if cx.touches[*id] == vid {
+                    cx.touches[*id] = ViewId::default();
+                }
+
+                if let Some((_, column)) = pressed {
+                    if self.header_at(state, *position) == Some(column) {
+                        if let Some(on_sort) = &self.on_sort {
+                            let order = match state.sort {
+                                Some((sorted, order)) if sorted == column => order.reversed(),
+                                _ => SortOrder::Ascending,
+                            };
+                            cx[StateHandle::<TableState>::new(vid)].sort = Some((column, order));
+                            on_sort(cx, column, order);
+                        }
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<R> View for Table<R>
+where
+    R: Hash + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let state = table_state(vid, cx).clone();
+        if self.header_event(vid, &state, event, cx) {
+            return;
+        }
+
+        if let Event::Scroll { position, delta } = event {
+            let rect = LocalRect::new(LocalPoint::zero(), state.size);
+            if rect.contains(*position) && !cx.scroll_consumed {
+                let scroll = (state.scroll + delta.y).clamp(0.0, self.max_scroll(&state));
+                if scroll != state.scroll {
+                    cx[StateHandle::<TableState>::new(vid)].scroll = scroll;
+                    cx.scroll_consumed = true;
+                }
+            }
+        }
+
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            let offset = cx.get_layout(path).offset;
+            cell.process(&event.offset(-offset), path, cx, actions);
+        });
+
+        if let Some(selection) = &self.selection {
+            let row = match event {
+                Event::TouchBegin { position, .. } | Event::TouchMove { position, .. } => {
+                    self.row_at(&state, *position)
+                }
+                _ => None,
+            };
+            select_rows(
+                selection.as_ref(),
+                &self.rows,
+                row,
+                event,
+                |cx| &mut table_state(vid, cx).select,
+                cx,
+            );
+        }
+
+        // As with `cursor`, cells' own cursors go first.
+        if let Event::TouchMove { position, .. } = event {
+            if cx.mouse_button.is_none()
+                && cx.cursor.is_none()
+                && self.resize_edge(&state, *position).is_some()
+            {
+                cx.cursor = Some(CursorIcon::ResizeHorizontal);
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let vid = args.cx.view_id(path);
+        let state = table_state(vid, args.cx).clone();
+        let theme = args.cx.theme();
+        let rect = LocalRect::new(LocalPoint::zero(), state.size);
+
+        args.vger.save();
+        args.vger.scissor(rect);
+        let world_rect = args.vger.current_transform().outer_transformed_rect(&rect);
+        args.cx.push_clip(world_rect);
+
+        if let Some(selection) = &self.selection {
+            let selected = selection.selected(args.cx, &self.rows);
+            let paint = args.vger.color_paint(theme.accent_background);
+            for row in self.visible(&state).filter(|row| selected[*row]) {
+                args.vger.fill_rect(self.row_rect(&state, row), 0.0, paint);
+            }
+        }
+
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            let offset = args.cx.get_layout(path).offset;
+            args.vger.save();
+            args.vger.translate(offset);
+            cell.draw(path, args);
+            args.vger.restore();
+        });
+
+        // Header over the rows.
+        let body = Self::body_height(&state);
+        let header = LocalRect::new([0.0, body].into(), [state.size.width, HEADER_HEIGHT].into());
+        let paint = args.vger.color_paint(theme.control_background);
+        args.vger.fill_rect(header, 0.0, paint);
+        let edges = self.edges(&state);
+        let paint = args.vger.color_paint(theme.control_foreground);
+        for x in &edges[1..] {
+            args.vger.stroke_segment(
+                LocalPoint::new(*x, body + 4.0),
+                LocalPoint::new(*x, state.size.height - 4.0),
+                1.0,
+                paint,
+            );
+        }
+        if let Some((column, order)) = state.sort.filter(|(c, _)| *c < self.columns.len()) {
+            let c = LocalPoint::new(
+                edges[column + 1] - CELL_PADDING - SORT_ARROW,
+                body + HEADER_HEIGHT / 2.0,
+            );
+            let dy = match order {
+                SortOrder::Ascending => SORT_ARROW / 2.0,
+                SortOrder::Descending => -SORT_ARROW / 2.0,
+            };
+            let paint = args.vger.color_paint(theme.text);
+            let tip = c + LocalOffset::new(0.0, dy);
+            let left = c + LocalOffset::new(-SORT_ARROW, -dy);
+            let right = c + LocalOffset::new(SORT_ARROW, -dy);
+            args.vger.stroke_segment(left, tip, 1.0, paint);
+            args.vger.stroke_segment(tip, right, 1.0, paint);
+        }
+        self.for_each_title(path, |_, title, path| {
+            let offset = args.cx.get_layout(path).offset;
+            args.vger.save();
+            args.vger.translate(offset);
+            title.draw(path, args);
+            args.vger.restore();
+        });
+
+        args.cx.pop_clip();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let vid = args.cx.view_id(path);
+        let fill = |given: f32, wanted: f32| if given.is_finite() { given } else { wanted };
+        let mut state = table_state(vid, args.cx).clone();
+        let edges = self.edges(&state);
+        state.size = LocalSize::new(
+            fill(args.sz.width, *edges.last().unwrap()),
+            fill(
+                args.sz.height,
+                HEADER_HEIGHT + self.rows.len() as f32 * self.row_height,
+            ),
+        );
+        // Rows may have been removed since we scrolled.
+        state.scroll = state.scroll.min(self.max_scroll(&state));
+        let size = state.size;
+        let body = Self::body_height(&state);
+
+        self.for_each_cell(&state, path, |row, i, cell, path| {
+            let width = (edges[i + 1] - edges[i] - 2.0 * CELL_PADDING).max(0.0);
+            let rect = self.row_rect(&state, row);
+            let cell_size =
+                cell.layout(path, &mut args.size(LocalSize::new(width, self.row_height)));
+            let offset = LocalOffset::new(
+                edges[i] + CELL_PADDING,
+                rect.origin.y + (self.row_height - cell_size.height) / 2.0,
+            );
+            args.cx.set_layout_offset(path, offset);
+        });
+        self.for_each_title(path, |i, title, path| {
+            let width = (edges[i + 1] - edges[i] - 2.0 * (CELL_PADDING + SORT_ARROW)).max(0.0);
+            let title_size =
+                title.layout(path, &mut args.size(LocalSize::new(width, HEADER_HEIGHT)));
+            let offset = LocalOffset::new(
+                edges[i] + CELL_PADDING,
+                body + (HEADER_HEIGHT - title_size.height) / 2.0,
+            );
+            args.cx.set_layout_offset(path, offset);
+        });
+
+        let table = table_state(vid, args.cx);
+        table.size = size;
+        table.scroll = state.scroll;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        if cx.state_map.get(&vid).is_some_and(|holder| holder.dirty) {
+            // Scrolled, resized or sorted, so redraw it all.
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(path, rect, xform);
+            return;
+        }
+        let state = table_state(vid, cx).clone();
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            let offset = cx.get_layout(path).offset;
+            cell.dirty(path, xform.pre_translate(offset), cx);
+        });
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let vid = cx.view_id(path);
+        let state = table_state(vid, cx).clone();
+        let rect = LocalRect::new(LocalPoint::zero(), state.size);
+        if cx.text_probe.is_none() && !rect.contains(pt) {
+            return None;
+        }
+        let mut hit = None;
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            let offset = cx.get_layout(path).offset;
+            hit = cell.hittest(path, pt - offset, cx).or(hit);
+        });
+        self.for_each_title(path, |_, title, path| {
+            let offset = cx.get_layout(path).offset;
+            hit = title.hittest(path, pt - offset, cx).or(hit);
+        });
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let vid = cx.view_id(path);
+        let state = table_state(vid, cx).clone();
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            cell.commands(path, cx, cmds)
+        });
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        let vid = cx.view_id(path);
+        map.push(vid);
+        let state = table_state(vid, cx).clone();
+        self.for_each_cell(&state, path, |_, _, cell, path| cell.gc_view(path, cx, map));
+        self.for_each_title(path, |_, title, path| title.gc_view(path, cx, map));
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let vid = cx.view_id(path);
+        let state = table_state(vid, cx).clone();
+        let mut children = vec![];
+        self.for_each_title(path, |_, title, path| {
+            children.extend(title.access(path, cx, nodes))
+        });
+        self.for_each_cell(&state, path, |_, _, cell, path| {
+            children.extend(cell.access(path, cx, nodes))
+        });
+
+        let aid = vid.access_id();
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::Table);
+        builder.set_children(children);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<R> private::Sealed for Table<R> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const ROWS: usize = 10_000;
+
+    fn numbers() -> impl View {
+        state(
+            || SortOrder::Ascending,
+            |order, _| {
+                state(HashSet::<usize>::new, move |selection, cx| {
+                    let mut rows: Vec<usize> = (0..ROWS).collect();
+                    if cx[order] == SortOrder::Descending {
+                        rows.reverse();
+                    }
+                    table(
+                        rows,
+                        vec![
+                            column("n", |n: &usize| text(&n.to_string())),
+                            column("double", |n: &usize| text(&format!("{}x2", n))).width(150.0),
+                        ],
+                    )
+                    .selection(selection)
+                    .on_sort(move |cx, _, sorted| cx[order] = sorted)
+                })
+            },
+        )
+    }
+
+    #[test]
+    fn test_virtualized() {
+        let mut h = Harness::with_size(numbers(), Size::new(400.0, 300.0));
+        h.expect_text("0");
+        h.expect_text("9x2");
+        h.expect_no_text("9999");
+        assert!(h.texts().len() < 40);
+
+        // Scrolling 100 rows down.
+        h.scroll(Point::new(100.0, 100.0), [0.0, 100.0 * DEFAULT_ROW_HEIGHT]);
+        h.expect_no_text("0");
+        h.expect_text("100");
+        let n = h.find_text("n").unwrap();
+        assert!(h.find_text("100").unwrap().origin.y < n.origin.y);
+    }
+
+    #[test]
+    fn test_sort_and_resize() {
+        let mut h = Harness::with_size(numbers(), Size::new(400.0, 300.0));
+        h.click("n");
+        h.expect_text("0");
+        h.click("n");
+        h.expect_text("9999");
+        h.expect_no_text("0");
+
+        let double = h.find_text("double").unwrap().origin.x;
+        let edge = Point::new(DEFAULT_COLUMN_WIDTH, 300.0 - HEADER_HEIGHT / 2.0);
+        h.drag(edge, Point::new(edge.x + 50.0, edge.y));
+        assert_eq!(h.find_text("double").unwrap().origin.x, double + 50.0);
+
+        // Resizing didn't sort.
+        h.expect_text("9999");
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut h = Harness::with_size(numbers(), Size::new(400.0, 300.0));
+        let selected = |h: &Harness<_>| {
+            let mut rows: Vec<usize> = h.states::<HashSet<usize>>()[0].iter().copied().collect();
+            rows.sort();
+            rows
+        };
+
+        h.click("2x2");
+        h.context().key_mods.shift = true;
+        h.click("5");
+        h.context().key_mods.shift = false;
+        assert_eq!(selected(&h), vec![2, 3, 4, 5]);
+
+        h.context().key_mods.control = true;
+        h.click("3");
+        h.context().key_mods.control = false;
+        assert_eq!(selected(&h), vec![2, 4, 5]);
+    }
+}
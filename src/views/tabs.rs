@@ -0,0 +1,277 @@
+use crate::*;
+use accesskit::Role;
+use std::any::Any;
+
+/// One page of a `tab_view`.
+pub struct Tab {
+    label: String,
+    content: AnyView,
+}
+
+/// A page labelled `label` in the bar of a `tab_view`.
+pub fn tab(label: &str, content: impl View) -> Tab {
+    Tab {
+        label: label.to_string(),
+        content: any_view(content),
+    }
+}
+
+/// Where the tab bar is, from the last layout.
+#[derive(Clone, Copy, Debug, Default)]
+struct TabLayout {
+    bar: LocalOffset,
+}
+
+fn tab_layout(vid: ViewId, cx: &mut Context) -> &mut TabLayout {
+    cx.init_state(vid, &TabLayout::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Struct for `tab_view`.
+pub struct TabView<B> {
+    selection: B,
+    tabs: Vec<Tab>,
+}
+
+/// Shows one of `tabs` at a time, picked by clicking its label in a bar
+/// across the top. `selection` is the index of the tab shown.
+///
+/// Tabs which aren't shown keep their state, so switching back finds them
+/// as they were left.
+pub fn tab_view<B: Binding<usize>>(selection: B, tabs: Vec<Tab>) -> TabView<B> {
+    TabView { selection, tabs }
+}
+
+impl<B> TabView<B>
+where
+    B: Binding<usize>,
+{
+    fn selected(&self, cx: &Context) -> Option<usize> {
+        let last = self.tabs.len().checked_sub(1)?;
+        Some((*self.selection.get(cx)).min(last))
+    }
+
+    fn bar(&self, cx: &Context) -> impl View {
+        let selected = self.selected(cx);
+        let theme = cx.theme();
+        let selection = self.selection;
+        hstack(
+            self.tabs
+                .iter()
+                .enumerate()
+                .map(|(i, tab)| {
+                    let (color, background) = if selected == Some(i) {
+                        (theme.accent, theme.accent_background)
+                    } else {
+                        (theme.text, theme.control_background)
+                    };
+                    text(&tab.label)
+                        .color(color)
+                        .padding(Auto)
+                        .background(rectangle().color(background))
+                        .tap(move |cx| selection.set(cx, i))
+                        .role(Role::Tab)
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<B> View for TabView<B>
+where
+    B: Binding<usize>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        let bar = tab_layout(vid, cx).bar;
+        path.push(0);
+        self.bar(cx).process(&event.offset(-bar), path, cx, actions);
+        path.pop();
+
+        if let Some(i) = self.selected(cx) {
+            path.push(1 + i as u64);
+            self.tabs[i].content.process(event, path, cx, actions);
+            path.pop();
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        if let Some(i) = self.selected(args.cx) {
+            path.push(1 + i as u64);
+            self.tabs[i].content.draw(path, args);
+            path.pop();
+        }
+
+        let vid = args.cx.view_id(path);
+        let bar = tab_layout(vid, args.cx).bar;
+        args.vger.save();
+        args.vger.translate(bar);
+        path.push(0);
+        self.bar(args.cx).draw(path, args);
+        path.pop();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let size = args.sz;
+        path.push(0);
+        let bar_size = self.bar(args.cx).layout(path, args);
+        path.pop();
+
+        let content_size = LocalSize::new(size.width, (size.height - bar_size.height).max(0.0));
+        if let Some(i) = self.selected(args.cx) {
+            path.push(1 + i as u64);
+            self.tabs[i]
+                .content
+                .layout(path, &mut args.size(content_size));
+            path.pop();
+        }
+
+        let vid = args.cx.view_id(path);
+        tab_layout(vid, args.cx).bar = [0.0, content_size.height].into();
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let vid = cx.view_id(path);
+        let bar = tab_layout(vid, cx).bar;
+        path.push(0);
+        self.bar(cx).dirty(path, xform.pre_translate(bar), cx);
+        path.pop();
+
+        if let Some(i) = self.selected(cx) {
+            path.push(1 + i as u64);
+            self.tabs[i].content.dirty(path, xform, cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let mut hit = None;
+        if let Some(i) = self.selected(cx) {
+            path.push(1 + i as u64);
+            hit = self.tabs[i].content.hittest(path, pt, cx);
+            path.pop();
+        }
+
+        let vid = cx.view_id(path);
+        let bar = tab_layout(vid, cx).bar;
+        path.push(0);
+        hit = self.bar(cx).hittest(path, pt - bar, cx).or(hit);
+        path.pop();
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        if let Some(i) = self.selected(cx) {
+            path.push(1 + i as u64);
+            self.tabs[i].content.commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.bar(cx).gc_view(path, cx, map);
+        path.pop();
+
+        // Keep every tab's state, not just the one shown.
+        for (i, tab) in self.tabs.iter().enumerate() {
+            path.push(1 + i as u64);
+            tab.content.gc_view(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let mut children = vec![];
+        path.push(0);
+        children.extend(self.bar(cx).access(path, cx, nodes));
+        path.pop();
+        if let Some(i) = self.selected(cx) {
+            path.push(1 + i as u64);
+            children.extend(self.tabs[i].content.access(path, cx, nodes));
+            path.pop();
+        }
+
+        let aid = cx.view_id(path).access_id();
+        let mut builder = accesskit::NodeBuilder::new(Role::TabList);
+        builder.set_children(children);
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+
+    fn is_flexible(&self) -> bool {
+        true
+    }
+}
+
+impl<B> private::Sealed for TabView<B> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_tabs() {
+        let view = state(
+            || 0,
+            |selected, _| {
+                tab_view(
+                    selected,
+                    vec![
+                        tab(
+                            "Inbox",
+                            state(
+                                || 0,
+                                |n, cx| {
+                                    button(text(&format!("read {}", cx[n])), move |cx| cx[n] += 1)
+                                },
+                            ),
+                        ),
+                        tab("Sent", text("nothing sent")),
+                    ],
+                )
+            },
+        );
+        let mut h = Harness::new(view);
+        let inbox = h.find_text("Inbox").unwrap();
+        assert!(inbox.origin.y > h.find_text("read 0").unwrap().origin.y);
+        h.expect_no_text("nothing sent");
+
+        h.click("read 0");
+        h.click("Sent");
+        h.expect_text("nothing sent");
+        h.expect_no_text("read 1");
+        assert_eq!(h.states::<usize>(), vec![1]);
+
+        // The inbox kept its count.
+        h.click("Inbox");
+        h.expect_text("read 1");
+    }
+}
@@ -35,16 +35,12 @@ where
     ) {
         let vid = cx.view_id(path);
         match &event {
-            Event::TouchBegin { id, position } => {
-                if self.hittest(path, *position, cx).is_some() {
-                    cx.touches[*id] = vid;
-                }
+            Event::TouchBegin { id, position } if self.hittest(path, *position, cx).is_some() => {
+                cx.touches[*id] = vid;
             }
-            Event::TouchEnd { id, position: _ } => {
-                if cx.touches[*id] == vid {
-                    cx.touches[*id] = ViewId::default();
-                    actions.push(Box::new((self.func)(cx)))
-                }
+            Event::TouchEnd { id, position: _ } if cx.touches[*id] == vid => {
+                cx.touches[*id] = ViewId::default();
+                actions.push(Box::new((self.func)(cx)))
             }
             _ => (),
         }
@@ -78,7 +74,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -129,16 +125,12 @@ where
     ) {
         let vid = cx.view_id(path);
         match &event {
-            Event::TouchBegin { id, position } => {
-                if self.hittest(path, *position, cx).is_some() {
-                    cx.touches[*id] = vid;
-                }
+            Event::TouchBegin { id, position } if self.hittest(path, *position, cx).is_some() => {
+                cx.touches[*id] = vid;
             }
-            Event::TouchEnd { id, position: _ } => {
-                if cx.touches[*id] == vid {
-                    cx.touches[*id] = ViewId::default();
-                    actions.push(Box::new(self.action.clone()))
-                }
+            Event::TouchEnd { id, position: _ } if cx.touches[*id] == vid => {
+                cx.touches[*id] = ViewId::default();
+                actions.push(Box::new(self.action.clone()))
             }
             _ => (),
         }
@@ -172,7 +164,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -9,8 +9,10 @@ pub trait TextModifiers: View + Sized {
 #[derive(Clone)]
 pub struct Text {
     text: String,
-    size: u32,
-    color: Color,
+    /// Uses the size of the environment's `Font` if not set.
+    size: Option<u32>,
+    /// Uses the theme's text color if not set.
+    color: Option<Color>,
 }
 
 impl Text {
@@ -19,25 +21,102 @@ impl Text {
         Text {
             text: self.text,
             size: self.size,
-            color,
+            color: Some(color),
+        }
+    }
+
+    fn font(&self, cx: &mut Context) -> Font {
+        let font = cx.init_env(&Font::default);
+        Font {
+            size: self.size.unwrap_or(font.size),
+            ..font
+        }
+    }
+}
+
+impl Context {
+    /// Whether to move text onto whole device pixels, which keeps small
+    /// text sharp. On by default. Across a line, glyphs are still placed
+    /// to a quarter of a pixel, so spacing stays even. Text moving slowly,
+    /// as in an animation, steps a pixel at a time when snapped, so it
+    /// can be turned off.
+    pub fn set_text_pixel_snapping(&mut self, snap: bool) {
+        self.text_pixel_snapping = snap;
+    }
+
+    /// Antialiases text drawn with `Font` against the color subpixels of
+    /// a display laid out in `order`, which makes small text sharper, or
+    /// just its coverage with `None`, the default. Only upright text over
+    /// an opaque background looks right, and surface formats without
+    /// color channels to blend one by one fall back to gray.
+    pub fn set_lcd_text(&mut self, order: Option<SubpixelOrder>) {
+        if self.lcd_text != order {
+            self.lcd_text = order;
+            self.glyph_renderer = None;
+            self.full_redraw = true;
+        }
+    }
+
+    /// Moves text about to be drawn at the origin onto the pixel grid.
+    pub(crate) fn snap_text(&self, vger: &mut Painter) {
+        if self.text_pixel_snapping {
+            vger.translate(pixel_snap_offset(&vger.current_transform(), self.scale));
         }
     }
 }
 
+/// How far to move the origin so it lands on a whole device pixel. Glyphs
+/// are rasterized on whole pixels from their origin, but drawn anywhere
+/// else they are resampled and come out blurry. Rotated text is left alone.
+fn pixel_snap_offset(xform: &LocalToWorld, scale: f32) -> LocalOffset {
+    if xform.m12 != 0.0 || xform.m21 != 0.0 || xform.m11 == 0.0 || xform.m22 == 0.0 {
+        return LocalOffset::zero();
+    }
+    let snap = |world: f32, local_scale: f32| {
+        let px = world * scale;
+        (px.round() - px) / scale / local_scale
+    };
+    LocalOffset::new(snap(xform.m31, xform.m11), snap(xform.m32, xform.m22))
+}
+
 impl View for Text {
     fn draw(&self, _path: &mut IdPath, args: &mut DrawArgs) {
+        // Text views don't keep their layout, so measure again.
+        let font = self.font(args.cx);
+        let bounds = args
+            .cx
+            .text_layout(&font, self.text.as_str(), None)
+            .bounds();
+        let rect = LocalRect::new(LocalPoint::zero(), bounds.size);
+        if !args.cx.is_damaged(&rect, &args.vger.current_transform()) {
+            return;
+        }
+        let color = self.color.unwrap_or_else(|| args.cx.theme().text);
         let vger = &mut args.vger;
-        let origin = vger.text_bounds(self.text.as_str(), self.size, None).origin;
+        let origin = bounds.origin;
 
         vger.save();
         vger.translate([-origin.x, -origin.y]);
-        vger.text(self.text.as_str(), self.size, self.color, None);
+        args.cx.snap_text(vger);
+        font.draw(args.cx, vger, self.text.as_str(), color, None);
         vger.restore();
     }
-    fn layout(&self, _path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        (args.text_bounds)(self.text.as_str(), self.size, None).size
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let size = self
+            .font(args.cx)
+            .measure(args, self.text.as_str(), None)
+            .size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
     }
-    fn hittest(&self, _path: &mut IdPath, _pt: LocalPoint, _cx: &mut Context) -> Option<ViewId> {
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        cx.probe_text(path, &self.text, pt);
         None
     }
 
@@ -60,14 +139,14 @@ impl TextModifiers for Text {
         Self {
             text: self.text,
             color: self.color,
-            size,
+            size: Some(size),
         }
     }
     fn color(self, color: Color) -> Text {
         Text {
             text: self.text,
             size: self.size,
-            color,
+            color: Some(color),
         }
     }
 }
@@ -78,8 +157,8 @@ impl private::Sealed for Text {}
 pub fn text(name: &str) -> Text {
     Text {
         text: String::from(name),
-        size: Text::DEFAULT_SIZE,
-        color: TEXT_COLOR,
+        size: None,
+        color: None,
     }
 }
 
@@ -89,17 +168,39 @@ where
 {
     fn draw(&self, _path: &mut IdPath, args: &mut DrawArgs) {
         let txt = &format!("{}", self);
+        let font = args.cx.init_env(&Font::default);
+        let bounds = args.cx.text_layout(&font, txt, None).bounds();
+        let rect = LocalRect::new(LocalPoint::zero(), bounds.size);
+        if !args.cx.is_damaged(&rect, &args.vger.current_transform()) {
+            return;
+        }
+        let color = args.cx.theme().text;
         let vger = &mut args.vger;
-        let origin = vger.text_bounds(txt, Text::DEFAULT_SIZE, None).origin;
+        let origin = bounds.origin;
 
         vger.save();
         vger.translate([-origin.x, -origin.y]);
-        vger.text(txt, Text::DEFAULT_SIZE, TEXT_COLOR, None);
+        args.cx.snap_text(vger);
+        font.draw(args.cx, vger, txt, color, None);
         vger.restore();
     }
-    fn layout(&self, _path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
         let txt = &format!("{}", self);
-        (args.text_bounds)(txt, Text::DEFAULT_SIZE, None).size
+        let font = args.cx.init_env(&Font::default);
+        let size = font.measure(args, txt, None).size;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        cx.probe_text(path, &format!("{}", self), pt);
+        None
     }
 
     fn access(
@@ -123,17 +224,39 @@ where
     fn font_size(self, size: u32) -> Text {
         Text {
             text: format!("{}", self),
-            size,
-            color: TEXT_COLOR,
+            size: Some(size),
+            color: None,
         }
     }
     fn color(self, color: Color) -> Text {
         Text {
             text: format!("{}", self),
-            size: Text::DEFAULT_SIZE,
-            color,
+            size: None,
+            color: Some(color),
         }
     }
 }
 
 impl<V> private::Sealed for V where V: std::fmt::Display {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_pixel_snap_offset() {
+        let xform = LocalToWorld::translation(10.3, 4.0);
+        let offset = pixel_snap_offset(&xform, 2.0);
+        assert!((offset.x - 0.2).abs() < 1e-5);
+        assert_eq!(offset.y, 0.0);
+
+        // Positions are snapped in device pixels, through any scaling.
+        let xform = LocalToWorld::scale(2.0, 2.0).then_translate([3.2, 0.0].into());
+        let offset = pixel_snap_offset(&xform, 1.0);
+        assert!((offset.x - -0.1).abs() < 1e-5);
+
+        let rotated = LocalToWorld::rotation(euclid::Angle::radians(0.5));
+        assert_eq!(pixel_snap_offset(&rotated, 2.0), LocalOffset::zero());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::*;
+use accesskit::{Action, ActionData, NodeBuilder, NodeId, Role, TextPosition, TextSelection};
+use std::any::Any;
+use std::ops::Range;
+
+/// Editable text as told to assistive technology. Positions are in
+/// characters.
+pub(crate) struct AccessText {
+    pub text: String,
+    pub anchor: usize,
+    pub focus: usize,
+}
+
+/// A change to editable text asked for by assistive technology.
+pub(crate) enum TextRequest {
+    Select { anchor: usize, focus: usize },
+    SetValue(String),
+    ReplaceSelection(String),
+}
+
+/// Character ranges of each line, with the line break at the end.
+fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if c == '\n' {
+            lines.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    lines.push(start..text.chars().count());
+    lines
+}
+
+/// Makes editable text readable and editable by screen readers. Each
+/// line is an inline text box under the view's node.
+pub(crate) struct TextAccess<V, G, E> {
+    child: V,
+    role: Role,
+    multiline: bool,
+    get: G,
+    edit: E,
+}
+
+impl<V, G, E> TextAccess<V, G, E>
+where
+    V: View,
+    G: Fn(&mut Context) -> AccessText + 'static,
+    E: Fn(&mut Context, TextRequest) + 'static,
+{
+    pub fn new(child: V, role: Role, get: G, edit: E) -> Self {
+        Self {
+            child,
+            role,
+            multiline: false,
+            get,
+            edit,
+        }
+    }
+
+    /// Reports that the text can have more than one line.
+    pub fn multiline(self) -> Self {
+        Self {
+            multiline: true,
+            ..self
+        }
+    }
+
+    fn request(&self, request: &accesskit::ActionRequest, aid: NodeId, cx: &mut Context) {
+        let text = (self.get)(cx).text;
+        let lines = line_ranges(&text);
+        let index = |position: &TextPosition| {
+            let line =
+                (0..lines.len()).find(|i| part_access_id(aid, *i as u64 + 1) == position.node)?;
+            Some(lines[line].start + position.character_index)
+        };
+        let request = match (request.action, &request.data) {
+            (Action::SetValue, Some(ActionData::Value(value))) => {
+                TextRequest::SetValue(value.to_string())
+            }
+            (Action::ReplaceSelectedText, Some(ActionData::Value(value))) => {
+                TextRequest::ReplaceSelection(value.to_string())
+            }
+            (Action::SetTextSelection, Some(ActionData::SetTextSelection(selection))) => {
+                match (index(&selection.anchor), index(&selection.focus)) {
+                    (Some(anchor), Some(focus)) => TextRequest::Select { anchor, focus },
+                    _ => return,
+                }
+            }
+            _ => return,
+        };
+        (self.edit)(cx, request);
+    }
+}
+
+impl<V, G, E> View for TextAccess<V, G, E>
+where
+    V: View,
+    G: Fn(&mut Context) -> AccessText + 'static,
+    E: Fn(&mut Context, TextRequest) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Access(request) = event {
+            let aid = cx.view_id(path).access_id();
+            if request.target == aid {
+                self.request(request, aid, cx);
+            }
+        }
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let child_aid = self.child.access(path, cx, nodes);
+        path.pop();
+
+        let aid = cx.view_id(path).access_id();
+        let AccessText {
+            text,
+            anchor,
+            focus,
+        } = (self.get)(cx);
+        let chars: Vec<char> = text.chars().collect();
+        let lines = line_ranges(&text);
+        let position = |index: usize| {
+            let line = lines
+                .iter()
+                .position(|line| index < line.end)
+                .unwrap_or(lines.len() - 1);
+            TextPosition {
+                node: part_access_id(aid, line as u64 + 1),
+                character_index: index - lines[line].start,
+            }
+        };
+
+        let mut children: Vec<NodeId> = child_aid.into_iter().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let line_chars = &chars[line.clone()];
+            let mut builder = NodeBuilder::new(Role::InlineTextBox);
+            builder.set_value(line_chars.iter().collect::<String>());
+            builder.set_character_lengths(
+                line_chars
+                    .iter()
+                    .map(|c| c.len_utf8() as u8)
+                    .collect::<Vec<_>>(),
+            );
+            let id = part_access_id(aid, i as u64 + 1);
+            nodes.push((id, builder.build(&mut cx.access_node_classes)));
+            children.push(id);
+        }
+
+        let mut builder = NodeBuilder::new(self.role);
+        builder.set_children(children);
+        builder.set_text_selection(TextSelection {
+            anchor: position(anchor.min(chars.len())),
+            focus: position(focus.min(chars.len())),
+        });
+        builder.set_value(text);
+        if self.multiline {
+            builder.set_multiline();
+        }
+        for action in [
+            Action::Focus,
+            Action::SetValue,
+            Action::SetTextSelection,
+            Action::ReplaceSelectedText,
+        ] {
+            builder.add_action(action);
+        }
+        nodes.push((aid, builder.build(&mut cx.access_node_classes)));
+        Some(aid)
+    }
+}
+
+impl<V, G, E> private::Sealed for TextAccess<V, G, E> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_line_ranges() {
+        assert_eq!(line_ranges("one\ntwo"), vec![0..4, 4..7]);
+        assert_eq!(line_ranges("one\n"), vec![0..4, 4..4]);
+        assert_eq!(line_ranges(""), vec![0..0]);
+    }
+}
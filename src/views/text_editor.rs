@@ -1,29 +1,234 @@
 use crate::*;
+use std::ops::Range;
+use std::rc::Rc;
+
+const CARET_HEIGHT: f32 = 20.0;
+
+/// How close to the top or bottom a drag scrolls the editor.
+const SCROLL_EDGE: f32 = 20.0;
+
+/// Colors byte ranges of the text in a `text_editor`. See
+/// `text_editor_highlighted`.
+pub type Highlighter = dyn Fn(&str) -> Vec<(Range<usize>, Color)>;
+
+/// A selected range of text, or a caret if empty. Positions are in
+/// characters (not bytes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Selection {
+    /// Where the selection started.
+    anchor: usize,
+
+    /// Where the caret is.
+    head: usize,
+}
+
+impl Selection {
+    fn caret(position: usize) -> Self {
+        Self {
+            anchor: position,
+            head: position,
+        }
+    }
+
+    fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
 
 /// View-model for `text_editor`.
 struct TextEditorState {
-    cursor: usize,
+    /// Carets and selections, sorted and non-overlapping. Never empty.
+    /// The primary selection, which IME input and scrolling follow, is
+    /// `selections[primary]`.
+    selections: Vec<Selection>,
+    primary: usize,
+
     glyph_rects: Vec<LocalRect>,
     lines: Vec<LineMetrics>,
+
+    /// Height of the editor when last drawn, for mapping clicks to glyphs.
+    height: f32,
+
+    /// How far the text is scrolled up, in points.
+    scroll: f32,
+
+    /// Scroll the primary caret into view on the next draw.
+    follow_caret: bool,
+
+    /// Text being composed by an input method, shown at the cursor.
+    preedit: String,
+
+    /// Cursor within the preedit text, in characters.
+    preedit_cursor: Option<usize>,
+
+    /// Highlighter output for the text it was last run on, as a color
+    /// for each character.
+    highlights: Option<(String, Vec<Option<Color>>)>,
+
+    /// The text as the editor last saw it, to tell when something else
+    /// changes it.
+    text: String,
+}
+
+/// Converts highlighted byte ranges to a color for each character.
+fn char_colors(text: &str, spans: &[(Range<usize>, Color)]) -> Vec<Option<Color>> {
+    text.char_indices()
+        .map(|(i, _)| {
+            spans
+                .iter()
+                .rev()
+                .find(|(range, _)| range.contains(&i))
+                .map(|(_, color)| *color)
+        })
+        .collect()
+}
+
+/// Splits a line of glyphs into runs of the same color.
+fn color_runs(colors: &[Option<Color>], line: Range<usize>) -> Vec<(Range<usize>, Option<Color>)> {
+    let mut runs: Vec<(Range<usize>, Option<Color>)> = vec![];
+    for i in line {
+        let color = colors.get(i).copied().flatten();
+        match runs.last_mut() {
+            Some((range, c)) if *c == color => range.end = i + 1,
+            _ => runs.push((i..i + 1, color)),
+        }
+    }
+    runs
+}
+
+/// Draws text laid out as a whole in `layout`, but with each glyph in its
+/// own color.
+fn draw_highlighted(
+    cx: &mut Context,
+    vger: &mut Painter,
+    layout: &Rc<TextLayout>,
+    colors: &[Option<Color>],
+    default_color: Color,
+    font: &Font,
+) {
+    for line in &layout.lines {
+        for (range, color) in color_runs(colors, line.glyph_start..line.glyph_end) {
+            font.draw_glyphs(cx, vger, layout, range, color.unwrap_or(default_color));
+        }
+    }
+}
+
+/// Byte offset of the character at `index`, or the end of the string.
+pub(crate) fn byte_index(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len())
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
 }
 
 impl TextEditorState {
-    fn fwd(&mut self, len: usize) {
-        self.cursor += 1;
-        if self.cursor > len {
-            self.cursor = len;
+    /// Position of the primary caret.
+    fn cursor(&self) -> usize {
+        self.selections[self.primary].head
+    }
+
+    /// Sorts selections and merges any that overlap, keeping track of
+    /// the primary one.
+    fn normalize(&mut self) {
+        let primary = self.selections[self.primary];
+        self.selections.sort_by_key(|s| s.range().start);
+
+        let mut merged: Vec<Selection> = vec![];
+        let mut primary_index = 0;
+        for s in self.selections.drain(..) {
+            match merged.last_mut() {
+                Some(last)
+                    if s.range().start < last.range().end
+                        || (s.is_empty() && s.head == last.head) =>
+                {
+                    let start = last.range().start.min(s.range().start);
+                    let end = last.range().end.max(s.range().end);
+                    *last = if last.anchor <= last.head {
+                        Selection {
+                            anchor: start,
+                            head: end,
+                        }
+                    } else {
+                        Selection {
+                            anchor: end,
+                            head: start,
+                        }
+                    };
+                }
+                _ => merged.push(s),
+            }
+            if s == primary {
+                primary_index = merged.len() - 1;
+            }
         }
+        self.selections = merged;
+        self.primary = primary_index;
+    }
+
+    /// Moves every caret with `f`, collapsing selections.
+    fn move_carets(&mut self, f: impl Fn(&Self, Selection) -> usize) {
+        let moved: Vec<_> = self
+            .selections
+            .iter()
+            .map(|s| Selection::caret(f(self, *s)))
+            .collect();
+        self.selections = moved;
+        self.normalize();
     }
-    fn back(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
+
+    /// Replaces each selection in `text`. `f` returns the character range
+    /// to remove for a selection (usually the selection itself) and the
+    /// string to insert in its place. Carets end up after the insertions.
+    fn replace_selections(
+        &mut self,
+        text: &str,
+        f: impl Fn(Selection) -> (Range<usize>, String),
+    ) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::new();
+        let mut copied = 0;
+        let mut carets = vec![];
+        for s in &self.selections {
+            let (range, insert) = f(*s);
+            let start = range.start.max(copied).min(chars.len());
+            let end = range.end.max(start).min(chars.len());
+            result.extend(&chars[copied..start]);
+            result.push_str(&insert);
+            copied = end;
+            carets.push(Selection::caret(result.chars().count()));
         }
+        result.extend(&chars[copied..]);
+        self.selections = carets;
+        self.normalize();
+        result
+    }
+
+    fn insert(&mut self, text: &str, s: &str) -> String {
+        self.replace_selections(text, |sel| (sel.range(), s.to_string()))
     }
 
-    fn find_line(&self) -> usize {
+    fn backspace(&mut self, text: &str) -> String {
+        self.replace_selections(text, |sel| {
+            if sel.is_empty() {
+                (sel.head.saturating_sub(1)..sel.head, String::new())
+            } else {
+                (sel.range(), String::new())
+            }
+        })
+    }
+
+    fn find_line(&self, cursor: usize) -> usize {
         let mut i = 0;
         for line in &self.lines {
-            if self.cursor >= line.glyph_start && self.cursor < line.glyph_end {
+            if cursor >= line.glyph_start && cursor < line.glyph_end {
                 break;
             }
             i += 1;
@@ -37,7 +242,7 @@ impl TextEditorState {
         range: std::ops::Range<usize>,
         rects: &[LocalRect],
     ) -> usize {
-        let mut d = std::f32::MAX;
+        let mut d = f32::MAX;
         let mut closest = 0;
         for i in range {
             let dp = rects[i].center().distance_to(p);
@@ -49,89 +254,336 @@ impl TextEditorState {
         closest
     }
 
-    fn down(&mut self) {
-        let p = self.glyph_rects[self.cursor].center();
+    fn down(&self, cursor: usize) -> usize {
+        let p = match self.glyph_rects.get(cursor) {
+            Some(r) => r.center(),
+            None => return cursor,
+        };
 
-        let line = self.find_line() + 1;
+        let line = self.find_line(cursor) + 1;
         if line < self.lines.len() {
             let metrics = self.lines[line];
-            self.cursor =
-                self.closest_in_range(p, metrics.glyph_start..metrics.glyph_end, &self.glyph_rects);
+            self.closest_in_range(p, metrics.glyph_start..metrics.glyph_end, &self.glyph_rects)
+        } else {
+            cursor
         }
     }
 
-    fn up(&mut self) {
-        let p = self.glyph_rects[self.cursor].center();
+    fn up(&self, cursor: usize) -> usize {
+        let p = match self.glyph_rects.get(cursor) {
+            Some(r) => r.center(),
+            None => return cursor,
+        };
 
-        let line = self.find_line();
-        if line > 0 {
+        let line = self.find_line(cursor);
+        if line > 0 && line <= self.lines.len() {
             let metrics = self.lines[line - 1];
-            self.cursor =
-                self.closest_in_range(p, metrics.glyph_start..metrics.glyph_end, &self.glyph_rects);
+            self.closest_in_range(p, metrics.glyph_start..metrics.glyph_end, &self.glyph_rects)
+        } else {
+            cursor
+        }
+    }
+
+    /// Caret position closest to a point in the editor's local space.
+    fn position_at(&self, p: LocalPoint) -> usize {
+        // Glyphs are laid out from the top of the editor.
+        let p = LocalPoint::new(p.x, p.y - self.height - self.scroll);
+
+        let line = self.lines.iter().min_by(|a, b| {
+            let da = (a.bounds.center().y - p.y).abs();
+            let db = (b.bounds.center().y - p.y).abs();
+            da.partial_cmp(&db).unwrap()
+        });
+        match line {
+            Some(line) if line.glyph_start < line.glyph_end => {
+                let range = line.glyph_start..line.glyph_end;
+                let i = self.closest_in_range(p, range, &self.glyph_rects);
+                // Put the caret on whichever side of the glyph is closer.
+                if p.x > self.glyph_rects[i].center().x {
+                    i + 1
+                } else {
+                    i
+                }
+            }
+            Some(line) => line.glyph_start,
+            None => 0,
+        }
+    }
+
+    /// Handles a click. With control or command held, adds a caret
+    /// instead of replacing the existing ones, and with shift held,
+    /// extends the primary selection.
+    fn click(&mut self, p: LocalPoint, mods: KeyboardModifiers) {
+        self.follow_caret = true;
+        let caret = Selection::caret(self.position_at(p));
+        if mods.shift {
+            self.drag_to(p);
+        } else if mods.control || mods.command {
+            self.selections.push(caret);
+            self.primary = self.selections.len() - 1;
+            self.normalize();
+        } else {
+            self.selections = vec![caret];
+            self.primary = 0;
+        }
+    }
+
+    /// Extends the primary selection to a point, while dragging.
+    fn drag_to(&mut self, p: LocalPoint) {
+        self.follow_caret = true;
+        self.selections[self.primary].head = self.position_at(p);
+        self.normalize();
+    }
+
+    /// Selects the word at the primary caret, or if there's already a
+    /// selection, adds a selection for its next occurrence.
+    fn select_next_occurrence(&mut self, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        let primary = self.selections[self.primary];
+
+        if primary.is_empty() {
+            let mut start = primary.head.min(chars.len());
+            let mut end = start;
+            while start > 0 && is_word_char(chars[start - 1]) {
+                start -= 1;
+            }
+            while end < chars.len() && is_word_char(chars[end]) {
+                end += 1;
+            }
+            self.selections[self.primary] = Selection {
+                anchor: start,
+                head: end,
+            };
+            self.normalize();
+            return;
+        }
+
+        let needle = &chars[primary.range()];
+        let after = self.selections.iter().map(|s| s.range().end).max().unwrap();
+        let found = (after..chars.len())
+            .chain(0..after)
+            .find(|&i| chars[i..].starts_with(needle));
+        if let Some(start) = found {
+            let s = Selection {
+                anchor: start,
+                head: start + needle.len(),
+            };
+            if !self.selections.contains(&s) {
+                self.selections.push(s);
+                self.primary = self.selections.len() - 1;
+                self.normalize();
+            }
         }
     }
 
-    fn key(&mut self, k: &Key, text: String) -> String {
+    /// Moves selections to match text which was changed by something
+    /// other than the editor, as when a file is reloaded. Positions
+    /// before or after the changed part move with the text around them,
+    /// and ones inside it go to its end.
+    fn sync(&mut self, text: &str) {
+        if self.text == text {
+            return;
+        }
+        let old: Vec<char> = self.text.chars().collect();
+        let new: Vec<char> = text.chars().collect();
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let old_end = old.len() - suffix;
+        let new_end = new.len() - suffix;
+        let map = |i: usize| {
+            if i <= prefix {
+                i
+            } else if i >= old_end {
+                (i - old_end + new_end).min(new.len())
+            } else {
+                new_end
+            }
+        };
+        for s in &mut self.selections {
+            s.anchor = map(s.anchor);
+            s.head = map(s.head);
+        }
+        self.normalize();
+        self.text = text.to_string();
+    }
+
+    /// Runs an edit of `text` by the editor, first catching up with any
+    /// changes from elsewhere.
+    fn edit(&mut self, text: String, f: impl FnOnce(&mut Self, String) -> String) -> String {
+        self.sync(&text);
+        let text = f(self, text);
+        self.text = text.clone();
+        text
+    }
+
+    fn key(&mut self, k: &Key, mods: KeyboardModifiers, text: String) -> String {
+        self.follow_caret = true;
+        if !self.preedit.is_empty() {
+            // The input method is handling keys.
+            return text;
+        }
+        let len = text.chars().count();
         match k {
+            Key::Character('d') | Key::Character('D') if mods.control || mods.command => {
+                self.select_next_occurrence(&text);
+                text
+            }
+            Key::Character(_) if mods.control || mods.command => text,
             Key::ArrowLeft => {
-                self.back();
+                self.move_carets(|_, s| {
+                    if s.is_empty() {
+                        s.head.saturating_sub(1)
+                    } else {
+                        s.range().start
+                    }
+                });
                 text
             }
             Key::ArrowRight => {
-                self.fwd(text.len());
+                self.move_carets(|_, s| {
+                    if s.is_empty() {
+                        (s.head + 1).min(len)
+                    } else {
+                        s.range().end
+                    }
+                });
                 text
             }
             Key::ArrowUp => {
-                self.up();
+                self.move_carets(|this, s| this.up(s.head));
                 text
             }
             Key::ArrowDown => {
-                self.down();
+                self.move_carets(|this, s| this.down(s.head));
                 text
             }
-            Key::Backspace => {
-                if self.cursor > 0 {
-                    let mut t = text;
-                    t.remove(self.cursor - 1);
-                    self.back();
-                    t
-                } else {
-                    text
-                }
-            }
-            Key::Character(c) => {
-                let mut t = text;
-                t.insert_str(self.cursor, &format!("{}", c));
-                self.cursor += 1;
-                t
-            }
-            Key::Space => {
-                let mut t = text;
-                t.insert(self.cursor, ' ');
-                self.cursor += 1;
-                t
-            }
+            Key::Backspace => self.backspace(&text),
+            Key::Character(c) => self.insert(&text, &c.to_string()),
+            Key::Space => self.insert(&text, " "),
             Key::Home => {
-                self.cursor = 0;
+                self.move_carets(|_, _| 0);
                 text
             }
             Key::End => {
-                self.cursor = text.len();
+                self.move_carets(|_, _| len);
                 text
             }
             _ => text,
         }
     }
+
+    fn ime(&mut self, event: &ImeEvent, text: String) -> String {
+        self.follow_caret = true;
+        match event {
+            ImeEvent::Preedit {
+                text: preedit,
+                cursor,
+            } => {
+                self.preedit = preedit.clone();
+                self.preedit_cursor = cursor.map(|(start, _)| start);
+                text
+            }
+            ImeEvent::Commit(s) => {
+                self.preedit.clear();
+                self.preedit_cursor = None;
+                self.insert(&text, s)
+            }
+        }
+    }
+
+    /// Total height of the laid out text.
+    fn content_height(&self) -> f32 {
+        self.lines
+            .last()
+            .map(|line| -line.bounds.min_y())
+            .unwrap_or(0.0)
+    }
+
+    /// Sets the scroll offset, keeping the text on screen.
+    fn set_scroll(&mut self, scroll: f32) {
+        let max = (self.content_height() - self.height).max(0.0);
+        self.scroll = scroll.clamp(0.0, max);
+    }
+
+    /// Scrolls so a caret drawn at `p`, relative to the top of the text,
+    /// is in view.
+    fn scroll_to(&mut self, p: LocalPoint) {
+        let mut scroll = self.scroll;
+        // Screen y of the caret's top and bottom is y + height + scroll.
+        if p.y + CARET_HEIGHT + self.height + scroll > self.height {
+            scroll = -(p.y + CARET_HEIGHT);
+        }
+        if p.y + self.height + scroll < 0.0 {
+            scroll = -p.y - self.height;
+        }
+        self.set_scroll(scroll);
+    }
+
+    /// Scrolls when dragging near the top or bottom edge.
+    fn drag_scroll(&mut self, p: LocalPoint) {
+        if p.y < SCROLL_EDGE {
+            self.set_scroll(self.scroll + SCROLL_EDGE - p.y.max(0.0));
+        } else if p.y > self.height - SCROLL_EDGE {
+            self.set_scroll(self.scroll - (p.y.min(self.height) - (self.height - SCROLL_EDGE)));
+        }
+    }
+
+    /// Maps a position in the text to a position in the displayed text,
+    /// which has the preedit inserted at the primary caret.
+    fn display_index(&self, i: usize) -> usize {
+        if i > self.cursor() {
+            i + self.preedit.chars().count()
+        } else {
+            i
+        }
+    }
+
+    /// The text with any preedit text inserted at the cursor, and the
+    /// cursor position in that string.
+    fn display(&self, text: &str) -> (String, usize) {
+        let mut t = text.to_string();
+        t.insert_str(byte_index(text, self.cursor()), &self.preedit);
+        let cursor = self.cursor()
+            + self
+                .preedit_cursor
+                .unwrap_or_else(|| self.preedit.chars().count());
+        (t, cursor)
+    }
 }
 
 impl TextEditorState {
     fn new() -> Self {
         Self {
-            cursor: 0,
+            selections: vec![Selection::caret(0)],
+            primary: 0,
             glyph_rects: vec![],
             lines: vec![],
+            height: 0.0,
+            scroll: 0.0,
+            follow_caret: false,
+            preedit: String::new(),
+            preedit_cursor: None,
+            highlights: None,
+            text: String::new(),
         }
     }
+
+    /// Colors for each character of the displayed text, rerunning the
+    /// highlighter only if the text has changed.
+    fn display_colors(&mut self, text: &str, highlighter: &Highlighter) -> Vec<Option<Color>> {
+        if !matches!(&self.highlights, Some((t, _)) if t == text) {
+            self.highlights = Some((text.to_string(), char_colors(text, &highlighter(text))));
+        }
+        let mut colors = self.highlights.as_ref().unwrap().1.clone();
+        let cursor = self.cursor().min(colors.len());
+        colors.splice(cursor..cursor, self.preedit.chars().map(|_| None));
+        colors
+    }
 }
 
 /// A multi-line text editor.
@@ -139,42 +591,440 @@ impl TextEditorState {
 /// This shows how a complex View with internal
 /// state can be created from more atomic Views.
 pub fn text_editor(text: impl Binding<String>) -> impl View {
+    editor(text, None)
+}
+
+/// A `text_editor` which colors its text with `highlighter`, for building
+/// code editors. The highlighter returns colors for byte ranges of the
+/// text, and only reruns when the text changes.
+pub fn text_editor_highlighted(
+    text: impl Binding<String>,
+    highlighter: impl Fn(&str) -> Vec<(Range<usize>, Color)> + 'static,
+) -> impl View {
+    editor(text, Some(Rc::new(highlighter)))
+}
+
+/// Where to draw a caret before glyph `i`.
+fn caret_point(rects: &[LocalRect], i: usize) -> LocalPoint {
+    match rects.get(i) {
+        Some(r) => r.origin,
+        None => match rects.last() {
+            Some(r) => [r.origin.x + r.size.width, r.origin.y].into(),
+            None => [0.0, -CARET_HEIGHT].into(),
+        },
+    }
+}
+
+/// Rectangles covering a range of glyphs, one per line.
+fn selection_rects(
+    rects: &[LocalRect],
+    lines: &[LineMetrics],
+    range: Range<usize>,
+) -> Vec<LocalRect> {
+    let mut result = vec![];
+    for line in lines {
+        let start = range.start.max(line.glyph_start);
+        let end = range.end.min(line.glyph_end);
+        if start >= end {
+            continue;
+        }
+        let x0 = rects[start].origin.x;
+        let x1 = if end < line.glyph_end {
+            rects[end].origin.x
+        } else {
+            rects[end - 1].max_x()
+        };
+        result.push(LocalRect::new(
+            [x0, line.bounds.min_y()].into(),
+            [x1 - x0, line.bounds.height()].into(),
+        ));
+    }
+    result
+}
+
+fn editor(text: impl Binding<String>, highlighter: Option<Rc<Highlighter>>) -> impl View {
     focus(move |has_focus| {
-        state(TextEditorState::new, move |state, cx| {
-            let cursor = cx[state].cursor;
-            canvas(move |cx, rect, vger| {
-                vger.translate([0.0, rect.height()]);
-                let font_size = 18;
+        let highlighter = highlighter.clone();
+        state(TextEditorState::new, move |state, _| {
+            let highlighter = highlighter.clone();
+            // Catches up with changes to the text from elsewhere.
+            let sync = move |cx: &mut Context| {
+                if *text.get(cx) != cx[state].text {
+                    let t = text.with(cx, |t| t.clone());
+                    cx[state].sync(&t);
+                }
+            };
+            let canvas = painter_canvas(move |cx, rect, vger| {
+                let font = cx.init_env(&Font::default);
                 let break_width = Some(rect.width());
+                let theme = cx.theme();
+                sync(cx);
+
+                let (display, cursor) = cx[state].display(text.get(cx));
+                let layout = cx.text_layout(&font, &display, break_width);
+                let (rects, lines) = (&layout.glyphs, &layout.lines);
+
+                cx[state].lines = lines.clone();
+                cx[state].height = rect.height();
+                if cx[state].follow_caret {
+                    cx[state].follow_caret = false;
+                    cx[state].scroll_to(caret_point(rects, cursor));
+                } else {
+                    // Stay in range if the text or size changed.
+                    let scroll = cx[state].scroll;
+                    cx[state].set_scroll(scroll);
+                }
 
-                vger.text(text.get(cx), font_size, TEXT_COLOR, break_width);
+                vger.scissor(rect);
+                vger.translate([0.0, rect.height() + cx[state].scroll]);
+                cx.snap_text(vger);
 
                 if has_focus {
-                    let rects = vger.glyph_positions(text.get(cx), font_size, break_width);
-                    let lines = vger.line_metrics(text.get(cx), font_size, break_width);
-                    let glyph_rect_paint = vger.color_paint(vger::Color::MAGENTA);
-                    let p = if cursor == rects.len() {
-                        if let Some(r) = rects.last() {
-                            [r.origin.x + r.size.width, r.origin.y].into()
-                        } else {
-                            [0.0, -20.0].into()
+                    let selection_paint = vger.color_paint(theme.accent_background);
+                    for s in &cx[state].selections {
+                        let range = cx[state].display_index(s.range().start)
+                            ..cx[state].display_index(s.range().end);
+                        for r in selection_rects(rects, lines, range) {
+                            vger.fill_rect(r, 0.0, selection_paint);
                         }
-                    } else {
-                        rects[cursor].origin
-                    };
-                    vger.fill_rect(LocalRect::new(p, [2.0, 20.0].into()), 0.0, glyph_rect_paint);
+                    }
+                }
+
+                match &highlighter {
+                    Some(highlighter) => {
+                        let t = text.with(cx, |t| t.clone());
+                        let colors = cx[state].display_colors(&t, highlighter.as_ref());
+                        draw_highlighted(cx, vger, &layout, &colors, theme.text, &font);
+                    }
+                    None => font.draw(cx, vger, &display, theme.text, break_width),
+                }
+
+                if has_focus {
+                    // Underline text which is still being composed.
+                    let start = cx[state].cursor();
+                    let end = start + cx[state].preedit.chars().count();
+                    let underline_paint = vger.color_paint(theme.text);
+                    for r in &rects[start.min(rects.len())..end.min(rects.len())] {
+                        let underline = LocalRect::new(r.origin, [r.size.width, 1.0].into());
+                        vger.fill_rect(underline, 0.0, underline_paint);
+                    }
 
-                    cx[state].glyph_rects = rects;
-                    cx[state].lines = lines;
+                    let glyph_rect_paint = vger.color_paint(theme.cursor);
+                    for (i, s) in cx[state].selections.iter().enumerate() {
+                        if i != cx[state].primary {
+                            let p = caret_point(rects, cx[state].display_index(s.head));
+                            vger.fill_rect(
+                                LocalRect::new(p, [2.0, CARET_HEIGHT].into()),
+                                0.0,
+                                glyph_rect_paint,
+                            );
+                        }
+                    }
+                    let cursor_rect =
+                        LocalRect::new(caret_point(rects, cursor), [2.0, CARET_HEIGHT].into());
+                    vger.fill_rect(cursor_rect, 0.0, glyph_rect_paint);
+                    cx.allow_ime(cursor_rect, vger.current_transform());
                 }
+
+                cx[state].glyph_rects = rects.clone();
+            })
+            .drag_p(move |cx, p, gesture, _| match gesture {
+                GestureState::Began => {
+                    sync(cx);
+                    let mods = cx.key_mods;
+                    cx[state].click(p, mods);
+                }
+                GestureState::Changed => {
+                    cx[state].drag_scroll(p);
+                    cx[state].drag_to(p);
+                }
+                _ => (),
             })
             .key(move |cx, k| {
+                if let Some(key) = k.pressed().filter(|_| has_focus) {
+                    let t = text.with(cx, |t| t.clone());
+                    let new_t = cx[state].edit(t, |state, t| state.key(&key, k.mods, t));
+                    text.with_mut(cx, |t| *t = new_t);
+                }
+            })
+            .ime(move |cx, event| {
                 if has_focus {
                     let t = text.with(cx, |t| t.clone());
-                    let new_t = cx[state].key(&k, t);
+                    let new_t = cx[state].edit(t, |state, t| state.ime(event, t));
                     text.with_mut(cx, |t| *t = new_t);
                 }
             })
+            .cursor(CursorIcon::Text);
+
+            TextAccess::new(
+                canvas,
+                accesskit::Role::TextField,
+                move |cx| {
+                    sync(cx);
+                    let Selection { anchor, head } = cx[state].selections[cx[state].primary];
+                    AccessText {
+                        text: text.get(cx).clone(),
+                        anchor,
+                        focus: head,
+                    }
+                },
+                move |cx, request| {
+                    sync(cx);
+                    let t = match request {
+                        TextRequest::Select { anchor, focus } => {
+                            cx[state].selections = vec![Selection {
+                                anchor,
+                                head: focus,
+                            }];
+                            cx[state].primary = 0;
+                            cx[state].follow_caret = true;
+                            return;
+                        }
+                        TextRequest::SetValue(value) => {
+                            cx[state].selections = vec![Selection::caret(value.chars().count())];
+                            cx[state].primary = 0;
+                            cx[state].text = value.clone();
+                            value
+                        }
+                        TextRequest::ReplaceSelection(value) => {
+                            let t = text.with(cx, |t| t.clone());
+                            cx[state].edit(t, |state, t| state.insert(&t, &value))
+                        }
+                    };
+                    text.with_mut(cx, |text| *text = t);
+                },
+            )
+            .multiline()
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_external_change() {
+        let mut state = TextEditorState::new();
+        state.sync("hello world");
+        state.selections = vec![
+            Selection::caret(2),
+            Selection {
+                anchor: 6,
+                head: 11,
+            },
+        ];
+
+        // Text inserted before the selections moves them along.
+        state.sync("oh, hello world");
+        assert_eq!(
+            state.selections,
+            vec![
+                Selection::caret(6),
+                Selection {
+                    anchor: 10,
+                    head: 15
+                }
+            ]
+        );
+
+        // Replacing the selected part leaves the caret at its end.
+        state.sync("oh, hello");
+        assert_eq!(
+            state.selections,
+            vec![Selection::caret(6), Selection::caret(9)]
+        );
+
+        // Shorter text keeps carets in range.
+        state.sync("hi");
+        assert_eq!(state.selections, vec![Selection::caret(2)]);
+        assert_eq!(
+            state.key(
+                &Key::Character('!'),
+                KeyboardModifiers::default(),
+                "hi".into()
+            ),
+            "hi!"
+        );
+    }
+
+    #[test]
+    fn test_ime_commit() {
+        let mut state = TextEditorState::new();
+        let text = state.key(
+            &Key::Character('a'),
+            KeyboardModifiers::default(),
+            String::new(),
+        );
+
+        let text = state.ime(
+            &ImeEvent::Preedit {
+                text: "にほん".into(),
+                cursor: Some((3, 3)),
+            },
+            text,
+        );
+        assert_eq!(text, "a");
+        assert_eq!(state.display(&text), ("aにほん".to_string(), 4));
+
+        // Keys go to the input method while composing.
+        let text = state.key(&Key::Backspace, KeyboardModifiers::default(), text);
+        assert_eq!(text, "a");
+
+        let text = state.ime(&ImeEvent::Commit("日本".into()), text);
+        assert_eq!(text, "a日本");
+        assert_eq!(state.cursor(), 3);
+        assert!(state.preedit.is_empty());
+
+        let text = state.key(&Key::Backspace, KeyboardModifiers::default(), text);
+        assert_eq!(text, "a日");
+        let text = state.key(&Key::Character('!'), KeyboardModifiers::default(), text);
+        assert_eq!(text, "a日!");
+    }
+
+    #[test]
+    fn test_highlight_runs() {
+        let text = "let x = 1;";
+        let colors = char_colors(text, &[(0..3, RED_HIGHLIGHT), (8..9, GREEN_HIGHLIGHT)]);
+        assert_eq!(colors[0], Some(RED_HIGHLIGHT));
+        assert_eq!(colors[3], None);
+        assert_eq!(colors[8], Some(GREEN_HIGHLIGHT));
+
+        let runs = color_runs(&colors, 0..text.len());
+        let ranges: Vec<_> = runs.iter().map(|(r, _)| r.clone()).collect();
+        assert_eq!(ranges, [0..3, 3..8, 8..9, 9..10]);
+
+        // Runs are split at line boundaries.
+        assert_eq!(color_runs(&colors, 1..2).len(), 1);
+    }
+
+    #[test]
+    fn test_highlight_cache() {
+        use std::cell::Cell;
+        let calls = Rc::new(Cell::new(0));
+        let c = calls.clone();
+        let highlighter = move |_: &str| {
+            c.set(c.get() + 1);
+            vec![(0..1, RED_HIGHLIGHT)]
+        };
+
+        let mut state = TextEditorState::new();
+        state.display_colors("ab", &highlighter);
+        let colors = state.display_colors("ab", &highlighter);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(colors, [Some(RED_HIGHLIGHT), None]);
+
+        state.display_colors("abc", &highlighter);
+        assert_eq!(calls.get(), 2);
+    }
+
+    fn ctrl() -> KeyboardModifiers {
+        KeyboardModifiers {
+            control: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_multiple_carets() {
+        let none = KeyboardModifiers::default();
+        let mut state = TextEditorState::new();
+        state.selections = vec![Selection::caret(1), Selection::caret(3)];
+
+        let text = state.key(&Key::Character('x'), none, "abcd".into());
+        assert_eq!(text, "axbcxd");
+        assert_eq!(state.selections, [Selection::caret(2), Selection::caret(5)]);
+
+        let text = state.key(&Key::Backspace, none, text);
+        let text = state.key(&Key::Backspace, none, text);
+        assert_eq!(text, "bd");
+        assert_eq!(state.selections, [Selection::caret(0), Selection::caret(1)]);
+
+        // Carets which meet are merged.
+        state.key(&Key::ArrowLeft, none, text);
+        assert_eq!(state.selections, [Selection::caret(0)]);
+    }
+
+    #[test]
+    fn test_drag_select() {
+        let none = KeyboardModifiers::default();
+        let mut state = TextEditorState::new();
+        state.height = 100.0;
+        state.glyph_rects = (0..4)
+            .map(|i| LocalRect::new([10.0 * i as f32, -20.0].into(), [10.0, 20.0].into()))
+            .collect();
+        state.lines = vec![LineMetrics {
+            glyph_start: 0,
+            glyph_end: 4,
+            bounds: LocalRect::new([0.0, -20.0].into(), [40.0, 20.0].into()),
+        }];
+
+        // Points are in the editor's space, where the line is at the top.
+        state.click([12.0, 90.0].into(), none);
+        assert_eq!(state.selections, [Selection::caret(1)]);
+
+        state.drag_to([37.0, 85.0].into());
+        assert_eq!(state.selections[0].range(), 1..4);
+
+        let text = state.key(&Key::Backspace, none, "abcd".into());
+        assert_eq!(text, "a");
+
+        // Shift-click extends the selection.
+        state.selections = vec![Selection::caret(2)];
+        let shift = KeyboardModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        state.click([3.0, 90.0].into(), shift);
+        assert_eq!(state.selections[0].range(), 0..2);
+    }
+
+    #[test]
+    fn test_scroll_to_caret() {
+        let mut state = TextEditorState::new();
+        state.height = 100.0;
+        state.lines = (0..10)
+            .map(|i| LineMetrics {
+                glyph_start: i,
+                glyph_end: i + 1,
+                bounds: LocalRect::new([0.0, -20.0 * (i + 1) as f32].into(), [50.0, 20.0].into()),
+            })
+            .collect();
+
+        // A caret below the bottom edge scrolls it into view.
+        state.scroll_to([0.0, -120.0].into());
+        assert_eq!(state.scroll, 20.0);
+
+        state.scroll_to([0.0, -20.0].into());
+        assert_eq!(state.scroll, 0.0);
+
+        // Dragging at the bottom edge scrolls down, but not past the end.
+        for _ in 0..10 {
+            state.drag_scroll([0.0, 0.0].into());
+        }
+        assert_eq!(state.scroll, 100.0);
+    }
+
+    #[test]
+    fn test_select_next_occurrence() {
+        let mut state = TextEditorState::new();
+        let text = String::from("foo bar foo baz foo");
+        state.selections = vec![Selection::caret(1)];
+
+        let text = state.key(&Key::Character('d'), ctrl(), text);
+        assert_eq!(state.selections[0].range(), 0..3);
+
+        let text = state.key(&Key::Character('d'), ctrl(), text);
+        let text = state.key(&Key::Character('d'), ctrl(), text);
+        assert_eq!(state.selections.len(), 3);
+        assert_eq!(state.cursor(), 19);
+
+        // Wraps around, but doesn't add duplicates.
+        let text = state.key(&Key::Character('d'), ctrl(), text);
+        assert_eq!(state.selections.len(), 3);
+
+        let text = state.key(&Key::Character('q'), KeyboardModifiers::default(), text);
+        assert_eq!(text, "q bar q baz q");
+    }
+}
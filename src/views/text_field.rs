@@ -0,0 +1,491 @@
+use crate::*;
+use std::any::Any;
+use std::rc::Rc;
+
+const TEXT_FIELD_HEIGHT: f32 = 30.0;
+const TEXT_FIELD_PADDING: f32 = 6.0;
+
+type SubmitFn = dyn Fn(&mut Context);
+
+#[derive(Clone, Default)]
+pub struct TextFieldOptions {
+    /// Shown dimmed while the field is empty.
+    placeholder: String,
+
+    /// Draw bullets instead of the text, for passwords.
+    secure: bool,
+
+    /// Called when Enter is pressed.
+    on_submit: Option<Rc<SubmitFn>>,
+}
+
+pub trait TextFieldMods: View + Sized {
+    fn placeholder(self, placeholder: &str) -> Self;
+    fn secure(self) -> Self;
+    fn on_submit(self, f: impl Fn(&mut Context) + 'static) -> Self;
+}
+
+/// Editing state for `text_field`.
+struct TextFieldState {
+    /// Caret position, in chars.
+    cursor: usize,
+
+    /// How far the text is scrolled left, to keep the caret visible.
+    scroll: f32,
+
+    /// Glyph rects of the displayed text from the last draw, unscrolled.
+    glyph_rects: Vec<LocalRect>,
+
+    /// Text being composed by an input method.
+    preedit: String,
+    preedit_cursor: Option<usize>,
+}
+
+/// The result of a key press in a `text_field`.
+#[derive(Debug, PartialEq)]
+enum FieldEdit {
+    Text(String),
+    Submit(String),
+}
+
+impl TextFieldState {
+    fn new() -> Self {
+        Self {
+            cursor: 0,
+            scroll: 0.0,
+            glyph_rects: vec![],
+            preedit: String::new(),
+            preedit_cursor: None,
+        }
+    }
+
+    /// The cursor, kept in range if the text was changed elsewhere.
+    fn cursor(&self, text: &str) -> usize {
+        self.cursor.min(text.chars().count())
+    }
+
+    fn insert(&mut self, mut text: String, s: &str) -> String {
+        // Fields are single line.
+        let s: String = s.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+        let cursor = self.cursor(&text);
+        text.insert_str(byte_index(&text, cursor), &s);
+        self.cursor = cursor + s.chars().count();
+        text
+    }
+
+    fn key(&mut self, k: &Key, mods: KeyboardModifiers, mut text: String) -> FieldEdit {
+        if !self.preedit.is_empty() {
+            // The input method is handling keys.
+            return FieldEdit::Text(text);
+        }
+        let len = text.chars().count();
+        let cursor = self.cursor(&text);
+        match k {
+            Key::Enter => return FieldEdit::Submit(text),
+            Key::Character(_) if mods.control || mods.command => (),
+            Key::Character(c) => return FieldEdit::Text(self.insert(text, &c.to_string())),
+            Key::Space => return FieldEdit::Text(self.insert(text, " ")),
+            Key::Backspace if cursor > 0 => {
+                text.remove(byte_index(&text, cursor - 1));
+                self.cursor = cursor - 1;
+            }
+            Key::Delete if cursor < len => {
+                text.remove(byte_index(&text, cursor));
+            }
+            Key::ArrowLeft => self.cursor = cursor.saturating_sub(1),
+            Key::ArrowRight => self.cursor = (cursor + 1).min(len),
+            Key::Home | Key::ArrowUp => self.cursor = 0,
+            Key::End | Key::ArrowDown => self.cursor = len,
+            _ => (),
+        }
+        FieldEdit::Text(text)
+    }
+
+    fn ime(&mut self, event: &ImeEvent, text: String) -> String {
+        match event {
+            ImeEvent::Preedit {
+                text: preedit,
+                cursor,
+            } => {
+                self.preedit = preedit.clone();
+                self.preedit_cursor = cursor.map(|(start, _)| start);
+                text
+            }
+            ImeEvent::Commit(s) => {
+                self.preedit.clear();
+                self.preedit_cursor = None;
+                self.insert(text, s)
+            }
+        }
+    }
+
+    /// The text as drawn, with any preedit inserted and bullets in secure
+    /// mode, and the caret position in it.
+    fn display(&self, text: &str, secure: bool) -> (String, usize) {
+        let cursor = self.cursor(text);
+        let mut t: String = if secure {
+            text.chars().map(|_| '•').collect()
+        } else {
+            text.to_string()
+        };
+        t.insert_str(byte_index(&t, cursor), &self.preedit);
+        let caret = cursor
+            + self
+                .preedit_cursor
+                .unwrap_or_else(|| self.preedit.chars().count());
+        (t, caret)
+    }
+
+    /// Moves the caret to the glyph boundary nearest `x`, which is in
+    /// field coordinates.
+    fn click(&mut self, x: f32, text: &str) {
+        let x = x - TEXT_FIELD_PADDING + self.scroll;
+        let len = text.chars().count();
+        self.cursor = self
+            .glyph_rects
+            .iter()
+            .position(|r| x < r.center().x)
+            .unwrap_or(len)
+            .min(len);
+    }
+
+    /// Scrolls so the caret at `caret_x` is visible in a field `width`
+    /// wide.
+    fn scroll_to(&mut self, caret_x: f32, width: f32) {
+        let visible = (width - 2.0 * TEXT_FIELD_PADDING).max(0.0);
+        if caret_x < self.scroll {
+            self.scroll = caret_x;
+        } else if caret_x > self.scroll + visible {
+            self.scroll = caret_x - visible;
+        }
+    }
+}
+
+fn caret_x(rects: &[LocalRect], i: usize) -> f32 {
+    match rects.get(i) {
+        Some(r) => r.origin.x,
+        None => rects.last().map(|r| r.max_x()).unwrap_or(0.0),
+    }
+}
+
+/// Struct for `text_field`. Fills the available width, at a fixed height.
+struct FieldFrame<V> {
+    child: V,
+}
+
+impl<V: View> View for FieldFrame<V> {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let sz = LocalSize::new(args.sz.width, TEXT_FIELD_HEIGHT.min(args.sz.height));
+        path.push(0);
+        self.child.layout(path, &mut args.size(sz));
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for FieldFrame<V> {}
+
+/// A single-line text field, for forms.
+///
+/// Long text scrolls horizontally to keep the caret in view. Use
+/// `.placeholder` for a hint shown while empty, `.secure` for passwords
+/// and `.on_submit` to respond to Enter.
+pub fn text_field(text: impl Binding<String>) -> impl TextFieldMods {
+    modview(move |opts: TextFieldOptions, _| {
+        focus(move |has_focus| {
+            let opts = opts.clone();
+            let submit = opts.on_submit.clone();
+            let field = state(TextFieldState::new, move |state, _| {
+                let opts = opts.clone();
+                let submit = submit.clone();
+                let secure = opts.secure;
+                let canvas = painter_canvas(move |cx, rect, vger| {
+                    let theme = cx.theme();
+                    let bg = vger.color_paint(theme.control_background);
+                    vger.fill_rect(rect, 4.0, bg);
+
+                    let font = cx.init_env(&Font::default);
+                    let font_size = font.size as f32;
+                    let (display, caret) = cx[state].display(text.get(cx), opts.secure);
+                    let layout = cx.text_layout(&font, &display, None);
+                    let rects = &layout.glyphs;
+                    let x = caret_x(rects, caret);
+                    if has_focus {
+                        cx[state].scroll_to(x, rect.width());
+                    }
+                    let scroll = cx[state].scroll;
+
+                    vger.save();
+                    vger.scissor(rect);
+                    vger.translate([
+                        TEXT_FIELD_PADDING - scroll,
+                        rect.height() / 2.0 + font_size / 2.0,
+                    ]);
+                    cx.snap_text(vger);
+
+                    if display.is_empty() {
+                        let mut color = theme.text;
+                        color.a *= 0.5;
+                        font.draw(cx, vger, &opts.placeholder, color, None);
+                    } else {
+                        font.draw(cx, vger, &display, theme.text, None);
+                    }
+
+                    if has_focus {
+                        let start = cx[state].cursor(text.get(cx));
+                        let end = start + cx[state].preedit.chars().count();
+                        let underline_paint = vger.color_paint(theme.text);
+                        for r in &rects[start.min(rects.len())..end.min(rects.len())] {
+                            let underline = LocalRect::new(r.origin, [r.size.width, 1.0].into());
+                            vger.fill_rect(underline, 0.0, underline_paint);
+                        }
+
+                        let cursor_rect = LocalRect::new(
+                            [x, -font_size - 2.0].into(),
+                            [2.0, font_size + 4.0].into(),
+                        );
+                        let paint = vger.color_paint(theme.cursor);
+                        vger.fill_rect(cursor_rect, 0.0, paint);
+                        cx.allow_ime(cursor_rect, vger.current_transform());
+                    }
+                    vger.restore();
+
+                    cx[state].glyph_rects = rects.clone();
+                })
+                .drag_p(move |cx, p, gesture, _| {
+                    if gesture == GestureState::Began {
+                        let t = text.with(cx, |t| t.clone());
+                        cx[state].click(p.x, &t);
+                    }
+                })
+                .key(move |cx, k| {
+                    // Holding enter submits once.
+                    let repeat_submit = |key: &Key| *key == Key::Enter && k.is_repeat();
+                    if let Some(key) = k.pressed().filter(|key| has_focus && !repeat_submit(key)) {
+                        let t = text.with(cx, |t| t.clone());
+                        match cx[state].key(&key, k.mods, t) {
+                            FieldEdit::Text(new_t) => text.with_mut(cx, |t| *t = new_t),
+                            FieldEdit::Submit(_) => {
+                                if let Some(submit) = &submit {
+                                    submit(cx)
+                                }
+                            }
+                        }
+                    }
+                })
+                .ime(move |cx, event| {
+                    if has_focus {
+                        let t = text.with(cx, |t| t.clone());
+                        let new_t = cx[state].ime(event, t);
+                        text.with_mut(cx, |t| *t = new_t);
+                    }
+                })
+                .cursor(CursorIcon::Text);
+
+                TextAccess::new(
+                    canvas,
+                    accesskit::Role::TextField,
+                    move |cx| {
+                        let (text, caret) = cx[state].display(text.get(cx), secure);
+                        AccessText {
+                            text,
+                            anchor: caret,
+                            focus: caret,
+                        }
+                    },
+                    move |cx, request| {
+                        let t = match request {
+                            TextRequest::Select { focus, .. } => {
+                                cx[state].cursor = focus;
+                                return;
+                            }
+                            TextRequest::SetValue(value) => {
+                                cx[state].cursor = 0;
+                                cx[state].insert(String::new(), &value)
+                            }
+                            TextRequest::ReplaceSelection(value) => {
+                                let t = text.with(cx, |t| t.clone());
+                                cx[state].insert(t, &value)
+                            }
+                        };
+                        text.with_mut(cx, |text| *text = t);
+                    },
+                )
+            });
+            FieldFrame { child: field }
+        })
+    })
+}
+
+impl<F> TextFieldMods for ModView<TextFieldOptions, F>
+where
+    ModView<TextFieldOptions, F>: View,
+{
+    fn placeholder(self, placeholder: &str) -> Self {
+        let mut opts = self.value;
+        opts.placeholder = placeholder.into();
+        ModView {
+            func: self.func,
+            value: opts,
+        }
+    }
+
+    fn secure(self) -> Self {
+        let mut opts = self.value;
+        opts.secure = true;
+        ModView {
+            func: self.func,
+            value: opts,
+        }
+    }
+
+    fn on_submit(self, f: impl Fn(&mut Context) + 'static) -> Self {
+        let mut opts = self.value;
+        opts.on_submit = Some(Rc::new(f));
+        ModView {
+            func: self.func,
+            value: opts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_field_keys() {
+        let none = KeyboardModifiers::default();
+        let mut state = TextFieldState::new();
+        let edit = |state: &mut TextFieldState, k, t: &str| match state.key(&k, none, t.into()) {
+            FieldEdit::Text(t) => t,
+            FieldEdit::Submit(_) => panic!("unexpected submit"),
+        };
+
+        let t = edit(&mut state, Key::Character('a'), "");
+        let t = edit(&mut state, Key::Character('c'), &t);
+        let t = edit(&mut state, Key::ArrowLeft, &t);
+        let t = edit(&mut state, Key::Character('b'), &t);
+        assert_eq!(t, "abc");
+        let t = edit(&mut state, Key::Delete, &t);
+        assert_eq!(t, "ab");
+        let t = edit(&mut state, Key::Home, &t);
+        let t = edit(&mut state, Key::Backspace, &t);
+        assert_eq!(t, "ab");
+
+        assert_eq!(
+            state.key(&Key::Enter, none, t.clone()),
+            FieldEdit::Submit(t)
+        );
+
+        // Pasted newlines are dropped.
+        let t = state.ime(&ImeEvent::Commit("x\ny".into()), "".into());
+        assert_eq!(t, "xy");
+    }
+
+    #[test]
+    fn test_secure_display() {
+        let mut state = TextFieldState::new();
+        state.cursor = 2;
+        state.preedit = "に".into();
+        assert_eq!(state.display("pass", true), ("••に••".to_string(), 3));
+
+        // The text may have shrunk since the cursor was set.
+        assert_eq!(state.display("p", false), ("pに".to_string(), 2));
+    }
+
+    #[test]
+    fn test_scroll_to_caret() {
+        let mut state = TextFieldState::new();
+        let width = 100.0 + 2.0 * TEXT_FIELD_PADDING;
+        state.scroll_to(50.0, width);
+        assert_eq!(state.scroll, 0.0);
+        state.scroll_to(250.0, width);
+        assert_eq!(state.scroll, 150.0);
+        state.scroll_to(120.0, width);
+        assert_eq!(state.scroll, 120.0);
+    }
+
+    #[test]
+    fn test_submit() {
+        let view = state(String::new, |name, _| {
+            state(
+                || 0,
+                move |count, cx| {
+                    vstack((
+                        text_field(name)
+                            .placeholder("name")
+                            .on_submit(move |cx| cx[count] += 1),
+                        text(&format!("{} {}", cx[name], cx[count])),
+                    ))
+                },
+            )
+        });
+
+        let mut harness = Harness::new(view);
+        harness.key(Key::Tab);
+        harness.type_text("ab\n");
+        harness.expect_text("ab 1");
+
+        // Held keys repeat, except for submitting.
+        harness.hold_key(Key::Character('c'), 2);
+        harness.hold_key(Key::Enter, 3);
+        harness.expect_text("abccc 2");
+    }
+}
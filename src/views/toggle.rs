@@ -6,18 +6,23 @@ pub fn toggle(on: impl Binding<bool>) -> impl View {
         || (),
         move |_, cx| {
             let b = *on.get(cx);
+            let theme = cx.theme();
             zstack((
                 rectangle()
                     .color(if b {
-                        AZURE_HIGHLIGHT_BACKGROUND
+                        theme.accent_background
                     } else {
-                        CONTROL_BACKGROUND
+                        theme.control_background
                     })
                     .corner_radius(10.0)
                     .size([40.0, 20.0])
                     .tap(move |cx| on.with_mut(cx, |b| *b = !*b)),
                 circle()
-                    .color(if b { AZURE_HIGHLIGHT } else { MEDIUM_GRAY })
+                    .color(if b {
+                        theme.accent
+                    } else {
+                        theme.control_foreground
+                    })
                     .size([10.0, 10.0])
                     .offset([if b { 25.0 } else { 5.0 }, 5.0]),
             ))
@@ -31,6 +36,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_toggle() {
         let mut cx = Context::new();
 
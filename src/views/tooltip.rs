@@ -0,0 +1,302 @@
+use crate::*;
+use std::any::Any;
+
+/// Seconds the mouse rests over a view before its tooltip shows.
+pub const TOOLTIP_DELAY: f32 = 0.5;
+const TOOLTIP_FADE: f32 = 0.1;
+const TOOLTIP_TEXT_SIZE: u32 = 12;
+const TOOLTIP_PADDING: f32 = 4.0;
+
+/// Room left for the pointer between it and a tooltip below it.
+const TOOLTIP_POINTER_GAP: f32 = 20.0;
+
+/// Distance kept from the edges of the window.
+const TOOLTIP_MARGIN: f32 = 4.0;
+
+#[derive(Clone, Copy, Default)]
+struct TooltipState {
+    hovering: bool,
+    /// Where the mouse came to rest.
+    position: LocalPoint,
+}
+
+fn tooltip_state(vid: ViewId, cx: &mut Context) -> &mut TooltipState {
+    cx.init_state(vid, &TooltipState::default);
+    cx.state_map
+        .get_mut(&vid)
+        .unwrap()
+        .state
+        .downcast_mut()
+        .unwrap()
+}
+
+/// Where a tooltip of `size` goes for a pointer at `pointer`: below and
+/// to the right of it, or above it if there's no room below, and moved
+/// to stay inside `bounds`.
+fn place_tooltip(pointer: LocalPoint, size: LocalSize, bounds: LocalRect) -> LocalRect {
+    let bounds = bounds.inflate(-TOOLTIP_MARGIN, -TOOLTIP_MARGIN);
+    let mut origin = LocalPoint::new(pointer.x, pointer.y - TOOLTIP_POINTER_GAP - size.height);
+    if origin.y < bounds.min_y() {
+        origin.y = pointer.y + TOOLTIP_MARGIN;
+    }
+    origin.x = origin
+        .x
+        .min(bounds.max_x() - size.width)
+        .max(bounds.min_x());
+    origin.y = origin
+        .y
+        .min(bounds.max_y() - size.height)
+        .max(bounds.min_y());
+    LocalRect::new(origin, size)
+}
+
+/// Struct for the `tooltip` modifier.
+pub struct Tooltip<V> {
+    child: V,
+    text: String,
+}
+
+impl<V> Tooltip<V>
+where
+    V: View,
+{
+    pub fn new(child: V, text: String) -> Self {
+        Self { child, text }
+    }
+
+    /// The view's state, and the opacity of its tooltip, which is animated.
+    fn states(&self, path: &mut IdPath, cx: &mut Context) -> (ViewId, StateHandle<f32>) {
+        let vid = cx.view_id(path);
+        path.push(1);
+        let opacity = cx.view_id(path);
+        path.pop();
+        cx.init_state(opacity, &|| 0.0f32);
+        (vid, StateHandle::new(opacity))
+    }
+
+    fn hide(opacity: StateHandle<f32>, cx: &mut Context) {
+        cx.stop_animating(opacity);
+        if cx[opacity] != 0.0 {
+            cx[opacity] = 0.0;
+        }
+    }
+
+    fn draw_label(&self, rect: LocalRect, opacity: f32, cx: &Context, vger: &mut Painter) {
+        let theme = cx.theme();
+        let mut background = theme.control_background;
+        let mut color = theme.foreground_on(background);
+        background.a *= opacity;
+        color.a *= opacity;
+
+        let paint = vger.color_paint(background);
+        vger.fill_rect(rect, 4.0, paint);
+
+        let bounds = vger.text_bounds(&self.text, TOOLTIP_TEXT_SIZE, None);
+        vger.save();
+        vger.translate(
+            rect.origin - bounds.origin + LocalOffset::new(TOOLTIP_PADDING, TOOLTIP_PADDING),
+        );
+        cx.snap_text(vger);
+        vger.text(&self.text, TOOLTIP_TEXT_SIZE, color, None);
+        vger.restore();
+    }
+}
+
+impl<V> View for Tooltip<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        let (vid, opacity) = self.states(path, cx);
+        match event {
+            Event::TouchMove { position, .. } if cx.mouse_button.is_none() => {
+                let inside = cx.get_layout(path).rect.contains(*position);
+                let state = tooltip_state(vid, cx);
+                if inside && !state.hovering {
+                    state.hovering = true;
+                    state.position = *position;
+                    cx.play_timeline(
+                        opacity,
+                        timeline()
+                            .at(0.0, 0.0)
+                            .at(TOOLTIP_DELAY, 0.0)
+                            .at(TOOLTIP_DELAY + TOOLTIP_FADE, 1.0),
+                    );
+                } else if inside {
+                    // Follow the mouse until the tooltip shows.
+                    if cx[opacity] == 0.0 {
+                        tooltip_state(vid, cx).position = *position;
+                    }
+                } else if state.hovering {
+                    state.hovering = false;
+                    Self::hide(opacity, cx);
+                }
+            }
+            // Clicking dismisses the tooltip until the mouse comes back.
+            Event::TouchBegin { .. } => Self::hide(opacity, cx),
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+
+        let (vid, opacity) = self.states(path, args.cx);
+        let opacity = args.cx[opacity];
+        if opacity <= 0.0 {
+            return;
+        }
+        let pointer = tooltip_state(vid, args.cx).position;
+        let xform = args.vger.current_transform();
+        let window = WorldRect::new(WorldPoint::zero(), args.cx.window_size);
+        let bounds = match xform.inverse() {
+            Some(inverse) => inverse.outer_transformed_rect(&window),
+            None => return,
+        };
+
+        let text = args.vger.text_bounds(&self.text, TOOLTIP_TEXT_SIZE, None);
+        let size = text.size + LocalSize::new(2.0 * TOOLTIP_PADDING, 2.0 * TOOLTIP_PADDING);
+        let rect = place_tooltip(pointer, size, bounds);
+
+        let style = LayerStyle {
+            opacity,
+            shadow: Some(Shadow {
+                radius: 4.0,
+                offset: [0.0, -2.0].into(),
+                color: Color::new(0.0, 0.0, 0.0, 0.2),
+            }),
+            floating: true,
+            ..LayerStyle::default()
+        };
+        match args.cx.begin_layer(rect, xform, &style) {
+            Some(mut layer) => {
+                self.draw_label(rect, 1.0, args.cx, &mut layer);
+                args.cx.end_layer(args.vger, layer, xform, style);
+            }
+            None => self.draw_label(rect, opacity, args.cx, args.vger),
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let size = self.child.layout(path, args);
+        path.pop();
+
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), size),
+                offset: LocalOffset::zero(),
+            },
+        );
+        size
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(1);
+        map.push(cx.view_id(path));
+        path.pop();
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for Tooltip<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_tooltip_delay() {
+        let mut h = Harness::new(text("help").padding(Auto).tooltip("Shows help"));
+        h.update();
+        let help = h.find_text("help").unwrap().center();
+
+        h.hover(help);
+        h.advance(Duration::from_secs_f32(TOOLTIP_DELAY / 2.0));
+        assert_eq!(h.states::<f32>(), vec![0.0]);
+        h.advance(Duration::from_secs(1));
+        assert_eq!(h.states::<f32>(), vec![1.0]);
+
+        h.hover(Point::new(-10.0, -10.0));
+        assert_eq!(h.states::<f32>(), vec![0.0]);
+    }
+
+    #[test]
+    fn test_tooltip_click_dismisses() {
+        let mut h = Harness::new(text("help").padding(Auto).tooltip("Shows help"));
+        h.update();
+        let help = h.find_text("help").unwrap().center();
+
+        h.hover(help);
+        h.advance(Duration::from_secs(1));
+        h.click_at(help);
+        assert_eq!(h.states::<f32>(), vec![0.0]);
+
+        // It stays hidden while the mouse is still over the view.
+        h.hover(Point::new(help.x + 1.0, help.y));
+        h.advance(Duration::from_secs(1));
+        assert_eq!(h.states::<f32>(), vec![0.0]);
+    }
+
+    #[test]
+    fn test_place_tooltip() {
+        let bounds = LocalRect::new(LocalPoint::zero(), [200.0, 100.0].into());
+        let size = LocalSize::new(50.0, 20.0);
+
+        let rect = place_tooltip([20.0, 80.0].into(), size, bounds);
+        assert_eq!(rect.origin, LocalPoint::new(20.0, 40.0));
+
+        // No room below, so it goes above, and it's kept off the right edge.
+        let rect = place_tooltip([190.0, 10.0].into(), size, bounds);
+        assert_eq!(rect.origin, LocalPoint::new(146.0, 14.0));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::*;
+use std::any::Any;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TransformOp {
+    /// Counterclockwise, in radians.
+    Rotate(f32),
+    Scale(LocalOffset),
+}
+
+/// Struct for the `rotate` and `scale` modifiers.
+pub struct TransformView<V> {
+    child: V,
+    op: TransformOp,
+
+    /// Point the view turns or grows around, relative to its bounds, from
+    /// `(0, 0)` at the bottom left to `(1, 1)` at the top right.
+    anchor: LocalPoint,
+}
+
+impl<V> TransformView<V>
+where
+    V: View,
+{
+    pub fn rotate(child: V, angle: f32) -> Self {
+        Self::new(child, TransformOp::Rotate(angle))
+    }
+
+    pub fn scale(child: V, factor: impl Into<LocalOffset>) -> Self {
+        Self::new(child, TransformOp::Scale(factor.into()))
+    }
+
+    fn new(child: V, op: TransformOp) -> Self {
+        Self {
+            child,
+            op,
+            anchor: [0.5, 0.5].into(),
+        }
+    }
+
+    /// Transforms around `anchor` rather than the center. `(0, 0)` is the
+    /// bottom left of the view and `(1, 1)` the top right.
+    pub fn anchor(self, anchor: impl Into<LocalPoint>) -> Self {
+        Self {
+            anchor: anchor.into(),
+            ..self
+        }
+    }
+
+    fn anchor_point(&self, path: &IdPath, cx: &Context) -> LocalPoint {
+        let rect = cx.get_layout(path).rect;
+        rect.origin + LocalOffset::new(self.anchor.x * rect.width(), self.anchor.y * rect.height())
+    }
+
+    /// Maps the child's coordinates to ours.
+    fn matrix(&self, path: &IdPath, cx: &Context) -> LocalTransform {
+        let a = self.anchor_point(path, cx).to_vector();
+        let m = LocalTransform::translation(-a.x, -a.y);
+        let m = match self.op {
+            TransformOp::Rotate(angle) => m.then_rotate(euclid::Angle::radians(angle)),
+            TransformOp::Scale(s) => m.then_scale(s.x, s.y),
+        };
+        m.then_translate(a)
+    }
+
+    /// Maps our coordinates to the child's, if the transform can be
+    /// undone. Views scaled to nothing can't be touched.
+    fn inverse(&self, path: &IdPath, cx: &Context) -> Option<LocalTransform> {
+        self.matrix(path, cx).inverse()
+    }
+}
+
+impl<V> View for TransformView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let event = match self.inverse(path, cx) {
+            Some(inverse) => event.transform(&inverse),
+            None => event.clone(),
+        };
+        path.push(0);
+        self.child.process(&event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let a = self.anchor_point(path, args.cx).to_vector();
+        args.vger.save();
+        // vger applies the last transform given to points first.
+        args.vger.translate(a);
+        match self.op {
+            TransformOp::Rotate(angle) => args.vger.rotate(angle),
+            TransformOp::Scale(s) => args.vger.scale(s),
+        }
+        args.vger.translate(-a);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+        args.vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+
+        // Transforms don't change layout, only where the view is drawn.
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let m = self.matrix(path, cx);
+        path.push(0);
+        self.child.dirty(path, m.then(&xform), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let inverse = self.inverse(path, cx)?;
+        path.push(0);
+        let id = self.child.hittest(path, inverse.transform_point(pt), cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for TransformView<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn counter() -> (Rc<Cell<i32>>, impl View) {
+        let taps = Rc::new(Cell::new(0));
+        let t = taps.clone();
+        let view = rectangle()
+            .tap(move |_| t.set(t.get() + 1))
+            .size([100.0, 20.0]);
+        (taps, view)
+    }
+
+    #[test]
+    fn test_rotate_hittest() {
+        let (taps, view) = counter();
+        let view = view.rotate(std::f32::consts::FRAC_PI_2);
+        let mut harness = Harness::with_size(view, Size::new(200.0, 200.0));
+
+        // The bar is vertical now, around the window's center.
+        harness.click_at(Point::new(100.0, 140.0));
+        assert_eq!(taps.get(), 1);
+        harness.click_at(Point::new(140.0, 100.0));
+        assert_eq!(taps.get(), 1);
+    }
+
+    #[test]
+    fn test_scale_hittest() {
+        // The bar spans (50, 90) to (150, 110) untransformed.
+        let (taps, view) = counter();
+        let mut harness = Harness::with_size(view.scale(0.5), Size::new(200.0, 200.0));
+        harness.click_at(Point::new(60.0, 100.0));
+        assert_eq!(taps.get(), 0);
+        harness.click_at(Point::new(120.0, 100.0));
+        assert_eq!(taps.get(), 1);
+
+        // Grown up and to the right from the bottom left.
+        let (taps, view) = counter();
+        let view = view.scale(2.0).anchor([0.0, 0.0]);
+        let mut harness = Harness::with_size(view, Size::new(200.0, 200.0));
+        harness.click_at(Point::new(190.0, 125.0));
+        assert_eq!(taps.get(), 1);
+    }
+}
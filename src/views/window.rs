@@ -73,7 +73,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -157,7 +157,7 @@ where
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
         path.push(0);
-        self.child.gc(path, cx, map);
+        self.child.gc_view(path, cx, map);
         path.pop();
     }
 
@@ -175,3 +175,133 @@ where
 }
 
 impl<V> private::Sealed for FullscreenView<V> {}
+
+/// Struct for the `window_drag_area` modifier.
+pub struct WindowDragView<V> {
+    child: V,
+}
+
+impl<V> WindowDragView<V>
+where
+    V: View,
+{
+    pub fn new(v: V) -> Self {
+        Self { child: v }
+    }
+}
+
+impl<V> View for WindowDragView<V>
+where
+    V: View,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+
+        // Controls within the area keep their presses.
+        if let Event::TouchBegin { id, position } = event {
+            if cx.touches[*id].is_default() && self.hittest(path, *position, cx).is_some() {
+                cx.window_drag_requested = true;
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc_view(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for WindowDragView<V> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_window_drag_area() {
+        let view = hstack((
+            rectangle().window_drag_area(),
+            button("close", |_| ()).window_drag_area(),
+        ));
+        let mut harness = Harness::with_size(view, Size::new(200.0, 100.0));
+        harness.click_at(Point::new(50.0, 50.0));
+        assert!(harness.context().window_drag_requested);
+
+        // Buttons in the area still take their clicks.
+        harness.context().window_drag_requested = false;
+        harness.click("close");
+        assert!(!harness.context().window_drag_requested);
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_click_through_empty() {
+        let view = || hstack((circle(), spacer()));
+        let mut harness = Harness::with_size(view(), Size::new(200.0, 100.0));
+        let cx = harness.context();
+        assert!(!cx.clicks_through_at(&view(), [150.0, 50.0].into()));
+
+        // Beside the circle, and in its bounding box's corner.
+        cx.set_click_through_empty(true);
+        assert!(!cx.clicks_through_at(&view(), [50.0, 50.0].into()));
+        assert!(cx.clicks_through_at(&view(), [150.0, 50.0].into()));
+        assert!(cx.clicks_through_at(&view(), [5.0, 5.0].into()));
+
+        cx.set_click_through(true);
+        assert!(cx.clicks_through_at(&view(), [50.0, 50.0].into()));
+    }
+}
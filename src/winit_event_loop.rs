@@ -1,22 +1,34 @@
 use crate::*;
 
 use futures::executor::block_on;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, VecDeque},
-    sync::{Arc, Mutex},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{PhysicalPosition, PhysicalSize, Position},
     event::{
-        ElementState, Event as WEvent, MouseButton as WMouseButton, Touch, TouchPhase,
-        VirtualKeyCode, WindowEvent,
+        ElementState, Event as WEvent, Ime, MouseButton as WMouseButton, MouseScrollDelta, Touch,
+        TouchPhase, VirtualKeyCode, WindowEvent,
     },
-    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
-    window::{Window, WindowBuilder},
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    window::{Window, WindowBuilder, WindowId},
 };
 
-type WorkQueue = VecDeque<Box<dyn FnOnce(&mut Context) + Send>>;
+type Work = Box<dyn FnOnce(&mut Context) + Send>;
+
+/// Work sent with `on_main` and `on_window`, and the window it's for.
+/// `None` is the main window.
+type WorkQueue = VecDeque<(Option<WindowId>, Work)>;
+
+/// Points scrolled per line, for mice which scroll in lines.
+const LINE_HEIGHT: f32 = 20.0;
 
 #[cfg(not(target_arch = "wasm32"))]
 lazy_static! {
@@ -28,17 +40,99 @@ lazy_static! {
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn on_main(f: impl FnOnce(&mut Context) + Send + 'static) {
-    GLOBAL_WORK_QUEUE.lock().unwrap().push_back(Box::new(f));
+    send_work(None, Box::new(f));
+}
 
-    // Wake up the event loop.
-    let opt_proxy = GLOBAL_EVENT_LOOP_PROXY.lock().unwrap();
-    if let Some(proxy) = &*opt_proxy {
-        if let Err(err) = proxy.send_event(()) {
-            println!("error waking up event loop: {:?}", err);
+/// Like `on_main`, in the `Context` of `window`. Dropped if the window has
+/// closed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn on_window(window: WindowId, f: impl FnOnce(&mut Context) + Send + 'static) {
+    send_work(Some(window), Box::new(f));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn send_work(window: Option<WindowId>, work: Work) {
+    GLOBAL_WORK_QUEUE.lock().unwrap().push_back((window, work));
+    wake_event_loop();
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// The web's event loop proxy can't leave its thread, and neither can
+    /// anything which wakes it.
+    static WEB_EVENT_LOOP_PROXY: std::cell::RefCell<Option<EventLoopProxy<()>>> =
+        std::cell::RefCell::new(None);
+}
+
+/// Wakes the event loop, so it updates windows.
+pub(crate) fn wake_event_loop() {
+    let wake = |proxy: &Option<EventLoopProxy<()>>| {
+        if let Some(proxy) = proxy {
+            if let Err(err) = proxy.send_event(()) {
+                println!("error waking up event loop: {:?}", err);
+            }
         }
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    wake(&GLOBAL_EVENT_LOOP_PROXY.lock().unwrap());
+    #[cfg(target_arch = "wasm32")]
+    WEB_EVENT_LOOP_PROXY.with(|proxy| wake(&proxy.borrow()));
+}
+
+/// Runs work sent with `on_main`, for running views without the event loop.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn run_main_queue(cx: &mut Context) {
+    while let Some((_, f)) = pop_work() {
+        f(cx);
     }
 }
 
+/// Takes the next piece of work, letting go of the queue so the work can
+/// send more.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn pop_work() -> Option<(Option<WindowId>, Work)> {
+    GLOBAL_WORK_QUEUE.lock().unwrap().pop_front()
+}
+
+/// An AccessKit platform adapter for a window, like
+/// `accesskit_winit::Adapter`, which tells assistive technology about the
+/// window's views. See `AppConfig::access_adapter`.
+pub trait AccessAdapter {
+    /// Sends the nodes which changed in an update.
+    fn update(&mut self, update: accesskit::TreeUpdate);
+
+    /// Lets the adapter follow the window's events, as
+    /// `accesskit_winit::Adapter::on_event` needs to.
+    fn on_event(&mut self, _window: &Window, _event: &WindowEvent) {}
+}
+
+/// Makes a window's `AccessAdapter`, from the window, its whole tree, and
+/// where to send requests.
+pub(crate) type MakeAccessAdapter =
+    dyn Fn(&Window, accesskit::TreeUpdate, AccessActions) -> Box<dyn AccessAdapter>;
+
+/// Sends requests from assistive technology to a window's views, as
+/// `Event::Access`. Give it to the window's AccessKit adapter as its
+/// action handler.
+#[derive(Clone, Copy, Debug)]
+pub struct AccessActions {
+    window: WindowId,
+}
+
+impl accesskit::ActionHandler for AccessActions {
+    fn do_action(&self, request: accesskit::ActionRequest) {
+        #[cfg(not(target_arch = "wasm32"))]
+        on_window(self.window, move |cx| cx.access.requests.push(request));
+        // AccessKit has no adapter for the web.
+        #[cfg(target_arch = "wasm32")]
+        let _ = (self.window, request);
+    }
+}
+
+/// The `on_main` queue is global, so tests using it can't run at once.
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub(crate) static MAIN_QUEUE_LOCK: Mutex<()> = Mutex::new(());
+
 struct Setup {
     size: PhysicalSize<u32>,
     surface: wgpu::Surface,
@@ -47,41 +141,39 @@ struct Setup {
     queue: wgpu::Queue,
 }
 
-async fn setup(window: &Window) -> Setup {
-    #[cfg(target_arch = "wasm32")]
-    {
-        use winit::platform::web::WindowExtWebSys;
-        let query_string = web_sys::window().unwrap().location().search().unwrap();
-        let level: log::Level = parse_url_query_string(&query_string, "RUST_LOG")
-            .map(|x| x.parse().ok())
-            .flatten()
-            .unwrap_or(log::Level::Error);
-        console_log::init_with_level(level).expect("could not initialize logger");
-        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-        // On wasm, append the canvas to the document body
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| doc.body())
-            .and_then(|body| {
-                body.append_child(&web_sys::Element::from(window.canvas()))
-                    .ok()
-            })
-            .expect("couldn't append canvas to document body");
-    }
+/// Sets up logging and adds the window's canvas to the page.
+#[cfg(target_arch = "wasm32")]
+fn init_web(window: &Window) {
+    use winit::platform::web::WindowExtWebSys;
+    let query_string = web_sys::window().unwrap().location().search().unwrap();
+    let level: log::Level = parse_url_query_string(&query_string, "RUST_LOG")
+        .map(|x| x.parse().ok())
+        .flatten()
+        .unwrap_or(log::Level::Error);
+    console_log::init_with_level(level).expect("could not initialize logger");
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    // On wasm, append the canvas to the document body
+    web_sys::window()
+        .and_then(|win| win.document())
+        .and_then(|doc| doc.body())
+        .and_then(|body| {
+            body.append_child(&web_sys::Element::from(window.canvas()))
+                .ok()
+        })
+        .expect("couldn't append canvas to document body");
+}
 
+async fn setup(window: &Window, instance: &wgpu::Instance) -> Setup {
     // log::info!("Initializing the surface...");
 
     let backend = wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all);
-    let instance_desc = wgpu::InstanceDescriptor::default();
-
-    let instance = wgpu::Instance::new(instance_desc);
     let (size, surface) = unsafe {
         let size = window.inner_size();
         let surface = instance.create_surface(&window);
         (size, surface.unwrap())
     };
     let adapter =
-        wgpu::util::initialize_adapter_from_env_or_default(&instance, backend, Some(&surface))
+        wgpu::util::initialize_adapter_from_env_or_default(instance, backend, Some(&surface))
             .await
             .expect("No suitable GPU adapters found on the system!");
 
@@ -113,9 +205,182 @@ async fn setup(window: &Window) -> Setup {
     }
 }
 
+/// Is an uncaptured wgpu error caused by losing the device? wgpu 0.16
+/// doesn't have a device lost callback, so like wgpu does for running out
+/// of memory, we look for wgpu-core's error among the causes.
+fn is_device_lost(err: &wgpu::Error) -> bool {
+    let source = match err {
+        wgpu::Error::OutOfMemory { .. } => return true,
+        wgpu::Error::Validation { source, .. } => source,
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use wgpu_core::device::{queue::QueueSubmitError, DeviceError};
+
+        let mut cause: Option<&(dyn std::error::Error + 'static)> = Some(source.as_ref());
+        while let Some(err) = cause {
+            // Submitting is where loss usually shows up, and its error
+            // doesn't give the device error as a source.
+            if let Some(DeviceError::Lost) | Some(DeviceError::OutOfMemory) = err.downcast_ref() {
+                return true;
+            }
+            if let Some(QueueSubmitError::Queue(DeviceError::Lost)) = err.downcast_ref() {
+                return true;
+            }
+            cause = err.source();
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = source;
+
+    false
+}
+
+/// GPU resources for a window, which can be recreated if the device is
+/// lost.
+struct Gpu {
+    surface: wgpu::Surface,
+    adapter: wgpu::Adapter,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    config: wgpu::SurfaceConfiguration,
+    vger: Vger,
+
+    /// Set when the device is lost.
+    lost: Arc<AtomicBool>,
+}
+
+impl Gpu {
+    /// Creates the window's GPU resources, adding how long that took to
+    /// `timings`.
+    fn new(
+        window: &Window,
+        instance: &wgpu::Instance,
+        transparent: bool,
+        vsync: bool,
+        timings: &mut StartupTimings,
+    ) -> Self {
+        let start = Stopwatch::start();
+        let setup = block_on(setup(window, instance));
+        timings.gpu += start.elapsed();
+        let device = Arc::new(setup.device);
+        let queue = Arc::new(setup.queue);
+
+        let lost = Arc::new(AtomicBool::new(false));
+        let lost_flag = lost.clone();
+        device.on_uncaptured_error(Box::new(move |err| {
+            if is_device_lost(&err) {
+                println!("GPU device lost: {}", err);
+                lost_flag.store(true, Ordering::SeqCst);
+            } else {
+                // Other errors are bugs, so keep wgpu's default behavior.
+                panic!("wgpu error: {}", err);
+            }
+        }));
+
+        let capabilities = setup.surface.get_capabilities(&setup.adapter);
+        let config = wgpu::SurfaceConfiguration {
+            // Copied from to keep frames for partial redraws, where the
+            // backend allows. See `configure`.
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: capabilities.formats[0],
+            width: setup.size.width,
+            height: setup.size.height,
+            present_mode: if vsync {
+                wgpu::PresentMode::Fifo
+            } else {
+                wgpu::PresentMode::AutoNoVsync
+            },
+            alpha_mode: alpha_mode(&capabilities.alpha_modes, transparent),
+            view_formats: vec![],
+        };
+        let start = Stopwatch::start();
+        let vger = Vger::new(device.clone(), queue.clone(), config.format);
+        timings.renderer += start.elapsed();
+
+        let mut gpu = Self {
+            surface: setup.surface,
+            adapter: setup.adapter,
+            device,
+            queue,
+            config,
+            vger,
+            lost,
+        };
+        gpu.configure();
+        gpu
+    }
+
+    /// Is the window too small to draw into, as when minimized?
+    fn is_minimized(&self) -> bool {
+        self.config.width == 0 || self.config.height == 0
+    }
+
+    fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) -> bool {
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.configure()
+    }
+
+    /// Configures the surface, switching formats if the current one is no
+    /// longer supported, for example after moving to another display.
+    /// Returns true if the format changed, so caches should be dropped.
+    fn configure(&mut self) -> bool {
+        if self.is_minimized() {
+            // Surfaces can't be zero sized. We'll configure when restored.
+            return false;
+        }
+        let formats = self.surface.get_capabilities(&self.adapter).formats;
+        let mut changed = false;
+        if !formats.contains(&self.config.format) {
+            if let Some(format) = formats.first() {
+                println!("surface format changed to {:?}", format);
+                self.config.format = *format;
+                self.vger = Vger::new(self.device.clone(), self.queue.clone(), *format);
+                changed = true;
+            }
+        }
+
+        // Not every backend can copy from surfaces, and wgpu doesn't say
+        // which until configuring fails.
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        self.surface.configure(&self.device, &self.config);
+        if block_on(self.device.pop_error_scope()).is_some() {
+            self.config.usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+            self.surface.configure(&self.device, &self.config);
+        }
+        changed
+    }
+}
+
+/// How the surface is composited with what's behind the window. vger
+/// draws over a transparent clear, which gives premultiplied alpha.
+fn alpha_mode(modes: &[wgpu::CompositeAlphaMode], transparent: bool) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::*;
+    if transparent {
+        for mode in [PreMultiplied, Inherit] {
+            if modes.contains(&mode) {
+                return mode;
+            }
+        }
+    }
+    Auto
+}
+
 fn process_event(cx: &mut Context, view: &impl View, event: &Event, window: &Window) {
     cx.process(view, event);
 
+    if std::mem::take(&mut cx.window_drag_requested) {
+        if let Err(err) = window.drag_window() {
+            println!("couldn't drag window: {}", err);
+        }
+    }
+
     if cx.grab_cursor && !cx.prev_grab_cursor {
         println!("grabbing cursor");
         window
@@ -134,55 +399,861 @@ fn process_event(cx: &mut Context, view: &impl View, event: &Event, window: &Win
     }
 
     cx.prev_grab_cursor = cx.grab_cursor;
+
+    if cx.cursor != cx.prev_cursor {
+        window.set_cursor_icon(cursor_icon(cx.cursor()));
+        cx.prev_cursor = cx.cursor;
+    }
+}
+
+fn cursor_icon(icon: CursorIcon) -> winit::window::CursorIcon {
+    use winit::window::CursorIcon as Icon;
+    match icon {
+        CursorIcon::Default => Icon::Default,
+        CursorIcon::Text => Icon::Text,
+        CursorIcon::Pointer => Icon::Hand,
+        CursorIcon::Grab => Icon::Grab,
+        CursorIcon::Grabbing => Icon::Grabbing,
+        CursorIcon::ResizeHorizontal => Icon::EwResize,
+        CursorIcon::ResizeVertical => Icon::NsResize,
+        CursorIcon::ResizeDiagonal => Icon::NwseResize,
+        CursorIcon::Crosshair => Icon::Crosshair,
+        CursorIcon::NotAllowed => Icon::NotAllowed,
+        CursorIcon::Move => Icon::Move,
+    }
+}
+
+pub(crate) fn color_scheme(theme: winit::window::Theme) -> ColorScheme {
+    match theme {
+        winit::window::Theme::Light => ColorScheme::Light,
+        winit::window::Theme::Dark => ColorScheme::Dark,
+    }
 }
 
 /// Call this function to run your UI.
+///
+/// Use `App` to customize the window or add plugins.
 pub fn rui(view: impl View) {
+    App::new(view).run()
+}
+
+/// Creates a window with `options`.
+fn build_window(options: &WindowOptions, target: &EventLoopWindowTarget<()>) -> Window {
+    let logical = |size: Size| winit::dpi::LogicalSize::new(size.width, size.height);
+    let mut builder = WindowBuilder::new()
+        .with_title(&options.title)
+        .with_resizable(options.resizable)
+        .with_transparent(options.transparent)
+        .with_decorations(options.decorations);
+    if options.always_on_top {
+        builder = builder.with_window_level(winit::window::WindowLevel::AlwaysOnTop);
+    }
+    if let Some(size) = options.size {
+        builder = builder.with_inner_size(logical(size));
+    }
+    if let Some(size) = options.min_size {
+        builder = builder.with_min_inner_size(logical(size));
+    }
+    if let Some(size) = options.max_size {
+        builder = builder.with_max_inner_size(logical(size));
+    }
+
+    // Shown once it's moved to where it was left.
+    builder = builder.with_visible(options.persist.is_none());
+    builder.build(target).unwrap()
+}
+
+/// Where a window was left. See `WindowOptions::persist`.
+#[derive(Serialize, Deserialize)]
+struct WindowFrame {
+    /// Content size in points.
+    size: [f32; 2],
+
+    /// Outer position in pixels, where the platform has one.
+    position: Option<[i32; 2]>,
+}
+
+/// A window with its own root view, `Context` and GPU resources.
+struct AppWindow {
+    window: Window,
+    instance: Rc<wgpu::Instance>,
+    gpu: Gpu,
+    cx: Context,
+    view: AnyView,
+    transparent: bool,
+    vsync: bool,
+
+    /// Limits the frame rate. See `WindowOptions::max_fps`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pacer: FramePacer,
+
+    /// Last title sent to the window.
+    window_title: String,
+
+    /// The mouse and fingers.
+    input: WinitInput,
+
+    /// Last click through state sent to the window.
+    click_through: bool,
+
+    /// Is the cursor over the window where there's no view? See
+    /// `Context::set_click_through_empty`.
+    cursor_over_empty: bool,
+
+    /// Last IME state sent to the window.
+    ime_allowed: bool,
+    ime_cursor_area: Option<WorldRect>,
+
+    /// Files dropped since the last update. Each arrives in its own
+    /// event, so they're sent to views together.
+    dropped_files: Vec<std::path::PathBuf>,
+
+    /// Makes `access` once there's a tree to give it.
+    make_access: Option<Rc<MakeAccessAdapter>>,
+    access: Option<Box<dyn AccessAdapter>>,
+
+    /// See `WindowOptions::persist`.
+    persist: Option<String>,
+}
+
+impl AppWindow {
+    /// Opens a window. Startup is timed from `launch`.
+    fn new(
+        options: &WindowOptions,
+        view: AnyView,
+        instance: Rc<wgpu::Instance>,
+        launch: Stopwatch,
+        target: &EventLoopWindowTarget<()>,
+    ) -> Self {
+        let mut timings = StartupTimings::default();
+        let start = Stopwatch::start();
+        let window = build_window(options, target);
+
+        #[cfg(target_arch = "wasm32")]
+        init_web(&window);
+        timings.window = start.elapsed();
+
+        let transparent = options.transparent;
+        let vsync = options.vsync;
+        let gpu = Gpu::new(&window, &instance, transparent, vsync, &mut timings);
+
+        let mut cx = Context::new();
+        cx.begin_startup(launch, timings);
+        cx.set_scale_factor(window.scale_factor() as f32);
+        cx.transparent_window = transparent;
+        if !transparent {
+            cx.set_lcd_text(options.lcd_text);
+        }
+        cx.set_system_color_scheme(window.theme().map(color_scheme));
+        cx.window_title = options.title.clone();
+        cx.window_id = Some(window.id());
+
+        Self {
+            window,
+            instance,
+            gpu,
+            cx,
+            view,
+            transparent,
+            vsync,
+            #[cfg(not(target_arch = "wasm32"))]
+            pacer: FramePacer::new(options.max_fps),
+            window_title: options.title.clone(),
+            input: WinitInput::default(),
+            click_through: false,
+            cursor_over_empty: false,
+            ime_allowed: false,
+            ime_cursor_area: None,
+            dropped_files: vec![],
+            make_access: None,
+            access: None,
+            persist: options.persist.clone(),
+        }
+    }
+
+    /// Moves the window to where `store` says it was left, then shows it.
+    fn restore_frame(&self, store: &PersistentStore) {
+        let Some(key) = &self.persist else {
+            return;
+        };
+        if let Some(frame) = store.get::<WindowFrame>(key) {
+            let [width, height] = frame.size;
+            self.window
+                .set_inner_size(winit::dpi::LogicalSize::new(width, height));
+            if let Some([x, y]) = frame.position {
+                self.window
+                    .set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+            }
+        }
+        self.window.set_visible(true);
+    }
+
+    /// The window's frame and the key to save it under, if it's persisted.
+    fn frame(&self) -> Option<(&str, WindowFrame)> {
+        let key = self.persist.as_deref()?;
+        let size = self.size();
+
+        // Minimized.
+        if size[0] <= 0.0 || size[1] <= 0.0 {
+            return None;
+        }
+        let position = self.window.outer_position().ok().map(|p| [p.x, p.y]);
+        Some((key, WindowFrame { size, position }))
+    }
+
+    /// The window's size in points.
+    fn size(&self) -> [f32; 2] {
+        let window_size = self.window.inner_size();
+        let scale = self.window.scale_factor() as f32;
+        // println!("window_size: {:?}", window_size);
+        [
+            window_size.width as f32 / scale,
+            window_size.height as f32 / scale,
+        ]
+    }
+
+    fn update(&mut self) {
+        let [width, height] = self.size();
+        let cx = &mut self.cx;
+        cx.set_scale_factor(self.window.scale_factor() as f32);
+
+        if !self.dropped_files.is_empty() {
+            let event = Event::DropFiles {
+                position: self.input.mouse_position,
+                paths: std::mem::take(&mut self.dropped_files),
+            };
+            process_event(cx, &self.view, &event, &self.window);
+        }
+        for request in std::mem::take(&mut cx.access.requests) {
+            process_event(cx, &self.view, &Event::Access(request), &self.window);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.pacer.frame_started(start);
+
+        if cx.update(&self.view, &mut self.gpu.vger, [width, height].into()) {
+            self.window.request_redraw();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            cx.frame_timings.update = start.elapsed();
+        }
+
+        self.update_access();
+        let cx = &mut self.cx;
+
+        if let Err(err) = cx.save_persistent_state() {
+            println!("failed to save persistent state: {}", err);
+        }
+
+        if cx.window_title != self.window_title {
+            self.window_title = cx.window_title.clone();
+            self.window.set_title(&cx.window_title);
+        }
+
+        self.update_click_through();
+    }
+
+    /// Lets clicks through where views asked. The window gets no cursor
+    /// events while they pass through, so the cursor is followed by
+    /// polling.
+    fn update_click_through(&mut self) {
+        if !self.cx.click_through_empty {
+            self.cursor_over_empty = false;
+        } else if self.cursor_over_empty {
+            let scale = self.window.scale_factor();
+            self.cursor_over_empty = global_cursor_position()
+                .map(|cursor| cursor.to_physical::<f64>(scale))
+                .zip(self.window.inner_position().ok())
+                .is_some_and(|(cursor, origin)| {
+                    let size = self.window.inner_size();
+                    let x = cursor.x - origin.x as f64;
+                    let y = cursor.y - origin.y as f64;
+                    let inside =
+                        x >= 0.0 && y >= 0.0 && x < size.width as f64 && y < size.height as f64;
+                    inside && self.clicks_through_at(PhysicalPosition::new(x, y))
+                });
+        }
+
+        let click_through = self.cx.click_through || self.cursor_over_empty;
+        if click_through != self.click_through {
+            self.click_through = click_through;
+            if let Err(err) = self.window.set_cursor_hittest(!click_through) {
+                println!("couldn't set click through: {}", err);
+            }
+        }
+    }
+
+    /// Would a click at `position`, in pixels from the top left of the
+    /// window's content, pass through?
+    fn clicks_through_at(&mut self, position: PhysicalPosition<f64>) -> bool {
+        let scale = self.window.scale_factor() as f32;
+        let height = self.gpu.config.height as f32;
+        let point = LocalPoint::new(
+            position.x as f32 / scale,
+            (height - position.y as f32) / scale,
+        );
+        self.cx.clicks_through_at(&self.view, point)
+    }
+
+    /// Sends what changed in the accessibility tree to the window's
+    /// adapter, making it after the first update, once there's a tree.
+    fn update_access(&mut self) {
+        let cx = &mut self.cx;
+        if let Some(access) = &mut self.access {
+            if let Some(update) = cx.take_access_update() {
+                access.update(update);
+            }
+        } else if let Some(make) = self.make_access.as_ref().filter(|_| !cx.access.is_empty()) {
+            let actions = AccessActions {
+                window: self.window.id(),
+            };
+            self.access = Some(make(&self.window, cx.access_tree(), actions));
+        } else {
+            // Nothing will take the changes, so don't keep them.
+            cx.take_access_update();
+        }
+    }
+
+    fn render(&mut self) {
+        let [width, height] = self.size();
+        let Self {
+            window,
+            instance,
+            gpu,
+            cx,
+            ..
+        } = self;
+
+        if gpu.is_lost() {
+            println!("recreating GPU resources");
+            let mut timings = StartupTimings::default();
+            *gpu = Gpu::new(window, instance, self.transparent, self.vsync, &mut timings);
+            cx.reset_gpu_resources();
+        }
+        if gpu.is_minimized() {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+
+        // println!("RedrawRequested");
+        cx.layer_renderer.get_or_insert_with(|| {
+            LayerRenderer::new(gpu.device.clone(), gpu.queue.clone(), gpu.config.format)
+        });
+        let info = RenderInfo {
+            device: &gpu.device,
+            surface: &gpu.surface,
+            config: &gpu.config,
+            queue: &gpu.queue,
+        };
+        let scale = window.scale_factor() as f32;
+        let result = cx.render(
+            info,
+            &self.view,
+            &mut gpu.vger,
+            [width, height].into(),
+            scale,
+        );
+        match result {
+            Ok(()) => (),
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                // Start over with a new device on the next frame.
+                gpu.lost.store(true, Ordering::SeqCst);
+                window.request_redraw();
+            }
+            Err(err) => {
+                println!("couldn't render frame: {:?}", err);
+                if gpu.configure() {
+                    cx.reset_gpu_resources();
+                }
+                window.request_redraw();
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            cx.frame_timings.render = start.elapsed();
+        }
+
+        if cx.ime_allowed != self.ime_allowed {
+            self.ime_allowed = cx.ime_allowed;
+            window.set_ime_allowed(self.ime_allowed);
+        }
+        if self.ime_allowed && cx.ime_cursor_area != self.ime_cursor_area {
+            self.ime_cursor_area = cx.ime_cursor_area;
+            if let Some(rect) = self.ime_cursor_area {
+                // Flip y coordinate.
+                window.set_ime_position(winit::dpi::LogicalPosition::new(
+                    rect.min_x(),
+                    height - rect.min_y(),
+                ));
+            }
+        }
+    }
+
+    /// Runs idle work and decides whether the event loop should keep
+    /// going. Other windows may already have asked it to.
+    fn idle(&mut self, control_flow: &mut ControlFlow) {
+        let cx = &mut self.cx;
+
+        // Use what's left of the frame for idle work.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            cx.run_idle_after_frame();
+            if cx.has_idle_work() {
+                *control_flow = ControlFlow::Poll;
+            }
+        }
+
+        // Keep updating while something's changing, no faster than the
+        // frame rate limit.
+        if cx.needs_frame() {
+            #[cfg(not(target_arch = "wasm32"))]
+            let next = self.pacer.next_frame(std::time::Instant::now());
+            #[cfg(target_arch = "wasm32")]
+            let next = None;
+            *control_flow = match (next, *control_flow) {
+                (_, ControlFlow::Poll) | (None, _) => ControlFlow::Poll,
+                (Some(next), ControlFlow::WaitUntil(other)) => {
+                    ControlFlow::WaitUntil(next.min(other))
+                }
+                (Some(next), _) => ControlFlow::WaitUntil(next),
+            };
+        }
+
+        // Wake up to check watched theme and script files.
+        #[cfg(not(target_arch = "wasm32"))]
+        if (cx.is_watching_theme() || cx.is_watching_scripts())
+            && *control_flow == ControlFlow::Wait
+        {
+            *control_flow = ControlFlow::WaitUntil(std::time::Instant::now() + FILE_POLL_INTERVAL);
+        }
+
+        // Follow the cursor while clicks pass through.
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.cursor_over_empty {
+            let next = std::time::Instant::now() + CURSOR_POLL_INTERVAL;
+            *control_flow = match *control_flow {
+                ControlFlow::Poll => ControlFlow::Poll,
+                ControlFlow::WaitUntil(other) => ControlFlow::WaitUntil(next.min(other)),
+                _ => ControlFlow::WaitUntil(next),
+            };
+        }
+    }
+
+    fn mouse_motion(&mut self, delta: (f64, f64)) {
+        // Flip y coordinate.
+        let d: LocalOffset = [delta.0 as f32, -delta.1 as f32].into();
+
+        let event = Event::TouchMove {
+            id: 0,
+            position: self.input.mouse_position,
+            delta: d,
+        };
+
+        process_event(&mut self.cx, &self.view, &event, &self.window);
+    }
+
+    /// Runs the app command `name`, as for a native menu item.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn command(&mut self, name: String) {
+        process_event(
+            &mut self.cx,
+            &self.view,
+            &Event::Command(name),
+            &self.window,
+        );
+    }
+
+    fn handle_event(&mut self, event: WindowEvent<'_>) {
+        if let Some(access) = &mut self.access {
+            access.on_event(&self.window, &event);
+        }
+        if let WindowEvent::CursorMoved { position, .. } = event {
+            if self.cx.click_through_empty && global_cursor_position().is_some() {
+                self.cursor_over_empty = self.clicks_through_at(position);
+                self.update_click_through();
+            }
+        }
+        let Self {
+            window,
+            gpu,
+            cx,
+            view,
+            input,
+            dropped_files,
+            ..
+        } = self;
+
+        match event {
+            WindowEvent::Resized(size) => {
+                // println!("Resizing to {:?}", size);
+                if gpu.resize(size) {
+                    cx.reset_gpu_resources();
+                }
+                window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size: &mut size,
+            } => {
+                // Moved to a display with a different density.
+                cx.set_scale_factor(scale_factor as f32);
+                if gpu.resize(size) {
+                    cx.reset_gpu_resources();
+                }
+                window.request_redraw();
+            }
+            WindowEvent::DroppedFile(path) => {
+                dropped_files.push(path);
+            }
+            WindowEvent::ThemeChanged(theme) => {
+                cx.set_system_color_scheme(Some(color_scheme(theme)));
+            }
+            event => {
+                let scale = window.scale_factor() as f32;
+                let height = gpu.config.height as f32;
+                if let Some(event) = input.translate(&event, cx, scale, height) {
+                    process_event(cx, view, &event, window);
+                }
+            }
+        }
+    }
+}
+
+/// Turns window events into `Event`s for views, keeping track of the
+/// mouse and fingers. Used by rui's windows and by `RuiSurface`.
+#[derive(Default)]
+pub(crate) struct WinitInput {
+    pub(crate) mouse_position: LocalPoint,
+
+    /// Ids in `Event`s for the fingers on a touch screen.
+    touches: TouchSlots,
+
+    /// Keys which are down, with the key they went down as and how many
+    /// times they've repeated.
+    held_keys: HashMap<VirtualKeyCode, (Key, u32)>,
+}
+
+impl WinitInput {
+    /// Updates `cx` for input which views don't see by itself, like the
+    /// mouse button and modifier keys, and returns the event for views, if
+    /// there is one. `height` is the window's in pixels, for flipping y.
+    pub(crate) fn translate(
+        &mut self,
+        event: &WindowEvent<'_>,
+        cx: &mut Context,
+        scale: f32,
+        height: f32,
+    ) -> Option<Event> {
+        match event {
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    cx.mouse_button = match button {
+                        WMouseButton::Left => Some(MouseButton::Left),
+                        WMouseButton::Right => Some(MouseButton::Right),
+                        WMouseButton::Middle => Some(MouseButton::Center),
+                        _ => None,
+                    };
+                    cx.pressures[0] = 1.0;
+                    Some(Event::TouchBegin {
+                        id: 0,
+                        position: self.mouse_position,
+                    })
+                }
+                ElementState::Released => {
+                    cx.mouse_button = None;
+                    Some(Event::TouchEnd {
+                        id: 0,
+                        position: self.mouse_position,
+                    })
+                }
+            },
+            WindowEvent::Touch(Touch {
+                phase,
+                location,
+                id: touch,
+                force,
+                ..
+            }) => {
+                let position = [
+                    location.x as f32 / scale,
+                    (height - location.y as f32) / scale,
+                ]
+                .into();
+                let pressure = force.map_or(1.0, |force| force.normalized() as f32);
+
+                match phase {
+                    TouchPhase::Started => self.touches.begin(*touch, position).map(|id| {
+                        cx.pressures[id] = pressure;
+                        Event::TouchBegin { id, position }
+                    }),
+                    TouchPhase::Moved => {
+                        self.touches.move_to(*touch, position).map(|(id, delta)| {
+                            cx.pressures[id] = pressure;
+                            Event::TouchMove {
+                                id,
+                                position,
+                                delta,
+                            }
+                        })
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled => self
+                        .touches
+                        .end(*touch)
+                        .map(|(id, _)| Event::TouchEnd { id, position }),
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                // winit's deltas move content right and down.
+                let delta: LocalOffset = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [x * LINE_HEIGHT, -y * LINE_HEIGHT].into(),
+                    MouseScrollDelta::PixelDelta(p) => {
+                        [p.x as f32 / scale, -p.y as f32 / scale].into()
+                    }
+                };
+                Some(Event::Scroll {
+                    position: self.mouse_position,
+                    delta,
+                })
+            }
+
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = [
+                    position.x as f32 / scale,
+                    (height - position.y as f32) / scale,
+                ]
+                .into();
+                None
+            }
+
+            WindowEvent::KeyboardInput { input, .. } => input
+                .virtual_keycode
+                .and_then(|code| self.key_event(code, input.state, cx.key_mods))
+                .map(Event::Key),
+
+            // Keys released while another window has focus don't come
+            // back up.
+            WindowEvent::Focused(false) => {
+                self.held_keys.clear();
+                None
+            }
+
+            WindowEvent::Ime(ime) => {
+                let event = match ime {
+                    Ime::Preedit(text, cursor) => {
+                        // winit reports byte offsets; we use characters.
+                        let chars = |i: usize| text[..i].chars().count();
+                        let cursor = cursor.map(|(start, end)| (chars(start), chars(end)));
+                        Some(ImeEvent::Preedit {
+                            text: text.clone(),
+                            cursor,
+                        })
+                    }
+                    Ime::Commit(text) => Some(ImeEvent::Commit(text.clone())),
+                    Ime::Disabled => Some(ImeEvent::Preedit {
+                        text: String::new(),
+                        cursor: None,
+                    }),
+                    Ime::Enabled => None,
+                };
+                event.map(Event::Ime)
+            }
+
+            WindowEvent::ModifiersChanged(mods) => {
+                cx.key_mods = KeyboardModifiers {
+                    shift: mods.shift(),
+                    control: mods.ctrl(),
+                    alt: mods.alt(),
+                    command: mods.logo(),
+                };
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl WinitInput {
+    /// The event for a key going down or up. winit sends presses again as
+    /// the key repeats, which are counted here.
+    fn key_event(
+        &mut self,
+        code: VirtualKeyCode,
+        state: ElementState,
+        mods: KeyboardModifiers,
+    ) -> Option<KeyEvent> {
+        match state {
+            ElementState::Pressed => {
+                let (key, repeat) = match self.held_keys.get_mut(&code) {
+                    Some((key, repeat)) => {
+                        *repeat += 1;
+                        (*key, *repeat)
+                    }
+                    None => {
+                        let key = key(code, mods.shift)?;
+                        self.held_keys.insert(code, (key, 0));
+                        (key, 0)
+                    }
+                };
+                Some(KeyEvent::press(key).mods(mods).repeat(repeat))
+            }
+            // Up as the key it went down as, even if shift has been let go.
+            ElementState::Released => self
+                .held_keys
+                .remove(&code)
+                .map(|(key, _)| KeyEvent::release(key).mods(mods)),
+        }
+    }
+}
+
+/// The key for a key code, with the letters in upper case if `shift` is
+/// held.
+fn key(code: VirtualKeyCode, shift: bool) -> Option<Key> {
+    match code {
+        // VirtualKeyCode::Character(c) => Some(Key::Character(c)),
+        VirtualKeyCode::Key1 => Some(Key::Character('1')),
+        VirtualKeyCode::Key2 => Some(Key::Character('2')),
+        VirtualKeyCode::Key3 => Some(Key::Character('3')),
+        VirtualKeyCode::Key4 => Some(Key::Character('4')),
+        VirtualKeyCode::Key5 => Some(Key::Character('5')),
+        VirtualKeyCode::Key6 => Some(Key::Character('6')),
+        VirtualKeyCode::Key7 => Some(Key::Character('7')),
+        VirtualKeyCode::Key8 => Some(Key::Character('8')),
+        VirtualKeyCode::Key9 => Some(Key::Character('9')),
+        VirtualKeyCode::Key0 => Some(Key::Character('0')),
+        VirtualKeyCode::A => Some(Key::Character(if shift { 'A' } else { 'a' })),
+        VirtualKeyCode::B => Some(Key::Character(if shift { 'B' } else { 'b' })),
+        VirtualKeyCode::C => Some(Key::Character(if shift { 'C' } else { 'c' })),
+        VirtualKeyCode::D => Some(Key::Character(if shift { 'D' } else { 'd' })),
+        VirtualKeyCode::E => Some(Key::Character(if shift { 'E' } else { 'e' })),
+        VirtualKeyCode::F => Some(Key::Character(if shift { 'F' } else { 'f' })),
+        VirtualKeyCode::G => Some(Key::Character(if shift { 'G' } else { 'g' })),
+        VirtualKeyCode::H => Some(Key::Character(if shift { 'H' } else { 'h' })),
+        VirtualKeyCode::I => Some(Key::Character(if shift { 'I' } else { 'i' })),
+        VirtualKeyCode::J => Some(Key::Character(if shift { 'J' } else { 'j' })),
+        VirtualKeyCode::K => Some(Key::Character(if shift { 'K' } else { 'k' })),
+        VirtualKeyCode::L => Some(Key::Character(if shift { 'L' } else { 'l' })),
+        VirtualKeyCode::M => Some(Key::Character(if shift { 'M' } else { 'm' })),
+        VirtualKeyCode::N => Some(Key::Character(if shift { 'N' } else { 'n' })),
+        VirtualKeyCode::O => Some(Key::Character(if shift { 'O' } else { 'o' })),
+        VirtualKeyCode::P => Some(Key::Character(if shift { 'P' } else { 'p' })),
+        VirtualKeyCode::Q => Some(Key::Character(if shift { 'Q' } else { 'q' })),
+        VirtualKeyCode::R => Some(Key::Character(if shift { 'R' } else { 'r' })),
+        VirtualKeyCode::S => Some(Key::Character(if shift { 'S' } else { 's' })),
+        VirtualKeyCode::T => Some(Key::Character(if shift { 'T' } else { 't' })),
+        VirtualKeyCode::U => Some(Key::Character(if shift { 'U' } else { 'u' })),
+        VirtualKeyCode::V => Some(Key::Character(if shift { 'V' } else { 'v' })),
+        VirtualKeyCode::W => Some(Key::Character(if shift { 'W' } else { 'w' })),
+        VirtualKeyCode::X => Some(Key::Character(if shift { 'X' } else { 'x' })),
+        VirtualKeyCode::Y => Some(Key::Character(if shift { 'Y' } else { 'y' })),
+        VirtualKeyCode::Z => Some(Key::Character(if shift { 'Z' } else { 'z' })),
+        VirtualKeyCode::Semicolon => Some(Key::Character(';')),
+        VirtualKeyCode::Colon => Some(Key::Character(';')),
+        VirtualKeyCode::Caret => Some(Key::Character('^')),
+        VirtualKeyCode::Asterisk => Some(Key::Character('*')),
+        VirtualKeyCode::Period => Some(Key::Character('.')),
+        VirtualKeyCode::Comma => Some(Key::Character(',')),
+        VirtualKeyCode::Return => Some(Key::Enter),
+        VirtualKeyCode::Tab => Some(Key::Tab),
+        VirtualKeyCode::Space => Some(Key::Space),
+        VirtualKeyCode::Down => Some(Key::ArrowDown),
+        VirtualKeyCode::Left => Some(Key::ArrowLeft),
+        VirtualKeyCode::Right => Some(Key::ArrowRight),
+        VirtualKeyCode::Up => Some(Key::ArrowUp),
+        VirtualKeyCode::End => Some(Key::End),
+        VirtualKeyCode::Home => Some(Key::Home),
+        VirtualKeyCode::PageDown => Some(Key::PageDown),
+        VirtualKeyCode::PageUp => Some(Key::PageUp),
+        VirtualKeyCode::Back => Some(Key::Backspace),
+        VirtualKeyCode::Delete => Some(Key::Delete),
+        VirtualKeyCode::Escape => Some(Key::Escape),
+        VirtualKeyCode::F1 => Some(Key::F1),
+        VirtualKeyCode::F2 => Some(Key::F2),
+        VirtualKeyCode::F3 => Some(Key::F3),
+        VirtualKeyCode::F4 => Some(Key::F4),
+        VirtualKeyCode::F5 => Some(Key::F5),
+        VirtualKeyCode::F6 => Some(Key::F6),
+        VirtualKeyCode::F7 => Some(Key::F7),
+        VirtualKeyCode::F8 => Some(Key::F8),
+        VirtualKeyCode::F9 => Some(Key::F9),
+        VirtualKeyCode::F10 => Some(Key::F10),
+        VirtualKeyCode::F11 => Some(Key::F11),
+        VirtualKeyCode::F12 => Some(Key::F12),
+        _ => None,
+    }
+}
+
+pub(crate) fn run_app(view: impl View, mut app_config: AppConfig) {
+    let launch = Stopwatch::start();
     let event_loop = EventLoop::new();
 
-    let mut window_title = String::from("rui");
-    let builder = WindowBuilder::new().with_title(&window_title);
-    let window = builder.build(&event_loop).unwrap();
-
-    let setup = block_on(setup(&window));
-    let surface = setup.surface;
-    let device = Arc::new(setup.device);
-    let size = setup.size;
-    let adapter = setup.adapter;
-    let queue = Arc::new(setup.queue);
-
-    let mut config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format: surface.get_capabilities(&adapter).formats[0],
-        width: size.width,
-        height: size.height,
-        present_mode: wgpu::PresentMode::Fifo,
-        alpha_mode: wgpu::CompositeAlphaMode::Auto,
-        view_formats: vec![],
-    };
-    surface.configure(&device, &config);
+    // Windows share an instance, so only the first pays for creating it.
+    let instance = Rc::new(
+        app_config
+            .wgpu_instance
+            .take()
+            .unwrap_or_else(|| wgpu::Instance::new(wgpu::InstanceDescriptor::default())),
+    );
+
+    let mut main = AppWindow::new(
+        &app_config.window,
+        AnyView::new(view),
+        instance.clone(),
+        launch,
+        &event_loop,
+    );
 
     #[cfg(not(target_arch = "wasm32"))]
     {
         *GLOBAL_EVENT_LOOP_PROXY.lock().unwrap() = Some(event_loop.create_proxy());
     }
+    #[cfg(target_arch = "wasm32")]
+    WEB_EVENT_LOOP_PROXY.with(|proxy| *proxy.borrow_mut() = Some(event_loop.create_proxy()));
 
-    let mut vger = Vger::new(device.clone(), queue.clone(), config.format);
-    let mut cx = Context::new();
-    let mut mouse_position = LocalPoint::zero();
+    main.make_access = app_config.access_adapter.clone();
+    if let Some(path) = default_persistence_path() {
+        main.cx.set_persistence_path(path);
+    }
+    app_config.start(&mut main.cx);
+    main.restore_frame(&main.cx.persistent);
 
     let mut commands: Vec<CommandInfo> = Vec::new();
     let mut command_map = HashMap::new();
-    cx.commands(&view, &mut commands);
+    main.cx.commands(&main.view, &mut commands);
 
     {
         // So we can infer a type for CommandMap when winit is enabled.
         command_map.insert("", "");
     }
 
-    let mut access_nodes = vec![];
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let native_menus = if app_config.menus.is_empty() {
+        None
+    } else {
+        match crate::menu::native::NativeMenus::new(&app_config.menus, &app_config.window.title) {
+            Ok(menus) => Some(menus),
+            Err(err) => {
+                println!("couldn't make native menus, drawing them instead: {}", err);
+                None
+            }
+        }
+    };
+
+    // Work sent with `on_main` runs in the main window. Closing it quits.
+    let main_id = main.window.id();
+    let mut windows = HashMap::new();
+    windows.insert(main_id, main);
 
-    event_loop.run(move |event, _, control_flow| {
+    // Where mouse motion goes when the cursor is grabbed.
+    let mut focused = main_id;
+
+    event_loop.run(move |event, target, control_flow| {
         // ControlFlow::Poll continuously runs the event loop, even if the OS hasn't
         // dispatched any events. This is ideal for games and similar applications.
         // *control_flow = ControlFlow::Poll;
@@ -193,39 +1264,60 @@ pub fn rui(view: impl View) {
         *control_flow = ControlFlow::Wait;
 
         match event {
+            // After winit has set up the app, so its menus can be replaced.
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            WEvent::NewEvents(winit::event::StartCause::Init) => {
+                if let (Some(menus), Some(main)) = (&native_menus, windows.get_mut(&main_id)) {
+                    match menus.show(&main.window) {
+                        Ok(()) => main.cx.set_native_menus(true),
+                        Err(err) => println!("couldn't show native menus: {}", err),
+                    }
+                }
+            }
             WEvent::WindowEvent {
+                window_id,
                 event: WindowEvent::CloseRequested,
-                ..
             } => {
-                println!("The close button was pressed; stopping");
-                *control_flow = ControlFlow::Exit
+                if window_id == main_id {
+                    println!("The close button was pressed; stopping");
+                    *control_flow = ControlFlow::Exit;
+                    flush_persistent_state(&mut windows);
+                } else {
+                    windows.remove(&window_id);
+                }
             }
-            WEvent::WindowEvent {
-                event:
-                    WindowEvent::Resized(size)
-                    | WindowEvent::ScaleFactorChanged {
-                        new_inner_size: &mut size,
-                        ..
-                    },
-                ..
-            } => {
-                // println!("Resizing to {:?}", size);
-                config.width = size.width.max(1);
-                config.height = size.height.max(1);
-                surface.configure(&device, &config);
-                window.request_redraw();
+            WEvent::WindowEvent { window_id, event } => {
+                if let WindowEvent::Focused(true) = event {
+                    focused = window_id;
+                }
+                let moved = matches!(event, WindowEvent::Resized(_) | WindowEvent::Moved(_));
+                if let Some(window) = windows.get_mut(&window_id) {
+                    window.handle_event(event);
+                }
+                if moved {
+                    save_frame(&mut windows, window_id, main_id);
+                }
             }
             WEvent::UserEvent(_) => {
                 // println!("received user event");
 
                 // Process the work queue.
                 #[cfg(not(target_arch = "wasm32"))]
-                {
-                    while let Some(f) = GLOBAL_WORK_QUEUE.lock().unwrap().pop_front() {
-                        f(&mut cx);
+                while let Some((window_id, f)) = pop_work() {
+                    if let Some(window) = windows.get_mut(&window_id.unwrap_or(main_id)) {
+                        f(&mut window.cx);
+                    }
+                }
+
+                #[cfg(any(target_os = "macos", target_os = "windows"))]
+                for name in crate::menu::native::take_chosen() {
+                    if let Some(main) = windows.get_mut(&main_id) {
+                        main.command(name);
                     }
                 }
             }
+            // As when quitting from the macOS app menu.
+            WEvent::LoopDestroyed => flush_persistent_state(&mut windows),
             WEvent::MainEventsCleared => {
                 // Application update code.
 
@@ -234,301 +1326,157 @@ pub fn rui(view: impl View) {
                 // You only need to call this if you've determined that you need to redraw, in
                 // applications which do not always need to. Applications that redraw continuously
                 // can just render here instead.
-
-                let window_size = window.inner_size();
-                let scale = window.scale_factor() as f32;
-                // println!("window_size: {:?}", window_size);
-                let width = window_size.width as f32 / scale;
-                let height = window_size.height as f32 / scale;
-
-                if cx.update(&view, &mut vger, &mut access_nodes, [width, height].into()) {
-                    window.request_redraw();
-                }
-
-                if cx.window_title != window_title {
-                    window_title = cx.window_title.clone();
-                    window.set_title(&cx.window_title);
+                for window in windows.values_mut() {
+                    window.update();
                 }
             }
-            WEvent::RedrawRequested(_) => {
+            WEvent::RedrawRequested(window_id) => {
                 // Redraw the application.
                 //
                 // It's preferable for applications that do not render continuously to render in
                 // this event rather than in MainEventsCleared, since rendering in here allows
                 // the program to gracefully handle redraws requested by the OS.
-
-                let window_size = window.inner_size();
-                let scale = window.scale_factor() as f32;
-                // println!("window_size: {:?}", window_size);
-                let width = window_size.width as f32 / scale;
-                let height = window_size.height as f32 / scale;
-
-                // println!("RedrawRequested");
-                cx.render(
-                    RenderInfo {
-                        device: &device,
-                        surface: &surface,
-                        config: &config,
-                        queue: &queue,
-                    },
-                    &view,
-                    &mut vger,
-                    [width, height].into(),
-                    scale,
-                );
+                if let Some(window) = windows.get_mut(&window_id) {
+                    window.render();
+                }
             }
-            WEvent::WindowEvent {
-                event: WindowEvent::MouseInput { state, button, .. },
-                ..
-            } => {
-                match state {
-                    ElementState::Pressed => {
-                        cx.mouse_button = match button {
-                            WMouseButton::Left => Some(MouseButton::Left),
-                            WMouseButton::Right => Some(MouseButton::Right),
-                            WMouseButton::Middle => Some(MouseButton::Center),
-                            _ => None,
-                        };
-                        let event = Event::TouchBegin {
-                            id: 0,
-                            position: mouse_position,
-                        };
-                        process_event(&mut cx, &view, &event, &window)
-                    }
-                    ElementState::Released => {
-                        cx.mouse_button = None;
-                        let event = Event::TouchEnd {
-                            id: 0,
-                            position: mouse_position,
-                        };
-                        process_event(&mut cx, &view, &event, &window)
-                    }
-                };
+            WEvent::RedrawEventsCleared => {
+                for window in windows.values_mut() {
+                    window.idle(control_flow);
+                }
             }
-            WEvent::WindowEvent {
-                window_id,
-                event:
-                    WindowEvent::Touch(Touch {
-                        phase, location, ..
-                    }),
+            WEvent::DeviceEvent {
+                event: winit::event::DeviceEvent::MouseMotion { delta },
                 ..
             } => {
-                // Do not handle events from other windows.
-                if window_id != window.id() {
-                    return;
+                if let Some(window) = windows.get_mut(&focused) {
+                    window.mouse_motion(delta);
                 }
+            }
+            _ => (),
+        }
 
-                let scale = window.scale_factor() as f32;
-                let position = [
-                    location.x as f32 / scale,
-                    (config.height as f32 - location.y as f32) / scale,
-                ]
-                .into();
-
-                let delta = position - cx.previous_position[0];
-
-                // TODO: Multi-Touch management
-                let event = match phase {
-                    TouchPhase::Started => Some(Event::TouchBegin { id: 0, position }),
-                    TouchPhase::Moved => Some(Event::TouchMove {
-                        id: 0,
-                        position,
-                        delta,
-                    }),
-                    TouchPhase::Ended | TouchPhase::Cancelled => {
-                        Some(Event::TouchEnd { id: 0, position })
-                    }
-                };
+        // Open and close windows as views asked.
+        let mut requests = vec![];
+        for window in windows.values_mut() {
+            requests.append(&mut window.cx.window_requests);
+        }
+        if windows
+            .get(&main_id)
+            .is_some_and(|main| main.cx.close_requested)
+        {
+            *control_flow = ControlFlow::Exit;
+            flush_persistent_state(&mut windows);
+        }
+        windows.retain(|id, window| *id == main_id || !window.cx.close_requested);
 
-                if let Some(event) = event {
-                    process_event(&mut cx, &view, &event, &window);
-                }
+        for request in requests {
+            if cfg!(target_arch = "wasm32") {
+                println!("extra windows aren't supported on the web");
+                continue;
             }
-            WEvent::WindowEvent {
-                event: WindowEvent::CursorMoved { position, .. },
-                ..
-            } => {
-                let scale = window.scale_factor() as f32;
-                mouse_position = [
-                    position.x as f32 / scale,
-                    (config.height as f32 - position.y as f32) / scale,
-                ]
-                .into();
-                // let event = Event::TouchMove {
-                //     id: 0,
-                //     position: mouse_position,
-                // };
-                // process_event(&mut cx, &view, &event, &window)
+            let mut window = AppWindow::new(
+                &request.options,
+                request.view,
+                instance.clone(),
+                Stopwatch::start(),
+                target,
+            );
+            window.make_access = app_config.access_adapter.clone();
+            app_config.run_start_hooks(&mut window.cx);
+            if let Some(main) = windows.get(&main_id) {
+                window.restore_frame(&main.cx.persistent);
             }
+            windows.insert(window.window.id(), window);
+        }
+    });
+}
 
-            WEvent::WindowEvent {
-                event: WindowEvent::KeyboardInput { input, .. },
-                ..
-            } => {
-                if input.state == ElementState::Pressed {
-                    if let Some(code) = input.virtual_keycode {
-                        let key = match code {
-                            // VirtualKeyCode::Character(c) => Some(Key::Character(c)),
-                            VirtualKeyCode::Key1 => Some(Key::Character('1')),
-                            VirtualKeyCode::Key2 => Some(Key::Character('2')),
-                            VirtualKeyCode::Key3 => Some(Key::Character('3')),
-                            VirtualKeyCode::Key4 => Some(Key::Character('4')),
-                            VirtualKeyCode::Key5 => Some(Key::Character('5')),
-                            VirtualKeyCode::Key6 => Some(Key::Character('6')),
-                            VirtualKeyCode::Key7 => Some(Key::Character('7')),
-                            VirtualKeyCode::Key8 => Some(Key::Character('8')),
-                            VirtualKeyCode::Key9 => Some(Key::Character('9')),
-                            VirtualKeyCode::Key0 => Some(Key::Character('0')),
-                            VirtualKeyCode::A => {
-                                Some(Key::Character(if cx.key_mods.shift { 'A' } else { 'a' }))
-                            }
-                            VirtualKeyCode::B => {
-                                Some(Key::Character(if cx.key_mods.shift { 'B' } else { 'b' }))
-                            }
-                            VirtualKeyCode::C => {
-                                Some(Key::Character(if cx.key_mods.shift { 'C' } else { 'c' }))
-                            }
-                            VirtualKeyCode::D => {
-                                Some(Key::Character(if cx.key_mods.shift { 'D' } else { 'd' }))
-                            }
-                            VirtualKeyCode::E => {
-                                Some(Key::Character(if cx.key_mods.shift { 'E' } else { 'e' }))
-                            }
-                            VirtualKeyCode::F => {
-                                Some(Key::Character(if cx.key_mods.shift { 'F' } else { 'f' }))
-                            }
-                            VirtualKeyCode::G => {
-                                Some(Key::Character(if cx.key_mods.shift { 'G' } else { 'g' }))
-                            }
-                            VirtualKeyCode::H => {
-                                Some(Key::Character(if cx.key_mods.shift { 'H' } else { 'h' }))
-                            }
-                            VirtualKeyCode::I => {
-                                Some(Key::Character(if cx.key_mods.shift { 'I' } else { 'i' }))
-                            }
-                            VirtualKeyCode::J => {
-                                Some(Key::Character(if cx.key_mods.shift { 'J' } else { 'j' }))
-                            }
-                            VirtualKeyCode::K => {
-                                Some(Key::Character(if cx.key_mods.shift { 'K' } else { 'k' }))
-                            }
-                            VirtualKeyCode::L => {
-                                Some(Key::Character(if cx.key_mods.shift { 'L' } else { 'l' }))
-                            }
-                            VirtualKeyCode::M => {
-                                Some(Key::Character(if cx.key_mods.shift { 'M' } else { 'm' }))
-                            }
-                            VirtualKeyCode::N => {
-                                Some(Key::Character(if cx.key_mods.shift { 'N' } else { 'n' }))
-                            }
-                            VirtualKeyCode::O => {
-                                Some(Key::Character(if cx.key_mods.shift { 'O' } else { 'o' }))
-                            }
-                            VirtualKeyCode::P => {
-                                Some(Key::Character(if cx.key_mods.shift { 'P' } else { 'p' }))
-                            }
-                            VirtualKeyCode::Q => {
-                                Some(Key::Character(if cx.key_mods.shift { 'Q' } else { 'q' }))
-                            }
-                            VirtualKeyCode::R => {
-                                Some(Key::Character(if cx.key_mods.shift { 'R' } else { 'r' }))
-                            }
-                            VirtualKeyCode::S => {
-                                Some(Key::Character(if cx.key_mods.shift { 'S' } else { 's' }))
-                            }
-                            VirtualKeyCode::T => {
-                                Some(Key::Character(if cx.key_mods.shift { 'T' } else { 't' }))
-                            }
-                            VirtualKeyCode::U => {
-                                Some(Key::Character(if cx.key_mods.shift { 'U' } else { 'u' }))
-                            }
-                            VirtualKeyCode::V => {
-                                Some(Key::Character(if cx.key_mods.shift { 'V' } else { 'v' }))
-                            }
-                            VirtualKeyCode::W => {
-                                Some(Key::Character(if cx.key_mods.shift { 'W' } else { 'w' }))
-                            }
-                            VirtualKeyCode::X => {
-                                Some(Key::Character(if cx.key_mods.shift { 'X' } else { 'x' }))
-                            }
-                            VirtualKeyCode::Y => {
-                                Some(Key::Character(if cx.key_mods.shift { 'Y' } else { 'y' }))
-                            }
-                            VirtualKeyCode::Z => {
-                                Some(Key::Character(if cx.key_mods.shift { 'Z' } else { 'z' }))
-                            }
-                            VirtualKeyCode::Semicolon => Some(Key::Character(';')),
-                            VirtualKeyCode::Colon => Some(Key::Character(';')),
-                            VirtualKeyCode::Caret => Some(Key::Character('^')),
-                            VirtualKeyCode::Asterisk => Some(Key::Character('*')),
-                            VirtualKeyCode::Period => Some(Key::Character('.')),
-                            VirtualKeyCode::Comma => Some(Key::Character(',')),
-                            VirtualKeyCode::Return => Some(Key::Enter),
-                            VirtualKeyCode::Tab => Some(Key::Tab),
-                            VirtualKeyCode::Space => Some(Key::Space),
-                            VirtualKeyCode::Down => Some(Key::ArrowDown),
-                            VirtualKeyCode::Left => Some(Key::ArrowLeft),
-                            VirtualKeyCode::Right => Some(Key::ArrowRight),
-                            VirtualKeyCode::Up => Some(Key::ArrowUp),
-                            VirtualKeyCode::End => Some(Key::End),
-                            VirtualKeyCode::Home => Some(Key::Home),
-                            VirtualKeyCode::PageDown => Some(Key::PageDown),
-                            VirtualKeyCode::PageUp => Some(Key::PageUp),
-                            VirtualKeyCode::Back => Some(Key::Backspace),
-                            VirtualKeyCode::Delete => Some(Key::Delete),
-                            VirtualKeyCode::Escape => Some(Key::Escape),
-                            VirtualKeyCode::F1 => Some(Key::F1),
-                            VirtualKeyCode::F2 => Some(Key::F2),
-                            VirtualKeyCode::F3 => Some(Key::F3),
-                            VirtualKeyCode::F4 => Some(Key::F4),
-                            VirtualKeyCode::F5 => Some(Key::F5),
-                            VirtualKeyCode::F6 => Some(Key::F6),
-                            VirtualKeyCode::F7 => Some(Key::F7),
-                            VirtualKeyCode::F8 => Some(Key::F8),
-                            VirtualKeyCode::F9 => Some(Key::F9),
-                            VirtualKeyCode::F10 => Some(Key::F10),
-                            VirtualKeyCode::F11 => Some(Key::F11),
-                            VirtualKeyCode::F12 => Some(Key::F12),
-                            _ => None,
-                        };
-
-                        if let Some(key) = key {
-                            cx.process(&view, &Event::Key(key))
-                        }
-                    }
-                }
-            }
+/// How often to check where the cursor is while clicks pass through the
+/// window.
+#[cfg(not(target_arch = "wasm32"))]
+const CURSOR_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(30);
 
-            WEvent::WindowEvent {
-                event: WindowEvent::ModifiersChanged(mods),
-                ..
-            } => {
-                cx.key_mods = KeyboardModifiers {
-                    shift: mods.shift(),
-                    control: mods.ctrl(),
-                    alt: mods.alt(),
-                    command: mods.logo(),
-                };
-            }
+/// Where the cursor is on the screen, for following it while the window
+/// lets clicks through. `None` where that isn't supported.
+#[cfg(target_os = "windows")]
+fn global_cursor_position() -> Option<Position> {
+    #[repr(C)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
 
-            WEvent::DeviceEvent {
-                event: winit::event::DeviceEvent::MouseMotion { delta },
-                ..
-            } => {
-                // Flip y coordinate.
-                let d: LocalOffset = [delta.0 as f32, -delta.1 as f32].into();
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetCursorPos(point: *mut Point) -> i32;
+    }
 
-                let event = Event::TouchMove {
-                    id: 0,
-                    position: mouse_position,
-                    delta: d,
-                };
+    let mut point = Point { x: 0, y: 0 };
+    let ok = unsafe { GetCursorPos(&mut point) } != 0;
+    ok.then(|| PhysicalPosition::new(point.x, point.y).into())
+}
 
-                process_event(&mut cx, &view, &event, &window);
-            }
-            _ => (),
+#[cfg(target_os = "macos")]
+fn global_cursor_position() -> Option<Position> {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct CGPoint {
+        x: f64,
+        y: f64,
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventCreate(source: *const c_void) -> *const c_void;
+        fn CGEventGetLocation(event: *const c_void) -> CGPoint;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    // In points from the top left of the main display, as winit's window
+    // positions are before scaling.
+    unsafe {
+        let event = CGEventCreate(std::ptr::null());
+        if event.is_null() {
+            return None;
         }
-    });
+        let location = CGEventGetLocation(event);
+        CFRelease(event);
+        Some(winit::dpi::LogicalPosition::new(location.x, location.y).into())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn global_cursor_position() -> Option<Position> {
+    None
+}
+
+/// Saves where a window is with the main window's `persistent_state`
+/// values, which are the ones written to disk.
+fn save_frame(windows: &mut HashMap<WindowId, AppWindow>, window_id: WindowId, main_id: WindowId) {
+    let Some((key, frame)) = windows.get(&window_id).and_then(|window| {
+        let (key, frame) = window.frame()?;
+        Some((key.to_string(), frame))
+    }) else {
+        return;
+    };
+    if let Some(main) = windows.get_mut(&main_id) {
+        main.cx.persistent.set(&key, &frame);
+    }
+}
+
+/// Writes what's waiting to be saved before the app quits.
+fn flush_persistent_state(windows: &mut HashMap<WindowId, AppWindow>) {
+    for window in windows.values_mut() {
+        if let Err(err) = window.cx.flush_persistent_state() {
+            println!("failed to save persistent state: {}", err);
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -549,3 +1497,99 @@ pub fn parse_url_query_string<'a>(query: &'a str, search_key: &str) -> Option<&'
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_device_lost() {
+        use wgpu_core::device::{queue::QueueSubmitError, DeviceError};
+
+        let validation = |cause: Box<dyn std::error::Error + Send + Sync>, description: &str| {
+            wgpu::Error::Validation {
+                source: Box::new(wgpu_core::error::ContextError {
+                    string: "Queue::submit",
+                    cause,
+                    label_key: "",
+                    label: String::new(),
+                }),
+                description: description.into(),
+            }
+        };
+
+        let lost = validation(Box::new(DeviceError::Lost), "Parent device is lost");
+        assert!(is_device_lost(&lost));
+        let lost = validation(
+            Box::new(QueueSubmitError::Queue(DeviceError::Lost)),
+            "Parent device is lost",
+        );
+        assert!(is_device_lost(&lost));
+
+        // Other errors are bugs, whatever they say.
+        let invalid = validation(Box::new(DeviceError::Invalid), "buffer was lost track of");
+        assert!(!is_device_lost(&invalid));
+    }
+
+    #[test]
+    fn test_window_frame() {
+        let mut store = PersistentStore::default();
+        store.set(
+            "main",
+            &WindowFrame {
+                size: [640.0, 480.0],
+                position: None,
+            },
+        );
+        let frame = store.get::<WindowFrame>("main").unwrap();
+        assert_eq!(frame.size, [640.0, 480.0]);
+        assert_eq!(frame.position, None);
+
+        let options = WindowOptions::default().persist("main");
+        assert_eq!(options.persist.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_access_actions() {
+        use accesskit::{Action, ActionHandler, ActionRequest};
+
+        let _lock = MAIN_QUEUE_LOCK.lock().unwrap();
+        let actions = AccessActions {
+            window: unsafe { WindowId::dummy() },
+        };
+        actions.do_action(ActionRequest {
+            action: Action::Default,
+            target: root_access_id(),
+            data: None,
+        });
+
+        // The request waits for the window's next update.
+        let (window, f) = pop_work().unwrap();
+        assert_eq!(window, Some(actions.window));
+        let mut cx = Context::new();
+        f(&mut cx);
+        assert_eq!(cx.access.requests.len(), 1);
+        assert_eq!(cx.access.requests[0].action, Action::Default);
+    }
+
+    #[test]
+    fn test_key_repeat() {
+        let mut input = WinitInput::default();
+        let shift = KeyboardModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let mut key = |state, mods| input.key_event(VirtualKeyCode::A, state, mods);
+        let down = key(ElementState::Pressed, shift).unwrap();
+        assert_eq!(down, KeyEvent::press(Key::Character('A')).mods(shift));
+        assert_eq!(key(ElementState::Pressed, shift).unwrap().repeat, 1);
+        assert_eq!(key(ElementState::Pressed, shift).unwrap().repeat, 2);
+
+        let none = KeyboardModifiers::default();
+        let up = key(ElementState::Released, none).unwrap();
+        assert_eq!(up, KeyEvent::release(Key::Character('A')));
+        assert_eq!(key(ElementState::Released, none), None);
+        assert!(!key(ElementState::Pressed, none).unwrap().is_repeat());
+    }
+}